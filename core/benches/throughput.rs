@@ -0,0 +1,128 @@
+//! Throughput of the backup and wipe data paths: file hashing, the encryption
+//! pipeline, manifest hashing and the overwrite engine.
+//!
+//!     cargo bench --features bench
+//!
+//! Scratch files go to `SECUREWIPE_BENCH_DIR`, else /dev/shm, else the temp dir, so
+//! by default disk speed is kept out of the numbers. To measure the overwrite engine
+//! against a block device, point `SECUREWIPE_BENCH_DEVICE` at a loop device made for
+//! the purpose (`losetup -f --show disk.img`); everything on it is overwritten.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use securewipe::bench;
+use securewipe::{BackupManifest, OverwriteFill};
+
+const FILE_SIZES: [u64; 2] = [1024 * 1024, 64 * 1024 * 1024];
+const DEVICE_BYTES: u64 = 256 * 1024 * 1024;
+
+fn scratch_dir() -> tempfile::TempDir {
+    let base = std::env::var_os("SECUREWIPE_BENCH_DIR")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from("/dev/shm")).filter(|shm| shm.is_dir()))
+        .unwrap_or_else(std::env::temp_dir);
+    tempfile::tempdir_in(base).expect("create scratch directory")
+}
+
+fn write_source(dir: &tempfile::TempDir, size: u64) -> PathBuf {
+    let path = dir.path().join(format!("source-{}", size));
+    let mut file = File::create(&path).unwrap();
+    let chunk: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let mut written = 0;
+    while written < size {
+        let len = (size - written).min(chunk.len() as u64) as usize;
+        file.write_all(&chunk[..len]).unwrap();
+        written += len as u64;
+    }
+    path
+}
+
+fn bench_file_hash(c: &mut Criterion) {
+    let dir = scratch_dir();
+    let mut group = c.benchmark_group("file_hash");
+    for size in FILE_SIZES {
+        let source = write_source(&dir, size);
+        group.throughput(Throughput::Bytes(size));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &source, |b, source| {
+            b.iter(|| bench::file_hash(source).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_encrypt(c: &mut Criterion) {
+    let dir = scratch_dir();
+    let mut group = c.benchmark_group("encrypt_file");
+    for size in FILE_SIZES {
+        let source = write_source(&dir, size);
+        let dest = dir.path().join(format!("dest-{}", size));
+        group.throughput(Throughput::Bytes(size));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &source, |b, source| {
+            b.iter(|| bench::encrypt_file(source, &dest).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn manifest_with(files: usize) -> BackupManifest {
+    BackupManifest {
+        files: (0..files).map(|i| (format!("home/user/documents/file-{:06}.txt", i), format!("{:064x}", i))).collect(),
+        created_at: "2025-09-20T10:15:00+00:00".to_string(),
+        total_files: files,
+        total_bytes: files as u64 * 4096,
+        manifest_sha256: String::new(),
+        skipped_files: Vec::new(),
+        sparse_files: HashMap::new(),
+        encoded_paths: HashMap::new(),
+        ciphertext_sha256: HashMap::new(),
+        keystream_offsets: HashMap::new(),
+    }
+}
+
+fn bench_manifest_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("manifest_hash");
+    for files in [1_000, 100_000] {
+        let manifest = manifest_with(files);
+        group.throughput(Throughput::Elements(files as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(files), &manifest, |b, manifest| {
+            b.iter(|| bench::manifest_hash(manifest))
+        });
+    }
+    group.finish();
+}
+
+fn bench_overwrite(c: &mut Criterion) {
+    let dir = scratch_dir();
+    let (mut target, size, label) = match std::env::var_os("SECUREWIPE_BENCH_DEVICE") {
+        Some(device) => {
+            let mut file = OpenOptions::new().write(true).open(&device).expect("open SECUREWIPE_BENCH_DEVICE");
+            let size = file.seek(SeekFrom::End(0)).unwrap().min(DEVICE_BYTES);
+            (file, size, "device")
+        }
+        None => {
+            let file = File::create(dir.path().join("overwrite-target")).unwrap();
+            file.set_len(DEVICE_BYTES).unwrap();
+            (file, DEVICE_BYTES, "tmpfs")
+        }
+    };
+
+    let mut group = c.benchmark_group(format!("overwrite_{}", label));
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(size));
+    let fills = [
+        ("zeros", OverwriteFill::Zeros),
+        ("random", OverwriteFill::Random),
+        ("pattern", OverwriteFill::Pattern { seed: 42 }),
+    ];
+    for (name, fill) in fills {
+        group.bench_function(name, |b| b.iter(|| bench::overwrite(&mut target, size, &fill).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_file_hash, bench_encrypt, bench_manifest_hash, bench_overwrite);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+#![no_main]
+//! A certificate file handed to `cert verify`: schema validation, the signature and
+//! QR checks, the certificate ID and the typed certificate structs
+
+use ed25519_dalek::SigningKey;
+use libfuzzer_sys::fuzz_target;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use securewipe::{BackupCertificate, CertificateValidator, WipeCertificate};
+
+fn validator() -> &'static CertificateValidator {
+    static VALIDATOR: OnceLock<CertificateValidator> = OnceLock::new();
+    VALIDATOR.get_or_init(|| {
+        let schemas = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../certs/schemas");
+        CertificateValidator::from_schema_dir(Some(schemas)).expect("load certificate schemas")
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else { return };
+    let _ = validator().validate_certificate_json(text);
+
+    let Ok(value) = serde_json::from_str::<Value>(text) else { return };
+    let public_key = SigningKey::from_bytes(&[7; 32]).verifying_key().to_bytes();
+    let _ = securewipe::canonicalize_json(&value);
+    let _ = securewipe::verify_certificate_signature(&value, &public_key);
+    if let Some(qr) = value.pointer("/signature/qr").and_then(Value::as_str) {
+        let _ = securewipe::qr::verify(qr, &public_key);
+    }
+    if let Some(cert_id) = value.get("cert_id").and_then(Value::as_str) {
+        let _ = securewipe::cert_id::parse(cert_id);
+    }
+    match value.get("cert_type").and_then(Value::as_str) {
+        Some("backup") => drop(serde_json::from_value::<BackupCertificate>(value)),
+        Some("wipe") => drop(serde_json::from_value::<WipeCertificate>(value)),
+        _ => {}
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+//! `lsblk -J` output, from whatever util-linux release the machine has
+
+use libfuzzer_sys::fuzz_target;
+use securewipe::LinuxDeviceDiscovery;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(json) = std::str::from_utf8(data) {
+        let _ = LinuxDeviceDiscovery::new_without_enrichment().devices_from_lsblk_json(json);
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+//! Public key PEM files given to `cert verify --pubkey` and `--trusted-key`
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(pem) = std::str::from_utf8(data) {
+        let _ = securewipe::parse_public_key_pem(pem);
+    }
+});
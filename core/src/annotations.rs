@@ -0,0 +1,150 @@
+//! Operator notes. `--note` on backup and wipe goes into the certificate's metadata
+//! before it is signed; `cert annotate` adds auditor notes afterwards to a
+//! `<cert_id>_annotations.jsonl` sidecar next to the certificate, so the signed
+//! payload is never touched. Each annotation records the SHA-256 of the certificate
+//! file it was made against.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Event recorded in the audit log for every annotation
+pub const CERTIFICATE_ANNOTATED_EVENT: &str = "certificate_annotated";
+
+/// Longest note accepted, in characters
+pub const MAX_NOTE_CHARS: usize = 500;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnnotationError {
+    #[error("Note is empty")]
+    Empty,
+    #[error("Note is {0} characters; the limit is {MAX_NOTE_CHARS}")]
+    TooLong(usize),
+    #[error("Note contains control characters")]
+    ControlCharacters,
+    #[error("Certificate not found: {0}")]
+    NotFound(String),
+    #[error("{0}: {1}")]
+    Io(String, String),
+    #[error("{0} line {1} is unreadable: {2}")]
+    Corrupt(String, usize, String),
+    #[error(transparent)]
+    Id(#[from] crate::cert_id::CertIdError),
+}
+
+fn io_error(path: &Path, e: impl std::fmt::Display) -> AnnotationError {
+    AnnotationError::Io(path.display().to_string(), e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Annotation {
+    pub cert_id: String,
+    pub at: String,
+    pub author: String,
+    pub note: String,
+    /// SHA-256 of the certificate file when the note was added
+    pub certificate_sha256: String,
+}
+
+/// Trimmed `note`, or why it can't go on a certificate
+pub fn validate_note(note: &str) -> Result<String, AnnotationError> {
+    let note = note.trim();
+    let chars = note.chars().count();
+    if chars == 0 {
+        return Err(AnnotationError::Empty);
+    }
+    if chars > MAX_NOTE_CHARS {
+        return Err(AnnotationError::TooLong(chars));
+    }
+    if note.chars().any(char::is_control) {
+        return Err(AnnotationError::ControlCharacters);
+    }
+    Ok(note.to_string())
+}
+
+pub fn annotations_path(cert_dir: &Path, cert_id: &str) -> PathBuf {
+    cert_dir.join(format!("{}_annotations.jsonl", cert_id))
+}
+
+/// Append a note about `cert_id` to its sidecar
+pub fn annotate(cert_dir: &Path, cert_id: &str, author: &str, note: &str) -> Result<Annotation, AnnotationError> {
+    let note = validate_note(note)?;
+    let cert_path = crate::cert_id::certificate_path(cert_dir, cert_id)?;
+    let cert_bytes = fs::read(&cert_path).map_err(|_| AnnotationError::NotFound(cert_path.display().to_string()))?;
+    let annotation = Annotation {
+        cert_id: cert_id.to_string(),
+        at: Utc::now().to_rfc3339(),
+        author: author.to_string(),
+        note,
+        certificate_sha256: format!("{:x}", Sha256::digest(&cert_bytes)),
+    };
+
+    let path = annotations_path(cert_dir, cert_id);
+    let mut line = serde_json::to_vec(&annotation).map_err(|e| io_error(&path, e))?;
+    line.push(b'\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(&path).map_err(|e| io_error(&path, e))?;
+    file.write_all(&line).and_then(|_| file.sync_data()).map_err(|e| io_error(&path, e))?;
+    Ok(annotation)
+}
+
+/// Notes about `cert_id`, oldest first; none when it has no sidecar
+pub fn annotations(cert_dir: &Path, cert_id: &str) -> Result<Vec<Annotation>, AnnotationError> {
+    crate::cert_id::certificate_path(cert_dir, cert_id)?;
+    let path = annotations_path(cert_dir, cert_id);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(&path, e)),
+    };
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| AnnotationError::Corrupt(path.display().to_string(), i + 1, e.to_string()))
+        })
+        .collect()
+}
+
+/// Put `note` into a certificate's metadata; call before it is signed
+pub fn set_operator_note(cert: &mut serde_json::Value, note: Option<&str>) {
+    if let Some(note) = note {
+        cert["metadata"]["operator_note"] = note.into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_note() {
+        assert_eq!(validate_note("  customer approved via ticket 4521 ").unwrap(), "customer approved via ticket 4521");
+        assert!(matches!(validate_note("   "), Err(AnnotationError::Empty)));
+        assert!(matches!(validate_note(&"x".repeat(MAX_NOTE_CHARS + 1)), Err(AnnotationError::TooLong(_))));
+        assert!(matches!(validate_note("line one\nline two"), Err(AnnotationError::ControlCharacters)));
+    }
+
+    #[test]
+    fn test_annotate_leaves_certificate_untouched() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cert = r#"{"cert_id": "WPE_1", "signature": {"sig": "abc"}}"#;
+        fs::write(dir.path().join("WPE_1.json"), cert).unwrap();
+
+        assert!(annotations(dir.path(), "WPE_1").unwrap().is_empty());
+        annotate(dir.path(), "WPE_1", "auditor", "Drive returned to customer").unwrap();
+        let second = annotate(dir.path(), "WPE_1", "auditor", "Re-checked label").unwrap();
+
+        let notes = annotations(dir.path(), "WPE_1").unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[1], second);
+        assert_eq!(notes[0].certificate_sha256, format!("{:x}", Sha256::digest(cert.as_bytes())));
+        assert_eq!(fs::read_to_string(dir.path().join("WPE_1.json")).unwrap(), cert);
+        assert!(matches!(annotate(dir.path(), "WPE_2", "auditor", "note"), Err(AnnotationError::NotFound(_))));
+    }
+}
@@ -0,0 +1,393 @@
+pub use crate::audit::login_name;
+use crate::device::RiskLevel;
+use crate::signer::{canonicalize_json, load_public_key, sign_certificate, verify_certificate_signature, SignerError};
+use crate::wipe::WipePlan;
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Errors raised while issuing or checking a wipe approval
+#[derive(Debug, thiserror::Error)]
+pub enum ApprovalError {
+    #[error("Roles file error: {0}")]
+    Roles(String),
+
+    #[error("Operator '{0}' is not listed in the roles file")]
+    UnknownOperator(String),
+
+    #[error("'{0}' is not listed with the approver role")]
+    NotApprover(String),
+
+    #[error("Operator '{0}' cannot approve their own wipe")]
+    SelfApproval(String),
+
+    #[error("Approval is for a different plan (expected {expected}, approval covers {actual})")]
+    PlanMismatch { expected: String, actual: String },
+
+    #[error("Approval expired at {0}")]
+    Expired(String),
+
+    #[error("Approval signature does not verify against {0}'s public key")]
+    BadSignature(String),
+
+    #[error("Invalid approval document: {0}")]
+    Invalid(String),
+
+    #[error("The drive or its plan changed since the plan was approved ({changed}); plan a new wipe and approve that")]
+    PlanChanged { changed: String },
+
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// May run wipes; HIGH/CRITICAL wipes need a second person's approval
+    Operator,
+    /// May approve other operators' HIGH/CRITICAL wipes
+    Approver,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleEntry {
+    pub name: String,
+    pub role: Role,
+    /// Ed25519 public key PEM; required for approvers, relative to the roles file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key_path: Option<PathBuf>,
+}
+
+/// Who may operate and who may approve on this workstation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Roles {
+    pub users: Vec<RoleEntry>,
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+impl Roles {
+    pub fn load(path: &Path) -> Result<Self, ApprovalError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ApprovalError::Roles(format!("{}: {}", path.display(), e)))?;
+        let mut roles: Roles = serde_json::from_str(&content)
+            .map_err(|e| ApprovalError::Roles(format!("{}: {}", path.display(), e)))?;
+        roles.base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(roles)
+    }
+
+    /// Load the configured roles file (SECUREWIPE_ROLES, or roles.json in the data directory)
+    pub fn load_default() -> Result<Self, ApprovalError> {
+        let config = crate::config::Config::load().map_err(|e| ApprovalError::Roles(e.to_string()))?;
+        Self::load(&config.roles_file)
+    }
+
+    pub fn find(&self, name: &str) -> Option<&RoleEntry> {
+        self.users.iter().find(|user| user.name == name)
+    }
+
+    fn approver_key(&self, name: &str) -> Result<[u8; 32], ApprovalError> {
+        let entry = self
+            .find(name)
+            .filter(|entry| entry.role == Role::Approver)
+            .ok_or_else(|| ApprovalError::NotApprover(name.to_string()))?;
+        let key_path = entry
+            .public_key_path
+            .as_ref()
+            .ok_or_else(|| ApprovalError::Roles(format!("approver '{}' has no public_key_path", name)))?;
+        Ok(load_public_key(&self.base_dir.join(key_path))?)
+    }
+}
+
+/// HIGH and CRITICAL drives need a second person's approval before a destructive wipe
+pub fn requires_approval(risk: &RiskLevel) -> bool {
+    matches!(risk, RiskLevel::High | RiskLevel::Critical)
+}
+
+/// The parts of a wipe plan an approver signs off on
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ApprovalSubject {
+    pub device: String,
+    pub serial: Option<String>,
+    pub risk: RiskLevel,
+    pub policy: String,
+    pub main_method: String,
+    pub hpa_dco_clear: bool,
+}
+
+impl ApprovalSubject {
+    pub fn from_plan(plan: &WipePlan, serial: Option<String>, risk: RiskLevel) -> Self {
+        ApprovalSubject {
+            device: plan.device.clone(),
+            serial,
+            risk,
+            policy: match plan.policy {
+                crate::wipe::WipePolicy::Clear => "CLEAR".to_string(),
+                crate::wipe::WipePolicy::Purge => "PURGE".to_string(),
+            },
+            main_method: plan.main_method.clone(),
+            hpa_dco_clear: plan.hpa_dco_clear,
+        }
+    }
+
+    /// SHA-256 over the RFC 8785 canonical JSON of the subject
+    pub fn plan_hash(&self) -> Result<String, ApprovalError> {
+        let value = serde_json::to_value(self).map_err(|e| ApprovalError::Invalid(e.to_string()))?;
+        Ok(format!("{:x}", Sha256::digest(canonicalize_json(&value)?)))
+    }
+}
+
+/// Produced by the operator and handed to an approver
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApprovalRequest {
+    pub subject: ApprovalSubject,
+    pub plan_sha256: String,
+    pub requested_by: String,
+    pub requested_at: String,
+}
+
+impl ApprovalRequest {
+    pub fn new(subject: ApprovalSubject, requested_by: &str) -> Result<Self, ApprovalError> {
+        Ok(ApprovalRequest {
+            plan_sha256: subject.plan_hash()?,
+            subject,
+            requested_by: requested_by.to_string(),
+            requested_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ApprovalSignature {
+    pub alg: String,
+    pub pubkey_id: String,
+    pub sig: String,
+    pub canonicalization: String,
+}
+
+/// A signed approval, embedded verbatim in the wipe certificate
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Approval {
+    pub subject: ApprovalSubject,
+    pub plan_sha256: String,
+    pub requested_by: String,
+    pub approver: String,
+    pub approved_at: String,
+    pub expires_at: String,
+    pub signature: ApprovalSignature,
+}
+
+/// Sign `request` as `approver`. The request hash is re-derived so a hand-edited
+/// subject cannot ride on an old hash.
+pub fn sign_approval(
+    request: &ApprovalRequest,
+    approver: &str,
+    valid_for: chrono::Duration,
+    signing_key: &SigningKey,
+) -> Result<Approval, ApprovalError> {
+    let plan_sha256 = request.subject.plan_hash()?;
+    if plan_sha256 != request.plan_sha256 {
+        return Err(ApprovalError::PlanMismatch { expected: plan_sha256, actual: request.plan_sha256.clone() });
+    }
+    if approver == request.requested_by {
+        return Err(ApprovalError::SelfApproval(approver.to_string()));
+    }
+
+    let approved_at = chrono::Utc::now();
+    let mut value = serde_json::json!({
+        "subject": request.subject,
+        "plan_sha256": plan_sha256,
+        "requested_by": request.requested_by,
+        "approver": approver,
+        "approved_at": approved_at.to_rfc3339(),
+        "expires_at": (approved_at + valid_for).to_rfc3339(),
+    });
+    sign_certificate(&mut value, signing_key, false)?;
+    // pubkey_id is outside the signed bytes, so it can name the approver
+    value["signature"]["pubkey_id"] = serde_json::Value::String(format!("approver:{}", approver));
+
+    serde_json::from_value(value).map_err(|e| ApprovalError::Invalid(e.to_string()))
+}
+
+/// Check that `approval` covers exactly `subject`, is unexpired, was signed by a
+/// listed approver, and that the approver is not the operator running the wipe
+pub fn verify_approval(
+    approval: &Approval,
+    subject: &ApprovalSubject,
+    roles: &Roles,
+    operator: &str,
+) -> Result<(), ApprovalError> {
+    if roles.find(operator).is_none() {
+        return Err(ApprovalError::UnknownOperator(operator.to_string()));
+    }
+    if approval.approver == operator {
+        return Err(ApprovalError::SelfApproval(operator.to_string()));
+    }
+
+    let expected = subject.plan_hash()?;
+    if approval.plan_sha256 != expected || approval.subject != *subject {
+        return Err(ApprovalError::PlanMismatch { expected, actual: approval.plan_sha256.clone() });
+    }
+
+    let expires_at = chrono::DateTime::parse_from_rfc3339(&approval.expires_at)
+        .map_err(|e| ApprovalError::Invalid(format!("expires_at: {}", e)))?;
+    if expires_at < chrono::Utc::now() {
+        return Err(ApprovalError::Expired(approval.expires_at.clone()));
+    }
+
+    let public_key = roles.approver_key(&approval.approver)?;
+    let value = serde_json::to_value(approval).map_err(|e| ApprovalError::Invalid(e.to_string()))?;
+    if !verify_certificate_signature(&value, &public_key)? {
+        return Err(ApprovalError::BadSignature(approval.approver.clone()));
+    }
+    Ok(())
+}
+
+/// SHA-256 over the RFC 8785 canonical JSON of a whole wipe plan, as printed by
+/// `securewipe wipe --format json`
+pub fn wipe_plan_hash(plan: &WipePlan) -> Result<String, ApprovalError> {
+    let value = serde_json::to_value(plan).map_err(|e| ApprovalError::Invalid(e.to_string()))?;
+    Ok(format!("{:x}", Sha256::digest(canonicalize_json(&value)?)))
+}
+
+/// Check that `approved`, a plan a script saved earlier and passed back with
+/// `--approve-plan`, is exactly the plan computed now. Returns the plan's hash.
+pub fn check_approved_plan(approved: &WipePlan, current: &WipePlan) -> Result<String, ApprovalError> {
+    let expected = wipe_plan_hash(current)?;
+    if wipe_plan_hash(approved)? == expected {
+        return Ok(expected);
+    }
+
+    let fields = |plan: &WipePlan| match serde_json::to_value(plan) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (approved, current) = (fields(approved), fields(current));
+    let mut changed: Vec<&str> = approved
+        .keys()
+        .chain(current.keys())
+        .filter(|key| approved.get(*key) != current.get(*key))
+        .map(String::as_str)
+        .collect();
+    changed.sort_unstable();
+    changed.dedup();
+    Err(ApprovalError::PlanChanged { changed: changed.join(", ") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use rand::rngs::OsRng;
+
+    fn subject() -> ApprovalSubject {
+        ApprovalSubject {
+            device: "/dev/sdb".to_string(),
+            serial: Some("WD-WCC4E1234567".to_string()),
+            risk: RiskLevel::High,
+            policy: "PURGE".to_string(),
+            main_method: "controller_sanitize".to_string(),
+            hpa_dco_clear: false,
+        }
+    }
+
+    /// Roles file with operator "bob" and approver "alice", plus alice's key
+    fn roles_with_approver(dir: &Path) -> (Roles, SigningKey) {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let mut der = vec![0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+        der.extend_from_slice(signing_key.verifying_key().as_bytes());
+        let pem = format!("-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n", STANDARD.encode(der));
+        fs::write(dir.join("alice.pem"), pem).unwrap();
+
+        let roles_path = dir.join("roles.json");
+        fs::write(
+            &roles_path,
+            r#"{"users": [
+                {"name": "bob", "role": "operator"},
+                {"name": "alice", "role": "approver", "public_key_path": "alice.pem"}
+            ]}"#,
+        )
+        .unwrap();
+        (Roles::load(&roles_path).unwrap(), signing_key)
+    }
+
+    #[test]
+    fn test_requires_approval() {
+        assert!(requires_approval(&RiskLevel::Critical));
+        assert!(requires_approval(&RiskLevel::High));
+        assert!(!requires_approval(&RiskLevel::Safe));
+    }
+
+    #[test]
+    fn test_approval_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let (roles, alice_key) = roles_with_approver(dir.path());
+        let request = ApprovalRequest::new(subject(), "bob").unwrap();
+
+        let approval = sign_approval(&request, "alice", chrono::Duration::hours(1), &alice_key).unwrap();
+        assert_eq!(approval.signature.pubkey_id, "approver:alice");
+        verify_approval(&approval, &subject(), &roles, "bob").unwrap();
+
+        // A different drive, or a policy downgrade, is not covered
+        let mut other = subject();
+        other.policy = "CLEAR".to_string();
+        assert!(matches!(verify_approval(&approval, &other, &roles, "bob"), Err(ApprovalError::PlanMismatch { .. })));
+
+        // Approvers cannot approve for themselves; unknown operators are rejected
+        assert!(matches!(verify_approval(&approval, &subject(), &roles, "alice"), Err(ApprovalError::SelfApproval(_))));
+        assert!(matches!(verify_approval(&approval, &subject(), &roles, "mallory"), Err(ApprovalError::UnknownOperator(_))));
+    }
+
+    #[test]
+    fn test_approval_rejects_tampering_and_wrong_roles() {
+        let dir = tempfile::tempdir().unwrap();
+        let (roles, alice_key) = roles_with_approver(dir.path());
+        let request = ApprovalRequest::new(subject(), "bob").unwrap();
+        let approval = sign_approval(&request, "alice", chrono::Duration::hours(1), &alice_key).unwrap();
+
+        let mut extended = approval.clone();
+        extended.expires_at = (chrono::Utc::now() + chrono::Duration::days(365)).to_rfc3339();
+        assert!(matches!(verify_approval(&extended, &subject(), &roles, "bob"), Err(ApprovalError::BadSignature(_))));
+
+        let mut impersonated = approval.clone();
+        impersonated.approver = "bob".to_string();
+        assert!(matches!(verify_approval(&impersonated, &subject(), &roles, "alice"), Err(ApprovalError::NotApprover(_))));
+
+        let expired = sign_approval(&request, "alice", chrono::Duration::seconds(-1), &alice_key).unwrap();
+        assert!(matches!(verify_approval(&expired, &subject(), &roles, "bob"), Err(ApprovalError::Expired(_))));
+
+        assert!(matches!(
+            sign_approval(&request, "bob", chrono::Duration::hours(1), &alice_key),
+            Err(ApprovalError::SelfApproval(_))
+        ));
+    }
+    #[test]
+    fn test_check_approved_plan() {
+        use crate::wipe::{plan_wipe, DeviceState, WipePolicy};
+
+        let mut plan = plan_wipe("/dev/sdb", Some(WipePolicy::Purge), false, false, Some(""), Some("sanitize"));
+        plan.device_state = Some(DeviceState {
+            device_id: None,
+            serial: Some("WD-WCC4E1234567".to_string()),
+            model: Some("WDC WD10EZEX".to_string()),
+            capacity_bytes: 1_000_204_886_016,
+            mountpoints: Vec::new(),
+        });
+        let saved: WipePlan = serde_json::from_str(&serde_json::to_string_pretty(&plan).unwrap()).unwrap();
+        assert_eq!(check_approved_plan(&saved, &plan).unwrap(), wipe_plan_hash(&plan).unwrap());
+
+        // A different drive at the same path, now mounted
+        let mut swapped = plan.clone();
+        if let Some(state) = swapped.device_state.as_mut() {
+            state.serial = Some("S3Z9NB0K123456".to_string());
+            state.mountpoints.push("/mnt/data".to_string());
+        }
+        match check_approved_plan(&saved, &swapped) {
+            Err(ApprovalError::PlanChanged { changed }) => assert_eq!(changed, "device_state"),
+            other => panic!("expected PlanChanged, got {:?}", other),
+        }
+    }
+}
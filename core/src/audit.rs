@@ -0,0 +1,360 @@
+//! Tamper-evident audit log: one JSON line per event in `audit.jsonl` under the data
+//! directory. Each entry carries the SHA-256 of the entry before it, so editing,
+//! reordering or deleting an earlier line breaks the chain from that point on.
+
+use crate::config::Config;
+use crate::signer::{canonicalize_json, sign_certificate, SignerError};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+/// Event name for every signature made with a private key
+pub const SIGNATURE_EVENT: &str = "signature_created";
+
+/// `prev_hash` of the first entry
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditError {
+    #[error("Audit log error: {0}")]
+    Io(String),
+    #[error("Audit log entry {0} is unreadable: {1}")]
+    Corrupt(usize, String),
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: String,
+    pub event: String,
+    pub operator: String,
+    pub details: Value,
+    pub prev_hash: String,
+    /// SHA-256 of the canonical JSON of every other field
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> Result<String, AuditError> {
+        let value = serde_json::json!({
+            "seq": self.seq,
+            "timestamp": self.timestamp,
+            "event": self.event,
+            "operator": self.operator,
+            "details": self.details,
+            "prev_hash": self.prev_hash,
+        });
+        let canonical = canonicalize_json(&value).map_err(|e| AuditError::Io(e.to_string()))?;
+        Ok(format!("{:x}", Sha256::digest(canonical)))
+    }
+}
+
+/// Result of walking the hash chain
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ChainStatus {
+    pub entries: usize,
+    pub intact: bool,
+    /// Sequence number of the first entry that doesn't chain, when broken
+    pub broken_at: Option<u64>,
+    pub reason: Option<String>,
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `audit.jsonl` in the configured data directory
+    pub fn open_default() -> Result<Self, AuditError> {
+        let config = Config::load().map_err(|e| AuditError::Io(e.to_string()))?;
+        Ok(Self::new(config.data_dir.join("audit.jsonl")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an event, chained to the current last entry. The file is locked for the
+    /// read-then-append so concurrent runs can't fork the chain.
+    pub fn append(&self, event: &str, operator: &str, details: Value) -> Result<AuditEntry, AuditError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| AuditError::Io(format!("{}: {}", parent.display(), e)))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| AuditError::Io(format!("{}: {}", self.path.display(), e)))?;
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(AuditError::Io(format!("{}: {}", self.path.display(), std::io::Error::last_os_error())));
+        }
+
+        let (seq, prev_hash) = match self.entries()?.last() {
+            Some(last) => (last.seq + 1, last.hash.clone()),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+        let mut entry = AuditEntry {
+            seq,
+            timestamp: Utc::now().to_rfc3339(),
+            event: event.to_string(),
+            operator: operator.to_string(),
+            details,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash()?;
+
+        let line = serde_json::to_string(&entry).map_err(|e| AuditError::Io(e.to_string()))?;
+        writeln!(file, "{}", line)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| AuditError::Io(format!("{}: {}", self.path.display(), e)))?;
+        // Closing the file releases the lock
+        Ok(entry)
+    }
+
+    /// Every entry in order; an empty log when the file doesn't exist yet
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, AuditError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AuditError::Io(format!("{}: {}", self.path.display(), e))),
+        };
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| AuditError::Corrupt(index + 1, e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Re-hash every entry and check each links to the one before
+    pub fn verify(&self) -> Result<ChainStatus, AuditError> {
+        let entries = self.entries()?;
+        let mut prev_hash = GENESIS_HASH.to_string();
+        for (index, entry) in entries.iter().enumerate() {
+            let reason = if entry.seq != index as u64 {
+                Some(format!("expected sequence {}, found {}", index, entry.seq))
+            } else if entry.prev_hash != prev_hash {
+                Some("previous-entry hash does not match; an earlier entry was changed or removed".to_string())
+            } else if entry.compute_hash()? != entry.hash {
+                Some("entry hash does not match its contents".to_string())
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                return Ok(ChainStatus { entries: entries.len(), intact: false, broken_at: Some(entry.seq), reason: Some(reason) });
+            }
+            prev_hash = entry.hash.clone();
+        }
+        Ok(ChainStatus { entries: entries.len(), intact: true, broken_at: None, reason: None })
+    }
+}
+
+/// Short fingerprint of a signing key: the first 16 hex digits of SHA-256 over its
+/// public key
+pub fn key_id(signing_key: &SigningKey) -> String {
+    public_key_id(&signing_key.verifying_key().to_bytes())
+}
+
+/// `key_id` from the public key alone, for verifiers
+pub fn public_key_id(public_key: &[u8; 32]) -> String {
+    let digest = format!("{:x}", Sha256::digest(public_key));
+    digest[..16].to_string()
+}
+
+/// Login name recorded as the operator of an audit event
+pub fn operator() -> String {
+    login_name()
+}
+
+/// Login name of the person running this process, from the real uid rather than
+/// `$USER`, which the caller sets. Under sudo (euid 0) it is the invoking user from
+/// `SUDO_UID`, or `SUDO_USER` if that uid has no passwd entry. A uid without a name
+/// is returned as its number, which no roles file lists.
+pub fn login_name() -> String {
+    let uid = unsafe { libc::getuid() };
+    if unsafe { libc::geteuid() } == 0 {
+        let sudo_uid = std::env::var("SUDO_UID").ok().and_then(|id| id.parse::<libc::uid_t>().ok());
+        if let Some(name) = sudo_uid.and_then(user_name) {
+            return name;
+        }
+        if let Some(name) = std::env::var("SUDO_USER").ok().filter(|name| !name.is_empty()) {
+            return name;
+        }
+    }
+    user_name(uid).unwrap_or_else(|| uid.to_string())
+}
+
+/// The passwd entry name for `uid`
+fn user_name(uid: libc::uid_t) -> Option<String> {
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut found: *mut libc::passwd = std::ptr::null_mut();
+    let rc = unsafe { libc::getpwuid_r(uid, &mut passwd, buf.as_mut_ptr(), buf.len(), &mut found) };
+    if rc != 0 || found.is_null() || passwd.pw_name.is_null() {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(passwd.pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Record a signature over document `cert_id` in the default audit log
+pub fn record_signature(cert_id: &str, signing_key: &SigningKey) -> Result<AuditEntry, AuditError> {
+    AuditLog::open_default()?.append(
+        SIGNATURE_EVENT,
+        &operator(),
+        serde_json::json!({ "cert_id": cert_id, "key_id": key_id(signing_key) }),
+    )
+}
+
+/// Sign a certificate and record the signature. When the record can't be written the
+/// certificate's previous signature (or none) is put back, so no signature leaves here
+/// unaudited.
+pub fn sign_and_record(value: &mut Value, signing_key: &SigningKey, force: bool) -> Result<AuditEntry, AuditError> {
+    let previous = value.get("signature").cloned();
+    sign_certificate(value, signing_key, force)?;
+    let cert_id = value.get("cert_id").and_then(Value::as_str).unwrap_or("unknown").to_string();
+    record_signature(&cert_id, signing_key).inspect_err(|_| match previous {
+        Some(signature) => value["signature"] = signature,
+        None => {
+            if let Some(object) = value.as_object_mut() {
+                object.remove("signature");
+            }
+        }
+    })
+}
+
+/// Signing activity of one key over a report period
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct KeyUsage {
+    pub key_id: String,
+    pub signatures: usize,
+    pub first_used: String,
+    pub last_used: String,
+    pub operators: Vec<String>,
+    pub cert_ids: Vec<String>,
+}
+
+/// Summarize signature events with timestamps in `[since, until)`, per key
+pub fn key_usage(
+    entries: &[AuditEntry],
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Vec<KeyUsage> {
+    let mut by_key: BTreeMap<String, KeyUsage> = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| entry.event == SIGNATURE_EVENT) {
+        let at = match DateTime::parse_from_rfc3339(&entry.timestamp) {
+            Ok(at) => at.with_timezone(&Utc),
+            Err(_) => continue,
+        };
+        if since.is_some_and(|since| at < since) || until.is_some_and(|until| at >= until) {
+            continue;
+        }
+        let key_id = entry.details["key_id"].as_str().unwrap_or("unknown").to_string();
+        let cert_id = entry.details["cert_id"].as_str().unwrap_or("unknown").to_string();
+        let usage = by_key.entry(key_id.clone()).or_insert_with(|| KeyUsage {
+            key_id,
+            signatures: 0,
+            first_used: entry.timestamp.clone(),
+            last_used: entry.timestamp.clone(),
+            operators: Vec::new(),
+            cert_ids: Vec::new(),
+        });
+        usage.signatures += 1;
+        usage.last_used = entry.timestamp.clone();
+        if !usage.operators.contains(&entry.operator) {
+            usage.operators.push(entry.operator.clone());
+        }
+        usage.cert_ids.push(cert_id);
+    }
+    by_key.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_name_from_passwd() {
+        assert_eq!(user_name(0).as_deref(), Some("root"));
+        assert!(!login_name().is_empty());
+    }
+
+    #[test]
+    fn test_audit_chain_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        assert!(log.verify().unwrap().intact);
+
+        for cert_id in ["cert-a", "cert-b", "cert-c"] {
+            log.append(SIGNATURE_EVENT, "alice", serde_json::json!({ "cert_id": cert_id, "key_id": "k1" })).unwrap();
+        }
+        let status = log.verify().unwrap();
+        assert!(status.intact);
+        assert_eq!(status.entries, 3);
+
+        // Rewriting an earlier entry is caught, even with its own hash left alone
+        let content = fs::read_to_string(log.path()).unwrap();
+        fs::write(log.path(), content.replacen("cert-b", "cert-x", 1)).unwrap();
+        let status = log.verify().unwrap();
+        assert!(!status.intact);
+        assert_eq!(status.broken_at, Some(1));
+
+        // So is dropping an entry
+        let lines: Vec<&str> = content.lines().collect();
+        fs::write(log.path(), format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(log.verify().unwrap().broken_at, Some(2));
+    }
+
+    #[test]
+    fn test_key_usage_report() {
+        let signing_key = SigningKey::from_bytes(&[3u8; 32]);
+        let other_key = SigningKey::from_bytes(&[4u8; 32]);
+        assert_eq!(key_id(&signing_key).len(), 16);
+        assert_ne!(key_id(&signing_key), key_id(&other_key));
+
+        let entry = |seq: u64, timestamp: &str, operator: &str, key: &SigningKey, cert_id: &str| AuditEntry {
+            seq,
+            timestamp: timestamp.to_string(),
+            event: SIGNATURE_EVENT.to_string(),
+            operator: operator.to_string(),
+            details: serde_json::json!({ "cert_id": cert_id, "key_id": key_id(key) }),
+            prev_hash: String::new(),
+            hash: String::new(),
+        };
+        let entries = vec![
+            entry(0, "2025-01-10T09:00:00+00:00", "alice", &signing_key, "c1"),
+            entry(1, "2025-02-10T09:00:00+00:00", "bob", &signing_key, "c2"),
+            entry(2, "2025-02-11T09:00:00+00:00", "alice", &other_key, "c3"),
+            entry(3, "2025-03-10T09:00:00+00:00", "alice", &signing_key, "c4"),
+        ];
+
+        let all = key_usage(&entries, None, None);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all.iter().map(|usage| usage.signatures).sum::<usize>(), 4);
+
+        let since = DateTime::parse_from_rfc3339("2025-02-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let until = DateTime::parse_from_rfc3339("2025-03-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let february = key_usage(&entries, Some(since), Some(until));
+        let usage = february.iter().find(|usage| usage.key_id == key_id(&signing_key)).unwrap();
+        assert_eq!(usage.signatures, 1);
+        assert_eq!(usage.operators, vec!["bob".to_string()]);
+        assert_eq!(usage.cert_ids, vec!["c2".to_string()]);
+    }
+}
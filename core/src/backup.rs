@@ -1,31 +1,889 @@
-use aes::cipher::{KeyIvInit, StreamCipher};
+use aes::cipher::{generic_array::GenericArray, KeyIvInit, StreamCipher, StreamCipherSeek};
 use aes::Aes256;
 use chrono::Utc;
 use ctr::Ctr64BE;
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::{thread, time::Duration};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{thread, time::{Duration, Instant}};
 use uuid::Uuid;
-use crate::device::{Device, DeviceDiscovery, LinuxDeviceDiscovery};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
-type Aes256Ctr = Ctr64BE<Aes256>;
+use crate::cert::{DestinationCanary, MonotonicSpan};
+use crate::cleanup::Artifact;
+use crate::clock::MonotonicStart;
+use crate::events::LogLevel;
+use crate::remediation::{self, IoFailure};
+use crate::removal;
+use crate::secrets::Secret;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) type Aes256Ctr = Ctr64BE<Aes256>;
+
+/// A backup's data-encryption key and initial counter block, wiped when dropped
+#[derive(Zeroize, ZeroizeOnDrop)]
+struct DataKey {
+    key: [u8; 32],
+    iv: [u8; 16],
+}
+
+impl DataKey {
+    /// Build the cipher straight from these buffers, without an unwiped copy
+    fn cipher(&self) -> Aes256Ctr {
+        Aes256Ctr::new(GenericArray::from_slice(&self.key), GenericArray::from_slice(&self.iv))
+    }
+}
+
+/// Walk a file with SEEK_DATA/SEEK_HOLE and return its (offset, length) data extents
+#[cfg(target_os = "linux")]
+fn find_data_extents(file: &File, len: u64) -> std::io::Result<Vec<(u64, u64)>> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    let mut extents = Vec::new();
+    let mut pos: libc::off_t = 0;
+
+    while (pos as u64) < len {
+        let data = unsafe { libc::lseek(fd, pos, libc::SEEK_DATA) };
+        if data < 0 {
+            let err = std::io::Error::last_os_error();
+            // ENXIO: no data beyond `pos`, the rest of the file is a hole
+            if err.raw_os_error() == Some(libc::ENXIO) {
+                break;
+            }
+            return Err(err);
+        }
+
+        let hole = unsafe { libc::lseek(fd, data, libc::SEEK_HOLE) };
+        if hole < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        extents.push((data as u64, (hole - data) as u64));
+        pos = hole;
+    }
+
+    Ok(extents)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BackupManifest {
     pub files: HashMap<String, String>, // relative_path -> sha256
     pub created_at: String,
     pub total_files: usize,
     pub total_bytes: u64,
     pub manifest_sha256: String,
+    #[serde(default)]
+    pub skipped_files: Vec<SkippedFile>,
+    #[serde(default)]
+    pub sparse_files: HashMap<String, SparseExtents>, // relative_path -> data extents
+    #[serde(default)]
+    pub encoded_paths: HashMap<String, EncodedPath>, // "b64:..." key -> display name
+    #[serde(default)]
+    pub ciphertext_sha256: HashMap<String, String>, // relative_path -> sha256 of the stored file
+    #[serde(default)]
+    pub keystream_offsets: HashMap<String, u64>, // relative_path -> AES-CTR byte offset of its first data byte
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Prefix for manifest keys that carry the raw path bytes as URL-safe base64
+pub const ENCODED_PATH_PREFIX: &str = "b64:";
+
+/// Human-readable name for a manifest entry whose path is not valid UTF-8
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct EncodedPath {
+    pub display: String,
+    pub byte_len: usize,
+}
+
+/// Build the manifest key for a relative path. Valid UTF-8 paths are stored as-is;
+/// anything else is stored byte-exact as base64 with a lossy display name alongside.
+pub fn encode_manifest_path(rel_path: &Path) -> (String, Option<EncodedPath>) {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+    if let Some(utf8) = rel_path.to_str() {
+        if !utf8.starts_with(ENCODED_PATH_PREFIX) {
+            return (utf8.to_string(), None);
+        }
+    }
+
+    #[cfg(unix)]
+    let raw = {
+        use std::os::unix::ffi::OsStrExt;
+        rel_path.as_os_str().as_bytes().to_vec()
+    };
+    #[cfg(not(unix))]
+    let raw = rel_path.to_string_lossy().as_bytes().to_vec();
+
+    let key = format!("{}{}", ENCODED_PATH_PREFIX, URL_SAFE_NO_PAD.encode(&raw));
+    let encoded = EncodedPath {
+        display: rel_path.to_string_lossy().to_string(),
+        byte_len: raw.len(),
+    };
+    (key, Some(encoded))
+}
+
+/// Inverse of `encode_manifest_path`; returns None for a corrupt encoded key, and for
+/// any path that isn't purely relative (absolute, `..` or `.` components), so a
+/// tampered manifest can't point a restore or verify outside the backup
+pub fn decode_manifest_path(key: &str) -> Option<PathBuf> {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+    let path = match key.strip_prefix(ENCODED_PATH_PREFIX) {
+        None => PathBuf::from(key),
+        Some(encoded) => {
+            let raw = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::ffi::OsStrExt;
+                PathBuf::from(std::ffi::OsStr::from_bytes(&raw))
+            }
+            #[cfg(not(unix))]
+            {
+                PathBuf::from(String::from_utf8(raw).ok()?)
+            }
+        }
+    };
+    let relative = path.components().next().is_some()
+        && path.components().all(|component| matches!(component, std::path::Component::Normal(_)));
+    relative.then_some(path)
+}
+
+/// What `backup_single_file` produced for one source file
+struct StoredFile {
+    original_hash: String,
+    ciphertext_hash: String,
+    file_bytes: u64,
+    sparse: Option<SparseExtents>,
+}
+
+fn hash_zeros(hasher: &mut Sha256, mut count: u64) {
+    let zeros = [0u8; 8192];
+    while count > 0 {
+        let n = std::cmp::min(count, zeros.len() as u64) as usize;
+        hasher.update(&zeros[..n]);
+        count -= n as u64;
+    }
+}
+
+/// Data-bearing regions of a sparse file; everything outside `extents` is a hole
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SparseExtents {
+    pub logical_size: u64,
+    pub extents: Vec<(u64, u64)>, // (offset, length)
+}
+
+impl SparseExtents {
+    pub fn data_bytes(&self) -> u64 {
+        self.extents.iter().map(|(_, len)| len).sum()
+    }
+}
+
+/// A source file that could not be backed up under `BackupErrorPolicy::Skip`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// What `backup --dry-run` found: the manifest a backup would write, and everything
+/// it would leave out
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct BackupPreview {
+    pub source_paths: Vec<String>,
+    pub excludes: Vec<String>,
+    pub total_files: usize,
+    pub total_bytes: u64,
+    /// Manifest paths and sizes, as the backup would record them
+    pub files: Vec<PreviewFile>,
+    /// The biggest files, largest first
+    pub largest_files: Vec<PreviewFile>,
+    pub excluded_files: usize,
+    pub excluded_bytes: u64,
+    /// Directories and files that couldn't be read
+    pub unreadable: Vec<SkippedFile>,
+    /// Files the destination filesystem can't store; empty without a destination
+    pub incompatible: Vec<SkippedFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_filesystem: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_free_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct PreviewFile {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Largest files listed by a backup preview
+const PREVIEW_LARGEST_FILES: usize = 20;
+
+/// What to do when a single source file cannot be read or written
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BackupErrorPolicy {
+    #[serde(rename = "abort")]
+    Abort,
+    #[serde(rename = "skip")]
+    Skip,
+}
+
+/// PBKDF2 rounds for a passphrase-derived backup key
+pub const BACKUP_KDF_ITERATIONS: u32 = 600_000;
+
+/// How a backup's key was derived from the backup passphrase, saved as `key.json` so the
+/// backup can be decrypted later. Holds nothing secret.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupKeyParams {
+    pub kdf: String,
+    pub iterations: u32,
+    /// Base64
+    pub salt: String,
+    /// Base64 AES-CTR initial counter block
+    pub iv: String,
+}
+
+impl BackupKeyParams {
+    fn generate(rng: &mut ChaCha20Rng, iv: &[u8; 16]) -> Self {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let mut salt = [0u8; 16];
+        rng.fill_bytes(&mut salt);
+        Self {
+            kdf: "PBKDF2-HMAC-SHA256".to_string(),
+            iterations: BACKUP_KDF_ITERATIONS,
+            salt: STANDARD.encode(salt),
+            iv: STANDARD.encode(iv),
+        }
+    }
+
+    /// The AES-256 key these parameters derive from `passphrase`
+    pub fn derive_key(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>, Box<dyn std::error::Error>> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        if self.kdf != "PBKDF2-HMAC-SHA256" {
+            return Err(format!("Unsupported backup key derivation: {}", self.kdf).into());
+        }
+        let salt = STANDARD.decode(&self.salt)?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), &salt, self.iterations, key.as_mut());
+        Ok(key)
+    }
+}
+
+/// Fingerprint of a data key, kept in the progress journal so a resume with the wrong
+/// passphrase is refused before anything is encrypted under a different key
+fn key_check(key: &[u8; 32]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"securewipe-backup-key-check");
+    hasher.update(key);
+    format!("{:x}", hasher.finalize())
+}
+
+/// How the copy loop reacts to a destination running out of space: it keeps
+/// `reserve_bytes` free, and when a file won't fit it waits for the operator to free
+/// space (polling every `poll`) or cancel, up to `max_wait`
+#[derive(Debug, Clone)]
+pub struct SpaceWait {
+    pub reserve_bytes: u64,
+    pub poll: Duration,
+    pub max_wait: Option<Duration>,
+}
+
+impl Default for SpaceWait {
+    fn default() -> Self {
+        Self {
+            reserve_bytes: 16 * 1024 * 1024,
+            poll: Duration::from_secs(5),
+            max_wait: Some(Duration::from_secs(30 * 60)),
+        }
+    }
+}
+
+/// Times a file is retried after the destination reports it is full
+const OUT_OF_SPACE_RETRIES: usize = 3;
+
+static ABORT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn request_abort(_signal: libc::c_int) {
+    ABORT_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Make SIGTERM and SIGINT stop a backup at the next file boundary instead of killing
+/// it mid-write, so the backup directory is left resumable
+#[cfg(unix)]
+pub fn install_abort_handler() {
+    let handler = request_abort as extern "C" fn(libc::c_int);
+    unsafe {
+        libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+        libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install_abort_handler() {}
+
+fn abort_requested() -> bool {
+    ABORT_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Bytes available to unprivileged writers on the filesystem holding `path`
+#[cfg(target_os = "linux")]
+fn free_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Bytes `path` will take on the destination: its allocated size, so sparse files
+/// aren't counted at their logical length
+fn stored_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::metadata(path) else { return 0 };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        std::cmp::min(metadata.len(), metadata.blocks() * 512)
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.len()
+    }
+}
+
+/// Written to `progress.jsonl` in the backup directory as the copy runs, and removed
+/// once `manifest.json` exists. It lets an interrupted backup be resumed.
+pub(crate) const JOURNAL_FILE: &str = "progress.jsonl";
+
+/// Plain-text note at the backup root for whoever finds the drive later
+const README_FILE: &str = "README.txt";
+/// `BackupMetadata` at the backup root
+const METADATA_FILE: &str = "metadata.json";
+
+/// Files the backup itself keeps at its root. Source files with these names at the
+/// top of the manifest would overwrite them, so they are skipped.
+pub const RESERVED_ROOT_FILES: &[&str] = &["manifest.json", "key.json", JOURNAL_FILE, README_FILE, METADATA_FILE];
+
+/// A backup is written into `<dest>/.inprogress-<id>` and renamed to `<dest>/<id>` only
+/// once its manifest and certificate are on disk, so a directory named by a backup id
+/// is always a complete backup
+pub const IN_PROGRESS_PREFIX: &str = ".inprogress-";
+
+/// What a backup was started with; the journal's first entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct BackupRun {
+    backup_id: String,
+    device: String,
+    destination: String,
+    source_paths: Vec<String>,
+    /// `--exclude` patterns, so a resumed backup leaves out the same files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    excludes: Vec<String>,
+    /// `--note`, so a resumed backup's certificate still carries it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    encryption_method: String,
+    #[serde(default)]
+    key_check: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JournalEntry {
+    Start(BackupRun),
+    /// About to write `path` (a manifest key) starting at `keystream_offset`
+    Begin { path: String, keystream_offset: u64, length: u64 },
+    Stored {
+        path: String,
+        original_hash: String,
+        ciphertext_hash: String,
+        file_bytes: u64,
+        keystream_offset: u64,
+        keystream_end: u64,
+        sparse: Option<SparseExtents>,
+        encoded: Option<EncodedPath>,
+    },
+    /// Why the backup stopped, when it was for a reason worth recording
+    Interrupted { reason: String, at: String },
+}
+
+/// Append-only, synced after every entry so it never runs ahead of what it describes
+struct Journal {
+    file: File,
+}
+
+impl Journal {
+    fn open(backup_dir: &Path) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(backup_dir.join(JOURNAL_FILE))?;
+        Ok(Self { file })
+    }
+
+    fn append(&mut self, entry: &JournalEntry) -> Result<(), Box<dyn std::error::Error>> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Entries in order; a torn last line from a crash mid-append is dropped
+    fn read(backup_dir: &Path) -> Result<Vec<JournalEntry>, Box<dyn std::error::Error>> {
+        let path = backup_dir.join(JOURNAL_FILE);
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("No resumable progress in {}: {}", backup_dir.display(), e))?;
+        let lines: Vec<&str> = content.lines().filter(|line| !line.trim().is_empty()).collect();
+        let mut entries = Vec::with_capacity(lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                Err(_) if i + 1 == lines.len() => break,
+                Err(e) => return Err(format!("Corrupt {} line {}: {}", path.display(), i + 1, e).into()),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Exclusive lock on a backup directory, held for as long as a backup writes into it,
+/// so stale-directory detection can tell a running backup from a crashed one
+struct DirLock {
+    _dir: File,
+}
+
+impl DirLock {
+    /// `None` when another process holds the lock
+    #[cfg(unix)]
+    fn try_acquire(dir: &Path) -> std::io::Result<Option<Self>> {
+        use std::os::unix::io::AsRawFd;
+
+        let file = File::open(dir)?;
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let error = std::io::Error::last_os_error();
+            return match error.kind() {
+                std::io::ErrorKind::WouldBlock => Ok(None),
+                _ => Err(error),
+            };
+        }
+        // Dropping `_dir` releases it
+        Ok(Some(Self { _dir: file }))
+    }
+
+    #[cfg(not(unix))]
+    fn try_acquire(dir: &Path) -> std::io::Result<Option<Self>> {
+        Ok(Some(Self { _dir: File::open(dir)? }))
+    }
+}
+
+/// An in-progress backup directory no running backup holds
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct StaleBackup {
+    pub path: String,
+    pub backup_id: String,
+    /// Files the journal records as stored
+    pub files_stored: usize,
+    /// Whether `backup --resume` can finish it (it has a journal and a passphrase-derived key)
+    pub resumable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<String>,
+}
+
+/// In-progress backup directories under `destination` left by a backup that crashed
+/// or was stopped. Directories a running backup is writing into are left out.
+pub fn stale_backups(destination: &Path) -> std::io::Result<Vec<StaleBackup>> {
+    let mut stale = Vec::new();
+    for dir in fs::read_dir(destination)?.flatten().map(|entry| entry.path()) {
+        let Some(backup_id) = dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(IN_PROGRESS_PREFIX))
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        if !dir.is_dir() || !matches!(DirLock::try_acquire(&dir), Ok(Some(_))) {
+            continue;
+        }
+        let entries = Journal::read(&dir).unwrap_or_default();
+        stale.push(StaleBackup {
+            path: dir.display().to_string(),
+            backup_id,
+            files_stored: entries.iter().filter(|entry| matches!(entry, JournalEntry::Stored { .. })).count(),
+            resumable: matches!(entries.first(), Some(JournalEntry::Start(_))) && dir.join("key.json").exists(),
+            modified_at: fs::metadata(&dir)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(|modified| chrono::DateTime::<Utc>::from(modified).to_rfc3339()),
+        });
+    }
+    stale.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(stale)
+}
+
+/// Delete a stale in-progress directory, unless a backup has picked it up since it
+/// was listed
+pub fn remove_stale_backup(stale: &StaleBackup) -> Result<(), Box<dyn std::error::Error>> {
+    remove_in_progress_dir(Path::new(&stale.path))
+}
+
+pub(crate) fn remove_in_progress_dir(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let is_in_progress = dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(IN_PROGRESS_PREFIX));
+    if !is_in_progress {
+        return Err(format!("{} is not an in-progress backup directory", dir.display()).into());
+    }
+    let _lock = DirLock::try_acquire(dir)?.ok_or_else(|| format!("{} is being written by a running backup", dir.display()))?;
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+/// The in-progress directory for `dir` when `dir` is given by its final name
+/// (`<dest>/<id>`) but the backup never finished
+fn in_progress_dir(dir: &Path) -> PathBuf {
+    if dir.exists() {
+        return dir.to_path_buf();
+    }
+    match (dir.parent(), dir.file_name()) {
+        (Some(parent), Some(name)) => {
+            let candidate = parent.join(format!("{}{}", IN_PROGRESS_PREFIX, name.to_string_lossy()));
+            if candidate.is_dir() { candidate } else { dir.to_path_buf() }
+        }
+        _ => dir.to_path_buf(),
+    }
+}
+
+/// Give a finished in-progress directory its final name. A directory that already
+/// has it (a backup started before in-progress directories existed) stays put.
+fn finalize_backup_dir(backup_dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let Some(id) = backup_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix(IN_PROGRESS_PREFIX))
+    else {
+        return Ok(backup_dir.to_path_buf());
+    };
+    let parent = backup_dir.parent().unwrap_or(Path::new("."));
+    let final_dir = parent.join(id);
+    if final_dir.exists() {
+        return Err(format!("Cannot finish {}: {} already exists", backup_dir.display(), final_dir.display()).into());
+    }
+    fs::rename(backup_dir, &final_dir)?;
+    sync_dir(parent);
+    Ok(final_dir)
+}
+
+/// Best effort: some filesystems (e.g. vfat on USB sticks) can't fsync a directory
+fn sync_dir(dir: &Path) {
+    let _ = File::open(dir).and_then(|dir| dir.sync_all());
+}
+
+/// Size of the destination canary; a multiple of any sector size O_DIRECT needs
+const CANARY_BYTES: usize = 64 * 1024;
+
+/// Write a random file to `dir` synchronously, bypassing the page cache where the
+/// filesystem allows it, read it back the same way and compare. The file is removed.
+pub fn write_canary(dir: &Path) -> DestinationCanary {
+    let path = dir.join(format!(".securewipe-canary-{}", Uuid::new_v4()));
+    let mut canary = DestinationCanary { passed: false, bytes: CANARY_BYTES as u64, direct_io: false, error: None };
+    match round_trip_canary(&path) {
+        Ok((matched, direct_io)) => {
+            canary.passed = matched;
+            canary.direct_io = direct_io;
+            if !matched {
+                canary.error = Some("data read back differs from what was written".to_string());
+            }
+        }
+        Err(e) => canary.error = Some(e.to_string()),
+    }
+    let _ = fs::remove_file(&path);
+    canary
+}
+
+/// Whether the canary read back intact, and whether direct I/O was used
+fn round_trip_canary(path: &Path) -> std::io::Result<(bool, bool)> {
+    // O_DIRECT needs buffers aligned to the logical block size; 4 KiB covers all of them
+    const ALIGN: usize = 4096;
+    let mut written_storage = vec![0u8; CANARY_BYTES + ALIGN];
+    let offset = written_storage.as_ptr().align_offset(ALIGN);
+    let written = &mut written_storage[offset..offset + CANARY_BYTES];
+    ChaCha20Rng::from_entropy().fill_bytes(written);
+
+    let (mut file, direct_io) = open_canary(path, true)?;
+    file.write_all(written)?;
+    file.sync_all()?;
+    drop(file);
+
+    let mut read_storage = vec![0u8; CANARY_BYTES + ALIGN];
+    let offset = read_storage.as_ptr().align_offset(ALIGN);
+    let read = &mut read_storage[offset..offset + CANARY_BYTES];
+    let (mut file, _) = open_canary(path, false)?;
+    if !direct_io {
+        drop_cached_pages(&file);
+    }
+    file.read_exact(read)?;
+    Ok((read[..] == written[..], direct_io))
+}
+
+/// Open with O_DIRECT|O_SYNC, falling back to O_SYNC on filesystems that reject O_DIRECT
+#[cfg(target_os = "linux")]
+fn open_canary(path: &Path, create: bool) -> std::io::Result<(File, bool)> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let open = |flags: i32| {
+        let mut options = OpenOptions::new();
+        if create {
+            options.write(true).create_new(true);
+        } else {
+            options.read(true);
+        }
+        options.custom_flags(flags).open(path)
+    };
+    match open(libc::O_DIRECT | libc::O_SYNC) {
+        Ok(file) => Ok((file, true)),
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {
+            // A failed create_new may have left the file behind
+            if create {
+                let _ = fs::remove_file(path);
+            }
+            open(libc::O_SYNC).map(|file| (file, false))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_canary(path: &Path, create: bool) -> std::io::Result<(File, bool)> {
+    let file = if create { OpenOptions::new().write(true).create_new(true).open(path)? } else { File::open(path)? };
+    Ok((file, false))
+}
+
+/// Best effort: drop the canary from the page cache so the read comes from the media
+#[cfg(target_os = "linux")]
+fn drop_cached_pages(file: &File) {
+    use std::os::unix::io::AsRawFd;
+
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn drop_cached_pages(_file: &File) {}
+
+fn write_synced(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Files stored so far, accumulated into the manifest once the copy finishes
+#[derive(Default)]
+struct CopyProgress {
+    files: HashMap<String, String>,
+    sparse: HashMap<String, SparseExtents>,
+    encoded: HashMap<String, EncodedPath>,
+    ciphertext_sha256: HashMap<String, String>,
+    keystream_offsets: HashMap<String, u64>,
+    skipped: Vec<SkippedFile>,
+    total_bytes: u64,
+}
+
+impl CopyProgress {
+    fn record(&mut self, rel_path: String, encoded: Option<EncodedPath>, stored: StoredFile, keystream_offset: u64) {
+        let StoredFile { original_hash, ciphertext_hash, file_bytes, sparse } = stored;
+        if let Some(layout) = sparse {
+            self.sparse.insert(rel_path.clone(), layout);
+        }
+        if let Some(encoded) = encoded {
+            self.encoded.insert(rel_path.clone(), encoded);
+        }
+        self.ciphertext_sha256.insert(rel_path.clone(), ciphertext_hash);
+        self.keystream_offsets.insert(rel_path.clone(), keystream_offset);
+        self.files.insert(rel_path, original_hash);
+        self.total_bytes += file_bytes;
+    }
+}
+
+/// Destination filesystem families with naming or size restrictions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DestinationFs {
+    Fat,
+    ExFat,
+    Ntfs,
+    Other,
+}
+
+impl DestinationFs {
+    /// Detect the filesystem holding `path` from its statfs magic
+    #[cfg(target_os = "linux")]
+    pub fn detect(path: &Path) -> Self {
+        use std::os::unix::ffi::OsStrExt;
+
+        let c_path = match std::ffi::CString::new(path.as_os_str().as_bytes()) {
+            Ok(c_path) => c_path,
+            Err(_) => return DestinationFs::Other,
+        };
+        let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return DestinationFs::Other;
+        }
+
+        match stat.f_type as u64 {
+            0x4d44 => DestinationFs::Fat,        // MSDOS_SUPER_MAGIC
+            0x2011_bab0 => DestinationFs::ExFat, // EXFAT_SUPER_MAGIC
+            0x5346_544e => DestinationFs::Ntfs,  // NTFS_SB_MAGIC (ntfs3)
+            // FUSE_SUPER_MAGIC covers every FUSE filesystem; only ntfs-3g's is NTFS
+            0x6573_7546 => {
+                let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+                match fs::read_to_string("/proc/self/mountinfo") {
+                    Ok(mountinfo) if is_fuse_ntfs_mount(&mountinfo, &path) => DestinationFs::Ntfs,
+                    _ => DestinationFs::Other,
+                }
+            }
+            _ => DestinationFs::Other,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn detect(_path: &Path) -> Self {
+        DestinationFs::Other
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DestinationFs::Fat => "FAT32",
+            DestinationFs::ExFat => "exFAT",
+            DestinationFs::Ntfs => "NTFS",
+            DestinationFs::Other => "native",
+        }
+    }
+
+    fn max_file_size(&self) -> Option<u64> {
+        match self {
+            DestinationFs::Fat => Some(0xFFFF_FFFF), // 4 GiB - 1
+            _ => None,
+        }
+    }
+
+    fn is_windows_family(&self) -> bool {
+        !matches!(self, DestinationFs::Other)
+    }
+
+    /// Explain why a single path component cannot be created, if it can't
+    fn check_name(&self, component: &std::ffi::OsStr) -> Option<String> {
+        let lossy = component.to_string_lossy();
+
+        if self.is_windows_family() {
+            let name = match component.to_str() {
+                Some(name) => name,
+                None => return Some(format!("name {:?} is not valid UTF-8", lossy)),
+            };
+            if let Some(c) = name.chars().find(|c| c.is_control() || "\\:*?\"<>|".contains(*c)) {
+                return Some(format!("name {:?} contains {:?}", name, c));
+            }
+            if name.ends_with('.') || name.ends_with(' ') {
+                return Some(format!("name {:?} ends with a dot or space", name));
+            }
+            if name.encode_utf16().count() > 255 {
+                return Some(format!("name {:?} is longer than 255 characters", name));
+            }
+        } else if component.len() > 255 {
+            return Some(format!("name {:?} is longer than 255 bytes", lossy));
+        }
+        None
+    }
+
+    /// Check a relative destination path and its size against this filesystem
+    pub fn check_entry(&self, rel_path: &Path, size: u64) -> Option<String> {
+        for component in rel_path.components() {
+            if let std::path::Component::Normal(name) = component {
+                if let Some(problem) = self.check_name(name) {
+                    return Some(problem);
+                }
+            }
+        }
+        match self.max_file_size() {
+            Some(limit) if size > limit => Some(format!(
+                "{} bytes exceeds the {} per-file limit of {} bytes", size, self.name(), limit)),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the mount holding `path` (absolute, symlinks resolved) is ntfs-3g, i.e. a
+/// `fuseblk` mount with an `ntfs` subtype in /proc/self/mountinfo
+fn is_fuse_ntfs_mount(mountinfo: &str, path: &Path) -> bool {
+    // Later lines are mounted over earlier ones, so the last longest match wins
+    let mut best: Option<(usize, &str)> = None;
+    for line in mountinfo.lines() {
+        let Some((mount, fs_fields)) = line.split_once(" - ") else { continue };
+        let (Some(mount_point), Some(fs_type)) = (mount.split(' ').nth(4), fs_fields.split(' ').next()) else { continue };
+        // Mount points escape space, tab, newline and backslash as octal
+        let mount_point = mount_point.replace("\\040", " ").replace("\\011", "\t").replace("\\012", "\n").replace("\\134", "\\");
+        let depth = Path::new(&mount_point).components().count();
+        if path.starts_with(&mount_point) && best.is_none_or(|(best_depth, _)| depth >= best_depth) {
+            best = Some((depth, fs_type));
+        }
+    }
+    best.and_then(|(_, fs_type)| fs_type.strip_prefix("fuseblk."))
+        .is_some_and(|subtype| subtype == "ntfs" || subtype == "ntfs-3g")
+}
+
+/// A manifest entry that failed at-rest verification
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct VerificationFailure {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Outcome of re-checking a stored backup against its manifest. The backup key is
+/// not retained, so integrity is checked on the ciphertext rather than the plaintext.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BackupVerificationReport {
+    pub backup_dir: String,
+    pub manifest_valid: bool,
+    pub files_checked: usize,
+    pub files_passed: usize,
+    /// Entries from older backups without a recorded ciphertext hash; only presence is checked
+    pub presence_only: usize,
+    pub failures: Vec<VerificationFailure>,
+    pub passed: bool,
+}
+
+/// Outcome of decrypting a backup into a directory
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RestoreReport {
+    pub backup_dir: String,
+    pub target_dir: String,
+    pub files_restored: usize,
+    /// Length of the restored files, holes included
+    pub logical_bytes: u64,
+    /// Bytes decrypted and written; sparse files restore only their data extents
+    pub physical_bytes: u64,
+    pub sparse_files: usize,
+    pub failures: Vec<VerificationFailure>,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BackupResult {
     pub manifest: BackupManifest,
     pub destination: String,
@@ -33,6 +891,92 @@ pub struct BackupResult {
     pub verification_samples: usize,
     pub verification_passed: bool,
     pub backup_id: String,
+    /// Whole operation measured on the monotonic clock
+    #[serde(default)]
+    pub timing: Option<MonotonicSpan>,
+    /// Write check of the destination made before the copy
+    #[serde(default)]
+    pub destination_canary: Option<DestinationCanary>,
+}
+
+/// Saved as `metadata.json` at the backup root, next to a `README.txt` saying the same
+/// in words, so a backup found years later can be identified without this tool
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupMetadata {
+    pub backup_id: String,
+    pub created_at: String,
+    pub tool: String,
+    pub tool_version: String,
+    pub device: String,
+    pub source_paths: Vec<String>,
+    pub encryption_method: String,
+    pub total_files: usize,
+    pub total_bytes: u64,
+    pub manifest_sha256: String,
+    /// The backup certificate, in the certificates directory of the machine that made it
+    pub cert_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_serial: Option<String>,
+    /// Whether `key.json` is present, i.e. the passphrase can decrypt the files
+    pub restorable: bool,
+}
+
+impl BackupMetadata {
+    /// The `README.txt` text
+    pub fn readme(&self) -> String {
+        let mut text = format!(
+            "SecureWipe encrypted backup\n\
+             ===========================\n\n\
+             Backup ID:     {}\n\
+             Created:       {}\n\
+             Made by:       {} {}\n\
+             Source device: {}\n\
+             Source paths:  {}\n\
+             Files:         {} ({} bytes)\n\
+             Encryption:    {}\n\
+             Certificate:   {}{}\n\n\
+             The files in this folder are encrypted copies of the source paths above.\n\
+             manifest.json lists each file with the SHA-256 of its original content and\n\
+             of the stored copy; metadata.json holds the details above.\n\n\
+             Check the copies are intact with:\n\
+             \n    securewipe backup verify --dir <this folder>\n\n",
+            self.backup_id,
+            self.created_at,
+            self.tool,
+            self.tool_version,
+            self.device,
+            self.source_paths.join(", "),
+            self.total_files,
+            self.total_bytes,
+            self.encryption_method,
+            self.cert_id,
+            self.certificate_serial.as_deref().map(|serial| format!(" (No. {})", serial)).unwrap_or_default(),
+        );
+        if self.restorable {
+            text.push_str(
+                "How to restore\n\
+                 --------------\n\
+                 You need the passphrase the backup was made with. key.json holds the key\n\
+                 derivation parameters (no secrets): the 256-bit AES key is\n\
+                 PBKDF2-HMAC-SHA256(passphrase, salt, iterations). The files are AES-256-CTR\n\
+                 with one keystream across the whole backup: the initial counter block is\n\
+                 key.json's iv (64-bit big-endian counter in its last 8 bytes), and each\n\
+                 file starts at the byte offset manifest.json gives in keystream_offsets.\n\
+                 Decrypt each file from that offset; its SHA-256 must then match the\n\
+                 manifest's files entry. Files listed in sparse_files store only their\n\
+                 data extents, at their original offsets, and the keystream runs over\n\
+                 those bytes alone. `securewipe backup restore` does all of this.\n",
+            );
+        } else {
+            text.push_str(
+                "How to restore\n\
+                 --------------\n\
+                 This backup was made without a passphrase and its random key was not kept,\n\
+                 so the files cannot be decrypted. It only shows which files were copied.\n",
+            );
+        }
+        text
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,85 +991,702 @@ pub struct BackupCertificate {
     pub signature: Option<SignatureInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DeviceInfo {
-    pub device_path: String,
-    pub model: Option<String>,
-    pub serial: Option<String>,
-    pub capacity_bytes: Option<u64>,
-    pub bus_type: Option<String>,
-}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    pub device_path: String,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub capacity_bytes: Option<u64>,
+    pub bus_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub source_paths: Vec<String>,
+    pub destination: String,
+    pub encryption_method: String,
+    pub compression_method: String,
+    pub manifest: BackupManifest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationInfo {
+    pub samples_verified: usize,
+    pub samples_passed: usize,
+    pub verification_method: String,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureInfo {
+    pub alg: String,
+    pub pubkey_id: String,
+    pub sig: String,
+}
+
+pub trait BackupOperations {
+    fn perform_backup(
+        &self,
+        device: &str,
+        paths: &[String],
+        destination: &str,
+    ) -> Result<BackupResult, Box<dyn std::error::Error>>;
+}
+
+pub struct EncryptedBackup {
+    pub logger: Box<dyn BackupLogger>,
+    pub on_error: BackupErrorPolicy,
+    /// Backup passphrase; without one each backup gets a random key that isn't kept
+    pub passphrase: Option<Secret>,
+    pub space_wait: SpaceWait,
+    /// Directory manifest paths are relative to; the first source path's parent when None
+    pub source_base: Option<PathBuf>,
+    /// Certificate device block for a source that isn't a local disk, such as a phone
+    pub source_device: Option<crate::cert::CertificateDevice>,
+    /// Patterns for files and directories under the source paths to leave out
+    pub excludes: Vec<String>,
+    /// Operator note recorded in the certificate's metadata
+    pub note: Option<String>,
+}
+
+pub trait BackupLogger {
+    fn log(&self, level: &str, step_id: &str, message: &str, data: Option<serde_json::Value>);
+}
+
+pub struct JsonLogger;
+
+impl BackupLogger for JsonLogger {
+    fn log(&self, level: &str, step_id: &str, message: &str, data: Option<serde_json::Value>) {
+        let level = match level {
+            "debug" => LogLevel::Debug,
+            "warn" => LogLevel::Warn,
+            "error" => LogLevel::Error,
+            _ => LogLevel::Info,
+        };
+        crate::events::log_with(level, message, serde_json::json!({"step_id": step_id, "data": data}));
+    }
+}
+
+impl EncryptedBackup {
+    pub fn new() -> Self {
+        Self {
+            logger: Box::new(JsonLogger),
+            on_error: BackupErrorPolicy::Abort,
+            passphrase: None,
+            space_wait: SpaceWait::default(),
+            source_base: None,
+            source_device: None,
+            excludes: Vec::new(),
+            note: None,
+        }
+    }
+
+    pub fn with_error_policy(mut self, on_error: BackupErrorPolicy) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    pub fn with_passphrase(mut self, passphrase: Option<Secret>) -> Self {
+        self.passphrase = passphrase;
+        self
+    }
+
+    pub fn with_space_wait(mut self, space_wait: SpaceWait) -> Self {
+        self.space_wait = space_wait;
+        self
+    }
+
+    /// Leave out files and directories matching any of `excludes` (see `glob::path_excluded`)
+    pub fn with_excludes(mut self, excludes: Vec<String>) -> Self {
+        self.excludes = excludes;
+        self
+    }
+
+    pub fn with_note(mut self, note: Option<String>) -> Self {
+        self.note = note;
+        self
+    }
+
+    /// Back up from a staged or mounted copy of another device's files: manifest paths
+    /// are taken relative to `base`, and the certificate describes `device`
+    pub fn with_source(mut self, base: &Path, device: crate::cert::CertificateDevice) -> Self {
+        self.source_base = Some(base.to_path_buf());
+        self.source_device = Some(device);
+        self
+    }
+
+    /// Block until the destination has room for `needed` bytes plus the reserve. When it
+    /// doesn't, emit `destination_full` and poll until space is freed, the backup is
+    /// cancelled, or `max_wait` runs out. `after_failure` waits even if statvfs reports
+    /// room, since a write just failed (quotas aren't visible to statvfs).
+    fn wait_for_space(
+        &self,
+        backup_dir: &Path,
+        file: &Path,
+        needed: u64,
+        after_failure: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let wanted = needed.saturating_add(self.space_wait.reserve_bytes);
+        let free = free_space(backup_dir);
+        if !after_failure && free.is_none_or(|free| free >= wanted) {
+            return Ok(());
+        }
+
+        let display = crate::locale::DisplayFormat::current();
+        let free_text = free.map_or("unknown".to_string(), |free| display.bytes_exact(free));
+        self.logger.log("warn", "destination_full",
+            &format!("Destination is full ({} free, {} needed for {:?}). Free up space to continue, \
+                      or cancel; a cancelled backup can be resumed later", free_text, display.bytes_exact(wanted), file),
+            Some(serde_json::json!({
+                "backup_dir": backup_dir.to_string_lossy(),
+                "file": file.to_string_lossy(),
+                "free_bytes": free,
+                "needed_bytes": wanted,
+                "options": ["free_space_to_resume", "cancel_to_abort"],
+            })));
+
+        let waiting_since = Instant::now();
+        let mut last_check = Instant::now();
+        loop {
+            if abort_requested() {
+                return Err("Backup cancelled while waiting for destination space".into());
+            }
+            if removal::device_removed() {
+                return Err("The destination was removed while waiting for space".into());
+            }
+            if let Some(max_wait) = self.space_wait.max_wait {
+                if waiting_since.elapsed() >= max_wait {
+                    return Err(format!("Destination still full after waiting {}s", max_wait.as_secs()).into());
+                }
+            }
+            thread::sleep(std::cmp::min(self.space_wait.poll, Duration::from_millis(250)));
+            if last_check.elapsed() < self.space_wait.poll {
+                continue;
+            }
+            last_check = Instant::now();
+
+            // Without statvfs there's nothing to poll; retry the write after one interval
+            let free = free_space(backup_dir);
+            if free.is_none_or(|free| free >= wanted) {
+                self.logger.log("info", "destination_space_available", "Destination has space again, continuing",
+                    Some(serde_json::json!({ "free_bytes": free, "waited_secs": waiting_since.elapsed().as_secs() })));
+                return Ok(());
+            }
+        }
+    }
+
+    /// Encrypt `files` into `backup_dir`, journalling each one. Files already in
+    /// `progress` (from a resumed journal) are skipped.
+    #[tracing::instrument(skip_all, fields(files = files.len()))]
+    fn copy_files(
+        &self,
+        files: &[PathBuf],
+        source_base: &Path,
+        backup_dir: &Path,
+        cipher: &mut Aes256Ctr,
+        journal: &mut Journal,
+        progress: &mut CopyProgress,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for file_path in files {
+            // Get relative path; the destination keeps the exact on-disk bytes
+            let rel = file_path.strip_prefix(source_base).unwrap_or(file_path);
+            let (rel_path, encoded) = encode_manifest_path(rel);
+            if progress.files.contains_key(&rel_path) {
+                continue;
+            }
+            if abort_requested() {
+                return Err("Backup cancelled".into());
+            }
+            if removal::device_removed() {
+                return Err("A device used by the backup was removed".into());
+            }
+
+            self.logger.log("info", "file_processing", &format!("Processing file: {:?}", file_path), None);
+            let dest_file = backup_dir.join(rel);
+            let length = fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+            self.wait_for_space(backup_dir, file_path, stored_size(file_path), false)?;
+
+            let mut attempt = 0;
+            loop {
+                // A retry continues the keystream rather than rewinding it, so no
+                // keystream byte ever encrypts two different plaintexts
+                let keystream_offset: u64 = cipher.current_pos();
+                journal.append(&JournalEntry::Begin { path: rel_path.clone(), keystream_offset, length })?;
+
+                match self.backup_single_file(file_path, &dest_file, cipher) {
+                    Ok(stored) => {
+                        journal.append(&JournalEntry::Stored {
+                            path: rel_path.clone(),
+                            original_hash: stored.original_hash.clone(),
+                            ciphertext_hash: stored.ciphertext_hash.clone(),
+                            file_bytes: stored.file_bytes,
+                            keystream_offset,
+                            keystream_end: cipher.current_pos(),
+                            sparse: stored.sparse.clone(),
+                            encoded: encoded.clone(),
+                        })?;
+                        if let Some(ref layout) = stored.sparse {
+                            self.logger.log("info", "sparse_file",
+                                &format!("Stored {:?} sparse: {} of {} bytes allocated", file_path, layout.data_bytes(), layout.logical_size), None);
+                        }
+                        progress.record(rel_path, encoded, stored, keystream_offset);
+                        break;
+                    }
+                    Err(e) => {
+                        // Don't leave a truncated ciphertext behind for a skipped file
+                        let _ = fs::remove_file(&dest_file);
+                        // With the disk gone every later file would fail the same way
+                        if removal::device_removed() || e.downcast_ref::<std::io::Error>().is_some_and(removal::is_removal) {
+                            return Err(e);
+                        }
+                        if remediation::classify(e.as_ref(), None) == Some(IoFailure::NoSpace) && attempt < OUT_OF_SPACE_RETRIES {
+                            attempt += 1;
+                            self.wait_for_space(backup_dir, file_path, stored_size(file_path), true)?;
+                            continue;
+                        }
+                        self.handle_file_error(file_path, e, &mut progress.skipped)?;
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuild progress from a journal, keeping only stored files whose ciphertext is
+    /// still intact. Also returns the first keystream byte no earlier attempt can have used.
+    fn replay_journal(&self, backup_dir: &Path, entries: &[JournalEntry]) -> (CopyProgress, u64) {
+        let mut progress = CopyProgress::default();
+        let mut keystream_next = 0u64;
+
+        for entry in entries {
+            match entry {
+                JournalEntry::Start(_) | JournalEntry::Interrupted { .. } => {}
+                JournalEntry::Begin { path, keystream_offset, length } => {
+                    // A partly written file may have used up to its length, or further
+                    // if it grew while being copied
+                    let written = decode_manifest_path(path)
+                        .and_then(|rel| fs::metadata(backup_dir.join(rel)).ok())
+                        .map_or(0, |m| m.len());
+                    keystream_next = keystream_next.max(keystream_offset + (*length).max(written));
+                }
+                JournalEntry::Stored {
+                    path, original_hash, ciphertext_hash, file_bytes, keystream_offset, keystream_end, sparse, encoded,
+                } => {
+                    keystream_next = keystream_next.max(*keystream_end);
+                    let intact = decode_manifest_path(path)
+                        .map(|rel| backup_dir.join(rel))
+                        .filter(|dest| fs::metadata(dest).is_ok_and(|m| m.len() == *file_bytes))
+                        .is_some_and(|dest| self.compute_file_hash(&dest).ok().as_ref() == Some(ciphertext_hash));
+                    if intact {
+                        let stored = StoredFile {
+                            original_hash: original_hash.clone(),
+                            ciphertext_hash: ciphertext_hash.clone(),
+                            file_bytes: *file_bytes,
+                            sparse: sparse.clone(),
+                        };
+                        progress.record(path.clone(), encoded.clone(), stored, *keystream_offset);
+                    } else {
+                        self.logger.log("warn", "resume_file_redo",
+                            &format!("{} is missing or damaged in the backup and will be copied again", path), None);
+                    }
+                }
+            }
+        }
+
+        (progress, keystream_next)
+    }
+
+    /// Log why a started backup stopped and say how to pick it up again
+    fn interrupted(&self, backup_dir: &Path, error: Box<dyn std::error::Error>, progress: &CopyProgress) -> Box<dyn std::error::Error> {
+        let resumable = backup_dir.join("key.json").exists();
+        let failure = remediation::classify(error.as_ref(), Some(backup_dir));
+        let mut data = remediation::fields(failure);
+        if failure == Some(IoFailure::DeviceDisconnected) {
+            // Best effort: the journal is on the destination, which may be the disk that went
+            let reason = removal::INTERRUPTED_DEVICE_REMOVED.to_string();
+            if let Ok(mut journal) = Journal::open(backup_dir) {
+                let _ = journal.append(&JournalEntry::Interrupted { reason: reason.clone(), at: Utc::now().to_rfc3339() });
+            }
+            data.insert("status".to_string(), reason.into());
+        }
+        data.insert("backup_dir".to_string(), backup_dir.to_string_lossy().into());
+        data.insert("reason".to_string(), error.to_string().into());
+        data.insert("files_stored".to_string(), progress.files.len().into());
+        data.insert("resumable".to_string(), resumable.into());
+        self.logger.log("error", "backup_aborted",
+            &format!("Backup stopped after {} files: {}", progress.files.len(), error), Some(data.into()));
+        if resumable {
+            format!("{}. {} files are stored; continue with `securewipe backup --resume {}`",
+                error, progress.files.len(), backup_dir.display()).into()
+        } else {
+            format!("{}. This backup has no passphrase-derived key, so it cannot be resumed; \
+                     {} was removed, start a new backup", error, backup_dir.display()).into()
+        }
+    }
+
+    /// Write the manifest, verify samples and issue the certificate for a finished copy
+    #[tracing::instrument(skip_all)]
+    fn complete_backup(
+        &self,
+        run: &BackupRun,
+        backup_dir: &Path,
+        source_base: &Path,
+        progress: CopyProgress,
+        started: MonotonicStart,
+        canary: DestinationCanary,
+    ) -> Result<BackupResult, Box<dyn std::error::Error>> {
+        let total_bytes = progress.total_bytes;
+        self.logger.log("info", "encryption_complete",
+            &format!("Encrypted {} files, {} total, {} skipped", progress.files.len(),
+                     crate::locale::DisplayFormat::current().bytes_exact(total_bytes), progress.skipped.len()),
+            Some(serde_json::json!({ "total_bytes": total_bytes })));
+        
+        // Create manifest
+        let mut manifest = BackupManifest {
+            total_files: progress.files.len(),
+            files: progress.files,
+            created_at: Utc::now().to_rfc3339(),
+            total_bytes,
+            manifest_sha256: String::new(),
+            skipped_files: progress.skipped,
+            sparse_files: progress.sparse,
+            encoded_paths: progress.encoded,
+            ciphertext_sha256: progress.ciphertext_sha256,
+            keystream_offsets: progress.keystream_offsets,
+        };
+        
+        manifest.manifest_sha256 = self.compute_manifest_hash(&manifest);
+        
+        // Save manifest
+        let manifest_path = backup_dir.join("manifest.json");
+        let manifest_json = serde_json::to_string_pretty(&manifest)?;
+        write_synced(&manifest_path, manifest_json.as_bytes())?;
+        let _ = fs::remove_file(backup_dir.join(JOURNAL_FILE));
+        
+        self.logger.log("info", "manifest_created", "Manifest created and saved", None);
+        
+        // Verify random files
+        self.logger.log("info", "verification_start", "Starting post-copy verification", None);
+        let (samples, verified) = self.verify_random_files(&manifest, backup_dir, source_base, 5)?;
+        let verification_passed = samples == verified;
+        
+        self.logger.log(
+            if verification_passed { "info" } else { "error" },
+            "verification_complete",
+            &format!("Verified {}/{} samples", verified, samples),
+            Some(serde_json::json!({
+                "samples_total": samples,
+                "samples_verified": verified,
+                "passed": verification_passed
+            }))
+        );
+        
+        let result = BackupResult {
+            manifest,
+            destination: run.destination.clone(),
+            encryption_method: run.encryption_method.clone(),
+            verification_samples: samples,
+            verification_passed,
+            backup_id: run.backup_id.clone(),
+            timing: Some(started.finish()),
+            destination_canary: Some(canary),
+        };
+
+        // Add artificial delay for small backups (< 1MB) to allow UI to properly show progress
+        if total_bytes < 50_000_000 {
+            self.logger.log("info", "small_backup_delay", 
+                &format!("Small backup detected ({} bytes), adding UI synchronization delay", total_bytes), None);
+            std::thread::sleep(std::time::Duration::from_secs(3));
+        }
+
+        // Create and save certificate
+        let mut certificate = self.create_backup_certificate(&run.device, &result, &run.source_paths, &run.excludes)?;
+        crate::annotations::set_operator_note(&mut certificate, run.note.as_deref());
+        if let Some(cost) = certificate.pointer("/metadata/cost") {
+            self.logger.log("info", "cost_summary", "Backup cost summary", Some(cost.clone()));
+        }
+        let serial = crate::serial::assign_serial(&mut certificate)?;
+        self.logger.log("info", "certificate_serial", &format!("Certificate number: {}", serial), None);
+        
+        // Automatically sign the certificate if signing key is available
+        match self.try_sign_certificate(&mut certificate) {
+            Ok(_) => {
+                self.logger.log("info", "certificate_signed", "Certificate automatically signed", None);
+            }
+            Err(e) => {
+                self.logger.log("warn", "certificate_signing_failed", 
+                    &format!("Certificate created but not signed: {}", e), None);
+            }
+        }
+        
+        let cert_path = self.save_certificate(&certificate)?;
+
+        self.logger.log("info", "certificate_created", &format!("Certificate saved to: {:?}", cert_path), None);
+
+        let metadata = BackupMetadata {
+            backup_id: run.backup_id.clone(),
+            created_at: result.manifest.created_at.clone(),
+            tool: "securewipe".to_string(),
+            tool_version: format!("v{}", env!("CARGO_PKG_VERSION")),
+            device: run.device.clone(),
+            source_paths: run.source_paths.clone(),
+            encryption_method: run.encryption_method.clone(),
+            total_files: result.manifest.total_files,
+            total_bytes: result.manifest.total_bytes,
+            manifest_sha256: result.manifest.manifest_sha256.clone(),
+            cert_id: certificate.get("cert_id").and_then(|v| v.as_str()).unwrap_or(&run.backup_id).to_string(),
+            certificate_serial: Some(serial),
+            restorable: backup_dir.join("key.json").exists(),
+        };
+        if let Err(e) = write_backup_info(backup_dir, &metadata) {
+            self.logger.log("warn", "backup_info_failed",
+                &format!("Could not write {} and {}: {}", README_FILE, METADATA_FILE, e), None);
+        }
+        sync_dir(backup_dir);
+
+        // Only now does the backup appear under its id
+        let final_dir = finalize_backup_dir(backup_dir)?;
+        self.logger.log("info", "backup_dir_finalized", &format!("Backup stored in {:?}", final_dir),
+            Some(serde_json::json!({ "backup_dir": final_dir.to_string_lossy() })));
+        self.logger.log("info", "backup_complete", "Backup operation completed successfully", None);
+
+        Ok(result)
+    }
+
+    /// Write and read back a canary file in `dir`, log the result and refuse to go on
+    /// when it fails, before any time goes into copying
+    #[tracing::instrument(skip_all)]
+    fn check_destination(&self, dir: &Path) -> Result<DestinationCanary, Box<dyn std::error::Error>> {
+        let canary = write_canary(dir);
+        self.logger.log(if canary.passed { "info" } else { "error" }, "destination_canary",
+            &match canary.error {
+                Some(ref error) => format!("Destination failed its write check: {}", error),
+                None => format!("Destination write check passed ({} bytes{})", canary.bytes,
+                    if canary.direct_io { ", direct I/O" } else { "" }),
+            },
+            Some(serde_json::to_value(&canary)?));
+        if !canary.passed {
+            return Err(format!("The destination {} failed a write check ({}); it may be read-only or failing",
+                dir.display(), canary.error.as_deref().unwrap_or("unknown error")).into());
+        }
+        Ok(canary)
+    }
+
+    /// Walk the sources as `perform_backup` would and report what it would copy, without
+    /// writing anything. With a destination, also check every file against its filesystem.
+    pub fn preview_backup(&self, paths: &[String], destination: Option<&str>) -> Result<BackupPreview, Box<dyn std::error::Error>> {
+        let source_paths = if paths.is_empty() { Self::get_default_paths() } else { paths.to_vec() };
+        let walk = walk_sources(&source_paths, &self.excludes);
+        let source_base = match self.source_base {
+            Some(ref base) => base.clone(),
+            None => Path::new(&source_paths[0]).parent().unwrap_or(Path::new("/")).to_path_buf(),
+        };
+        let destination = match destination {
+            Some(destination) => Some(
+                shellexpand::full(destination)
+                    .map_err(|e| format!("Failed to expand destination path '{}': {}", destination, e))?
+                    .to_string(),
+            ),
+            None => None,
+        };
+        let dest_fs = destination.as_deref().map(|dest| DestinationFs::detect(Path::new(dest)));
+
+        let mut files = Vec::with_capacity(walk.files.len());
+        let mut incompatible = Vec::new();
+        for file_path in &walk.files {
+            let rel = file_path.strip_prefix(&source_base).unwrap_or(file_path);
+            let size = fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+            let problem = match dest_fs {
+                Some(_) if rel.to_str().is_some_and(|rel| RESERVED_ROOT_FILES.contains(&rel)) => {
+                    Some("name is reserved for the backup's own files at its root".to_string())
+                }
+                Some(dest_fs) => dest_fs.check_entry(rel, size),
+                None => None,
+            };
+            match problem {
+                Some(reason) => incompatible.push(SkippedFile { path: file_path.display().to_string(), reason }),
+                None => files.push(PreviewFile { path: encode_manifest_path(rel).0, size }),
+            }
+        }
+
+        let mut largest_files = files.clone();
+        largest_files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        largest_files.truncate(PREVIEW_LARGEST_FILES);
+
+        Ok(BackupPreview {
+            total_files: files.len(),
+            total_bytes: files.iter().map(|file| file.size).sum(),
+            excluded_files: walk.excluded.len(),
+            excluded_bytes: walk.excluded.iter().map(|path| tree_size(path)).sum(),
+            unreadable: walk
+                .unreadable
+                .iter()
+                .map(|(path, error)| SkippedFile { path: path.display().to_string(), reason: error.to_string() })
+                .collect(),
+            incompatible,
+            destination_filesystem: dest_fs.map(|dest_fs| dest_fs.name().to_string()),
+            destination_free_bytes: destination.as_deref().and_then(|dest| free_space(Path::new(dest))),
+            destination,
+            largest_files,
+            files,
+            source_paths,
+            excludes: self.excludes.clone(),
+        })
+    }
+
+    /// Finish a backup that stopped part-way (cancelled, destination full, crashed),
+    /// from the `progress.jsonl` journal in `backup_dir`. Only passphrase-keyed backups
+    /// can be resumed; without the passphrase their key is gone.
+    pub fn resume_backup(&self, backup_dir: &Path) -> Result<BackupResult, Box<dyn std::error::Error>> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let started = MonotonicStart::now();
+        let backup_dir = &in_progress_dir(backup_dir);
+        if backup_dir.join("manifest.json").exists() {
+            return Err(format!("{} is already a complete backup", backup_dir.display()).into());
+        }
+        let _lock = DirLock::try_acquire(backup_dir)?
+            .ok_or_else(|| format!("{} is being written by another backup", backup_dir.display()))?;
+        let canary = self.check_destination(backup_dir)?;
+        let entries = Journal::read(backup_dir)?;
+        let run = match entries.first() {
+            Some(JournalEntry::Start(run)) => run.clone(),
+            _ => return Err(format!("{} does not start with the backup's parameters", JOURNAL_FILE).into()),
+        };
+        // Its files were staged or mounted in a temporary directory that is gone now
+        if run.device.starts_with("adb:") || run.device.starts_with("mtp:") {
+            return Err("Phone backups can't be resumed; start a new backup from the phone".into());
+        }
+
+        let params_json = fs::read_to_string(backup_dir.join("key.json")).map_err(|_| {
+            "This backup was made without a passphrase, so its key was not kept and it cannot be resumed"
+        })?;
+        let params: BackupKeyParams = serde_json::from_str(&params_json)?;
+        let passphrase = self.passphrase.as_ref()
+            .ok_or("Resuming this backup needs the backup passphrase it was started with")?;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BackupInfo {
-    pub source_paths: Vec<String>,
-    pub destination: String,
-    pub encryption_method: String,
-    pub compression_method: String,
-    pub manifest: BackupManifest,
-}
+        let mut data_key = DataKey { key: [0u8; 32], iv: [0u8; 16] };
+        data_key.key.copy_from_slice(params.derive_key(passphrase.expose())?.as_ref());
+        let iv = STANDARD.decode(&params.iv)?;
+        if iv.len() != data_key.iv.len() {
+            return Err("key.json has an invalid IV".into());
+        }
+        data_key.iv.copy_from_slice(&iv);
+        if run.key_check.as_deref() != Some(key_check(&data_key.key).as_str()) {
+            return Err("The backup passphrase does not match the one this backup was started with".into());
+        }
+        let mut cipher = data_key.cipher();
+        drop(data_key);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VerificationInfo {
-    pub samples_verified: usize,
-    pub samples_passed: usize,
-    pub verification_method: String,
-    pub passed: bool,
-}
+        let (mut progress, keystream_next) = self.replay_journal(backup_dir, &entries);
+        cipher.seek(keystream_next);
+        self.logger.log("info", "backup_resume",
+            &format!("Resuming backup {}: {} files already stored", run.backup_id, progress.files.len()),
+            Some(serde_json::json!({
+                "backup_id": run.backup_id,
+                "backup_dir": backup_dir.to_string_lossy(),
+                "files_stored": progress.files.len(),
+            })));
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SignatureInfo {
-    pub alg: String,
-    pub pubkey_id: String,
-    pub sig: String,
-}
+        let files = self.collect_files(&run.source_paths, &run.excludes, &mut progress.skipped)?;
+        let source_base = Path::new(&run.source_paths[0]).parent().unwrap_or(Path::new("/"));
+        let dest_fs = DestinationFs::detect(backup_dir);
+        let files = self.preflight_destination(dest_fs, &files, source_base, &mut progress.skipped)?;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BackupLog {
-    pub timestamp: String,
-    pub level: String,
-    pub step_id: String,
-    pub message: String,
-    pub data: Option<serde_json::Value>,
-}
+        let mut journal = Journal::open(backup_dir)?;
+        if let Err(e) = self.copy_files(&files, source_base, backup_dir, &mut cipher, &mut journal, &mut progress) {
+            return Err(self.interrupted(backup_dir, e, &progress));
+        }
+        self.complete_backup(&run, backup_dir, source_base, progress, started, canary)
+    }
 
-pub trait BackupOperations {
-    fn perform_backup(
+    /// Record a per-file failure, or propagate it when the policy is `Abort`
+    fn handle_file_error(
         &self,
-        device: &str,
-        paths: &[String],
-        destination: &str,
-    ) -> Result<BackupResult, Box<dyn std::error::Error>>;
-}
+        path: &Path,
+        error: Box<dyn std::error::Error>,
+        skipped: &mut Vec<SkippedFile>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.on_error {
+            BackupErrorPolicy::Abort => Err(format!("{}: {}", path.display(), error).into()),
+            BackupErrorPolicy::Skip => {
+                let mut data = remediation::fields(remediation::classify(error.as_ref(), None));
+                data.insert("path".to_string(), path.to_string_lossy().into());
+                data.insert("reason".to_string(), error.to_string().into());
+                self.logger.log("warn", "file_skipped",
+                    &format!("Skipping {:?}: {}", path, error), Some(data.into()));
+                skipped.push(SkippedFile {
+                    path: path.to_string_lossy().to_string(),
+                    reason: error.to_string(),
+                });
+                Ok(())
+            }
+        }
+    }
 
-pub struct EncryptedBackup {
-    pub logger: Box<dyn BackupLogger>,
-}
+    /// Reject (or skip, per policy) files the destination filesystem cannot store,
+    /// before anything is written
+    #[tracing::instrument(skip_all)]
+    fn preflight_destination(
+        &self,
+        dest_fs: DestinationFs,
+        files: &[PathBuf],
+        source_base: &Path,
+        skipped: &mut Vec<SkippedFile>,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let mut accepted = Vec::with_capacity(files.len());
+        let mut problems = Vec::new();
 
-pub trait BackupLogger {
-    fn log(&self, level: &str, step_id: &str, message: &str, data: Option<serde_json::Value>);
-}
+        for file_path in files {
+            let rel = file_path.strip_prefix(source_base).unwrap_or(file_path);
+            if rel.to_str().is_some_and(|rel| RESERVED_ROOT_FILES.contains(&rel)) {
+                skipped.push(SkippedFile {
+                    path: file_path.display().to_string(),
+                    reason: "name is reserved for the backup's own files at its root".to_string(),
+                });
+                continue;
+            }
+            let size = fs::metadata(file_path).map(|m| m.len()).unwrap_or(0);
+            match dest_fs.check_entry(rel, size) {
+                Some(problem) => problems.push((file_path.clone(), problem)),
+                None => accepted.push(file_path.clone()),
+            }
+        }
 
-pub struct JsonLogger;
+        if problems.is_empty() {
+            return Ok(accepted);
+        }
 
-impl BackupLogger for JsonLogger {
-    fn log(&self, level: &str, step_id: &str, message: &str, data: Option<serde_json::Value>) {
-        let log_entry = BackupLog {
-            timestamp: Utc::now().to_rfc3339(),
-            level: level.to_string(),
-            step_id: step_id.to_string(),
-            message: message.to_string(),
-            data,
-        };
-        eprintln!("{}", serde_json::to_string(&log_entry).unwrap_or_default());
-    }
-}
+        self.logger.log("warn", "destination_preflight",
+            &format!("{} file(s) incompatible with {} destination", problems.len(), dest_fs.name()),
+            Some(serde_json::json!({
+                "filesystem": dest_fs.name(),
+                "incompatible": problems.iter()
+                    .map(|(path, problem)| format!("{}: {}", path.display(), problem))
+                    .collect::<Vec<_>>(),
+            })));
 
-impl EncryptedBackup {
-    pub fn new() -> Self {
-        Self {
-            logger: Box::new(JsonLogger),
+        if self.on_error == BackupErrorPolicy::Abort {
+            let listed: Vec<String> = problems.iter().take(5)
+                .map(|(path, problem)| format!("  {}: {}", path.display(), problem))
+                .collect();
+            let more = if problems.len() > 5 {
+                format!("\n  ... and {} more", problems.len() - 5)
+            } else {
+                String::new()
+            };
+            return Err(format!(
+                "Destination filesystem ({}) cannot store {} file(s):\n{}{}\n\
+                 Choose a Linux-native destination such as ext4, rename the files, or rerun with --on-error skip",
+                dest_fs.name(), problems.len(), listed.join("\n"), more).into());
         }
+
+        for (path, problem) in problems {
+            let reason = format!("incompatible with {} destination: {}", dest_fs.name(), problem);
+            self.handle_file_error(&path, reason.into(), skipped)?;
+        }
+        Ok(accepted)
     }
 
     fn get_default_paths() -> Vec<String> {
@@ -137,57 +1698,21 @@ impl EncryptedBackup {
         ]
     }
 
-    fn get_device_info(&self, device_path: &str) -> Option<Device> {
-        let discovery = LinuxDeviceDiscovery::new();
-        match discovery.discover_devices() {
-            Ok(devices) => {
-                devices.into_iter().find(|device| {
-                    // device.name is already in format "/dev/nvme0n1"
-                    device.name == device_path ||
-                    // Handle case where device_path might be just "nvme0n1" 
-                    device.name == format!("/dev/{}", device_path) ||
-                    // Handle case where device_path has /dev/ but device.name doesn't
-                    format!("/dev/{}", device.name.trim_start_matches("/dev/")) == device_path
-                })
-            }
-            Err(e) => {
-                self.logger.log("warn", "device_discovery_failed", 
-                    &format!("Failed to discover device info for {}: {}", device_path, e), None);
-                None
-            }
-        }
-    }
-
-    fn collect_files(&self, paths: &[String]) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
-        let mut files = Vec::new();
-        
-        for path_str in paths {
-            let path = Path::new(path_str);
-            if path.is_file() {
-                files.push(path.to_path_buf());
-            } else if path.is_dir() {
-                self.collect_files_recursive(path, &mut files)?;
-            }
-        }
-        
-        Ok(files)
-    }
-
-    fn collect_files_recursive(&self, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.is_file() {
-                files.push(path);
-            } else if path.is_dir() {
-                self.collect_files_recursive(&path, files)?;
-            }
+    #[tracing::instrument(skip_all)]
+    fn collect_files(
+        &self,
+        paths: &[String],
+        excludes: &[String],
+        skipped: &mut Vec<SkippedFile>,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let walk = walk_sources(paths, excludes);
+        for (path, error) in walk.unreadable {
+            self.handle_file_error(&path, error.into(), skipped)?;
         }
-        Ok(())
+        Ok(walk.files)
     }
 
-    fn compute_file_hash(&self, file_path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    pub(crate) fn compute_file_hash(&self, file_path: &Path) -> Result<String, Box<dyn std::error::Error>> {
         let mut file = File::open(file_path)?;
         let mut hasher = Sha256::new();
         let mut buffer = [0u8; 8192];
@@ -203,11 +1728,12 @@ impl EncryptedBackup {
         Ok(format!("{:x}", hasher.finalize()))
     }
 
-    fn encrypt_and_compress_file(
+    pub(crate) fn encrypt_and_compress_file(
         &self,
         source: &Path,
         dest: &Path,
         cipher: &mut Aes256Ctr,
+        hasher: &mut Sha256,
     ) -> Result<u64, Box<dyn std::error::Error>> {
         let mut source_file = File::open(source)?;
         let mut dest_file = File::create(dest)?;
@@ -225,12 +1751,115 @@ impl EncryptedBackup {
             cipher.apply_keystream(&mut buffer[..bytes_read]);
             
             dest_file.write_all(&buffer[..bytes_read])?;
+            hasher.update(&buffer[..bytes_read]);
             total_bytes += bytes_read as u64;
         }
         
         Ok(total_bytes)
     }
 
+    /// Encrypt only the data extents of a sparse file, writing each at its original
+    /// offset so the destination keeps the same holes
+    fn encrypt_sparse_file(
+        &self,
+        source: &Path,
+        dest: &Path,
+        cipher: &mut Aes256Ctr,
+        sparse: &SparseExtents,
+        hasher: &mut Sha256,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut source_file = File::open(source)?;
+        let mut dest_file = File::create(dest)?;
+        let mut buffer = [0u8; 8192];
+        // Holes read back as zeros, so they are hashed as zeros
+        let mut hashed_to = 0u64;
+
+        for &(offset, length) in &sparse.extents {
+            source_file.seek(SeekFrom::Start(offset))?;
+            dest_file.seek(SeekFrom::Start(offset))?;
+            hash_zeros(hasher, offset.saturating_sub(hashed_to));
+            hashed_to = hashed_to.max(offset);
+
+            let mut remaining = length;
+            while remaining > 0 {
+                let want = std::cmp::min(remaining, buffer.len() as u64) as usize;
+                let bytes_read = source_file.read(&mut buffer[..want])?;
+                if bytes_read == 0 {
+                    break; // File shrank while we were reading
+                }
+                cipher.apply_keystream(&mut buffer[..bytes_read]);
+                dest_file.write_all(&buffer[..bytes_read])?;
+                hasher.update(&buffer[..bytes_read]);
+                hashed_to += bytes_read as u64;
+                remaining -= bytes_read as u64;
+            }
+        }
+
+        // Trailing hole: extend to the logical size without allocating blocks
+        dest_file.set_len(sparse.logical_size)?;
+        hash_zeros(hasher, sparse.logical_size.saturating_sub(hashed_to));
+        Ok(sparse.logical_size)
+    }
+
+    /// Return the data extents of `path` if it occupies fewer blocks than its length
+    fn detect_sparse(&self, path: &Path) -> Option<SparseExtents> {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let metadata = fs::metadata(path).ok()?;
+            if metadata.blocks() * 512 >= metadata.len() {
+                return None;
+            }
+
+            let file = File::open(path).ok()?;
+            match find_data_extents(&file, metadata.len()) {
+                Ok(extents) => Some(SparseExtents {
+                    logical_size: metadata.len(),
+                    extents,
+                }),
+                Err(e) => {
+                    self.logger.log("debug", "sparse_probe_failed",
+                        &format!("SEEK_DATA unsupported for {:?}: {}", path, e), None);
+                    None
+                }
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = path;
+            None
+        }
+    }
+
+    /// Hash and encrypt one source file
+    fn backup_single_file(
+        &self,
+        source: &Path,
+        dest: &Path,
+        cipher: &mut Aes256Ctr,
+    ) -> Result<StoredFile, Box<dyn std::error::Error>> {
+        let original_hash = self.compute_file_hash(source)?;
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let sparse = self.detect_sparse(source);
+        let mut hasher = Sha256::new();
+        let file_bytes = match sparse {
+            Some(ref extents) => self.encrypt_sparse_file(source, dest, cipher, extents, &mut hasher)?,
+            None => self.encrypt_and_compress_file(source, dest, cipher, &mut hasher)?,
+        };
+        Ok(StoredFile {
+            original_hash,
+            ciphertext_hash: format!("{:x}", hasher.finalize()),
+            file_bytes,
+            sparse,
+        })
+    }
+
+    #[tracing::instrument(name = "verify_samples", skip_all)]
     fn verify_random_files(
         &self,
         manifest: &BackupManifest,
@@ -250,7 +1879,10 @@ impl EncryptedBackup {
         for _ in 0..samples {
             let idx = (rng.next_u32() as usize) % files.len();
             let rel_path = files[idx];
-            let original_path = source_base.join(rel_path);
+            let original_path = match decode_manifest_path(rel_path) {
+                Some(path) => source_base.join(path),
+                None => continue,
+            };
             
             if original_path.exists() {
                 let computed_hash = self.compute_file_hash(&original_path)?;
@@ -263,7 +1895,295 @@ impl EncryptedBackup {
         Ok((samples, verified))
     }
 
-    fn compute_manifest_hash(&self, manifest: &BackupManifest) -> String {
+    /// Re-check a stored backup: the manifest's own hash, then every listed file's
+    /// presence and ciphertext hash
+    #[tracing::instrument(skip_all, fields(backup_dir = %backup_dir.display()))]
+    pub fn verify_backup(&self, backup_dir: &Path) -> Result<BackupVerificationReport, Box<dyn std::error::Error>> {
+        let manifest_path = backup_dir.join("manifest.json");
+        let manifest_json = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+        let manifest: BackupManifest = serde_json::from_str(&manifest_json)
+            .map_err(|e| format!("Invalid manifest {}: {}", manifest_path.display(), e))?;
+
+        self.logger.log("info", "backup_verify_start",
+            &format!("Verifying {} files in {:?}", manifest.files.len(), backup_dir), None);
+
+        let mut failures = Vec::new();
+        let manifest_valid = self.compute_manifest_hash(&manifest) == manifest.manifest_sha256;
+        if !manifest_valid {
+            failures.push(VerificationFailure {
+                path: "manifest.json".to_string(),
+                reason: "manifest hash mismatch; the manifest was modified after the backup".to_string(),
+            });
+        }
+
+        let mut keys: Vec<_> = manifest.files.keys().collect();
+        keys.sort();
+        let mut files_passed = 0;
+        let mut presence_only = 0;
+
+        for key in &keys {
+            let display = manifest.encoded_paths.get(*key)
+                .map_or_else(|| key.to_string(), |encoded| encoded.display.clone());
+            let mut fail = |reason: String| failures.push(VerificationFailure { path: display.clone(), reason });
+
+            let stored = match decode_manifest_path(key) {
+                Some(rel) => backup_dir.join(rel),
+                None => {
+                    fail("manifest path is corrupt or not a plain relative path".to_string());
+                    continue;
+                }
+            };
+            if !stored.is_file() {
+                fail("missing from backup".to_string());
+                continue;
+            }
+
+            match manifest.ciphertext_sha256.get(*key) {
+                Some(expected) => match self.compute_file_hash(&stored) {
+                    Ok(actual) if &actual == expected => files_passed += 1,
+                    Ok(_) => fail("stored content hash mismatch".to_string()),
+                    Err(e) => fail(format!("unreadable: {}", e)),
+                },
+                None => {
+                    presence_only += 1;
+                    files_passed += 1;
+                }
+            }
+        }
+
+        let report = BackupVerificationReport {
+            backup_dir: backup_dir.display().to_string(),
+            manifest_valid,
+            files_checked: keys.len(),
+            files_passed,
+            presence_only,
+            passed: failures.is_empty(),
+            failures,
+        };
+
+        self.logger.log(
+            if report.passed { "info" } else { "error" },
+            "backup_verify_complete",
+            &format!("Verified {}/{} files", report.files_passed, report.files_checked),
+            Some(serde_json::json!({
+                "manifest_valid": report.manifest_valid,
+                "failures": report.failures.len(),
+                "presence_only": report.presence_only,
+                "passed": report.passed
+            }))
+        );
+
+        Ok(report)
+    }
+
+    /// Decrypt every file of a passphrase-protected backup into `target_dir`, which
+    /// must not already hold them. Sparse files are restored with their holes: the
+    /// file is sized with `set_len` and only the data extents are written.
+    pub fn restore_backup(&self, backup_dir: &Path, target_dir: &Path) -> Result<RestoreReport, Box<dyn std::error::Error>> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let manifest_path = backup_dir.join("manifest.json");
+        let manifest_json = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+        let manifest: BackupManifest = serde_json::from_str(&manifest_json)
+            .map_err(|e| format!("Invalid manifest {}: {}", manifest_path.display(), e))?;
+        let params_json = fs::read_to_string(backup_dir.join("key.json"))
+            .map_err(|_| "This backup was made without a passphrase, so its key was not kept and it cannot be restored")?;
+        let params: BackupKeyParams = serde_json::from_str(&params_json)?;
+        let passphrase = self.passphrase.as_ref()
+            .ok_or("Restoring this backup needs the backup passphrase it was made with")?;
+
+        let mut data_key = DataKey { key: [0u8; 32], iv: [0u8; 16] };
+        data_key.key.copy_from_slice(params.derive_key(passphrase.expose())?.as_ref());
+        let iv = STANDARD.decode(&params.iv)?;
+        if iv.len() != data_key.iv.len() {
+            return Err("key.json has an invalid IV".into());
+        }
+        data_key.iv.copy_from_slice(&iv);
+        let mut cipher = data_key.cipher();
+        drop(data_key);
+
+        self.logger.log("info", "restore_start",
+            &format!("Restoring {} files from {:?} to {:?}", manifest.files.len(), backup_dir, target_dir), None);
+
+        let mut report = RestoreReport {
+            backup_dir: backup_dir.display().to_string(),
+            target_dir: target_dir.display().to_string(),
+            files_restored: 0,
+            logical_bytes: 0,
+            physical_bytes: 0,
+            sparse_files: 0,
+            failures: Vec::new(),
+            passed: false,
+        };
+        if self.compute_manifest_hash(&manifest) != manifest.manifest_sha256 {
+            report.failures.push(VerificationFailure {
+                path: "manifest.json".to_string(),
+                reason: "manifest hash mismatch; the manifest was modified after the backup".to_string(),
+            });
+        }
+
+        let mut keys: Vec<_> = manifest.files.keys().collect();
+        keys.sort();
+        for (index, key) in keys.iter().enumerate() {
+            if abort_requested() {
+                return Err("Restore cancelled".into());
+            }
+            let display = manifest.encoded_paths.get(*key)
+                .map_or_else(|| key.to_string(), |encoded| encoded.display.clone());
+            let (rel, keystream_offset) = match (decode_manifest_path(key), manifest.keystream_offsets.get(*key)) {
+                (Some(rel), Some(offset)) => (rel, *offset),
+                (None, _) => {
+                    report.failures.push(VerificationFailure { path: display, reason: "manifest path is corrupt or not a plain relative path".to_string() });
+                    continue;
+                }
+                (_, None) => {
+                    report.failures.push(VerificationFailure {
+                        path: display,
+                        reason: "no keystream offset recorded; the backup predates restorable manifests".to_string(),
+                    });
+                    continue;
+                }
+            };
+            let dest = target_dir.join(&rel);
+            cipher.seek(keystream_offset);
+            let sparse = manifest.sparse_files.get(*key);
+            let mut hasher = Sha256::new();
+            let restored = match dest.parent().map_or(Ok(()), fs::create_dir_all) {
+                Err(e) => Err(e.into()),
+                Ok(()) => match sparse {
+                    Some(layout) => self.decrypt_sparse_file(&backup_dir.join(&rel), &dest, &mut cipher, layout, &mut hasher),
+                    None => self.decrypt_file(&backup_dir.join(&rel), &dest, &mut cipher, &mut hasher),
+                },
+            };
+            let (logical, physical) = match restored {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    // Never remove a file that was already there
+                    if e.downcast_ref::<std::io::Error>().map(std::io::Error::kind) != Some(std::io::ErrorKind::AlreadyExists) {
+                        let _ = fs::remove_file(&dest);
+                    }
+                    report.failures.push(VerificationFailure { path: display, reason: format!("restore failed: {}", e) });
+                    continue;
+                }
+            };
+            if format!("{:x}", hasher.finalize()) != manifest.files[*key] {
+                let _ = fs::remove_file(&dest);
+                report.failures.push(VerificationFailure {
+                    path: display,
+                    reason: "restored content hash mismatch; wrong passphrase or damaged backup".to_string(),
+                });
+                continue;
+            }
+
+            report.files_restored += 1;
+            report.logical_bytes += logical;
+            report.physical_bytes += physical;
+            report.sparse_files += usize::from(sparse.is_some());
+            self.logger.log("info", "restore_progress",
+                &format!("Restored {}", display),
+                Some(serde_json::json!({
+                    "path": display,
+                    "files_done": index + 1,
+                    "files_total": keys.len(),
+                    "logical_bytes": logical,
+                    "physical_bytes": physical,
+                    "total_logical_bytes": report.logical_bytes,
+                    "total_physical_bytes": report.physical_bytes,
+                })));
+        }
+
+        report.passed = report.failures.is_empty();
+        self.logger.log(
+            if report.passed { "info" } else { "error" },
+            "restore_complete",
+            &format!("Restored {}/{} files", report.files_restored, keys.len()),
+            Some(serde_json::json!({
+                "logical_bytes": report.logical_bytes,
+                "physical_bytes": report.physical_bytes,
+                "sparse_files": report.sparse_files,
+                "failures": report.failures.len(),
+                "passed": report.passed
+            }))
+        );
+
+        Ok(report)
+    }
+
+    /// Decrypt a stored file into a new file at `dest`, hashing the plaintext.
+    /// Returns its logical and written byte counts, which are equal here.
+    fn decrypt_file(
+        &self,
+        stored: &Path,
+        dest: &Path,
+        cipher: &mut Aes256Ctr,
+        hasher: &mut Sha256,
+    ) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let mut stored_file = File::open(stored)?;
+        let mut dest_file = OpenOptions::new().write(true).create_new(true).open(dest)?;
+        let mut buffer = [0u8; 8192];
+        let mut total_bytes = 0u64;
+
+        loop {
+            let bytes_read = stored_file.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            cipher.apply_keystream(&mut buffer[..bytes_read]);
+            dest_file.write_all(&buffer[..bytes_read])?;
+            hasher.update(&buffer[..bytes_read]);
+            total_bytes += bytes_read as u64;
+        }
+
+        Ok((total_bytes, total_bytes))
+    }
+
+    /// Inverse of `encrypt_sparse_file`: size `dest` to the logical length, then
+    /// decrypt each data extent in place so the holes stay unallocated. Returns the
+    /// logical length and the data bytes written.
+    fn decrypt_sparse_file(
+        &self,
+        stored: &Path,
+        dest: &Path,
+        cipher: &mut Aes256Ctr,
+        sparse: &SparseExtents,
+        hasher: &mut Sha256,
+    ) -> Result<(u64, u64), Box<dyn std::error::Error>> {
+        let mut stored_file = File::open(stored)?;
+        let mut dest_file = OpenOptions::new().write(true).create_new(true).open(dest)?;
+        dest_file.set_len(sparse.logical_size)?;
+        let mut buffer = [0u8; 8192];
+        let mut hashed_to = 0u64;
+        let mut written = 0u64;
+
+        for &(offset, length) in &sparse.extents {
+            stored_file.seek(SeekFrom::Start(offset))?;
+            dest_file.seek(SeekFrom::Start(offset))?;
+            hash_zeros(hasher, offset.saturating_sub(hashed_to));
+            hashed_to = hashed_to.max(offset);
+
+            let mut remaining = length;
+            while remaining > 0 {
+                let want = std::cmp::min(remaining, buffer.len() as u64) as usize;
+                let bytes_read = stored_file.read(&mut buffer[..want])?;
+                if bytes_read == 0 {
+                    return Err(format!("stored file ends inside the extent at offset {}", offset).into());
+                }
+                cipher.apply_keystream(&mut buffer[..bytes_read]);
+                dest_file.write_all(&buffer[..bytes_read])?;
+                hasher.update(&buffer[..bytes_read]);
+                hashed_to += bytes_read as u64;
+                written += bytes_read as u64;
+                remaining -= bytes_read as u64;
+            }
+        }
+
+        hash_zeros(hasher, sparse.logical_size.saturating_sub(hashed_to));
+        Ok((sparse.logical_size, written))
+    }
+
+    pub(crate) fn compute_manifest_hash(&self, manifest: &BackupManifest) -> String {
         // Create a deterministic string representation for hashing
         let mut entries: Vec<_> = manifest.files.iter().collect();
         entries.sort_by_key(|(k, _)| *k);
@@ -274,8 +2194,36 @@ impl EncryptedBackup {
             hasher.update(hash.as_bytes());
         }
         hasher.update(manifest.created_at.as_bytes());
-        hasher.update(&manifest.total_files.to_le_bytes());
-        hasher.update(&manifest.total_bytes.to_le_bytes());
+        hasher.update(manifest.total_files.to_le_bytes());
+        hasher.update(manifest.total_bytes.to_le_bytes());
+        for skipped in &manifest.skipped_files {
+            hasher.update(skipped.path.as_bytes());
+            hasher.update(skipped.reason.as_bytes());
+        }
+        let mut sparse: Vec<_> = manifest.sparse_files.iter().collect();
+        sparse.sort_by_key(|(k, _)| *k);
+        for (path, layout) in sparse {
+            hasher.update(path.as_bytes());
+            hasher.update(layout.logical_size.to_le_bytes());
+            for (offset, length) in &layout.extents {
+                hasher.update(offset.to_le_bytes());
+                hasher.update(length.to_le_bytes());
+            }
+        }
+        // Empty for backups made before ciphertext hashes were recorded, keeping their hash stable
+        let mut stored: Vec<_> = manifest.ciphertext_sha256.iter().collect();
+        stored.sort_by_key(|(k, _)| *k);
+        for (path, hash) in stored {
+            hasher.update(path.as_bytes());
+            hasher.update(hash.as_bytes());
+        }
+        // Likewise empty for backups made before keystream offsets were recorded
+        let mut offsets: Vec<_> = manifest.keystream_offsets.iter().collect();
+        offsets.sort_by_key(|(k, _)| *k);
+        for (path, offset) in offsets {
+            hasher.update(path.as_bytes());
+            hasher.update(offset.to_le_bytes());
+        }
         
         format!("{:x}", hasher.finalize())
     }
@@ -285,156 +2233,48 @@ impl EncryptedBackup {
         device: &str,
         result: &BackupResult,
         source_paths: &[String],
-    ) -> serde_json::Value {
-        // Get real device information
-        let device_info = self.get_device_info(device);
-        
-        // Determine destination type based on path
-        let dest_type = if result.destination.contains("/media/") || 
-                          result.destination.contains("/mnt/") ||
-                          result.destination.to_lowercase().contains("usb") {
-            "usb"
-        } else if result.destination.contains("://") || 
-                  result.destination.to_lowercase().contains("nas") {
-            "nas"
-        } else {
-            "other"
+        excludes: &[String],
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let device_info = match self.source_device {
+            Some(ref device_info) => device_info.clone(),
+            None => crate::cert_builder::certificate_device(device),
         };
-
-        // Create a schema-compliant certificate structure
-        serde_json::json!({
-            "cert_type": "backup",
-            "cert_id": result.backup_id,
-            "certificate_version": "v1.0.0",
-            "created_at": Utc::now().to_rfc3339(),
-            "issuer": {
-                "organization": "SecureWipe (SIH)",
-                "tool_name": "securewipe",
-                "tool_version": "v1.0.0",
-                "country": "IN"
-            },
-            "device": {
-                "model": device_info.as_ref().and_then(|d| d.model.as_ref()).unwrap_or(&"Unknown".to_string()).clone(),
-                "serial": device_info.as_ref().and_then(|d| d.serial.as_ref()).unwrap_or(&"N/A".to_string()).clone(),
-                "bus": device_info.as_ref().and_then(|d| d.bus.as_ref()).unwrap_or(&"UNKNOWN".to_string()).clone(),
-                "capacity_bytes": device_info.as_ref().map(|d| d.capacity_bytes).unwrap_or(0),
-                "path": device
-            },
-            "files_summary": {
-                "count": result.manifest.total_files,
-                "personal_bytes": result.manifest.total_bytes,
-                "included_paths": source_paths
-            },
-            "destination": {
-                "type": dest_type,
-                "path": result.destination
-            },
-            "crypto": {
-                "alg": result.encryption_method,
-                "manifest_sha256": result.manifest.manifest_sha256,
-                "key_management": "ephemeral_session_key"
-            },
-            "verification": {
-                "strategy": "sampled_files",
-                "failures": if result.verification_passed { 0 } else { 1 }
-            },
-            "policy": {
-                "name": "NIST SP 800-88 Rev.1",
-                "version": "2023.12"
-            },
-            "result": if result.verification_passed { "PASS" } else { "FAIL" },
-            "environment": {
-                "operator": "Automated",
-                "os_kernel": std::env::consts::OS,
-                "tool_version": "v1.0.0"
-            },
-            "exceptions": {
-                "text": "None"
-            },
-            "metadata": {
-                "qr_payload": {
-                    "cert_id": result.backup_id,
-                    "issued_at": Utc::now().to_rfc3339(),
-                    "device_model": device_info.as_ref().and_then(|d| d.model.as_ref()).unwrap_or(&"Unknown".to_string()).clone(),
-                    "result": if result.verification_passed { "PASS" } else { "FAIL" },
-                    "nist_level": "SP 800-88 Rev.1",
-                    "method": "AES-256-CTR",
-                    "verify_url": "https://verify.securewipe.sih/certificate"
-                }
-            },
-            "verify_url": "https://verify.securewipe.sih/certificate"
-        })
+        let identity = crate::cert::IssuerIdentity::current()?;
+        let cert = crate::cert_builder::backup_certificate(result, device_info, source_paths, excludes, &identity)?;
+        Ok(serde_json::to_value(cert)?)
     }
 
     fn save_certificate(&self, cert: &serde_json::Value) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let home = std::env::var("HOME").unwrap_or_else(|_| "/home/user".to_string());
-        let cert_dir = Path::new(&home).join("SecureWipe").join("certificates");
-        fs::create_dir_all(&cert_dir)?;
-        
-        let cert_id = cert.get("cert_id")
-            .and_then(|v| v.as_str())
-            .ok_or("Certificate ID not found")?;
-        
-        let cert_file = cert_dir.join(format!("{}.json", cert_id));
-        let cert_json = serde_json::to_string_pretty(cert)?;
-        fs::write(&cert_file, cert_json)?;
-        
-        Ok(cert_file)
-    }
-
-    /// Attempt to automatically sign a certificate using available private key
-    fn populate_metadata(&self, cert: &mut serde_json::Value) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(cert_obj) = cert.as_object_mut() {
-            // Get certificate details for QR payload (collect strings first to avoid borrowing issues)
-            let cert_id = cert_obj.get("cert_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown").to_string();
-            
-            let issued_at = cert_obj.get("created_at")
-                .and_then(|v| v.as_str())
-                .unwrap_or("").to_string();
-            
-            let device_model = cert_obj
-                .get("device")
-                .and_then(|d| d.get("model"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("Unknown").to_string();
-            
-            // Create QR payload object as per schema
-            let qr_payload = serde_json::json!({
-                "cert_id": cert_id,
-                "issued_at": issued_at,
-                "device_model": device_model,
-                "result": "PASS"
-            });
-            
-            // Update metadata with proper QR payload object
-            if let Some(metadata) = cert_obj.get_mut("metadata") {
-                if let Some(metadata_obj) = metadata.as_object_mut() {
-                    metadata_obj.insert("qr_payload".to_string(), qr_payload);
-                }
-            }
-            
-            self.logger.log("info", "metadata_populated", 
-                &format!("Populated metadata with QR payload for cert: {}", cert_id), None);
-        }
-        Ok(())
+        let cert_dir = crate::config::Config::load()?.certificates_dir;
+        fs::create_dir_all(&cert_dir)?;
+        
+        let cert_id = cert.get("cert_id")
+            .and_then(|v| v.as_str())
+            .ok_or("Certificate ID not found")?;
+        
+        let cert_file = cert_dir.join(format!("{}.json", cert_id));
+        let cert_json = serde_json::to_string_pretty(cert)?;
+        write_synced(&cert_file, cert_json.as_bytes())?;
+        
+        Ok(cert_file)
     }
 
+    /// Attempt to automatically sign a certificate using available private key
     fn try_sign_certificate(&self, cert: &mut serde_json::Value) -> Result<(), Box<dyn std::error::Error>> {
-        use crate::signer::{load_private_key, sign_certificate};
+        use crate::audit::sign_and_record;
+        use crate::signer::load_private_key;
         use std::path::PathBuf;
         
         // Try multiple locations for the private key
         let key_paths = vec![
-            // 1. Environment variable (if set)
-            std::env::var("SECUREWIPE_SIGN_KEY_PATH").ok().map(PathBuf::from),
+            // 1. Configured sign_key_path (env var or config file)
+            crate::config::Config::load().ok().and_then(|config| config.sign_key_path),
             // 2. Project-relative path (for development)
             Some(PathBuf::from("keys/dev_private.pem")),
             // 3. Absolute path to development key
             Some(PathBuf::from("/home/user/projects/erase-sure/keys/dev_private.pem")),
-            // 4. User's SecureWipe directory
-            std::env::var("HOME").ok().map(|h| PathBuf::from(h).join("SecureWipe/keys/private.pem")),
+            // 4. The data directory
+            crate::config::Config::load().ok().map(|config| config.data_dir.join("keys/private.pem")),
         ];
         
         for key_path in key_paths.into_iter().flatten() {
@@ -442,11 +2282,8 @@ impl EncryptedBackup {
                 match load_private_key(Some(key_path.clone())) {
                     Ok(signing_key) => {
                         // Sign the certificate (force=true to overwrite null signature)
-                        match sign_certificate(cert, &signing_key, true) {
+                        match sign_and_record(cert, &signing_key, true) {
                             Ok(_) => {
-                                // Populate metadata after successful signing
-                                self.populate_metadata(cert)?;
-                                
                                 self.logger.log("info", "signing_success", 
                                     &format!("Certificate signed using key: {}", key_path.display()), None);
                                 return Ok(());
@@ -473,13 +2310,16 @@ impl EncryptedBackup {
 }
 
 impl BackupOperations for EncryptedBackup {
+    #[tracing::instrument(name = "backup", skip_all, fields(device = %device))]
     fn perform_backup(
         &self,
         device: &str,
         paths: &[String],
         destination: &str,
     ) -> Result<BackupResult, Box<dyn std::error::Error>> {
-        let backup_id = Uuid::new_v4().to_string();
+        let started = MonotonicStart::now();
+        // Also the certificate's ID and the backup directory's name
+        let backup_id = crate::cert_id::generate(crate::cert_id::CertKind::Backup);
         
         self.logger.log("info", "backup_start", &format!("Starting backup for device {}", device), None);
         
@@ -495,129 +2335,145 @@ impl BackupOperations for EncryptedBackup {
             .map_err(|e| format!("Failed to expand destination path '{}': {}", destination, e))?;
         let destination_path = Path::new(expanded_destination.as_ref());
         
-        // Create backup directory
-        let backup_dir = destination_path.join(&backup_id);
+        match stale_backups(destination_path) {
+            Ok(stale) if !stale.is_empty() => {
+                self.logger.log("warn", "stale_backups_found",
+                    &format!("{} unfinished backup(s) in {}; resume them with `backup --resume` or remove them \
+                              with `backup stale --remove`", stale.len(), destination_path.display()),
+                    Some(serde_json::json!({ "stale": stale })));
+            }
+            _ => {}
+        }
+
+        fs::create_dir_all(destination_path)?;
+        let canary = self.check_destination(destination_path)?;
+
+        // Create backup directory; it gets its final name once the backup is complete
+        let backup_dir = destination_path.join(format!("{}{}", IN_PROGRESS_PREFIX, backup_id));
         fs::create_dir_all(&backup_dir)?;
+        // Removed if the backup stops before it can be resumed
+        let partial = Artifact::partial_backup(&backup_dir);
+        let _lock = DirLock::try_acquire(&backup_dir)?;
         
         self.logger.log("info", "backup_dir_created", &format!("Created backup directory: {:?}", backup_dir), None);
         
-        // Generate encryption key and IV
-        let mut key = [0u8; 32];
-        let mut iv = [0u8; 16];
+        // Generate the IV, and a key from the passphrase when there is one
+        let mut data_key = DataKey { key: [0u8; 32], iv: [0u8; 16] };
         let mut rng = ChaCha20Rng::from_entropy();
-        rng.fill_bytes(&mut key);
-        rng.fill_bytes(&mut iv);
+        rng.fill_bytes(&mut data_key.iv);
+        let encryption_method = match &self.passphrase {
+            Some(passphrase) => {
+                let params = BackupKeyParams::generate(&mut rng, &data_key.iv);
+                data_key.key.copy_from_slice(params.derive_key(passphrase.expose())?.as_ref());
+                fs::write(backup_dir.join("key.json"), serde_json::to_string_pretty(&params)?)?;
+                self.logger.log("info", "backup_key_derived",
+                    &format!("Backup key derived from the passphrase in the {}", passphrase.source),
+                    Some(serde_json::json!({ "kdf": params.kdf, "iterations": params.iterations })));
+                "AES-256-CTR+PBKDF2-HMAC-SHA256"
+            }
+            None => {
+                rng.fill_bytes(&mut data_key.key);
+                "AES-256-CTR"
+            }
+        };
         
-        let mut cipher = Aes256Ctr::new(&key.into(), &iv.into());
+        let run = BackupRun {
+            backup_id: backup_id.clone(),
+            device: device.to_string(),
+            destination: destination.to_string(),
+            source_paths: source_paths.clone(),
+            excludes: self.excludes.clone(),
+            note: self.note.clone(),
+            encryption_method: encryption_method.to_string(),
+            key_check: self.passphrase.as_ref().map(|_| key_check(&data_key.key)),
+        };
+        let mut cipher = data_key.cipher();
+        drop(data_key);
         
         // Collect files
         self.logger.log("info", "file_collection", "Collecting files from source paths", None);
-        let files = self.collect_files(&source_paths)?;
+        let mut progress = CopyProgress::default();
+        let files = self.collect_files(&source_paths, &self.excludes, &mut progress.skipped)?;
+        let source_base = match self.source_base {
+            Some(ref base) => base.as_path(),
+            None => Path::new(&source_paths[0]).parent().unwrap_or(Path::new("/")),
+        };
         
-        // Process files
-        let mut manifest_files = HashMap::new();
-        let mut total_bytes = 0u64;
-        let source_base = Path::new(&source_paths[0]).parent().unwrap_or(Path::new("/"));
+        // Preflight: make sure every name and size fits the destination filesystem
+        let dest_fs = DestinationFs::detect(&backup_dir);
+        let files = self.preflight_destination(dest_fs, &files, source_base, &mut progress.skipped)?;
         
-        for file_path in &files {
-            self.logger.log("info", "file_processing", &format!("Processing file: {:?}", file_path), None);
-            
-            // Compute original hash
-            let original_hash = self.compute_file_hash(file_path)?;
-            
-            // Get relative path
-            let rel_path = file_path.strip_prefix(source_base)
-                .unwrap_or(file_path)
-                .to_string_lossy()
-                .to_string();
-            
-            // Encrypt and write
-            let dest_file = backup_dir.join(&rel_path);
-            if let Some(parent) = dest_file.parent() {
-                fs::create_dir_all(parent)?;
+        // Process files
+        let mut journal = Journal::open(&backup_dir)?;
+        journal.append(&JournalEntry::Start(run.clone()))?;
+        if let Err(e) = self.copy_files(&files, source_base, &backup_dir, &mut cipher, &mut journal, &mut progress) {
+            let error = self.interrupted(&backup_dir, e, &progress);
+            if backup_dir.join("key.json").exists() {
+                partial.keep();
             }
-            
-            let file_bytes = self.encrypt_and_compress_file(file_path, &dest_file, &mut cipher)?;
-            
-            manifest_files.insert(rel_path, original_hash);
-            total_bytes += file_bytes;
+            return Err(error);
         }
+        // Every file is stored; a failure from here on leaves a complete copy
+        partial.keep();
         
-        self.logger.log("info", "encryption_complete", &format!("Encrypted {} files, {} bytes total", files.len(), total_bytes), None);
-        
-        // Create manifest
-        let mut manifest = BackupManifest {
-            files: manifest_files,
-            created_at: Utc::now().to_rfc3339(),
-            total_files: files.len(),
-            total_bytes,
-            manifest_sha256: String::new(),
-        };
-        
-        manifest.manifest_sha256 = self.compute_manifest_hash(&manifest);
-        
-        // Save manifest
-        let manifest_path = backup_dir.join("manifest.json");
-        let manifest_json = serde_json::to_string_pretty(&manifest)?;
-        fs::write(manifest_path, manifest_json)?;
-        
-        self.logger.log("info", "manifest_created", "Manifest created and saved", None);
-        
-        // Verify random files
-        self.logger.log("info", "verification_start", "Starting post-copy verification", None);
-        let (samples, verified) = self.verify_random_files(&manifest, &backup_dir, source_base, 5)?;
-        let verification_passed = samples == verified;
-        
-        self.logger.log(
-            if verification_passed { "info" } else { "error" },
-            "verification_complete",
-            &format!("Verified {}/{} samples", verified, samples),
-            Some(serde_json::json!({
-                "samples_total": samples,
-                "samples_verified": verified,
-                "passed": verification_passed
-            }))
-        );
-        
-        let result = BackupResult {
-            manifest,
-            destination: destination.to_string(),
-            encryption_method: "AES-256-CTR".to_string(),
-            verification_samples: samples,
-            verification_passed,
-            backup_id: backup_id.clone(),
-        };
+        self.complete_backup(&run, &backup_dir, source_base, progress, started, canary)
+    }
+}
 
-        // Add artificial delay for small backups (< 1MB) to allow UI to properly show progress
-        if total_bytes < 50_000_000 {
-            self.logger.log("info", "small_backup_delay", 
-                &format!("Small backup detected ({} bytes), adding UI synchronization delay", total_bytes), None);
-            std::thread::sleep(std::time::Duration::from_secs(3));
-        }
+/// Everything under the source paths, split by whether a backup would copy it
+#[derive(Debug, Default)]
+struct SourceWalk {
+    files: Vec<PathBuf>,
+    excluded: Vec<PathBuf>,
+    unreadable: Vec<(PathBuf, std::io::Error)>,
+}
 
-        // Create and save certificate
-        let mut certificate = self.create_backup_certificate(device, &result, &source_paths);
-        
-        // Automatically sign the certificate if signing key is available
-        match self.try_sign_certificate(&mut certificate) {
-            Ok(_) => {
-                self.logger.log("info", "certificate_signed", "Certificate automatically signed", None);
-            }
-            Err(e) => {
-                self.logger.log("warn", "certificate_signing_failed", 
-                    &format!("Certificate created but not signed: {}", e), None);
+/// Walk `paths` without stopping at errors. An excluded directory is listed once and
+/// not descended into.
+fn walk_sources(paths: &[String], excludes: &[String]) -> SourceWalk {
+    fn visit(path: &Path, excludes: &[String], walk: &mut SourceWalk) {
+        if excludes.iter().any(|pattern| crate::glob::path_excluded(pattern, path)) {
+            walk.excluded.push(path.to_path_buf());
+        } else if path.is_file() {
+            walk.files.push(path.to_path_buf());
+        } else if path.is_dir() {
+            let entries = match fs::read_dir(path) {
+                Ok(entries) => entries,
+                Err(e) => return walk.unreadable.push((path.to_path_buf(), e)),
+            };
+            for entry in entries {
+                match entry {
+                    Ok(entry) => visit(&entry.path(), excludes, walk),
+                    Err(e) => walk.unreadable.push((path.to_path_buf(), e)),
+                }
             }
         }
-        
-        let cert_path = self.save_certificate(&certificate)?;
+    }
 
-        self.logger.log("info", "certificate_created", &format!("Certificate saved to: {:?}", cert_path), None);
-        self.logger.log("info", "backup_complete", "Backup operation completed successfully", None);
+    let mut walk = SourceWalk::default();
+    for path in paths {
+        visit(Path::new(path), excludes, &mut walk);
+    }
+    walk
+}
 
-        Ok(result)
+/// Total size of the regular files at or under `path`
+pub(crate) fn tree_size(path: &Path) -> u64 {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|entry| tree_size(&entry.path())).sum())
+            .unwrap_or(0),
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        _ => 0,
     }
 }
 
+/// Write `metadata.json` and `README.txt` at the backup root
+fn write_backup_info(backup_dir: &Path, metadata: &BackupMetadata) -> std::io::Result<()> {
+    fs::write(backup_dir.join(METADATA_FILE), serde_json::to_string_pretty(metadata)?)?;
+    fs::write(backup_dir.join(README_FILE), metadata.readme())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -663,6 +2519,11 @@ mod tests {
             total_files: 1,
             total_bytes: 1024,
             manifest_sha256: "test_hash".to_string(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
         
         let json = serde_json::to_string(&manifest);
@@ -683,6 +2544,11 @@ mod tests {
             total_files: 0,
             total_bytes: 0,
             manifest_sha256: "empty_hash".to_string(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
         
         let result = BackupResult {
@@ -692,6 +2558,8 @@ mod tests {
             verification_samples: 5,
             verification_passed: true,
             backup_id: "test-backup-id".to_string(),
+            timing: None,
+            destination_canary: None,
         };
         
         let json = serde_json::to_string(&result);
@@ -720,6 +2588,11 @@ mod tests {
             total_files: 2,
             total_bytes: 2048,
             manifest_sha256: String::new(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
         
         let manifest2 = BackupManifest {
@@ -728,6 +2601,11 @@ mod tests {
             total_files: 2,
             total_bytes: 2048,
             manifest_sha256: String::new(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
         
         let hash1 = backup.compute_manifest_hash(&manifest1);
@@ -747,6 +2625,11 @@ mod tests {
             total_files: 0,
             total_bytes: 0,
             manifest_sha256: "test_hash".to_string(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
         
         let result = BackupResult {
@@ -756,9 +2639,11 @@ mod tests {
             verification_samples: 5,
             verification_passed: true,
             backup_id: "test-backup-id".to_string(),
+            timing: None,
+            destination_canary: None,
         };
         
-        let cert = backup.create_backup_certificate("test_device", &result, &["~/Documents".to_string()]);
+        let cert = backup.create_backup_certificate("test_device", &result, &["~/Documents".to_string()], &[]).unwrap();
         
         assert_eq!(cert["cert_type"], "backup");
         assert_eq!(cert["cert_id"], "test-backup-id");
@@ -780,6 +2665,11 @@ mod tests {
             total_files: 0,
             total_bytes: 0,
             manifest_sha256: "test_hash".to_string(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
         
         let result = BackupResult {
@@ -789,9 +2679,11 @@ mod tests {
             verification_samples: 5,
             verification_passed: true,
             backup_id: "test-backup-id".to_string(),
+            timing: None,
+            destination_canary: None,
         };
         
-        let cert = backup.create_backup_certificate("test_device", &result, &["~/Documents".to_string()]);
+        let cert = backup.create_backup_certificate("test_device", &result, &["~/Documents".to_string()], &[]).unwrap();
         
         // Test serialization
         let json = serde_json::to_string_pretty(&cert);
@@ -801,7 +2693,288 @@ mod tests {
         let deserialized: crate::cert::BackupCertificate = serde_json::from_str(&json.unwrap()).unwrap();
         assert_eq!(deserialized.cert_type, cert["cert_type"]);
         assert_eq!(deserialized.cert_id, cert["cert_id"]);
-    assert_eq!(deserialized.crypto.get("alg").unwrap(), &cert["crypto"]["alg"]);
+        assert_eq!(deserialized.crypto.alg, cert["crypto"]["alg"]);
+    }
+    
+    #[test]
+    fn test_file_error_policy_skip_records_reason() {
+        let backup = EncryptedBackup::new().with_error_policy(BackupErrorPolicy::Skip);
+        let mut skipped = Vec::new();
+        let err: Box<dyn std::error::Error> = "Permission denied".into();
+        
+        let result = backup.handle_file_error(Path::new("/home/user/secret.txt"), err, &mut skipped);
+        assert!(result.is_ok());
+        assert_eq!(skipped, vec![SkippedFile {
+            path: "/home/user/secret.txt".to_string(),
+            reason: "Permission denied".to_string(),
+        }]);
+        
+        let exceptions = crate::cert_builder::backup_exceptions(&skipped);
+        assert_eq!(exceptions.items.unwrap()[0], "/home/user/secret.txt: Permission denied");
+        assert!(exceptions.text.unwrap().contains("1 file(s) skipped"));
+    }
+    
+    #[test]
+    fn test_file_error_policy_abort_propagates() {
+        let backup = EncryptedBackup::new();
+        let mut skipped = Vec::new();
+        let err: Box<dyn std::error::Error> = "File vanished".into();
+        
+        let result = backup.handle_file_error(Path::new("/tmp/gone.txt"), err, &mut skipped);
+        assert!(result.is_err());
+        assert!(skipped.is_empty());
+        let exceptions = crate::cert_builder::backup_exceptions(&skipped);
+        assert_eq!(serde_json::to_value(exceptions).unwrap(), serde_json::json!({ "text": "None" }));
+    }
+    
+    #[test]
+    fn test_sparse_file_backup_preserves_holes() {
+        let backup = EncryptedBackup::new();
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let dest_dir = tempfile::TempDir::new().unwrap();
+        
+        // 8 MiB file with a single 4 KiB data block in the middle
+        let sparse_path = source_dir.path().join("disk.img");
+        {
+            let mut file = File::create(&sparse_path).unwrap();
+            file.set_len(8 * 1024 * 1024).unwrap();
+            file.seek(SeekFrom::Start(4 * 1024 * 1024)).unwrap();
+            file.write_all(&[0xAB; 4096]).unwrap();
+        }
+        
+        // Not every filesystem reports holes; nothing to assert if this one doesn't
+        let layout = match backup.detect_sparse(&sparse_path) {
+            Some(layout) => layout,
+            None => return,
+        };
+        assert_eq!(layout.logical_size, 8 * 1024 * 1024);
+        assert!(layout.data_bytes() < layout.logical_size);
+        assert!(layout.extents.iter().any(|(off, len)| *off <= 4 * 1024 * 1024 && off + len >= 4 * 1024 * 1024 + 4096));
+        
+        let paths = vec![source_dir.path().to_str().unwrap().to_string()];
+        let result = backup.perform_backup("/dev/test", &paths, dest_dir.path().to_str().unwrap()).unwrap();
+        
+        let rel_path = result.manifest.files.keys().next().unwrap().clone();
+        assert_eq!(result.manifest.sparse_files.get(&rel_path), Some(&layout));
+        
+        let stored = fs::metadata(dest_dir.path().join(&result.backup_id).join(&rel_path)).unwrap();
+        assert_eq!(stored.len(), 8 * 1024 * 1024);
+        
+        // Holes are hashed as zeros, matching what reads back from the stored file
+        let report = backup.verify_backup(&dest_dir.path().join(&result.backup_id)).unwrap();
+        assert!(report.passed);
+    }
+    
+    #[test]
+    fn test_restore_keeps_sparse_files_sparse() {
+        use std::os::unix::fs::MetadataExt;
+
+        let backup = EncryptedBackup::new().with_passphrase(Some(Secret::for_tests("correct horse")));
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let dest_dir = tempfile::TempDir::new().unwrap();
+        let target_dir = tempfile::TempDir::new().unwrap();
+        fs::write(source_dir.path().join("notes.txt"), b"plain file before the sparse one").unwrap();
+        let sparse_path = source_dir.path().join("disk.img");
+        {
+            let mut file = File::create(&sparse_path).unwrap();
+            file.seek(SeekFrom::Start(4 * 1024 * 1024)).unwrap();
+            file.write_all(&[0x5au8; 4096]).unwrap();
+            file.set_len(8 * 1024 * 1024).unwrap();
+        }
+        if backup.detect_sparse(&sparse_path).is_none() {
+            return; // Filesystem without hole support
+        }
+
+        let paths = vec![source_dir.path().to_str().unwrap().to_string()];
+        let result = backup.perform_backup("/dev/test", &paths, dest_dir.path().to_str().unwrap()).unwrap();
+        let report = backup.restore_backup(&dest_dir.path().join(&result.backup_id), target_dir.path()).unwrap();
+        assert!(report.passed, "{:?}", report.failures);
+        assert_eq!(report.files_restored, 2);
+        assert_eq!(report.sparse_files, 1);
+        assert_eq!(report.logical_bytes, 8 * 1024 * 1024 + 32);
+        assert!(report.physical_bytes < 1024 * 1024);
+
+        let source_name = source_dir.path().file_name().unwrap();
+        let restored = target_dir.path().join(source_name).join("disk.img");
+        assert_eq!(fs::read(&restored).unwrap(), fs::read(&sparse_path).unwrap());
+        assert!(fs::metadata(&restored).unwrap().blocks() * 512 < 8 * 1024 * 1024);
+        assert_eq!(fs::read(target_dir.path().join(source_name).join("notes.txt")).unwrap(), b"plain file before the sparse one");
+
+        // Existing files are left alone, and the wrong passphrase restores nothing
+        let again = backup.restore_backup(&dest_dir.path().join(&result.backup_id), target_dir.path()).unwrap();
+        assert!(!again.passed);
+        let other_target = tempfile::TempDir::new().unwrap();
+        let wrong = EncryptedBackup::new().with_passphrase(Some(Secret::for_tests("wrong horse")));
+        let report = wrong.restore_backup(&dest_dir.path().join(&result.backup_id), other_target.path()).unwrap();
+        assert_eq!(report.files_restored, 0);
+        assert!(report.failures.iter().all(|failure| failure.reason.contains("hash mismatch")));
+        assert!(!other_target.path().join(source_name).join("notes.txt").exists());
+
+        // A tampered manifest can't send decrypted data outside the target
+        let backup_dir = dest_dir.path().join(&result.backup_id);
+        let manifest_path = backup_dir.join("manifest.json");
+        let mut manifest: BackupManifest = serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        let (key, hash) = manifest.files.iter().find(|(key, _)| key.ends_with("notes.txt")).map(|(k, v)| (k.clone(), v.clone())).unwrap();
+        let offset = manifest.keystream_offsets[&key];
+        for escape in ["../x", "/etc/x"] {
+            manifest.files.insert(escape.to_string(), hash.clone());
+            manifest.keystream_offsets.insert(escape.to_string(), offset);
+        }
+        fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
+        let inner_target = other_target.path().join("inner");
+        fs::create_dir(&inner_target).unwrap();
+        let report = backup.restore_backup(&backup_dir, &inner_target).unwrap();
+        for escape in ["../x", "/etc/x"] {
+            assert!(report.failures.iter().any(|failure| failure.path == escape && failure.reason.contains("not a plain relative path")));
+        }
+        assert!(!other_target.path().join("x").exists());
+        assert_eq!(report.files_restored, 2);
+    }
+
+    #[test]
+    fn test_verify_backup_detects_tampering() {
+        let backup = EncryptedBackup::new();
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let dest_dir = tempfile::TempDir::new().unwrap();
+        fs::write(source_dir.path().join("a.txt"), b"alpha").unwrap();
+        fs::write(source_dir.path().join("b.txt"), b"bravo").unwrap();
+        
+        let paths = vec![source_dir.path().to_str().unwrap().to_string()];
+        let result = backup.perform_backup("/dev/test", &paths, dest_dir.path().to_str().unwrap()).unwrap();
+        let backup_dir = dest_dir.path().join(&result.backup_id);
+        let source_name = source_dir.path().file_name().unwrap();
+        
+        let report = backup.verify_backup(&backup_dir).unwrap();
+        assert!(report.passed);
+        assert!(report.manifest_valid);
+        assert_eq!(report.files_checked, 2);
+        assert_eq!(report.files_passed, 2);
+        assert_eq!(report.presence_only, 0);
+        
+        // Flip the stored ciphertext of one file and delete the other
+        fs::write(backup_dir.join(source_name).join("a.txt"), b"xxxxx").unwrap();
+        fs::remove_file(backup_dir.join(source_name).join("b.txt")).unwrap();
+        let report = backup.verify_backup(&backup_dir).unwrap();
+        assert!(!report.passed);
+        assert_eq!(report.files_passed, 0);
+        let reasons: Vec<_> = report.failures.iter().map(|f| f.reason.as_str()).collect();
+        assert!(reasons.contains(&"stored content hash mismatch"));
+        assert!(reasons.contains(&"missing from backup"));
+        
+        // Editing the manifest breaks its own hash
+        let manifest_path = backup_dir.join("manifest.json");
+        let mut manifest: BackupManifest = serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        manifest.total_bytes += 1;
+        fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
+        assert!(!backup.verify_backup(&backup_dir).unwrap().manifest_valid);
+    }
+    
+    #[test]
+    fn test_manifest_path_encoding_roundtrip() {
+        let plain = Path::new("Documents/report.txt");
+        let (key, encoded) = encode_manifest_path(plain);
+        assert_eq!(key, "Documents/report.txt");
+        assert!(encoded.is_none());
+        assert_eq!(decode_manifest_path(&key), Some(plain.to_path_buf()));
+        
+        // A literal name that looks like an encoded key must be encoded too
+        let tricky = Path::new("b64:not-really");
+        let (key, encoded) = encode_manifest_path(tricky);
+        assert_ne!(key, "b64:not-really");
+        assert!(encoded.is_some());
+        assert_eq!(decode_manifest_path(&key), Some(tricky.to_path_buf()));
+        
+        assert_eq!(decode_manifest_path("b64:***"), None);
+        assert_eq!(decode_manifest_path("../x"), None);
+        assert_eq!(decode_manifest_path("/etc/x"), None);
+        assert_eq!(decode_manifest_path("docs/../../x"), None);
+        {
+            use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+            assert_eq!(decode_manifest_path(&format!("{}{}", ENCODED_PATH_PREFIX, URL_SAFE_NO_PAD.encode("../x"))), None);
+        }
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_filename_backup() {
+        use std::os::unix::ffi::OsStrExt;
+        
+        let backup = EncryptedBackup::new();
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let dest_dir = tempfile::TempDir::new().unwrap();
+        
+        let raw_name = std::ffi::OsStr::from_bytes(b"caf\xe9.txt");
+        fs::write(source_dir.path().join(raw_name), b"latin-1 name").unwrap();
+        
+        let paths = vec![source_dir.path().to_str().unwrap().to_string()];
+        let result = backup.perform_backup("/dev/test", &paths, dest_dir.path().to_str().unwrap()).unwrap();
+        
+        let key = result.manifest.files.keys().next().unwrap().clone();
+        assert!(key.starts_with(ENCODED_PATH_PREFIX));
+        assert!(result.manifest.encoded_paths[&key].display.contains("caf"));
+        assert!(result.verification_passed);
+        
+        let rel = decode_manifest_path(&key).unwrap();
+        assert!(rel.as_os_str().as_bytes().ends_with(b"caf\xe9.txt"));
+        assert!(dest_dir.path().join(&result.backup_id).join(&rel).exists());
+    }
+    
+    #[test]
+    fn test_fuse_ntfs_mount_detection() {
+        let mountinfo = "\
+22 1 8:2 / / rw,relatime shared:1 - ext4 /dev/sda2 rw
+40 22 8:17 / /media/usb rw,relatime shared:20 - fuseblk.ntfs /dev/sdb1 rw,user_id=0
+41 22 0:45 / /media/my\\040drive rw,nosuid shared:21 - fuseblk /dev/sdc1 rw,user_id=0
+42 22 0:46 / /home/op/cloud rw,nosuid shared:22 - fuse.rclone remote: rw,user_id=1000
+43 40 0:47 / /media/usb/inner rw,nosuid shared:23 - fuse.sshfs host:/ rw,user_id=0
+";
+        assert!(is_fuse_ntfs_mount(mountinfo, Path::new("/media/usb/backups")));
+        assert!(!is_fuse_ntfs_mount(mountinfo, Path::new("/media/usb/inner/backups")));
+        assert!(!is_fuse_ntfs_mount(mountinfo, Path::new("/media/usbstick")));
+        // A bare fuseblk mount doesn't say which filesystem it is
+        assert!(!is_fuse_ntfs_mount(mountinfo, Path::new("/media/my drive/backups")));
+        assert!(!is_fuse_ntfs_mount(mountinfo, Path::new("/home/op/cloud")));
+    }
+
+    #[test]
+    fn test_destination_fs_name_checks() {
+        assert!(DestinationFs::Fat.check_entry(Path::new("docs/notes:v2.txt"), 10).is_some());
+        assert!(DestinationFs::Ntfs.check_entry(Path::new("docs/trailing."), 10).is_some());
+        assert!(DestinationFs::Other.check_entry(Path::new("docs/notes:v2.txt"), 10).is_none());
+        
+        let long_name = "a".repeat(256);
+        assert!(DestinationFs::Other.check_entry(Path::new(&long_name), 10).is_some());
+        assert!(DestinationFs::ExFat.check_entry(Path::new(&long_name), 10).is_some());
+        assert!(DestinationFs::ExFat.check_entry(Path::new(&"a".repeat(255)), 10).is_none());
+        
+        let five_gib = 5 * 1024 * 1024 * 1024;
+        assert!(DestinationFs::Fat.check_entry(Path::new("disk.img"), five_gib).is_some());
+        assert!(DestinationFs::ExFat.check_entry(Path::new("disk.img"), five_gib).is_none());
+    }
+    
+    #[test]
+    fn test_destination_preflight_policy() {
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let ok_file = source_dir.path().join("ok.txt");
+        let bad_file = source_dir.path().join("bad:name.txt");
+        fs::write(&ok_file, b"fine").unwrap();
+        fs::write(&bad_file, b"colon").unwrap();
+        let files = vec![ok_file.clone(), bad_file.clone()];
+        
+        let mut skipped = Vec::new();
+        let err = EncryptedBackup::new()
+            .preflight_destination(DestinationFs::Fat, &files, source_dir.path(), &mut skipped)
+            .unwrap_err();
+        assert!(err.to_string().contains("FAT32"));
+        assert!(err.to_string().contains("bad:name.txt"));
+        
+        let accepted = EncryptedBackup::new()
+            .with_error_policy(BackupErrorPolicy::Skip)
+            .preflight_destination(DestinationFs::Fat, &files, source_dir.path(), &mut skipped)
+            .unwrap();
+        assert_eq!(accepted, vec![ok_file]);
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].reason.contains("FAT32"));
     }
     
     #[test]
@@ -839,7 +3012,8 @@ mod tests {
         fs::write(&file2, b"content2").unwrap();
         
         let paths = vec![temp_dir.path().to_str().unwrap().to_string()];
-        let files = backup.collect_files(&paths);
+        let mut skipped = Vec::new();
+        let files = backup.collect_files(&paths, &[], &mut skipped);
         
         assert!(files.is_ok());
         let files = files.unwrap();
@@ -852,7 +3026,34 @@ mod tests {
         assert!(file_names.contains(&"file1.txt".to_string()));
         assert!(file_names.contains(&"file2.txt".to_string()));
     }
-    
+
+    #[test]
+    fn test_preview_backup_with_excludes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("project");
+        fs::create_dir_all(source.join("node_modules/left-pad")).unwrap();
+        fs::write(source.join("notes.txt"), b"notes").unwrap();
+        fs::write(source.join("big.bin"), vec![0u8; 4096]).unwrap();
+        fs::write(source.join("scratch.tmp"), b"scratch").unwrap();
+        fs::write(source.join("node_modules/left-pad/index.js"), b"module.exports = pad").unwrap();
+        let dest = temp_dir.path().join("dest");
+        fs::create_dir_all(&dest).unwrap();
+
+        let backup = EncryptedBackup::new().with_excludes(vec!["node_modules".to_string(), "*.tmp".to_string()]);
+        let paths = vec![source.to_str().unwrap().to_string()];
+        let preview = backup.preview_backup(&paths, Some(dest.to_str().unwrap())).unwrap();
+
+        assert_eq!(preview.total_files, 2);
+        assert_eq!(preview.total_bytes, 4096 + 5);
+        assert_eq!(preview.largest_files[0], PreviewFile { path: "project/big.bin".to_string(), size: 4096 });
+        assert_eq!(preview.excluded_files, 2);
+        assert_eq!(preview.excluded_bytes, 20 + 7);
+        assert!(preview.unreadable.is_empty());
+        assert!(preview.destination_filesystem.is_some());
+        // Nothing is written to the destination
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+    }
+
     // Integration tests for complete backup workflow
     #[test]
     fn test_complete_backup_workflow() {
@@ -899,6 +3100,35 @@ mod tests {
         // Verify files are encrypted (different from original)
         let encrypted_content1 = fs::read(backup_dir.join("Documents/document1.txt")).unwrap();
         assert_ne!(encrypted_content1, b"This is document 1 content");
+
+        // The drive identifies itself without the tool
+        let metadata: BackupMetadata =
+            serde_json::from_str(&fs::read_to_string(backup_dir.join(METADATA_FILE)).unwrap()).unwrap();
+        assert_eq!(metadata.backup_id, backup_result.backup_id);
+        assert_eq!(metadata.total_files, 2);
+        assert!(!metadata.restorable);
+        let readme = fs::read_to_string(backup_dir.join(README_FILE)).unwrap();
+        assert!(readme.contains(&backup_result.backup_id));
+        assert!(readme.contains("cannot be decrypted"));
+    }
+
+    #[test]
+    fn test_reserved_root_names_are_skipped() {
+        let backup = EncryptedBackup::new();
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let readme = source_dir.path().join("README.txt");
+        let notes = source_dir.path().join("notes.txt");
+        fs::write(&readme, b"the user's own readme").unwrap();
+        fs::write(&notes, b"notes").unwrap();
+        let dest_dir = tempfile::TempDir::new().unwrap();
+
+        let paths = vec![readme.to_string_lossy().to_string(), notes.to_string_lossy().to_string()];
+        let result = backup.perform_backup("/dev/test_device", &paths, dest_dir.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(result.manifest.files.keys().collect::<Vec<_>>(), vec!["notes.txt"]);
+        assert!(result.manifest.skipped_files.iter().any(|s| s.path.ends_with("README.txt")));
+        let written = fs::read_to_string(dest_dir.path().join(&result.backup_id).join(README_FILE)).unwrap();
+        assert!(written.starts_with("SecureWipe encrypted backup"));
     }
     
     #[test]
@@ -915,6 +3145,11 @@ mod tests {
             total_files: 1,
             total_bytes: 1024,
             manifest_sha256: "manifest_hash_123".to_string(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
         
         let result = BackupResult {
@@ -924,9 +3159,11 @@ mod tests {
             verification_samples: 5,
             verification_passed: true,
             backup_id: "test-backup-id-123".to_string(),
+            timing: None,
+            destination_canary: None,
         };
         
-        let cert = backup.create_backup_certificate("/dev/test_device", &result, &["~/Documents".to_string()]);
+        let cert = backup.create_backup_certificate("/dev/test_device", &result, &["~/Documents".to_string()], &[]).unwrap();
         
         // Test that the certificate can be serialized to valid JSON
         let cert_json = serde_json::to_string_pretty(&cert).unwrap();
@@ -1029,4 +3266,199 @@ mod tests {
         let hash2 = backup.compute_file_hash(&large_file).unwrap();
         assert_eq!(hash_str, hash2, "Hash should be deterministic");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_backup_key_params_derive_key() {
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let mut params = BackupKeyParams::generate(&mut rng, &[1u8; 16]);
+        params.iterations = 1000;
+
+        let key = params.derive_key("correct horse").unwrap();
+        assert_eq!(key, params.derive_key("correct horse").unwrap());
+        assert_ne!(key, params.derive_key("wrong horse").unwrap());
+
+        // The saved parameters alone are enough to re-derive the key
+        let saved: BackupKeyParams = serde_json::from_str(&serde_json::to_string(&params).unwrap()).unwrap();
+        assert_eq!(saved.derive_key("correct horse").unwrap(), key);
+        assert!(!serde_json::to_string(&params).unwrap().contains("correct horse"));
+
+        let other_salt = BackupKeyParams { iterations: 1000, ..BackupKeyParams::generate(&mut rng, &[1u8; 16]) };
+        assert_ne!(other_salt.derive_key("correct horse").unwrap(), key);
+
+        params.kdf = "scrypt".to_string();
+        assert!(params.derive_key("correct horse").is_err());
+    }
+
+    #[test]
+    fn test_backup_records_keystream_offsets() {
+        let backup = EncryptedBackup::new();
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let dest_dir = tempfile::TempDir::new().unwrap();
+        fs::write(source_dir.path().join("a.txt"), vec![b'a'; 1000]).unwrap();
+        fs::write(source_dir.path().join("b.txt"), vec![b'b'; 300]).unwrap();
+
+        let paths = vec![source_dir.path().to_str().unwrap().to_string()];
+        let result = backup.perform_backup("/dev/test", &paths, dest_dir.path().to_str().unwrap()).unwrap();
+
+        // One keystream across both files, each starting where the previous ended
+        let mut offsets: Vec<u64> = result.manifest.keystream_offsets.values().copied().collect();
+        offsets.sort();
+        assert!(offsets == vec![0, 1000] || offsets == vec![0, 300]);
+
+        // The journal is only needed until the manifest exists
+        let backup_dir = dest_dir.path().join(&result.backup_id);
+        assert!(!backup_dir.join(JOURNAL_FILE).exists());
+        assert!(backup.verify_backup(&backup_dir).unwrap().passed);
+    }
+
+    #[test]
+    fn test_journal_replay_skips_damaged_files() {
+        let backup = EncryptedBackup::new();
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("done.txt"), b"ciphertext").unwrap();
+        fs::write(dir.path().join("damaged.txt"), b"cipher").unwrap();
+        fs::write(dir.path().join("partial.txt"), vec![0u8; 700]).unwrap();
+
+        let stored = |path: &str, offset: u64, bytes: u64, hash: String| JournalEntry::Stored {
+            path: path.to_string(),
+            original_hash: "plain".to_string(),
+            ciphertext_hash: hash,
+            file_bytes: bytes,
+            keystream_offset: offset,
+            keystream_end: offset + bytes,
+            sparse: None,
+            encoded: None,
+        };
+        let done_hash = format!("{:x}", Sha256::digest(b"ciphertext"));
+        let mut journal = Journal::open(dir.path()).unwrap();
+        for entry in [
+            JournalEntry::Begin { path: "done.txt".to_string(), keystream_offset: 0, length: 10 },
+            stored("done.txt", 0, 10, done_hash.clone()),
+            JournalEntry::Begin { path: "damaged.txt".to_string(), keystream_offset: 10, length: 10 },
+            stored("damaged.txt", 10, 10, done_hash),
+            // Interrupted after writing more than the source's length at the time
+            JournalEntry::Begin { path: "partial.txt".to_string(), keystream_offset: 20, length: 500 },
+        ] {
+            journal.append(&entry).unwrap();
+        }
+        // A crash mid-append leaves a torn last line
+        journal.file.write_all(b"{\"type\":\"sto").unwrap();
+
+        let entries = Journal::read(dir.path()).unwrap();
+        assert_eq!(entries.len(), 5);
+        let (progress, keystream_next) = backup.replay_journal(dir.path(), &entries);
+        assert_eq!(progress.files.keys().collect::<Vec<_>>(), vec!["done.txt"]);
+        assert_eq!(progress.keystream_offsets["done.txt"], 0);
+        assert_eq!(progress.total_bytes, 10);
+        assert_eq!(keystream_next, 720);
+    }
+
+    #[test]
+    fn test_resume_requires_passphrase_key() {
+        let backup = EncryptedBackup::new();
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(backup.resume_backup(dir.path()).is_err());
+
+        let run = BackupRun {
+            backup_id: "b1".to_string(),
+            device: "/dev/test".to_string(),
+            destination: "/mnt/backup".to_string(),
+            source_paths: vec!["/home/user/Documents".to_string()],
+            excludes: Vec::new(),
+            note: None,
+            encryption_method: "AES-256-CTR".to_string(),
+            key_check: None,
+        };
+        Journal::open(dir.path()).unwrap().append(&JournalEntry::Start(run)).unwrap();
+        let error = backup.resume_backup(dir.path()).unwrap_err().to_string();
+        assert!(error.contains("without a passphrase"), "{}", error);
+
+        fs::write(dir.path().join("manifest.json"), "{}").unwrap();
+        assert!(backup.resume_backup(dir.path()).unwrap_err().to_string().contains("already a complete backup"));
+    }
+
+    #[test]
+    fn test_stale_in_progress_backups() {
+        let dest = tempfile::TempDir::new().unwrap();
+        let crashed = dest.path().join(format!("{}crashed", IN_PROGRESS_PREFIX));
+        let running = dest.path().join(format!("{}running", IN_PROGRESS_PREFIX));
+        fs::create_dir_all(&crashed).unwrap();
+        fs::create_dir_all(&running).unwrap();
+        fs::create_dir_all(dest.path().join("finished")).unwrap();
+        let run = BackupRun {
+            backup_id: "crashed".to_string(),
+            device: "/dev/test".to_string(),
+            destination: dest.path().display().to_string(),
+            source_paths: vec!["/home/user/Documents".to_string()],
+            excludes: Vec::new(),
+            note: None,
+            encryption_method: "AES-256-CTR+PBKDF2-HMAC-SHA256".to_string(),
+            key_check: None,
+        };
+        Journal::open(&crashed).unwrap().append(&JournalEntry::Start(run)).unwrap();
+        fs::write(crashed.join("key.json"), "{}").unwrap();
+        let _running_lock = DirLock::try_acquire(&running).unwrap().unwrap();
+
+        let stale = stale_backups(dest.path()).unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].backup_id, "crashed");
+        assert_eq!(stale[0].files_stored, 0);
+        assert!(stale[0].resumable);
+        // `--resume <dest>/<id>` finds the unfinished directory
+        assert_eq!(in_progress_dir(&dest.path().join("crashed")), crashed);
+
+        remove_stale_backup(&stale[0]).unwrap();
+        assert!(!crashed.exists());
+        assert!(running.exists());
+    }
+
+    #[test]
+    fn test_destination_canary() {
+        let dest = tempfile::TempDir::new().unwrap();
+        let canary = write_canary(dest.path());
+        assert!(canary.passed, "{:?}", canary.error);
+        assert_eq!(canary.bytes, CANARY_BYTES as u64);
+        assert_eq!(fs::read_dir(dest.path()).unwrap().count(), 0);
+
+        let missing = write_canary(&dest.path().join("unplugged"));
+        assert!(!missing.passed);
+        assert!(missing.error.is_some());
+
+        let backup = EncryptedBackup::new();
+        let error = backup.check_destination(&dest.path().join("unplugged")).unwrap_err().to_string();
+        assert!(error.contains("failed a write check"), "{}", error);
+    }
+
+    #[test]
+    fn test_finished_backup_leaves_no_in_progress_dir() {
+        let backup = EncryptedBackup::new();
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let dest_dir = tempfile::TempDir::new().unwrap();
+        fs::write(source_dir.path().join("a.txt"), b"contents").unwrap();
+
+        let paths = vec![source_dir.path().to_str().unwrap().to_string()];
+        let result = backup.perform_backup("/dev/test", &paths, dest_dir.path().to_str().unwrap()).unwrap();
+
+        let names: Vec<String> = fs::read_dir(dest_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec![result.backup_id.clone()]);
+        assert!(stale_backups(dest_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_data_key_wiped_on_drop() {
+        let mut slot = std::mem::MaybeUninit::new(DataKey { key: [0x5a; 32], iv: [0xa5; 16] });
+        // Run the destructor in place, then look at what it left in the buffers
+        let (key, iv) = unsafe {
+            std::ptr::drop_in_place(slot.as_mut_ptr());
+            (
+                std::ptr::addr_of!((*slot.as_ptr()).key).read(),
+                std::ptr::addr_of!((*slot.as_ptr()).iv).read(),
+            )
+        };
+        assert_eq!(key, [0u8; 32]);
+        assert_eq!(iv, [0u8; 16]);
+    }
+}
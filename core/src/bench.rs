@@ -0,0 +1,34 @@
+//! Entry points for `benches/throughput.rs` into the backup and wipe data paths,
+//! which are otherwise private. Built only with the `bench` feature; not a stable API.
+
+use aes::cipher::{generic_array::GenericArray, KeyIvInit};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::path::Path;
+
+use crate::backup::{Aes256Ctr, BackupManifest, EncryptedBackup};
+use crate::wipe::{overwrite_range, BadRegion, OverwriteFill};
+
+/// SHA-256 of a file, as recorded in backup manifests
+pub fn file_hash(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    EncryptedBackup::new().compute_file_hash(path)
+}
+
+/// Encrypt `source` to `dest` the way a backup copies a file, with a fixed key.
+/// Returns the bytes written and the ciphertext's SHA-256.
+pub fn encrypt_file(source: &Path, dest: &Path) -> Result<(u64, String), Box<dyn std::error::Error>> {
+    let mut cipher = Aes256Ctr::new(GenericArray::from_slice(&[7u8; 32]), GenericArray::from_slice(&[0u8; 16]));
+    let mut hasher = Sha256::new();
+    let written = EncryptedBackup::new().encrypt_and_compress_file(source, dest, &mut cipher, &mut hasher)?;
+    Ok((written, format!("{:x}", hasher.finalize())))
+}
+
+/// The manifest's own SHA-256, as stored in `manifest_sha256`
+pub fn manifest_hash(manifest: &BackupManifest) -> String {
+    EncryptedBackup::new().compute_manifest_hash(manifest)
+}
+
+/// One overwrite pass over the first `size` bytes of `target`
+pub fn overwrite(target: &mut File, size: u64, fill: &OverwriteFill) -> std::io::Result<Vec<BadRegion>> {
+    overwrite_range(target, size, fill, &mut |_| {})
+}
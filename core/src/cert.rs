@@ -1,131 +1,78 @@
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "operations")]
 use serde_json::Value;
-use std::process::Command;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
-use crate::device::{LinuxDeviceDiscovery, Device, DeviceDiscovery};
-
-fn get_device_info(device: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
-    // Use proper device discovery to get detailed information including bus type
-    let discovery = LinuxDeviceDiscovery::new();
-    match discovery.discover_devices() {
-        Ok(devices) => {
-            if let Some(device_info) = devices.into_iter().find(|d| {
-                // device.name is already in format "/dev/nvme0n1"
-                d.name == device ||
-                // Handle case where device might be just "nvme0n1"
-                d.name == format!("/dev/{}", device) ||
-                // Handle case where device has /dev/ but d.name doesn't
-                format!("/dev/{}", d.name.trim_start_matches("/dev/")) == device
-            }) {
-                let bus = device_info.bus.unwrap_or_else(|| "UNKNOWN".to_string());
-                return Ok(serde_json::json!({
-                    "model": device_info.model.unwrap_or_else(|| "Unknown".to_string()),
-                    "serial": device_info.serial.unwrap_or_else(|| "Unknown".to_string()),
-                    "bus": bus.clone(),
-                    "capacity_bytes": device_info.capacity_bytes,
-                    "path": device_info.name,
-                    "firmware": "N/A", // Not available from device discovery yet
-                    "logical_block_size": 512, // Default, could be enhanced
-                    "total_lbas": device_info.capacity_bytes / 512, // Approximate
-                    "protocol_path": bus
-                }));
-            }
-        }
-        Err(e) => {
-            eprintln!("Device discovery failed: {}, falling back to basic lsblk", e);
-        }
-    }
-
-    // Fallback to basic lsblk info if device discovery fails
-    let lsblk_output = std::process::Command::new("lsblk")
-        .arg("-J")
-        .arg("-o")
-        .arg("NAME,SIZE,MODEL,SERIAL,TYPE,MOUNTPOINT")
-        .arg(device)
-        .output()?;
+use crate::policy::{IssuerPolicy, PolicyError, SitePolicy};
+#[cfg(feature = "operations")]
+use crate::cert_builder;
+
+pub use securewipe_types::certificate::{
+    BackupCertificate, BackupCertificateBuilder, BackupCrypto, BackupDestination, BackupMetadata, BackupPolicy,
+    BackupVerification, BusType, CertificateDevice, CertificateResult, CommandRecord, CostSummary, Coverage,
+    DestinationCanary, DestinationType, Environment, Evidence, Exceptions, FilesSummary, HostInfo, HpaDco, Issuer, KeyManagement,
+    MediaType, MissingField, MonotonicSpan, QrPayload, ResidualSignature, TimeSource, VerificationStrategy, WipeCertificate,
+    WipeCertificateBuilder, WipeMetadata, WipePolicyRecord, WipeVerification,
+};
+
+/// The issuing organization and verify link base every certificate carries, from the
+/// site policy's `issuer` section. `Default` is the built-in identity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuerIdentity {
+    pub organization: String,
+    pub country: Option<String>,
+    /// Without a trailing slash
+    pub verify_base_url: String,
+}
 
-    let device_name = std::path::Path::new(device)
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-
-    if lsblk_output.status.success() {
-        let lsblk_text = String::from_utf8_lossy(&lsblk_output.stdout);
-        if let Ok(lsblk_json) = serde_json::from_str::<serde_json::Value>(&lsblk_text) {
-            if let Some(blockdevices) = lsblk_json["blockdevices"].as_array() {
-                if let Some(device_info) = blockdevices.first() {
-                    return Ok(serde_json::json!({
-                        "model": device_info["model"].as_str().unwrap_or("Unknown"),
-                        "serial": device_info["serial"].as_str().unwrap_or("Unknown"),
-                        "bus": "UNKNOWN", // lsblk doesn't provide bus type
-                        "capacity_bytes": 0, // Would need parsing of size string
-                        "path": device,
-                        "firmware": "N/A",
-                        "logical_block_size": 512,
-                        "total_lbas": 0,
-                        "protocol_path": "UNKNOWN"
-                    }));
-                }
-            }
+impl Default for IssuerIdentity {
+    fn default() -> Self {
+        Self {
+            organization: "SecureWipe (SIH)".to_string(),
+            country: Some("IN".to_string()),
+            verify_base_url: "https://verify.securewipe.local/cert".to_string(),
         }
     }
-
-    // Final fallback
-    Ok(serde_json::json!({
-        "model": "Unknown",
-        "serial": "Unknown",
-        "bus": "UNKNOWN",
-        "capacity_bytes": 0,
-        "path": device,
-        "firmware": "N/A",
-        "logical_block_size": 512,
-        "total_lbas": 0,
-        "protocol_path": "UNKNOWN"
-    }))
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CertificateSignature {
-    pub alg: String, // "Ed25519"
-    pub pubkey_id: String, // "sih_root_v1"
-    pub sig: String, // Base64 signature
-}
+impl IssuerIdentity {
+    /// From the configured site policy. Fails rather than falling back when the policy
+    /// can't be read, or when it is strict and leaves a field unset.
+    pub fn current() -> Result<Self, PolicyError> {
+        Self::from_policy(&SitePolicy::load_default()?.issuer)
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BackupCertificate {
-    pub cert_id: String,
-    pub cert_type: String, // "backup"
-    pub certificate_version: String,
-    pub created_at: String,
-    pub issuer: serde_json::Value,
-    pub device: serde_json::Value,
-    pub files_summary: serde_json::Value,
-    pub destination: serde_json::Value,
-    pub crypto: serde_json::Value,
-    pub verification: serde_json::Value,
-    pub policy: serde_json::Value,
-    pub result: String,
-    pub environment: serde_json::Value,
-    pub exceptions: serde_json::Value,
-    pub signature: Option<CertificateSignature>,
-    pub metadata: serde_json::Value,
-    pub verify_url: String,
-}
+    pub fn from_policy(policy: &IssuerPolicy) -> Result<Self, PolicyError> {
+        let builtin = Self::default();
+        let field = |value: &Option<String>, name: &'static str| match value.as_deref().map(str::trim) {
+            Some(value) if !value.is_empty() => Ok(Some(value.to_string())),
+            _ if policy.strict => Err(PolicyError::IssuerUnset(name)),
+            _ => Ok(None),
+        };
+        let organization = field(&policy.organization, "organization")?.unwrap_or(builtin.organization);
+        let country = field(&policy.country, "country")?.or(builtin.country);
+        let verify_base_url = match field(&policy.verify_base_url, "verify_base_url")? {
+            Some(url) if url.starts_with("https://") || url.starts_with("http://") => url.trim_end_matches('/').to_string(),
+            Some(url) => return Err(PolicyError::VerifyBaseUrl(url)),
+            None => builtin.verify_base_url,
+        };
+        Ok(Self { organization, country, verify_base_url })
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WipeCertificate {
-    pub cert_id: String,
-    pub cert_type: String, // "wipe"
-    pub certificate_version: String,
-    pub created_at: String,
-    pub device: serde_json::Value,
-    pub wipe_summary: serde_json::Value,
-    pub linkage: Option<serde_json::Value>,
-    pub signature: Option<CertificateSignature>,
+    pub fn issuer(&self) -> Issuer {
+        Issuer {
+            organization: self.organization.clone(),
+            tool_name: "securewipe".to_string(),
+            tool_version: format!("v{}", env!("CARGO_PKG_VERSION")),
+            country: self.country.clone(),
+        }
+    }
+
+    pub fn verify_url(&self, cert_id: &str) -> String {
+        format!("{}/{}", self.verify_base_url, cert_id)
+    }
 }
 
+#[cfg(feature = "operations")]
 #[allow(dead_code)] // MVP: Implementation pending
 pub trait CertificateOperations {
     fn create_backup_certificate(
@@ -157,43 +104,20 @@ pub trait CertificateOperations {
     ) -> Result<String, Box<dyn std::error::Error>>;
 }
 
+#[cfg(feature = "operations")]
 #[allow(dead_code)] // MVP: Implementation pending
 pub struct Ed25519CertificateManager;
 
+#[cfg(feature = "operations")]
 impl CertificateOperations for Ed25519CertificateManager {
     fn create_backup_certificate(
         &self,
-        _backup_result: &crate::backup::BackupResult,
+        backup_result: &crate::backup::BackupResult,
     ) -> Result<BackupCertificate, Box<dyn std::error::Error>> {
-        // Stub implementation - will create actual signed certificates
-        Ok(BackupCertificate {
-            cert_id: "stub_backup_cert_id".to_string(),
-            cert_type: "backup".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: chrono::Utc::now().to_rfc3339(),
-            issuer: serde_json::json!({
-                "organization": "SecureWipe (SIH)",
-                "tool_name": "securewipe",
-                "tool_version": "v2.1.0",
-                "country": "IN"
-            }),
-            device: serde_json::json!({}),
-            files_summary: serde_json::json!({"count": 0, "personal_bytes": 0}),
-            destination: serde_json::json!({"type": "other", "path": "/backup"}),
-            crypto: serde_json::json!({"alg": "AES-256-CTR", "manifest_sha256": "stub_hash"}),
-            verification: serde_json::json!({"strategy": "sampled_files", "samples": 0}),
-            policy: serde_json::json!({"name": "NIST SP 800-88 Rev.1", "version": "2023.12"}),
-            result: "PASS".to_string(),
-            environment: serde_json::json!({"operator": "test", "os_kernel": "test"}),
-            exceptions: serde_json::json!({"text": "None"}),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "stub_signature".to_string(),
-            }),
-            metadata: serde_json::json!({}),
-            verify_url: "http://localhost:8000/verify".to_string(),
-        })
+        // The manager never sees the source disk, so the device block stays unknown
+        let identity = IssuerIdentity::current()?;
+        let cert = cert_builder::backup_certificate(backup_result, cert_builder::unknown_device(None), &[], &[], &identity)?;
+        Ok(cert)
     }
     
     fn create_wipe_certificate(
@@ -201,53 +125,9 @@ impl CertificateOperations for Ed25519CertificateManager {
         wipe_result: &crate::wipe::WipeResult,
         backup_cert_id: Option<&str>,
     ) -> Result<WipeCertificate, Box<dyn std::error::Error>> {
-        let cert_id = uuid::Uuid::new_v4().to_string();
-        let created_at = chrono::Utc::now().to_rfc3339();
-        
-        // Get device information
-        let device_info = get_device_info(&wipe_result.device)?;
-        
-        // Create comprehensive wipe summary
-        let wipe_summary = serde_json::json!({
-            "policy": match wipe_result.policy {
-                crate::wipe::WipePolicy::Clear => "CLEAR",
-                crate::wipe::WipePolicy::Purge => "PURGE"
-            },
-            "method": wipe_result.method,
-            "commands_executed": wipe_result.commands.len(),
-            "verification_samples": wipe_result.verification_samples,
-            "verification_passed": wipe_result.verification_passed,
-            "fallback_reason": wipe_result.fallback_reason,
-            "execution_log": wipe_result.commands.iter().map(|cmd| serde_json::json!({
-                "command": cmd.command,
-                "exit_code": cmd.exit_code,
-                "elapsed_ms": cmd.elapsed_ms,
-                "success": cmd.exit_code == 0
-            })).collect::<Vec<_>>(),
-            "total_execution_time_ms": wipe_result.commands.iter().map(|c| c.elapsed_ms).sum::<u64>()
-        });
-
-        // Create linkage if backup cert provided
-        let linkage = backup_cert_id.map(|id| serde_json::json!({
-            "backup_cert_id": id,
-            "chain_type": "backup_then_wipe",
-            "created_at": created_at
-        }));
-
-        Ok(WipeCertificate {
-            cert_id: cert_id.clone(),
-            cert_type: "wipe".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at,
-            device: device_info,
-            wipe_summary,
-            linkage,
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: format!("unsigned_wipe_{}", cert_id), // Will be replaced with real signature
-            }),
-        })
+        let identity = IssuerIdentity::current()?;
+        let device = cert_builder::certificate_device(&wipe_result.device);
+        cert_builder::wipe_certificate(wipe_result, device, backup_cert_id, &identity)
     }
     
     fn export_to_pdf(
@@ -281,238 +161,206 @@ impl CertificateOperations for Ed25519CertificateManager {
     }
 }
 
-/// Build a schema-compliant wipe certificate JSON (unsigned)
+/// Physical destruction methods accepted on a destroy certificate
+#[cfg(feature = "operations")]
+pub const DESTROY_METHODS: &[&str] = &["shred", "disintegrate", "pulverize", "incinerate", "melt", "degauss"];
+
+/// What the operator attests about a drive that was physically destroyed instead of
+/// being wiped electronically
+#[cfg(feature = "operations")]
+#[derive(Debug, Clone, Default)]
+pub struct DestructionAttestation {
+    /// Block device to read model/serial/capacity from, if the drive is still readable
+    pub device_path: Option<String>,
+    pub serial: Option<String>,
+    pub model: Option<String>,
+    pub bus: Option<String>,
+    pub capacity_bytes: Option<u64>,
+    pub asset_tag: Option<String>,
+    pub method: String,
+    pub reason: Option<String>,
+    /// RFC 3339; defaults to the time the certificate is built
+    pub performed_at: Option<String>,
+    pub location: Option<String>,
+    pub operator: String,
+    pub witness: String,
+    pub equipment: Option<String>,
+    pub particle_size_mm: Option<f64>,
+    pub notes: Option<String>,
+    pub photos: Vec<std::path::PathBuf>,
+    pub backup_cert_id: Option<String>,
+    pub wipe_cert_id: Option<String>,
+}
+
+/// Build a schema-compliant physical destruction certificate JSON (unsigned)
 ///
 /// Contracts:
-/// - Inputs: crate::wipe::WipeResult and optional backup_cert_id
-/// - Output: serde_json::Value matching certs/schemas/wipe_schema.json except for signature (added later)
-/// - Errors: if device information cannot be gathered minimally (model/serial/bus/capacity)
-pub fn build_wipe_certificate_json(
-    wipe_result: &crate::wipe::WipeResult,
-    backup_cert_id: Option<&str>,
+/// - Inputs: operator attestation; photos are hashed, not embedded
+/// - Output: serde_json::Value matching certs/schemas/destroy_schema.json except for signature
+/// - Errors: unknown method, missing serial/operator/witness, bad timestamp, unreadable photo
+#[cfg(feature = "operations")]
+pub fn build_destroy_certificate_json(
+    attestation: &DestructionAttestation,
 ) -> Result<Value, Box<dyn std::error::Error>> {
-    // Generate certificate id
-    let cert_id = format!(
-        "WPE_{}",
-        uuid::Uuid::new_v4().to_string().replace('-', "")
-    );
-
-    // Tool version
-    let tool_version = format!("v{}", env!("CARGO_PKG_VERSION"));
-
-    // Created at
-    let created_at = chrono::Utc::now().to_rfc3339();
-
-    // Issuer
-    let issuer = serde_json::json!({
-        "organization": "SecureWipe (SIH)",
-        "tool_name": "securewipe",
-        "tool_version": tool_version,
-        "country": "IN"
-    });
-
-    // Device info (schema-compliant)
-    let device = schema_device_info(&wipe_result.device)?;
+    if !DESTROY_METHODS.contains(&attestation.method.as_str()) {
+        return Err(format!(
+            "Unknown destruction method '{}' (expected one of: {})",
+            attestation.method,
+            DESTROY_METHODS.join(", ")
+        ).into());
+    }
+    if attestation.operator.trim().is_empty() || attestation.witness.trim().is_empty() {
+        return Err("Both an operator and a witness are required".into());
+    }
 
-    // Policy mapping
-    let nist_level = match wipe_result.policy {
-        crate::wipe::WipePolicy::Clear => "CLEAR",
-        crate::wipe::WipePolicy::Purge => "PURGE"
+    let performed_at = match &attestation.performed_at {
+        Some(ts) => chrono::DateTime::parse_from_rfc3339(ts)
+            .map_err(|e| format!("Invalid --performed-at '{}': {}", ts, e))?
+            .to_rfc3339(),
+        None => chrono::Utc::now().to_rfc3339(),
     };
 
-    let method = wipe_result.method.clone();
-    let action_mapping = match method.as_str() {
-        "controller_sanitize" => "Controller sanitize → PURGE/CLEAR",
-        "overwrite" => "Overwrite pass → NIST level",
-        _ => "Method → NIST mapping",
-    };
+    let cert_id = crate::cert_id::generate(crate::cert_id::CertKind::Destroy);
+    let identity = IssuerIdentity::current()?;
+    let issuer = serde_json::to_value(identity.issuer())?;
 
-    let policy = serde_json::json!({
-        "nist_level": nist_level,
-        "method": method,
-        "action_mapping": action_mapping
-    });
+    let device = destroy_device_info(attestation)?;
 
-    // HPA/DCO section: cleared for PURGE (we call clear_hpa_dco)
-    let hpa_dco = serde_json::json!({
-        "cleared": matches!(wipe_result.policy, crate::wipe::WipePolicy::Purge)
+    let mut policy = serde_json::json!({
+        "nist_level": "DESTROY",
+        "method": attestation.method
     });
+    if let Some(reason) = &attestation.reason {
+        policy["reason"] = Value::String(reason.clone());
+    }
 
-    // Commands array
-    let commands: Vec<Value> = wipe_result
-        .commands
-        .iter()
-        .map(|c| serde_json::json!({
-            "cmd": c.command,
-            "exit": c.exit_code,
-            "ms": c.elapsed_ms
-        }))
-        .collect();
-
-    // Verify object
-    let (verify_result, failures) = if wipe_result.verification_passed {
-        ("PASS", 0)
-    } else {
-        ("FAIL", 1)
-    };
-
-    let verify = serde_json::json!({
-        "strategy": "random_sectors",
-        "samples": wipe_result.verification_samples,
-        "coverage": {"mode": "samples", "samples": wipe_result.verification_samples},
-        "failures": failures,
-        "result": verify_result
-    });
-
-    // Overall result
-    let result_str = if wipe_result.verification_passed { "PASS" } else { "FAIL" };
-
-    // Environment
-    let operator = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
-    let kernel = uname_kernel_string();
-    let environment = serde_json::json!({
-        "operator": operator,
-        "os_kernel": kernel,
-        "tool_version": format!("v{}", env!("CARGO_PKG_VERSION")),
+    let mut destruction = serde_json::json!({
+        "performed_at": performed_at,
+        "operator": attestation.operator,
+        "witness": attestation.witness
     });
+    for (key, value) in [
+        ("location", &attestation.location),
+        ("equipment", &attestation.equipment),
+        ("notes", &attestation.notes),
+    ] {
+        if let Some(value) = value {
+            destruction[key] = Value::String(value.clone());
+        }
+    }
+    if let Some(size) = attestation.particle_size_mm {
+        destruction["particle_size_mm"] = serde_json::json!(size);
+    }
 
-    // Evidence (optional fields only) – provide empty object to satisfy required presence
-    let evidence = serde_json::json!({});
-
-    // Linkage – schema requires linkage; if absent, use placeholder "UNLINKED"
-    let linkage = serde_json::json!({
-        "backup_cert_id": backup_cert_id.unwrap_or("UNLINKED")
-    });
+    let photos = attestation
+        .photos
+        .iter()
+        .map(|path| photo_evidence(path))
+        .collect::<Result<Vec<Value>, _>>()?;
 
-    // Exceptions – none by default
-    let exceptions = serde_json::json!({"items": [], "text": "None"});
+    let mut linkage = serde_json::json!({});
+    if let Some(id) = &attestation.backup_cert_id {
+        linkage["backup_cert_id"] = Value::String(id.clone());
+    }
+    if let Some(id) = &attestation.wipe_cert_id {
+        linkage["wipe_cert_id"] = Value::String(id.clone());
+    }
 
-    // Metadata – optional; keep minimal for now
-    let metadata = serde_json::json!({});
+    let environment = serde_json::to_value(cert_builder::environment(None))?;
 
-    // Verify URL – optional but useful
-    let verify_url = format!(
-        "https://verify.securewipe.local/cert/{}",
-        &cert_id
-    );
+    let verify_url = identity.verify_url(&cert_id);
 
-    let cert = serde_json::json!({
-        "cert_type": "wipe",
+    Ok(serde_json::json!({
+        "cert_type": "destroy",
         "cert_id": cert_id,
         "certificate_version": "v1.0.0",
-        "created_at": created_at,
+        "created_at": chrono::Utc::now().to_rfc3339(),
         "issuer": issuer,
         "device": device,
         "policy": policy,
-        "hpa_dco": hpa_dco,
-        "commands": commands,
-        "verify": verify,
-        "result": result_str,
+        "destruction": destruction,
+        "evidence": {"photos": photos},
+        "result": "DESTROYED",
         "environment": environment,
-        "evidence": evidence,
         "linkage": linkage,
-        "exceptions": exceptions,
-        "metadata": metadata,
+        "metadata": {},
         "verify_url": verify_url
-    });
-
-    Ok(cert)
+    }))
 }
 
-// Helper: produce kernel string like "Linux 6.8.0-35-generic"
-fn uname_kernel_string() -> String {
-    match Command::new("uname").arg("-sr").output() {
-        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).trim().to_string(),
-        _ => "Linux".to_string(),
+// Helper: device object for a destroy certificate. A readable drive fills in the
+// details; explicit values always win, since a dead drive can only be described
+// from its label.
+#[cfg(feature = "operations")]
+fn destroy_device_info(attestation: &DestructionAttestation) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut device = match &attestation.device_path {
+        Some(path) => serde_json::to_value(cert_builder::device_from_lsblk(path)?)?,
+        None => serde_json::json!({"model": "Unknown", "serial": "Unknown", "bus": "UNKNOWN", "capacity_bytes": 0}),
+    };
+
+    if let Some(serial) = &attestation.serial {
+        device["serial"] = Value::String(serial.clone());
+    }
+    if let Some(model) = &attestation.model {
+        device["model"] = Value::String(model.clone());
+    }
+    if let Some(bus) = &attestation.bus {
+        let bus = match bus.to_uppercase().as_str() {
+            "NVME" => "NVMe".to_string(),
+            other @ ("SATA" | "USB" | "SAS" | "VIRTIO" | "UNKNOWN") => other.to_string(),
+            _ => return Err(format!("Unknown bus '{}' (expected SATA, NVMe, USB, SAS, VIRTIO or UNKNOWN)", bus).into()),
+        };
+        device["bus"] = Value::String(bus);
+    }
+    if let Some(capacity) = attestation.capacity_bytes {
+        device["capacity_bytes"] = serde_json::json!(capacity);
+    }
+    if let Some(tag) = &attestation.asset_tag {
+        device["asset_tag"] = Value::String(tag.clone());
+    }
+
+    let serial = device.get("serial").and_then(|v| v.as_str()).unwrap_or("");
+    if serial.trim().is_empty() || serial == "Unknown" {
+        return Err("A drive serial is required; pass --serial or a readable --device".into());
     }
+    Ok(device)
 }
 
-// Helper: Build schema-compliant device object from a path (disk or partition)
-fn schema_device_info(path: &str) -> Result<Value, Box<dyn std::error::Error>> {
-    // Query lsblk JSON for this path; we will walk up to the disk-level device
-    let output = Command::new("lsblk")
-        .args(&["-J", "-b", "-o", "NAME,TYPE,SIZE,MODEL,SERIAL,TRAN,PKNAME", path])
-        .output()?;
-    if !output.status.success() {
-        return Err(format!("lsblk failed for {}", path).into());
-    }
-    let lsblk: Value = serde_json::from_slice(&output.stdout)?;
-    let mut model = None;
-    let mut serial = None;
-    let mut tran = None;
+// Helper: name, size and SHA-256 of an evidence photo
+#[cfg(feature = "operations")]
+fn photo_evidence(path: &std::path::Path) -> Result<Value, Box<dyn std::error::Error>> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Cannot read photo {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
     let mut size_bytes: u64 = 0;
-    let mut disk_name = None;
-
-    if let Some(arr) = lsblk.get("blockdevices").and_then(|v| v.as_array()) {
-        if let Some(dev) = arr.first() {
-            // If this is a partition, prefer parent pkname; otherwise use itself
-            let dtype = dev.get("type").and_then(|v| v.as_str()).unwrap_or("");
-            let name = dev.get("name").and_then(|v| v.as_str()).unwrap_or("");
-            let pkname = dev.get("pkname").and_then(|v| v.as_str());
-            let parent_name = if dtype == "part" { pkname.unwrap_or(name) } else { name };
-            disk_name = Some(parent_name.to_string());
-
-            // If parent different from current, we need to query lsblk without path filter to find parent entry with details
-            if dtype == "part" && pkname.is_some() {
-                let all = Command::new("lsblk")
-                    .args(&["-J", "-b", "-o", "NAME,TYPE,SIZE,MODEL,SERIAL,TRAN"]) 
-                    .output()?;
-                if all.status.success() {
-                    let all_json: Value = serde_json::from_slice(&all.stdout)?;
-                    if let Some(devs) = all_json.get("blockdevices").and_then(|v| v.as_array()) {
-                        for d in devs {
-                            if d.get("name").and_then(|v| v.as_str()) == Some(parent_name) {
-                                model = d.get("model").and_then(|v| v.as_str()).map(|s| s.to_string());
-                                serial = d.get("serial").and_then(|v| v.as_str()).map(|s| s.to_string());
-                                tran = d.get("tran").and_then(|v| v.as_str()).map(|s| s.to_string());
-                                size_bytes = d.get("size").and_then(|v| v.as_str()).and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
-                                break;
-                            }
-                        }
-                    }
-                }
-            } else {
-                model = dev.get("model").and_then(|v| v.as_str()).map(|s| s.to_string());
-                serial = dev.get("serial").and_then(|v| v.as_str()).map(|s| s.to_string());
-                tran = dev.get("tran").and_then(|v| v.as_str()).map(|s| s.to_string());
-                size_bytes = dev.get("size").and_then(|v| v.as_str()).and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
-            }
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
         }
+        hasher.update(&buffer[..read]);
+        size_bytes += read as u64;
     }
 
-    let bus: String = match tran.as_deref().map(|s| s.to_lowercase()) {
-        Some(ref t) if t == "sata" || t == "ata" => "SATA".to_string(),
-        Some(ref t) if t == "nvme" => "NVMe".to_string(),
-        Some(ref t) if t == "usb" => "USB".to_string(),
-        Some(other) => {
-            let upper = other.to_uppercase();
-            match upper.as_str() {
-                "SAS" | "VIRTIO" => upper,
-                _ => "UNKNOWN".to_string(),
-            }
-        },
-        None => "UNKNOWN".to_string(),
-    };
-
-    let model = model.unwrap_or_else(|| "Unknown".to_string());
-    let serial = serial.unwrap_or_else(|| "Unknown".to_string());
-    let capacity_bytes = size_bytes;
-    let disk_path = format!("/dev/{}", disk_name.unwrap_or_else(|| path.trim_start_matches("/dev/").to_string()));
-
-    let device = serde_json::json!({
-        "model": model,
-        "serial": serial,
-    "bus": bus,
-        "capacity_bytes": capacity_bytes,
-        "path": disk_path
-    });
-
-    Ok(device)
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    Ok(serde_json::json!({
+        "file_name": file_name,
+        "sha256": format!("{:x}", hasher.finalize()),
+        "size_bytes": size_bytes
+    }))
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "operations"))]
 mod tests {
     use super::*;
+    use securewipe_types::certificate::CertificateSignature;
     use crate::backup::{BackupResult, BackupManifest};
     use crate::wipe::{WipeResult, WipePolicy};
     use std::collections::HashMap;
@@ -528,12 +376,19 @@ mod tests {
                 total_files: 0,
                 total_bytes: 0,
                 manifest_sha256: "dummy_hash".to_string(),
+                skipped_files: Vec::new(),
+                sparse_files: HashMap::new(),
+                encoded_paths: HashMap::new(),
+                ciphertext_sha256: HashMap::new(),
+                keystream_offsets: HashMap::new(),
             },
             destination: "test".to_string(),
             encryption_method: "AES-256-CTR".to_string(),
             verification_samples: 5,
             verification_passed: true,
             backup_id: "test-backup-123".to_string(),
+            timing: None,
+            destination_canary: None,
         };
         
         let result = cert_mgr.create_backup_certificate(&backup_result);
@@ -560,6 +415,19 @@ mod tests {
             verification_samples: 5,
             verification_passed: true,
             fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
         };
         
         let result = cert_mgr.create_wipe_certificate(&wipe_result, Some("backup_cert_123"));
@@ -577,6 +445,8 @@ mod tests {
             alg: "Ed25519".to_string(),
             pubkey_id: "sih_root_v1".to_string(),
             sig: "test_signature".to_string(),
+            canonicalization: None,
+            qr: None,
         };
         let json = serde_json::to_string(&sig);
         assert!(json.is_ok());
@@ -586,57 +456,188 @@ mod tests {
         assert_eq!(deserialized.pubkey_id, "sih_root_v1");
     }
     
+    fn sample_device() -> CertificateDevice {
+        CertificateDevice {
+            model: "Test SSD 1TB".to_string(),
+            serial: "TEST123456".to_string(),
+            bus: BusType::Nvme,
+            capacity_bytes: 1000000000000,
+            logical_block_size: None,
+            total_lbas: None,
+            firmware: None,
+            namespace_id: None,
+            path: Some("/dev/nvme0n1".to_string()),
+            protocol_path: None,
+            media_type: None,
+            device_id: None,
+        }
+    }
+
+    fn sample_environment() -> Environment {
+        Environment {
+            operator: "test".to_string(),
+            os_kernel: "Linux 6.8.0".to_string(),
+            tool_version: "v1.0.0".to_string(),
+            device_firmware: None,
+            containerized: None,
+            time_source: None,
+            destination_canary: None,
+        }
+    }
+
+    fn sample_signature() -> CertificateSignature {
+        CertificateSignature {
+            alg: "Ed25519".to_string(),
+            pubkey_id: "sih_root_v1".to_string(),
+            sig: "c2lnbmF0dXJl".to_string(),
+            canonicalization: None,
+            qr: None,
+        }
+    }
+
+    fn sample_backup_certificate(cert_id: &str) -> BackupCertificate {
+        BackupCertificateBuilder::new(cert_id, "2023-01-01T00:00:00Z")
+            .issuer(IssuerIdentity::default().issuer())
+            .device(sample_device())
+            .files_summary(FilesSummary {
+                count: 100,
+                personal_bytes: 500000000,
+                included_paths: None,
+                excluded_paths: None,
+            })
+            .destination(BackupDestination {
+                kind: DestinationType::Other,
+                label: None,
+                fs: None,
+                mountpoint: None,
+                path: Some("/mnt/backup".to_string()),
+            })
+            .crypto(BackupCrypto {
+                alg: "AES-256-CTR".to_string(),
+                manifest_sha256: "a".repeat(64),
+                key_management: KeyManagement::EphemeralSessionKey,
+            })
+            .verification(BackupVerification {
+                strategy: VerificationStrategy::SampledFiles,
+                samples: Some(5),
+                coverage: None,
+                failures: 0,
+                notes: None,
+            })
+            .policy(BackupPolicy {
+                name: "NIST SP 800-88 Rev.1".to_string(),
+                version: "2023.12".to_string(),
+            })
+            .result(CertificateResult::Pass)
+            .environment(sample_environment())
+            .signature(sample_signature())
+            .build()
+            .unwrap()
+    }
+
+    fn sample_wipe_certificate(cert_id: &str) -> WipeCertificate {
+        WipeCertificateBuilder::new(cert_id, "2023-01-01T00:00:00Z")
+            .issuer(IssuerIdentity::default().issuer())
+            .device(sample_device())
+            .policy(WipePolicyRecord {
+                nist_level: "PURGE".to_string(),
+                method: "nvme_sanitize".to_string(),
+                action_mapping: None,
+                method_decision: None,
+                overwrite: None,
+            })
+            .verify(WipeVerification {
+                strategy: VerificationStrategy::ControllerStatus,
+                samples: Some(5),
+                coverage: None,
+                failures: 0,
+                result: Some(CertificateResult::Pass),
+                replay: None,
+                histogram: None,
+                residual_signatures: None,
+                pattern: None,
+            })
+            .result(CertificateResult::Pass)
+            .environment(sample_environment())
+            .linkage("test_backup_123")
+            .signature(sample_signature())
+            .build()
+            .unwrap()
+    }
+
     #[test]
     fn test_backup_certificate_serialization() {
-        let cert = BackupCertificate {
-            cert_id: "backup_123".to_string(),
-            cert_type: "backup".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: "2023-01-01T00:00:00Z".to_string(),
-            issuer: serde_json::json!({"organization": "SecureWipe (SIH)"}),
-            device: serde_json::json!({"name": "/dev/sda"}),
-            files_summary: serde_json::json!({"count": 100}),
-            destination: serde_json::json!({"type": "other"}),
-            crypto: serde_json::json!({"alg": "AES-256-CTR", "manifest_sha256": "abc123"}),
-            verification: serde_json::json!({"strategy": "sampled_files"}),
-            policy: serde_json::json!({"name": "NIST SP 800-88 Rev.1"}),
-            result: "PASS".to_string(),
-            environment: serde_json::json!({"operator": "test"}),
-            exceptions: serde_json::json!({"text": "None"}),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "signature".to_string(),
-            }),
-            metadata: serde_json::json!({}),
-            verify_url: "http://localhost:8000/verify".to_string(),
-        };
-        
-        let json = serde_json::to_string(&cert);
-        assert!(json.is_ok());
+        let cert = sample_backup_certificate("backup_123");
+
+        let json = serde_json::to_value(&cert).unwrap();
+        assert_eq!(json["cert_type"], "backup");
+        assert_eq!(json["destination"]["type"], "other");
+        assert_eq!(json["result"], "PASS");
+        assert!(json.get("verify_url").is_none());
+
+        let deserialized: BackupCertificate = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, cert);
     }
     
     #[test]
     fn test_wipe_certificate_serialization() {
-        let cert = WipeCertificate {
-            cert_id: "wipe_123".to_string(),
-            cert_type: "wipe".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: "2023-01-01T00:00:00Z".to_string(),
-            device: serde_json::json!({"name": "/dev/sda"}),
-            wipe_summary: serde_json::json!({"policy": "PURGE"}),
-            linkage: Some(serde_json::json!({"backup_cert_id": "backup_123"})),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "signature".to_string(),
-            }),
+        let cert = sample_wipe_certificate("wipe_123");
+
+        let json = serde_json::to_value(&cert).unwrap();
+        assert_eq!(json["cert_type"], "wipe");
+        assert_eq!(json["device"]["bus"], "NVMe");
+        assert_eq!(json["linkage"]["backup_cert_id"], "test_backup_123");
+        assert_eq!(json["verify"]["strategy"], "controller_status");
+
+        let deserialized: WipeCertificate = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, cert);
+    }
+
+    #[test]
+    fn test_destroy_certificate_json() {
+        use sha2::Digest;
+
+        let photo = std::env::temp_dir().join(format!("securewipe_destroy_{}.jpg", std::process::id()));
+        std::fs::write(&photo, b"shredded platter").unwrap();
+
+        let mut attestation = DestructionAttestation {
+            serial: Some("Z9A1B2C3".to_string()),
+            model: Some("Seagate ST1000DM010".to_string()),
+            bus: Some("sata".to_string()),
+            capacity_bytes: Some(1000204886016),
+            method: "shred".to_string(),
+            performed_at: Some("2024-03-04T09:40:00+05:30".to_string()),
+            operator: "asha".to_string(),
+            witness: "R. Kumar".to_string(),
+            particle_size_mm: Some(20.0),
+            photos: vec![photo.clone()],
+            wipe_cert_id: Some("WPE_123".to_string()),
+            ..Default::default()
         };
-        
-        let json = serde_json::to_string(&cert);
-        assert!(json.is_ok());
+
+        let cert = build_destroy_certificate_json(&attestation).unwrap();
+        assert_eq!(cert["cert_type"], "destroy");
+        assert!(cert["cert_id"].as_str().unwrap().starts_with("DST_"));
+        assert_eq!(cert["device"]["bus"], "SATA");
+        assert_eq!(cert["policy"]["nist_level"], "DESTROY");
+        assert_eq!(cert["destruction"]["witness"], "R. Kumar");
+        assert_eq!(cert["evidence"]["photos"][0]["size_bytes"], 16);
+        assert_eq!(
+            cert["evidence"]["photos"][0]["sha256"],
+            format!("{:x}", sha2::Sha256::digest(b"shredded platter"))
+        );
+        assert_eq!(cert["linkage"]["wipe_cert_id"], "WPE_123");
+        assert!(cert["linkage"].get("backup_cert_id").is_none());
+
+        attestation.method = "microwave".to_string();
+        assert!(build_destroy_certificate_json(&attestation).is_err());
+        attestation.method = "shred".to_string();
+        attestation.serial = None;
+        assert!(build_destroy_certificate_json(&attestation).is_err());
+
+        std::fs::remove_file(&photo).unwrap();
     }
-    
+
     #[test]
     fn test_pdf_export() {
         let cert_mgr = Ed25519CertificateManager;
@@ -651,36 +652,7 @@ mod tests {
     #[test]
     fn test_backup_certificate_pdf_generation() {
         let cert_mgr = Ed25519CertificateManager;
-        let cert = BackupCertificate {
-            cert_id: "test_backup_pdf_123".to_string(),
-            cert_type: "backup".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: "2023-01-01T00:00:00Z".to_string(),
-            issuer: serde_json::json!({"organization": "SecureWipe (SIH)"}),
-            device: serde_json::json!({
-                "model": "Test SSD 1TB",
-                "serial": "TEST123456",
-                "capacity_bytes": 1000000000000u64
-            }),
-            files_summary: serde_json::json!({
-                "count": 100,
-                "personal_bytes": 500000000u64
-            }),
-            destination: serde_json::json!({"type": "other"}),
-            crypto: serde_json::json!({"alg": "AES-256-CTR", "manifest_sha256": "abc123"}),
-            verification: serde_json::json!({"strategy": "sampled_files"}),
-            policy: serde_json::json!({"name": "NIST SP 800-88 Rev.1"}),
-            result: "PASS".to_string(),
-            environment: serde_json::json!({"operator": "test"}),
-            exceptions: serde_json::json!({"text": "None"}),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "signature".to_string(),
-            }),
-            metadata: serde_json::json!({}),
-            verify_url: "http://localhost:8000/verify".to_string(),
-        };
+        let cert = sample_backup_certificate("test_backup_pdf_123");
 
         let result = cert_mgr.generate_backup_certificate_pdf(&cert, Some("https://verify.example.com"));
         assert!(result.is_ok());
@@ -694,31 +666,7 @@ mod tests {
     #[test]
     fn test_wipe_certificate_pdf_generation() {
         let cert_mgr = Ed25519CertificateManager;
-        let cert = WipeCertificate {
-            cert_id: "test_wipe_pdf_456".to_string(),
-            cert_type: "wipe".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: "2023-01-01T00:00:00Z".to_string(),
-            device: serde_json::json!({
-                "model": "Test SSD 1TB",
-                "serial": "TEST123456",
-                "capacity_bytes": 1000000000000u64
-            }),
-            wipe_summary: serde_json::json!({
-                "policy": "PURGE",
-                "method": "nvme_sanitize",
-                "verification_samples": 5,
-                "verification_passed": true
-            }),
-            linkage: Some(serde_json::json!({
-                "backup_cert_id": "test_backup_123"
-            })),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "signature".to_string(),
-            }),
-        };
+        let cert = sample_wipe_certificate("test_wipe_pdf_456");
 
         let result = cert_mgr.generate_wipe_certificate_pdf(&cert, None);
         assert!(result.is_ok());
@@ -728,4 +676,4 @@ mod tests {
             assert!(path.contains(".pdf"));
         }
     }
-}
\ No newline at end of file
+}
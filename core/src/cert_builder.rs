@@ -0,0 +1,890 @@
+//! Assembles backup and wipe certificates. `EncryptedBackup`, `Ed25519CertificateManager`
+//! and the `wipe` command all build through here, so the issuer, environment and device
+//! blocks look the same whichever path produced a certificate.
+
+use crate::backup::{BackupResult, SkippedFile};
+use crate::cert::{
+    BackupCertificate, BackupCertificateBuilder, BackupCrypto, BackupDestination, BackupMetadata, BackupPolicy,
+    BackupVerification, BusType, CertificateDevice, CertificateResult, CommandRecord, CostSummary, Coverage, DestinationType,
+    Environment, Evidence, Exceptions, FilesSummary, HpaDco, IssuerIdentity, KeyManagement, MediaType, MissingField, MonotonicSpan, QrPayload,
+    ResidualSignature, VerificationStrategy, WipeCertificate, WipeCertificateBuilder, WipeMetadata, WipePolicyRecord,
+    WipeVerification,
+};
+use crate::device::{Device, DeviceDiscovery, LinuxDeviceDiscovery};
+use crate::device_id::stable_id;
+use crate::virtual_disk::VirtualKind;
+use crate::wipe::{BadRegion, WipePolicy, WipeResult};
+use serde_json::Value;
+use std::error::Error;
+use std::process::Command;
+
+/// Environment block; `timing` is the operation's span on the monotonic clock
+pub fn environment(timing: Option<MonotonicSpan>) -> Environment {
+    Environment {
+        operator: crate::audit::operator(),
+        os_kernel: kernel_string(),
+        tool_version: format!("v{}", env!("CARGO_PKG_VERSION")),
+        device_firmware: None,
+        containerized: None,
+        time_source: Some(crate::clock::time_source(timing)),
+        destination_canary: None,
+    }
+}
+
+// Helper: produce kernel string like "Linux 6.8.0-35-generic"
+pub(crate) fn kernel_string() -> String {
+    match Command::new("uname").arg("-sr").output() {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).trim().to_string(),
+        _ => "Linux".to_string(),
+    }
+}
+
+/// Device block for `path`: lsblk first, then device discovery, then an entry that
+/// records only the path. Fields that can't be read are left out rather than guessed.
+pub fn certificate_device(path: &str) -> CertificateDevice {
+    if let Ok(device) = device_from_lsblk(path) {
+        return device;
+    }
+    match find_device(path) {
+        Some(device) => device_from_discovery(&device),
+        None => unknown_device(Some(path)),
+    }
+}
+
+/// A device nothing is known about, e.g. for a backup whose source disk wasn't recorded
+pub fn unknown_device(path: Option<&str>) -> CertificateDevice {
+    CertificateDevice {
+        model: "Unknown".to_string(),
+        serial: "Unknown".to_string(),
+        bus: BusType::Unknown,
+        capacity_bytes: 0,
+        logical_block_size: None,
+        total_lbas: None,
+        firmware: None,
+        namespace_id: None,
+        path: path.map(|p| p.to_string()),
+        protocol_path: None,
+        media_type: None,
+        device_id: None,
+    }
+}
+
+pub fn device_from_discovery(device: &Device) -> CertificateDevice {
+    CertificateDevice {
+        model: device.model.clone().unwrap_or_else(|| "Unknown".to_string()),
+        serial: device.serial.clone().unwrap_or_else(|| "Unknown".to_string()),
+        bus: device.bus.as_deref().map_or(BusType::Unknown, BusType::from_transport),
+        capacity_bytes: device.capacity_bytes,
+        device_id: device.device_id.clone(),
+        ..unknown_device(Some(&device.name))
+    }
+}
+
+fn find_device(path: &str) -> Option<Device> {
+    let devices = LinuxDeviceDiscovery::new().discover_devices().ok()?;
+    devices.into_iter().find(|d| {
+        // d.name is already in format "/dev/nvme0n1"; path may or may not carry /dev/
+        d.name == path
+            || d.name == format!("/dev/{}", path)
+            || format!("/dev/{}", d.name.trim_start_matches("/dev/")) == path
+    })
+}
+
+/// Device block for a disk or partition path, read from lsblk (partitions resolve to
+/// their parent disk). Errors when lsblk cannot describe the path.
+pub fn device_from_lsblk(path: &str) -> Result<CertificateDevice, Box<dyn Error>> {
+    // Query lsblk JSON for this path; we will walk up to the disk-level device
+    let output = Command::new("lsblk")
+        .args(["-J", "-b", "-o", "NAME,TYPE,SIZE,MODEL,SERIAL,TRAN,PKNAME,WWN", path])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("lsblk failed for {}", path).into());
+    }
+    let lsblk: Value = serde_json::from_slice(&output.stdout)?;
+    let mut model = None;
+    let mut serial = None;
+    let mut tran = None;
+    let mut wwn = None;
+    let mut size_bytes: u64 = 0;
+    let mut disk_name = None;
+
+    if let Some(arr) = lsblk.get("blockdevices").and_then(|v| v.as_array()) {
+        if let Some(dev) = arr.first() {
+            // If this is a partition, prefer parent pkname; otherwise use itself
+            let dtype = dev.get("type").and_then(|v| v.as_str()).unwrap_or("");
+            let name = dev.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let pkname = dev.get("pkname").and_then(|v| v.as_str());
+            let parent_name = if dtype == "part" { pkname.unwrap_or(name) } else { name };
+            disk_name = Some(parent_name.to_string());
+
+            // If parent different from current, we need to query lsblk without path filter to find parent entry with details
+            if dtype == "part" && pkname.is_some() {
+                let all = Command::new("lsblk")
+                    .args(["-J", "-b", "-o", "NAME,TYPE,SIZE,MODEL,SERIAL,TRAN,WWN"])
+                    .output()?;
+                if all.status.success() {
+                    let all_json: Value = serde_json::from_slice(&all.stdout)?;
+                    if let Some(devs) = all_json.get("blockdevices").and_then(|v| v.as_array()) {
+                        for d in devs {
+                            if d.get("name").and_then(|v| v.as_str()) == Some(parent_name) {
+                                model = d.get("model").and_then(|v| v.as_str()).map(|s| s.to_string());
+                                serial = d.get("serial").and_then(|v| v.as_str()).map(|s| s.to_string());
+                                tran = d.get("tran").and_then(|v| v.as_str()).map(|s| s.to_string());
+                                wwn = d.get("wwn").and_then(|v| v.as_str()).map(|s| s.to_string());
+                                size_bytes = d.get("size").and_then(|v| v.as_str()).and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
+                                break;
+                            }
+                        }
+                    }
+                }
+            } else {
+                model = dev.get("model").and_then(|v| v.as_str()).map(|s| s.to_string());
+                serial = dev.get("serial").and_then(|v| v.as_str()).map(|s| s.to_string());
+                tran = dev.get("tran").and_then(|v| v.as_str()).map(|s| s.to_string());
+                wwn = dev.get("wwn").and_then(|v| v.as_str()).map(|s| s.to_string());
+                size_bytes = dev.get("size").and_then(|v| v.as_str()).and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
+            }
+        }
+    }
+
+    let disk_path = format!("/dev/{}", disk_name.unwrap_or_else(|| path.trim_start_matches("/dev/").to_string()));
+
+    let device_id = stable_id(wwn.as_deref(), model.as_deref(), serial.as_deref());
+    Ok(CertificateDevice {
+        device_id,
+        model: model.unwrap_or_else(|| "Unknown".to_string()),
+        serial: serial.unwrap_or_else(|| "Unknown".to_string()),
+        bus: tran.as_deref().map_or(BusType::Unknown, BusType::from_transport),
+        capacity_bytes: size_bytes,
+        ..unknown_device(Some(&disk_path))
+    })
+}
+
+/// Where a backup went, judged from its destination path
+pub fn destination_type(destination: &str) -> DestinationType {
+    if destination.contains("/media/") || destination.contains("/mnt/") || destination.to_lowercase().contains("usb") {
+        DestinationType::Usb
+    } else if destination.contains("://") || destination.to_lowercase().contains("nas") {
+        DestinationType::Nas
+    } else {
+        DestinationType::Other
+    }
+}
+
+/// Summarize skipped files for the certificate `exceptions` block
+pub fn backup_exceptions(skipped: &[SkippedFile]) -> Exceptions {
+    if skipped.is_empty() {
+        return Exceptions { items: None, text: Some("None".to_string()) };
+    }
+
+    Exceptions {
+        items: Some(skipped.iter().map(|s| format!("{}: {}", s.path, s.reason)).collect()),
+        text: Some(format!("{} file(s) skipped due to read errors", skipped.len())),
+    }
+}
+
+/// Unsigned backup certificate for a finished backup; `cert_id` is the backup id
+pub fn backup_certificate(
+    result: &BackupResult,
+    device: CertificateDevice,
+    source_paths: &[String],
+    excluded_paths: &[String],
+    identity: &IssuerIdentity,
+) -> Result<BackupCertificate, MissingField> {
+    let created_at = chrono::Utc::now().to_rfc3339();
+    let outcome = CertificateResult::from_passed(result.verification_passed);
+    let cost = crate::cost::estimate(&device, result.timing, result.manifest.total_bytes);
+    let qr_payload = QrPayload {
+        cert_id: Some(result.backup_id.clone()),
+        issued_at: Some(created_at.clone()),
+        device_model: Some(device.model.clone()),
+        result: Some(outcome),
+        nist_level: Some("SP 800-88 Rev.1".to_string()),
+        method: Some(result.encryption_method.clone()),
+        verify_url: Some(identity.verify_url(&result.backup_id)),
+        ..QrPayload::default()
+    };
+
+    BackupCertificateBuilder::new(result.backup_id.clone(), created_at)
+        .issuer(identity.issuer())
+        .device(device)
+        .files_summary(FilesSummary {
+            count: result.manifest.total_files as u64,
+            personal_bytes: result.manifest.total_bytes,
+            included_paths: Some(source_paths.to_vec()),
+            excluded_paths: (!excluded_paths.is_empty()).then(|| excluded_paths.to_vec()),
+        })
+        .destination(BackupDestination {
+            kind: destination_type(&result.destination),
+            label: None,
+            fs: None,
+            mountpoint: None,
+            path: Some(result.destination.clone()),
+        })
+        .crypto(BackupCrypto {
+            alg: result.encryption_method.clone(),
+            manifest_sha256: result.manifest.manifest_sha256.clone(),
+            key_management: KeyManagement::EphemeralSessionKey,
+        })
+        .verification(BackupVerification {
+            strategy: VerificationStrategy::SampledFiles,
+            samples: Some(result.verification_samples as u64),
+            coverage: None,
+            failures: if result.verification_passed { 0 } else { 1 },
+            notes: None,
+        })
+        .policy(BackupPolicy {
+            name: "NIST SP 800-88 Rev.1".to_string(),
+            version: "2023.12".to_string(),
+        })
+        .result(outcome)
+        .environment(Environment { destination_canary: result.destination_canary.clone(), ..environment(result.timing) })
+        .exceptions(backup_exceptions(&result.manifest.skipped_files))
+        .metadata(BackupMetadata { qr_payload: Some(qr_payload), cost, ..BackupMetadata::default() })
+        .verify_url(identity.verify_url(&result.backup_id))
+        .build()
+}
+
+/// Overall wipe outcome: a pass that left bad regions unwritten is only a pass with exceptions
+pub fn wipe_outcome(wipe_result: &WipeResult) -> CertificateResult {
+    match (wipe_result.verification_passed, wipe_result.bad_regions.is_empty()) {
+        (false, _) => CertificateResult::Fail,
+        (true, true) => CertificateResult::Pass,
+        (true, false) => CertificateResult::PassWithExceptions,
+    }
+}
+
+/// List the regions the overwrite engine skipped for the certificate `exceptions` block
+pub fn wipe_exceptions(bad_regions: &[BadRegion], passed: bool) -> Exceptions {
+    if bad_regions.is_empty() {
+        return Exceptions { items: Some(Vec::new()), text: Some("None".to_string()) };
+    }
+
+    let bad_bytes: u64 = bad_regions.iter().map(|region| region.length).sum();
+    Exceptions {
+        items: Some(
+            bad_regions
+                .iter()
+                .map(|region| format!("offset {} length {}: {}", region.offset, region.length, region.error))
+                .collect(),
+        ),
+        text: Some(format!(
+            "{} region(s), {} bytes, could not be overwritten{}",
+            bad_regions.len(),
+            bad_bytes,
+            if passed { "; accepted by site policy" } else { "" }
+        )),
+    }
+}
+
+/// HPA/DCO section from what the drive reported after the clear step. Only a
+/// confirming re-read counts as cleared; no readout means no claim either way.
+fn hpa_dco_block(wipe_result: &WipeResult) -> HpaDco {
+    match &wipe_result.hpa_dco {
+        Some(check) => HpaDco {
+            cleared: check.passed == Some(true),
+            commands: Some(check.commands.clone()),
+            before: check.before,
+            after: check.after,
+            dco_real_max: check.dco_real_max,
+            result: check.passed.map(CertificateResult::from_passed),
+        },
+        None => HpaDco::default(),
+    }
+}
+
+/// Unsigned wipe certificate for a finished wipe
+pub fn wipe_certificate(
+    wipe_result: &WipeResult,
+    mut device: CertificateDevice,
+    backup_cert_id: Option<&str>,
+    identity: &IssuerIdentity,
+) -> Result<WipeCertificate, Box<dyn Error>> {
+    mark_media_type(&mut device, wipe_result);
+    let cert_id = crate::cert_id::generate(crate::cert_id::CertKind::Wipe);
+
+    // Policy mapping
+    let nist_level = match wipe_result.policy {
+        WipePolicy::Clear => "CLEAR",
+        WipePolicy::Purge => "PURGE"
+    };
+    let action_mapping = match wipe_result.method.as_str() {
+        "controller_sanitize" => "Controller sanitize → PURGE/CLEAR",
+        "overwrite" => "Overwrite pass → NIST level",
+        _ => "Method → NIST mapping",
+    };
+
+    let commands = wipe_result
+        .commands
+        .iter()
+        .map(|c| CommandRecord {
+            cmd: c.command.clone(),
+            exit: c.exit_code,
+            ms: c.elapsed_ms,
+            stdout_sha256: None,
+            stderr_sha256: None,
+        })
+        .collect();
+
+    let failures = match (&wipe_result.pattern_verification, &wipe_result.verification) {
+        (Some(pattern), _) => pattern.sectors_mismatched as usize + wipe_result.residual_signatures.len(),
+        (None, Some(record)) => {
+            record.samples.iter().filter(|sample| !sample.passed).count() + wipe_result.residual_signatures.len()
+        }
+        (None, None) => if wipe_result.verification_passed { 0 } else { 1 },
+    };
+    let (strategy, coverage) = match &wipe_result.pattern_verification {
+        Some(pattern) if pattern.full => (VerificationStrategy::FullReadback, Coverage::Percent { percent: 100.0 }),
+        _ => (
+            VerificationStrategy::RandomSectors,
+            Coverage::Samples { samples: wipe_result.verification_samples as u64 },
+        ),
+    };
+    let mut verify = WipeVerification {
+        strategy,
+        samples: Some(wipe_result.verification_samples as u64),
+        coverage: Some(coverage),
+        failures: failures as u64,
+        result: Some(CertificateResult::from_passed(wipe_result.verification_passed)),
+        replay: None,
+        histogram: None,
+        residual_signatures: None,
+        pattern: None,
+    };
+    // Seed and mismatch counts from comparing the device against the keyed overwrite pattern
+    if let Some(pattern) = &wipe_result.pattern_verification {
+        verify.pattern = Some(serde_json::to_value(pattern)?);
+    }
+    // Seed, offsets and digests let `cert replay-verify` re-read the same sectors
+    if let Some(record) = &wipe_result.verification {
+        verify.replay = Some(serde_json::to_value(record)?);
+        verify.histogram = Some(serde_json::to_value(record.histogram())?);
+        verify.residual_signatures = Some(
+            wipe_result
+                .residual_signatures
+                .iter()
+                .map(|finding| ResidualSignature { offset: finding.offset, kind: finding.kind.clone() })
+                .collect(),
+        );
+    }
+
+    let cost = wipe_cost(wipe_result, device.clone());
+
+    let mut builder = WipeCertificateBuilder::new(cert_id.clone(), chrono::Utc::now().to_rfc3339())
+        .issuer(identity.issuer())
+        .device(device)
+        .policy(WipePolicyRecord {
+            nist_level: nist_level.to_string(),
+            method: wipe_result.method.clone(),
+            action_mapping: Some(action_mapping.to_string()),
+            method_decision: wipe_result.method_decision.clone(),
+            overwrite: wipe_result.overwrite.as_ref().map(serde_json::to_value).transpose()?,
+        })
+        .hpa_dco(hpa_dco_block(wipe_result))
+        .commands(commands)
+        .verify(verify)
+        .result(wipe_outcome(wipe_result))
+        .environment(environment(wipe_result.timing))
+        .host(crate::host::probe())
+        .exceptions(wipe_exceptions(&wipe_result.bad_regions, wipe_result.verification_passed))
+        .metadata(WipeMetadata { cost, ..WipeMetadata::default() })
+        .verify_url(identity.verify_url(&cert_id));
+
+    if let Some(backup_cert_id) = backup_cert_id {
+        builder = builder.linkage(backup_cert_id);
+    }
+    if let Some(identity_proof) = identity_proof_json(wipe_result)? {
+        builder = builder.identity_proof(identity_proof);
+    }
+    if let Some(delta) = &wipe_result.smart_delta {
+        builder = builder
+            .smart_delta(serde_json::to_value(delta)?)
+            .evidence(Evidence { smart_snapshot_sha256: Some(delta.after.raw_sha256.clone()), ..Evidence::default() });
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Cost summary of a finished wipe of `device`
+pub fn wipe_cost(wipe_result: &WipeResult, mut device: CertificateDevice) -> Option<CostSummary> {
+    mark_media_type(&mut device, wipe_result);
+    // An overwrite counts what it wrote; a controller sanitize covers the whole drive
+    let bytes_processed = match &wipe_result.overwrite {
+        Some(overwrite) => overwrite.passes.iter().map(|pass| pass.bytes_written).sum(),
+        None => device.capacity_bytes,
+    };
+    crate::cost::estimate(&device, wipe_result.timing, bytes_processed)
+}
+
+/// Record whether the wiped device was physical or virtual. An image file has no lsblk
+/// entry, so its model and capacity are filled in from the file itself.
+fn mark_media_type(device: &mut CertificateDevice, wipe_result: &WipeResult) {
+    let Some(media) = &wipe_result.virtual_media else {
+        device.media_type = Some(MediaType::Physical);
+        return;
+    };
+    device.media_type = Some(MediaType::Virtual);
+    if media.kind == VirtualKind::NetworkVolume {
+        device.bus = BusType::Network;
+    }
+    if media.kind == VirtualKind::ImageFile {
+        if device.model == "Unknown" {
+            device.model = media.describe();
+        }
+        if device.capacity_bytes == 0 {
+            device.capacity_bytes = std::fs::metadata(&wipe_result.device).map(|m| m.len()).unwrap_or(0);
+        }
+    }
+}
+
+/// Build a schema-compliant wipe certificate JSON (unsigned)
+///
+/// Contracts:
+/// - Inputs: crate::wipe::WipeResult and optional backup_cert_id
+/// - Output: serde_json::Value matching certs/schemas/wipe_schema.json except for signature (added later)
+/// - Device details come from [`certificate_device`]; unreadable fields stay "Unknown"
+pub fn build_wipe_certificate_json(
+    wipe_result: &WipeResult,
+    backup_cert_id: Option<&str>,
+) -> Result<Value, Box<dyn Error>> {
+    let identity = IssuerIdentity::current()?;
+    let device = certificate_device(&wipe_result.device);
+    // Linkage – schema requires linkage; if absent, use placeholder "UNLINKED"
+    let cert = wipe_certificate(wipe_result, device, Some(backup_cert_id.unwrap_or("UNLINKED")), &identity)?;
+    Ok(serde_json::to_value(cert)?)
+}
+
+// Helper: before/after controller identity and what comparing them shows.
+// Omitted when neither snapshot could be captured.
+fn identity_proof_json(wipe_result: &WipeResult) -> Result<Option<Value>, Box<dyn Error>> {
+    let (before, after) = (&wipe_result.identity_before, &wipe_result.identity_after);
+    if before.is_none() && after.is_none() {
+        return Ok(None);
+    }
+
+    let mut proof = serde_json::json!({});
+    if let Some(before) = before {
+        proof["before"] = serde_json::to_value(before)?;
+    }
+    if let Some(after) = after {
+        proof["after"] = serde_json::to_value(after)?;
+    }
+
+    let same_device = match (before, after) {
+        (Some(before), Some(after)) => after.same_device_as(before),
+        _ => false,
+    };
+    proof["same_device"] = Value::Bool(same_device);
+
+    if let (Some(before), Some(after)) = (before, after) {
+        if let Some(recorded) = after.sanitize_recorded_since(before) {
+            proof["sanitize_recorded"] = Value::Bool(recorded);
+        }
+    }
+
+    Ok(Some(proof))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::BackupManifest;
+    use crate::policy::{IssuerPolicy, PolicyError};
+    use crate::wipe::{HpaDcoCheck, IdentitySnapshot, MaxSectors, OverwriteFill, OverwritePass, OverwriteProfile, OverwriteRecord, PatternVerification};
+    use std::collections::HashMap;
+
+    fn backup_result() -> BackupResult {
+        BackupResult {
+            manifest: BackupManifest {
+                files: HashMap::new(),
+                created_at: "2023-01-01T00:00:00Z".to_string(),
+                total_files: 3,
+                total_bytes: 4096,
+                manifest_sha256: "b".repeat(64),
+                skipped_files: Vec::new(),
+                sparse_files: HashMap::new(),
+                encoded_paths: HashMap::new(),
+                ciphertext_sha256: HashMap::new(),
+                keystream_offsets: HashMap::new(),
+            },
+            destination: "/media/user/BACKUP".to_string(),
+            encryption_method: "AES-256-CTR".to_string(),
+            verification_samples: 3,
+            verification_passed: true,
+            backup_id: "BCK_test_001".to_string(),
+            timing: None,
+            destination_canary: None,
+        }
+    }
+
+    #[test]
+    fn test_backup_certificate_uses_real_outcome() {
+        let mut result = backup_result();
+        result.verification_passed = false;
+        let cert = backup_certificate(&result, unknown_device(Some("/dev/sdz")), &["~/Documents".to_string()], &[], &IssuerIdentity::default()).unwrap();
+
+        assert_eq!(cert.cert_id, "BCK_test_001");
+        assert_eq!(cert.result, CertificateResult::Fail);
+        assert_eq!(cert.verification.failures, 1);
+        assert_eq!(cert.destination.kind, DestinationType::Usb);
+        assert_eq!(cert.environment.tool_version, format!("v{}", env!("CARGO_PKG_VERSION")));
+        assert!(cert.environment.time_source.is_some());
+        // The QR payload reports the real result rather than a fixed PASS
+        assert_eq!(cert.metadata.qr_payload.unwrap().result, Some(CertificateResult::Fail));
+        assert!(cert.signature.is_none());
+    }
+
+    #[test]
+    fn test_issuer_identity_from_policy() {
+        let policy = IssuerPolicy {
+            organization: Some("Acme Recycling".to_string()),
+            verify_base_url: Some("https://certs.acme.example/v/".to_string()),
+            ..IssuerPolicy::default()
+        };
+        let identity = IssuerIdentity::from_policy(&policy).unwrap();
+        assert_eq!(identity.issuer().organization, "Acme Recycling");
+        // Unset fields fall back to the built-in identity
+        assert_eq!(identity.country, IssuerIdentity::default().country);
+        assert_eq!(identity.verify_url("WPE_1"), "https://certs.acme.example/v/WPE_1");
+
+        let cert = backup_certificate(&backup_result(), unknown_device(None), &[], &[], &identity).unwrap();
+        assert_eq!(cert.verify_url.as_deref(), Some("https://certs.acme.example/v/BCK_test_001"));
+        assert_eq!(cert.metadata.qr_payload.unwrap().verify_url, cert.verify_url);
+
+        let strict = IssuerPolicy { strict: true, ..policy.clone() };
+        assert!(matches!(IssuerIdentity::from_policy(&strict), Err(PolicyError::IssuerUnset("country"))));
+        let strict = IssuerPolicy { country: Some("DE".to_string()), ..strict };
+        assert_eq!(IssuerIdentity::from_policy(&strict).unwrap().country.as_deref(), Some("DE"));
+
+        let relative = IssuerPolicy { verify_base_url: Some("certs.acme.example".to_string()), ..policy };
+        assert!(matches!(IssuerIdentity::from_policy(&relative), Err(PolicyError::VerifyBaseUrl(_))));
+    }
+
+    #[test]
+    fn test_unknown_device_is_not_guessed() {
+        let device = certificate_device("/dev/securewipe-does-not-exist");
+        assert_eq!(device.bus, BusType::Unknown);
+        assert_eq!(device.path.as_deref(), Some("/dev/securewipe-does-not-exist"));
+        assert!(device.firmware.is_none());
+        assert!(device.total_lbas.is_none());
+    }
+
+    #[test]
+    fn test_wipe_certificate_records_pattern_readback() {
+        let wipe_result = WipeResult {
+            device: "/dev/sdz".to_string(),
+            policy: WipePolicy::Purge,
+            method: "overwrite".to_string(),
+            commands: vec![],
+            verification_samples: 128,
+            verification_passed: false,
+            fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: Some(PatternVerification {
+                seed: 42,
+                algorithm: "chacha20".to_string(),
+                full: true,
+                sectors_checked: 4096,
+                sectors_mismatched: 2,
+                mismatched_offsets: vec![4096, 8192],
+                passed: false,
+            }),
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: Some(MonotonicSpan { start_ms: 1_000, end_ms: 61_000, duration_ms: 60_000 }),
+            virtual_media: None,
+            smart_delta: None,
+        };
+
+        let cert = wipe_certificate(&wipe_result, unknown_device(Some("/dev/sdz")), None, &IssuerIdentity::default()).unwrap();
+        let time_source = cert.environment.time_source.as_ref().unwrap();
+        assert_eq!(time_source.monotonic.map(|span| span.duration_ms), Some(60_000));
+        assert!(!cert.host.unwrap().hostname.is_empty());
+        assert_eq!(cert.verify.strategy, VerificationStrategy::FullReadback);
+        assert_eq!(cert.verify.coverage, Some(Coverage::Percent { percent: 100.0 }));
+        assert_eq!(cert.verify.failures, 2);
+        assert_eq!(cert.verify.pattern.unwrap()["mismatched_offsets"][1], 8192);
+        let cost = cert.metadata.cost.unwrap();
+        assert_eq!(cost.wall_time_ms, 60_000);
+        assert_eq!(cost.bytes_processed, 0);
+    }
+
+    #[test]
+    fn test_wipe_certificate_marks_virtual_media() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("guest.qcow2");
+        std::fs::write(&image, vec![0u8; 8192]).unwrap();
+        let image_path = image.display().to_string();
+        let mut wipe_result = WipeResult {
+            device: image_path.clone(),
+            policy: WipePolicy::Clear,
+            method: "overwrite".to_string(),
+            commands: vec![],
+            verification_samples: 32,
+            verification_passed: true,
+            fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: Some(crate::virtual_disk::VirtualMedia {
+                kind: VirtualKind::ImageFile,
+                format: Some("qcow2".to_string()),
+                detected_by: "qcow2 image file".to_string(),
+            }),
+            smart_delta: None,
+        };
+
+        let cert = wipe_certificate(&wipe_result, unknown_device(Some(&image_path)), None, &IssuerIdentity::default()).unwrap();
+        assert_eq!(cert.device.media_type, Some(MediaType::Virtual));
+        assert_eq!(cert.device.model, "qcow2 disk image");
+        assert_eq!(cert.device.capacity_bytes, 8192);
+        assert_eq!(serde_json::to_value(&cert.device).unwrap()["media_type"], "virtual");
+
+        wipe_result.virtual_media = None;
+        let cert = wipe_certificate(&wipe_result, unknown_device(Some("/dev/sdz")), None, &IssuerIdentity::default()).unwrap();
+        assert_eq!(cert.device.media_type, Some(MediaType::Physical));
+    }
+
+    #[test]
+    fn test_wipe_certificate_lists_bad_regions() {
+        let mut wipe_result = WipeResult {
+            device: "/dev/sdz".to_string(),
+            policy: WipePolicy::Clear,
+            method: "overwrite".to_string(),
+            commands: vec![],
+            verification_samples: 32,
+            verification_passed: true,
+            fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: vec![BadRegion {
+                offset: 4096,
+                length: 1024,
+                error: "Input/output error (os error 5)".to_string(),
+            }],
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
+        };
+
+        let cert = wipe_certificate(&wipe_result, unknown_device(Some("/dev/sdz")), None, &IssuerIdentity::default()).unwrap();
+        assert_eq!(cert.result, CertificateResult::PassWithExceptions);
+        assert_eq!(cert.verify.result, Some(CertificateResult::Pass));
+        let exceptions = cert.exceptions;
+        assert_eq!(exceptions.items.unwrap(), vec!["offset 4096 length 1024: Input/output error (os error 5)"]);
+        assert!(exceptions.text.unwrap().contains("accepted by site policy"));
+
+        // Over the policy allowance the wipe fails, but the regions are still listed
+        wipe_result.verification_passed = false;
+        let cert = wipe_certificate(&wipe_result, unknown_device(Some("/dev/sdz")), None, &IssuerIdentity::default()).unwrap();
+        assert_eq!(cert.result, CertificateResult::Fail);
+        assert_eq!(cert.exceptions.items.unwrap().len(), 1);
+
+        wipe_result.verification_passed = true;
+        wipe_result.bad_regions.clear();
+        assert_eq!(wipe_outcome(&wipe_result), CertificateResult::Pass);
+    }
+
+    #[test]
+    fn test_wipe_certificate_records_overwrite_passes() {
+        let pass = |pass, fill| OverwritePass { pass, fill, bytes_written: 1 << 30, bad_bytes: 0 };
+        let wipe_result = WipeResult {
+            device: "/dev/sdz".to_string(),
+            policy: WipePolicy::Purge,
+            method: "overwrite".to_string(),
+            commands: vec![],
+            verification_samples: 128,
+            verification_passed: true,
+            fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: Some(OverwriteRecord {
+                profile: OverwriteProfile::Dod522022M3Pass,
+                passes: vec![
+                    pass(1, OverwriteFill::Zeros),
+                    pass(2, OverwriteFill::Repeat { bytes: vec![0xFF] }),
+                    pass(3, OverwriteFill::Random),
+                ],
+            }),
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
+        };
+
+        let cert = wipe_certificate(&wipe_result, unknown_device(Some("/dev/sdz")), None, &IssuerIdentity::default()).unwrap();
+        let overwrite = cert.policy.overwrite.unwrap();
+        assert_eq!(overwrite["profile"], "dod-5220.22-m-3pass");
+        assert_eq!(overwrite["passes"].as_array().unwrap().len(), 3);
+        assert_eq!(overwrite["passes"][1]["fill"], "repeat");
+        assert_eq!(overwrite["passes"][1]["bytes"], "ff");
+    }
+
+    #[test]
+    fn test_wipe_certificate_hpa_dco_from_readback() {
+        let mut wipe_result = WipeResult {
+            device: "/dev/sdz".to_string(),
+            policy: WipePolicy::Purge,
+            method: "overwrite".to_string(),
+            commands: vec![],
+            verification_samples: 128,
+            verification_passed: false,
+            fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: Some(HpaDcoCheck {
+                before: Some(MaxSectors { current: 976771055, native: 976773168 }),
+                after: Some(MaxSectors { current: 976771055, native: 976773168 }),
+                dco_real_max: None,
+                commands: vec!["hdparm --yes-i-know-what-i-am-doing -Np976773168 /dev/sdz".to_string()],
+                passed: Some(false),
+            }),
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
+        };
+
+        // The clear was attempted but the re-read still shows the HPA
+        let cert = wipe_certificate(&wipe_result, unknown_device(Some("/dev/sdz")), None, &IssuerIdentity::default()).unwrap();
+        let hpa_dco = cert.hpa_dco.unwrap();
+        assert!(!hpa_dco.cleared);
+        assert_eq!(hpa_dco.result, Some(CertificateResult::Fail));
+        assert_eq!(hpa_dco.before.unwrap().current, 976771055);
+        assert_eq!(hpa_dco.commands.unwrap().len(), 1);
+        assert_eq!(cert.result, CertificateResult::Fail);
+
+        // PURGE alone is not evidence: with no readout nothing is claimed
+        wipe_result.hpa_dco = None;
+        wipe_result.verification_passed = true;
+        let hpa_dco = wipe_certificate(&wipe_result, unknown_device(Some("/dev/sdz")), None, &IssuerIdentity::default()).unwrap().hpa_dco.unwrap();
+        assert!(!hpa_dco.cleared);
+        assert_eq!(hpa_dco.result, None);
+    }
+
+    #[test]
+    fn test_identity_proof_json() {
+        let before = IdentitySnapshot {
+            captured_at: "2023-01-01T00:00:00Z".to_string(),
+            source: "nvme_id_ctrl".to_string(),
+            model: Some("Samsung SSD 980 PRO 1TB".to_string()),
+            serial: Some("S4EWNX0R123456".to_string()),
+            firmware: Some("5B2QGXA7".to_string()),
+            capacity_bytes: Some(1000204886016),
+            sanitize_status: Some(0),
+            raw_sha256: "a".repeat(64),
+        };
+        let after = IdentitySnapshot {
+            captured_at: "2023-01-01T00:05:00Z".to_string(),
+            sanitize_status: Some(257),
+            ..before.clone()
+        };
+        let mut wipe_result = WipeResult {
+            device: "/dev/nvme0n1".to_string(),
+            policy: WipePolicy::Purge,
+            method: "controller_sanitize".to_string(),
+            commands: vec![],
+            verification_samples: 5,
+            verification_passed: true,
+            fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
+        };
+        assert!(identity_proof_json(&wipe_result).unwrap().is_none());
+
+        wipe_result.identity_before = Some(before);
+        wipe_result.identity_after = Some(after);
+        let proof = identity_proof_json(&wipe_result).unwrap().unwrap();
+        assert_eq!(proof["same_device"], true);
+        assert_eq!(proof["sanitize_recorded"], true);
+        assert_eq!(proof["after"]["sanitize_status"], 257);
+
+        // A missing post-wipe snapshot can't prove anything about the device
+        wipe_result.identity_after = None;
+        let proof = identity_proof_json(&wipe_result).unwrap().unwrap();
+        assert_eq!(proof["same_device"], false);
+        assert!(proof.get("sanitize_recorded").is_none());
+    }
+    #[test]
+    fn test_smart_delta_in_certificate() {
+        let snapshot = |bytes: u64, hash: char| crate::smart::SmartSnapshot {
+            captured_at: "2023-01-01T00:00:00Z".to_string(),
+            counter: "Total_LBAs_Written".to_string(),
+            host_bytes_written: bytes,
+            resolution_bytes: 512,
+            raw_sha256: hash.to_string().repeat(64),
+        };
+        let wipe_result = WipeResult {
+            device: "/dev/sdz".to_string(),
+            policy: WipePolicy::Clear,
+            method: "overwrite".to_string(),
+            commands: vec![],
+            verification_samples: 5,
+            verification_passed: true,
+            fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: crate::smart::SmartDelta::compare(snapshot(1000, 'a'), snapshot(1000, 'b'), Some(1 << 30)),
+        };
+
+        let cert = wipe_certificate(&wipe_result, unknown_device(Some("/dev/sdz")), None, &IssuerIdentity::default()).unwrap();
+        let delta = cert.smart_delta.unwrap();
+        assert_eq!(delta["assessment"], "nothing_written");
+        assert_eq!(delta["suspicious"], true);
+        assert_eq!(cert.evidence.smart_snapshot_sha256, Some("b".repeat(64)));
+    }
+}
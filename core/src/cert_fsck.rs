@@ -0,0 +1,205 @@
+//! Consistency check over the certificates directory, for evidence stores that are
+//! kept for years: certificates that no longer parse or match their schema, broken
+//! signatures, PDFs whose certificate is gone, cert ids issued twice or stored under
+//! another file name, and a search index that no longer matches the files.
+
+use crate::cert_index::{CertIndex, IndexDrift};
+use crate::schema::CertificateValidator;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    /// Not readable as JSON
+    Unreadable,
+    /// Fails its certificate schema, or has no known cert_type
+    Schema,
+    /// Signature missing or not made by the trusted key
+    Signature,
+    /// A PDF with no certificate JSON next to it
+    OrphanedPdf,
+    /// The same cert_id in more than one file
+    DuplicateCertId,
+    /// Stored under a file name other than `<cert_id>.json`
+    Misnamed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Problem {
+    pub kind: ProblemKind,
+    pub path: String,
+    pub cert_id: Option<String>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FsckReport {
+    pub certificates: usize,
+    /// Whether signatures were checked; they need the issuer's public key
+    pub signatures_checked: bool,
+    /// Ordered by kind, then path
+    pub problems: Vec<Problem>,
+    /// None when no index was compared
+    pub index_drift: Option<IndexDrift>,
+}
+
+impl FsckReport {
+    pub fn is_clean(&self) -> bool {
+        self.problems.is_empty() && self.index_drift.as_ref().is_none_or(IndexDrift::is_empty)
+    }
+}
+
+/// Check every certificate in `dir`. Signatures are verified against `public_key`
+/// when one is given, and `index` is compared with the directory when given.
+pub fn check(dir: &Path, public_key: Option<&[u8; 32]>, index: Option<&CertIndex>) -> Result<FsckReport, Box<dyn std::error::Error>> {
+    let mut report = FsckReport { signatures_checked: public_key.is_some(), ..FsckReport::default() };
+    let mut names: Vec<String> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().filter_map(|entry| entry.file_name().into_string().ok()).collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    names.sort();
+
+    let validator = CertificateValidator::default();
+    let mut problem = |kind, path: &Path, cert_id: Option<&str>, detail: String| {
+        report.problems.push(Problem { kind, path: path.display().to_string(), cert_id: cert_id.map(str::to_string), detail });
+    };
+    let mut files_by_id: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for name in &names {
+        let path = dir.join(name);
+        if let Some(stem) = name.strip_suffix(".pdf") {
+            if !names.contains(&format!("{}.json", stem)) {
+                problem(ProblemKind::OrphanedPdf, &path, None, format!("no {}.json for this PDF", stem));
+            }
+            continue;
+        }
+        let Some(stem) = name.strip_suffix(".json") else { continue };
+        if name == crate::serial::STORE_FILE {
+            continue;
+        }
+
+        let cert = match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|content| {
+            serde_json::from_str::<Value>(&content).map_err(|e| e.to_string())
+        }) {
+            Ok(cert) => cert,
+            Err(e) => {
+                problem(ProblemKind::Unreadable, &path, None, e);
+                continue;
+            }
+        };
+        report.certificates += 1;
+        let cert_id = cert["cert_id"].as_str();
+
+        match validator.validate_certificate(&cert) {
+            Ok(result) if result.valid => {}
+            Ok(result) => problem(ProblemKind::Schema, &path, cert_id, result.errors.join("; ")),
+            Err(e) => problem(ProblemKind::Schema, &path, cert_id, e.to_string()),
+        }
+
+        if let Some(public_key) = public_key {
+            match crate::signer::verify_certificate_signature(&cert, public_key) {
+                Ok(true) => {}
+                Ok(false) => problem(ProblemKind::Signature, &path, cert_id, "signature does not match the public key".to_string()),
+                Err(e) => problem(ProblemKind::Signature, &path, cert_id, e.to_string()),
+            }
+        }
+
+        if let Some(cert_id) = cert_id {
+            if cert_id != stem {
+                problem(ProblemKind::Misnamed, &path, Some(cert_id), format!("expected {}.json", cert_id));
+            }
+            files_by_id.entry(cert_id.to_string()).or_default().push(name.clone());
+        }
+    }
+
+    for (cert_id, files) in files_by_id.iter().filter(|(_, files)| files.len() > 1) {
+        for name in files {
+            let others: Vec<&str> = files.iter().filter(|other| *other != name).map(String::as_str).collect();
+            problem(ProblemKind::DuplicateCertId, &dir.join(name), Some(cert_id), format!("also in {}", others.join(", ")));
+        }
+    }
+
+    report.problems.sort_by(|a, b| (a.kind, &a.path).cmp(&(b.kind, &b.path)));
+    report.index_drift = index.map(CertIndex::drift).transpose()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use serde_json::json;
+
+    fn kinds(report: &FsckReport) -> Vec<(ProblemKind, String)> {
+        report
+            .problems
+            .iter()
+            .map(|problem| (problem.kind, Path::new(&problem.path).file_name().unwrap().to_string_lossy().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_finds_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let write = |name: &str, cert: &Value| fs::write(dir.path().join(name), cert.to_string()).unwrap();
+
+        let mut signed = json!({"cert_id": "WPE_1", "cert_type": "wipe"});
+        crate::signer::sign_certificate(&mut signed, &key, false).unwrap();
+        write("WPE_1.json", &signed);
+        fs::write(dir.path().join("WPE_1.pdf"), b"%PDF").unwrap();
+
+        let mut tampered = json!({"cert_id": "WPE_2", "cert_type": "wipe"});
+        crate::signer::sign_certificate(&mut tampered, &key, false).unwrap();
+        tampered["result"] = json!("PASS");
+        write("WPE_2.json", &tampered);
+        // A copy under another name, e.g. left behind by a manual restore
+        write("WPE_2 (copy).json", &tampered);
+
+        write("WPE_3.json", &json!({"cert_id": "WPE_3", "cert_type": "shred"}));
+        fs::write(dir.path().join("WPE_4.json"), "{\"cert_id\": ").unwrap();
+        fs::write(dir.path().join("WPE_5.pdf"), b"%PDF").unwrap();
+        write("serials.json", &json!({"SIH": 5}));
+        fs::write(dir.path().join("WPE_1_annotations.jsonl"), "").unwrap();
+
+        let report = check(dir.path(), None, None).unwrap();
+        assert!(!report.signatures_checked);
+        assert_eq!(report.certificates, 4);
+        let found = kinds(&report);
+        assert!(found.contains(&(ProblemKind::Unreadable, "WPE_4.json".to_string())));
+        assert!(found.contains(&(ProblemKind::Schema, "WPE_3.json".to_string())));
+        assert!(found.contains(&(ProblemKind::OrphanedPdf, "WPE_5.pdf".to_string())));
+        assert!(found.contains(&(ProblemKind::DuplicateCertId, "WPE_2.json".to_string())));
+        assert!(found.contains(&(ProblemKind::DuplicateCertId, "WPE_2 (copy).json".to_string())));
+        assert!(found.contains(&(ProblemKind::Misnamed, "WPE_2 (copy).json".to_string())));
+        assert!(!found.iter().any(|(kind, _)| *kind == ProblemKind::Signature));
+
+        let signatures = check(dir.path(), Some(key.verifying_key().as_bytes()), None).unwrap();
+        let bad: Vec<String> = kinds(&signatures)
+            .into_iter()
+            .filter(|(kind, _)| *kind == ProblemKind::Signature)
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(bad, ["WPE_2 (copy).json", "WPE_2.json", "WPE_3.json"]);
+    }
+
+    #[test]
+    fn test_check_reports_index_drift() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut index = CertIndex::open_in_memory(dir.path()).unwrap();
+        assert!(check(dir.path(), None, Some(&index)).unwrap().is_clean());
+
+        fs::write(dir.path().join("WPE_1.json"), json!({"cert_id": "WPE_1", "cert_type": "wipe"}).to_string()).unwrap();
+        let drift = check(dir.path(), None, Some(&index)).unwrap().index_drift.unwrap();
+        assert_eq!(drift.unindexed.len(), 1);
+
+        index.refresh().unwrap();
+        assert!(check(dir.path(), None, Some(&index)).unwrap().index_drift.unwrap().is_empty());
+        assert!(check(&dir.path().join("missing"), None, None).unwrap().is_clean());
+    }
+}
@@ -0,0 +1,200 @@
+//! Certificate IDs: `<TYPE>_<YYYYMMDD>T<HHMMSS>Z_<random>_<check>`, for example
+//! `WPE_20250630T120000Z_7K3QX9M2PA_H`. The type prefix says what kind of certificate
+//! it is, the timestamp when the ID was issued (UTC), the random part keeps IDs issued
+//! in the same second apart, and the check character (Luhn mod 32 over everything
+//! before it) catches a mistyped or misread ID. All characters are Crockford base32,
+//! so an ID read off a printed label can't confuse 0/O or 1/I/L.
+//!
+//! Certificates issued before this scheme have UUIDs or `WPE_<hex>` IDs; they stay
+//! valid for lookups (`is_storable`) but don't `parse`. `find_certificate` resolves
+//! either kind to its file in the store.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::path::{Path, PathBuf};
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const RANDOM_CHARS: usize = 10;
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CertIdError {
+    #[error("'{0}' is not a certificate ID in the TYPE_TIMESTAMP_RANDOM_CHECK form")]
+    Format(String),
+    #[error("'{0}' has an unknown certificate type prefix")]
+    UnknownType(String),
+    #[error("'{0}' has a wrong check character; it may be mistyped")]
+    Checksum(String),
+    #[error("'{0}' can't be a stored certificate's ID")]
+    Unsafe(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CertKind {
+    Backup,
+    Wipe,
+    Destroy,
+    System,
+}
+
+impl CertKind {
+    pub fn prefix(self) -> &'static str {
+        match self {
+            CertKind::Backup => "BCK",
+            CertKind::Wipe => "WPE",
+            CertKind::Destroy => "DST",
+            CertKind::System => "SYS",
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        [CertKind::Backup, CertKind::Wipe, CertKind::Destroy, CertKind::System].into_iter().find(|kind| kind.prefix() == prefix)
+    }
+}
+
+/// What a well-formed ID says about its certificate
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ParsedCertId {
+    pub kind: CertKind,
+    /// When the ID was issued, to the second
+    pub issued_at: String,
+}
+
+/// A new ID for a certificate of `kind`
+pub fn generate(kind: CertKind) -> String {
+    let mut rng = rand::thread_rng();
+    let random: String = (0..RANDOM_CHARS).map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char).collect();
+    compose(kind, Utc::now(), &random)
+}
+
+fn compose(kind: CertKind, at: DateTime<Utc>, random: &str) -> String {
+    let body = format!("{}_{}_{}", kind.prefix(), at.format(TIMESTAMP_FORMAT), random);
+    format!("{}_{}", body, check_char(&body))
+}
+
+/// Luhn mod 32 over the base32 characters of `body`; separators are skipped
+fn check_char(body: &str) -> char {
+    let mut sum = 0;
+    for (i, value) in body.bytes().rev().filter_map(base32_value).enumerate() {
+        let addend = if i % 2 == 0 { value * 2 } else { value };
+        sum += addend / 32 + addend % 32;
+    }
+    ALPHABET[(32 - sum % 32) % 32] as char
+}
+
+fn base32_value(byte: u8) -> Option<usize> {
+    ALPHABET.iter().position(|&c| c == byte)
+}
+
+/// Check an ID and read its type and timestamp
+pub fn parse(id: &str) -> Result<ParsedCertId, CertIdError> {
+    let format_error = || CertIdError::Format(id.to_string());
+    let parts: Vec<&str> = id.split('_').collect();
+    let [prefix, timestamp, random, check] = parts[..] else {
+        return Err(format_error());
+    };
+    let kind = CertKind::from_prefix(prefix).ok_or_else(|| CertIdError::UnknownType(id.to_string()))?;
+    let issued_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).map_err(|_| format_error())?;
+    if random.len() != RANDOM_CHARS || !random.bytes().all(|b| base32_value(b).is_some()) || check.len() != 1 {
+        return Err(format_error());
+    }
+    let body = &id[..id.len() - 2];
+    if !check.starts_with(check_char(body)) {
+        return Err(CertIdError::Checksum(id.to_string()));
+    }
+    Ok(ParsedCertId { kind, issued_at: issued_at.and_utc().to_rfc3339() })
+}
+
+/// Whether `id`, new-scheme or legacy, can name a file in the certificate store: only
+/// letters, digits, `.`, `_` and `-`, and not starting with a dot
+pub fn is_storable(id: &str) -> bool {
+    !id.is_empty() && !id.starts_with('.') && id.bytes().all(|b| b.is_ascii_alphanumeric() || b"._-".contains(&b))
+}
+
+/// `<cert_dir>/<id>.json`, refusing IDs that could point outside the store
+pub fn certificate_path(cert_dir: &Path, id: &str) -> Result<PathBuf, CertIdError> {
+    if !is_storable(id) {
+        return Err(CertIdError::Unsafe(id.to_string()));
+    }
+    Ok(cert_dir.join(format!("{}.json", id)))
+}
+
+/// The stored certificate `id` names. When no such file exists and `id` has the current
+/// form but fails `parse`, that error is returned instead, so a mistyped ID reads as a
+/// typo rather than a missing certificate. Legacy IDs only need to be `is_storable`.
+pub fn find_certificate(cert_dir: &Path, id: &str) -> Result<PathBuf, CertIdError> {
+    let path = certificate_path(cert_dir, id)?;
+    if path.is_file() {
+        return Ok(path);
+    }
+    match parse(id) {
+        Ok(_) | Err(CertIdError::Format(_)) => Ok(path),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_and_parse() {
+        let at = DateTime::parse_from_rfc3339("2025-06-30T12:00:00Z").unwrap().with_timezone(&Utc);
+        let id = compose(CertKind::Wipe, at, "7K3QX9M2PA");
+        assert!(id.starts_with("WPE_20250630T120000Z_7K3QX9M2PA_"));
+        assert_eq!(parse(&id).unwrap(), ParsedCertId { kind: CertKind::Wipe, issued_at: at.to_rfc3339() });
+
+        for kind in [CertKind::Backup, CertKind::Wipe, CertKind::Destroy, CertKind::System] {
+            let id = generate(kind);
+            assert_eq!(parse(&id).unwrap().kind, kind);
+            assert!(is_storable(&id));
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_mistyped_and_legacy_ids() {
+        let at = DateTime::parse_from_rfc3339("2025-06-30T12:00:00Z").unwrap().with_timezone(&Utc);
+        let id = compose(CertKind::Backup, at, "7K3QX9M2PA");
+
+        // Any single changed character is caught
+        let typo = id.replacen("7K3Q", "7K3R", 1);
+        assert_eq!(parse(&typo), Err(CertIdError::Checksum(typo.clone())));
+        let swapped = id.replacen("7K", "K7", 1);
+        assert!(matches!(parse(&swapped), Err(CertIdError::Checksum(_))));
+
+        assert!(matches!(parse(&id.replacen("BCK", "XYZ", 1)), Err(CertIdError::UnknownType(_))));
+        assert!(matches!(parse("WPE_0f6c7a2e2b1d4f439c556d0a1e2b3c4d"), Err(CertIdError::Format(_))));
+        assert!(matches!(parse("0f6c7a2e-2b1d-4f43-9c55-6d0a1e2b3c4d"), Err(CertIdError::Format(_))));
+    }
+
+    #[test]
+    fn test_certificate_path() {
+        let dir = Path::new("/certs");
+        assert_eq!(
+            certificate_path(dir, "0f6c7a2e-2b1d-4f43-9c55-6d0a1e2b3c4d").unwrap(),
+            dir.join("0f6c7a2e-2b1d-4f43-9c55-6d0a1e2b3c4d.json")
+        );
+        assert!(certificate_path(dir, "../../etc/passwd").is_err());
+        assert!(certificate_path(dir, ".hidden").is_err());
+        assert!(certificate_path(dir, "").is_err());
+    }
+
+    #[test]
+    fn test_find_certificate_reports_typos() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = DateTime::parse_from_rfc3339("2025-06-30T12:00:00Z").unwrap().with_timezone(&Utc);
+        let id = compose(CertKind::Wipe, at, "7K3QX9M2PA");
+        std::fs::write(dir.path().join(format!("{}.json", id)), "{}").unwrap();
+
+        assert_eq!(find_certificate(dir.path(), &id).unwrap(), dir.path().join(format!("{}.json", id)));
+        let typo = id.replacen("7K3Q", "7K3R", 1);
+        assert_eq!(find_certificate(dir.path(), &typo), Err(CertIdError::Checksum(typo.clone())));
+        // Legacy and unissued well-formed IDs resolve to a path; reading it reports the miss
+        assert!(find_certificate(dir.path(), "0f6c7a2e-2b1d-4f43-9c55-6d0a1e2b3c4d").is_ok());
+        assert!(find_certificate(dir.path(), &generate(CertKind::Backup)).is_ok());
+        assert!(find_certificate(dir.path(), "../x").is_err());
+    }
+}
@@ -0,0 +1,433 @@
+//! Searchable index over the certificates directory, so the UI can page through
+//! thousands of certificates without reading every JSON file. The index lives in the
+//! inventory database and is brought up to date from the directory before each search:
+//! only files whose modification time changed are parsed again.
+
+use rusqlite::{params, params_from_iter, Connection};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS certificate_index (
+    cert_id TEXT PRIMARY KEY,
+    cert_type TEXT NOT NULL,
+    serial_number TEXT,
+    device_serial TEXT,
+    device_model TEXT,
+    policy TEXT,
+    result TEXT,
+    created_at TEXT NOT NULL,
+    path TEXT NOT NULL UNIQUE,
+    mtime_ns INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS certificate_index_by_created ON certificate_index(created_at);
+";
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+
+/// Free-text and structured filters for `CertIndex::search`. Every field is optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct CertificateQuery {
+    /// Matched case-insensitively against cert id, certificate number, drive serial and model
+    pub text: Option<String>,
+    pub cert_type: Option<String>,
+    pub result: Option<String>,
+    /// NIST level: CLEAR, PURGE or DESTROY
+    pub policy: Option<String>,
+    /// Created at or after (RFC 3339)
+    pub since: Option<String>,
+    /// Created before (RFC 3339)
+    pub until: Option<String>,
+    pub offset: u32,
+    /// Page size, 50 when unset and at most 500
+    pub limit: Option<u32>,
+}
+
+/// One certificate in a search result
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CertificateHit {
+    pub cert_id: String,
+    pub cert_type: String,
+    pub serial_number: Option<String>,
+    pub device_serial: Option<String>,
+    pub device_model: Option<String>,
+    pub policy: Option<String>,
+    pub result: Option<String>,
+    pub created_at: String,
+    /// The certificate JSON
+    pub path: String,
+    /// The exported PDF next to it, when there is one
+    pub pdf_path: Option<String>,
+}
+
+/// One page of search results, newest first
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CertificatePage {
+    /// Matches across all pages
+    pub total: u64,
+    pub offset: u32,
+    pub limit: u32,
+    pub items: Vec<CertificateHit>,
+}
+
+/// Where the index and the certificates directory disagree; empty right after `refresh`
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct IndexDrift {
+    /// Certificates on disk the index doesn't have
+    pub unindexed: Vec<String>,
+    /// Indexed certificates modified since they were indexed
+    pub stale: Vec<String>,
+    /// Index rows whose file is gone
+    pub dangling: Vec<String>,
+}
+
+impl IndexDrift {
+    pub fn is_empty(&self) -> bool {
+        self.unindexed.is_empty() && self.stale.is_empty() && self.dangling.is_empty()
+    }
+}
+
+pub struct CertIndex {
+    conn: Connection,
+    dir: PathBuf,
+}
+
+impl CertIndex {
+    /// Open (creating if needed) the index in the database at `db_path` for the
+    /// certificates in `dir`
+    pub fn open(db_path: &Path, dir: &Path) -> Result<Self, Box<dyn Error>> {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::with_connection(Connection::open(db_path)?, dir)
+    }
+
+    /// The configured inventory database and certificates directory
+    pub fn open_default() -> Result<Self, Box<dyn Error>> {
+        let config = crate::config::Config::load()?;
+        Self::open(&config.inventory_db, &config.certificates_dir)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::with_connection(Connection::open_in_memory()?, dir)
+    }
+
+    fn with_connection(conn: Connection, dir: &Path) -> Result<Self, Box<dyn Error>> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(CertIndex { conn, dir: dir.to_path_buf() })
+    }
+
+    /// Bring the index up to date with the certificates directory: new and modified
+    /// files are parsed, removed ones dropped. JSON without cert_id and cert_type
+    /// (serial counters, sidecars) is ignored. Returns how many rows changed.
+    pub fn refresh(&mut self) -> Result<usize, Box<dyn Error>> {
+        let mut known = self.known_paths()?;
+
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return self.remove_paths(known.into_keys()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let tx = self.conn.transaction()?;
+        let mut changed = 0;
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let path_text = path.to_string_lossy().to_string();
+            let mtime_ns = modified_ns(&path);
+            if known.remove(&path_text) == Some(mtime_ns) {
+                continue;
+            }
+
+            let cert = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+            let text = |pointer: &str| {
+                cert.as_ref()
+                    .and_then(|cert| cert.pointer(pointer))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            };
+            let (cert_id, cert_type) = match (text("/cert_id"), text("/cert_type")) {
+                (Some(cert_id), Some(cert_type)) => (cert_id, cert_type),
+                _ => {
+                    changed += tx.execute("DELETE FROM certificate_index WHERE path = ?1", params![path_text])?;
+                    continue;
+                }
+            };
+
+            tx.execute("DELETE FROM certificate_index WHERE path = ?1", params![path_text])?;
+            tx.execute(
+                "INSERT OR REPLACE INTO certificate_index
+                 (cert_id, cert_type, serial_number, device_serial, device_model, policy, result, created_at, path, mtime_ns)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    cert_id,
+                    cert_type,
+                    text("/serial_number"),
+                    text("/device/serial"),
+                    text("/device/model"),
+                    text("/policy/nist_level"),
+                    text("/result"),
+                    text("/created_at").map(|at| normalize_time(&at).unwrap_or(at)).unwrap_or_default(),
+                    path_text,
+                    mtime_ns
+                ],
+            )?;
+            changed += 1;
+        }
+        tx.commit()?;
+
+        Ok(changed + self.remove_paths(known.into_keys())?)
+    }
+
+    /// Compare the index with the certificates directory without changing either
+    pub fn drift(&self) -> Result<IndexDrift, Box<dyn Error>> {
+        let mut known = self.known_paths()?;
+        let mut drift = IndexDrift::default();
+
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => Some(entries),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        for path in entries.into_iter().flatten().flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let path_text = path.to_string_lossy().to_string();
+            match known.remove(&path_text) {
+                Some(mtime_ns) if mtime_ns == modified_ns(&path) => {}
+                Some(_) => drift.stale.push(path_text),
+                // Only what refresh would index counts as missing from it
+                None => {
+                    let cert = std::fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+                    if cert.is_some_and(|cert| cert["cert_id"].is_string() && cert["cert_type"].is_string()) {
+                        drift.unindexed.push(path_text);
+                    }
+                }
+            }
+        }
+        drift.dangling = known.into_keys().collect();
+        drift.unindexed.sort();
+        drift.stale.sort();
+        drift.dangling.sort();
+        Ok(drift)
+    }
+
+    fn known_paths(&self) -> Result<HashMap<String, i64>, Box<dyn Error>> {
+        let mut statement = self.conn.prepare("SELECT path, mtime_ns FROM certificate_index")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// One page of certificates matching `query`, newest first
+    pub fn search(&self, query: &CertificateQuery) -> Result<CertificatePage, Box<dyn Error>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+        if let Some(text) = query.text.as_deref().map(str::trim).filter(|text| !text.is_empty()) {
+            values.push(format!("%{}%", escape_like(text)));
+            let n = values.len();
+            conditions.push(format!(
+                "(cert_id LIKE ?{n} ESCAPE '\\' OR serial_number LIKE ?{n} ESCAPE '\\' \
+                 OR device_serial LIKE ?{n} ESCAPE '\\' OR device_model LIKE ?{n} ESCAPE '\\')"
+            ));
+        }
+        for (column, op, value) in [
+            ("cert_type", "=", &query.cert_type),
+            ("result", "=", &query.result),
+            ("policy", "=", &query.policy),
+            ("created_at", ">=", &query.since),
+            ("created_at", "<", &query.until),
+        ] {
+            if let Some(value) = value {
+                let value = if column == "created_at" {
+                    normalize_time(value).ok_or_else(|| format!("invalid date {}, expected RFC 3339", value))?
+                } else {
+                    value.clone()
+                };
+                values.push(value);
+                conditions.push(format!("{} {} ?{}", column, op, values.len()));
+            }
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let total: i64 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM certificate_index {}", filter),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT cert_id, cert_type, serial_number, device_serial, device_model, policy, result, created_at, path
+             FROM certificate_index {} ORDER BY created_at DESC, cert_id LIMIT {} OFFSET {}",
+            filter, limit, query.offset
+        ))?;
+        let rows = statement.query_map(params_from_iter(values.iter()), |row| {
+            let path: String = row.get(8)?;
+            let pdf = Path::new(&path).with_extension("pdf");
+            Ok(CertificateHit {
+                cert_id: row.get(0)?,
+                cert_type: row.get(1)?,
+                serial_number: row.get(2)?,
+                device_serial: row.get(3)?,
+                device_model: row.get(4)?,
+                policy: row.get(5)?,
+                result: row.get(6)?,
+                created_at: row.get(7)?,
+                pdf_path: pdf.exists().then(|| pdf.to_string_lossy().to_string()),
+                path,
+            })
+        })?;
+
+        Ok(CertificatePage {
+            total: total as u64,
+            offset: query.offset,
+            limit,
+            items: rows.collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+
+    fn remove_paths(&self, paths: impl Iterator<Item = String>) -> Result<usize, Box<dyn Error>> {
+        let mut removed = 0;
+        for path in paths {
+            removed += self.conn.execute("DELETE FROM certificate_index WHERE path = ?1", params![path])?;
+        }
+        Ok(removed)
+    }
+}
+
+fn modified_ns(path: &Path) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|at| at.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since| since.as_nanos() as i64)
+        .unwrap_or(0)
+}
+
+/// UTC with fixed-width nanoseconds, so stored times and filter bounds compare as text
+fn normalize_time(value: &str) -> Option<String> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|at| at.with_timezone(&chrono::Utc).to_rfc3339_opts(chrono::SecondsFormat::Nanos, true))
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn write_cert(dir: &Path, cert_id: &str, cert_type: &str, model: &str, result: &str, created_at: &str) {
+        let cert = json!({
+            "cert_id": cert_id,
+            "cert_type": cert_type,
+            "serial_number": format!("SIH-2025-{}", &cert_id[cert_id.len() - 6..]),
+            "created_at": created_at,
+            "device": {"model": model, "serial": format!("SN-{}", cert_id)},
+            "policy": {"nist_level": "PURGE"},
+            "result": result
+        });
+        std::fs::write(dir.join(format!("{}.json", cert_id)), cert.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_cert_index_search_and_paging() {
+        let dir = TempDir::new().unwrap();
+        write_cert(dir.path(), "wipe-000001", "wipe", "Samsung SSD 870", "PASS", "2025-01-01T00:00:00Z");
+        write_cert(dir.path(), "wipe-000002", "wipe", "WDC WD10EZEX", "FAIL", "2025-02-01T00:00:00Z");
+        write_cert(dir.path(), "backup-000003", "backup", "Samsung SSD 870", "PASS", "2025-03-01T00:00:00Z");
+        std::fs::write(dir.path().join("serials.json"), "{\"SIH\": 3}").unwrap();
+
+        let mut index = CertIndex::open_in_memory(dir.path()).unwrap();
+        assert_eq!(index.refresh().unwrap(), 3);
+        assert_eq!(index.refresh().unwrap(), 0);
+
+        let all = index.search(&CertificateQuery::default()).unwrap();
+        assert_eq!(all.total, 3);
+        assert_eq!(all.items[0].cert_id, "backup-000003");
+
+        let samsung = index.search(&CertificateQuery { text: Some("samsung".into()), ..Default::default() }).unwrap();
+        assert_eq!(samsung.total, 2);
+
+        let by_number = index.search(&CertificateQuery { text: Some("SIH-2025-000002".into()), ..Default::default() }).unwrap();
+        assert_eq!(by_number.items[0].cert_id, "wipe-000002");
+
+        let wipes_passed = CertificateQuery {
+            cert_type: Some("wipe".into()),
+            result: Some("PASS".into()),
+            ..Default::default()
+        };
+        assert_eq!(index.search(&wipes_passed).unwrap().items[0].cert_id, "wipe-000001");
+
+        let page = index.search(&CertificateQuery { offset: 1, limit: Some(1), ..Default::default() }).unwrap();
+        assert_eq!((page.total, page.items.len()), (3, 1));
+        assert_eq!(page.items[0].cert_id, "wipe-000002");
+
+        let since = CertificateQuery { since: Some("2025-02-01T01:00:00+01:00".into()), ..Default::default() };
+        assert_eq!(index.search(&since).unwrap().total, 2);
+        assert!(index.search(&CertificateQuery { until: Some("March".into()), ..Default::default() }).is_err());
+
+        // A literal '%' matches nothing rather than everything
+        assert_eq!(index.search(&CertificateQuery { text: Some("%".into()), ..Default::default() }).unwrap().total, 0);
+    }
+
+    #[test]
+    fn test_cert_index_refresh_drops_removed_files() {
+        let dir = TempDir::new().unwrap();
+        write_cert(dir.path(), "wipe-000001", "wipe", "Samsung SSD 870", "PASS", "2025-01-01T00:00:00Z");
+        write_cert(dir.path(), "wipe-000002", "wipe", "WDC WD10EZEX", "FAIL", "2025-02-01T00:00:00Z");
+
+        let mut index = CertIndex::open_in_memory(dir.path()).unwrap();
+        index.refresh().unwrap();
+        std::fs::remove_file(dir.path().join("wipe-000001.json")).unwrap();
+        assert_eq!(index.refresh().unwrap(), 1);
+
+        let page = index.search(&CertificateQuery::default()).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].cert_id, "wipe-000002");
+    }
+
+    #[test]
+    fn test_cert_index_drift() {
+        let dir = TempDir::new().unwrap();
+        write_cert(dir.path(), "wipe-000001", "wipe", "Samsung SSD 870", "PASS", "2025-01-01T00:00:00Z");
+        write_cert(dir.path(), "wipe-000002", "wipe", "WDC WD10EZEX", "FAIL", "2025-02-01T00:00:00Z");
+        std::fs::write(dir.path().join("serials.json"), "{\"SIH\": 3}").unwrap();
+
+        let mut index = CertIndex::open_in_memory(dir.path()).unwrap();
+        assert_eq!(index.drift().unwrap().unindexed.len(), 2);
+        index.refresh().unwrap();
+        assert!(index.drift().unwrap().is_empty());
+
+        std::fs::remove_file(dir.path().join("wipe-000001.json")).unwrap();
+        write_cert(dir.path(), "backup-000003", "backup", "Samsung SSD 870", "PASS", "2025-03-01T00:00:00Z");
+        let drift = index.drift().unwrap();
+        assert_eq!(drift.unindexed, [dir.path().join("backup-000003.json").to_string_lossy()]);
+        assert_eq!(drift.dangling, [dir.path().join("wipe-000001.json").to_string_lossy()]);
+
+        index.refresh().unwrap();
+        assert!(index.drift().unwrap().is_empty());
+    }
+}
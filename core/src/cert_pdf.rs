@@ -1,14 +1,44 @@
 use crate::cert::{BackupCertificate, WipeCertificate};
+use crate::cleanup::Artifact;
 use crate::pdf::{PdfGenerator, ensure_certificates_dir};
 use anyhow::Result;
 use std::path::PathBuf;
 use std::process::Command;
-use tracing::{info, warn};
+use tracing::info;
+
+/// How much a certificate PDF carries beyond the certificate page itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PdfDetail {
+    /// Appendix with every executed command (wipe certificates), so the paper copy
+    /// stands alone in an audit
+    #[default]
+    Full,
+    /// Certificate page only, with a one-line command summary
+    Summary,
+}
+
+impl PdfDetail {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PdfDetail::Full => "full",
+            PdfDetail::Summary => "summary",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "full" => Some(PdfDetail::Full),
+            "summary" => Some(PdfDetail::Summary),
+            _ => None,
+        }
+    }
+}
 
 /// High-level PDF certificate generation functions
 pub struct CertificatePdfGenerator {
     verify_base_url: Option<String>,
     use_python_generator: bool,
+    detail: PdfDetail,
 }
 
 impl CertificatePdfGenerator {
@@ -16,6 +46,7 @@ impl CertificatePdfGenerator {
         Self { 
             verify_base_url,
             use_python_generator: true, // Default to Python for high quality
+            detail: PdfDetail::default(),
         }
     }
 
@@ -23,10 +54,18 @@ impl CertificatePdfGenerator {
         Self { 
             verify_base_url,
             use_python_generator: false,
+            detail: PdfDetail::default(),
         }
     }
 
+    /// Choose whether PDFs get the command log appendix (Python generator only)
+    pub fn with_detail(mut self, detail: PdfDetail) -> Self {
+        self.detail = detail;
+        self
+    }
+
     /// Generate PDF for backup certificate and save to standard location
+    #[tracing::instrument(name = "pdf", skip_all)]
     pub fn generate_backup_certificate_pdf(
         &self,
         cert: &BackupCertificate,
@@ -47,6 +86,7 @@ impl CertificatePdfGenerator {
     }
 
     /// Generate PDF for backup certificate from JSON string (bypasses struct deserialization issues)
+    #[tracing::instrument(name = "pdf", skip_all)]
     pub fn generate_backup_pdf_from_json(
         &self,
         cert_json: &str,
@@ -67,6 +107,7 @@ impl CertificatePdfGenerator {
     }
 
     /// Generate PDF for wipe certificate and save to standard location
+    #[tracing::instrument(name = "pdf", skip_all)]
     pub fn generate_wipe_certificate_pdf(
         &self,
         cert: &WipeCertificate,
@@ -84,6 +125,7 @@ impl CertificatePdfGenerator {
     }
 
     /// Generate PDF for wipe certificate from JSON string (bypasses struct deserialization issues)
+    #[tracing::instrument(name = "pdf", skip_all)]
     pub fn generate_wipe_pdf_from_json(
         &self,
         cert_json: &str,
@@ -103,6 +145,64 @@ impl CertificatePdfGenerator {
         self.call_python_generator(cert_json, &certs_dir.join(format!("{}.pdf", cert_id)), "wipe")
     }
 
+    /// Generate PDF for a physical destruction certificate from JSON
+    #[tracing::instrument(name = "pdf", skip_all)]
+    pub fn generate_destroy_pdf_from_json(
+        &self,
+        cert_json: &str,
+    ) -> Result<PathBuf> {
+        let cert_value: serde_json::Value = serde_json::from_str(cert_json)?;
+        let cert_id = cert_value.get("cert_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing cert_id in certificate"))?;
+            
+        info!(cert_id = %cert_id, "Generating destroy certificate PDF from JSON");
+        
+        let certs_dir = ensure_certificates_dir()?;
+        self.call_python_generator(cert_json, &certs_dir.join(format!("{}.pdf", cert_id)), "destroy")
+    }
+
+    /// Generate PDF for a system sanitization certificate from JSON
+    #[tracing::instrument(name = "pdf", skip_all)]
+    pub fn generate_system_pdf_from_json(
+        &self,
+        cert_json: &str,
+    ) -> Result<PathBuf> {
+        let cert_value: serde_json::Value = serde_json::from_str(cert_json)?;
+        let cert_id = cert_value.get("cert_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing cert_id in certificate"))?;
+            
+        info!(cert_id = %cert_id, "Generating system certificate PDF from JSON");
+        
+        let certs_dir = ensure_certificates_dir()?;
+        self.call_python_generator(cert_json, &certs_dir.join(format!("{}.pdf", cert_id)), "system")
+    }
+
+    /// Generate PDF for a backup, wipe, destroy or system certificate JSON at a caller-chosen path
+    #[tracing::instrument(name = "pdf", skip_all)]
+    pub fn generate_pdf_from_json_to(
+        &self,
+        cert_json: &str,
+        output_path: &std::path::Path,
+    ) -> Result<PathBuf> {
+        let cert_value: serde_json::Value = serde_json::from_str(cert_json)?;
+        let cert_type = cert_value.get("cert_type")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid certificate: missing cert_type"))?;
+        
+        if !matches!(cert_type, "backup" | "wipe" | "destroy" | "system") {
+            anyhow::bail!("Unsupported certificate type: {}", cert_type);
+        }
+        
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        
+        info!(cert_type = %cert_type, output = %output_path.display(), "Generating certificate PDF at chosen path");
+        self.call_python_generator(cert_json, output_path, cert_type)
+    }
+
     /// Generate PDF for certificate from JSON and certificate type
     pub fn generate_certificate_pdf_from_json(
         &self,
@@ -149,6 +249,7 @@ impl CertificatePdfGenerator {
     }
 
     /// Call the Python PDF generator script
+    #[tracing::instrument(name = "python_generator", skip_all, fields(cert_type = %cert_type))]
     fn call_python_generator(
         &self,
         cert_json: &str,
@@ -159,12 +260,12 @@ impl CertificatePdfGenerator {
         
         info!("Using Python PDF generator for high-quality output");
         
-        // Create temporary certificate file
-        let temp_dir = std::env::temp_dir();
-        let temp_cert_file = temp_dir.join(format!("cert_{}.json", uuid::Uuid::new_v4()));
+        // Create temporary certificate file, removed however this returns
+        let temp_cert = Artifact::temp_file(std::env::temp_dir().join(format!("cert_{}.json", uuid::Uuid::new_v4())));
+        let temp_cert_file = temp_cert.path();
         
         {
-            let mut file = std::fs::File::create(&temp_cert_file)?;
+            let mut file = std::fs::File::create(temp_cert_file)?;
             file.write_all(cert_json.as_bytes())?;
             file.flush()?;
         }
@@ -196,6 +297,10 @@ impl CertificatePdfGenerator {
             .arg(output_path)
             .arg("--type")
             .arg(cert_type)
+            .arg("--detail")
+            .arg(self.detail.as_str())
+            .arg("--format")
+            .arg(serde_json::to_string(&crate::locale::DisplayFormat::current())?)
             .arg("--no-validate")
             .current_dir(&project_root)
             .output()
@@ -203,10 +308,7 @@ impl CertificatePdfGenerator {
                 anyhow::anyhow!("Failed to execute Python PDF generator: {}. Make sure python3 is available and required packages are installed (reportlab, jsonschema, qrcode[pil])", e)
             })?;
         
-        // Clean up temp file
-        if let Err(e) = std::fs::remove_file(&temp_cert_file) {
-            warn!("Failed to clean up temp file: {}", e);
-        }
+        drop(temp_cert);
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -266,70 +368,107 @@ pub fn generate_wipe_pdf(
     generator.generate_wipe_certificate_pdf(cert)
 }
 
+/// Export a stored certificate's PDF: rendered with the configured issuer's verify URL,
+/// then, when `signing_key` is given, stamped over its bytes with the stamp recorded in
+/// the audit log. Without `output_path` the PDF goes to the certificates directory.
+/// `cert --export-pdf` and the GUI both export through here.
+pub fn export_certificate_pdf(
+    cert_json: &str,
+    output_path: Option<&std::path::Path>,
+    detail: PdfDetail,
+    signing_key: Option<&ed25519_dalek::SigningKey>,
+) -> Result<PathBuf> {
+    let cert_value: serde_json::Value = serde_json::from_str(cert_json)?;
+    let cert_id = cert_value.get("cert_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing cert_id in certificate"))?;
+    let cert_type = cert_value.get("cert_type")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid certificate: missing cert_type"))?;
+
+    let identity = crate::cert::IssuerIdentity::current()?;
+    // Generic JSON rendering avoids struct definition conflicts between lib and bin
+    let generator = CertificatePdfGenerator::new(Some(identity.verify_base_url)).with_detail(detail);
+    let pdf_path = match (output_path, cert_type) {
+        (Some(output_path), _) => generator.generate_pdf_from_json_to(cert_json, output_path)?,
+        (None, "backup") => generator.generate_backup_pdf_from_json(cert_json)?,
+        (None, "wipe") => generator.generate_wipe_pdf_from_json(cert_json)?,
+        (None, "destroy") => generator.generate_destroy_pdf_from_json(cert_json)?,
+        (None, "system") => generator.generate_system_pdf_from_json(cert_json)?,
+        (None, _) => anyhow::bail!("Unsupported certificate type: {}", cert_type),
+    };
+
+    if let Some(signing_key) = signing_key {
+        let stamped = crate::pdf_stamp::stamp(&std::fs::read(&pdf_path)?, cert_id, signing_key)
+            .map_err(|e| anyhow::anyhow!("Failed to stamp PDF: {}", e))?;
+        crate::audit::record_signature(&format!("pdf:{}", cert_id), signing_key)
+            .map_err(|e| anyhow::anyhow!("Failed to record PDF signature: {}", e))?;
+        std::fs::write(&pdf_path, stamped)?;
+    }
+    Ok(pdf_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cert::CertificateSignature;
 
     fn create_test_backup_cert() -> BackupCertificate {
-        BackupCertificate {
-            cert_id: "test_backup_pdf_integration_123".to_string(),
-            cert_type: "backup".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: "2023-12-05T14:30:22.123456Z".to_string(),
-            issuer: serde_json::json!({"organization": "SecureWipe (SIH)"}),
-            device: serde_json::json!({
+        serde_json::from_value(serde_json::json!({
+            "cert_id": "test_backup_pdf_integration_123",
+            "cert_type": "backup",
+            "certificate_version": "v1.0.0",
+            "created_at": "2023-12-05T14:30:22.123456Z",
+            "issuer": {"organization": "SecureWipe (SIH)", "tool_name": "securewipe", "tool_version": "v1.0.0"},
+            "device": {
                 "model": "Test SSD 1TB",
                 "serial": "TEST123456",
+                "bus": "NVMe",
                 "capacity_bytes": 1000000000000u64
-            }),
-            files_summary: serde_json::json!({
-                "count": 100,
-                "personal_bytes": 500000000u64
-            }),
-            destination: serde_json::json!({"type": "other"}),
-            crypto: serde_json::json!({"alg": "AES-256-CTR", "manifest_sha256": "a1b2c3d4e5f67890123456789012345678901234567890123456789012345678"}),
-            verification: serde_json::json!({"strategy": "sampled_files"}),
-            policy: serde_json::json!({"name": "NIST SP 800-88 Rev.1"}),
-            result: "PASS".to_string(),
-            environment: serde_json::json!({"operator": "test"}),
-            exceptions: serde_json::json!({"text": "None"}),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "test_signature_data_here".to_string(),
-            }),
-            metadata: serde_json::json!({}),
-            verify_url: "http://localhost:8000/verify".to_string(),
-        }
+            },
+            "files_summary": {"count": 100, "personal_bytes": 500000000u64},
+            "destination": {"type": "other"},
+            "crypto": {
+                "alg": "AES-256-CTR",
+                "manifest_sha256": "a1b2c3d4e5f67890123456789012345678901234567890123456789012345678",
+                "key_management": "ephemeral_session_key"
+            },
+            "verification": {"strategy": "sampled_files", "failures": 0},
+            "policy": {"name": "NIST SP 800-88 Rev.1", "version": "2023.12"},
+            "result": "PASS",
+            "environment": {"operator": "test", "os_kernel": "Linux 6.8.0", "tool_version": "v1.0.0"},
+            "exceptions": {"text": "None"},
+            "signature": {"alg": "Ed25519", "pubkey_id": "sih_root_v1", "sig": "test_signature_data_here"},
+            "metadata": {},
+            "verify_url": "http://localhost:8000/verify"
+        }))
+        .unwrap()
     }
 
     fn create_test_wipe_cert() -> WipeCertificate {
-        WipeCertificate {
-            cert_id: "test_wipe_pdf_integration_456".to_string(),
-            cert_type: "wipe".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: "2023-12-05T15:00:30.654321Z".to_string(),
-            device: serde_json::json!({
+        serde_json::from_value(serde_json::json!({
+            "cert_id": "test_wipe_pdf_integration_456",
+            "cert_type": "wipe",
+            "certificate_version": "v1.0.0",
+            "created_at": "2023-12-05T15:00:30.654321Z",
+            "issuer": {"organization": "SecureWipe (SIH)", "tool_name": "securewipe", "tool_version": "v1.0.0"},
+            "device": {
                 "model": "Test SSD 1TB",
                 "serial": "TEST123456",
+                "bus": "NVMe",
                 "capacity_bytes": 1000000000000u64
-            }),
-            wipe_summary: serde_json::json!({
-                "policy": "PURGE",
-                "method": "nvme_sanitize",
-                "verification_samples": 5,
-                "verification_passed": true
-            }),
-            linkage: Some(serde_json::json!({
-                "backup_cert_id": "test_backup_123"
-            })),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "test_wipe_signature_data_here".to_string(),
-            }),
-        }
+            },
+            "policy": {"nist_level": "PURGE", "method": "nvme_sanitize"},
+            "commands": [],
+            "verify": {"strategy": "controller_status", "samples": 5, "failures": 0, "result": "PASS"},
+            "result": "PASS",
+            "environment": {"operator": "test", "os_kernel": "Linux 6.8.0", "tool_version": "v1.0.0"},
+            "evidence": {},
+            "linkage": {"backup_cert_id": "test_backup_123"},
+            "exceptions": {"text": "None"},
+            "signature": {"alg": "Ed25519", "pubkey_id": "sih_root_v1", "sig": "test_wipe_signature_data_here"},
+            "metadata": {}
+        }))
+        .unwrap()
     }
 
     #[test]
@@ -400,4 +539,13 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().exists());
     }
+
+    #[test]
+    fn test_pdf_detail_parse() {
+        assert_eq!(PdfDetail::default(), PdfDetail::Full);
+        for detail in [PdfDetail::Full, PdfDetail::Summary] {
+            assert_eq!(PdfDetail::parse(detail.as_str()), Some(detail));
+        }
+        assert_eq!(PdfDetail::parse("verbose"), None);
+    }
 }
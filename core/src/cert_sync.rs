@@ -0,0 +1,524 @@
+//! Incremental certificate sync for air-gapped sites. `cert export-delta` packs the
+//! certificates (and their `.jsonl` sidecars) that changed since a cursor into one
+//! signed bundle file for sneakernet transfer; `cert import-delta` on the central
+//! audit server checks the bundle and every certificate in it against the issuer's
+//! key, then adds them to its certificates directory.
+//!
+//! A cursor is the modification time and name of the last file a bundle carried, so
+//! a sidecar that grew since is carried again. The exporting site keeps its cursor
+//! in `sync.cursor` in the data directory; the importing server keeps the cursor it
+//! has reached for each site in `sync_state.json`, so a bundle that would leave a gap
+//! is refused and one that was already imported changes nothing. Exports can be capped
+//! by file count and size; the rest follows in the next bundle.
+
+use crate::config::Config;
+use chrono::Utc;
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub const BUNDLE_FORMAT: &str = "securewipe-cert-delta/1";
+/// The exporting site's cursor, in the data directory
+pub const CURSOR_FILE: &str = "sync.cursor";
+const STATE_FILE: &str = "sync_state.json";
+const LOCK_FILE: &str = "sync_state.lock";
+
+/// Events recorded in the audit log
+pub const DELTA_EXPORTED_EVENT: &str = "cert_delta_exported";
+pub const DELTA_IMPORTED_EVENT: &str = "cert_delta_imported";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    #[error("{0}: {1}")]
+    Io(String, String),
+    #[error("Invalid cursor {0:?}: expected v1:<mtime_ns>:<file name> or a cursor file")]
+    Cursor(String),
+    #[error("Invalid delta bundle: {0}")]
+    Bundle(String),
+    #[error("Signature check failed: {0}")]
+    Signature(String),
+    #[error("Bundle from {site} continues from {since}, but this server has imported {reached}; import the bundles in between first, or pass --allow-gap")]
+    Gap { site: String, since: String, reached: String },
+    #[error("{0} already exists here with different content")]
+    Conflict(String),
+    #[error("Sync state error: {0}")]
+    Store(String),
+}
+
+fn io_error(path: &Path, e: impl fmt::Display) -> SyncError {
+    SyncError::Io(path.display().to_string(), e.to_string())
+}
+
+/// Position in a site's certificates directory: everything up to and including
+/// this file has been exported
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cursor {
+    mtime_ns: i128,
+    name: String,
+}
+
+impl Cursor {
+    pub fn parse(value: &str) -> Result<Self, SyncError> {
+        let invalid = || SyncError::Cursor(value.to_string());
+        let rest = value.trim().strip_prefix("v1:").ok_or_else(invalid)?;
+        let (mtime, name) = rest.split_once(':').ok_or_else(invalid)?;
+        let mtime_ns = mtime.parse().map_err(|_| invalid())?;
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self { mtime_ns, name: name.to_string() })
+    }
+
+    /// A cursor given on the command line: the token itself, or a file holding it
+    pub fn resolve(value: &str) -> Result<Self, SyncError> {
+        let path = Path::new(value);
+        if path.is_file() {
+            let content = fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+            return Self::parse(&content);
+        }
+        Self::parse(value)
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v1:{}:{}", self.mtime_ns, self.name)
+    }
+}
+
+/// One file carried in a bundle, byte for byte
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BundleFile {
+    pub name: String,
+    pub sha256: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeltaBundle {
+    pub format: String,
+    pub site: String,
+    /// Cursor the bundle continues from; none for a site's first bundle
+    pub since: Option<String>,
+    /// Cursor after the last file in the bundle
+    pub cursor: String,
+    /// Changed files left for the next bundle by --max-files or --max-bytes
+    pub remaining: usize,
+    pub created_at: String,
+    pub files: Vec<BundleFile>,
+    /// Ed25519 over the rest of the bundle, as for certificates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Value>,
+}
+
+/// Caps on a single bundle; a bundle always carries at least one file
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    pub max_files: Option<usize>,
+    pub max_bytes: Option<u64>,
+}
+
+/// The hostname, the default site name for bundles
+pub fn default_site() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "local".to_string())
+}
+
+/// Certificates and sidecars in `cert_dir` changed after `since`, oldest first
+fn changed_files(cert_dir: &Path, since: Option<&Cursor>) -> Result<Vec<(Cursor, PathBuf)>, SyncError> {
+    let mut changed = Vec::new();
+    for entry in fs::read_dir(cert_dir).map_err(|e| io_error(cert_dir, e))?.flatten() {
+        let path = entry.path();
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else { continue };
+        if !is_synced(&name, &path) {
+            continue;
+        }
+        let modified = entry.metadata().and_then(|meta| meta.modified()).map_err(|e| io_error(&path, e))?;
+        let mtime_ns = modified.duration_since(UNIX_EPOCH).map(|age| age.as_nanos() as i128).unwrap_or(0);
+        let cursor = Cursor { mtime_ns, name };
+        if since.is_none_or(|since| cursor > *since) {
+            changed.push((cursor, path));
+        }
+    }
+    changed.sort();
+    Ok(changed)
+}
+
+/// Certificates (JSON with a cert_type) and the append-only sidecars next to them;
+/// other JSON in the directory, such as the serial counters, stays local
+fn is_synced(name: &str, path: &Path) -> bool {
+    if name.starts_with('.') {
+        return false;
+    }
+    if name.ends_with(".jsonl") {
+        return true;
+    }
+    name.ends_with(".json")
+        && fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+            .is_some_and(|value| value.get("cert_type").is_some())
+}
+
+/// A signed bundle of what changed in `cert_dir` after `since`, or `None` when
+/// nothing did. The signature is recorded in the audit log as `bundle:<cursor>`.
+pub fn export_delta(
+    cert_dir: &Path,
+    site: &str,
+    since: Option<&Cursor>,
+    limits: Limits,
+    signing_key: &SigningKey,
+) -> Result<Option<DeltaBundle>, SyncError> {
+    let changed = changed_files(cert_dir, since)?;
+    let mut files = Vec::new();
+    let mut cursor = None;
+    let mut bytes = 0u64;
+    for (position, path) in &changed {
+        let content = fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+        let full = limits.max_files.is_some_and(|max| files.len() >= max)
+            || limits.max_bytes.is_some_and(|max| bytes + content.len() as u64 > max);
+        if full && !files.is_empty() {
+            break;
+        }
+        bytes += content.len() as u64;
+        files.push(BundleFile { name: position.name.clone(), sha256: sha256_hex(content.as_bytes()), content });
+        cursor = Some(position.clone());
+    }
+    let Some(cursor) = cursor else { return Ok(None) };
+
+    let bundle = DeltaBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        site: site.to_string(),
+        since: since.map(Cursor::to_string),
+        cursor: cursor.to_string(),
+        remaining: changed.len() - files.len(),
+        created_at: Utc::now().to_rfc3339(),
+        files,
+        signature: None,
+    };
+    let mut value = serde_json::to_value(&bundle).map_err(|e| SyncError::Bundle(e.to_string()))?;
+    crate::signer::sign_certificate(&mut value, signing_key, false)
+        .map_err(|e| SyncError::Signature(e.to_string()))?;
+    crate::audit::record_signature(&format!("bundle:{}", cursor), signing_key)
+        .map_err(|e| SyncError::Signature(format!("could not record the bundle signature: {}", e)))?;
+    serde_json::from_value(value).map(Some).map_err(|e| SyncError::Bundle(e.to_string()))
+}
+
+pub fn write_bundle(bundle: &DeltaBundle, path: &Path) -> Result<(), SyncError> {
+    let json = serde_json::to_string_pretty(bundle).map_err(|e| SyncError::Bundle(e.to_string()))?;
+    write_synced(path, json.as_bytes())
+}
+
+pub fn read_bundle(path: &Path) -> Result<DeltaBundle, SyncError> {
+    let content = fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+    serde_json::from_str(&content).map_err(|e| SyncError::Bundle(format!("{}: {}", path.display(), e)))
+}
+
+/// Check the bundle's signature, its cursors, every file's hash, and the signature
+/// of every signed certificate in it. Returns the certificates that carry no signature.
+pub fn verify_bundle(bundle: &DeltaBundle, public_key: &[u8; 32]) -> Result<Vec<String>, SyncError> {
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(SyncError::Bundle(format!("unknown format {:?}", bundle.format)));
+    }
+    let value = serde_json::to_value(bundle).map_err(|e| SyncError::Bundle(e.to_string()))?;
+    match crate::signer::verify_certificate_signature(&value, public_key) {
+        Ok(true) => {}
+        Ok(false) => return Err(SyncError::Signature("the bundle was altered or signed by another key".to_string())),
+        Err(e) => return Err(SyncError::Signature(e.to_string())),
+    }
+    bundle.since.as_deref().map(Cursor::parse).transpose()?;
+    Cursor::parse(&bundle.cursor)?;
+
+    let mut unsigned = Vec::new();
+    for file in &bundle.files {
+        let plain = !file.name.is_empty() && !file.name.starts_with('.') && !file.name.contains(['/', '\\']);
+        if !plain || !(file.name.ends_with(".json") || file.name.ends_with(".jsonl")) {
+            return Err(SyncError::Bundle(format!("unexpected file name {:?}", file.name)));
+        }
+        if sha256_hex(file.content.as_bytes()) != file.sha256 {
+            return Err(SyncError::Bundle(format!("{} does not match its sha256", file.name)));
+        }
+        if !file.name.ends_with(".json") {
+            continue;
+        }
+        let cert: Value = serde_json::from_str(&file.content)
+            .map_err(|e| SyncError::Bundle(format!("{}: {}", file.name, e)))?;
+        if cert.get("signature").is_none() {
+            unsigned.push(file.name.clone());
+            continue;
+        }
+        match crate::signer::verify_certificate_signature(&cert, public_key) {
+            Ok(true) => {}
+            Ok(false) => return Err(SyncError::Signature(format!("{} has an invalid signature", file.name))),
+            Err(e) => return Err(SyncError::Signature(format!("{}: {}", file.name, e))),
+        }
+    }
+    Ok(unsigned)
+}
+
+/// How far the central server has imported one site's bundles
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SiteState {
+    pub cursor: String,
+    pub imported_at: String,
+    pub bundles: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Imported,
+    /// The server had already reached the bundle's cursor; nothing was written
+    AlreadyImported,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ImportOutcome {
+    pub site: String,
+    pub status: ImportStatus,
+    pub since: Option<String>,
+    pub cursor: String,
+    /// Files added, or sidecars that grew
+    pub written: Vec<String>,
+    /// Files already here with the same content
+    pub unchanged: Vec<String>,
+    /// Certificates that carry no signature
+    pub unsigned: Vec<String>,
+}
+
+/// Per-site import progress in a data directory
+pub struct SyncState {
+    dir: PathBuf,
+}
+
+impl SyncState {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The state in the configured data directory
+    pub fn open_default() -> Result<Self, SyncError> {
+        let config = Config::load().map_err(|e| SyncError::Store(e.to_string()))?;
+        Ok(Self::new(config.data_dir))
+    }
+
+    pub fn sites(&self) -> Result<BTreeMap<String, SiteState>, SyncError> {
+        let path = self.dir.join(STATE_FILE);
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| SyncError::Store(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(io_error(&path, e)),
+        }
+    }
+
+    /// Verify `bundle` and add its files to `cert_dir`. Files go in one at a time, so
+    /// an interrupted import can simply be run again: files already written are then
+    /// found unchanged. The site's cursor only moves once every file is in place.
+    pub fn import(
+        &self,
+        bundle: &DeltaBundle,
+        public_key: &[u8; 32],
+        cert_dir: &Path,
+        allow_gap: bool,
+        dry_run: bool,
+    ) -> Result<ImportOutcome, SyncError> {
+        let unsigned = verify_bundle(bundle, public_key)?;
+        let since = bundle.since.as_deref().map(Cursor::parse).transpose()?;
+        let cursor = Cursor::parse(&bundle.cursor)?;
+
+        self.update(dry_run, |sites| {
+            let reached = sites.get(&bundle.site).map(|state| Cursor::parse(&state.cursor)).transpose()?;
+            let mut outcome = ImportOutcome {
+                site: bundle.site.clone(),
+                status: ImportStatus::AlreadyImported,
+                since: bundle.since.clone(),
+                cursor: bundle.cursor.clone(),
+                written: Vec::new(),
+                unchanged: Vec::new(),
+                unsigned,
+            };
+            if reached.as_ref().is_some_and(|reached| *reached >= cursor) {
+                return Ok(outcome);
+            }
+            if since > reached && !allow_gap {
+                return Err(SyncError::Gap {
+                    site: bundle.site.clone(),
+                    since: bundle.since.clone().unwrap_or_default(),
+                    reached: reached.map_or_else(|| "nothing from it yet".to_string(), |reached| format!("only up to {}", reached)),
+                });
+            }
+
+            // Check every file before writing any, so a conflict leaves nothing half done
+            let mut pending = Vec::new();
+            for file in &bundle.files {
+                let path = cert_dir.join(&file.name);
+                match fs::read_to_string(&path) {
+                    Ok(existing) if existing == file.content => outcome.unchanged.push(file.name.clone()),
+                    // Sidecars are append-only: a longer copy of the same lines replaces ours
+                    Ok(existing) if file.name.ends_with(".jsonl") && file.content.starts_with(&existing) => pending.push(file),
+                    Ok(_) => return Err(SyncError::Conflict(path.display().to_string())),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => pending.push(file),
+                    Err(e) => return Err(io_error(&path, e)),
+                }
+            }
+            if !dry_run {
+                fs::create_dir_all(cert_dir).map_err(|e| io_error(cert_dir, e))?;
+                for file in &pending {
+                    write_synced(&cert_dir.join(&file.name), file.content.as_bytes())?;
+                }
+            }
+            outcome.written = pending.iter().map(|file| file.name.clone()).collect();
+            outcome.status = ImportStatus::Imported;
+
+            let bundles = sites.get(&bundle.site).map_or(0, |state| state.bundles);
+            sites.insert(
+                bundle.site.clone(),
+                SiteState { cursor: bundle.cursor.clone(), imported_at: Utc::now().to_rfc3339(), bundles: bundles + 1 },
+            );
+            Ok(outcome)
+        })
+    }
+
+    /// Apply `change` under the state lock, saving the result unless it fails or
+    /// this is a dry run
+    fn update<T>(
+        &self,
+        dry_run: bool,
+        change: impl FnOnce(&mut BTreeMap<String, SiteState>) -> Result<T, SyncError>,
+    ) -> Result<T, SyncError> {
+        fs::create_dir_all(&self.dir).map_err(|e| io_error(&self.dir, e))?;
+        let lock_path = self.dir.join(LOCK_FILE);
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| io_error(&lock_path, e))?;
+        if unsafe { libc::flock(lock.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(io_error(&lock_path, std::io::Error::last_os_error()));
+        }
+
+        let mut sites = self.sites()?;
+        let result = change(&mut sites)?;
+        if !dry_run {
+            let json = serde_json::to_string_pretty(&sites).map_err(|e| SyncError::Store(e.to_string()))?;
+            write_synced(&self.dir.join(STATE_FILE), json.as_bytes())?;
+        }
+        // Dropping `lock` releases it
+        Ok(result)
+    }
+}
+
+/// Write `path` under a temporary name, sync it and rename it into place
+fn write_synced(path: &Path, bytes: &[u8]) -> Result<(), SyncError> {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.partial", name));
+    File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| io_error(path, e))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::{Duration, SystemTime};
+
+    fn write(dir: &Path, name: &str, content: &str, age_secs: u64) {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age_secs)).unwrap();
+    }
+
+    fn certificate(cert_id: &str, key: &SigningKey) -> String {
+        let mut cert = json!({"cert_id": cert_id, "cert_type": "wipe", "created_at": "2025-01-01T00:00:00Z"});
+        crate::signer::sign_certificate(&mut cert, key, false).unwrap();
+        serde_json::to_string_pretty(&cert).unwrap()
+    }
+
+    #[test]
+    fn test_export_and_import_deltas() {
+        let site_dir = tempfile::tempdir().unwrap();
+        let central = tempfile::tempdir().unwrap();
+        let central_certs = central.path().join("certificates");
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        write(site_dir.path(), "a.json", &certificate("a", &key), 300);
+        write(site_dir.path(), "b.json", &certificate("b", &key), 200);
+        write(site_dir.path(), "a_annotations.jsonl", "{\"note\":1}\n", 100);
+        write(site_dir.path(), "serials.json", "{\"SIH\":2}", 50);
+
+        // Capped at two files, so the sidecar waits for the next bundle
+        let limits = Limits { max_files: Some(2), max_bytes: None };
+        let first = export_delta(site_dir.path(), "lab1", None, limits, &key).unwrap().unwrap();
+        let names: Vec<&str> = first.files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!((names, first.remaining), (vec!["a.json", "b.json"], 1));
+
+        let state = SyncState::new(central.path());
+        let cursor = Cursor::parse(&first.cursor).unwrap();
+        let second = export_delta(site_dir.path(), "lab1", Some(&cursor), Limits::default(), &key).unwrap().unwrap();
+        assert_eq!(second.files.len(), 1);
+
+        // Out of order: the second bundle would leave a gap
+        let gap = state.import(&second, &public_key, &central_certs, false, false);
+        assert!(matches!(gap, Err(SyncError::Gap { .. })), "{:?}", gap);
+
+        let outcome = state.import(&first, &public_key, &central_certs, false, false).unwrap();
+        assert_eq!((outcome.status, outcome.written.len()), (ImportStatus::Imported, 2));
+        assert_eq!(state.import(&first, &public_key, &central_certs, false, false).unwrap().status, ImportStatus::AlreadyImported);
+        state.import(&second, &public_key, &central_certs, false, false).unwrap();
+        assert_eq!(fs::read_to_string(central_certs.join("a_annotations.jsonl")).unwrap(), "{\"note\":1}\n");
+
+        // A sidecar that grew is carried again and replaces the shorter copy
+        let cursor = Cursor::parse(&second.cursor).unwrap();
+        assert!(export_delta(site_dir.path(), "lab1", Some(&cursor), Limits::default(), &key).unwrap().is_none());
+        write(site_dir.path(), "a_annotations.jsonl", "{\"note\":1}\n{\"note\":2}\n", 0);
+        let third = export_delta(site_dir.path(), "lab1", Some(&cursor), Limits::default(), &key).unwrap().unwrap();
+        let outcome = state.import(&third, &public_key, &central_certs, false, false).unwrap();
+        assert_eq!(outcome.written, ["a_annotations.jsonl"]);
+        assert_eq!(state.sites().unwrap()["lab1"].bundles, 3);
+    }
+
+    #[test]
+    fn test_tampered_bundles_are_refused() {
+        let site_dir = tempfile::tempdir().unwrap();
+        let central = tempfile::tempdir().unwrap();
+        let key = SigningKey::from_bytes(&[9u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        write(site_dir.path(), "a.json", &certificate("a", &key), 10);
+        let bundle = export_delta(site_dir.path(), "lab1", None, Limits::default(), &key).unwrap().unwrap();
+        assert_eq!(verify_bundle(&bundle, &public_key).unwrap(), Vec::<String>::new());
+
+        let mut edited = bundle.clone();
+        edited.files[0].content = edited.files[0].content.replace("\"a\"", "\"z\"");
+        assert!(matches!(verify_bundle(&edited, &public_key), Err(SyncError::Signature(_))));
+
+        let other = SigningKey::from_bytes(&[1u8; 32]).verifying_key().to_bytes();
+        let state = SyncState::new(central.path());
+        assert!(state.import(&bundle, &other, &central.path().join("certificates"), false, false).is_err());
+        assert!(state.sites().unwrap().is_empty());
+
+        assert_eq!(Cursor::parse(&bundle.cursor).unwrap().to_string(), bundle.cursor);
+        assert!(Cursor::parse("v1:notanumber:a.json").is_err());
+    }
+}
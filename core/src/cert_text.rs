@@ -0,0 +1,394 @@
+//! Plain-text and Markdown renderings of a certificate, for tickets and email where a
+//! PDF is awkward. Each field is one labelled line in a fixed order, so a screen reader
+//! reads it top to bottom and two exports diff line by line. Fields the certificate
+//! doesn't have are left out rather than shown as N/A.
+
+use crate::locale::DisplayFormat;
+use serde_json::Value;
+
+/// Which text rendering `cert --export-text` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextStyle {
+    /// Headings underlined with `=` and `-`, `Label: value` lines
+    #[default]
+    Plain,
+    /// Headings and a bullet list per section, for trackers that render Markdown
+    Markdown,
+}
+
+impl TextStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextStyle::Plain => "plain",
+            TextStyle::Markdown => "markdown",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "plain" | "text" => Some(TextStyle::Plain),
+            "markdown" | "md" => Some(TextStyle::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// How a field's value is shown
+#[derive(Clone, Copy)]
+enum Kind {
+    Text,
+    /// Ids, hashes and signatures: verbatim, as code in Markdown
+    Code,
+    Bytes,
+    Count,
+    Timestamp,
+    /// `yes`/`no`
+    Flag,
+}
+
+struct Field {
+    label: &'static str,
+    pointer: &'static str,
+    kind: Kind,
+}
+
+const fn field(label: &'static str, pointer: &'static str, kind: Kind) -> Field {
+    Field { label, pointer, kind }
+}
+
+const SUMMARY: &[Field] = &[
+    field("Certificate ID", "/cert_id", Kind::Code),
+    field("Certificate No.", "/serial_number", Kind::Code),
+    field("Type", "/cert_type", Kind::Text),
+    field("Version", "/certificate_version", Kind::Text),
+    field("Created", "/created_at", Kind::Timestamp),
+    field("Result", "/result", Kind::Text),
+    field("Verification URL", "/verify_url", Kind::Text),
+];
+
+const DEVICE: &[Field] = &[
+    field("Model", "/device/model", Kind::Text),
+    field("Serial number", "/device/serial", Kind::Code),
+    field("Bus", "/device/bus", Kind::Text),
+    field("Capacity", "/device/capacity_bytes", Kind::Bytes),
+    field("Path", "/device/path", Kind::Code),
+    field("Firmware", "/device/firmware", Kind::Text),
+    field("Asset tag", "/device/asset_tag", Kind::Text),
+];
+
+const BACKUP: &[Field] = &[
+    field("Files", "/files_summary/count", Kind::Count),
+    field("Personal data", "/files_summary/personal_bytes", Kind::Bytes),
+    field("Destination type", "/destination/type", Kind::Text),
+    field("Destination label", "/destination/label", Kind::Text),
+    field("File system", "/destination/fs", Kind::Text),
+    field("Encryption", "/crypto/alg", Kind::Text),
+    field("Key management", "/crypto/key_management", Kind::Text),
+    field("Manifest SHA-256", "/crypto/manifest_sha256", Kind::Code),
+    field("Verification strategy", "/verification/strategy", Kind::Text),
+    field("Verification failures", "/verification/failures", Kind::Count),
+    field("Policy", "/policy/name", Kind::Text),
+];
+
+const WIPE_POLICY: &[Field] = &[
+    field("NIST level", "/policy/nist_level", Kind::Text),
+    field("Method", "/policy/method", Kind::Text),
+    field("Action mapping", "/policy/action_mapping", Kind::Text),
+    field("HPA/DCO cleared", "/hpa_dco/cleared", Kind::Flag),
+];
+
+const WIPE_VERIFICATION: &[Field] = &[
+    field("Strategy", "/verify/strategy", Kind::Text),
+    field("Samples", "/verify/samples", Kind::Count),
+    field("Failures", "/verify/failures", Kind::Count),
+    field("Result", "/verify/result", Kind::Text),
+    field("Backup certificate", "/linkage/backup_cert_id", Kind::Code),
+    field("Logs SHA-256", "/evidence/logs_sha256", Kind::Code),
+];
+
+const DESTRUCTION: &[Field] = &[
+    field("NIST level", "/policy/nist_level", Kind::Text),
+    field("Method", "/policy/method", Kind::Text),
+    field("Reason", "/policy/reason", Kind::Text),
+    field("Performed at", "/destruction/performed_at", Kind::Timestamp),
+    field("Location", "/destruction/location", Kind::Text),
+    field("Equipment", "/destruction/equipment", Kind::Text),
+    field("Operator", "/destruction/operator", Kind::Text),
+    field("Witness", "/destruction/witness", Kind::Text),
+    field("Notes", "/destruction/notes", Kind::Text),
+    field("Wipe certificate", "/linkage/wipe_cert_id", Kind::Code),
+];
+
+const HOST: &[Field] = &[
+    field("Hostname", "/host/hostname", Kind::Text),
+    field("Manufacturer", "/host/system_manufacturer", Kind::Text),
+    field("Model", "/host/system_model", Kind::Text),
+    field("Chassis serial", "/host/chassis_serial", Kind::Code),
+    field("System serial", "/host/system_serial", Kind::Code),
+    field("System UUID", "/host/system_uuid", Kind::Code),
+    field("Machine ID", "/host/machine_id", Kind::Code),
+    field("Asset tag", "/host/asset_tag", Kind::Text),
+    field("BIOS vendor", "/host/bios_vendor", Kind::Text),
+    field("BIOS version", "/host/bios_version", Kind::Text),
+    field("BIOS date", "/host/bios_date", Kind::Text),
+];
+
+const SYSTEM_POLICY: &[Field] = &[
+    field("NIST level", "/policy/nist_level", Kind::Text),
+    field("Notes", "/notes", Kind::Text),
+];
+
+const ENVIRONMENT: &[Field] = &[
+    field("Operator", "/environment/operator", Kind::Text),
+    field("Kernel", "/environment/os_kernel", Kind::Text),
+    field("Tool version", "/environment/tool_version", Kind::Text),
+    field("Operator note", "/metadata/operator_note", Kind::Text),
+    field("Exceptions", "/exceptions/text", Kind::Text),
+];
+
+const ISSUER: &[Field] = &[
+    field("Organization", "/issuer/organization", Kind::Text),
+    field("Country", "/issuer/country", Kind::Text),
+    field("Tool", "/issuer/tool_name", Kind::Text),
+    field("Algorithm", "/signature/alg", Kind::Text),
+    field("Public key ID", "/signature/pubkey_id", Kind::Code),
+    field("Signature", "/signature/sig", Kind::Code),
+];
+
+/// Render `cert` as text. Sizes and dates follow `display`; the JSON stays the
+/// record of what was signed.
+pub fn render(cert: &Value, style: TextStyle, display: &DisplayFormat) -> String {
+    let mut sections: Vec<(&str, &[Field])> = vec![("Certificate", SUMMARY), ("Device", DEVICE)];
+    let title = match cert["cert_type"].as_str() {
+        Some("backup") => {
+            sections.push(("Backup", BACKUP));
+            "SecureWipe Backup Certificate"
+        }
+        Some("wipe") => {
+            sections.push(("Sanitization policy", WIPE_POLICY));
+            sections.push(("Verification", WIPE_VERIFICATION));
+            sections.push(("Host", HOST));
+            "SecureWipe Data Sanitization Certificate"
+        }
+        Some("destroy") => {
+            sections.push(("Physical destruction", DESTRUCTION));
+            "SecureWipe Physical Destruction Certificate"
+        }
+        Some("system") => {
+            sections.push(("System", HOST));
+            sections.push(("Sanitization policy", SYSTEM_POLICY));
+            "SecureWipe System Sanitization Certificate"
+        }
+        _ => "SecureWipe Certificate",
+    };
+    sections.push(("Environment", ENVIRONMENT));
+    sections.push(("Issuer and signature", ISSUER));
+
+    let mut text = heading(style, 1, title);
+    for (name, fields) in sections {
+        let mut lines: Vec<String> = fields
+            .iter()
+            .filter_map(|field| Some(line(style, field.label, &value(cert.pointer(field.pointer)?, field.kind, style, display)?)))
+            .collect();
+        if name == "Issuer and signature" && cert.get("signature").is_none() {
+            lines.push(line(style, "Signature", "none (unsigned certificate)"));
+        }
+        if lines.is_empty() {
+            continue;
+        }
+        text.push('\n');
+        text.push_str(&heading(style, 2, name));
+        text.push('\n');
+        for line in lines {
+            text.push_str(&line);
+        }
+    }
+
+    if let Some(components) = cert["components"].as_array().filter(|components| !components.is_empty()) {
+        text.push('\n');
+        text.push_str(&heading(style, 2, "Drives"));
+        text.push('\n');
+        for component in components {
+            let text_of = |pointer: &str| component.pointer(pointer).and_then(Value::as_str).unwrap_or("?");
+            let (cert_id, model, serial) = match style {
+                TextStyle::Plain => (text_of("/cert_id").to_string(), text_of("/device/model").to_string(), text_of("/device/serial").to_string()),
+                TextStyle::Markdown => (code(text_of("/cert_id")), escape_markdown(text_of("/device/model")), code(text_of("/device/serial"))),
+            };
+            let capacity = component["device"]["capacity_bytes"].as_u64().map_or("?".to_string(), |bytes| display.bytes_exact(bytes));
+            text.push_str(&format!(
+                "- {} ({}): {}, serial {}, {}; {} {}, {}\n",
+                cert_id,
+                text_of("/cert_type"),
+                model,
+                serial,
+                capacity,
+                text_of("/nist_level"),
+                text_of("/method"),
+                text_of("/result"),
+            ));
+        }
+    }
+
+    if let Some(commands) = cert["commands"].as_array().filter(|commands| !commands.is_empty()) {
+        text.push('\n');
+        text.push_str(&heading(style, 2, "Commands executed"));
+        text.push('\n');
+        for command in commands {
+            let cmd = command["cmd"].as_str().unwrap_or("");
+            let cmd = match style {
+                TextStyle::Plain => cmd.to_string(),
+                TextStyle::Markdown => code(cmd),
+            };
+            let exit = command["exit"].as_i64().map_or("?".to_string(), |exit| exit.to_string());
+            let seconds = command["ms"].as_u64().map_or("?".to_string(), |ms| display.number(ms as f64 / 1000.0, 1));
+            text.push_str(&format!("- {} (exit {}, {} s)\n", cmd, exit, seconds));
+        }
+    }
+    text
+}
+
+fn heading(style: TextStyle, level: usize, title: &str) -> String {
+    match style {
+        TextStyle::Markdown => format!("{} {}\n", "#".repeat(level), title),
+        TextStyle::Plain => {
+            let rule = if level == 1 { '=' } else { '-' };
+            format!("{}\n{}\n", title, rule.to_string().repeat(title.chars().count()))
+        }
+    }
+}
+
+fn line(style: TextStyle, label: &str, value: &str) -> String {
+    match style {
+        TextStyle::Plain => format!("{}: {}\n", label, value),
+        TextStyle::Markdown => format!("- **{}:** {}\n", label, value),
+    }
+}
+
+fn value(value: &Value, kind: Kind, style: TextStyle, display: &DisplayFormat) -> Option<String> {
+    let plain = match (kind, value) {
+        (_, Value::Null) => return None,
+        (Kind::Bytes, Value::Number(n)) => display.bytes_exact(n.as_u64()?),
+        (Kind::Count, Value::Number(n)) => display.integer(n.as_u64()?),
+        (Kind::Timestamp, Value::String(s)) => display.timestamp(s),
+        (Kind::Flag, Value::Bool(b)) => if *b { "yes" } else { "no" }.to_string(),
+        (_, Value::String(s)) => s.trim().to_string(),
+        (_, other) => other.to_string(),
+    };
+    if plain.is_empty() {
+        return None;
+    }
+    // Values are single lines, so each field stays one line in the export
+    let plain = plain.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(match (style, kind) {
+        (TextStyle::Plain, _) => plain,
+        (TextStyle::Markdown, Kind::Code) => code(&plain),
+        (TextStyle::Markdown, _) => escape_markdown(&plain),
+    })
+}
+
+fn code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn wipe_cert() -> Value {
+        json!({
+            "cert_type": "wipe",
+            "cert_id": "WPE_2024_001",
+            "certificate_version": "v1.0.0",
+            "created_at": "2024-01-15T11:45:30+05:30",
+            "device": {"model": "Test SSD 1TB", "serial": "TEST123", "bus": "NVMe", "capacity_bytes": 1_000_204_886_016u64},
+            "policy": {"nist_level": "PURGE", "method": "nvme_sanitize"},
+            "hpa_dco": {"cleared": true},
+            "commands": [{"cmd": "nvme sanitize /dev/nvme0n1 --sanitize-action=0x02", "exit": 0, "ms": 30000}],
+            "verify": {"strategy": "RANDOM_SAMPLE", "samples": 128, "failures": 0, "result": "PASS"},
+            "result": "PASS",
+            "environment": {"operator": "jane_doe", "os_kernel": "Linux 6.1.0"},
+            "host": {"hostname": "lab-laptop-07", "system_manufacturer": "Dell Inc.", "bios_version": "1.38.0"},
+            "metadata": {"operator_note": "Customer *approved*\nvia ticket 4521"},
+            "exceptions": {"text": ""},
+            "issuer": {"organization": "SecureWipe", "country": "IN"},
+            "signature": {"alg": "Ed25519", "pubkey_id": "sih_root_v1", "sig": "c2lnbmF0dXJl"}
+        })
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let text = render(&wipe_cert(), TextStyle::Plain, &DisplayFormat::default());
+        assert!(text.starts_with("SecureWipe Data Sanitization Certificate\n========================================\n\n"));
+        assert!(text.contains("\nDevice\n------\n\nModel: Test SSD 1TB\n"));
+        assert!(text.contains("Created: 2024-01-15 11:45:30 +05:30\n"));
+        assert!(text.contains("Capacity: 1.00 TB (1,000,204,886,016 bytes)\n"));
+        assert!(text.contains("HPA/DCO cleared: yes\n"));
+        assert!(text.contains("\nHost\n----\n\nHostname: lab-laptop-07\nManufacturer: Dell Inc.\nBIOS version: 1.38.0\n"));
+        assert!(text.contains("Operator note: Customer *approved* via ticket 4521\n"));
+        assert!(text.contains("- nvme sanitize /dev/nvme0n1 --sanitize-action=0x02 (exit 0, 30.0 s)\n"));
+        assert!(!text.contains("Exceptions"));
+        assert!(!text.contains("Physical destruction"));
+        assert!(!text.contains("unsigned"));
+    }
+
+    #[test]
+    fn test_render_system() {
+        let cert = json!({
+            "cert_type": "system",
+            "cert_id": "SYS_2024_001",
+            "host": {"hostname": "lab-laptop-07", "system_model": "Latitude 7490", "chassis_serial": "5J5PX32"},
+            "policy": {"nist_level": "PURGE"},
+            "components": [{
+                "cert_id": "WPE_2024_001",
+                "cert_type": "wipe",
+                "device": {"model": "Test SSD 1TB", "serial": "TEST123", "bus": "NVMe", "capacity_bytes": 1_000_204_886_016u64},
+                "nist_level": "PURGE",
+                "method": "nvme_sanitize",
+                "result": "PASS"
+            }],
+            "result": "PASS"
+        });
+        let text = render(&cert, TextStyle::Plain, &DisplayFormat::default());
+        assert!(text.starts_with("SecureWipe System Sanitization Certificate\n"));
+        assert!(text.contains("\nSystem\n------\n\nHostname: lab-laptop-07\nModel: Latitude 7490\nChassis serial: 5J5PX32\n"));
+        assert!(text.contains("\nSanitization policy\n-------------------\n\nNIST level: PURGE\n"));
+        assert!(text.contains(
+            "- WPE_2024_001 (wipe): Test SSD 1TB, serial TEST123, 1.00 TB (1,000,204,886,016 bytes); PURGE nvme_sanitize, PASS\n"
+        ));
+        assert!(!text.contains("\nDevice\n"));
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let mut cert = wipe_cert();
+        cert.as_object_mut().unwrap().remove("signature");
+        let text = render(&cert, TextStyle::Markdown, &DisplayFormat::default());
+        assert!(text.starts_with("# SecureWipe Data Sanitization Certificate\n\n"));
+        assert!(text.contains("## Verification\n\n- **Strategy:** RANDOM\\_SAMPLE\n"));
+        assert!(text.contains("- **Certificate ID:** `WPE_2024_001`\n"));
+        assert!(text.contains("- **Operator note:** Customer \\*approved\\* via ticket 4521\n"));
+        assert!(text.contains("- `nvme sanitize /dev/nvme0n1 --sanitize-action=0x02` (exit 0, 30.0 s)\n"));
+        assert!(text.contains("- **Signature:** none (unsigned certificate)\n"));
+
+        let other = render(&json!({"cert_id": "X1"}), TextStyle::Markdown, &DisplayFormat::default());
+        assert!(other.starts_with("# SecureWipe Certificate\n"));
+        assert_eq!(TextStyle::parse("md"), Some(TextStyle::Markdown));
+        assert_eq!(TextStyle::parse("html"), None);
+    }
+}
@@ -0,0 +1,424 @@
+//! Temporary files and directories that only exist on the way to a result: a
+//! certificate's `.tmp` file before it is renamed into place, the JSON handed to the
+//! PDF generator, the in-progress directory of a backup that can't be resumed.
+//!
+//! While an operation runs, each one is held by an `Artifact`. Dropping the guard
+//! (an early return, an error, a cancelled backup) removes it; `keep` hands it over
+//! once it has become the result. Live temp files are also in a process-wide registry
+//! that the SIGINT/SIGTERM handler empties before the process dies. Whatever a killed
+//! or crashed process still leaves behind is found by `orphans` and removed by
+//! `securewipe cleanup`.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "operations")]
+use crate::backup::{self, StaleBackup};
+
+/// Temp files of live artifacts, as C strings so the signal handler can unlink them
+/// without allocating
+static REGISTRY: Mutex<Vec<CString>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// A file written next to its final name and renamed into place, or handed to a
+    /// helper process
+    TempFile,
+    /// A backup's `.inprogress-<id>` directory
+    PartialBackup,
+    /// A UI session record whose CLI process is gone (its logs are kept)
+    SessionRecord,
+}
+
+/// Removes `path` when dropped, unless `keep` was called
+#[derive(Debug)]
+pub struct Artifact {
+    path: PathBuf,
+    kind: ArtifactKind,
+    kept: bool,
+}
+
+impl Artifact {
+    /// A temp file, registered so a SIGINT/SIGTERM still removes it
+    pub fn temp_file(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        if let (Ok(mut files), Some(c_path)) = (REGISTRY.lock(), c_path(&path)) {
+            files.push(c_path);
+        }
+        Self { path, kind: ArtifactKind::TempFile, kept: false }
+    }
+
+    /// A partial backup directory. It is only removed by dropping the guard: deleting
+    /// a tree isn't safe in a signal handler, and a cancelled backup returns normally.
+    pub fn partial_backup(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), kind: ArtifactKind::PartialBackup, kept: false }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The artifact became the result (or must survive for a resume): leave it be
+    pub fn keep(mut self) -> PathBuf {
+        self.kept = true;
+        self.path.clone()
+    }
+}
+
+impl Drop for Artifact {
+    fn drop(&mut self) {
+        if self.kind == ArtifactKind::TempFile {
+            if let (Ok(mut files), Some(c_path)) = (REGISTRY.lock(), c_path(&self.path)) {
+                if let Some(index) = files.iter().position(|file| *file == c_path) {
+                    files.swap_remove(index);
+                }
+            }
+        }
+        if self.kept {
+            return;
+        }
+        let _ = match self.kind {
+            ArtifactKind::PartialBackup => fs::remove_dir_all(&self.path),
+            _ => fs::remove_file(&self.path),
+        };
+    }
+}
+
+#[cfg(unix)]
+fn c_path(path: &Path) -> Option<CString> {
+    use std::os::unix::ffi::OsStrExt;
+    CString::new(path.as_os_str().as_bytes()).ok()
+}
+
+#[cfg(not(unix))]
+fn c_path(path: &Path) -> Option<CString> {
+    CString::new(path.to_string_lossy().as_bytes()).ok()
+}
+
+/// Write `contents` to `<path>.tmp` and rename it over `path`, so readers never see a
+/// torn file. The temp file is removed if anything fails or the process is cancelled.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = Artifact::temp_file(path.with_file_name(tmp_name));
+    fs::write(tmp.path(), contents)?;
+    fs::rename(tmp.path(), path)?;
+    tmp.keep();
+    Ok(())
+}
+
+#[cfg(unix)]
+extern "C" fn remove_registered_and_exit(signal: libc::c_int) {
+    // try_lock: if the interrupted code holds the lock, it was mid-registration and
+    // waiting for it would deadlock
+    if let Ok(files) = REGISTRY.try_lock() {
+        for file in files.iter() {
+            unsafe { libc::unlink(file.as_ptr()) };
+        }
+    }
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+/// Remove registered temp files on SIGINT/SIGTERM, then die of the signal as before.
+/// `backup` replaces this with its own handler, which stops at a file boundary.
+#[cfg(unix)]
+pub fn install_cancel_handler() {
+    let handler = remove_registered_and_exit as extern "C" fn(libc::c_int);
+    unsafe {
+        libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+        libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install_cancel_handler() {}
+
+/// Whether `pid` is still a running securewipe process (guards against pid reuse on Linux)
+pub fn process_alive(pid: u32) -> bool {
+    if pid == 0 {
+        return false;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match fs::read(format!("/proc/{}/cmdline", pid)) {
+            Ok(cmdline) => String::from_utf8_lossy(&cmdline).contains("securewipe"),
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+            .unwrap_or(false)
+    }
+}
+
+/// Where to look for leftovers
+#[derive(Debug, Clone)]
+pub struct CleanupScope {
+    pub certificates_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub temp_dir: PathBuf,
+    /// Backup destinations to check for unfinished backups
+    pub backup_destinations: Vec<PathBuf>,
+    /// Also count unfinished backups that `backup --resume` could still finish
+    pub include_resumable: bool,
+    /// Temp files younger than this are left alone: a running command may be
+    /// about to rename them
+    pub min_age: Duration,
+}
+
+/// An artifact no running operation owns
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Orphan {
+    pub path: String,
+    pub kind: ArtifactKind,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<String>,
+}
+
+/// Leftovers in `scope`, sorted by path. Directories that don't exist are skipped.
+pub fn orphans(scope: &CleanupScope) -> Vec<Orphan> {
+    let now = SystemTime::now();
+    let mut found = Vec::new();
+
+    let sessions_dir = scope.data_dir.join("sessions");
+    for dir in [&scope.certificates_dir, &scope.data_dir, &sessions_dir] {
+        for path in files_in(dir) {
+            if has_name(&path, |name| name.ends_with(".tmp")) && older_than(&path, now, scope.min_age) {
+                found.extend(orphan(&path, ArtifactKind::TempFile));
+            }
+        }
+    }
+
+    // Certificate JSON handed to the Python PDF generator
+    for path in files_in(&scope.temp_dir) {
+        let pdf_input = has_name(&path, |name| {
+            name.strip_prefix("cert_")
+                .and_then(|rest| rest.strip_suffix(".json"))
+                .is_some_and(|id| uuid::Uuid::parse_str(id).is_ok())
+        });
+        if pdf_input && older_than(&path, now, scope.min_age) {
+            found.extend(orphan(&path, ArtifactKind::TempFile));
+        }
+    }
+
+    for path in files_in(&sessions_dir) {
+        if !has_name(&path, |name| name.ends_with(".json")) {
+            continue;
+        }
+        let pid = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|record| record.get("pid").and_then(|pid| pid.as_u64()));
+        if let Some(pid) = pid {
+            if !process_alive(pid as u32) {
+                found.extend(orphan(&path, ArtifactKind::SessionRecord));
+            }
+        }
+    }
+
+    #[cfg(feature = "operations")]
+    for destination in &scope.backup_destinations {
+        for stale in backup::stale_backups(destination).unwrap_or_default() {
+            if scope.include_resumable || !stale.resumable {
+                found.push(partial_backup(&stale));
+            }
+        }
+    }
+
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    // The certificates directory may be the data directory
+    found.dedup_by(|a, b| a.path == b.path);
+    found
+}
+
+/// Remove one leftover found by `orphans`. A partial backup is only removed if no
+/// backup has picked it up since.
+#[cfg(feature = "operations")]
+pub fn remove(orphan: &Orphan) -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new(&orphan.path);
+    match orphan.kind {
+        ArtifactKind::PartialBackup => backup::remove_in_progress_dir(path),
+        ArtifactKind::TempFile | ArtifactKind::SessionRecord => Ok(fs::remove_file(path)?),
+    }
+}
+
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file()).collect())
+        .unwrap_or_default()
+}
+
+fn has_name(path: &Path, matches: impl Fn(&str) -> bool) -> bool {
+    path.file_name().and_then(|name| name.to_str()).is_some_and(matches)
+}
+
+fn older_than(path: &Path, now: SystemTime, min_age: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() >= min_age)
+}
+
+fn orphan(path: &Path, kind: ArtifactKind) -> Option<Orphan> {
+    let metadata = fs::metadata(path).ok()?;
+    Some(Orphan {
+        path: path.display().to_string(),
+        kind,
+        bytes: metadata.len(),
+        modified_at: metadata.modified().ok().map(|modified| DateTime::<Utc>::from(modified).to_rfc3339()),
+    })
+}
+
+#[cfg(feature = "operations")]
+fn partial_backup(stale: &StaleBackup) -> Orphan {
+    Orphan {
+        path: stale.path.clone(),
+        kind: ArtifactKind::PartialBackup,
+        bytes: backup::tree_size(Path::new(&stale.path)),
+        modified_at: stale.modified_at.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(root: &Path) -> CleanupScope {
+        CleanupScope {
+            certificates_dir: root.join("certs"),
+            data_dir: root.join("data"),
+            temp_dir: root.join("tmp"),
+            backup_destinations: vec![root.join("dest")],
+            include_resumable: false,
+            min_age: Duration::ZERO,
+        }
+    }
+
+    fn registered(path: &Path) -> bool {
+        REGISTRY.lock().unwrap().contains(&c_path(path).unwrap())
+    }
+
+    #[test]
+    fn test_artifact_removed_unless_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let dropped = dir.path().join("dropped.tmp");
+        let kept = dir.path().join("kept.tmp");
+        {
+            let artifact = Artifact::temp_file(&dropped);
+            fs::write(artifact.path(), b"x").unwrap();
+            assert!(registered(&dropped));
+        }
+        assert!(!dropped.exists());
+        assert!(!registered(&dropped));
+
+        let artifact = Artifact::temp_file(&kept);
+        fs::write(artifact.path(), b"x").unwrap();
+        assert_eq!(artifact.keep(), kept);
+        assert!(kept.exists());
+        assert!(!registered(&kept));
+
+        let partial = dir.path().join(".inprogress-x");
+        fs::create_dir_all(partial.join("files")).unwrap();
+        drop(Artifact::partial_backup(&partial));
+        assert!(!partial.exists());
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cert.json");
+        write_atomic(&path, "{}").unwrap();
+        write_atomic(&path, "{\"v\": 2}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"v\": 2}");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // The rename fails: the temp file goes with it
+        let missing = dir.path().join("missing").join("cert.json");
+        assert!(write_atomic(&missing, "{}").is_err());
+        assert!(!registered(&dir.path().join("missing").join("cert.json.tmp")));
+    }
+
+    #[test]
+    fn test_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        let scope = scope(dir.path());
+        for sub in ["certs", "data/sessions", "tmp", "dest"] {
+            fs::create_dir_all(dir.path().join(sub)).unwrap();
+        }
+        fs::write(dir.path().join("certs/BCK_x.json.tmp"), b"{").unwrap();
+        fs::write(dir.path().join("certs/BCK_x.json"), b"{}").unwrap();
+        fs::write(dir.path().join("data/serials.json.tmp"), b"{").unwrap();
+        fs::write(dir.path().join(format!("tmp/cert_{}.json", uuid::Uuid::new_v4())), b"{}").unwrap();
+        fs::write(dir.path().join("tmp/cert_notes.json"), b"{}").unwrap();
+        fs::write(dir.path().join("data/sessions/session_1.json"), r#"{"session_id": "session_1", "pid": 0}"#).unwrap();
+        fs::write(dir.path().join("data/sessions/session_1.stderr.log"), b"").unwrap();
+        let own_pid = std::process::id();
+        fs::write(dir.path().join("data/sessions/session_2.json"), format!(r#"{{"pid": {}}}"#, own_pid)).unwrap();
+        fs::create_dir(dir.path().join(format!("dest/{}unresumable", backup::IN_PROGRESS_PREFIX))).unwrap();
+        let resumable = dir.path().join(format!("dest/{}resumable", backup::IN_PROGRESS_PREFIX));
+        fs::create_dir(&resumable).unwrap();
+        fs::write(resumable.join("key.json"), b"{}").unwrap();
+        let start = r#"{"type": "start", "backup_id": "resumable", "device": "/dev/sdb", "destination": "/mnt", "source_paths": [], "encryption_method": "AES-256-CTR+PBKDF2-HMAC-SHA256"}"#;
+        fs::write(resumable.join(backup::JOURNAL_FILE), format!("{}\n", start)).unwrap();
+
+        let found = orphans(&scope);
+        let kinds: Vec<(ArtifactKind, String)> = found
+            .iter()
+            .map(|orphan| (orphan.kind, Path::new(&orphan.path).file_name().unwrap().to_string_lossy().into_owned()))
+            .collect();
+        assert!(kinds.contains(&(ArtifactKind::TempFile, "BCK_x.json.tmp".to_string())));
+        assert!(kinds.contains(&(ArtifactKind::TempFile, "serials.json.tmp".to_string())));
+        assert!(kinds.contains(&(ArtifactKind::SessionRecord, "session_1.json".to_string())));
+        assert!(kinds.contains(&(ArtifactKind::PartialBackup, format!("{}unresumable", backup::IN_PROGRESS_PREFIX))));
+        // The PDF input, but not a file that merely starts with cert_
+        assert_eq!(found.iter().filter(|orphan| orphan.path.contains("/tmp/cert_")).count(), 1);
+        // Not: finished certificates, session logs, the live session (ours, in a test
+        // binary whose name contains "securewipe"), or a backup that can be resumed
+        assert_eq!(found.len(), 5);
+
+        for orphan in &found {
+            remove(orphan).unwrap();
+        }
+        assert!(dir.path().join("certs/BCK_x.json").exists());
+        assert!(dir.path().join("data/sessions/session_1.stderr.log").exists());
+        assert!(resumable.exists());
+        assert!(orphans(&scope).is_empty());
+
+        let scope = CleanupScope { include_resumable: true, ..scope };
+        assert_eq!(orphans(&scope).len(), 1);
+    }
+
+    #[test]
+    fn test_fresh_temp_files_are_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let scope = CleanupScope { min_age: Duration::from_secs(600), ..scope(dir.path()) };
+        fs::create_dir_all(&scope.certificates_dir).unwrap();
+        fs::write(scope.certificates_dir.join("WPE_x.json.tmp"), b"{").unwrap();
+        assert!(orphans(&scope).is_empty());
+    }
+}
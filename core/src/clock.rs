@@ -0,0 +1,181 @@
+//! Where certificate timestamps come from. `created_at` is read from the system
+//! clock, which may be wrong; certificates also record whether that clock was
+//! NTP-synchronized, the boot it was read in, and how long the operation took on the
+//! monotonic clock, so a disputed timestamp can be reasoned about afterwards.
+
+use crate::cert::{MonotonicSpan, TimeSource};
+use std::process::Command;
+
+const BOOT_ID_PATH: &str = "/proc/sys/kernel/random/boot_id";
+
+/// Milliseconds since boot on `CLOCK_MONOTONIC`
+pub fn monotonic_ms() -> u64 {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts);
+    }
+    ts.tv_sec as u64 * 1000 + ts.tv_nsec as u64 / 1_000_000
+}
+
+/// The kernel's random id for the current boot
+pub fn boot_id() -> Option<String> {
+    std::fs::read_to_string(BOOT_ID_PATH)
+        .ok()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// Monotonic reading taken when an operation starts
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicStart(u64);
+
+impl MonotonicStart {
+    pub fn now() -> Self {
+        Self(monotonic_ms())
+    }
+
+    /// The span from this start until now
+    pub fn finish(self) -> MonotonicSpan {
+        let end_ms = monotonic_ms().max(self.0);
+        MonotonicSpan { start_ms: self.0, end_ms, duration_ms: end_ms - self.0 }
+    }
+}
+
+/// Clock sync state for the certificate being issued: chrony when it answers,
+/// otherwise systemd's view via `timedatectl`. `monotonic` is the operation's span.
+pub fn time_source(monotonic: Option<MonotonicSpan>) -> TimeSource {
+    let mut source = run("chronyc", &["-n", "tracking"])
+        .and_then(|out| parse_chronyc_tracking(&out))
+        .or_else(|| {
+            let status = run("timedatectl", &["show"])?;
+            let server = run("timedatectl", &["show-timesync", "--property=ServerName", "--value"]);
+            parse_timedatectl(&status, server.as_deref())
+        })
+        .unwrap_or_else(unknown_source);
+    source.boot_id = boot_id();
+    source.monotonic = monotonic;
+    source
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn unknown_source() -> TimeSource {
+    TimeSource {
+        clock_source: "unknown".to_string(),
+        synchronized: None,
+        reference: None,
+        offset_ms: None,
+        boot_id: None,
+        monotonic: None,
+    }
+}
+
+/// `chronyc -n tracking` output, e.g. "Leap status : Normal" and
+/// "System time : 0.000012 seconds slow of NTP time"
+fn parse_chronyc_tracking(output: &str) -> Option<TimeSource> {
+    let field = |name: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    };
+    let leap_status = field("Leap status")?;
+
+    // "A9FEA97B (169.254.169.123)"; an unsynchronized chrony reports "00000000 ()"
+    let reference = field("Reference ID")
+        .and_then(|id| Some(id.split_once('(')?.1.trim_end_matches(')').to_string()))
+        .filter(|server| !server.is_empty());
+    let offset_ms = field("System time").and_then(|value| {
+        let mut words = value.split_whitespace();
+        let seconds: f64 = words.next()?.parse().ok()?;
+        let sign = match words.nth(1)? {
+            "fast" => 1.0,
+            "slow" => -1.0,
+            _ => return None,
+        };
+        Some(sign * seconds * 1000.0)
+    });
+
+    Some(TimeSource {
+        clock_source: "chrony".to_string(),
+        synchronized: Some(leap_status != "Not synchronised"),
+        reference,
+        offset_ms,
+        ..unknown_source()
+    })
+}
+
+/// `timedatectl show` properties (NTP=yes, NTPSynchronized=yes) and the server
+/// from `timedatectl show-timesync`
+fn parse_timedatectl(output: &str, server: Option<&str>) -> Option<TimeSource> {
+    let property = |name: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key == name).then(|| value.trim() == "yes")
+        })
+    };
+    let synchronized = property("NTPSynchronized")?;
+    let clock_source = if property("NTP").unwrap_or(false) { "systemd-timesyncd" } else { "none" };
+
+    Some(TimeSource {
+        clock_source: clock_source.to_string(),
+        synchronized: Some(synchronized),
+        reference: server.map(str::trim).filter(|server| !server.is_empty()).map(str::to_string),
+        ..unknown_source()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chronyc_tracking() {
+        let synced = "Reference ID    : A9FEA97B (169.254.169.123)\n\
+                      Stratum         : 4\n\
+                      Ref time (UTC)  : Thu Oct 16 09:12:01 2025\n\
+                      System time     : 0.000250000 seconds slow of NTP time\n\
+                      Last offset     : -0.000012000 seconds\n\
+                      Leap status     : Normal\n";
+        let source = parse_chronyc_tracking(synced).unwrap();
+        assert_eq!(source.clock_source, "chrony");
+        assert_eq!(source.synchronized, Some(true));
+        assert_eq!(source.reference.as_deref(), Some("169.254.169.123"));
+        assert!((source.offset_ms.unwrap() + 0.25).abs() < 1e-9);
+
+        let unsynced = "Reference ID    : 00000000 ()\n\
+                        System time     : 0.000000000 seconds fast of NTP time\n\
+                        Leap status     : Not synchronised\n";
+        let source = parse_chronyc_tracking(unsynced).unwrap();
+        assert_eq!(source.synchronized, Some(false));
+        assert_eq!(source.reference, None);
+
+        assert!(parse_chronyc_tracking("506 Cannot talk to daemon\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_timedatectl() {
+        let output = "Timezone=Asia/Kolkata\nLocalRTC=no\nCanNTP=yes\nNTP=yes\nNTPSynchronized=yes\n";
+        let source = parse_timedatectl(output, Some("ntp.ubuntu.com\n")).unwrap();
+        assert_eq!(source.clock_source, "systemd-timesyncd");
+        assert_eq!(source.synchronized, Some(true));
+        assert_eq!(source.reference.as_deref(), Some("ntp.ubuntu.com"));
+
+        let source = parse_timedatectl("NTP=no\nNTPSynchronized=no\n", None).unwrap();
+        assert_eq!(source.clock_source, "none");
+        assert_eq!(source.synchronized, Some(false));
+        assert!(parse_timedatectl("", None).is_none());
+    }
+
+    #[test]
+    fn test_monotonic_span() {
+        let start = MonotonicStart::now();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let span = start.finish();
+        assert!(span.duration_ms >= 5);
+        assert_eq!(span.end_ms - span.start_ms, span.duration_ms);
+    }
+}
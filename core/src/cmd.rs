@@ -2,7 +2,6 @@ use clap::Args;
 use serde_json::json;
 use crate::logging::Logger;
 use anyhow::Result;
-use dirs;
 
 #[derive(Args)]
 pub struct DiscoverArgs {
@@ -10,24 +9,59 @@ pub struct DiscoverArgs {
     #[arg(long, default_value = "json")]
     pub format: String,
     
-    /// Disable device enrichment (for testing)
-    #[arg(long)]
+    /// Skip smartctl/hdparm/nvme enrichment and list what lsblk reports (~100 ms)
+    #[arg(long = "fast", visible_alias = "no-enrich")]
     pub no_enrich: bool,
+
+    /// Show which risk rule (see the site policy's `risk` section) fired for each disk
+    #[arg(long, conflicts_with_all = ["removable_targets", "phones"])]
+    pub explain_risk: bool,
+
+    /// List removable/USB volumes usable as backup destinations instead of disks
+    #[arg(long)]
+    pub removable_targets: bool,
+
+    /// List phones connected over ADB or MTP, usable with `backup --source`
+    #[arg(long, conflicts_with = "removable_targets")]
+    pub phones: bool,
+
+    /// Show this machine's identity (hostname, SMBIOS manufacturer, model, serials and
+    /// BIOS version), as recorded on wipe certificates, instead of disks
+    #[arg(long, conflicts_with_all = ["removable_targets", "phones", "explain_risk"])]
+    pub host: bool,
 }
 
+#[cfg(feature = "operations")]
 #[derive(Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct BackupArgs {
     /// Source device to backup from
-    #[arg(long)]
-    pub device: String,
+    #[arg(long, required_unless_present_any = ["resume", "source", "dry_run"])]
+    pub device: Option<String>,
     
     /// Destination path for backup
-    #[arg(long)]
-    pub dest: String,
+    #[arg(long, required_unless_present_any = ["resume", "dry_run"])]
+    pub dest: Option<String>,
     
     /// Specific paths to backup (defaults to common user directories)
     #[arg(long)]
     pub paths: Vec<String>,
+
+    /// Leave out files and directories matching this pattern; `*` matches any run of
+    /// characters. A pattern with a `/` is matched against the whole path, any other
+    /// against each path component (e.g. `node_modules`, `*.tmp`). Repeatable.
+    #[arg(long)]
+    pub exclude: Vec<String>,
+
+    /// List what the backup would copy, what --exclude leaves out and, with --dest,
+    /// whether it fits there, without writing anything
+    #[arg(long, conflicts_with_all = ["resume", "source"])]
+    pub dry_run: bool,
+
+    /// Note recorded in the certificate and shown on its PDF, e.g. "customer approved
+    /// via ticket 4521"
+    #[arg(long, value_parser = parse_note)]
+    pub note: Option<String>,
     
     /// Sign the generated certificate
     #[arg(long)]
@@ -40,8 +74,216 @@ pub struct BackupArgs {
     /// Allow overwriting existing signature
     #[arg(long)]
     pub force: bool,
+
+    /// Per-file error handling (skip, abort)
+    #[arg(long, default_value = "abort")]
+    pub on_error: String,
+
+    /// Block device that was mounted for this backup; it is recorded in the log and
+    /// safely unmounted once the backup finishes
+    #[arg(long)]
+    pub auto_mounted: Option<String>,
+
+    /// Continue a backup that was cancelled or ran out of space, from its backup
+    /// directory. Needs the backup passphrase it was started with.
+    #[arg(long, conflicts_with_all = ["device", "dest", "paths", "exclude", "source"])]
+    pub resume: Option<std::path::PathBuf>,
+
+    /// Back up a phone instead of a disk: `adb:` (USB debugging) or `mtp:` (file
+    /// transfer), optionally with the phone's id (`adb:<serial>`, `mtp:<bus>,<device>`).
+    /// --paths are then paths on the phone (defaults to its photo and download folders).
+    #[arg(long, conflicts_with = "device")]
+    pub source: Option<String>,
+
+    /// Seconds to wait for space to be freed when the destination fills up, before
+    /// stopping (the backup stays resumable)
+    #[arg(long, default_value_t = 1800)]
+    pub space_wait: u64,
+
+    #[command(flatten)]
+    pub resources: ResourceArgs,
+
+    #[command(subcommand)]
+    pub command: Option<BackupCommands>,
+}
+
+/// Flags for running a backup or wipe in the background without hogging the machine
+#[cfg(feature = "operations")]
+#[derive(Args, Default)]
+pub struct ResourceArgs {
+    /// CPU niceness for this run and the tools it starts, -20 (highest priority) to 19 (lowest)
+    #[arg(long, allow_hyphen_values = true, value_parser = clap::value_parser!(i32).range(-20..=19))]
+    pub nice: Option<i32>,
+
+    /// I/O scheduling class: idle (only use the disk when nothing else does) or
+    /// best-effort[:0-7]
+    #[arg(long, value_parser = crate::priority::IoClass::parse)]
+    pub ionice: Option<crate::priority::IoClass>,
+
+    /// cgroup v2 I/O weight, 1 to 10000 (other processes have 100); needs root
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..=10000))]
+    pub io_weight: Option<u16>,
+}
+
+#[cfg(feature = "operations")]
+impl ResourceArgs {
+    /// Lower this process's priorities as asked and log what took effect. Called before
+    /// the heavy work starts so the threads and processes it spawns inherit them.
+    fn apply(&self, logger: &Logger) {
+        let limits = crate::priority::ResourceLimits { nice: self.nice, io_class: self.ionice, io_weight: self.io_weight };
+        if limits.is_empty() {
+            return;
+        }
+
+        let applied = limits.apply();
+        for warning in &applied.warnings {
+            crate::events::warn(warning);
+        }
+        logger.log_json(&json!({
+            "step": "resource_limits",
+            "applied": applied,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+    }
+}
+
+/// What `run_backup` does: start a new backup or finish an interrupted one
+#[cfg(feature = "operations")]
+enum BackupStart<'a> {
+    New { device: &'a str, dest: &'a str },
+    /// `--source adb:` or `--source mtp:`
+    Phone { source: &'a str, dest: &'a str },
+    Resume(&'a std::path::Path),
+}
+
+#[cfg(feature = "operations")]
+impl BackupStart<'_> {
+    /// Where the backup is written, for telling a failing drive from an unplugged one
+    fn location(&self) -> std::path::PathBuf {
+        match self {
+            BackupStart::New { dest, .. } | BackupStart::Phone { dest, .. } => {
+                std::path::PathBuf::from(shellexpand::tilde(dest).as_ref())
+            }
+            BackupStart::Resume(dir) => dir.to_path_buf(),
+        }
+    }
+}
+
+/// Bring the phone's files into a local directory and back them up from there
+#[cfg(feature = "operations")]
+fn backup_from_phone(
+    engine: crate::backup::EncryptedBackup,
+    spec: &str,
+    paths: &[String],
+    dest: &str,
+    logger: &Logger,
+) -> Result<crate::backup::BackupResult, Box<dyn std::error::Error>> {
+    use crate::backup::BackupOperations;
+    use crate::phone::{PhoneFiles, PhoneSource};
+
+    let source = PhoneSource::parse(spec)?;
+    logger.log_info(&format!("Copying files from {} phone", source.transport.as_str()));
+    let phone = PhoneFiles::open(&source, paths)?;
+    logger.log_json(&json!({
+        "step": "phone_source",
+        "device": phone.device,
+        "paths": phone.local_paths(),
+        "missing_paths": phone.missing,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    for path in &phone.missing {
+        crate::events::warn(&format!("{} was not found on the phone", path));
+    }
+    if phone.local_paths().is_empty() {
+        return Err("None of the requested folders exist on the phone".into());
+    }
+
+    let device = format!("{}:{}", source.transport.as_str(), phone.device.id);
+    let engine = engine.with_source(phone.base(), phone.device.certificate_device());
+    engine.perform_backup(&device, phone.local_paths(), dest)
+}
+
+/// An error for the command's error response, classified against the device or
+/// destination the operation ran on so the response can carry a remediation hint
+#[cfg(feature = "operations")]
+fn io_failure(context: &str, error: &(dyn std::error::Error + 'static), location: &std::path::Path) -> anyhow::Error {
+    let message = format!("{}: {}", context, error);
+    match crate::remediation::classify(error, Some(location)) {
+        Some(failure) => anyhow::Error::new(crate::remediation::ClassifiedError { failure, message }),
+        None => anyhow::anyhow!(message),
+    }
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum BackupCommands {
+    /// Re-check a stored backup against its manifest
+    Verify {
+        /// Backup directory containing manifest.json
+        #[arg(long)]
+        dir: std::path::PathBuf,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Decrypt a passphrase-protected backup into a directory, keeping sparse files sparse
+    Restore {
+        /// Backup directory containing manifest.json and key.json
+        #[arg(long)]
+        dir: std::path::PathBuf,
+
+        /// Directory to restore into; files already there are not overwritten
+        #[arg(long)]
+        to: std::path::PathBuf,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Move backups outside the retention rules out of a destination (defaults from the site policy)
+    Prune {
+        /// Destination holding one directory per backup
+        #[arg(long)]
+        dir: std::path::PathBuf,
+
+        /// Always keep this many of the newest backups
+        #[arg(long)]
+        keep_last: Option<usize>,
+
+        /// Prune backups older than this age, e.g. 180d, 6m or 1y
+        #[arg(long)]
+        older_than: Option<String>,
+
+        /// Where pruned backups go (defaults to the policy archive_dir, else <dir>/.trash)
+        #[arg(long)]
+        to: Option<std::path::PathBuf>,
+
+        /// List what would be pruned without moving anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// List unfinished backups left in a destination by a crash or a stopped backup
+    Stale {
+        /// Destination holding one directory per backup
+        #[arg(long)]
+        dir: std::path::PathBuf,
+
+        /// Delete them, including ones that could still be resumed
+        #[arg(long)]
+        remove: bool,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
 }
 
+#[cfg(feature = "operations")]
 #[derive(Args)]
 pub struct WipeArgs {
     /// Device to wipe
@@ -83,6 +325,67 @@ pub struct WipeArgs {
     /// Link to existing backup certificate ID
     #[arg(long)]
     pub backup_cert_id: Option<String>,
+
+    /// Note recorded in the certificate and shown on its PDF, e.g. "customer approved
+    /// via ticket 4521"
+    #[arg(long, value_parser = parse_note)]
+    pub note: Option<String>,
+
+    /// Only run post-cancel cleanup on the device (never wipes or remounts)
+    #[arg(long)]
+    pub cleanup_only: bool,
+
+    /// Seed for picking verification sectors; recorded in the certificate so the
+    /// check can be replayed (random when omitted)
+    #[arg(long)]
+    pub verify_seed: Option<u64>,
+
+    /// Verification after an overwrite: heuristic (sectors look wiped), pattern (write a
+    /// keyed pattern and compare sampled sectors) or pattern-full (compare every sector)
+    #[arg(long, default_value = "heuristic")]
+    pub verify_mode: String,
+
+    /// Overwrite profile: nist-clear, dod-5220.22-m-3pass or gutmann-lite (defaults to
+    /// the site policy's profile, else nist-clear)
+    #[arg(long)]
+    pub overwrite_profile: Option<String>,
+
+    /// Days to look back for an earlier PASS wipe of the same drive (0 disables the check)
+    #[arg(long, default_value = "30")]
+    pub duplicate_window_days: u32,
+
+    /// What to do when the drive already has a recent PASS wipe at this policy or
+    /// stronger (warn or skip)
+    #[arg(long, default_value = "warn")]
+    pub on_duplicate: String,
+
+    /// Signed approval (from `approval sign`); required to wipe HIGH/CRITICAL devices
+    #[arg(long)]
+    pub approval: Option<std::path::PathBuf>,
+
+    /// A plan saved earlier from `--format json`; the wipe only runs if the drive
+    /// still plans exactly the same, and the plan stands in for the typed confirmation
+    #[arg(long, value_name = "PLAN_JSON")]
+    pub approve_plan: Option<std::path::PathBuf>,
+
+    /// Treat the target as virtual media (a disk image file or hypervisor/cloud volume)
+    /// even if it isn't recognised as one: overwrite only, no controller commands
+    #[arg(long = "virtual")]
+    pub virtual_media: bool,
+
+    /// Acknowledge that the target is a network block device (iSCSI, NBD, Ceph RBD,
+    /// NVMe over Fabrics): it is only overwritten through this host, and nothing
+    /// vouches for copies the remote storage keeps
+    #[arg(long)]
+    pub allow_network_device: bool,
+
+    /// Drop every capability the wipe doesn't need, and run overwrite passes without
+    /// any under a seccomp filter (always on in ISO builds)
+    #[arg(long)]
+    pub hardened: bool,
+
+    #[command(flatten)]
+    pub resources: ResourceArgs,
 }
 
 #[derive(Args)]
@@ -94,6 +397,22 @@ pub struct CertArgs {
     /// Export certificate as PDF
     #[arg(long)]
     pub export_pdf: Option<String>,
+
+    /// PDF detail: full (wipe PDFs get a command log appendix) or summary
+    #[arg(long, default_value = "full")]
+    pub pdf_detail: String,
+
+    /// Path to Ed25519 private key used to stamp the exported PDF (defaults to SECUREWIPE_SIGN_KEY_PATH)
+    #[arg(long)]
+    pub sign_key_path: Option<std::path::PathBuf>,
+
+    /// Print certificate as text, for tickets and email where a PDF is awkward
+    #[arg(long)]
+    pub export_text: Option<String>,
+
+    /// Text rendering for --export-text: plain or markdown
+    #[arg(long, default_value = "plain")]
+    pub text_format: String,
     
     #[command(subcommand)]
     pub command: Option<CertCommands>,
@@ -102,6 +421,7 @@ pub struct CertArgs {
 #[derive(clap::Subcommand)]
 pub enum CertCommands {
     /// Sign a certificate file
+    #[cfg(feature = "operations")]
     Sign {
         /// Path to certificate JSON file to sign
         #[arg(long)]
@@ -115,11 +435,15 @@ pub enum CertCommands {
         #[arg(long)]
         force: bool,
     },
-    /// Verify a signed certificate file
+    /// Verify a signed certificate file, or the signature stamp on an exported PDF
     Verify {
         /// Path to certificate JSON file to verify
+        #[arg(long, required_unless_present = "pdf", conflicts_with = "pdf")]
+        file: Option<std::path::PathBuf>,
+
+        /// Path to an exported certificate PDF whose stamp should be verified
         #[arg(long)]
-        file: std::path::PathBuf,
+        pdf: Option<std::path::PathBuf>,
         
         /// Path to Ed25519 public key PEM file
         #[arg(long)]
@@ -131,1250 +455,4678 @@ pub enum CertCommands {
         #[arg(long)]
         file: std::path::PathBuf,
     },
+    /// Re-read the sectors sampled during a wipe and compare them to the certificate
+    #[cfg(feature = "operations")]
+    ReplayVerify {
+        /// Wipe certificate ID (looked up in ~/SecureWipe/certificates)
+        cert_id: String,
+
+        /// Device to re-read
+        #[arg(long)]
+        device: String,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Check a scanned certificate QR payload (SW1:...) offline against the issuer's public key
+    VerifyQr {
+        /// Text decoded from the QR code by a scanner
+        payload: String,
+
+        /// Path to Ed25519 public key PEM file
+        #[arg(long)]
+        pubkey: std::path::PathBuf,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Re-render PDFs for stored certificates, e.g. after a branding or layout change
+    #[cfg(feature = "operations")]
+    RegeneratePdf {
+        /// Regenerate every stored certificate
+        #[arg(long, conflicts_with = "since", required_unless_present = "since")]
+        all: bool,
+
+        /// Only certificates created on or after this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// PDF detail: full or summary
+        #[arg(long, default_value = "full")]
+        pdf_detail: String,
+
+        /// Path to Ed25519 private key used to stamp the PDFs (defaults to SECUREWIPE_SIGN_KEY_PATH)
+        #[arg(long)]
+        sign_key_path: Option<std::path::PathBuf>,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Move certificates older than an age, with their PDFs and labels, to an archive
+    #[cfg(feature = "operations")]
+    Archive {
+        /// Archive certificates older than this age, e.g. 2y or 18m (defaults from the site policy)
+        #[arg(long)]
+        older_than: Option<String>,
+
+        /// Archive directory (defaults to <policy archive_dir>/certificates)
+        #[arg(long)]
+        to: Option<std::path::PathBuf>,
+
+        /// List what would be archived without moving anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Render a printable drive label (QR, serial, policy, date, result)
+    #[cfg(feature = "operations")]
+    Label {
+        /// Wipe or destroy certificate ID (looked up in ~/SecureWipe/certificates)
+        cert_id: String,
+
+        /// Label format (zpl or png)
+        #[arg(long, default_value = "zpl")]
+        format: String,
+
+        /// Where to write the label (defaults to <cert_id>_label.<format> next to the certificate)
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Add an auditor note to a certificate, kept in a sidecar file so the signed
+    /// certificate is never changed; without a note, list the existing ones
+    #[cfg(feature = "operations")]
+    Annotate {
+        /// Certificate ID (looked up in ~/SecureWipe/certificates)
+        cert_id: String,
+
+        /// The note
+        #[arg(value_parser = parse_note)]
+        note: Option<String>,
+
+        /// Who is adding the note (defaults to $USER)
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Total wall time, bytes processed and estimated energy of stored certificates,
+    /// e.g. for billing
+    #[cfg(feature = "operations")]
+    Report {
+        /// Start of the period (YYYY-MM-DD or RFC 3339), inclusive
+        #[arg(long)]
+        since: Option<String>,
+        /// End of the period (YYYY-MM-DD or RFC 3339), exclusive
+        #[arg(long)]
+        until: Option<String>,
+        /// Only certificates of this type (backup, wipe or destroy)
+        #[arg(long = "type")]
+        cert_type: Option<String>,
+        /// Output format (json, human or csv)
+        #[arg(long, default_value = "human")]
+        format: String,
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Check the certificates directory for unreadable or schema-invalid certificates,
+    /// broken signatures, orphaned PDFs, duplicate cert ids and search index drift
+    #[cfg(feature = "operations")]
+    Fsck {
+        /// Issuer's Ed25519 public key PEM; signatures are only checked when given
+        #[arg(long)]
+        pubkey: Option<std::path::PathBuf>,
+
+        /// Rebuild the search index from the directory before checking
+        #[arg(long)]
+        repair: bool,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+    /// Queue a later replay of a wipe certificate's verification samples; `tasks run`
+    /// carries it out once it is due
+    #[cfg(feature = "operations")]
+    ScheduleReverify {
+        /// Wipe certificate ID (looked up in ~/SecureWipe/certificates)
+        cert_id: String,
+
+        /// How long from now, e.g. 30d, 6w, 6m or 1y
+        #[arg(long)]
+        after: String,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Write the certificates and sidecars changed since a cursor to a signed bundle,
+    /// for carrying from an air-gapped site to the central audit server
+    #[cfg(feature = "operations")]
+    ExportDelta(ExportDeltaArgs),
+    /// Issue one signed certificate for a whole machine, aggregating the wipe or
+    /// destroy certificates of its drives
+    #[cfg(feature = "operations")]
+    System {
+        /// Certificate IDs of the machine's drives (looked up in ~/SecureWipe/certificates)
+        #[arg(required = true)]
+        cert_ids: Vec<String>,
+
+        /// Chassis serial to record when SMBIOS doesn't report one, e.g. from the case label
+        #[arg(long)]
+        chassis_serial: Option<String>,
+
+        /// Notes printed on the certificate
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Path to Ed25519 private key for signing (defaults to SECUREWIPE_SIGN_KEY_PATH)
+        #[arg(long)]
+        sign_key_path: Option<std::path::PathBuf>,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Check a bundle from `cert export-delta` and add its certificates here
+    ImportDelta {
+        /// Bundle file
+        bundle: std::path::PathBuf,
+
+        /// Issuer's Ed25519 public key PEM, checked against the bundle and every certificate in it
+        #[arg(long)]
+        pubkey: std::path::PathBuf,
+
+        /// Import even though earlier bundles from the site are missing
+        #[arg(long)]
+        allow_gap: bool,
+
+        /// Check the bundle and list what would be written without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
 }
 
-pub fn handle_discover(args: DiscoverArgs, logger: &Logger) -> Result<()> {
-    use crate::device::{DeviceDiscovery, LinuxDeviceDiscovery};
-    
-    logger.log_info("Starting device discovery");
-    
-    let discovery = if args.no_enrich {
-        LinuxDeviceDiscovery::new_without_enrichment()
-    } else {
-        LinuxDeviceDiscovery::new()
-    };
-    
-    match discovery.discover_devices() {
-        Ok(devices) => {
-            logger.log_info(&format!("Found {} devices", devices.len()));
-            
-            if args.format == "json" {
-                println!("{}", serde_json::to_string_pretty(&devices)?);
-            } else {
-                // Human-readable format
-                for device in &devices {
-                    println!("Device: {}", device.name);
-                    if let Some(ref model) = device.model {
-                        println!("  Model: {}", model);
-                    }
-                    if let Some(ref serial) = device.serial {
-                        println!("  Serial: {}", serial);
-                    }
-                    println!("  Capacity: {} bytes", device.capacity_bytes);
-                    if let Some(ref bus) = device.bus {
-                        println!("  Bus: {}", bus);
-                    }
-                    println!("  Risk Level: {:?}", device.risk_level);
-                    if !device.mountpoints.is_empty() {
-                        println!("  Mountpoints: {}", device.mountpoints.join(", "));
-                    }
-                    println!();
-                }
-            }
-            
-            Ok(())
-        }
-        Err(e) => {
-            let error_msg = format!("Device discovery failed: {}", e);
-            logger.log_error(&error_msg);
-            Err(anyhow::anyhow!(error_msg))
-        }
-    }
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct ExportDeltaArgs {
+    /// Cursor to continue from, or a cursor file (defaults to the cursor file)
+    #[arg(long)]
+    pub since: Option<String>,
+
+    /// Start from the beginning, ignoring the cursor file
+    #[arg(long, conflicts_with = "since")]
+    pub full: bool,
+
+    /// Bundle file to write
+    #[arg(long)]
+    pub out: std::path::PathBuf,
+
+    /// Cursor file, advanced after the bundle is written (defaults to <data_dir>/sync.cursor)
+    #[arg(long)]
+    pub cursor_file: Option<std::path::PathBuf>,
+
+    /// Most files in one bundle; the rest wait for the next export
+    #[arg(long)]
+    pub max_files: Option<usize>,
+
+    /// Most bytes of certificates in one bundle, e.g. to fit removable media
+    #[arg(long)]
+    pub max_bytes: Option<u64>,
+
+    /// Name of this site in the bundle (defaults to the hostname)
+    #[arg(long)]
+    pub site: Option<String>,
+
+    /// Path to Ed25519 private key used to sign the bundle (defaults to SECUREWIPE_SIGN_KEY_PATH)
+    #[arg(long)]
+    pub sign_key_path: Option<std::path::PathBuf>,
+
+    /// Output format (json or human)
+    #[arg(long, default_value = "json")]
+    pub format: String,
 }
 
-pub fn handle_backup(args: BackupArgs, logger: &Logger) -> Result<()> {
-    use crate::backup::{EncryptedBackup, BackupOperations};
-    
-    logger.log_info("Starting backup operation");
-    
-    let backup_engine = EncryptedBackup::new();
-    let paths = &args.paths;
-    
-    match backup_engine.perform_backup(&args.device, &paths, &args.dest) {
-        Ok(result) => {
-            logger.log_info("Backup completed successfully");
-            
-            println!("Backup completed successfully!");
-            println!("Backup ID: {}", result.backup_id);
-            println!("Encryption: {}", result.encryption_method);
-            println!("Files processed: {}", result.manifest.total_files);
-            println!("Total bytes: {}", result.manifest.total_bytes);
-            println!("Verification samples: {}/{}", 
-                     if result.verification_passed { result.verification_samples } else { 0 },
-                     result.verification_samples);
-            println!("Verification status: {}", 
-                     if result.verification_passed { "PASSED" } else { "FAILED" });
-            
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct DestroyArgs {
+    #[command(subcommand)]
+    pub command: DestroyCommands,
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum DestroyCommands {
+    /// Issue a signed certificate for a drive that was physically destroyed
+    Attest(DestroyAttestArgs),
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct InventoryArgs {
+    #[command(subcommand)]
+    pub command: InventoryCommands,
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum InventoryCommands {
+    /// List every drive in the inventory, most recently seen first
+    List {
+        /// Only show drives in this state (discovered, wiped, wipe_failed, destroyed)
+        #[arg(long)]
+        status: Option<String>,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Show one drive and every certificate issued for it
+    Show {
+        /// Drive serial number, stable device ID or alias
+        serial: String,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Give a drive a name that UI and CLI show and accept in place of its serial
+    Alias {
+        /// Drive serial number, stable device ID or current alias
+        drive: String,
+
+        /// New name for the drive
+        #[arg(required_unless_present = "clear")]
+        name: Option<String>,
+
+        /// Remove the drive's name
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
+    },
+    /// List named drives by stable device ID
+    Aliases {
+        /// Output format (json or human)
+        #[arg(long, default_value = "json")]
+        format: String,
+    },
+    /// Export the whole inventory
+    Export {
+        /// Export format (json or csv)
+        #[arg(long, default_value = "json")]
+        format: String,
+
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+    },
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct ApprovalArgs {
+    #[command(subcommand)]
+    pub command: ApprovalCommands,
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum ApprovalCommands {
+    /// Plan a wipe and write the request an approver has to sign
+    Request {
+        /// Device to wipe
+        #[arg(long)]
+        device: String,
+
+        /// Wipe policy (CLEAR, PURGE)
+        #[arg(long, default_value = "PURGE")]
+        policy: String,
+
+        /// Plan as if running from the bootable ISO (required for CRITICAL disks)
+        #[arg(long)]
+        iso_mode: bool,
+
+        /// Where to write the request (stdout when omitted)
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Sign an approval request with the approver's Ed25519 key
+    Sign {
+        /// Request file from `approval request`
+        #[arg(long)]
+        request: std::path::PathBuf,
+
+        /// Approver name as listed in the roles file
+        #[arg(long)]
+        approver: String,
+
+        /// Approver's Ed25519 private key (defaults to SECUREWIPE_SIGN_KEY_PATH)
+        #[arg(long)]
+        key: Option<std::path::PathBuf>,
+
+        /// How long the approval stays valid
+        #[arg(long, default_value = "24")]
+        valid_hours: i64,
+
+        /// Where to write the approval (stdout when omitted)
+        #[arg(long)]
+        output: Option<std::path::PathBuf>,
+    },
+}
+
+/// Global flags that override config files and environment for this run
+#[derive(Args, Default)]
+pub struct ConfigOverrides {
+    /// Base directory for certificates, the inventory and policy files
+    #[arg(long, global = true)]
+    pub data_dir: Option<std::path::PathBuf>,
+
+    /// Where certificates are written and looked up
+    #[arg(long, global = true)]
+    pub certificates_dir: Option<std::path::PathBuf>,
+
+    /// Inventory database path
+    #[arg(long, global = true)]
+    pub inventory_db: Option<std::path::PathBuf>,
+
+    /// Site wipe policy file
+    #[arg(long, global = true)]
+    pub policy_file: Option<std::path::PathBuf>,
+
+    /// Approval roles file
+    #[arg(long, global = true)]
+    pub roles_file: Option<std::path::PathBuf>,
+
+    /// Telemetry opt-in file
+    #[arg(long, global = true)]
+    pub telemetry_file: Option<std::path::PathBuf>,
+}
+
+impl ConfigOverrides {
+    pub fn into_layer(self) -> crate::config::ConfigLayer {
+        crate::config::ConfigLayer {
+            data_dir: self.data_dir,
+            certificates_dir: self.certificates_dir,
+            sign_key_path: None,
+            inventory_db: self.inventory_db,
+            policy_file: self.policy_file,
+            roles_file: self.roles_file,
+            telemetry_file: self.telemetry_file,
+        }
+    }
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommands,
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum ConfigCommands {
+    /// Print the effective configuration and where each setting came from
+    Show {
+        /// Output format (json or human)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+    /// Move ~/SecureWipe to the XDG data directory (or /var/lib/securewipe in system mode)
+    Migrate {
+        /// Show what would move without moving anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct SecretsArgs {
+    #[command(subcommand)]
+    pub command: SecretsCommands,
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum SecretsCommands {
+    /// Store a passphrase in the OS keyring (backup-passphrase or sign-key-passphrase)
+    Set {
+        name: String,
+        /// Read the passphrase from stdin instead of prompting
+        #[arg(long)]
+        stdin: bool,
+    },
+    /// Remove a passphrase from the OS keyring
+    Unset { name: String },
+    /// Show which passphrases are available and from where, never their values
+    Status {
+        /// Output format (json or human)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct KeysArgs {
+    #[command(subcommand)]
+    pub command: KeysCommands,
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum KeysCommands {
+    /// Summarize signatures per key from the audit log, and check the log's hash chain
+    UsageReport {
+        /// Start of the period (YYYY-MM-DD or RFC 3339), inclusive
+        #[arg(long)]
+        since: Option<String>,
+        /// End of the period (YYYY-MM-DD or RFC 3339), exclusive
+        #[arg(long)]
+        until: Option<String>,
+        /// Output format (json or human)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct CleanupArgs {
+    /// List what would be removed without removing anything
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Also look for unfinished backups in this destination (repeatable)
+    #[arg(long = "backup-dest", value_name = "DIR")]
+    pub backup_dests: Vec<std::path::PathBuf>,
+    /// Also remove unfinished backups that `backup --resume` could still finish
+    #[arg(long)]
+    pub include_resumable: bool,
+    /// Leave temp files younger than this many minutes alone; a running command may still need them
+    #[arg(long, value_name = "MINUTES", default_value_t = 10)]
+    pub min_age: u64,
+    /// Output format (json or human)
+    #[arg(long, default_value = "human")]
+    pub format: String,
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct IdentifyArgs {
+    /// Device to point out
+    #[arg(long)]
+    pub device: String,
+    /// How long to blink it (at most 300)
+    #[arg(long, default_value_t = 30)]
+    pub seconds: u64,
+    /// auto (ledctl, else reads), ledctl (enclosure locate LED) or reads (activity LED)
+    #[arg(long, default_value = "auto")]
+    pub method: String,
+    /// Output format (json or human)
+    #[arg(long, default_value = "human")]
+    pub format: String,
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct TasksArgs {
+    #[command(subcommand)]
+    pub command: TasksCommands,
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum TasksCommands {
+    /// Run every task that has come due
+    Run {
+        /// Output format (json or human)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+    /// List pending tasks
+    List {
+        /// Include tasks that have already run
+        #[arg(long)]
+        all: bool,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+    /// Every re-verification recorded for a certificate, oldest first
+    History {
+        /// Wipe certificate ID (looked up in ~/SecureWipe/certificates)
+        cert_id: String,
+
+        /// Output format (json or human)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct SchemaArgs {
+    #[command(subcommand)]
+    pub command: SchemaCommands,
+}
+
+#[cfg(feature = "operations")]
+#[derive(clap::Subcommand)]
+pub enum SchemaCommands {
+    /// Write a JSON Schema for every plan, result, progress, error and certificate document
+    Export {
+        /// Output directory (created if missing)
+        #[arg(long)]
+        out: std::path::PathBuf,
+    },
+}
+
+#[cfg(feature = "operations")]
+#[derive(Args)]
+pub struct DestroyAttestArgs {
+    /// Destruction method (shred, disintegrate, pulverize, incinerate, melt, degauss)
+    #[arg(long)]
+    pub method: String,
+
+    /// Person who carried out the destruction
+    #[arg(long)]
+    pub operator: String,
+
+    /// Person who witnessed the destruction
+    #[arg(long)]
+    pub witness: String,
+
+    /// Read model/serial/capacity from this device before it is destroyed
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Drive serial number (required unless --device can report it)
+    #[arg(long)]
+    pub serial: Option<String>,
+
+    /// Drive model
+    #[arg(long)]
+    pub model: Option<String>,
+
+    /// Drive bus (SATA, NVMe, USB, SAS)
+    #[arg(long)]
+    pub bus: Option<String>,
+
+    /// Drive capacity in bytes
+    #[arg(long)]
+    pub capacity_bytes: Option<u64>,
+
+    /// Organization asset tag on the drive
+    #[arg(long)]
+    pub asset_tag: Option<String>,
+
+    /// Why the drive could not be sanitized electronically
+    #[arg(long)]
+    pub reason: Option<String>,
+
+    /// When the destruction happened (RFC 3339, defaults to now)
+    #[arg(long)]
+    pub performed_at: Option<String>,
+
+    /// Where the destruction happened
+    #[arg(long)]
+    pub location: Option<String>,
+
+    /// Shredder/degausser make and model
+    #[arg(long)]
+    pub equipment: Option<String>,
+
+    /// Maximum particle size after destruction, in millimetres
+    #[arg(long)]
+    pub particle_size_mm: Option<f64>,
+
+    /// Free-form notes
+    #[arg(long)]
+    pub notes: Option<String>,
+
+    /// Photo of the drive or its remains; repeat for several photos (only hashes are stored)
+    #[arg(long = "photo")]
+    pub photos: Vec<std::path::PathBuf>,
+
+    /// Link to a backup certificate taken before destruction
+    #[arg(long)]
+    pub backup_cert_id: Option<String>,
+
+    /// Link to a failed or partial wipe certificate for the same drive
+    #[arg(long)]
+    pub wipe_cert_id: Option<String>,
+
+    /// Path to Ed25519 private key for signing
+    #[arg(long)]
+    pub sign_key_path: Option<std::path::PathBuf>,
+
+    /// Output format (json or human)
+    #[arg(long, default_value = "json")]
+    pub format: String,
+}
+
+pub fn handle_discover(args: DiscoverArgs, logger: &Logger) -> Result<()> {
+    use crate::device::{DeviceDiscovery, LinuxDeviceDiscovery};
+    
+    logger.log_info("Starting device discovery");
+
+    // A broken policy file must not silently fall back to the built-in risk rules
+    let site_policy = crate::policy::SitePolicy::load_default().map_err(|e| {
+        let error_msg = format!("Failed to load site policy: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let display = crate::locale::DisplayFormat::for_policy(&site_policy.display);
+    crate::risk::install(site_policy.risk);
+    
+    let discovery = if args.no_enrich {
+        LinuxDeviceDiscovery::new_without_enrichment()
+    } else {
+        LinuxDeviceDiscovery::new()
+    };
+
+    if args.removable_targets {
+        return handle_discover_removable_targets(&discovery, &args.format, &display, logger);
+    }
+    if args.phones {
+        return handle_discover_phones(&args.format, logger);
+    }
+    if args.host {
+        return handle_discover_host(&args.format, logger);
+    }
+    
+    match discovery.discover_devices() {
+        Ok(mut devices) => {
+            logger.log_info(&format!("Found {} devices", devices.len()));
+            for device in &mut devices {
+                if !args.explain_risk {
+                    device.risk_explanation = None;
+                }
+                device.capacity_display = Some(display.bytes(device.capacity_bytes));
+            }
+
+            with_inventory(logger, |inventory| {
+                for device in &devices {
+                    inventory.record_seen(device)?;
+                }
+                Ok(())
+            });
+            
+            if args.format == "json" {
+                println!("{}", serde_json::to_string_pretty(&devices)?);
+            } else {
+                // Human-readable format
+                println!("Host: {}", crate::host::describe(&crate::host::probe()));
+                println!();
+                for device in &devices {
+                    println!("Device: {}", device.name);
+                    if let Some(ref model) = device.model {
+                        println!("  Model: {}", model);
+                    }
+                    if let Some(ref serial) = device.serial {
+                        println!("  Serial: {}", serial);
+                    }
+                    println!("  Capacity: {}", display.bytes_exact(device.capacity_bytes));
+                    if let Some(ref bus) = device.bus {
+                        println!("  Bus: {}", bus);
+                    }
+                    println!("  Risk Level: {:?}", device.risk_level);
+                    if let Some(ref explanation) = device.risk_explanation {
+                        match explanation.matched {
+                            Some(ref matched) => println!("  Risk Rule: {} (matched {})", explanation.rule, matched),
+                            None => println!("  Risk Rule: {}", explanation.rule),
+                        }
+                    }
+                    if !device.mountpoints.is_empty() {
+                        println!("  Mountpoints: {}", device.mountpoints.join(", "));
+                    }
+                    println!();
+                }
+            }
+            
+            Ok(())
+        }
+        Err(e) => {
+            let error_msg = format!("Device discovery failed: {}", e);
+            logger.log_error(&error_msg);
+            Err(anyhow::anyhow!(error_msg))
+        }
+    }
+}
+
+fn handle_discover_removable_targets(
+    discovery: &crate::device::LinuxDeviceDiscovery,
+    format: &str,
+    display: &crate::locale::DisplayFormat,
+    logger: &Logger,
+) -> Result<()> {
+    let volumes = discovery.discover_removable_targets().map_err(|e| {
+        let error_msg = format!("Removable target discovery failed: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    logger.log_info(&format!("Found {} removable volumes", volumes.len()));
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&volumes)?);
+    } else {
+        for volume in &volumes {
+            println!("Volume: {} ({})", volume.device,
+                     volume.mountpoint.as_deref().unwrap_or("not mounted"));
+            if let Some(ref label) = volume.label {
+                println!("  Label: {}", label);
+            }
+            if let Some(ref fs) = volume.filesystem {
+                println!("  Filesystem: {}", fs);
+            }
+            println!("  Capacity: {}", display.bytes_exact(volume.capacity_bytes));
+            if let Some(free) = volume.free_bytes {
+                println!("  Free: {}", display.bytes_exact(free));
+            }
+            println!("  Backing device: {}", volume.backing_device);
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_discover_host(format: &str, logger: &Logger) -> Result<()> {
+    let host = crate::host::probe();
+    logger.log_info(&format!("Host: {}", crate::host::describe(&host)));
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&host)?);
+        return Ok(());
+    }
+    println!("Hostname: {}", host.hostname);
+    for (label, value) in [
+        ("Manufacturer", &host.system_manufacturer),
+        ("Model", &host.system_model),
+        ("System serial", &host.system_serial),
+        ("Chassis serial", &host.chassis_serial),
+        ("Asset tag", &host.asset_tag),
+        ("System UUID", &host.system_uuid),
+        ("Machine ID", &host.machine_id),
+        ("BIOS vendor", &host.bios_vendor),
+        ("BIOS version", &host.bios_version),
+        ("BIOS date", &host.bios_date),
+    ] {
+        if let Some(value) = value {
+            println!("{}: {}", label, value);
+        }
+    }
+    Ok(())
+}
+
+fn handle_discover_phones(format: &str, logger: &Logger) -> Result<()> {
+    use crate::phone::{list_devices, Transport};
+
+    let mut phones = Vec::new();
+    for transport in [Transport::Adb, Transport::Mtp] {
+        // Either tool may be absent; list whatever the other one finds
+        match list_devices(transport) {
+            Ok(found) => phones.extend(found),
+            Err(e) => logger.log_info(&format!("Skipping {} phones: {}", transport.as_str(), e)),
+        }
+    }
+    logger.log_info(&format!("Found {} phones", phones.len()));
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&phones)?);
+    } else {
+        for phone in &phones {
+            println!("Phone: {} ({}:{})", phone.model, phone.transport.as_str(), phone.id);
+            if let Some(ref vendor) = phone.vendor {
+                println!("  Vendor: {}", vendor);
+            }
+            println!("  State: {}", phone.state);
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_backup(args: BackupArgs, logger: &Logger) -> Result<()> {
+    match args.command {
+        Some(BackupCommands::Verify { ref dir, ref format }) => return handle_backup_verify(dir, format, logger),
+        Some(BackupCommands::Restore { ref dir, ref to, ref format }) => return handle_backup_restore(dir, to, format, logger),
+        Some(BackupCommands::Prune { ref dir, keep_last, ref older_than, ref to, dry_run, ref format }) => {
+            return handle_backup_prune(dir, keep_last, older_than.clone(), to.clone(), dry_run, format, logger);
+        }
+        Some(BackupCommands::Stale { ref dir, remove, ref format }) => return handle_backup_stale(dir, remove, format, logger),
+        None => {}
+    }
+    if args.dry_run {
+        return handle_backup_dry_run(&args, logger);
+    }
+
+    // clap enforces both when no subcommand is given and this isn't a resume
+    let resume_dest = args.resume.as_ref().map(|dir| dir.display().to_string());
+    let (start, dest) = match (args.resume.as_deref(), args.source.as_deref(), args.device.as_deref(), args.dest.as_deref()) {
+        (Some(dir), _, _, _) => (BackupStart::Resume(dir), resume_dest.as_deref().unwrap_or_default()),
+        (None, Some(source), _, Some(dest)) => (BackupStart::Phone { source, dest }, dest),
+        (None, None, Some(device), Some(dest)) => (BackupStart::New { device, dest }, dest),
+        _ => return Err(anyhow::anyhow!("--device (or --source) and --dest are required")),
+    };
+
+    if let Some(ref device) = args.auto_mounted {
+        logger.log_json(&serde_json::json!({
+            "step": "destination_mounted",
+            "device": device,
+            "dest": dest,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+    }
+
+    let result = run_backup(&args, start, logger);
+
+    if let Some(ref device) = args.auto_mounted {
+        release_auto_mounted_destination(device, dest, logger);
+    }
+
+    result
+}
+
+#[cfg(feature = "operations")]
+fn handle_backup_dry_run(args: &BackupArgs, logger: &Logger) -> Result<()> {
+    use crate::backup::EncryptedBackup;
+
+    let preview = EncryptedBackup::new()
+        .with_excludes(args.exclude.clone())
+        .preview_backup(&args.paths, args.dest.as_deref())
+        .map_err(|e| {
+            let error_msg = format!("Backup preview failed: {}", e);
+            logger.log_error(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })?;
+    logger.log_json(&serde_json::json!({
+        "step": "backup_dry_run",
+        "source_paths": preview.source_paths,
+        "excludes": preview.excludes,
+        "total_files": preview.total_files,
+        "total_bytes": preview.total_bytes,
+        "excluded_files": preview.excluded_files,
+        "excluded_bytes": preview.excluded_bytes,
+        "unreadable": preview.unreadable.len(),
+        "incompatible": preview.incompatible.len(),
+        "destination_free_bytes": preview.destination_free_bytes,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    println!("{}", serde_json::to_string_pretty(&preview)?);
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+fn handle_backup_verify(dir: &std::path::Path, format: &str, logger: &Logger) -> Result<()> {
+    use crate::backup::EncryptedBackup;
+
+    logger.log_info(&format!("Verifying backup at {}", dir.display()));
+    let report = EncryptedBackup::new().verify_backup(dir).map_err(|e| {
+        let error_msg = format!("Backup verification failed: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Backup: {}", report.backup_dir);
+        println!("Manifest: {}", if report.manifest_valid { "intact" } else { "MODIFIED" });
+        println!("Files verified: {}/{}", report.files_passed, report.files_checked);
+        if report.presence_only > 0 {
+            println!("Checked for presence only (older backup): {}", report.presence_only);
+        }
+        for failure in &report.failures {
+            println!("  - {}: {}", failure.path, failure.reason);
+        }
+        println!("Verification status: {}", if report.passed { "PASSED" } else { "FAILED" });
+    }
+
+    if report.passed {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Backup verification failed: {} problem(s) found", report.failures.len()))
+    }
+}
+
+#[cfg(feature = "operations")]
+fn handle_backup_restore(dir: &std::path::Path, to: &std::path::Path, format: &str, logger: &Logger) -> Result<()> {
+    use crate::backup::EncryptedBackup;
+
+    let passphrase = crate::secrets::resolve(crate::secrets::SecretKind::BackupPassphrase)
+        .map_err(|e| {
+            let error_msg = format!("Failed to read backup passphrase: {}", e);
+            logger.log_error(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })?;
+    let passphrase = match passphrase {
+        Some(passphrase) => Some(passphrase),
+        None => crate::secrets::prompt("Backup passphrase").map_err(|e| anyhow::anyhow!("Failed to read backup passphrase: {}", e))?,
+    };
+
+    logger.log_info(&format!("Restoring backup at {} to {}", dir.display(), to.display()));
+    let report = EncryptedBackup::new().with_passphrase(passphrase).restore_backup(dir, to).map_err(|e| {
+        let error_msg = format!("Backup restore failed: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Backup: {}", report.backup_dir);
+        println!("Restored to: {}", report.target_dir);
+        println!("Files restored: {}", report.files_restored);
+        println!("Bytes: {} logical, {} written", report.logical_bytes, report.physical_bytes);
+        if report.sparse_files > 0 {
+            println!("Sparse files (holes kept): {}", report.sparse_files);
+        }
+        for failure in &report.failures {
+            println!("  - {}: {}", failure.path, failure.reason);
+        }
+        println!("Restore status: {}", if report.passed { "PASSED" } else { "FAILED" });
+    }
+
+    if report.passed {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Backup restore failed: {} problem(s) found", report.failures.len()))
+    }
+}
+
+#[cfg(feature = "operations")]
+fn handle_backup_prune(
+    dir: &std::path::Path,
+    keep_last: Option<usize>,
+    older_than: Option<String>,
+    to: Option<std::path::PathBuf>,
+    dry_run: bool,
+    format: &str,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::retention::prune_backups;
+
+    let retention = load_retention_policy(logger)?;
+    let keep_last = keep_last.or(retention.backups_keep_last);
+    let older_than = older_than.or(retention.backups_older_than);
+    if keep_last.is_none() && older_than.is_none() {
+        let error_msg = "No retention rule: pass --keep-last or --older-than, or set retention.backups_keep_last \
+                         or retention.backups_older_than in the site policy".to_string();
+        logger.log_error(&error_msg);
+        return Err(anyhow::anyhow!(error_msg));
+    }
+    let cutoff = match older_than {
+        Some(ref age) => Some(retention_cutoff(age, logger)?),
+        None => None,
+    };
+    let to = to
+        .or(retention.archive_dir.map(|archive| archive.join("backups")))
+        .unwrap_or_else(|| dir.join(".trash"));
+
+    let audit = crate::audit::AuditLog::open_default()?;
+    let pruned = prune_backups(dir, &to, keep_last.unwrap_or(0), cutoff, dry_run, &audit).map_err(|e| {
+        let error_msg = format!("Backup prune failed: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    let response = json!({
+        "op": "backup_prune",
+        "dir": dir.display().to_string(),
+        "to": to.display().to_string(),
+        "keep_last": keep_last,
+        "older_than": older_than,
+        "dry_run": dry_run,
+        "pruned": pruned,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+    logger.log_json(&response);
+    print_retained(&response, "pruned", "Backups", dry_run, format)
+}
+
+#[cfg(feature = "operations")]
+fn handle_backup_stale(dir: &std::path::Path, remove: bool, format: &str, logger: &Logger) -> Result<()> {
+    use crate::backup::{remove_stale_backup, stale_backups};
+
+    let stale = stale_backups(dir).map_err(|e| {
+        let error_msg = format!("Failed to read {}: {}", dir.display(), e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let mut removed = Vec::new();
+    if remove {
+        for backup in &stale {
+            match remove_stale_backup(backup) {
+                Ok(()) => removed.push(backup.path.clone()),
+                Err(e) => logger.log_json(&json!({
+                    "step": "stale_backup_remove_failed",
+                    "path": backup.path,
+                    "error": e.to_string(),
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                })),
+            }
+        }
+    }
+
+    let response = json!({
+        "op": "backup_stale",
+        "dir": dir.display().to_string(),
+        "stale": stale,
+        "removed": removed,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+    logger.log_json(&response);
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        return Ok(());
+    }
+    println!("Unfinished backups in {}: {}", dir.display(), stale.len());
+    for backup in &stale {
+        let state = if removed.contains(&backup.path) {
+            "removed"
+        } else if backup.resumable {
+            "resumable"
+        } else {
+            "not resumable"
+        };
+        println!("  {} ({} files stored, {})", backup.backup_id, backup.files_stored, state);
+    }
+    Ok(())
+}
+
+/// clap value parser for `--note`
+#[cfg(feature = "operations")]
+fn parse_note(value: &str) -> std::result::Result<String, String> {
+    crate::annotations::validate_note(value).map_err(|e| e.to_string())
+}
+
+/// Retention settings from the site policy
+#[cfg(feature = "operations")]
+fn load_retention_policy(logger: &Logger) -> Result<crate::policy::RetentionPolicy> {
+    crate::policy::SitePolicy::load_default()
+        .map(|policy| policy.retention)
+        .map_err(|e| {
+            let error_msg = format!("Failed to load site policy: {}", e);
+            logger.log_error(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })
+}
+
+/// The instant `age` (e.g. "2y") before now
+#[cfg(feature = "operations")]
+fn retention_cutoff(age: &str, logger: &Logger) -> Result<chrono::DateTime<chrono::Utc>> {
+    crate::retention::parse_age(age)
+        .map(|age| chrono::Utc::now() - age)
+        .ok_or_else(|| {
+            let error_msg = format!("Invalid age: {}. Use a number followed by d, w, m or y, e.g. 2y", age);
+            logger.log_error(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })
+}
+
+#[cfg(feature = "operations")]
+fn print_retained(response: &serde_json::Value, key: &str, label: &str, dry_run: bool, format: &str) -> Result<()> {
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(response)?);
+        return Ok(());
+    }
+    let items = response[key].as_array().cloned().unwrap_or_default();
+    let verb = if dry_run { "would move" } else { "moved" };
+    println!("{} {} to {}: {}", label, verb, response["to"].as_str().unwrap_or(""), items.len());
+    let display = crate::locale::DisplayFormat::current();
+    for item in &items {
+        println!("  {} (created {})", item["id"].as_str().unwrap_or(""), display.timestamp(item["created_at"].as_str().unwrap_or("")));
+    }
+    Ok(())
+}
+
+/// Unmount a destination the UI mounted for us, but only if it really backs `dest`
+#[cfg(feature = "operations")]
+fn release_auto_mounted_destination(device: &str, dest: &str, logger: &Logger) {
+    use securewipe::mount::{backing_device, unmount_destination};
+
+    let expanded = shellexpand::tilde(dest).to_string();
+    if backing_device(&expanded).as_deref() != Some(device) {
+        logger.log_json(&serde_json::json!({
+            "step": "destination_unmount_skipped",
+            "device": device,
+            "reason": format!("{} does not back the destination {}", device, dest),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+        return;
+    }
+
+    match unmount_destination(device) {
+        Ok(()) => logger.log_json(&serde_json::json!({
+            "step": "destination_unmounted",
+            "device": device,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        })),
+        Err(e) => {
+            logger.log_error(&format!("Failed to unmount {}: {}", device, e));
+            crate::events::warn(&format!("{} could not be unmounted; eject it manually before unplugging.", device));
+        }
+    }
+}
+
+#[cfg(feature = "operations")]
+fn run_backup(args: &BackupArgs, start: BackupStart, logger: &Logger) -> Result<()> {
+    use crate::backup::{EncryptedBackup, BackupOperations, BackupErrorPolicy, SpaceWait};
+    
+    logger.log_info("Starting backup operation");
+    
+    // Parse per-file error policy
+    let on_error = match args.on_error.as_str() {
+        "abort" => BackupErrorPolicy::Abort,
+        "skip" => BackupErrorPolicy::Skip,
+        _ => {
+            let error_msg = format!("Invalid --on-error value: {}. Must be skip or abort", args.on_error);
+            logger.log_error(&error_msg);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+    };
+    
+    let passphrase = crate::secrets::resolve(crate::secrets::SecretKind::BackupPassphrase).map_err(|e| {
+        let error_msg = format!("Failed to read backup passphrase: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    logger.log_json(&serde_json::json!({
+        "step": "backup_passphrase",
+        "available": passphrase.is_some(),
+        "source": passphrase.as_ref().map(|secret| &secret.source),
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    if passphrase.is_none() {
+        crate::events::warn(
+            "No backup passphrase is set, so this backup's key is not kept. \
+             Store one with `securewipe secrets set backup-passphrase`."
+        );
+    }
+
+    let backup_engine = EncryptedBackup::new()
+        .with_excludes(args.exclude.clone())
+        .with_note(args.note.clone())
+        .with_error_policy(on_error)
+        .with_passphrase(passphrase)
+        .with_space_wait(SpaceWait {
+            max_wait: Some(std::time::Duration::from_secs(args.space_wait)),
+            ..SpaceWait::default()
+        });
+    // Cancelling stops at the next file and leaves the backup resumable
+    crate::backup::install_abort_handler();
+    args.resources.apply(logger);
+    
+    let location = start.location();
+    let mut watched = vec![location.clone()];
+    if let BackupStart::New { device, .. } = start {
+        if device.starts_with("/dev/") {
+            watched.push(std::path::PathBuf::from(device));
+        }
+    }
+    let device_watch = crate::removal::DeviceWatch::start(&watched);
+    let outcome = match start {
+        BackupStart::New { device, dest } => backup_engine.perform_backup(device, &args.paths, dest),
+        BackupStart::Phone { source, dest } => backup_from_phone(backup_engine, source, &args.paths, dest, logger),
+        BackupStart::Resume(dir) => {
+            logger.log_info(&format!("Resuming backup in {}", dir.display()));
+            backup_engine.resume_backup(dir)
+        }
+    };
+    drop(device_watch);
+    match outcome {
+        Ok(result) => {
+            logger.log_info("Backup completed successfully");
+            
+            println!("Backup completed successfully!");
+            println!("Backup ID: {}", result.backup_id);
+            println!("Encryption: {}", result.encryption_method);
+            let display = crate::locale::DisplayFormat::current();
+            println!("Files processed: {}", display.integer(result.manifest.total_files as u64));
+            println!("Total bytes: {}", display.integer(result.manifest.total_bytes));
+            if !result.manifest.skipped_files.is_empty() {
+                println!("Skipped files: {}", result.manifest.skipped_files.len());
+                for skipped in &result.manifest.skipped_files {
+                    println!("  - {}: {}", skipped.path, skipped.reason);
+                }
+            }
+            println!("Verification samples: {}/{}", 
+                     if result.verification_passed { result.verification_samples } else { 0 },
+                     result.verification_samples);
+            println!("Verification status: {}", 
+                     if result.verification_passed { "PASSED" } else { "FAILED" });
+            
             if !result.verification_passed {
                 logger.log_error("Backup verification failed");
-                eprintln!("WARNING: Backup verification failed! Some files may be corrupted.");
+                crate::events::warn("Backup verification failed! Some files may be corrupted.");
                 return Err(anyhow::anyhow!("Backup verification failed"));
             }
-            
-            // Load the certificate already created by the backup process
-            use std::fs;
-            
-            logger.log_info("Loading backup certificate for signing");
-            let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Cannot determine home directory"))?;
-            let cert_dir = home_dir.join("SecureWipe").join("certificates");
-            let cert_file = cert_dir.join(format!("{}.json", result.backup_id));
-            
-            let cert_json = fs::read_to_string(&cert_file)
-                .map_err(|e| anyhow::anyhow!("Failed to read certificate file: {}", e))?;
-            let mut cert_value: serde_json::Value = serde_json::from_str(&cert_json)
-                .map_err(|e| anyhow::anyhow!("Failed to parse certificate JSON: {}", e))?;
-            
-            // Validate schema before proceeding
+            
+            // Load the certificate already created by the backup process
+            use std::fs;
+            
+            logger.log_info("Loading backup certificate for signing");
+            let cert_dir = crate::config::Config::load()?.certificates_dir;
+            let cert_file = cert_dir.join(format!("{}.json", result.backup_id));
+            
+            let cert_json = fs::read_to_string(&cert_file)
+                .map_err(|e| anyhow::anyhow!("Failed to read certificate file: {}", e))?;
+            let mut cert_value: serde_json::Value = serde_json::from_str(&cert_json)
+                .map_err(|e| anyhow::anyhow!("Failed to parse certificate JSON: {}", e))?;
+            
+            // Validate schema before proceeding
+            use crate::schema::CertificateValidator;
+            logger.log_info("Validating backup certificate schema");
+            let validator = CertificateValidator::default();
+            let validation_result = validator.validate_certificate(&cert_value)
+                .map_err(|e| anyhow::anyhow!("Schema validation error: {}", e))?;
+            
+            if !validation_result.valid {
+                logger.log_error("Backup certificate failed schema validation");
+                crate::events::warn(&format!("Generated certificate failed schema validation: {}", validation_result.errors.join("; ")));
+                // Continue anyway for backup operation, but log the issue
+            } else {
+                logger.log_info("Backup certificate passed schema validation");
+            }
+            
+            // Certificate file already exists from backup process
+            // Get the cert_id for logging
+            let cert_id = cert_value.get("cert_id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            
+            // Sign certificate if requested or if not already signed
+            let should_sign = args.sign || cert_value.get("signature").map_or(true, |sig| sig.is_null());
+            if should_sign {
+                use crate::signer::load_private_key;
+                
+                logger.log_info("Signing backup certificate");
+                logger.log_json(&serde_json::json!({
+                    "step": "certificate_signing",
+                    "cert_id": cert_id,
+                    "key_source": if args.sign_key_path.is_some() { "flag" } else { "env" },
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                }));
+                
+                let signing_key = load_private_key(args.sign_key_path.clone())
+                    .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
+                
+                crate::audit::sign_and_record(&mut cert_value, &signing_key, args.force)
+                    .map_err(|e| anyhow::anyhow!("Failed to sign certificate: {}", e))?;
+                
+                logger.log_json(&serde_json::json!({
+                    "step": "certificate_signed",
+                    "cert_id": cert_id,
+                    "signed": true,
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                }));
+            } else {
+                logger.log_info("Certificate already signed, skipping signing step");
+            }
+            
+            // Write certificate file atomically
+            let cert_json = serde_json::to_string_pretty(&cert_value)?;
+            crate::cleanup::write_atomic(&cert_file, cert_json)?;
+            
+            logger.log_json(&serde_json::json!({
+                "step": "certificate_updated",
+                "cert_id": cert_id,
+                "cert_path": cert_file.display().to_string(),
+                "signed": true,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            
+            println!("Backup certificate signed and updated: {}", cert_file.display());
+            
+            Ok(())
+        }
+        Err(e) => {
+            logger.log_error(&format!("Backup failed: {}", e));
+            Err(io_failure("Backup failed", e.as_ref(), &location))
+        }
+    }
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_wipe(args: WipeArgs, logger: &Logger) -> Result<()> {
+    use crate::wipe::WipePolicy;
+    use crate::device::{DeviceDiscovery, LinuxDeviceDiscovery};
+    use crate::wipe_plan::RiskSource;
+    
+    if args.cleanup_only {
+        use crate::wipe::NistAlignedWipe;
+        
+        logger.log_info(&format!("Running post-cancel cleanup for {}", args.device));
+        let commands = NistAlignedWipe.cleanup_after_cancel(&args.device)
+            .map_err(|e| anyhow::anyhow!("Cleanup failed for {}: {}", args.device, e))?;
+        
+        let response = json!({
+            "cmd": "wipe",
+            "action": "cleanup",
+            "device": args.device,
+            "commands": commands,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        logger.log_json(&response);
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        return Ok(());
+    }
+    
+    logger.log_info("Starting wipe planning");
+    
+    // Log CLI arguments
+    logger.log_json(&json!({
+        "step": "cli_args",
+        "device": args.device,
+        "policy": args.policy,
+        "iso_mode": args.iso_mode,
+        "samples": args.samples,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    
+    // Parse policy
+    let policy = match args.policy.as_str() {
+        "CLEAR" => Some(WipePolicy::Clear),
+        "PURGE" => Some(WipePolicy::Purge),
+        _ => {
+            let error_msg = format!("Invalid policy: {}. Must be CLEAR or PURGE", args.policy);
+            logger.log_error(&error_msg);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+    };
+    
+    if args.on_duplicate != "warn" && args.on_duplicate != "skip" {
+        let error_msg = format!("Invalid --on-duplicate value: {}. Must be warn or skip", args.on_duplicate);
+        logger.log_error(&error_msg);
+        return Err(anyhow::anyhow!(error_msg));
+    }
+
+    let verify_mode = match crate::wipe::VerifyMode::parse(&args.verify_mode) {
+        Some(mode) => mode,
+        None => {
+            let error_msg = format!("Invalid --verify-mode value: {}. Must be heuristic, pattern or pattern-full", args.verify_mode);
+            logger.log_error(&error_msg);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+    };
+
+    // Site policy decides how many unwritable bytes a wipe may leave and still pass
+    let site_policy = match crate::policy::SitePolicy::load_default() {
+        Ok(site_policy) => site_policy,
+        Err(e) => {
+            let error_msg = format!("Failed to load site policy: {}", e);
+            logger.log_error(&error_msg);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+    };
+    crate::risk::install(site_policy.risk.clone());
+
+    let overwrite_profile = match args.overwrite_profile.as_deref() {
+        None => None,
+        Some(name) => match crate::wipe::OverwriteProfile::parse(name) {
+            Some(profile) => Some(profile),
+            None => {
+                let error_msg = format!(
+                    "Invalid --overwrite-profile value: {}. Must be nist-clear, dod-5220.22-m-3pass or gutmann-lite",
+                    name
+                );
+                logger.log_error(&error_msg);
+                return Err(anyhow::anyhow!(error_msg));
+            }
+        },
+    };
+    
+    // Log controller probing attempts
+    logger.log_json(&json!({
+        "step": "controller_probe_start",
+        "device": args.device,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+
+    // Discover the drive's risk and assemble the plan the same way the GUI does
+    let discovery = LinuxDeviceDiscovery::new();
+    let discovered = discovery.discover_devices();
+    let request = crate::wipe_plan::PlanRequest {
+        device: args.device.clone(),
+        policy: policy.clone().unwrap_or(WipePolicy::Purge),
+        iso_mode: args.iso_mode,
+        samples: Some(args.samples),
+        verify_mode,
+        overwrite_profile,
+        declared_virtual: args.virtual_media,
+        allow_network_device: args.allow_network_device,
+        backup_cert_id: args.backup_cert_id.clone(),
+    };
+    let assembled = crate::wipe_plan::assemble(&request, discovered.as_deref().map_err(|e| e.to_string()), &site_policy);
+    match &assembled.risk_source {
+        RiskSource::Discovered(d) => {
+            logger.log_json(&json!({
+                "step": "device_risk_check",
+                "device": args.device,
+                "risk_level": d.risk_level,
+                "risk_explanation": d.risk_explanation,
+                "device_id": d.device_id,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            with_inventory(logger, |inventory| inventory.record_seen(d).map(|_| ()));
+        }
+        RiskSource::ImageFile | RiskSource::NotFound => {
+            logger.log_json(&json!({
+                "step": "device_risk_check",
+                "device": args.device,
+                "result": if matches!(assembled.risk_source, RiskSource::ImageFile) { "image_file" } else { "device_not_found" },
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+        }
+        RiskSource::DiscoveryFailed(e) => {
+            logger.log_json(&json!({
+                "step": "device_risk_check",
+                "device": args.device,
+                "error": e,
+                "result": "discovery_failed",
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+        }
+    }
+    if let Some(ref check) = assembled.backup_check {
+        logger.log_json(&json!({
+            "step": "backup_requirement_check",
+            "device": args.device,
+            "backup_cert_id": args.backup_cert_id,
+            "max_age_days": site_policy.backup.max_age_days,
+            "passed": check.is_ok(),
+            "reason": check.as_ref().err(),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+    }
+    logger.log_json(&json!({
+        "step": "media_check",
+        "device": args.device,
+        "media_type": if assembled.virtual_media.is_some() { "virtual" } else { "physical" },
+        "virtual_media": assembled.virtual_media,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    let device_serial_opt = assembled.serial().map(str::to_string);
+    let device_bus = assembled.device().and_then(|d| d.bus.clone());
+    let device_risk = assembled.risk.clone();
+    let is_critical = matches!(device_risk, crate::device::RiskLevel::Critical);
+    let crate::wipe_plan::AssembledPlan { plan, overwrite_profile, virtual_media, .. } = assembled;
+    
+    // Log planning decision
+    logger.log_json(&json!({
+        "step": "wipe_plan_generated",
+        "device": plan.device,
+        "risk": plan.risk,
+        "policy": plan.policy,
+        "main_method": plan.main_method,
+        "method_decision": plan.method_decision,
+        "overwrite_profile": plan.overwrite_profile,
+        "hpa_dco_clear": plan.hpa_dco_clear,
+        "blocked": plan.blocked,
+        "reason": plan.reason,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    
+    // Output based on format
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        // Human-readable format
+        println!("Wipe Plan for {}", plan.device);
+        println!("=======================");
+        println!("• Policy: {:?}", plan.policy);
+        println!("• Risk Level: {}", plan.risk);
+        println!("• Main Method: {}", plan.main_method);
+        for candidate in &plan.method_decision.candidates {
+            match (&candidate.rejection_reason, candidate.selected) {
+                (_, true) => println!("    ✓ {}", candidate.method),
+                (Some(reason), false) => println!("    ✗ {}: {}", candidate.method, reason),
+                (None, false) => println!("    ✗ {}", candidate.method),
+            }
+            for check in &candidate.checks {
+                let found = if check.supported { "supported" } else { "not supported" };
+                match check.detail {
+                    Some(ref detail) => println!("        {} {}: {} ({})", check.source, check.capability, found, detail),
+                    None => println!("        {} {}: {}", check.source, check.capability, found),
+                }
+            }
+        }
+        println!("• HPA/DCO Clear: {}", if plan.hpa_dco_clear { "Yes" } else { "No" });
+        if plan.main_method == "overwrite" {
+            let passes = overwrite_profile.passes(&plan.policy, None);
+            println!("• Overwrite Profile: {} ({} pass(es))", overwrite_profile.as_str(), passes.len());
+        }
+        println!("• Verification: {} {} samples", plan.verification.strategy, plan.verification.samples);
+        
+        if plan.blocked {
+            println!("• Status: ❌ BLOCKED");
+            if let Some(ref reason) = plan.reason {
+                println!("• Reason: {}", reason);
+            }
+        } else {
+            println!("• Status: ✅ Ready to proceed");
+            if args.iso_mode && plan.risk == "CRITICAL" {
+                println!("• Note: ISO mode enabled - CRITICAL disk wipe allowed");
+            }
+        }
+    }
+
+    // A scripted run passes back the plan it saw; anything different means the drive changed
+    let approved_plan = match args.approve_plan {
+        Some(ref path) => Some(check_approved_plan_file(path, &plan, logger)?),
+        None => None,
+    };
+
+    // Warn about (or skip) drives that already have a recent PASS wipe at this policy or stronger
+    let duplicate = match device_serial_opt {
+        Some(ref serial) if args.duplicate_window_days > 0 => find_duplicate_wipe(serial, &args.policy, args.duplicate_window_days, logger),
+        _ => None,
+    };
+    if let Some(ref earlier) = duplicate {
+        logger.log_json(&json!({
+            "step": "duplicate_wipe_detected",
+            "device": args.device,
+            "serial": device_serial_opt,
+            "earlier_cert_id": earlier.cert_id,
+            "earlier_policy": earlier.policy,
+            "earlier_issued_at": earlier.issued_at,
+            "window_days": args.duplicate_window_days,
+            "on_duplicate": args.on_duplicate,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+        crate::events::warn(&format!(
+            "{} already passed a {} wipe on {} (certificate {})",
+            device_serial_opt.as_deref().unwrap_or(&args.device),
+            earlier.policy.as_deref().unwrap_or("?"),
+            earlier.issued_at,
+            earlier.cert_id
+        ));
+    }
+
+    // Check for destructive wipe mode
+    if args.danger_allow_wipe {
+        if duplicate.is_some() && args.on_duplicate == "skip" {
+            logger.log_info("Skipping wipe of already-sanitized drive (--on-duplicate skip)");
+            println!("Wipe skipped: drive already has a recent PASS certificate.");
+            return Ok(());
+        }
+
+        logger.log_info("Destructive wipe mode requested");
+        
+        // Critical safety check: require SECUREWIPE_DANGER=1 environment variable
+        if !crate::config::Config::load()?.danger {
+            let error_msg = "SECUREWIPE_DANGER=1 environment variable required for destructive operations";
+            logger.log_error(error_msg);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+
+        // Check if device exists
+        if !std::path::Path::new(&args.device).exists() {
+            let error_msg = format!("Device {} does not exist", args.device);
+            logger.log_error(&error_msg);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+
+        // Check if plan is blocked
+        if plan.blocked {
+            let error_msg = format!("Wipe operation blocked: {}", plan.reason.unwrap_or_default());
+            logger.log_error(&error_msg);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+
+        // A wipe cut short by a dead battery leaves the drive neither intact nor sanitized
+        let power = crate::power::PowerStatus::read();
+        let low_battery = power.low_battery(site_policy.power.min_battery_percent);
+        logger.log_json(&json!({
+            "step": "power_check",
+            "ac_online": power.ac_online,
+            "battery_percent": power.battery_percent,
+            "on_battery": power.on_battery(),
+            "low_battery": low_battery.is_some(),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+        if let Some(reason) = low_battery {
+            if site_policy.power.on_low_battery == crate::policy::LowBatteryAction::Block {
+                let error_msg = format!("Wipe operation blocked: {}", reason);
+                logger.log_error(&error_msg);
+                return Err(anyhow::anyhow!(error_msg));
+            }
+            crate::events::warn(&reason);
+        }
+
+        // HIGH/CRITICAL devices need a second person's signed approval of this exact plan
+        let approval = if crate::approval::requires_approval(&device_risk) {
+            let approval = verify_wipe_approval(&args, &plan, device_serial_opt.clone(), device_risk.clone(), logger)?;
+            Some(approval)
+        } else {
+            None
+        };
+
+        // On a kiosk, make sure the drive is the one the operator means before asking them to confirm
+        if site_policy.kiosk.interlock != crate::policy::KioskInterlock::Off {
+            let outcome = crate::kiosk::check(&site_policy.kiosk, discovered.as_ref().ok().map(Vec::as_slice), &args.device);
+            logger.log_json(&json!({
+                "step": "kiosk_interlock",
+                "device": args.device,
+                "interlock": site_policy.kiosk.interlock,
+                "result": if outcome.is_ok() { "passed" } else { "refused" },
+                "error": outcome.as_ref().err().map(|e| e.to_string()),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            if let Err(e) = outcome {
+                logger.log_error(&e.to_string());
+                return Err(anyhow::anyhow!(e.to_string()));
+            }
+        }
+
+        // Final confirmation prompt (supports non-interactive token via env)
+        println!("This will PERMANENTLY DESTROY ALL DATA on {}.", args.device);
+
+        if let Some(ref plan_sha256) = approved_plan {
+            logger.log_info(&format!("Confirmed by approved plan {}", plan_sha256));
+        } else {
+            // Preferred confirmation string per safety policy: "WIPE <DEVICE_SERIAL>"
+            let expected_token_serial = device_serial_opt
+                .as_ref()
+                .map(|s| format!("WIPE {}", s));
+            let legacy_token = "CONFIRM WIPE".to_string();
+
+            // Check for non-interactive confirmation via environment variable
+            if let Ok(token) = std::env::var("SECUREWIPE_CONFIRM_TOKEN") {
+                let token_ok = match expected_token_serial {
+                    Some(ref expect) => token == *expect || token == legacy_token,
+                    None => token == legacy_token, // No serial available; accept legacy only
+                };
+                if !token_ok {
+                    let expected_msg = expected_token_serial
+                        .as_ref()
+                        .map(|s| s.as_str())
+                        .unwrap_or(legacy_token.as_str());
+                    let error_msg = format!(
+                        "Invalid confirmation token. Expected '{}'{}.",
+                        expected_msg,
+                        if expected_token_serial.is_some() { " or 'CONFIRM WIPE' (legacy)" } else { "" }
+                    );
+                    logger.log_error(&error_msg);
+                    return Err(anyhow::anyhow!(error_msg));
+                }
+            } else {
+                // Interactive path
+                if let Some(ref serial) = device_serial_opt {
+                    print!("Type 'WIPE {}' to proceed: ", serial);
+                } else {
+                    print!("Type 'CONFIRM WIPE' to proceed: ");
+                }
+                std::io::Write::flush(&mut std::io::stdout())?;
+
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                let input = input.trim();
+
+                let ok = match expected_token_serial {
+                    Some(ref expect) => input == expect || input == legacy_token,
+                    None => input == legacy_token,
+                };
+                if !ok {
+                    logger.log_info("Wipe operation cancelled by user (confirmation token mismatch)");
+                    println!("Wipe operation cancelled.");
+                    return Ok(());
+                }
+            }
+        }
+
+        logger.log_info("User confirmed destructive wipe - starting operation");
+        
+        // Perform the actual wipe
+        use crate::wipe::{NistAlignedWipe, WipeOptions};
+        let wipe_engine = NistAlignedWipe;
+        let options = WipeOptions {
+            verify_seed: args.verify_seed,
+            verify_mode,
+            bad_regions: site_policy.bad_regions.clone(),
+            profile: overwrite_profile,
+            virtual_media: virtual_media.clone(),
+            hardened: args.hardened || cfg!(feature = "iso"),
+        };
+        args.resources.apply(logger);
+        if options.hardened {
+            use crate::hardening::{limit_capabilities, WIPE_CAPABILITIES};
+            let kept = limit_capabilities(WIPE_CAPABILITIES).map_err(|e| {
+                let error_msg = format!("Hardened mode: {}", e);
+                logger.log_error(&error_msg);
+                anyhow::anyhow!(error_msg)
+            })?;
+            logger.log_json(&json!({
+                "step": "privileges_limited",
+                "capabilities": format!("{:#x}", kept),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+        }
+        let wipe_started = std::time::Instant::now();
+        // Stop at once, rather than retrying every sector, if the drive is unplugged
+        let device_watch = crate::removal::DeviceWatch::start(&[std::path::PathBuf::from(&args.device)]);
+        let power_watch = crate::power::PowerWatch::start(&site_policy.power);
+        let wipe_result = wipe_engine.perform_wipe_with_options(&args.device, policy.unwrap(), is_critical, &options)
+            .map_err(|e| io_failure("Wipe operation failed", e.as_ref(), std::path::Path::new(&args.device)))?;
+        drop(device_watch);
+        drop(power_watch);
+
+        logger.log_json(&json!({
+            "step": "wipe_completed",
+            "device": wipe_result.device,
+            "device_id": plan.device_state.as_ref().and_then(|state| state.device_id.as_deref()),
+            "method": wipe_result.method,
+            "verification_passed": wipe_result.verification_passed,
+            "verify_seed": wipe_result.verification.as_ref().map(|record| record.seed),
+            "pattern_verification": wipe_result.pattern_verification,
+            "bad_regions": wipe_result.bad_regions,
+            "overwrite": wipe_result.overwrite,
+            "hpa_dco": wipe_result.hpa_dco,
+            "partition_metadata": wipe_result.partition_metadata,
+            "timing": wipe_result.timing,
+            "smart_delta": wipe_result.smart_delta,
+            "max_bad_bytes": site_policy.bad_regions.max_bad_bytes,
+            "commands_executed": wipe_result.commands.len(),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+
+        if let Some(cost) = crate::cert_builder::wipe_cost(&wipe_result, crate::cert_builder::certificate_device(&wipe_result.device)) {
+            logger.log_json(&json!({
+                "step": "cost_summary",
+                "operation": "wipe",
+                "device": wipe_result.device,
+                "cost": cost,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+        }
+
+        if let Some(ref serial) = device_serial_opt {
+            with_inventory(logger, |inventory| inventory.record_wipe_outcome(serial, wipe_result.verification_passed));
+        }
+
+        record_telemetry(logger, &crate::telemetry::TelemetryRecord {
+            operation: "wipe".to_string(),
+            method: wipe_result.method.clone(),
+            policy: match wipe_result.policy {
+                crate::wipe::WipePolicy::Clear => "CLEAR".to_string(),
+                crate::wipe::WipePolicy::Purge => "PURGE".to_string(),
+            },
+            profile: wipe_result.overwrite.as_ref().map(|record| record.profile.as_str().to_string()),
+            bus: device_bus.unwrap_or_else(|| "unknown".to_string()),
+            duration_secs: wipe_started.elapsed().as_secs(),
+            success: wipe_result.verification_passed,
+            fallback: wipe_result.fallback_reason.is_some(),
+            date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        });
+
+        println!("Wipe operation completed!");
+        println!("Method used: {}", wipe_result.method);
+        println!("Commands executed: {}", wipe_result.commands.len());
+        println!("Verification samples: {}", wipe_result.verification_samples);
+        println!("Verification result: {}", if wipe_result.verification_passed { "PASSED" } else { "FAILED" });
+        if let Some(record) = &wipe_result.verification {
+            let totals = record.histogram().totals;
+            println!(
+                "Sample classes: {} zero-fill, {} constant-fill, {} random-fill, {} structured",
+                totals.zero_fill, totals.constant_fill, totals.random_fill, totals.structured
+            );
+            println!("Verification seed: {}", record.seed);
+        }
+        if let Some(pattern) = &wipe_result.pattern_verification {
+            println!(
+                "Pattern readback ({}): {} sectors checked, {} mismatched",
+                if pattern.full { "full" } else { "sampled" },
+                pattern.sectors_checked,
+                pattern.sectors_mismatched
+            );
+        }
+        
+        if let Some(reason) = &wipe_result.fallback_reason {
+            println!("Fallback reason: {}", reason);
+        }
+
+        if let Some(record) = &wipe_result.overwrite {
+            println!("Overwrite profile: {}", record.profile.as_str());
+            for pass in &record.passes {
+                println!(
+                    "  - pass {}: {} ({} bytes written)",
+                    pass.pass,
+                    pass.fill.describe(),
+                    pass.bytes_written
+                );
+            }
+        }
+
+        if let Some(check) = &wipe_result.hpa_dco {
+            let readout = |state: Option<crate::wipe::MaxSectors>| match state {
+                Some(state) => format!("{}/{}", state.current, state.native),
+                None => "unreadable".to_string(),
+            };
+            let outcome = match check.passed {
+                Some(true) => "confirmed",
+                Some(false) => "NOT cleared",
+                None => "not verifiable",
+            };
+            println!("HPA/DCO: {} (max sectors {} -> {})", outcome, readout(check.before), readout(check.after));
+        }
+
+        for region in &wipe_result.partition_metadata {
+            println!(
+                "Partition metadata {}: {} bytes at offset {} {}",
+                region.name,
+                region.length,
+                region.offset,
+                if region.verified { "zeroed" } else { "NOT verified zero" }
+            );
+        }
+
+        if !wipe_result.bad_regions.is_empty() {
+            println!(
+                "Bad regions skipped (site policy allows {} bytes):",
+                site_policy.bad_regions.max_bad_bytes
+            );
+            for region in &wipe_result.bad_regions {
+                println!("  - {} bytes at offset {}: {}", region.length, region.offset, region.error);
+            }
+        }
+
+        if !wipe_result.residual_signatures.is_empty() {
+            println!("Signatures still present after wipe:");
+            for finding in &wipe_result.residual_signatures {
+                println!("  - {} at offset {}", finding.kind, finding.offset);
+            }
+        }
+
+        if !wipe_result.verification_passed {
+            let error_msg = "Wipe verification failed! Some sectors may not be properly wiped.";
+            logger.log_error(error_msg);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+
+        // Generate, sign, and validate real wipe certificate
+        if args.sign || args.sign_key_path.is_some() {
+            logger.log_info("Generating real wipe certificate (schema-compliant)");
+
+            // Build schema-compliant JSON certificate
+            let mut cert_value = crate::cert_builder::build_wipe_certificate_json(&wipe_result, args.backup_cert_id.as_deref())
+                .map_err(|e| anyhow::anyhow!("Failed to build wipe certificate JSON: {}", e))?;
+            crate::annotations::set_operator_note(&mut cert_value, args.note.as_deref());
+            if let Some(ref approval) = approval {
+                cert_value["approval"] = serde_json::to_value(approval)?;
+            }
+
+            let serial = crate::serial::assign_serial(&mut cert_value)
+                .map_err(|e| anyhow::anyhow!("Failed to assign certificate serial number: {}", e))?;
+            logger.log_info(&format!("Certificate number: {}", serial));
+
+            // Sign certificate first (schema requires signature)
+            use crate::signer::load_private_key;
+            logger.log_info("Signing wipe certificate");
+            let signing_key = load_private_key(args.sign_key_path.clone())
+                .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
+            crate::audit::sign_and_record(&mut cert_value, &signing_key, args.force)
+                .map_err(|e| anyhow::anyhow!("Failed to sign wipe certificate: {}", e))?;
+
+            // Validate schema after signing
             use crate::schema::CertificateValidator;
-            logger.log_info("Validating backup certificate schema");
+            logger.log_info("Validating signed wipe certificate schema");
             let validator = CertificateValidator::default();
             let validation_result = validator.validate_certificate(&cert_value)
                 .map_err(|e| anyhow::anyhow!("Schema validation error: {}", e))?;
-            
             if !validation_result.valid {
-                logger.log_error("Backup certificate failed schema validation");
-                eprintln!("WARNING: Generated certificate failed schema validation:");
-                for error in &validation_result.errors {
-                    eprintln!("  - {}", error);
-                }
-                // Continue anyway for backup operation, but log the issue
-            } else {
-                logger.log_info("Backup certificate passed schema validation");
-            }
-            
-            // Certificate file already exists from backup process
-            // Get the cert_id for logging
-            let cert_id = cert_value.get("cert_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string();
-            
-            // Sign certificate if requested or if not already signed
-            let should_sign = args.sign || cert_value.get("signature").map_or(true, |sig| sig.is_null());
-            if should_sign {
-                use crate::signer::{load_private_key, sign_certificate};
-                
-                logger.log_info("Signing backup certificate");
-                logger.log_json(&serde_json::json!({
-                    "step": "certificate_signing",
-                    "cert_id": cert_id,
-                    "key_source": if args.sign_key_path.is_some() { "flag" } else { "env" },
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                }));
-                
-                let signing_key = load_private_key(args.sign_key_path.clone())
-                    .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
-                
-                sign_certificate(&mut cert_value, &signing_key, args.force)
-                    .map_err(|e| anyhow::anyhow!("Failed to sign certificate: {}", e))?;
-                
-                logger.log_json(&serde_json::json!({
-                    "step": "certificate_signed",
-                    "cert_id": cert_id,
-                    "signed": true,
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                }));
+                logger.log_error("Signed wipe certificate failed schema validation");
+                crate::events::warn(&format!("Signed certificate failed schema validation: {}", validation_result.errors.join("; ")));
             } else {
-                logger.log_info("Certificate already signed, skipping signing step");
+                logger.log_info("Signed wipe certificate passed schema validation");
             }
+
+            // Save certificate
+            let cert_dir = crate::config::Config::load()?.certificates_dir;
+            std::fs::create_dir_all(&cert_dir)?;
+            let cert_id = cert_value.get("cert_id").and_then(|v| v.as_str()).unwrap_or("wipe_cert").to_string();
+            let cert_file = cert_dir.join(format!("{}.json", cert_id));
             
-            // Write certificate file atomically
+            // Write certificate file
             let cert_json = serde_json::to_string_pretty(&cert_value)?;
-            let temp_file = cert_file.with_extension("tmp");
-            fs::write(&temp_file, &cert_json)?;
-            fs::rename(&temp_file, &cert_file)?;
+            crate::cleanup::write_atomic(&cert_file, cert_json)?;
             
-            logger.log_json(&serde_json::json!({
-                "step": "certificate_updated",
+            logger.log_json(&json!({
+                "step": "real_wipe_certificate_saved",
                 "cert_id": cert_id,
                 "cert_path": cert_file.display().to_string(),
                 "signed": true,
+                "backup_cert_linked": args.backup_cert_id.is_some(),
                 "timestamp": chrono::Utc::now().to_rfc3339()
             }));
+
+            with_inventory(logger, |inventory| inventory.record_certificate(&cert_value).map(|_| ()));
             
-            println!("Backup certificate signed and updated: {}", cert_file.display());
-            
-            Ok(())
+            println!("Wipe certificate saved: {}", cert_file.display());
+        }
+
+        logger.log_info("Destructive wipe operation completed successfully");
+        return Ok(());
+    }
+    
+    // Planning-only path: generate a schema-compliant unsigned certificate from a stub result and sign it
+    if args.sign || args.sign_key_path.is_some() {
+        use crate::wipe::{WipeResult, WipeCommand};
+        
+        // Generate a stub wipe result for certificate creation
+        let stub_wipe_result = WipeResult {
+            device: args.device.clone(),
+            policy: match args.policy.as_str() {
+                "CLEAR" => crate::wipe::WipePolicy::Clear,
+                "PURGE" => crate::wipe::WipePolicy::Purge,
+                _ => crate::wipe::WipePolicy::Purge,
+            },
+            method: plan.main_method.clone(),
+            commands: vec![WipeCommand {
+                command: format!("echo 'Wipe planned for {}'", args.device),
+                exit_code: 0,
+                elapsed_ms: 0,
+                output: "Planning completed successfully".to_string(),
+            }],
+            verification_samples: args.samples,
+            verification_passed: true,
+            fallback_reason: plan.reason.clone(),
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: Some(plan.method_decision.clone()),
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: virtual_media.clone(),
+            smart_delta: None,
+        };
+        
+        logger.log_info("Generating schema-compliant wipe certificate (planning mode)");
+        let mut cert_value = crate::cert_builder::build_wipe_certificate_json(&stub_wipe_result, args.backup_cert_id.as_deref())
+            .map_err(|e| anyhow::anyhow!("Failed to build wipe certificate JSON: {}", e))?;
+        crate::annotations::set_operator_note(&mut cert_value, args.note.as_deref());
+        
+        // Save certificate directory
+        let cert_dir = crate::config::Config::load()?.certificates_dir;
+        std::fs::create_dir_all(&cert_dir)?;
+        let cert_id = cert_value.get("cert_id").and_then(|v| v.as_str()).unwrap_or("wipe_cert").to_string();
+        let cert_file = cert_dir.join(format!("{}.json", cert_id));
+        
+        let serial = crate::serial::assign_serial(&mut cert_value)
+            .map_err(|e| anyhow::anyhow!("Failed to assign certificate serial number: {}", e))?;
+        logger.log_info(&format!("Certificate number: {}", serial));
+        
+        // Handle signing
+        use crate::signer::load_private_key;
+        
+        logger.log_info("Signing wipe certificate (planning mode)");
+        logger.log_json(&serde_json::json!({
+            "step": "wipe_certificate_signing",
+            "cert_id": cert_id,
+            "key_source": if args.sign_key_path.is_some() { "flag" } else { "env" },
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+        
+        let signing_key = load_private_key(args.sign_key_path.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
+        
+        crate::audit::sign_and_record(&mut cert_value, &signing_key, args.force)
+            .map_err(|e| anyhow::anyhow!("Failed to sign wipe certificate: {}", e))?;
+        
+        logger.log_json(&serde_json::json!({
+            "step": "wipe_certificate_signed",
+            "cert_id": cert_id,
+            "signed": true,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+        
+        // Write certificate file atomically
+        let cert_json = serde_json::to_string_pretty(&cert_value)?;
+        crate::cleanup::write_atomic(&cert_file, cert_json)?;
+        
+        logger.log_json(&serde_json::json!({
+            "step": "wipe_certificate_saved",
+            "cert_id": cert_id,
+            "cert_path": cert_file.display().to_string(),
+            "signed": true,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+        
+        println!("Wipe certificate saved: {}", cert_file.display());
+    }
+    
+    logger.log_info("Wipe planning completed");
+    Ok(())
+}
+
+/// Signing key for stamping exported PDFs. Without a key PDFs are still exported,
+/// unstamped, unless a key was asked for explicitly.
+fn load_pdf_signing_key(
+    sign_key_path: Option<std::path::PathBuf>,
+    logger: &Logger,
+) -> Result<Option<ed25519_dalek::SigningKey>> {
+    let explicit = sign_key_path.is_some();
+    match crate::signer::load_private_key(sign_key_path) {
+        Ok(signing_key) => Ok(Some(signing_key)),
+        Err(e) if explicit => {
+            let error_msg = format!("Failed to load PDF signing key: {}", e);
+            logger.log_error(&error_msg);
+            Err(anyhow::anyhow!(error_msg))
         }
         Err(e) => {
-            logger.log_error(&format!("Backup failed: {}", e));
-            Err(anyhow::anyhow!("Backup failed: {}", e))
+            logger.log_info(&format!("PDF not stamped, no signing key available: {}", e));
+            Ok(None)
+        }
+    }
+}
+
+/// Print a stored certificate as plain text or Markdown
+fn handle_cert_export_text(cert_id: &str, text_format: &str, logger: &Logger) -> Result<()> {
+    use securewipe::cert_text::{render, TextStyle};
+
+    let style = TextStyle::parse(text_format).ok_or_else(|| {
+        let error_msg = format!("Invalid --text-format: {}. Must be plain or markdown", text_format);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let cert_path = crate::cert_id::find_certificate(&cert_dir, cert_id)?;
+    let content = std::fs::read_to_string(&cert_path).map_err(|e| {
+        let error_msg = format!("Cannot read certificate {}: {}", cert_path.display(), e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let cert: serde_json::Value = serde_json::from_str(&content)?;
+
+    print!("{}", render(&cert, style, &securewipe::locale::DisplayFormat::current()));
+    logger.log_json(&json!({
+        "step": "cert_text_exported",
+        "cert_id": cert_id,
+        "text_format": style.as_str(),
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    Ok(())
+}
+
+pub fn handle_cert(args: CertArgs, logger: &Logger) -> Result<()> {
+    use securewipe::cert_pdf::PdfDetail;
+    use std::fs;
+    
+    logger.log_info("Processing certificate command");
+    
+    if let Some(cert_id) = args.show {
+        // Show certificate details
+        logger.log_info(&format!("Showing certificate: {}", cert_id));
+        
+        let response = json!({
+            "cmd": "cert",
+            "action": "show",
+            "cert_id": cert_id,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "status": "stub - not implemented"
+        });
+        
+        logger.log_json(&response);
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        return Ok(());
+    }
+    
+    if let Some(cert_id) = args.export_pdf {
+        logger.log_info(&format!("Exporting certificate to PDF: {}", cert_id));
+
+        let detail = PdfDetail::parse(&args.pdf_detail).ok_or_else(|| {
+            let error_msg = format!("Invalid --pdf-detail: {}. Must be full or summary", args.pdf_detail);
+            logger.log_error(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })?;
+        
+        // Try to find the certificate JSON file
+        let cert_dir = crate::config::Config::load()?.certificates_dir;
+        let cert_json_path = crate::cert_id::find_certificate(&cert_dir, &cert_id)?;
+        
+        if !cert_json_path.exists() {
+            let response = json!({
+                "cmd": "cert",
+                "action": "export_pdf",
+                "cert_id": cert_id,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "status": "error",
+                "error": format!("Certificate file not found: {}", cert_json_path.display())
+            });
+            
+            logger.log_json(&response);
+            println!("{}", serde_json::to_string_pretty(&response)?);
+            return Err(anyhow::anyhow!("Certificate file not found: {}", cert_json_path.display()));
+        }
+        
+        // Read and parse the certificate JSON
+        let cert_json = fs::read_to_string(&cert_json_path)?;
+        let cert_value: serde_json::Value = serde_json::from_str(&cert_json)?;
+        
+        let cert_type = cert_value.get("cert_type")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid certificate: missing cert_type"))?;
+
+        let signing_key = load_pdf_signing_key(args.sign_key_path.clone(), logger)?;
+        let pdf_path = securewipe::cert_pdf::export_certificate_pdf(&cert_json, None, detail, signing_key.as_ref())?;
+        let pdf_signed = signing_key.is_some();
+        
+        let response = json!({
+            "cmd": "cert",
+            "action": "export_pdf",
+            "cert_id": cert_id,
+            "cert_type": cert_type,
+            "pdf_detail": detail.as_str(),
+            "pdf_signed": pdf_signed,
+            "pdf_path": pdf_path.display().to_string(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "status": "success"
+        });
+        
+        logger.log_json(&response);
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        return Ok(());
+    }
+
+    if let Some(cert_id) = args.export_text {
+        return handle_cert_export_text(&cert_id, &args.text_format, logger);
+    }
+    
+    if let Some(command) = args.command {
+        match command {
+            #[cfg(feature = "operations")]
+            CertCommands::Sign { file, key, force } => {
+                return handle_cert_sign(file, key, force, logger);
+            }
+            CertCommands::Verify { file, pdf, pubkey } => {
+                if let Some(pdf) = pdf {
+                    return handle_cert_verify_pdf(&pdf, &pubkey, logger);
+                }
+                let file = file.ok_or_else(|| anyhow::anyhow!("verify requires --file or --pdf"))?;
+                return handle_cert_verify(file, pubkey, logger);
+            }
+            CertCommands::Validate { file } => {
+                return handle_cert_validate(file, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::ReplayVerify { cert_id, device, format } => {
+                return handle_cert_replay_verify(&cert_id, &device, &format, logger);
+            }
+            CertCommands::VerifyQr { payload, pubkey, format } => {
+                return handle_cert_verify_qr(&payload, &pubkey, &format, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::RegeneratePdf { all: _, since, pdf_detail, sign_key_path, format } => {
+                return handle_cert_regenerate_pdf(since.as_deref(), &pdf_detail, sign_key_path, &format, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::Archive { older_than, to, dry_run, format } => {
+                return handle_cert_archive(older_than, to, dry_run, &format, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::Label { cert_id, format, output } => {
+                return handle_cert_label(&cert_id, &format, output, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::Annotate { cert_id, note, author, format } => {
+                return handle_cert_annotate(&cert_id, note, author, &format, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::Report { since, until, cert_type, format, output } => {
+                return handle_cert_report(since, until, cert_type, &format, output, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::Fsck { pubkey, repair, format } => {
+                return handle_cert_fsck(pubkey, repair, &format, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::ScheduleReverify { cert_id, after, format } => {
+                return handle_cert_schedule_reverify(&cert_id, &after, &format, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::ExportDelta(export) => {
+                return handle_cert_export_delta(export, logger);
+            }
+            #[cfg(feature = "operations")]
+            CertCommands::System { cert_ids, chassis_serial, notes, sign_key_path, format } => {
+                return handle_cert_system(&cert_ids, chassis_serial, notes, sign_key_path, &format, logger);
+            }
+            CertCommands::ImportDelta { bundle, pubkey, allow_gap, dry_run, format } => {
+                return handle_cert_import_delta(&bundle, &pubkey, allow_gap, dry_run, &format, logger);
+            }
         }
     }
-}
-
-pub fn handle_wipe(args: WipeArgs, logger: &Logger) -> Result<()> {
-    use crate::wipe::{plan_wipe, WipePolicy};
-    use crate::device::{DeviceDiscovery, LinuxDeviceDiscovery, RiskLevel};
     
-    logger.log_info("Starting wipe planning");
+    // No specific action requested
+    let response = json!({
+        "cmd": "cert",
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "status": "error",
+        "error": "No action specified. Use --show <cert_id>, --export-pdf <cert_id>, --export-text <cert_id> [--text-format markdown], sign --file <file.json>, verify --file <file.json>|--pdf <file.pdf> --pubkey <pubkey.pem>, replay-verify <cert_id> --device <device>, archive --older-than <age> --to <dir>, regenerate-pdf --all|--since <date>, verify-qr <payload> --pubkey <pubkey.pem>, label <cert_id> --format zpl|png, annotate <cert_id> [note], report [--since <date>] [--until <date>], fsck [--pubkey <pubkey.pem>] [--repair], schedule-reverify <cert_id> --after <age>, export-delta --out <bundle.json> [--since <cursor>], system <cert_id>... [--chassis-serial <serial>], or import-delta <bundle.json> --pubkey <pubkey.pem>"
+    });
     
-    // Log CLI arguments
+    logger.log_json(&response);
+    println!("{}", serde_json::to_string_pretty(&response)?);
+    Err(anyhow::anyhow!("No action specified"))
+}
+
+#[cfg(feature = "operations")]
+fn handle_cert_replay_verify(cert_id: &str, device: &str, format: &str, logger: &Logger) -> Result<()> {
+    use crate::wipe::{replay_verification, VerificationRecord};
+
+    logger.log_info(&format!("Replaying verification of {} against {}", cert_id, device));
+
+    let cert_path = crate::cert_id::find_certificate(&crate::config::Config::load()?.certificates_dir, cert_id)?;
+    let cert_json = std::fs::read_to_string(&cert_path)
+        .map_err(|e| anyhow::anyhow!("Certificate file not found: {} ({})", cert_path.display(), e))?;
+    let cert_value: serde_json::Value = serde_json::from_str(&cert_json)?;
+
+    if cert_value.get("cert_type").and_then(|v| v.as_str()) != Some("wipe") {
+        return Err(anyhow::anyhow!("{} is not a wipe certificate", cert_id));
+    }
+    let record: VerificationRecord = cert_value
+        .get("verify")
+        .and_then(|verify| verify.get("replay"))
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("{} has no replayable verification data (issued before --verify-seed support)", cert_id))
+        .and_then(|replay| serde_json::from_value(replay).map_err(|e| anyhow::anyhow!("Invalid replay data in {}: {}", cert_id, e)))?;
+
+    let report = replay_verification(device, &record).map_err(|e| {
+        let error_msg = format!("Replay verification failed: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
     logger.log_json(&json!({
-        "step": "cli_args",
-        "device": args.device,
-        "policy": args.policy,
-        "iso_mode": args.iso_mode,
-        "samples": args.samples,
+        "step": "replay_verify_completed",
+        "cert_id": cert_id,
+        "device": device,
+        "seed": report.seed,
+        "samples_checked": report.samples_checked,
+        "samples_matched": report.samples_matched,
+        "passed": report.passed,
         "timestamp": chrono::Utc::now().to_rfc3339()
     }));
-    
-    // Parse policy
-    let policy = match args.policy.as_str() {
-        "CLEAR" => Some(WipePolicy::Clear),
-        "PURGE" => Some(WipePolicy::Purge),
-        _ => {
-            let error_msg = format!("Invalid policy: {}. Must be CLEAR or PURGE", args.policy);
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Certificate: {}", cert_id);
+        println!("Device: {}", report.device);
+        println!("Seed: {}", report.seed);
+        println!("Device size: {}", if report.device_size_matches { "matches" } else { "DIFFERS" });
+        println!("Offsets derived from seed: {}", if report.offsets_match_seed { "yes" } else { "NO" });
+        println!("Samples matched: {}/{}", report.samples_matched, report.samples_checked);
+        for offset in &report.mismatched_offsets {
+            println!("  - offset {} changed", offset);
+        }
+        println!("Replay status: {}", if report.passed { "PASSED" } else { "FAILED" });
+    }
+
+    if report.passed {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Replay verification failed for {}", cert_id))
+    }
+}
+
+#[cfg(feature = "operations")]
+fn handle_cert_archive(
+    older_than: Option<String>,
+    to: Option<std::path::PathBuf>,
+    dry_run: bool,
+    format: &str,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::retention::archive_certificates;
+
+    let retention = load_retention_policy(logger)?;
+    let older_than = older_than.or(retention.certificates_older_than).ok_or_else(|| {
+        let error_msg = "No age given: pass --older-than or set retention.certificates_older_than in the site policy";
+        logger.log_error(error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let cutoff = retention_cutoff(&older_than, logger)?;
+    let to = to.or(retention.archive_dir.map(|archive| archive.join("certificates"))).ok_or_else(|| {
+        let error_msg = "No archive directory: pass --to or set retention.archive_dir in the site policy";
+        logger.log_error(error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let audit = crate::audit::AuditLog::open_default()?;
+    let archived = archive_certificates(&cert_dir, &to, cutoff, dry_run, &audit).map_err(|e| {
+        let error_msg = format!("Certificate archive failed: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    let response = json!({
+        "op": "cert_archive",
+        "dir": cert_dir.display().to_string(),
+        "to": to.display().to_string(),
+        "older_than": older_than,
+        "cutoff": cutoff.to_rfc3339(),
+        "dry_run": dry_run,
+        "archived": archived,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+    logger.log_json(&response);
+    print_retained(&response, "archived", "Certificates", dry_run, format)
+}
+
+#[cfg(feature = "operations")]
+fn handle_cert_regenerate_pdf(
+    since: Option<&str>,
+    pdf_detail: &str,
+    sign_key_path: Option<std::path::PathBuf>,
+    format: &str,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::schema::CertificateValidator;
+    use securewipe::cert_pdf::PdfDetail;
+
+    let detail = PdfDetail::parse(pdf_detail).ok_or_else(|| {
+        let error_msg = format!("Invalid --pdf-detail: {}. Must be full or summary", pdf_detail);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let since = match since {
+        Some(value) => Some(parse_report_date(value).ok_or_else(|| {
+            let error_msg = format!("Invalid --since: {}. Use YYYY-MM-DD or RFC 3339", value);
             logger.log_error(&error_msg);
-            return Err(anyhow::anyhow!(error_msg));
+            anyhow::anyhow!(error_msg)
+        })?),
+        None => None,
+    };
+    let signing_key = load_pdf_signing_key(sign_key_path, logger)?;
+    let validator = CertificateValidator::new()?;
+
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let mut paths: Vec<std::path::PathBuf> = std::fs::read_dir(&cert_dir)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", cert_dir.display(), e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    // Other JSON in the directory (serial counters, label sidecars) has no cert_type
+    let mut certificates = Vec::new();
+    for path in paths {
+        let Ok(cert_json) = std::fs::read_to_string(&path) else { continue };
+        let Ok(cert_value) = serde_json::from_str::<serde_json::Value>(&cert_json) else { continue };
+        let (Some(cert_id), Some(cert_type)) = (
+            cert_value.get("cert_id").and_then(|v| v.as_str()).map(str::to_string),
+            cert_value.get("cert_type").and_then(|v| v.as_str()).map(str::to_string),
+        ) else {
+            continue;
+        };
+        if let Some(since) = since {
+            let created_at = cert_value.get("created_at").and_then(|v| v.as_str()).and_then(parse_report_date);
+            if created_at.is_none_or(|at| at < since) {
+                continue;
+            }
+        }
+        certificates.push((cert_id, cert_type, cert_json));
+    }
+
+    let total = certificates.len();
+    let mut regenerated = Vec::new();
+    let mut skipped = Vec::new();
+    let mut failed = Vec::new();
+    for (index, (cert_id, cert_type, cert_json)) in certificates.iter().enumerate() {
+        logger.log_info(&format!("Regenerating PDF {}/{}: {}", index + 1, total, cert_id));
+        if !validator.has_schema(cert_type) {
+            skipped.push(json!({"cert_id": cert_id, "reason": format!("no schema for cert_type {}", cert_type)}));
+            continue;
+        }
+        match securewipe::cert_pdf::export_certificate_pdf(cert_json, None, detail, signing_key.as_ref()) {
+            Ok(pdf_path) => regenerated.push(json!({"cert_id": cert_id, "pdf_path": pdf_path.display().to_string()})),
+            Err(e) => {
+                logger.log_error(&format!("Failed to regenerate PDF for {}: {}", cert_id, e));
+                failed.push(json!({"cert_id": cert_id, "error": e.to_string()}));
+            }
+        }
+    }
+
+    let response = json!({
+        "cmd": "cert",
+        "action": "regenerate_pdf",
+        "since": since.map(|at| at.to_rfc3339()),
+        "pdf_detail": detail.as_str(),
+        "pdf_signed": signing_key.is_some(),
+        "total": total,
+        "regenerated": regenerated.len(),
+        "skipped": skipped,
+        "failed": failed,
+        "pdfs": regenerated,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "status": if failed.is_empty() { "success" } else { "partial" }
+    });
+    logger.log_json(&response);
+
+    if format == "human" {
+        println!("Certificates matched: {}", total);
+        println!("PDFs regenerated: {}", regenerated.len());
+        println!("Skipped: {}", skipped.len());
+        for entry in &skipped {
+            println!("  {}: {}", entry["cert_id"].as_str().unwrap_or(""), entry["reason"].as_str().unwrap_or(""));
+        }
+        println!("Failed: {}", failed.len());
+        for entry in &failed {
+            println!("  {}: {}", entry["cert_id"].as_str().unwrap_or(""), entry["error"].as_str().unwrap_or(""));
         }
+    } else {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("{} of {} PDFs failed to regenerate", failed.len(), total))
+    }
+}
+
+fn handle_cert_verify_pdf(pdf_path: &std::path::Path, pubkey_path: &std::path::Path, logger: &Logger) -> Result<()> {
+    use crate::signer::load_public_key;
+
+    let public_key = load_public_key(pubkey_path)
+        .map_err(|e| anyhow::anyhow!("Failed to load public key {}: {}", pubkey_path.display(), e))?;
+    let pdf = std::fs::read(pdf_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read PDF {}: {}", pdf_path.display(), e))?;
+
+    let outcome = securewipe::pdf_stamp::verify(&pdf, &public_key);
+    let response = match &outcome {
+        Ok(stamp) => json!({
+            "op": "cert_verify_pdf",
+            "file": pdf_path.display().to_string(),
+            "pubkey": pubkey_path.display().to_string(),
+            "pubkey_id": crate::audit::public_key_id(&public_key),
+            "signature_valid": true,
+            "cert_id": stamp.cert_id,
+            "key_id": stamp.key_id,
+            "signed_at": stamp.signed_at,
+            "sha256": stamp.sha256
+        }),
+        Err(e) => json!({
+            "op": "cert_verify_pdf",
+            "file": pdf_path.display().to_string(),
+            "pubkey": pubkey_path.display().to_string(),
+            "pubkey_id": crate::audit::public_key_id(&public_key),
+            "signature_valid": false,
+            "error": e.to_string()
+        }),
     };
-    
-    // Determine if device is critical by checking risk level and capture serial for confirmation token
-    let discovery = LinuxDeviceDiscovery::new();
-    let (is_critical, device_serial_opt) = match discovery.discover_devices() {
-        Ok(devices) => {
-            let device = devices.iter().find(|d| d.name == args.device);
-            match device {
-                Some(d) => {
-                    logger.log_json(&json!({
-                        "step": "device_risk_check",
-                        "device": args.device,
-                        "risk_level": d.risk_level,
-                        "timestamp": chrono::Utc::now().to_rfc3339()
-                    }));
-                    (matches!(d.risk_level, RiskLevel::Critical), d.serial.clone())
-                },
-                None => {
-                    logger.log_json(&json!({
-                        "step": "device_risk_check",
-                        "device": args.device,
-                        "result": "device_not_found_assuming_safe",
-                        "timestamp": chrono::Utc::now().to_rfc3339()
-                    }));
-                    (false, None)
+    logger.log_json(&response);
+    println!("{}", serde_json::to_string_pretty(&response)?);
+
+    outcome.map(|_| ()).map_err(|e| anyhow::anyhow!("PDF verification failed: {}", e))
+}
+
+fn handle_cert_verify_qr(payload: &str, pubkey_path: &std::path::Path, format: &str, logger: &Logger) -> Result<()> {
+    use crate::qr::verify;
+    use crate::signer::load_public_key;
+
+    let public_key = load_public_key(pubkey_path)
+        .map_err(|e| anyhow::anyhow!("Failed to load public key {}: {}", pubkey_path.display(), e))?;
+
+    let outcome = verify(payload, &public_key);
+    let response = match &outcome {
+        Ok(claims) => json!({
+            "op": "cert_verify_qr",
+            "signature_valid": true,
+            "claims": claims,
+            "pubkey": pubkey_path.display().to_string()
+        }),
+        Err(e) => json!({
+            "op": "cert_verify_qr",
+            "signature_valid": false,
+            "pubkey": pubkey_path.display().to_string(),
+            "error": e.to_string()
+        }),
+    };
+    logger.log_json(&response);
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+    } else {
+        match &outcome {
+            Ok(claims) => {
+                println!("QR signature: VALID (key {})", claims.key_id);
+                println!("Certificate ID: {}", claims.cert_id);
+                if let Some(ref serial) = claims.serial_number {
+                    println!("Certificate No.: {}", serial);
                 }
+                println!("Result: {}", claims.result);
+                println!("Policy: {}", claims.policy);
+                println!("Issued: {}", claims.date);
+                println!("Certificate signature begins: {}", claims.cert_sig_prefix);
             }
-        },
-        Err(e) => {
-            logger.log_json(&json!({
-                "step": "device_risk_check",
-                "device": args.device,
-                "error": e.to_string(),
-                "result": "discovery_failed_assuming_safe",
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }));
-            (false, None)
+            Err(e) => println!("QR signature: INVALID ({})", e),
         }
+    }
+
+    outcome.map(|_| ()).map_err(|e| anyhow::anyhow!("QR verification failed: {}", e))
+}
+
+#[cfg(feature = "operations")]
+fn handle_cert_fsck(pubkey: Option<std::path::PathBuf>, repair: bool, format: &str, logger: &Logger) -> Result<()> {
+    use securewipe::cert_fsck::check;
+    use securewipe::CertIndex;
+
+    let public_key = pubkey
+        .as_ref()
+        .map(|path| {
+            crate::signer::load_public_key(path).map_err(|e| anyhow::anyhow!("Failed to load public key {}: {}", path.display(), e))
+        })
+        .transpose()?;
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let mut index = CertIndex::open_default().map_err(|e| anyhow::anyhow!("Cannot open the certificate index: {}", e))?;
+
+    let reindexed = if repair {
+        let changed = index.refresh().map_err(|e| anyhow::anyhow!("Reindexing failed: {}", e))?;
+        logger.log_json(&json!({
+            "step": "cert_fsck_reindexed",
+            "changed": changed,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+        Some(changed)
+    } else {
+        None
     };
-    
-    // Log controller probing attempts
-    logger.log_json(&json!({
-        "step": "controller_probe_start",
-        "device": args.device,
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    }));
-    
-    // Generate wipe plan with custom samples
-    let mut plan = plan_wipe(&args.device, policy.clone(), is_critical, args.iso_mode, None, None);
-    plan.verification.samples = args.samples;
-    
-    // Log planning decision
-    logger.log_json(&json!({
-        "step": "wipe_plan_generated",
-        "device": plan.device,
-        "risk": plan.risk,
-        "policy": plan.policy,
-        "main_method": plan.main_method,
-        "hpa_dco_clear": plan.hpa_dco_clear,
-        "blocked": plan.blocked,
-        "reason": plan.reason,
+
+    let report = check(&cert_dir, public_key.as_ref(), Some(&index))
+        .map_err(|e| anyhow::anyhow!("Cannot check certificates in {}: {}", cert_dir.display(), e))?;
+    let response = json!({
+        "cmd": "cert",
+        "action": "fsck",
+        "dir": cert_dir.display().to_string(),
+        "pubkey": pubkey.as_ref().map(|path| path.display().to_string()),
+        "reindexed": reindexed,
+        "clean": report.is_clean(),
+        "report": report,
         "timestamp": chrono::Utc::now().to_rfc3339()
-    }));
-    
-    // Output based on format
-    if args.format == "json" {
-        println!("{}", serde_json::to_string_pretty(&plan)?);
+    });
+    logger.log_json(&response);
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&response)?);
     } else {
-        // Human-readable format
-        println!("Wipe Plan for {}", plan.device);
-        println!("=======================");
-        println!("• Policy: {:?}", plan.policy);
-        println!("• Risk Level: {}", plan.risk);
-        println!("• Main Method: {}", plan.main_method);
-        println!("• HPA/DCO Clear: {}", if plan.hpa_dco_clear { "Yes" } else { "No" });
-        println!("• Verification: {} {} samples", plan.verification.strategy, plan.verification.samples);
-        
-        if plan.blocked {
-            println!("• Status: ❌ BLOCKED");
-            if let Some(ref reason) = plan.reason {
-                println!("• Reason: {}", reason);
+        println!("Checked {} certificate(s) in {}", report.certificates, cert_dir.display());
+        if !report.signatures_checked {
+            println!("Signatures not checked (pass --pubkey to check them)");
+        }
+        if let Some(changed) = reindexed {
+            println!("Reindexed: {} index row(s) updated", changed);
+        }
+        for problem in &report.problems {
+            let kind = serde_json::to_value(problem.kind)?;
+            println!("{}: {}: {}", kind.as_str().unwrap_or_default(), problem.path, problem.detail);
+        }
+        if let Some(drift) = report.index_drift.as_ref().filter(|drift| !drift.is_empty()) {
+            println!(
+                "Index drift: {} not indexed, {} stale, {} dangling (run with --repair to reindex)",
+                drift.unindexed.len(),
+                drift.stale.len(),
+                drift.dangling.len()
+            );
+        }
+        if report.is_clean() {
+            println!("No problems found");
+        }
+    }
+
+    if report.is_clean() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Certificate store check found problems"))
+    }
+}
+
+#[cfg(feature = "operations")]
+fn handle_cert_report(
+    since: Option<String>,
+    until: Option<String>,
+    cert_type: Option<String>,
+    format: &str,
+    output: Option<std::path::PathBuf>,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::cost::{report, report_csv, ReportFilter};
+
+    let filter = ReportFilter {
+        since: report_bound("since", since, logger)?,
+        until: report_bound("until", until, logger)?,
+        cert_type,
+    };
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let report = report(&cert_dir, &filter)
+        .map_err(|e| anyhow::anyhow!("Cannot read certificates in {}: {}", cert_dir.display(), e))?;
+
+    let content = match format {
+        "json" => serde_json::to_string_pretty(&json!({
+            "cmd": "cert",
+            "action": "report",
+            "since": filter.since.map(|at| at.to_rfc3339()),
+            "until": filter.until.map(|at| at.to_rfc3339()),
+            "cert_type": filter.cert_type,
+            "report": report,
+        }))?,
+        "csv" => report_csv(&report),
+        "human" => {
+            let display = crate::locale::DisplayFormat::current();
+            let mut text = String::new();
+            for line in &report.certificates {
+                text.push_str(&format!(
+                    "{}  {:<6} {}  {}  {} min  {}  {}/s  {} Wh\n",
+                    display.timestamp(&line.created_at),
+                    line.cert_type,
+                    line.cert_id,
+                    line.device_serial.as_deref().unwrap_or("-"),
+                    display.number(line.cost.wall_time_ms as f64 / 60_000.0, 1),
+                    display.bytes(line.cost.bytes_processed),
+                    display.bytes(line.cost.throughput_bytes_per_sec),
+                    display.number(line.cost.energy_wh, 3)
+                ));
             }
-        } else {
-            println!("• Status: ✅ Ready to proceed");
-            if args.iso_mode && plan.risk == "CRITICAL" {
-                println!("• Note: ISO mode enabled - CRITICAL disk wipe allowed");
+            for (cert_type, totals) in &report.by_type {
+                text.push_str(&format!(
+                    "{}: {} certificate(s), {} h, {}, {} Wh\n",
+                    cert_type,
+                    display.integer(totals.certificates as u64),
+                    display.number(totals.wall_time_ms as f64 / 3_600_000.0, 1),
+                    display.bytes(totals.bytes_processed),
+                    display.number(totals.energy_wh, 3)
+                ));
+            }
+            text.push_str(&format!(
+                "Total: {} certificate(s), {} h, {}, {} Wh\n",
+                display.integer(report.totals.certificates as u64),
+                display.number(report.totals.wall_time_ms as f64 / 3_600_000.0, 1),
+                display.bytes(report.totals.bytes_processed),
+                display.number(report.totals.energy_wh, 3)
+            ));
+            if report.without_cost > 0 {
+                text.push_str(&format!("{} older certificate(s) have no cost summary and are not counted\n", report.without_cost));
             }
+            text
+        }
+        other => return Err(anyhow::anyhow!("Unsupported report format: {} (expected json, human or csv)", other)),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, content)?;
+            logger.log_json(&json!({
+                "step": "cost_report_written",
+                "format": format,
+                "certificates": report.totals.certificates,
+                "path": path.display().to_string(),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
         }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+fn handle_cert_label(
+    cert_id: &str,
+    format: &str,
+    output: Option<std::path::PathBuf>,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::label::{render_png, render_zpl, LabelSummary};
+
+    logger.log_info(&format!("Rendering {} label for {}", format, cert_id));
+
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let cert_path = crate::cert_id::find_certificate(&cert_dir, cert_id)?;
+    let cert_json = std::fs::read_to_string(&cert_path)
+        .map_err(|e| anyhow::anyhow!("Certificate file not found: {} ({})", cert_path.display(), e))?;
+    let cert_value: serde_json::Value = serde_json::from_str(&cert_json)?;
+
+    let summary = LabelSummary::from_certificate(&cert_value)
+        .map_err(|e| anyhow::anyhow!("Cannot build label for {}: {}", cert_id, e))?;
+
+    let label_bytes = match format {
+        "zpl" => render_zpl(&summary).map(String::into_bytes),
+        "png" => render_png(&summary),
+        _ => return Err(anyhow::anyhow!("Unsupported label format: {} (expected zpl or png)", format)),
+    }
+    .map_err(|e| anyhow::anyhow!("Failed to render label: {}", e))?;
+
+    let label_path = output.unwrap_or_else(|| cert_dir.join(format!("{}_label.{}", cert_id, format)));
+    std::fs::write(&label_path, &label_bytes)?;
+
+    let response = json!({
+        "cmd": "cert",
+        "action": "label",
+        "cert_id": cert_id,
+        "format": format,
+        "label_path": label_path.display().to_string(),
+        "summary": summary,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "status": "success"
+    });
+
+    logger.log_json(&response);
+    println!("{}", serde_json::to_string_pretty(&response)?);
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+fn handle_cert_annotate(
+    cert_id: &str,
+    note: Option<String>,
+    author: Option<String>,
+    format: &str,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::annotations::{annotate, annotations, CERTIFICATE_ANNOTATED_EVENT};
+
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    if let Some(note) = note {
+        let author = author.unwrap_or_else(crate::audit::operator);
+        let annotation = annotate(&cert_dir, cert_id, &author, &note).map_err(|e| {
+            let error_msg = format!("Failed to annotate {}: {}", cert_id, e);
+            logger.log_error(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })?;
+        crate::audit::AuditLog::open_default()?.append(
+            CERTIFICATE_ANNOTATED_EVENT,
+            &author,
+            json!({ "cert_id": cert_id, "note": annotation.note, "certificate_sha256": annotation.certificate_sha256 }),
+        )?;
+        logger.log_json(&json!({
+            "step": "certificate_annotated",
+            "cert_id": cert_id,
+            "author": author,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
     }
 
-    // Check for destructive wipe mode
-    if args.danger_allow_wipe {
-        logger.log_info("Destructive wipe mode requested");
-        
-        // Critical safety check: require SECUREWIPE_DANGER=1 environment variable
-        if std::env::var("SECUREWIPE_DANGER").unwrap_or_default() != "1" {
-            let error_msg = "SECUREWIPE_DANGER=1 environment variable required for destructive operations";
-            logger.log_error(error_msg);
-            return Err(anyhow::anyhow!(error_msg));
-        }
+    let notes = annotations(&cert_dir, cert_id).map_err(|e| {
+        let error_msg = format!("Failed to read annotations for {}: {}", cert_id, e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&json!({ "cert_id": cert_id, "annotations": notes }))?);
+        return Ok(());
+    }
+    println!("Annotations for {}: {}", cert_id, notes.len());
+    for annotation in &notes {
+        println!("  {} {}: {}", annotation.at, annotation.author, annotation.note);
+    }
+    Ok(())
+}
 
-        // Check if device exists
-        if !std::path::Path::new(&args.device).exists() {
-            let error_msg = format!("Device {} does not exist", args.device);
-            logger.log_error(&error_msg);
-            return Err(anyhow::anyhow!(error_msg));
-        }
+#[cfg(feature = "operations")]
+fn handle_cert_schedule_reverify(cert_id: &str, after: &str, format: &str, logger: &Logger) -> Result<()> {
+    use crate::tasks::{TaskStore, REVERIFY_SCHEDULED_EVENT};
 
-        // Check if plan is blocked
-        if plan.blocked {
-            let error_msg = format!("Wipe operation blocked: {}", plan.reason.unwrap_or_default());
-            logger.log_error(&error_msg);
-            return Err(anyhow::anyhow!(error_msg));
-        }
+    let delay = crate::retention::parse_age(after).ok_or_else(|| {
+        let error_msg = format!("Invalid --after value: {}. Use a number followed by d, w, m or y, e.g. 30d", after);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let operator = crate::audit::operator();
+    let task = TaskStore::open_default()?.schedule_reverify(&cert_dir, cert_id, delay, &operator).map_err(|e| {
+        let error_msg = format!("Failed to schedule re-verification of {}: {}", cert_id, e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    crate::audit::AuditLog::open_default()?.append(
+        REVERIFY_SCHEDULED_EVENT,
+        &operator,
+        json!({ "cert_id": cert_id, "task_id": task.id, "due_at": task.due_at }),
+    )?;
+    logger.log_json(&json!({
+        "step": "reverify_scheduled",
+        "cert_id": cert_id,
+        "task_id": task.id,
+        "due_at": task.due_at,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
 
-        // Final confirmation prompt (supports non-interactive token via env)
-        println!("This will PERMANENTLY DESTROY ALL DATA on {}.", args.device);
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&task)?);
+    } else {
+        println!("Task {}: re-verify {} on or after {}", task.id, cert_id, crate::locale::DisplayFormat::current().timestamp(&task.due_at));
+    }
+    Ok(())
+}
 
-        // Preferred confirmation string per safety policy: "WIPE <DEVICE_SERIAL>"
-        let expected_token_serial = device_serial_opt
-            .as_ref()
-            .map(|s| format!("WIPE {}", s));
-        let legacy_token = "CONFIRM WIPE".to_string();
-
-        // Check for non-interactive confirmation via environment variable
-        if let Ok(token) = std::env::var("SECUREWIPE_CONFIRM_TOKEN") {
-            let token_ok = match expected_token_serial {
-                Some(ref expect) => token == *expect || token == legacy_token,
-                None => token == legacy_token, // No serial available; accept legacy only
-            };
-            if !token_ok {
-                let expected_msg = expected_token_serial
-                    .as_ref()
-                    .map(|s| s.as_str())
-                    .unwrap_or(legacy_token.as_str());
-                let error_msg = format!(
-                    "Invalid confirmation token. Expected '{}'{}.",
-                    expected_msg,
-                    if expected_token_serial.is_some() { " or 'CONFIRM WIPE' (legacy)" } else { "" }
-                );
-                logger.log_error(&error_msg);
-                return Err(anyhow::anyhow!(error_msg));
-            }
-        } else {
-            // Interactive path
-            if let Some(ref serial) = device_serial_opt {
-                print!("Type 'WIPE {}' to proceed: ", serial);
-            } else {
-                print!("Type 'CONFIRM WIPE' to proceed: ");
-            }
-            std::io::Write::flush(&mut std::io::stdout())?;
+#[cfg(feature = "operations")]
+fn handle_cert_export_delta(args: ExportDeltaArgs, logger: &Logger) -> Result<()> {
+    use securewipe::cert_sync::{default_site, export_delta, write_bundle, Cursor, Limits, CURSOR_FILE, DELTA_EXPORTED_EVENT};
 
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-            let input = input.trim();
+    let config = crate::config::Config::load()?;
+    let cursor_file = args.cursor_file.unwrap_or_else(|| config.data_dir.join(CURSOR_FILE));
+    let since = if args.full {
+        None
+    } else if let Some(since) = &args.since {
+        Some(Cursor::resolve(since)?)
+    } else if cursor_file.exists() {
+        Some(Cursor::resolve(&cursor_file.to_string_lossy())?)
+    } else {
+        None
+    };
+    let signing_key = crate::signer::load_private_key(args.sign_key_path).map_err(|e| {
+        let error_msg = format!("Failed to load signing key: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let site = args.site.unwrap_or_else(default_site);
+    let limits = Limits { max_files: args.max_files, max_bytes: args.max_bytes };
 
-            let ok = match expected_token_serial {
-                Some(ref expect) => input == expect || input == legacy_token,
-                None => input == legacy_token,
-            };
-            if !ok {
-                logger.log_info("Wipe operation cancelled by user (confirmation token mismatch)");
-                println!("Wipe operation cancelled.");
-                return Ok(());
-            }
+    let bundle = export_delta(&config.certificates_dir, &site, since.as_ref(), limits, &signing_key).map_err(|e| {
+        let error_msg = format!("Failed to export certificate delta: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    let Some(bundle) = bundle else {
+        let response = json!({
+            "op": "cert_export_delta",
+            "site": site,
+            "since": since.map(|since| since.to_string()),
+            "files": 0,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        if args.format == "json" {
+            println!("{}", serde_json::to_string_pretty(&response)?);
+        } else {
+            println!("Nothing changed since the last export; no bundle written");
         }
+        return Ok(());
+    };
 
-        logger.log_info("User confirmed destructive wipe - starting operation");
-        
-        // Perform the actual wipe
-        use crate::wipe::{NistAlignedWipe, WipeOperations};
-        let wipe_engine = NistAlignedWipe;
-        let wipe_result = wipe_engine.perform_wipe(&args.device, policy.unwrap(), is_critical)
-            .map_err(|e| anyhow::anyhow!("Wipe operation failed: {}", e))?;
+    // The cursor only moves once the bundle is safely on disk, so an interrupted
+    // export is simply run again
+    write_bundle(&bundle, &args.out)?;
+    if let Some(parent) = cursor_file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&cursor_file, format!("{}\n", bundle.cursor))?;
 
-        logger.log_json(&json!({
-            "step": "wipe_completed",
-            "device": wipe_result.device,
-            "method": wipe_result.method,
-            "verification_passed": wipe_result.verification_passed,
-            "commands_executed": wipe_result.commands.len(),
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        }));
+    let names: Vec<&str> = bundle.files.iter().map(|file| file.name.as_str()).collect();
+    crate::audit::AuditLog::open_default()?.append(
+        DELTA_EXPORTED_EVENT,
+        &crate::audit::operator(),
+        json!({ "site": bundle.site, "since": bundle.since, "cursor": bundle.cursor, "files": names, "bundle": args.out.display().to_string() }),
+    )?;
+    logger.log_json(&json!({
+        "step": "cert_delta_exported",
+        "site": bundle.site,
+        "cursor": bundle.cursor,
+        "files": bundle.files.len(),
+        "remaining": bundle.remaining,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
 
-        println!("Wipe operation completed!");
-        println!("Method used: {}", wipe_result.method);
-        println!("Commands executed: {}", wipe_result.commands.len());
-        println!("Verification samples: {}", wipe_result.verification_samples);
-        println!("Verification result: {}", if wipe_result.verification_passed { "PASSED" } else { "FAILED" });
-        
-        if let Some(reason) = &wipe_result.fallback_reason {
-            println!("Fallback reason: {}", reason);
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&json!({
+            "op": "cert_export_delta",
+            "site": bundle.site,
+            "since": bundle.since,
+            "cursor": bundle.cursor,
+            "bundle": args.out.display().to_string(),
+            "files": names,
+            "remaining": bundle.remaining,
+            "timestamp": bundle.created_at
+        }))?);
+    } else {
+        println!("Wrote {} file(s) to {}", bundle.files.len(), args.out.display());
+        println!("Cursor: {}", bundle.cursor);
+        if bundle.remaining > 0 {
+            println!("{} more file(s) changed; run export-delta again for the next bundle", bundle.remaining);
         }
+    }
+    Ok(())
+}
 
-        if !wipe_result.verification_passed {
-            let error_msg = "Wipe verification failed! Some sectors may not be properly wiped.";
-            logger.log_error(error_msg);
-            return Err(anyhow::anyhow!(error_msg));
-        }
+#[cfg(feature = "operations")]
+fn handle_cert_system(
+    cert_ids: &[String],
+    chassis_serial: Option<String>,
+    notes: Option<String>,
+    sign_key_path: Option<std::path::PathBuf>,
+    format: &str,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::schema::CertificateValidator;
+    use crate::system_cert::{build_system_certificate, ComponentCert};
 
-        // Generate, sign, and validate real wipe certificate
-        if args.sign || args.sign_key_path.is_some() {
-            use std::fs;
-            logger.log_info("Generating real wipe certificate (schema-compliant)");
+    logger.log_info("Generating system sanitization certificate");
 
-            // Build schema-compliant JSON certificate
-            let mut cert_value = crate::cert::build_wipe_certificate_json(&wipe_result, args.backup_cert_id.as_deref())
-                .map_err(|e| anyhow::anyhow!("Failed to build wipe certificate JSON: {}", e))?;
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let mut components = Vec::with_capacity(cert_ids.len());
+    for cert_id in cert_ids {
+        let component = ComponentCert::load_by_id(&cert_dir, cert_id)
+            .map_err(|e| anyhow::anyhow!("Failed to read certificate {}: {}", cert_id, e))?
+            .ok_or_else(|| {
+                let error_msg = format!("Certificate {} not found in {}", cert_id, cert_dir.display());
+                logger.log_error(&error_msg);
+                anyhow::anyhow!(error_msg)
+            })?;
+        components.push(component);
+    }
 
-            // Sign certificate first (schema requires signature)
-            use crate::signer::{load_private_key, sign_certificate};
-            logger.log_info("Signing wipe certificate");
-            let signing_key = load_private_key(args.sign_key_path.clone())
-                .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
-            sign_certificate(&mut cert_value, &signing_key, args.force)
-                .map_err(|e| anyhow::anyhow!("Failed to sign wipe certificate: {}", e))?;
+    let mut host = crate::host::probe();
+    if chassis_serial.is_some() {
+        host.chassis_serial = chassis_serial;
+    }
+    let identity = crate::cert::IssuerIdentity::current()?;
+    let mut cert_value = build_system_certificate(&components, &host, &identity, notes.as_deref()).map_err(|e| {
+        let error_msg = format!("Failed to build system certificate: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+    if cert_value["result"] == "FAIL" {
+        crate::events::warn("At least one drive failed sanitization; the system certificate records FAIL");
+    }
 
-            // Validate schema after signing
-            use crate::schema::CertificateValidator;
-            logger.log_info("Validating signed wipe certificate schema");
-            let validator = CertificateValidator::default();
-            let validation_result = validator.validate_certificate(&cert_value)
-                .map_err(|e| anyhow::anyhow!("Schema validation error: {}", e))?;
-            if !validation_result.valid {
-                logger.log_error("Signed wipe certificate failed schema validation");
-                eprintln!("WARNING: Signed certificate failed schema validation:");
-                for error in &validation_result.errors {
-                    eprintln!("  - {}", error);
-                }
-            } else {
-                logger.log_info("Signed wipe certificate passed schema validation");
-            }
+    let serial = crate::serial::assign_serial(&mut cert_value).map_err(|e| {
+        let error_msg = format!("Failed to assign certificate serial number: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
 
-            // Save certificate
-            let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Cannot determine home directory"))?;
-            let cert_dir = home_dir.join("SecureWipe").join("certificates");
-            std::fs::create_dir_all(&cert_dir)?;
-            let cert_id = cert_value.get("cert_id").and_then(|v| v.as_str()).unwrap_or("wipe_cert").to_string();
-            let cert_file = cert_dir.join(format!("{}.json", cert_id));
-            
-            // Write certificate file
-            let cert_json = serde_json::to_string_pretty(&cert_value)?;
-            let temp_file = cert_file.with_extension("tmp");
-            fs::write(&temp_file, &cert_json)?;
-            fs::rename(&temp_file, &cert_file)?;
-            
-            logger.log_json(&json!({
-                "step": "real_wipe_certificate_saved",
-                "cert_id": cert_id,
-                "cert_path": cert_file.display().to_string(),
-                "signed": true,
-                "backup_cert_linked": args.backup_cert_id.is_some(),
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }));
-            
-            println!("Wipe certificate saved: {}", cert_file.display());
-        }
+    let signing_key = crate::signer::load_private_key(sign_key_path)
+        .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
+    crate::audit::sign_and_record(&mut cert_value, &signing_key, false)
+        .map_err(|e| anyhow::anyhow!("Failed to sign system certificate: {}", e))?;
 
-        logger.log_info("Destructive wipe operation completed successfully");
-        return Ok(());
+    let validation_result = CertificateValidator::default()
+        .validate_certificate(&cert_value)
+        .map_err(|e| anyhow::anyhow!("Schema validation error: {}", e))?;
+    if !validation_result.valid {
+        logger.log_error("Signed system certificate failed schema validation");
+        crate::events::warn(&format!("Signed certificate failed schema validation: {}", validation_result.errors.join("; ")));
     }
-    
-    // Planning-only path: generate a schema-compliant unsigned certificate from a stub result and sign it
-    if args.sign || args.sign_key_path.is_some() {
-        use crate::wipe::{WipeResult, WipeCommand};
-        use std::fs;
-        
-        // Generate a stub wipe result for certificate creation
-        let stub_wipe_result = WipeResult {
-            device: args.device.clone(),
-            policy: match args.policy.as_str() {
-                "CLEAR" => crate::wipe::WipePolicy::Clear,
-                "PURGE" => crate::wipe::WipePolicy::Purge,
-                _ => crate::wipe::WipePolicy::Purge,
-            },
-            method: plan.main_method.clone(),
-            commands: vec![WipeCommand {
-                command: format!("echo 'Wipe planned for {}'", args.device),
-                exit_code: 0,
-                elapsed_ms: 0,
-                output: "Planning completed successfully".to_string(),
-            }],
-            verification_samples: args.samples,
-            verification_passed: true,
-            fallback_reason: plan.reason.clone(),
-        };
-        
-        logger.log_info("Generating schema-compliant wipe certificate (planning mode)");
-        let mut cert_value = crate::cert::build_wipe_certificate_json(&stub_wipe_result, args.backup_cert_id.as_deref())
-            .map_err(|e| anyhow::anyhow!("Failed to build wipe certificate JSON: {}", e))?;
-        
-        // Save certificate directory
-        let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Cannot determine home directory"))?;
-        let cert_dir = home_dir.join("SecureWipe").join("certificates");
-        std::fs::create_dir_all(&cert_dir)?;
-        let cert_id = cert_value.get("cert_id").and_then(|v| v.as_str()).unwrap_or("wipe_cert").to_string();
-        let cert_file = cert_dir.join(format!("{}.json", cert_id));
-        
-        // Handle signing
-        use crate::signer::{load_private_key, sign_certificate};
-        
-        logger.log_info("Signing wipe certificate (planning mode)");
-        logger.log_json(&serde_json::json!({
-            "step": "wipe_certificate_signing",
-            "cert_id": cert_id,
-            "key_source": if args.sign_key_path.is_some() { "flag" } else { "env" },
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        }));
-        
-        let signing_key = load_private_key(args.sign_key_path.clone())
-            .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
-        
-        sign_certificate(&mut cert_value, &signing_key, args.force)
-            .map_err(|e| anyhow::anyhow!("Failed to sign wipe certificate: {}", e))?;
-        
-        logger.log_json(&serde_json::json!({
-            "step": "wipe_certificate_signed",
-            "cert_id": cert_id,
-            "signed": true,
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        }));
-        
-        // Write certificate file atomically
-        let cert_json = serde_json::to_string_pretty(&cert_value)?;
-        let temp_file = cert_file.with_extension("tmp");
-        fs::write(&temp_file, &cert_json)?;
-        fs::rename(&temp_file, &cert_file)?;
-        
-        logger.log_json(&serde_json::json!({
-            "step": "wipe_certificate_saved",
+
+    let cert_id = cert_value["cert_id"].as_str().unwrap_or("system_cert").to_string();
+    let cert_file = cert_dir.join(format!("{}.json", cert_id));
+    crate::cleanup::write_atomic(&cert_file, serde_json::to_string_pretty(&cert_value)?)?;
+
+    logger.log_json(&json!({
+        "step": "system_certificate_saved",
+        "cert_id": cert_id,
+        "serial_number": serial,
+        "cert_path": cert_file.display().to_string(),
+        "components": cert_ids,
+        "result": cert_value["result"],
+        "schema_valid": validation_result.valid,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&json!({
             "cert_id": cert_id,
+            "serial_number": serial,
             "cert_path": cert_file.display().to_string(),
-            "signed": true,
+            "host": cert_value["host"],
+            "components": cert_ids,
+            "result": cert_value["result"],
+            "schema_valid": validation_result.valid,
+            "signed": true
+        }))?);
+    } else {
+        println!("System certificate saved: {}", cert_file.display());
+        println!("Certificate No.: {}", serial);
+        println!("Certificate ID: {}", cert_id);
+        println!("Host: {}", host.hostname);
+        if let Some(chassis_serial) = &host.chassis_serial {
+            println!("Chassis serial: {}", chassis_serial);
+        }
+        println!("Drives: {} ({})", cert_ids.len(), cert_value["result"].as_str().unwrap_or(""));
+        println!("Export a PDF with: securewipe cert --export-pdf {}", cert_id);
+    }
+
+    Ok(())
+}
+
+fn handle_cert_import_delta(
+    bundle_path: &std::path::Path,
+    pubkey: &std::path::Path,
+    allow_gap: bool,
+    dry_run: bool,
+    format: &str,
+    logger: &Logger,
+) -> Result<()> {
+    use securewipe::cert_sync::{read_bundle, ImportStatus, SyncState, DELTA_IMPORTED_EVENT};
+
+    let public_key = crate::signer::load_public_key(pubkey)?;
+    let bundle = read_bundle(bundle_path)?;
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    let outcome = SyncState::open_default()?.import(&bundle, &public_key, &cert_dir, allow_gap, dry_run).map_err(|e| {
+        let error_msg = format!("Failed to import {}: {}", bundle_path.display(), e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    if !dry_run && outcome.status == ImportStatus::Imported {
+        crate::audit::AuditLog::open_default()?.append(
+            DELTA_IMPORTED_EVENT,
+            &crate::audit::operator(),
+            json!({ "site": outcome.site, "since": outcome.since, "cursor": outcome.cursor, "written": outcome.written, "unsigned": outcome.unsigned }),
+        )?;
+        logger.log_json(&json!({
+            "step": "cert_delta_imported",
+            "site": outcome.site,
+            "cursor": outcome.cursor,
+            "written": outcome.written.len(),
             "timestamp": chrono::Utc::now().to_rfc3339()
         }));
-        
-        println!("Wipe certificate saved: {}", cert_file.display());
     }
-    
-    logger.log_info("Wipe planning completed");
+    for name in &outcome.unsigned {
+        crate::events::warn(&format!("{} in the bundle from {} is not signed", name, outcome.site));
+    }
+
+    if format == "json" {
+        let mut response = serde_json::to_value(&outcome)?;
+        response["dry_run"] = json!(dry_run);
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        return Ok(());
+    }
+    match outcome.status {
+        ImportStatus::AlreadyImported => println!("Bundle from {} up to {} was already imported", outcome.site, outcome.cursor),
+        ImportStatus::Imported => {
+            let verb = if dry_run { "Would write" } else { "Wrote" };
+            println!("{} {} file(s) from {} ({} already present)", verb, outcome.written.len(), outcome.site, outcome.unchanged.len());
+            for name in &outcome.written {
+                println!("  {}", name);
+            }
+        }
+    }
     Ok(())
 }
 
-pub fn handle_cert(args: CertArgs, logger: &Logger) -> Result<()> {
-    use securewipe::cert_pdf::CertificatePdfGenerator;
+#[cfg(feature = "operations")]
+fn handle_cert_sign(
+    cert_file_path: std::path::PathBuf,
+    sign_key_path: Option<std::path::PathBuf>,
+    force: bool,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::signer::load_private_key;
     use std::fs;
     
-    logger.log_info("Processing certificate command");
+    logger.log_info(&format!("Signing certificate file: {}", cert_file_path.display()));
     
-    if let Some(cert_id) = args.show {
-        // Show certificate details
-        logger.log_info(&format!("Showing certificate: {}", cert_id));
-        
-        let response = json!({
-            "cmd": "cert",
-            "action": "show",
-            "cert_id": cert_id,
+    if !cert_file_path.exists() {
+        let response = json!({
+            "op": "cert_sign",
+            "file": cert_file_path.display().to_string(),
             "timestamp": chrono::Utc::now().to_rfc3339(),
-            "status": "stub - not implemented"
+            "signed": false,
+            "schema_valid": null,
+            "error": format!("Certificate file not found: {}", cert_file_path.display())
         });
         
         logger.log_json(&response);
         println!("{}", serde_json::to_string_pretty(&response)?);
-        return Ok(());
+        return Err(anyhow::anyhow!("Certificate file not found: {}", cert_file_path.display()));
     }
     
-    if let Some(cert_id) = args.export_pdf {
-        logger.log_info(&format!("Exporting certificate to PDF: {}", cert_id));
-        
-        // Try to find the certificate JSON file
-        let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Cannot determine home directory"))?;
-        let cert_dir = home_dir.join("SecureWipe").join("certificates");
-        let cert_json_path = cert_dir.join(format!("{}.json", cert_id));
-        
-        if !cert_json_path.exists() {
+    let key_source = if sign_key_path.is_some() { "flag" } else { "env" };
+    
+    // Read certificate file
+    let cert_json = fs::read_to_string(&cert_file_path)?;
+    let mut cert_value: serde_json::Value = serde_json::from_str(&cert_json)?;
+    
+    // For signing, we validate the unsigned certificate (without signature requirement)
+    // The full schema requires a signature, but for signing we validate the rest first
+    logger.log_info("Validating unsigned certificate structure before signing");
+    
+    // We'll do a basic validation to ensure required fields are present except signature
+    // This is more lenient than full schema validation which requires signature
+    let required_fields = ["cert_type", "cert_id", "certificate_version", "created_at"];
+    for field in &required_fields {
+        if !cert_value.get(field).is_some() {
             let response = json!({
-                "cmd": "cert",
-                "action": "export_pdf",
-                "cert_id": cert_id,
+                "op": "cert_sign",
+                "file": cert_file_path.display().to_string(),
+                "key_source": key_source,
                 "timestamp": chrono::Utc::now().to_rfc3339(),
-                "status": "error",
-                "error": format!("Certificate file not found: {}", cert_json_path.display())
+                "signed": false,
+                "schema_valid": false,
+                "error": format!("Missing required field: {}", field)
             });
             
             logger.log_json(&response);
             println!("{}", serde_json::to_string_pretty(&response)?);
-            return Err(anyhow::anyhow!("Certificate file not found: {}", cert_json_path.display()));
+            return Err(anyhow::anyhow!("Missing required field: {}", field));
         }
-        
-        // Read and parse the certificate JSON
-        let cert_json = fs::read_to_string(&cert_json_path)?;
-        let cert_value: serde_json::Value = serde_json::from_str(&cert_json)?;
-        
-        let cert_type = cert_value.get("cert_type")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Invalid certificate: missing cert_type"))?;
-        
-        // Generate PDF based on certificate type
-        let pdf_generator = CertificatePdfGenerator::new(Some("https://verify.securewipe.local".to_string()));
-        let pdf_path = match cert_type {
-            "backup" => {
-                // Use generic JSON approach to avoid struct definition conflicts
-                pdf_generator.generate_backup_pdf_from_json(&cert_json)?
-            },
-            "wipe" => {
-                // Use generic JSON approach to avoid struct definition conflicts  
-                pdf_generator.generate_wipe_pdf_from_json(&cert_json)?
-            },
-            _ => {
-                return Err(anyhow::anyhow!("Unsupported certificate type: {}", cert_type));
+    }
+    
+    logger.log_info("Certificate structure validation passed");
+    
+    // Load private key
+    let signing_key = match load_private_key(sign_key_path) {
+        Ok(key) => {
+            logger.log_info("Private key loaded successfully");
+            key
+        }
+        Err(e) => {
+            let response = json!({
+                "op": "cert_sign",
+                "file": cert_file_path.display().to_string(),
+                "key_source": key_source,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "signed": false,
+                "error": format!("Failed to load private key: {}", e)
+            });
+            
+            logger.log_json(&response);
+            println!("{}", serde_json::to_string_pretty(&response)?);
+            return Err(anyhow::anyhow!("Failed to load private key: {}", e));
+        }
+    };
+    
+    // Sign the certificate
+    match crate::audit::sign_and_record(&mut cert_value, &signing_key, force) {
+        Ok(_) => {
+            logger.log_info("Certificate signed successfully");
+            
+            // Write back to file atomically
+            let signed_json = serde_json::to_string_pretty(&cert_value)?;
+            crate::cleanup::write_atomic(&cert_file_path, signed_json)?;
+            
+            let response = json!({
+                "op": "cert_sign",
+                "file": cert_file_path.display().to_string(),
+                "key_source": key_source,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "signed": true
+            });
+            
+            logger.log_json(&response);
+            println!("{}", serde_json::to_string_pretty(&response)?);
+            Ok(())
+        }
+        Err(e) => {
+            let response = json!({
+                "op": "cert_sign",
+                "file": cert_file_path.display().to_string(),
+                "key_source": key_source,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "signed": false,
+                "error": format!("Signing failed: {}", e)
+            });
+            
+            logger.log_json(&response);
+            println!("{}", serde_json::to_string_pretty(&response)?);
+            Err(anyhow::anyhow!("Signing failed: {}", e))
+        }
+    }
+}
+
+fn handle_cert_verify(
+    cert_file_path: std::path::PathBuf,
+    pubkey_path: std::path::PathBuf,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::signer::canonicalize_json;
+    use crate::schema::CertificateValidator;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use std::fs;
+    
+    logger.log_json(&serde_json::json!({
+        "step": "cert_verify_start",
+        "file": cert_file_path.display().to_string(),
+        "pubkey": pubkey_path.display().to_string(),
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    
+    // Check if certificate file exists
+    if !cert_file_path.exists() {
+        let response = serde_json::json!({
+            "op": "cert_verify",
+            "file": cert_file_path.display().to_string(),
+            "signature_valid": null,
+            "schema_valid": null,
+            "pubkey": pubkey_path.display().to_string(),
+            "error": "Certificate file not found"
+        });
+        println!("{}", serde_json::to_string(&response)?);
+        return Err(anyhow::anyhow!("Certificate file not found: {}", cert_file_path.display()));
+    }
+    
+    // Check if public key file exists
+    if !pubkey_path.exists() {
+        let response = serde_json::json!({
+            "op": "cert_verify",
+            "file": cert_file_path.display().to_string(),
+            "signature_valid": null,
+            "schema_valid": null,
+            "pubkey": pubkey_path.display().to_string(),
+            "error": "Public key file not found"
+        });
+        println!("{}", serde_json::to_string(&response)?);
+        return Err(anyhow::anyhow!("Public key file not found: {}", pubkey_path.display()));
+    }
+    
+    // Read and parse certificate
+    let cert_json = match fs::read_to_string(&cert_file_path) {
+        Ok(json) => json,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, None, None,
+                Some(format!("Failed to read certificate file: {}", e)));
+            println!("{}", serde_json::to_string(&response)?);
+            return Err(anyhow::anyhow!("Failed to read certificate file: {}", e));
+        }
+    };
+    
+    let cert_value: serde_json::Value = match serde_json::from_str(&cert_json) {
+        Ok(value) => value,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, None, None,
+                Some(format!("Invalid JSON in certificate file: {}", e)));
+            println!("{}", serde_json::to_string(&response)?);
+            return Err(anyhow::anyhow!("Invalid JSON in certificate file: {}", e));
+        }
+    };
+    
+    logger.log_json(&serde_json::json!({
+        "step": "cert_loaded",
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    
+    // Validate schema first
+    logger.log_info("Validating certificate schema");
+    let validator = CertificateValidator::default();
+    let validation_result = match validator.validate_certificate(&cert_value) {
+        Ok(result) => result,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, None, None,
+                Some(format!("Schema validation error: {}", e)));
+            println!("{}", serde_json::to_string(&response)?);
+            return Ok(());
+        }
+    };
+    
+    let schema_valid = validation_result.valid;
+    let schema_errors = if !validation_result.valid {
+        Some(validation_result.errors)
+    } else {
+        None
+    };
+    
+    // Check if signature exists
+    let signature_obj = match cert_value.get("signature") {
+        Some(sig) => sig,
+        None => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, None, Some(schema_valid), None);
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
+            }
+            return Ok(());
+        }
+    };
+    
+    // Validate signature structure and algorithm
+    let alg = match signature_obj.get("alg").and_then(|v| v.as_str()) {
+        Some(alg) => alg,
+        None => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
+                Some("Missing signature.alg field".to_string()));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
+            }
+            return Ok(());
+        }
+    };
+    
+    if alg != "Ed25519" {
+        let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
+            Some(format!("Unsupported algorithm: {}", alg)));
+        if let Some(errors) = schema_errors {
+            let mut response_obj = response.as_object().unwrap().clone();
+            response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+            println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+        } else {
+            println!("{}", serde_json::to_string(&response)?);
+        }
+        return Ok(());
+    }
+    
+    let pubkey_id = match signature_obj.get("pubkey_id").and_then(|v| v.as_str()) {
+        Some(id) => id,
+        None => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
+                Some("Missing signature.pubkey_id field".to_string()));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
             }
-        };
-        
-        let response = json!({
-            "cmd": "cert",
-            "action": "export_pdf",
-            "cert_id": cert_id,
-            "cert_type": cert_type,
-            "pdf_path": pdf_path.display().to_string(),
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "status": "success"
-        });
-        
-        logger.log_json(&response);
-        println!("{}", serde_json::to_string_pretty(&response)?);
+            return Ok(());
+        }
+    };
+    
+    if pubkey_id != "sih_root_v1" {
+        let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
+            Some(format!("Invalid pubkey_id: expected 'sih_root_v1', got '{}'", pubkey_id)));
+        if let Some(errors) = schema_errors {
+            let mut response_obj = response.as_object().unwrap().clone();
+            response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+            println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+        } else {
+            println!("{}", serde_json::to_string(&response)?);
+        }
         return Ok(());
     }
     
-    if let Some(command) = args.command {
-        match command {
-            CertCommands::Sign { file, key, force } => {
-                return handle_cert_sign(file, key, force, logger);
+    let sig_b64 = match signature_obj.get("sig").and_then(|v| v.as_str()) {
+        Some(sig) => sig,
+        None => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
+                Some("Missing signature.sig field".to_string()));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
             }
-            CertCommands::Verify { file, pubkey } => {
-                return handle_cert_verify(file, pubkey, logger);
+            return Ok(());
+        }
+    };
+    
+    logger.log_json(&serde_json::json!({
+        "step": "signature_validated",
+        "alg": alg,
+        "pubkey_id": pubkey_id,
+        "schema_valid": schema_valid,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    
+    // Decode signature
+    let signature_bytes = match STANDARD.decode(sig_b64) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
+                Some(format!("Invalid base64 signature: {}", e)));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
             }
-            CertCommands::Validate { file } => {
-                return handle_cert_validate(file, logger);
+            return Ok(());
+        }
+    };
+    
+    let signature = match Signature::try_from(signature_bytes.as_slice()) {
+        Ok(sig) => sig,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
+                Some(format!("Invalid signature format: {}", e)));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
             }
+            return Ok(());
         }
-    }
+    };
     
-    // No specific action requested
-    let response = json!({
-        "cmd": "cert",
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "status": "error",
-        "error": "No action specified. Use --show <cert_id>, --export-pdf <cert_id>, sign --file <file.json>, or verify --file <file.json> --pubkey <pubkey.pem>"
-    });
+    // Load public key from PEM file
+    let pubkey_pem = match fs::read_to_string(&pubkey_path) {
+        Ok(pem) => pem,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, None, Some(schema_valid),
+                Some(format!("Failed to read public key file: {}", e)));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
+            }
+            return Err(anyhow::anyhow!("Failed to read public key file: {}", e));
+        }
+    };
     
-    logger.log_json(&response);
-    println!("{}", serde_json::to_string_pretty(&response)?);
-    Err(anyhow::anyhow!("No action specified"))
-}
-
-fn handle_cert_sign(
-    cert_file_path: std::path::PathBuf,
-    sign_key_path: Option<std::path::PathBuf>,
-    force: bool,
-    logger: &Logger,
-) -> Result<()> {
-    use crate::signer::{load_private_key, sign_certificate};
-    use std::fs;
+    // Parse PEM and extract public key bytes
+    let pubkey_bytes = match parse_ed25519_public_key_pem(&pubkey_pem) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, None, Some(schema_valid),
+                Some(format!("Failed to parse public key PEM: {}", e)));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
+            }
+            return Err(anyhow::anyhow!("Failed to parse public key PEM: {}", e));
+        }
+    };
     
-    logger.log_info(&format!("Signing certificate file: {}", cert_file_path.display()));
+    let verifying_key = match VerifyingKey::from_bytes(&pubkey_bytes) {
+        Ok(key) => key,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, None, Some(schema_valid),
+                Some(format!("Invalid public key: {}", e)));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
+            }
+            return Err(anyhow::anyhow!("Invalid public key: {}", e));
+        }
+    };
     
-    if !cert_file_path.exists() {
-        let response = json!({
-            "op": "cert_sign",
-            "file": cert_file_path.display().to_string(),
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "signed": false,
-            "schema_valid": null,
-            "error": format!("Certificate file not found: {}", cert_file_path.display())
-        });
-        
-        logger.log_json(&response);
-        println!("{}", serde_json::to_string_pretty(&response)?);
-        return Err(anyhow::anyhow!("Certificate file not found: {}", cert_file_path.display()));
-    }
+    logger.log_json(&serde_json::json!({
+        "step": "pubkey_loaded",
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
     
-    let key_source = if sign_key_path.is_some() { "flag" } else { "env" };
+    // Remove signature for canonicalization
+    let mut unsigned_cert = cert_value.clone();
+    unsigned_cert.as_object_mut().unwrap().remove("signature");
     
-    // Read certificate file
-    let cert_json = fs::read_to_string(&cert_file_path)?;
-    let mut cert_value: serde_json::Value = serde_json::from_str(&cert_json)?;
+    // Canonicalize the unsigned certificate
+    let canonical_bytes = match canonicalize_json(&unsigned_cert) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
+                Some(format!("JSON canonicalization failed: {}", e)));
+            if let Some(errors) = schema_errors {
+                let mut response_obj = response.as_object().unwrap().clone();
+                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+            } else {
+                println!("{}", serde_json::to_string(&response)?);
+            }
+            return Ok(());
+        }
+    };
     
-    // For signing, we validate the unsigned certificate (without signature requirement)
-    // The full schema requires a signature, but for signing we validate the rest first
-    logger.log_info("Validating unsigned certificate structure before signing");
+    logger.log_json(&serde_json::json!({
+        "step": "canonicalization_complete",
+        "canonical_bytes": canonical_bytes.len(),
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
     
-    // We'll do a basic validation to ensure required fields are present except signature
-    // This is more lenient than full schema validation which requires signature
-    let required_fields = ["cert_type", "cert_id", "certificate_version", "created_at"];
-    for field in &required_fields {
-        if !cert_value.get(field).is_some() {
-            let response = json!({
-                "op": "cert_sign",
-                "file": cert_file_path.display().to_string(),
-                "key_source": key_source,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "signed": false,
-                "schema_valid": false,
-                "error": format!("Missing required field: {}", field)
-            });
-            
-            logger.log_json(&response);
-            println!("{}", serde_json::to_string_pretty(&response)?);
-            return Err(anyhow::anyhow!("Missing required field: {}", field));
-        }
+    // Verify signature
+    let is_valid = verifying_key.verify(&canonical_bytes, &signature).is_ok();
+    
+    logger.log_json(&serde_json::json!({
+        "step": "verification_complete",
+        "signature_valid": is_valid,
+        "schema_valid": schema_valid,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+    
+    // Output result
+    let mut response = create_verify_response(&cert_file_path, &pubkey_path, Some(is_valid), Some(schema_valid), None);
+    if cert_value.get("cert_type").and_then(|v| v.as_str()) == Some("system") {
+        // A system certificate is only as good as the drive certificates it
+        // aggregates; they are looked up next to it
+        let cert_dir = cert_file_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+        let chain = crate::system_cert::verify_chain(&cert_value, cert_dir, Some(&pubkey_bytes))?;
+        logger.log_json(&serde_json::json!({
+            "step": "chain_verified",
+            "chain_valid": chain.valid,
+            "components": chain.components.len(),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }));
+        response["chain"] = serde_json::to_value(&chain)?;
+    }
+    if let Some(errors) = schema_errors {
+        let mut response_obj = response.as_object().unwrap().clone();
+        response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
+        println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+    } else {
+        println!("{}", serde_json::to_string(&response)?);
     }
+
+    Ok(())
+}
+
+fn handle_cert_validate(
+    cert_file_path: std::path::PathBuf,
+    logger: &Logger,
+) -> Result<()> {
+    use crate::schema::CertificateValidator;
+    use std::fs;
     
-    logger.log_info("Certificate structure validation passed");
+    logger.log_info(&format!("Validating certificate schema: {}", cert_file_path.display()));
     
-    // Load private key
-    let signing_key = match load_private_key(sign_key_path) {
-        Ok(key) => {
-            logger.log_info("Private key loaded successfully");
-            key
-        }
+    if !cert_file_path.exists() {
+        let response = json!({
+            "op": "cert_validate",
+            "file": cert_file_path.display().to_string(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "schema_valid": null,
+            "error": format!("Certificate file not found: {}", cert_file_path.display())
+        });
+        
+        logger.log_json(&response);
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        return Err(anyhow::anyhow!("Certificate file not found: {}", cert_file_path.display()));
+    }
+    
+    // Read certificate file
+    let cert_json = match fs::read_to_string(&cert_file_path) {
+        Ok(json) => json,
         Err(e) => {
             let response = json!({
-                "op": "cert_sign",
+                "op": "cert_validate",
                 "file": cert_file_path.display().to_string(),
-                "key_source": key_source,
                 "timestamp": chrono::Utc::now().to_rfc3339(),
-                "signed": false,
-                "error": format!("Failed to load private key: {}", e)
+                "schema_valid": null,
+                "error": format!("Failed to read certificate file: {}", e)
             });
             
             logger.log_json(&response);
             println!("{}", serde_json::to_string_pretty(&response)?);
-            return Err(anyhow::anyhow!("Failed to load private key: {}", e));
+            return Err(anyhow::anyhow!("Failed to read certificate file: {}", e));
         }
     };
     
-    // Sign the certificate
-    match sign_certificate(&mut cert_value, &signing_key, force) {
-        Ok(()) => {
-            logger.log_info("Certificate signed successfully");
-            
-            // Write back to file atomically
-            let temp_file = cert_file_path.with_extension("tmp");
-            let signed_json = serde_json::to_string_pretty(&cert_value)?;
-            fs::write(&temp_file, &signed_json)?;
-            fs::rename(&temp_file, &cert_file_path)?;
-            
+    let cert_value: serde_json::Value = match serde_json::from_str(&cert_json) {
+        Ok(value) => value,
+        Err(e) => {
             let response = json!({
-                "op": "cert_sign",
+                "op": "cert_validate",
                 "file": cert_file_path.display().to_string(),
-                "key_source": key_source,
                 "timestamp": chrono::Utc::now().to_rfc3339(),
-                "signed": true
+                "schema_valid": false,
+                "error": format!("Invalid JSON in certificate file: {}", e)
             });
             
             logger.log_json(&response);
             println!("{}", serde_json::to_string_pretty(&response)?);
-            Ok(())
+            return Err(anyhow::anyhow!("Invalid JSON in certificate file: {}", e));
         }
+    };
+    
+    // Validate schema
+    let validator = CertificateValidator::default();
+    let validation_result = match validator.validate_certificate(&cert_value) {
+        Ok(result) => result,
         Err(e) => {
             let response = json!({
-                "op": "cert_sign",
+                "op": "cert_validate",
                 "file": cert_file_path.display().to_string(),
-                "key_source": key_source,
                 "timestamp": chrono::Utc::now().to_rfc3339(),
-                "signed": false,
-                "error": format!("Signing failed: {}", e)
+                "schema_valid": null,
+                "error": format!("Schema validation error: {}", e)
             });
             
             logger.log_json(&response);
             println!("{}", serde_json::to_string_pretty(&response)?);
-            Err(anyhow::anyhow!("Signing failed: {}", e))
+            return Err(anyhow::anyhow!("Schema validation error: {}", e));
         }
+    };
+    
+    // Create response
+    let mut response = json!({
+        "op": "cert_validate",
+        "file": cert_file_path.display().to_string(),
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "schema_valid": validation_result.valid,
+        "schema_type": validation_result.schema_id
+    });
+    
+    if !validation_result.valid {
+        response.as_object_mut().unwrap().insert(
+            "schema_errors".to_string(), 
+            serde_json::json!(validation_result.errors)
+        );
+    }
+    
+    logger.log_json(&response);
+    println!("{}", serde_json::to_string_pretty(&response)?);
+    
+    if validation_result.valid {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Certificate failed schema validation"))
     }
 }
 
-fn handle_cert_verify(
-    cert_file_path: std::path::PathBuf,
-    pubkey_path: std::path::PathBuf,
-    logger: &Logger,
-) -> Result<()> {
-    use crate::signer::canonicalize_json;
+#[cfg(feature = "operations")]
+pub fn handle_destroy(args: DestroyArgs, logger: &Logger) -> Result<()> {
+    match args.command {
+        DestroyCommands::Attest(attest) => handle_destroy_attest(attest, logger),
+    }
+}
+
+#[cfg(feature = "operations")]
+fn handle_destroy_attest(args: DestroyAttestArgs, logger: &Logger) -> Result<()> {
+    use crate::cert::{build_destroy_certificate_json, DestructionAttestation};
     use crate::schema::CertificateValidator;
-    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
-    use base64::{engine::general_purpose::STANDARD, Engine};
+    use crate::signer::load_private_key;
     use std::fs;
-    
-    logger.log_json(&serde_json::json!({
-        "step": "cert_verify_start",
-        "file": cert_file_path.display().to_string(),
-        "pubkey": pubkey_path.display().to_string(),
+
+    logger.log_info("Generating physical destruction certificate");
+
+    let attestation = DestructionAttestation {
+        device_path: args.device,
+        serial: args.serial,
+        model: args.model,
+        bus: args.bus,
+        capacity_bytes: args.capacity_bytes,
+        asset_tag: args.asset_tag,
+        method: args.method,
+        reason: args.reason,
+        performed_at: args.performed_at,
+        location: args.location,
+        operator: args.operator,
+        witness: args.witness,
+        equipment: args.equipment,
+        particle_size_mm: args.particle_size_mm,
+        notes: args.notes,
+        photos: args.photos,
+        backup_cert_id: args.backup_cert_id,
+        wipe_cert_id: args.wipe_cert_id,
+    };
+
+    let mut cert_value = build_destroy_certificate_json(&attestation).map_err(|e| {
+        let error_msg = format!("Failed to build destruction certificate: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    let serial = crate::serial::assign_serial(&mut cert_value).map_err(|e| {
+        let error_msg = format!("Failed to assign certificate serial number: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    // The schema requires a signature, so destruction certificates are always signed
+    let signing_key = load_private_key(args.sign_key_path)
+        .map_err(|e| anyhow::anyhow!("Failed to load signing key: {}", e))?;
+    crate::audit::sign_and_record(&mut cert_value, &signing_key, false)
+        .map_err(|e| anyhow::anyhow!("Failed to sign destruction certificate: {}", e))?;
+
+    let validation_result = CertificateValidator::default()
+        .validate_certificate(&cert_value)
+        .map_err(|e| anyhow::anyhow!("Schema validation error: {}", e))?;
+    if !validation_result.valid {
+        logger.log_error("Signed destruction certificate failed schema validation");
+        crate::events::warn(&format!("Signed certificate failed schema validation: {}", validation_result.errors.join("; ")));
+    }
+
+    let cert_dir = crate::config::Config::load()?.certificates_dir;
+    fs::create_dir_all(&cert_dir)?;
+    let cert_id = cert_value.get("cert_id").and_then(|v| v.as_str()).unwrap_or("destroy_cert").to_string();
+    let cert_file = cert_dir.join(format!("{}.json", cert_id));
+
+    crate::cleanup::write_atomic(&cert_file, serde_json::to_string_pretty(&cert_value)?)?;
+
+    logger.log_json(&json!({
+        "step": "destroy_certificate_saved",
+        "cert_id": cert_id,
+        "serial_number": serial,
+        "cert_path": cert_file.display().to_string(),
+        "method": cert_value["policy"]["method"],
+        "serial": cert_value["device"]["serial"],
+        "photos": attestation.photos.len(),
+        "schema_valid": validation_result.valid,
         "timestamp": chrono::Utc::now().to_rfc3339()
     }));
-    
-    // Check if certificate file exists
-    if !cert_file_path.exists() {
-        let response = serde_json::json!({
-            "op": "cert_verify",
-            "file": cert_file_path.display().to_string(),
-            "signature_valid": null,
-            "schema_valid": null,
-            "pubkey": pubkey_path.display().to_string(),
-            "error": "Certificate file not found"
-        });
-        println!("{}", serde_json::to_string(&response)?);
-        return Err(anyhow::anyhow!("Certificate file not found: {}", cert_file_path.display()));
+
+    with_inventory(logger, |inventory| inventory.record_certificate(&cert_value).map(|_| ()));
+
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&json!({
+            "cert_id": cert_id,
+            "serial_number": serial,
+            "cert_path": cert_file.display().to_string(),
+            "schema_valid": validation_result.valid,
+            "signed": true
+        }))?);
+    } else {
+        println!("Destruction certificate saved: {}", cert_file.display());
+        println!("Certificate No.: {}", serial);
+        println!("Certificate ID: {}", cert_id);
+        println!("Export a PDF with: securewipe cert --export-pdf {}", cert_id);
     }
-    
-    // Check if public key file exists
-    if !pubkey_path.exists() {
-        let response = serde_json::json!({
-            "op": "cert_verify",
-            "file": cert_file_path.display().to_string(),
-            "signature_valid": null,
-            "schema_valid": null,
-            "pubkey": pubkey_path.display().to_string(),
-            "error": "Public key file not found"
-        });
-        println!("{}", serde_json::to_string(&response)?);
-        return Err(anyhow::anyhow!("Public key file not found: {}", pubkey_path.display()));
+
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_inventory(args: InventoryArgs, logger: &Logger) -> Result<()> {
+    use crate::inventory::{export_csv, DeviceStatus, Inventory};
+
+    let inventory = Inventory::open_default().map_err(|e| {
+        let error_msg = format!("Failed to open inventory: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    match args.command {
+        InventoryCommands::List { status, format } => {
+            let status = match status {
+                Some(value) => Some(DeviceStatus::parse(&value).ok_or_else(|| {
+                    anyhow::anyhow!("Invalid status: {}. Must be discovered, wiped, wipe_failed or destroyed", value)
+                })?),
+                None => None,
+            };
+            let entries = inventory.list(status).map_err(|e| anyhow::anyhow!("Inventory query failed: {}", e))?;
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else {
+                for entry in &entries {
+                    println!(
+                        "{}{}  {}  {}  last seen {}  ({} certificate(s))",
+                        entry.serial,
+                        entry.alias.as_deref().map(|alias| format!(" [{}]", alias)).unwrap_or_default(),
+                        entry.model.as_deref().unwrap_or("unknown model"),
+                        entry.status.as_str(),
+                        entry.last_seen,
+                        entry.certificates.len()
+                    );
+                }
+                println!("{} drive(s)", entries.len());
+            }
+            Ok(())
+        }
+        InventoryCommands::Show { serial, format } => {
+            let entry = inventory.show(&serial).map_err(|e| anyhow::anyhow!("Inventory query failed: {}", e))?;
+            let entry = match entry {
+                Some(entry) => entry,
+                None => {
+                    if format == "json" {
+                        println!("{}", serde_json::to_string_pretty(&json!({
+                            "serial": serial,
+                            "status": "not_found"
+                        }))?);
+                    } else {
+                        println!("{} is not in the inventory", serial);
+                    }
+                    return Err(anyhow::anyhow!("No inventory record for serial {}", serial));
+                }
+            };
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&entry)?);
+            } else {
+                println!("Serial: {}", entry.serial);
+                if let Some(ref device_id) = entry.device_id {
+                    println!("Device ID: {}", device_id);
+                }
+                if let Some(ref alias) = entry.alias {
+                    println!("Alias: {}", alias);
+                }
+                if let Some(ref model) = entry.model {
+                    println!("Model: {}", model);
+                }
+                if let Some(capacity) = entry.capacity_bytes {
+                    println!("Capacity: {}", crate::locale::DisplayFormat::current().bytes_exact(capacity));
+                }
+                if let Some(ref path) = entry.last_path {
+                    println!("Last path: {}", path);
+                }
+                println!("First seen: {}", entry.first_seen);
+                println!("Last seen: {}", entry.last_seen);
+                println!("Status: {}", entry.status.as_str());
+                println!("Certificates:");
+                for cert in &entry.certificates {
+                    println!(
+                        "  - {} {} {} {} ({})",
+                        cert.cert_id,
+                        cert.cert_type,
+                        cert.policy.as_deref().unwrap_or("-"),
+                        cert.result.as_deref().unwrap_or("-"),
+                        cert.issued_at
+                    );
+                }
+            }
+            Ok(())
+        }
+        InventoryCommands::Alias { drive, name, clear: _ } => {
+            let device_id = inventory.set_alias(&drive, name.as_deref()).map_err(|e| {
+                let error_msg = format!("Failed to set alias: {}", e);
+                logger.log_error(&error_msg);
+                anyhow::anyhow!(error_msg)
+            })?;
+            logger.log_json(&json!({
+                "step": "device_alias_set",
+                "device_id": device_id,
+                "alias": name,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            Ok(())
+        }
+        InventoryCommands::Aliases { format } => {
+            let aliases = inventory.alias_map().map_err(|e| anyhow::anyhow!("Inventory query failed: {}", e))?;
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&aliases)?);
+            } else {
+                for (device_id, alias) in &aliases {
+                    println!("{}  {}", alias, device_id);
+                }
+            }
+            Ok(())
+        }
+        InventoryCommands::Export { format, output } => {
+            let entries = inventory.list(None).map_err(|e| anyhow::anyhow!("Inventory query failed: {}", e))?;
+            let content = match format.as_str() {
+                "json" => serde_json::to_string_pretty(&entries)?,
+                "csv" => export_csv(&entries),
+                _ => return Err(anyhow::anyhow!("Unsupported export format: {} (expected json or csv)", format)),
+            };
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, content)?;
+                    logger.log_json(&json!({
+                        "step": "inventory_exported",
+                        "format": format,
+                        "devices": entries.len(),
+                        "path": path.display().to_string(),
+                        "timestamp": chrono::Utc::now().to_rfc3339()
+                    }));
+                }
+                None => print!("{}", content),
+            }
+            Ok(())
+        }
     }
-    
-    // Read and parse certificate
-    let cert_json = match fs::read_to_string(&cert_file_path) {
-        Ok(json) => json,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, None, None,
-                Some(format!("Failed to read certificate file: {}", e)));
-            println!("{}", serde_json::to_string(&response)?);
-            return Err(anyhow::anyhow!("Failed to read certificate file: {}", e));
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_approval(args: ApprovalArgs, logger: &Logger) -> Result<()> {
+    use crate::approval::{sign_approval, ApprovalRequest, ApprovalSubject};
+    use crate::device::{DeviceDiscovery, LinuxDeviceDiscovery, RiskLevel};
+    use crate::wipe::{plan_wipe, WipePolicy};
+
+    match args.command {
+        ApprovalCommands::Request { device, policy, iso_mode, output } => {
+            let wipe_policy = match policy.as_str() {
+                "CLEAR" => WipePolicy::Clear,
+                "PURGE" => WipePolicy::Purge,
+                _ => return Err(anyhow::anyhow!("Invalid policy: {}. Must be CLEAR or PURGE", policy)),
+            };
+
+            let discovered = LinuxDeviceDiscovery::new()
+                .discover_devices()
+                .map_err(|e| anyhow::anyhow!("Device discovery failed: {}", e))?
+                .into_iter()
+                .find(|d| d.name == device)
+                .ok_or_else(|| anyhow::anyhow!("Device {} not found", device))?;
+
+            let is_critical = matches!(discovered.risk_level, RiskLevel::Critical);
+            let mut plan = plan_wipe(&device, Some(wipe_policy), is_critical, iso_mode, None, None);
+            crate::containers::block_plan(&mut plan, discovered.risk_explanation.as_ref());
+            if plan.blocked {
+                return Err(anyhow::anyhow!("Wipe plan is blocked: {}", plan.reason.unwrap_or_default()));
+            }
+
+            let operator = crate::approval::login_name();
+            let subject = ApprovalSubject::from_plan(&plan, discovered.serial.clone(), discovered.risk_level.clone());
+            let request = ApprovalRequest::new(subject, &operator)?;
+
+            logger.log_json(&json!({
+                "step": "approval_requested",
+                "device": device,
+                "risk": request.subject.risk,
+                "plan_sha256": request.plan_sha256,
+                "requested_by": operator,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            write_or_print(&serde_json::to_string_pretty(&request)?, output)
+        }
+        ApprovalCommands::Sign { request, approver, key, valid_hours, output } => {
+            let content = std::fs::read_to_string(&request)
+                .map_err(|e| anyhow::anyhow!("Cannot read approval request {}: {}", request.display(), e))?;
+            let request: ApprovalRequest = serde_json::from_str(&content)
+                .map_err(|e| anyhow::anyhow!("Invalid approval request: {}", e))?;
+
+            let signing_key = crate::signer::load_private_key(key)
+                .map_err(|e| anyhow::anyhow!("Failed to load approver key: {}", e))?;
+            let approval = sign_approval(&request, &approver, chrono::Duration::hours(valid_hours), &signing_key)?;
+            crate::audit::record_signature(&format!("approval:{}", approval.plan_sha256), &signing_key)
+                .map_err(|e| anyhow::anyhow!("Approval signed but could not be audited, so it was not written: {}", e))?;
+
+            logger.log_json(&json!({
+                "step": "approval_signed",
+                "device": approval.subject.device,
+                "plan_sha256": approval.plan_sha256,
+                "approver": approval.approver,
+                "expires_at": approval.expires_at,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            write_or_print(&serde_json::to_string_pretty(&approval)?, output)
+        }
+    }
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_schema(args: SchemaArgs, logger: &Logger) -> Result<()> {
+    use crate::contracts::{export_schemas, CONTRACT_VERSION};
+
+    match args.command {
+        SchemaCommands::Export { out } => {
+            let written = export_schemas(&out).map_err(|e| {
+                let error_msg = format!("Schema export failed: {}", e);
+                logger.log_error(&error_msg);
+                anyhow::anyhow!(error_msg)
+            })?;
+
+            let response = json!({
+                "cmd": "schema",
+                "action": "export",
+                "version": CONTRACT_VERSION,
+                "out": out.display().to_string(),
+                "files": written.iter().map(|path| path.display().to_string()).collect::<Vec<_>>(),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "status": "success"
+            });
+            logger.log_json(&response);
+            println!("{}", serde_json::to_string_pretty(&response)?);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_config(args: ConfigArgs, logger: &Logger) -> Result<()> {
+    match args.command {
+        ConfigCommands::Show { format } => {
+            let config = crate::config::Config::load().map_err(|e| {
+                let error_msg = format!("Failed to load configuration: {}", e);
+                logger.log_error(&error_msg);
+                anyhow::anyhow!(error_msg)
+            })?;
+            let entries = config.entries();
+
+            if format == "json" {
+                let settings: serde_json::Map<String, serde_json::Value> = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let source = config.sources.get(*key).cloned().unwrap_or(crate::config::ConfigSource::Default);
+                        (key.to_string(), json!({ "value": value, "source": source }))
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&json!({
+                    "cmd": "config",
+                    "action": "show",
+                    "settings": settings,
+                    "status": "success"
+                }))?);
+            } else {
+                for (key, value) in &entries {
+                    let source = config.sources.get(*key).cloned().unwrap_or(crate::config::ConfigSource::Default);
+                    println!("{:<18} {}  ({})", key, value, source);
+                }
+                if config.layout == crate::paths::LayoutKind::Legacy {
+                    println!();
+                    println!("Using ~/SecureWipe from an older release; `securewipe config migrate` moves it to the XDG data directory");
+                }
+            }
+            Ok(())
         }
+        ConfigCommands::Migrate { dry_run, format } => handle_config_migrate(dry_run, &format, logger),
+    }
+}
+
+#[cfg(feature = "operations")]
+fn handle_config_migrate(dry_run: bool, format: &str, logger: &Logger) -> Result<()> {
+    use crate::paths::{Layout, Migration};
+
+    let fail = |e: crate::paths::PathsError| {
+        let error_msg = format!("Migration failed: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
     };
-    
-    let cert_value: serde_json::Value = match serde_json::from_str(&cert_json) {
-        Ok(value) => value,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, None, None,
-                Some(format!("Invalid JSON in certificate file: {}", e)));
-            println!("{}", serde_json::to_string(&response)?);
-            return Err(anyhow::anyhow!("Invalid JSON in certificate file: {}", e));
+    let home = dirs::home_dir().ok_or_else(|| fail(crate::paths::PathsError::NoHome))?;
+    let migration = Layout::preferred().and_then(|to| Migration::plan(&home, to)).map_err(fail)?;
+    if !dry_run {
+        migration.apply().map_err(fail)?;
+        logger.log_info(&format!("Moved {} to {}", migration.from.display(), migration.to.data_dir.display()));
+    }
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&json!({
+            "cmd": "config",
+            "action": "migrate",
+            "dry_run": dry_run,
+            "migration": migration,
+            "status": "success"
+        }))?);
+    } else {
+        let verb = if dry_run { "Would move" } else { "Moved" };
+        println!("{} {} -> {}", verb, migration.from.display(), migration.to.data_dir.display());
+        if let Some(ref config_file) = migration.config_file {
+            println!("{} {} -> {}", verb, config_file.display(), migration.to.config_file.display());
         }
-    };
-    
-    logger.log_json(&serde_json::json!({
-        "step": "cert_loaded",
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    }));
-    
-    // Validate schema first
-    logger.log_info("Validating certificate schema");
-    let validator = CertificateValidator::default();
-    let validation_result = match validator.validate_certificate(&cert_value) {
-        Ok(result) => result,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, None, None,
-                Some(format!("Schema validation error: {}", e)));
-            println!("{}", serde_json::to_string(&response)?);
-            return Ok(());
+        if !dry_run {
+            println!("{} now links to the new directory", migration.from.display());
         }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_secrets(args: SecretsArgs, logger: &Logger) -> Result<()> {
+    use crate::secrets::{self, SecretKind};
+
+    let parse_kind = |name: &str| {
+        SecretKind::parse(name).ok_or_else(|| {
+            let names: Vec<&str> = SecretKind::ALL.iter().map(|kind| kind.name()).collect();
+            let error_msg = format!("Unknown secret: {}. Must be one of {}", name, names.join(", "));
+            logger.log_error(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })
     };
-    
-    let schema_valid = validation_result.valid;
-    let schema_errors = if !validation_result.valid {
-        Some(validation_result.errors)
-    } else {
-        None
+    // Nothing here can work without a keyring; point ISO users at the file fallback
+    let keyring_error = |kind: SecretKind, e: secrets::SecretError| {
+        let error_msg = match e {
+            secrets::SecretError::Unavailable(_) => format!(
+                "{}. Without a keyring (e.g. in ISO mode), put the passphrase in an owner-only file and set {} to its path",
+                e,
+                kind.file_env_var()
+            ),
+            e => e.to_string(),
+        };
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
     };
-    
-    // Check if signature exists
-    let signature_obj = match cert_value.get("signature") {
-        Some(sig) => sig,
-        None => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, None, Some(schema_valid), None);
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
-            } else {
-                println!("{}", serde_json::to_string(&response)?);
-            }
-            return Ok(());
+
+    match args.command {
+        SecretsCommands::Set { name, stdin } => {
+            let kind = parse_kind(&name)?;
+            let value = read_passphrase(kind, stdin)?;
+            secrets::store(kind, &value).map_err(|e| keyring_error(kind, e))?;
+            logger.log_json(&json!({
+                "step": "secret_stored",
+                "secret": kind.name(),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            println!("Stored {} in the OS keyring", kind.name());
         }
-    };
-    
-    // Validate signature structure and algorithm
-    let alg = match signature_obj.get("alg").and_then(|v| v.as_str()) {
-        Some(alg) => alg,
-        None => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
-                Some("Missing signature.alg field".to_string()));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+        SecretsCommands::Unset { name } => {
+            let kind = parse_kind(&name)?;
+            secrets::clear(kind).map_err(|e| keyring_error(kind, e))?;
+            logger.log_json(&json!({
+                "step": "secret_removed",
+                "secret": kind.name(),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            println!("Removed {} from the OS keyring", kind.name());
+        }
+        SecretsCommands::Status { format } => {
+            let statuses: Vec<(SecretKind, String)> = SecretKind::ALL
+                .into_iter()
+                .map(|kind| {
+                    let status = match secrets::resolve(kind) {
+                        Ok(Some(secret)) => format!("set ({})", secret.source),
+                        Ok(None) => match secrets::lookup(kind) {
+                            Err(e) => format!("not set ({})", e),
+                            Ok(_) => "not set".to_string(),
+                        },
+                        Err(e) => format!("unusable: {}", e),
+                    };
+                    (kind, status)
+                })
+                .collect();
+
+            if format == "json" {
+                let entries: serde_json::Map<String, serde_json::Value> = statuses
+                    .iter()
+                    .map(|(kind, status)| (kind.name().to_string(), json!(status)))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&json!({
+                    "cmd": "secrets",
+                    "action": "status",
+                    "secrets": entries,
+                    "status": "success"
+                }))?);
             } else {
-                println!("{}", serde_json::to_string(&response)?);
+                for (kind, status) in &statuses {
+                    println!("{:<20} {}", kind.name(), status);
+                }
             }
-            return Ok(());
         }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_cleanup(args: CleanupArgs, logger: &Logger) -> Result<()> {
+    use crate::cleanup::{orphans, remove, CleanupScope};
+
+    let config = crate::config::Config::load()?;
+    let scope = CleanupScope {
+        certificates_dir: config.certificates_dir,
+        data_dir: config.data_dir,
+        temp_dir: std::env::temp_dir(),
+        backup_destinations: args.backup_dests,
+        include_resumable: args.include_resumable,
+        min_age: std::time::Duration::from_secs(args.min_age * 60),
     };
-    
-    if alg != "Ed25519" {
-        let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
-            Some(format!("Unsupported algorithm: {}", alg)));
-        if let Some(errors) = schema_errors {
-            let mut response_obj = response.as_object().unwrap().clone();
-            response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-            println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
-        } else {
-            println!("{}", serde_json::to_string(&response)?);
+    let found = orphans(&scope);
+
+    let mut removed = Vec::new();
+    let mut failed = Vec::new();
+    if !args.dry_run {
+        for orphan in &found {
+            match remove(orphan) {
+                Ok(()) => removed.push(orphan.path.clone()),
+                Err(e) => {
+                    logger.log_json(&json!({
+                        "step": "cleanup_remove_failed",
+                        "path": orphan.path,
+                        "error": e.to_string(),
+                        "timestamp": chrono::Utc::now().to_rfc3339()
+                    }));
+                    failed.push(json!({ "path": orphan.path, "error": e.to_string() }));
+                }
+            }
         }
+    }
+
+    let response = json!({
+        "op": "cleanup",
+        "dry_run": args.dry_run,
+        "found": found,
+        "removed": removed,
+        "failed": failed,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+    logger.log_json(&response);
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&response)?);
         return Ok(());
     }
-    
-    let pubkey_id = match signature_obj.get("pubkey_id").and_then(|v| v.as_str()) {
-        Some(id) => id,
-        None => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
-                Some("Missing signature.pubkey_id field".to_string()));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
-            } else {
-                println!("{}", serde_json::to_string(&response)?);
-            }
-            return Ok(());
-        }
-    };
-    
-    if pubkey_id != "sih_root_v1" {
-        let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
-            Some(format!("Invalid pubkey_id: expected 'sih_root_v1', got '{}'", pubkey_id)));
-        if let Some(errors) = schema_errors {
-            let mut response_obj = response.as_object().unwrap().clone();
-            response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-            println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
-        } else {
-            println!("{}", serde_json::to_string(&response)?);
-        }
+    if found.is_empty() {
+        println!("Nothing to clean up");
         return Ok(());
     }
-    
-    let sig_b64 = match signature_obj.get("sig").and_then(|v| v.as_str()) {
-        Some(sig) => sig,
-        None => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
-                Some("Missing signature.sig field".to_string()));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
-            } else {
-                println!("{}", serde_json::to_string(&response)?);
-            }
-            return Ok(());
+    let verb = if args.dry_run { "Would remove" } else { "Removed" };
+    println!("{} {} of {} leftover(s):", verb, if args.dry_run { found.len() } else { removed.len() }, found.len());
+    for orphan in &found {
+        let state = if args.dry_run || removed.contains(&orphan.path) { "" } else { " (not removed)" };
+        println!("  {:?} {} ({} bytes){}", orphan.kind, orphan.path, orphan.bytes, state);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_identify(args: IdentifyArgs, logger: &Logger) -> Result<()> {
+    use crate::identify::{identify_device, IdentifyMethod};
+
+    let method = match args.method.as_str() {
+        "auto" => None,
+        "ledctl" => Some(IdentifyMethod::Ledctl),
+        "reads" => Some(IdentifyMethod::Reads),
+        other => {
+            let error_msg = format!("Invalid --method value: {}. Use auto, ledctl or reads", other);
+            logger.log_error(&error_msg);
+            return Err(anyhow::anyhow!(error_msg));
         }
     };
-    
-    logger.log_json(&serde_json::json!({
-        "step": "signature_validated",
-        "alg": alg,
-        "pubkey_id": pubkey_id,
-        "schema_valid": schema_valid,
+    if args.format != "json" {
+        println!("Blinking {} for {} seconds...", args.device, args.seconds.min(crate::identify::MAX_DURATION.as_secs()));
+    }
+    let report = identify_device(&args.device, std::time::Duration::from_secs(args.seconds), method).map_err(|e| {
+        logger.log_error(&e.to_string());
+        anyhow::anyhow!(e.to_string())
+    })?;
+
+    let response = json!({
+        "op": "identify",
+        "device": report.device,
+        "method": report.method,
+        "seconds": report.seconds,
+        "reads": report.reads,
         "timestamp": chrono::Utc::now().to_rfc3339()
-    }));
-    
-    // Decode signature
-    let signature_bytes = match STANDARD.decode(sig_b64) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
-                Some(format!("Invalid base64 signature: {}", e)));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
-            } else {
-                println!("{}", serde_json::to_string(&response)?);
+    });
+    logger.log_json(&response);
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+    } else {
+        let how = match report.method {
+            IdentifyMethod::Ledctl => "locate LED".to_string(),
+            IdentifyMethod::Reads => format!("activity LED, {} reads", report.reads),
+        };
+        println!("Done ({})", how);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_tasks(args: TasksArgs, logger: &Logger) -> Result<()> {
+    use crate::tasks::{reverifications, ReverifyOutcome, TaskStore, CERTIFICATE_REVERIFIED_EVENT};
+
+    let store = TaskStore::open_default()?;
+    let display = crate::locale::DisplayFormat::current();
+    match args.command {
+        TasksCommands::List { all, format } => {
+            let tasks: Vec<_> = store.tasks()?.into_iter().filter(|task| all || task.completed_at.is_none()).collect();
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&json!({ "tasks": tasks }))?);
+                return Ok(());
             }
-            return Ok(());
+            println!("Tasks: {}", tasks.len());
+            for task in &tasks {
+                let state = match (&task.completed_at, task.outcome) {
+                    (Some(at), Some(outcome)) => format!("ran {} ({})", display.timestamp(at), outcome.as_str()),
+                    _ => format!("due {}", display.timestamp(&task.due_at)),
+                };
+                println!("  {} re-verify {}: {}", task.id, task.cert_id, state);
+            }
+            Ok(())
         }
-    };
-    
-    let signature = match Signature::try_from(signature_bytes.as_slice()) {
-        Ok(sig) => sig,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
-                Some(format!("Invalid signature format: {}", e)));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
-            } else {
-                println!("{}", serde_json::to_string(&response)?);
+        TasksCommands::History { cert_id, format } => {
+            let cert_dir = crate::config::Config::load()?.certificates_dir;
+            crate::cert_id::find_certificate(&cert_dir, &cert_id)?;
+            let history = reverifications(&cert_dir, &cert_id).map_err(|e| {
+                let error_msg = format!("Failed to read re-verifications of {}: {}", cert_id, e);
+                logger.log_error(&error_msg);
+                anyhow::anyhow!(error_msg)
+            })?;
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&json!({ "cert_id": cert_id, "reverifications": history }))?);
+                return Ok(());
             }
-            return Ok(());
+            println!("Re-verifications of {}: {}", cert_id, history.len());
+            for entry in &history {
+                let samples = match entry.outcome {
+                    ReverifyOutcome::Passed | ReverifyOutcome::Failed => {
+                        format!(", {}/{} samples matched", entry.samples_matched, entry.samples_checked)
+                    }
+                    _ => String::new(),
+                };
+                println!("  {} {}{}", display.timestamp(&entry.at), entry.outcome.as_str(), samples);
+            }
+            Ok(())
         }
-    };
-    
-    // Load public key from PEM file
-    let pubkey_pem = match fs::read_to_string(&pubkey_path) {
-        Ok(pem) => pem,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, None, Some(schema_valid),
-                Some(format!("Failed to read public key file: {}", e)));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+        TasksCommands::Run { format } => {
+            let due = store.due(chrono::Utc::now())?;
+            let devices = if due.is_empty() {
+                Vec::new()
             } else {
-                println!("{}", serde_json::to_string(&response)?);
+                use crate::device::{DeviceDiscovery, LinuxDeviceDiscovery};
+                LinuxDeviceDiscovery::new().discover_devices().unwrap_or_else(|e| {
+                    crate::events::warn(&format!("Device discovery failed, treating every drive as unavailable: {}", e));
+                    Vec::new()
+                })
+            };
+            let cert_dir = crate::config::Config::load()?.certificates_dir;
+            let audit = crate::audit::AuditLog::open_default()?;
+            let operator = crate::audit::operator();
+
+            let mut results = Vec::new();
+            let mut errors = Vec::new();
+            for task in &due {
+                logger.log_info(&format!("Running task {}: re-verify {}", task.id, task.cert_id));
+                match store.run(&cert_dir, task, &devices) {
+                    Ok(result) => {
+                        audit.append(
+                            CERTIFICATE_REVERIFIED_EVENT,
+                            &operator,
+                            json!({
+                                "cert_id": result.cert_id,
+                                "task_id": result.task_id,
+                                "outcome": result.outcome,
+                                "device": result.device,
+                                "certificate_sha256": result.certificate_sha256
+                            }),
+                        )?;
+                        logger.log_json(&json!({
+                            "step": "certificate_reverified",
+                            "cert_id": result.cert_id,
+                            "task_id": result.task_id,
+                            "outcome": result.outcome,
+                            "device": result.device,
+                            "samples_checked": result.samples_checked,
+                            "samples_matched": result.samples_matched,
+                            "timestamp": chrono::Utc::now().to_rfc3339()
+                        }));
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        let error_msg = format!("Task {} ({}) failed: {}", task.id, task.cert_id, e);
+                        logger.log_error(&error_msg);
+                        errors.push(error_msg);
+                    }
+                }
             }
-            return Err(anyhow::anyhow!("Failed to read public key file: {}", e));
-        }
-    };
-    
-    // Parse PEM and extract public key bytes
-    let pubkey_bytes = match parse_ed25519_public_key_pem(&pubkey_pem) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, None, Some(schema_valid),
-                Some(format!("Failed to parse public key PEM: {}", e)));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&json!({ "ran": results, "errors": errors }))?);
+            } else {
+                println!("Tasks due: {}", due.len());
+                for result in &results {
+                    let on = result.device.as_deref().map(|device| format!(" on {}", device)).unwrap_or_default();
+                    match result.outcome {
+                        ReverifyOutcome::Passed | ReverifyOutcome::Failed => println!(
+                            "  {}: {}{}, {}/{} samples matched",
+                            result.cert_id,
+                            result.outcome.as_str(),
+                            on,
+                            result.samples_matched,
+                            result.samples_checked
+                        ),
+                        _ => println!("  {}: {}{}", result.cert_id, result.outcome.as_str(), on),
+                    }
+                    if let Some(detail) = &result.detail {
+                        println!("    {}", detail);
+                    }
+                }
+                for error in &errors {
+                    println!("  {}", error);
+                }
+            }
+
+            let failed = results.iter().filter(|result| result.outcome == ReverifyOutcome::Failed).count();
+            if failed > 0 {
+                Err(anyhow::anyhow!("{} re-verification(s) failed: wiped data no longer reads back as recorded", failed))
+            } else if !errors.is_empty() {
+                Err(anyhow::anyhow!("{} task(s) could not run", errors.len()))
             } else {
-                println!("{}", serde_json::to_string(&response)?);
+                Ok(())
             }
-            return Err(anyhow::anyhow!("Failed to parse public key PEM: {}", e));
         }
-    };
-    
-    let verifying_key = match VerifyingKey::from_bytes(&pubkey_bytes) {
-        Ok(key) => key,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, None, Some(schema_valid),
-                Some(format!("Invalid public key: {}", e)));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+    }
+}
+
+#[cfg(feature = "operations")]
+pub fn handle_keys(args: KeysArgs, logger: &Logger) -> Result<()> {
+    use crate::audit::{key_usage, AuditLog};
+
+    match args.command {
+        KeysCommands::UsageReport { since, until, format } => {
+            let since = report_bound("since", since, logger)?;
+            let until = report_bound("until", until, logger)?;
+
+            let log = AuditLog::open_default()?;
+            let chain = log.verify()?;
+            let usage = key_usage(&log.entries()?, since, until);
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&json!({
+                    "cmd": "keys",
+                    "action": "usage_report",
+                    "audit_log": log.path().display().to_string(),
+                    "since": since.map(|at| at.to_rfc3339()),
+                    "until": until.map(|at| at.to_rfc3339()),
+                    "chain": chain,
+                    "keys": usage,
+                    "status": if chain.intact { "success" } else { "tampered" }
+                }))?);
             } else {
-                println!("{}", serde_json::to_string(&response)?);
+                println!("Audit log: {} ({} entries)", log.path().display(), chain.entries);
+                match (&chain.broken_at, &chain.reason) {
+                    (Some(seq), Some(reason)) => println!("Hash chain: BROKEN at entry {} ({})", seq, reason),
+                    _ => println!("Hash chain: intact"),
+                }
+                if usage.is_empty() {
+                    println!("No signatures in this period");
+                }
+                for key in &usage {
+                    println!();
+                    println!("Key {}", key.key_id);
+                    println!("  Signatures: {}", key.signatures);
+                    println!("  First used: {}", key.first_used);
+                    println!("  Last used:  {}", key.last_used);
+                    println!("  Operators:  {}", key.operators.join(", "));
+                }
             }
-            return Err(anyhow::anyhow!("Invalid public key: {}", e));
-        }
-    };
-    
-    logger.log_json(&serde_json::json!({
-        "step": "pubkey_loaded",
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    }));
-    
-    // Remove signature for canonicalization
-    let mut unsigned_cert = cert_value.clone();
-    unsigned_cert.as_object_mut().unwrap().remove("signature");
-    
-    // Canonicalize the unsigned certificate
-    let canonical_bytes = match canonicalize_json(&unsigned_cert) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            let response = create_verify_response(&cert_file_path, &pubkey_path, Some(false), Some(schema_valid),
-                Some(format!("JSON canonicalization failed: {}", e)));
-            if let Some(errors) = schema_errors {
-                let mut response_obj = response.as_object().unwrap().clone();
-                response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-                println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
+
+            if chain.intact {
+                Ok(())
             } else {
-                println!("{}", serde_json::to_string(&response)?);
+                let error_msg = format!("Audit log hash chain is broken at entry {}", chain.broken_at.unwrap_or_default());
+                logger.log_error(&error_msg);
+                Err(anyhow::anyhow!(error_msg))
             }
-            return Ok(());
+        }
+    }
+}
+
+/// The --since/--until value of a report, if given
+#[cfg(feature = "operations")]
+fn report_bound(flag: &str, value: Option<String>, logger: &Logger) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+    value
+        .map(|value| {
+            parse_report_date(&value).ok_or_else(|| {
+                let error_msg = format!("Invalid --{} value: {}. Use YYYY-MM-DD or RFC 3339", flag, value);
+                logger.log_error(&error_msg);
+                anyhow::anyhow!(error_msg)
+            })
+        })
+        .transpose()
+}
+
+/// A report boundary: a bare date means midnight UTC
+#[cfg(feature = "operations")]
+fn parse_report_date(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(at.with_timezone(&chrono::Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|at| at.and_utc())
+}
+
+/// Read a new passphrase: prompted twice with echo off on a terminal, otherwise (or
+/// with --stdin) one line from stdin
+#[cfg(feature = "operations")]
+fn read_passphrase(kind: crate::secrets::SecretKind, from_stdin: bool) -> Result<zeroize::Zeroizing<String>> {
+    use crate::secrets::prompt;
+    use std::io::BufRead;
+    use zeroize::Zeroizing;
+
+    let prompted = if from_stdin { None } else { prompt(&format!("New {}", kind.name()))? };
+    let value = match prompted {
+        Some(first) => {
+            let second = prompt(&format!("Repeat {}", kind.name()))?;
+            if second.as_ref().map(|second| second.expose()) != Some(first.expose()) {
+                return Err(anyhow::anyhow!("Passphrases did not match"));
+            }
+            Zeroizing::new(first.expose().to_string())
+        }
+        None => {
+            let mut line = Zeroizing::new(String::new());
+            std::io::stdin().lock().read_line(&mut line)?;
+            Zeroizing::new(line.trim_end_matches(['\n', '\r']).to_string())
         }
     };
-    
-    logger.log_json(&serde_json::json!({
-        "step": "canonicalization_complete",
-        "canonical_bytes": canonical_bytes.len(),
+
+    if value.is_empty() {
+        return Err(anyhow::anyhow!("Refusing to store an empty {}", kind.name()));
+    }
+    Ok(value)
+}
+
+#[cfg(feature = "operations")]
+fn write_or_print(content: &str, output: Option<std::path::PathBuf>) -> Result<()> {
+    match output {
+        Some(path) => std::fs::write(&path, content)?,
+        None => println!("{}", content),
+    }
+    Ok(())
+}
+
+/// Load the approval passed with --approval and check it against the plan about to run
+#[cfg(feature = "operations")]
+fn verify_wipe_approval(
+    args: &WipeArgs,
+    plan: &crate::wipe::WipePlan,
+    serial: Option<String>,
+    risk: crate::device::RiskLevel,
+    logger: &Logger,
+) -> Result<crate::approval::Approval> {
+    use crate::approval::{verify_approval, Approval, ApprovalSubject, Roles};
+
+    let approval_path = args.approval.as_ref().ok_or_else(|| {
+        let error_msg = format!(
+            "{:?} device {} requires a second-person approval: run `securewipe approval request`, have an approver sign it, then pass --approval <file>",
+            risk, args.device
+        );
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })?;
+
+    let content = std::fs::read_to_string(approval_path)
+        .map_err(|e| anyhow::anyhow!("Cannot read approval {}: {}", approval_path.display(), e))?;
+    let approval: Approval = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid approval {}: {}", approval_path.display(), e))?;
+
+    let roles = Roles::load_default()?;
+    let operator = crate::approval::login_name();
+    let subject = ApprovalSubject::from_plan(plan, serial, risk);
+
+    if let Err(e) = verify_approval(&approval, &subject, &roles, &operator) {
+        let error_msg = format!("Wipe approval rejected: {}", e);
+        logger.log_error(&error_msg);
+        return Err(anyhow::anyhow!(error_msg));
+    }
+
+    logger.log_json(&json!({
+        "step": "approval_verified",
+        "device": args.device,
+        "plan_sha256": approval.plan_sha256,
+        "approver": approval.approver,
+        "operator": operator,
         "timestamp": chrono::Utc::now().to_rfc3339()
     }));
-    
-    // Verify signature
-    let is_valid = verifying_key.verify(&canonical_bytes, &signature).is_ok();
-    
-    logger.log_json(&serde_json::json!({
-        "step": "verification_complete",
-        "signature_valid": is_valid,
-        "schema_valid": schema_valid,
+    Ok(approval)
+}
+
+/// Compare the plan saved in `path` with the one just computed; returns its hash
+#[cfg(feature = "operations")]
+fn check_approved_plan_file(path: &std::path::Path, plan: &crate::wipe::WipePlan, logger: &Logger) -> Result<String> {
+    let approved: crate::wipe::WipePlan = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+        .map_err(|e| {
+            let error_msg = format!("Cannot read approved plan {}: {}", path.display(), e);
+            logger.log_error(&error_msg);
+            anyhow::anyhow!(error_msg)
+        })?;
+
+    let outcome = crate::approval::check_approved_plan(&approved, plan);
+    logger.log_json(&json!({
+        "step": "plan_approval_check",
+        "device": plan.device,
+        "approved_plan": path,
+        "plan_sha256": crate::approval::wipe_plan_hash(plan).ok(),
+        "matched": outcome.is_ok(),
         "timestamp": chrono::Utc::now().to_rfc3339()
     }));
-    
-    // Output result
-    let response = create_verify_response(&cert_file_path, &pubkey_path, Some(is_valid), Some(schema_valid), None);
-    if let Some(errors) = schema_errors {
-        let mut response_obj = response.as_object().unwrap().clone();
-        response_obj.insert("schema_errors".to_string(), serde_json::json!(errors));
-        println!("{}", serde_json::to_string(&serde_json::Value::Object(response_obj))?);
-    } else {
-        println!("{}", serde_json::to_string(&response)?);
-    }
-    
-    Ok(())
+    outcome.map_err(|e| {
+        let error_msg = format!("Wipe refused: {}", e);
+        logger.log_error(&error_msg);
+        anyhow::anyhow!(error_msg)
+    })
 }
 
-fn handle_cert_validate(
-    cert_file_path: std::path::PathBuf,
+/// Look up an earlier PASS wipe of `serial` inside the window. An unreadable
+/// inventory is treated as "no duplicate" rather than blocking the wipe.
+#[cfg(feature = "operations")]
+fn find_duplicate_wipe(
+    serial: &str,
+    policy: &str,
+    window_days: u32,
     logger: &Logger,
-) -> Result<()> {
-    use crate::schema::CertificateValidator;
-    use std::fs;
-    
-    logger.log_info(&format!("Validating certificate schema: {}", cert_file_path.display()));
-    
-    if !cert_file_path.exists() {
-        let response = json!({
-            "op": "cert_validate",
-            "file": cert_file_path.display().to_string(),
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "schema_valid": null,
-            "error": format!("Certificate file not found: {}", cert_file_path.display())
-        });
-        
-        logger.log_json(&response);
-        println!("{}", serde_json::to_string_pretty(&response)?);
-        return Err(anyhow::anyhow!("Certificate file not found: {}", cert_file_path.display()));
-    }
-    
-    // Read certificate file
-    let cert_json = match fs::read_to_string(&cert_file_path) {
-        Ok(json) => json,
-        Err(e) => {
-            let response = json!({
-                "op": "cert_validate",
-                "file": cert_file_path.display().to_string(),
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "schema_valid": null,
-                "error": format!("Failed to read certificate file: {}", e)
-            });
-            
-            logger.log_json(&response);
-            println!("{}", serde_json::to_string_pretty(&response)?);
-            return Err(anyhow::anyhow!("Failed to read certificate file: {}", e));
-        }
-    };
-    
-    let cert_value: serde_json::Value = match serde_json::from_str(&cert_json) {
-        Ok(value) => value,
+) -> Option<crate::inventory::CertificateRecord> {
+    let since = chrono::Utc::now() - chrono::Duration::days(window_days as i64);
+    match crate::inventory::Inventory::open_default().and_then(|inventory| inventory.recent_pass_wipe(serial, policy, since)) {
+        Ok(found) => found,
         Err(e) => {
-            let response = json!({
-                "op": "cert_validate",
-                "file": cert_file_path.display().to_string(),
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "schema_valid": false,
-                "error": format!("Invalid JSON in certificate file: {}", e)
-            });
-            
-            logger.log_json(&response);
-            println!("{}", serde_json::to_string_pretty(&response)?);
-            return Err(anyhow::anyhow!("Invalid JSON in certificate file: {}", e));
-        }
-    };
-    
-    // Validate schema
-    let validator = CertificateValidator::default();
-    let validation_result = match validator.validate_certificate(&cert_value) {
-        Ok(result) => result,
-        Err(e) => {
-            let response = json!({
-                "op": "cert_validate",
-                "file": cert_file_path.display().to_string(),
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-                "schema_valid": null,
-                "error": format!("Schema validation error: {}", e)
-            });
-            
-            logger.log_json(&response);
-            println!("{}", serde_json::to_string_pretty(&response)?);
-            return Err(anyhow::anyhow!("Schema validation error: {}", e));
+            logger.log_error(&format!("Duplicate wipe check skipped: {}", e));
+            None
         }
-    };
-    
-    // Create response
-    let mut response = json!({
-        "op": "cert_validate",
-        "file": cert_file_path.display().to_string(),
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "schema_valid": validation_result.valid,
-        "schema_type": validation_result.schema_id
-    });
-    
-    if !validation_result.valid {
-        response.as_object_mut().unwrap().insert(
-            "schema_errors".to_string(), 
-            serde_json::json!(validation_result.errors)
-        );
     }
-    
-    logger.log_json(&response);
-    println!("{}", serde_json::to_string_pretty(&response)?);
-    
-    if validation_result.valid {
-        Ok(())
-    } else {
-        Err(anyhow::anyhow!("Certificate failed schema validation"))
+}
+
+/// Inventory updates are best-effort: a locked or unwritable database must
+/// never block discovery, a wipe or certificate issuance
+fn with_inventory<F>(logger: &Logger, update: F)
+where
+    F: FnOnce(&crate::inventory::Inventory) -> std::result::Result<(), Box<dyn std::error::Error>>,
+{
+    let outcome = crate::inventory::Inventory::open_default().and_then(|inventory| update(&inventory));
+    if let Err(e) = outcome {
+        logger.log_error(&format!("Inventory update failed: {}", e));
+    }
+}
+
+/// Telemetry is opt-in and best-effort: a bad config or failed upload is logged but
+/// never fails the operation. Every record sent is shown to the user.
+#[cfg(feature = "operations")]
+fn record_telemetry(logger: &Logger, record: &crate::telemetry::TelemetryRecord) {
+    let outcome = crate::telemetry::TelemetryConfig::load_default().and_then(|config| config.record(record));
+    match outcome {
+        Ok(destinations) if destinations.is_empty() => {}
+        Ok(destinations) => {
+            logger.log_json(&json!({
+                "step": "telemetry_recorded",
+                "record": record,
+                "destinations": destinations,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            println!("Anonymized telemetry recorded to {}: {}", destinations.join(", "), json!(record));
+        }
+        Err(e) => logger.log_error(&format!("Telemetry not recorded: {}", e)),
     }
 }
 
 /// Parse Ed25519 public key from PEM format
 fn parse_ed25519_public_key_pem(pem_content: &str) -> Result<[u8; 32]> {
-    // Check for proper Ed25519 PUBLIC KEY PEM headers
-    if !pem_content.contains("-----BEGIN PUBLIC KEY-----") {
-        return Err(anyhow::anyhow!("Invalid PEM format. Expected '-----BEGIN PUBLIC KEY-----' for Ed25519 public key. Provide an Ed25519 PUBLIC KEY PEM."));
-    }
-    
-    if !pem_content.contains("-----END PUBLIC KEY-----") {
-        return Err(anyhow::anyhow!("Invalid PEM format. Missing '-----END PUBLIC KEY-----' footer. Provide an Ed25519 PUBLIC KEY PEM."));
-    }
-    
-    // Extract base64 content between headers
-    let lines: Vec<&str> = pem_content.lines().collect();
-    let start_idx = lines.iter().position(|&line| line.contains("BEGIN PUBLIC KEY"))
-        .ok_or_else(|| anyhow::anyhow!("No PEM begin marker found"))?;
-    let end_idx = lines.iter().position(|&line| line.contains("END PUBLIC KEY"))
-        .ok_or_else(|| anyhow::anyhow!("No PEM end marker found"))?;
-    
-    if start_idx >= end_idx {
-        return Err(anyhow::anyhow!("Invalid PEM structure"));
-    }
-    
-    let base64_lines = &lines[start_idx + 1..end_idx];
-    let base64_content = base64_lines.join("");
-    
-    use base64::{engine::general_purpose::STANDARD, Engine};
-    let der_bytes = STANDARD.decode(&base64_content)
-        .map_err(|e| anyhow::anyhow!("Failed to decode PEM base64: {}", e))?;
-    
-    // For Ed25519 public keys, the DER format has the key at the end
-    if der_bytes.len() < 32 {
-        return Err(anyhow::anyhow!("Invalid Ed25519 public key DER: too short ({} bytes). Provide an Ed25519 PUBLIC KEY PEM.", der_bytes.len()));
-    }
-    
-    let key_start = der_bytes.len() - 32;
-    let mut key_bytes = [0u8; 32];
-    key_bytes.copy_from_slice(&der_bytes[key_start..]);
-    
-    Ok(key_bytes)
+    crate::signer::parse_public_key_pem(pem_content)
+        .map_err(|e| anyhow::anyhow!("{}", e))
 }
 
 /// Helper function to create consistent verify response JSON
@@ -1400,7 +5152,7 @@ fn create_verify_response(
     response
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "operations"))]
 mod tests {
     use super::*;
     use crate::logging::Logger;
@@ -1410,6 +5162,10 @@ mod tests {
         let args = DiscoverArgs {
             format: "json".to_string(),
             no_enrich: false,
+            explain_risk: false,
+            removable_targets: false,
+            phones: false,
+            host: false,
         };
         assert_eq!(args.format, "json");
     }
@@ -1417,20 +5173,50 @@ mod tests {
     #[test]
     fn test_backup_args_creation() {
         let args = BackupArgs {
-            device: "/dev/sda".to_string(),
-            dest: "/mnt/backup".to_string(),
+            device: Some("/dev/sda".to_string()),
+            dest: Some("/mnt/backup".to_string()),
             paths: vec!["Documents".to_string(), "Pictures".to_string()],
+            exclude: Vec::new(),
+            dry_run: false,
+            note: None,
             sign: false,
             sign_key_path: None,
             force: false,
+            on_error: "abort".to_string(),
+            auto_mounted: None,
+            resume: None,
+            source: None,
+            space_wait: 1800,
+            resources: ResourceArgs::default(),
+            command: None,
         };
-        assert_eq!(args.device, "/dev/sda");
-        assert_eq!(args.dest, "/mnt/backup");
+        assert_eq!(args.device.as_deref(), Some("/dev/sda"));
+        assert_eq!(args.dest.as_deref(), Some("/mnt/backup"));
         assert_eq!(args.paths.len(), 2);
         assert!(!args.sign);
         assert!(!args.force);
     }
 
+    #[test]
+    fn test_resource_args_parsing() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct ResourceCli {
+            #[command(flatten)]
+            resources: ResourceArgs,
+        }
+
+        let cli = ResourceCli::try_parse_from(["securewipe", "--nice", "-5", "--ionice", "best-effort:7", "--io-weight", "50"]).unwrap();
+        assert_eq!(cli.resources.nice, Some(-5));
+        assert_eq!(cli.resources.ionice, Some(crate::priority::IoClass::BestEffort(7)));
+        assert_eq!(cli.resources.io_weight, Some(50));
+
+        assert!(ResourceCli::try_parse_from(["securewipe", "--nice", "20"]).is_err());
+        assert!(ResourceCli::try_parse_from(["securewipe", "--ionice", "realtime"]).is_err());
+        assert!(ResourceCli::try_parse_from(["securewipe", "--io-weight", "0"]).is_err());
+    }
+
     #[test]
     fn test_wipe_args_defaults() {
         let args = WipeArgs {
@@ -1444,6 +5230,19 @@ mod tests {
             force: false,
             danger_allow_wipe: false,
             backup_cert_id: None,
+            cleanup_only: false,
+            verify_seed: None,
+            duplicate_window_days: 30,
+            on_duplicate: "warn".to_string(),
+            verify_mode: "heuristic".to_string(),
+            overwrite_profile: None,
+            approval: None,
+            approve_plan: None,
+            virtual_media: false,
+            allow_network_device: false,
+            hardened: false,
+            note: None,
+            resources: ResourceArgs::default(),
         };
         assert_eq!(args.policy, "PURGE");
         assert!(!args.iso_mode);
@@ -1458,6 +5257,10 @@ mod tests {
         let args = CertArgs {
             show: Some("cert_123".to_string()),
             export_pdf: None,
+            pdf_detail: "full".to_string(),
+            sign_key_path: None,
+            export_text: None,
+            text_format: "plain".to_string(),
             command: None,
         };
         assert_eq!(args.show, Some("cert_123".to_string()));
@@ -1471,6 +5274,10 @@ mod tests {
         let args = DiscoverArgs {
             format: "json".to_string(),
             no_enrich: false,
+            explain_risk: false,
+            removable_targets: false,
+            phones: false,
+            host: false,
         };
         
         let result = handle_discover(args, &logger);
@@ -1497,12 +5304,22 @@ mod tests {
     fn test_handle_backup() {
         let logger = Logger::new();
         let args = BackupArgs {
-            device: "/dev/sda".to_string(),
-            dest: "/mnt/backup".to_string(),
+            device: Some("/dev/sda".to_string()),
+            dest: Some("/mnt/backup".to_string()),
             paths: vec!["Documents".to_string()],
+            exclude: Vec::new(),
+            dry_run: false,
+            note: None,
             sign: false,
             sign_key_path: None,
             force: false,
+            on_error: "abort".to_string(),
+            auto_mounted: None,
+            resume: None,
+            source: None,
+            space_wait: 1800,
+            resources: ResourceArgs::default(),
+            command: None,
         };
         
         let result = handle_backup(args, &logger);
@@ -1540,6 +5357,19 @@ mod tests {
             force: false,
             danger_allow_wipe: false,
             backup_cert_id: None,
+            cleanup_only: false,
+            verify_seed: None,
+            duplicate_window_days: 30,
+            on_duplicate: "warn".to_string(),
+            verify_mode: "heuristic".to_string(),
+            overwrite_profile: None,
+            approval: None,
+            approve_plan: None,
+            virtual_media: false,
+            allow_network_device: false,
+            hardened: false,
+            note: None,
+            resources: ResourceArgs::default(),
         };
         
         let result = handle_wipe(args, &logger);
@@ -1552,6 +5382,10 @@ mod tests {
         let args = CertArgs {
             show: Some("cert_123".to_string()),
             export_pdf: None,
+            pdf_detail: "full".to_string(),
+            sign_key_path: None,
+            export_text: None,
+            text_format: "plain".to_string(),
             command: None,
         };
         
@@ -1565,6 +5399,10 @@ mod tests {
         let args = DiscoverArgs {
             format: "json".to_string(),
             no_enrich: false,
+            explain_risk: false,
+            removable_targets: false,
+            phones: false,
+            host: false,
         };
         
         // This test verifies the JSON structure without printing
@@ -1608,12 +5446,22 @@ mod tests {
     #[test]
     fn test_backup_signing_flags() {
         let args = BackupArgs {
-            device: "/dev/sda".to_string(),
-            dest: "/mnt/backup".to_string(),
+            device: Some("/dev/sda".to_string()),
+            dest: Some("/mnt/backup".to_string()),
             paths: vec!["Documents".to_string()],
+            exclude: Vec::new(),
+            dry_run: false,
+            note: None,
             sign: true,
             sign_key_path: Some(std::path::PathBuf::from("/tmp/key")),
             force: true,
+            on_error: "skip".to_string(),
+            auto_mounted: None,
+            resume: None,
+            source: None,
+            space_wait: 1800,
+            resources: ResourceArgs::default(),
+            command: None,
         };
         
         assert!(args.sign);
@@ -1634,6 +5482,19 @@ mod tests {
             force: true,
             danger_allow_wipe: false,
             backup_cert_id: None,
+            cleanup_only: false,
+            verify_seed: None,
+            duplicate_window_days: 30,
+            on_duplicate: "warn".to_string(),
+            verify_mode: "heuristic".to_string(),
+            overwrite_profile: None,
+            approval: None,
+            approve_plan: None,
+            virtual_media: false,
+            allow_network_device: false,
+            hardened: false,
+            note: None,
+            resources: ResourceArgs::default(),
         };
         
         assert!(args.sign);
@@ -1644,16 +5505,25 @@ mod tests {
     #[test]
     fn test_cert_verify_args() {
         let verify_command = CertCommands::Verify {
-            file: std::path::PathBuf::from("/tmp/test_cert.json"),
+            file: Some(std::path::PathBuf::from("/tmp/test_cert.json")),
+            pdf: None,
             pubkey: std::path::PathBuf::from("keys/dev_public.pem"),
         };
         
         match verify_command {
-            CertCommands::Verify { file, pubkey } => {
-                assert_eq!(file, std::path::PathBuf::from("/tmp/test_cert.json"));
+            CertCommands::Verify { file, pdf, pubkey } => {
+                assert_eq!(file, Some(std::path::PathBuf::from("/tmp/test_cert.json")));
+                assert!(pdf.is_none());
                 assert_eq!(pubkey, std::path::PathBuf::from("keys/dev_public.pem"));
             }
             _ => panic!("Expected Verify command"),
         }
     }
+
+    #[test]
+    fn test_parse_report_date() {
+        assert_eq!(parse_report_date("2025-03-01").unwrap().to_rfc3339(), "2025-03-01T00:00:00+00:00");
+        assert_eq!(parse_report_date("2025-03-01T10:00:00+02:00").unwrap().to_rfc3339(), "2025-03-01T08:00:00+00:00");
+        assert!(parse_report_date("March 1").is_none());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,321 @@
+//! Effective settings, layered lowest to highest: built-in defaults, the system file
+//! (`/etc/securewipe/config.json`), the user file (`$XDG_CONFIG_HOME/securewipe/config.json`,
+//! `~/SecureWipe/config.json` before migration, or `SECUREWIPE_CONFIG`), environment
+//! variables (including `.env`), then CLI flags. The default paths come from
+//! `paths::Layout`. `securewipe config show` prints the result and where each value
+//! came from.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::paths::{Layout, LayoutKind};
+
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/securewipe/config.json";
+
+/// The global CLI flags for this run, installed once by main
+static CLI_LAYER: OnceLock<ConfigLayer> = OnceLock::new();
+
+/// Make `layer` the highest-precedence layer for every later `Config::load`
+pub fn set_cli_overrides(layer: ConfigLayer) {
+    let _ = CLI_LAYER.set(layer);
+}
+
+/// Errors raised while reading a config file
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Config file error: {0}")]
+    File(String),
+    #[error("Cannot determine home directory")]
+    NoHome,
+}
+
+/// Where an effective setting came from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "layer", rename_all = "snake_case")]
+pub enum ConfigSource {
+    Default,
+    SystemFile { path: PathBuf },
+    UserFile { path: PathBuf },
+    Env { var: String },
+    Cli { flag: String },
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::SystemFile { path } => write!(f, "system file {}", path.display()),
+            ConfigSource::UserFile { path } => write!(f, "user file {}", path.display()),
+            ConfigSource::Env { var } => write!(f, "env {}", var),
+            ConfigSource::Cli { flag } => write!(f, "flag {}", flag),
+        }
+    }
+}
+
+/// The settings one layer provides; unset fields leave lower layers in place.
+/// `danger` is deliberately absent: destructive mode is only ever armed per run,
+/// through SECUREWIPE_DANGER.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigLayer {
+    /// Base for every default path below; the layout's data directory when unset
+    pub data_dir: Option<PathBuf>,
+    pub certificates_dir: Option<PathBuf>,
+    /// Ed25519 PKCS#8 PEM used to sign certificates
+    pub sign_key_path: Option<PathBuf>,
+    pub inventory_db: Option<PathBuf>,
+    pub policy_file: Option<PathBuf>,
+    pub roles_file: Option<PathBuf>,
+    pub telemetry_file: Option<PathBuf>,
+}
+
+/// Environment variable for each layer field, in `ConfigLayer` order
+const ENV_VARS: &[(&str, &str)] = &[
+    ("data_dir", "SECUREWIPE_DATA_DIR"),
+    ("certificates_dir", "SECUREWIPE_CERTS_DIR"),
+    ("sign_key_path", "SECUREWIPE_SIGN_KEY_PATH"),
+    ("inventory_db", "SECUREWIPE_INVENTORY_DB"),
+    ("policy_file", "SECUREWIPE_POLICY"),
+    ("roles_file", "SECUREWIPE_ROLES"),
+    ("telemetry_file", "SECUREWIPE_TELEMETRY"),
+];
+
+impl ConfigLayer {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ConfigError::File(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_str(&content).map_err(|e| ConfigError::File(format!("{}: {}", path.display(), e)))
+    }
+
+    /// The layer a file contributes, or an empty one when the file doesn't exist
+    fn load_optional(path: &Path) -> Result<Self, ConfigError> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    fn from_env(env: &dyn Fn(&str) -> Option<String>) -> Self {
+        let var = |key: &str| {
+            ENV_VARS
+                .iter()
+                .find(|(field, _)| *field == key)
+                .and_then(|(_, var)| env(var))
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        ConfigLayer {
+            data_dir: var("data_dir"),
+            certificates_dir: var("certificates_dir"),
+            sign_key_path: var("sign_key_path"),
+            inventory_db: var("inventory_db"),
+            policy_file: var("policy_file"),
+            roles_file: var("roles_file"),
+            telemetry_file: var("telemetry_file"),
+        }
+    }
+
+    fn fields(&self) -> [(&'static str, Option<&PathBuf>); 7] {
+        [
+            ("data_dir", self.data_dir.as_ref()),
+            ("certificates_dir", self.certificates_dir.as_ref()),
+            ("sign_key_path", self.sign_key_path.as_ref()),
+            ("inventory_db", self.inventory_db.as_ref()),
+            ("policy_file", self.policy_file.as_ref()),
+            ("roles_file", self.roles_file.as_ref()),
+            ("telemetry_file", self.telemetry_file.as_ref()),
+        ]
+    }
+}
+
+/// The effective configuration after all layers are applied
+#[derive(Debug, Clone, Serialize)]
+pub struct Config {
+    /// Where the default paths came from
+    pub layout: LayoutKind,
+    pub data_dir: PathBuf,
+    pub certificates_dir: PathBuf,
+    pub sign_key_path: Option<PathBuf>,
+    pub inventory_db: PathBuf,
+    pub policy_file: PathBuf,
+    pub roles_file: PathBuf,
+    pub telemetry_file: PathBuf,
+    /// SECUREWIPE_DANGER=1: destructive wipes may run
+    pub danger: bool,
+    /// Which layer set each setting
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+impl Config {
+    /// Every layer, ending with the CLI flags installed by `set_cli_overrides`
+    pub fn load() -> Result<Self, ConfigError> {
+        let cli = CLI_LAYER.get().cloned().unwrap_or_default();
+        let env = |var: &str| std::env::var(var).ok();
+        let layout = Layout::detect().map_err(|_| ConfigError::NoHome)?;
+        let system_path = PathBuf::from(SYSTEM_CONFIG_PATH);
+        // A user file named by SECUREWIPE_CONFIG must exist; the default one is optional.
+        // In system mode the system file is the only default one.
+        let user = match env("SECUREWIPE_CONFIG") {
+            Some(path) => {
+                let path = PathBuf::from(path);
+                Some((ConfigLayer::load(&path)?, Layer::User(path)))
+            }
+            None if layout.kind == LayoutKind::System => None,
+            None => Some((ConfigLayer::load_optional(&layout.config_file)?, Layer::User(layout.config_file.clone()))),
+        };
+
+        let mut layers = vec![(ConfigLayer::load_optional(&system_path)?, Layer::System(system_path))];
+        layers.extend(user);
+        layers.push((ConfigLayer::from_env(&env), Layer::Env));
+        layers.push((cli, Layer::Cli));
+        Ok(Self::resolve(&layout, layers, env("SECUREWIPE_DANGER").as_deref() == Some("1")))
+    }
+
+    fn resolve(layout: &Layout, layers: Vec<(ConfigLayer, Layer)>, danger: bool) -> Self {
+        let mut merged: BTreeMap<&'static str, PathBuf> = BTreeMap::new();
+        let mut sources = BTreeMap::new();
+        for (layer, origin) in layers {
+            for (field, value) in layer.fields() {
+                if let Some(value) = value {
+                    merged.insert(field, value.clone());
+                    sources.insert(field.to_string(), origin.source(field));
+                }
+            }
+        }
+
+        let data_dir = merged.remove("data_dir").unwrap_or_else(|| layout.data_dir.clone());
+        let mut path = |field: &'static str, default: &str| {
+            sources.entry(field.to_string()).or_insert(ConfigSource::Default);
+            merged.remove(field).unwrap_or_else(|| data_dir.join(default))
+        };
+        let certificates_dir = path("certificates_dir", "certificates");
+        let inventory_db = path("inventory_db", "inventory.db");
+        let policy_file = path("policy_file", "policy.json");
+        let roles_file = path("roles_file", "roles.json");
+        let telemetry_file = path("telemetry_file", "telemetry.json");
+        let sign_key_path = merged.remove("sign_key_path");
+        sources.entry("data_dir".to_string()).or_insert(ConfigSource::Default);
+        sources.entry("sign_key_path".to_string()).or_insert(ConfigSource::Default);
+        sources.insert("layout".to_string(), ConfigSource::Default);
+        sources.insert(
+            "danger".to_string(),
+            if danger { ConfigSource::Env { var: "SECUREWIPE_DANGER".to_string() } } else { ConfigSource::Default },
+        );
+
+        Config {
+            layout: layout.kind,
+            data_dir,
+            certificates_dir,
+            sign_key_path,
+            inventory_db,
+            policy_file,
+            roles_file,
+            telemetry_file,
+            danger,
+            sources,
+        }
+    }
+
+    /// True when `field` wasn't set by any file, variable or flag. Optional files at a
+    /// default location may be missing; ones the user named must exist.
+    pub fn is_default(&self, field: &str) -> bool {
+        matches!(self.sources.get(field), None | Some(ConfigSource::Default))
+    }
+
+    /// Each setting with its effective value, for `config show`
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let path = |path: &Path| path.display().to_string();
+        vec![
+            ("layout", self.layout.as_str().to_string()),
+            ("data_dir", path(&self.data_dir)),
+            ("certificates_dir", path(&self.certificates_dir)),
+            ("sign_key_path", self.sign_key_path.as_deref().map(path).unwrap_or_else(|| "(unset)".to_string())),
+            ("inventory_db", path(&self.inventory_db)),
+            ("policy_file", path(&self.policy_file)),
+            ("roles_file", path(&self.roles_file)),
+            ("telemetry_file", path(&self.telemetry_file)),
+            ("danger", self.danger.to_string()),
+        ]
+    }
+}
+
+/// Which layer a `ConfigLayer` came from, before it's turned into per-field sources
+enum Layer {
+    System(PathBuf),
+    User(PathBuf),
+    Env,
+    Cli,
+}
+
+impl Layer {
+    fn source(&self, field: &str) -> ConfigSource {
+        match self {
+            Layer::System(path) => ConfigSource::SystemFile { path: path.clone() },
+            Layer::User(path) => ConfigSource::UserFile { path: path.clone() },
+            Layer::Env => ConfigSource::Env {
+                var: ENV_VARS.iter().find(|(name, _)| *name == field).map(|(_, var)| var.to_string()).unwrap_or_default(),
+            },
+            Layer::Cli => ConfigSource::Cli { flag: format!("--{}", field.replace('_', "-")) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(json: &str) -> ConfigLayer {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_config_layer_precedence() {
+        let layout = Layout::legacy(Path::new("/home/op"));
+        let env_vars: BTreeMap<&str, &str> = [("SECUREWIPE_POLICY", "/srv/env-policy.json")].into_iter().collect();
+        let env = |var: &str| env_vars.get(var).map(|value| value.to_string());
+        let layers = vec![
+            (layer(r#"{"data_dir": "/var/lib/securewipe", "policy_file": "/etc/securewipe/policy.json"}"#), Layer::System("/etc/securewipe/config.json".into())),
+            (layer(r#"{"policy_file": "/home/op/policy.json", "sign_key_path": "/home/op/key.pem"}"#), Layer::User("/home/op/SecureWipe/config.json".into())),
+            (ConfigLayer::from_env(&env), Layer::Env),
+            (ConfigLayer { sign_key_path: Some("/tmp/cli.pem".into()), ..Default::default() }, Layer::Cli),
+        ];
+        let config = Config::resolve(&layout, layers, false);
+
+        assert_eq!(config.data_dir, PathBuf::from("/var/lib/securewipe"));
+        assert_eq!(config.sources["data_dir"], ConfigSource::SystemFile { path: "/etc/securewipe/config.json".into() });
+        // Paths nobody set follow data_dir
+        assert_eq!(config.certificates_dir, PathBuf::from("/var/lib/securewipe/certificates"));
+        assert!(config.is_default("certificates_dir"));
+        // env beats both files, the flag beats the user file
+        assert_eq!(config.policy_file, PathBuf::from("/srv/env-policy.json"));
+        assert_eq!(config.sources["policy_file"], ConfigSource::Env { var: "SECUREWIPE_POLICY".to_string() });
+        assert_eq!(config.sign_key_path, Some(PathBuf::from("/tmp/cli.pem")));
+        assert_eq!(config.sources["sign_key_path"].to_string(), "flag --sign-key-path");
+        assert!(!config.danger);
+        assert!(config.is_default("danger"));
+    }
+
+    #[test]
+    fn test_config_defaults_and_file_errors() {
+        let config = Config::resolve(&Layout::legacy(Path::new("/home/op")), Vec::new(), true);
+        assert_eq!(config.inventory_db, PathBuf::from("/home/op/SecureWipe/inventory.db"));
+        assert_eq!(config.sign_key_path, None);
+        assert_eq!(config.sources["danger"].to_string(), "env SECUREWIPE_DANGER");
+        assert_eq!(config.entries().len(), config.sources.len());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        assert!(ConfigLayer::load(&path).is_err());
+        assert_eq!(ConfigLayer::load_optional(&path).unwrap(), ConfigLayer::default());
+
+        fs::write(&path, r#"{"danger": true}"#).unwrap();
+        assert!(ConfigLayer::load(&path).is_err(), "danger can't be set from a file");
+        fs::write(&path, r#"{"roles_file": "/etc/securewipe/roles.json"}"#).unwrap();
+        assert_eq!(ConfigLayer::load(&path).unwrap().roles_file, Some(PathBuf::from("/etc/securewipe/roles.json")));
+    }
+}
@@ -0,0 +1,282 @@
+//! Container storage on this machine: Docker and Podman storage roots, containerd,
+//! kubelet and Kubernetes local persistent volumes, and the upper directories of
+//! active overlay mounts. A disk holding any of them is in use even when nothing on
+//! it looks like a system mount, so discovery raises it to HIGH and wipe planning
+//! blocks it.
+
+use crate::device::{Device, RiskExplanation, RiskLevel};
+#[cfg(feature = "operations")]
+use crate::wipe::WipePlan;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+const DOCKER_DAEMON_JSON: &str = "/etc/docker/daemon.json";
+const CONTAINERS_STORAGE_CONF: &str = "/etc/containers/storage.conf";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageKind {
+    Docker,
+    Podman,
+    Containerd,
+    Kubelet,
+    KubernetesLocalPv,
+    Overlay,
+}
+
+impl StorageKind {
+    /// Risk rule name reported by `discover --explain-risk`
+    pub fn rule(self) -> &'static str {
+        match self {
+            StorageKind::Docker => "docker-storage",
+            StorageKind::Podman => "podman-storage",
+            StorageKind::Containerd => "containerd-storage",
+            StorageKind::Kubelet => "kubelet-storage",
+            StorageKind::KubernetesLocalPv => "k8s-local-pv",
+            StorageKind::Overlay => "overlay-storage",
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            StorageKind::Docker => "Docker data root",
+            StorageKind::Podman => "Podman/CRI-O storage",
+            StorageKind::Containerd => "containerd root",
+            StorageKind::Kubelet => "kubelet root",
+            StorageKind::KubernetesLocalPv => "Kubernetes local persistent volume",
+            StorageKind::Overlay => "overlay mount upper directory",
+        }
+    }
+
+    fn from_rule(rule: &str) -> Option<Self> {
+        [
+            StorageKind::Docker,
+            StorageKind::Podman,
+            StorageKind::Containerd,
+            StorageKind::Kubelet,
+            StorageKind::KubernetesLocalPv,
+            StorageKind::Overlay,
+        ]
+        .into_iter()
+        .find(|kind| kind.rule() == rule)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerStorage {
+    pub kind: StorageKind,
+    pub path: PathBuf,
+}
+
+/// Every container storage location in use on this machine. Tools that aren't
+/// installed simply contribute nothing.
+pub fn storage_roots() -> Vec<ContainerStorage> {
+    let mut roots = Vec::new();
+    let mut add = |kind: StorageKind, path: PathBuf| {
+        if path.exists() && !roots.iter().any(|root: &ContainerStorage| root.path == path) {
+            roots.push(ContainerStorage { kind, path });
+        }
+    };
+
+    let docker = fs::read_to_string(DOCKER_DAEMON_JSON).ok().and_then(|content| docker_data_root(&content));
+    add(StorageKind::Docker, docker.unwrap_or_else(|| PathBuf::from("/var/lib/docker")));
+    let podman = fs::read_to_string(CONTAINERS_STORAGE_CONF).ok().and_then(|content| storage_graphroot(&content));
+    add(StorageKind::Podman, podman.unwrap_or_else(|| PathBuf::from("/var/lib/containers/storage")));
+    add(StorageKind::Containerd, PathBuf::from("/var/lib/containerd"));
+    add(StorageKind::Kubelet, PathBuf::from("/var/lib/kubelet"));
+    for path in kubernetes_local_volumes() {
+        add(StorageKind::KubernetesLocalPv, path);
+    }
+    for path in fs::read_to_string("/proc/self/mounts").map(|mounts| overlay_upperdirs(&mounts)).unwrap_or_default() {
+        add(StorageKind::Overlay, path);
+    }
+    roots
+}
+
+/// Raise each disk backing container storage to HIGH and say why. CRITICAL disks
+/// keep their level and explanation.
+pub fn flag_devices(devices: &mut [Device], roots: &[ContainerStorage]) {
+    for root in roots {
+        let Some(index) = backing_device(&root.path, devices) else {
+            continue;
+        };
+        let device = &mut devices[index];
+        if matches!(device.risk_level, RiskLevel::Critical) {
+            continue;
+        }
+        device.risk_level = RiskLevel::High;
+        device.risk_explanation = Some(RiskExplanation {
+            rule: root.kind.rule().to_string(),
+            matched: Some(root.path.display().to_string()),
+        });
+    }
+}
+
+/// Why a plan for a disk flagged by `flag_devices` is blocked
+pub fn blocking_reason(explanation: Option<&RiskExplanation>) -> Option<String> {
+    let explanation = explanation?;
+    let kind = StorageKind::from_rule(&explanation.rule)?;
+    Some(format!(
+        "Device holds {} at {}; stop the containers using it and unmount it before wiping",
+        kind.describe(),
+        explanation.matched.as_deref().unwrap_or("an unknown path")
+    ))
+}
+
+/// Block `plan` when its disk was flagged by `flag_devices`, keeping any reason it
+/// was already blocked for
+#[cfg(feature = "operations")]
+pub fn block_plan(plan: &mut WipePlan, explanation: Option<&RiskExplanation>) {
+    if let Some(reason) = blocking_reason(explanation) {
+        crate::wipe::block_plan(plan, reason);
+    }
+}
+
+/// The disk whose mountpoint is the longest prefix of `path`
+fn backing_device(path: &Path, devices: &[Device]) -> Option<usize> {
+    devices
+        .iter()
+        .enumerate()
+        .flat_map(|(index, device)| device.mountpoints.iter().map(move |mountpoint| (index, Path::new(mountpoint))))
+        .filter(|(_, mountpoint)| mountpoint.is_absolute() && path.starts_with(mountpoint))
+        .max_by_key(|(_, mountpoint)| mountpoint.components().filter(|c| matches!(c, Component::Normal(_))).count())
+        .map(|(index, _)| index)
+}
+
+/// `data-root` (or the pre-17.05 `graph`) from /etc/docker/daemon.json
+fn docker_data_root(daemon_json: &str) -> Option<PathBuf> {
+    let json: serde_json::Value = serde_json::from_str(daemon_json).ok()?;
+    json["data-root"].as_str().or_else(|| json["graph"].as_str()).map(PathBuf::from)
+}
+
+/// `graphroot` in the `[storage]` table of containers-storage.conf
+fn storage_graphroot(conf: &str) -> Option<PathBuf> {
+    let mut in_storage = false;
+    for line in conf.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_storage = line == "[storage]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if in_storage && key.trim() == "graphroot" {
+            let value = value.trim().trim_matches('"');
+            return (!value.is_empty()).then(|| PathBuf::from(value));
+        }
+    }
+    None
+}
+
+/// Host paths of bound local and hostPath persistent volumes, when kubectl can reach
+/// a cluster
+fn kubernetes_local_volumes() -> Vec<PathBuf> {
+    Command::new("kubectl")
+        .args(["get", "pv", "-o", "json", "--request-timeout=5s"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| local_pv_paths(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+fn local_pv_paths(pv_list: &str) -> Vec<PathBuf> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(pv_list) else {
+        return Vec::new();
+    };
+    json["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|pv| pv["status"]["phase"] == "Bound")
+        .filter_map(|pv| pv["spec"]["local"]["path"].as_str().or_else(|| pv["spec"]["hostPath"]["path"].as_str()))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// `upperdir=` of every overlay mount in /proc/self/mounts
+fn overlay_upperdirs(mounts: &str) -> Vec<PathBuf> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.get(2) != Some(&"overlay") {
+                return None;
+            }
+            fields.get(3)?.split(',').find_map(|option| option.strip_prefix("upperdir=")).map(PathBuf::from)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk(name: &str, mountpoints: &[&str], risk_level: RiskLevel) -> Device {
+        Device {
+            name: name.to_string(),
+            model: None,
+            serial: None,
+            capacity_bytes: 0,
+            bus: None,
+            mountpoints: mountpoints.iter().map(|mp| mp.to_string()).collect(),
+            risk_level,
+            device_id: None,
+            risk_explanation: None,
+            capacity_display: None,
+        }
+    }
+
+    #[test]
+    fn test_flag_devices() {
+        let mut devices = vec![
+            disk("/dev/sda", &["/", "/boot/efi"], RiskLevel::Critical),
+            disk("/dev/sdb", &["/srv/docker"], RiskLevel::High),
+            disk("/dev/sdc", &["/run/media/k8s"], RiskLevel::Safe),
+            disk("/dev/sdd", &[], RiskLevel::Safe),
+        ];
+        let roots = vec![
+            ContainerStorage { kind: StorageKind::Docker, path: PathBuf::from("/srv/docker") },
+            ContainerStorage { kind: StorageKind::KubernetesLocalPv, path: PathBuf::from("/run/media/k8s/pv1") },
+            ContainerStorage { kind: StorageKind::Podman, path: PathBuf::from("/var/lib/containers/storage") },
+        ];
+        flag_devices(&mut devices, &roots);
+
+        // Podman storage lives on the root disk, which stays CRITICAL for its own reason
+        assert!(matches!(devices[0].risk_level, RiskLevel::Critical));
+        assert!(devices[0].risk_explanation.is_none());
+        assert_eq!(devices[1].risk_explanation.as_ref().unwrap().rule, "docker-storage");
+        assert!(matches!(devices[2].risk_level, RiskLevel::High));
+        assert_eq!(
+            blocking_reason(devices[2].risk_explanation.as_ref()).unwrap(),
+            "Device holds Kubernetes local persistent volume at /run/media/k8s/pv1; stop the containers using it and unmount it before wiping"
+        );
+        assert!(matches!(devices[3].risk_level, RiskLevel::Safe));
+        assert_eq!(blocking_reason(Some(&RiskExplanation { rule: "root".to_string(), matched: None })), None);
+    }
+
+    #[test]
+    fn test_storage_root_config() {
+        assert_eq!(docker_data_root(r#"{"data-root": "/srv/docker", "log-driver": "json-file"}"#), Some(PathBuf::from("/srv/docker")));
+        assert_eq!(docker_data_root(r#"{"graph": "/data/docker"}"#), Some(PathBuf::from("/data/docker")));
+        assert_eq!(docker_data_root("{}"), None);
+
+        let conf = "[storage]\ndriver = \"overlay\"\nrunroot = \"/run/containers/storage\"\ngraphroot = \"/mnt/podman\"\n\n[storage.options]\nmount_program = \"/usr/bin/fuse-overlayfs\"\n";
+        assert_eq!(storage_graphroot(conf), Some(PathBuf::from("/mnt/podman")));
+        assert_eq!(storage_graphroot("[engine]\ngraphroot = \"/x\"\n"), None);
+    }
+
+    #[test]
+    fn test_volume_and_overlay_paths() {
+        let pvs = r#"{"items": [
+            {"spec": {"local": {"path": "/mnt/disks/ssd1"}}, "status": {"phase": "Bound"}},
+            {"spec": {"hostPath": {"path": "/data/pv2"}}, "status": {"phase": "Bound"}},
+            {"spec": {"local": {"path": "/mnt/disks/ssd3"}}, "status": {"phase": "Available"}},
+            {"spec": {"nfs": {"path": "/exports"}}, "status": {"phase": "Bound"}}
+        ]}"#;
+        assert_eq!(local_pv_paths(pvs), vec![PathBuf::from("/mnt/disks/ssd1"), PathBuf::from("/data/pv2")]);
+
+        let mounts = "/dev/sda2 / ext4 rw,relatime 0 0\noverlay /var/lib/docker/overlay2/abc/merged overlay rw,lowerdir=/var/lib/docker/overlay2/l/X,upperdir=/srv/docker/overlay2/abc/diff,workdir=/srv/docker/overlay2/abc/work 0 0\n";
+        assert_eq!(overlay_upperdirs(mounts), vec![PathBuf::from("/srv/docker/overlay2/abc/diff")]);
+    }
+}
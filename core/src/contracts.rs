@@ -0,0 +1,141 @@
+#[cfg(feature = "operations")]
+use schemars::schema_for;
+#[cfg(feature = "operations")]
+use serde_json::Value;
+#[cfg(feature = "operations")]
+use std::error::Error;
+#[cfg(feature = "operations")]
+use std::path::{Path, PathBuf};
+
+pub use securewipe_types::{ErrorResponse, EventRecord, LogMessage, ProgressEvent};
+
+/// Version of the exported JSON contracts. Bump the minor version for additive
+/// changes and the major version when a field is removed or changes meaning.
+pub const CONTRACT_VERSION: &str = "1.2.0";
+
+/// Schemas generated from the Rust types, keyed by export name
+#[cfg(feature = "operations")]
+pub fn emitted_schemas() -> Vec<(&'static str, Value)> {
+    use crate::approval::{Approval, ApprovalRequest};
+    use crate::backup::{BackupResult, BackupVerificationReport};
+    use crate::cert::HostInfo;
+    use crate::device::{Device, RemovableVolume};
+    use crate::inventory::InventoryEntry;
+    use crate::label::LabelSummary;
+    use crate::wipe::{ReplayReport, WipePlan, WipeResult};
+
+    let schemas = vec![
+        ("device_list", schema_for!(Vec<Device>)),
+        ("removable_volumes", schema_for!(Vec<RemovableVolume>)),
+        ("host_info", schema_for!(HostInfo)),
+        ("wipe_plan", schema_for!(WipePlan)),
+        ("wipe_result", schema_for!(WipeResult)),
+        ("replay_report", schema_for!(ReplayReport)),
+        ("backup_result", schema_for!(BackupResult)),
+        ("backup_verification_report", schema_for!(BackupVerificationReport)),
+        ("inventory_entry", schema_for!(InventoryEntry)),
+        ("label_summary", schema_for!(LabelSummary)),
+        ("approval_request", schema_for!(ApprovalRequest)),
+        ("approval", schema_for!(Approval)),
+        ("progress_event", schema_for!(ProgressEvent)),
+        ("log_message", schema_for!(LogMessage)),
+        ("error_response", schema_for!(ErrorResponse)),
+        ("event_record", schema_for!(EventRecord)),
+    ];
+
+    schemas
+        .into_iter()
+        .map(|(name, schema)| (name, serde_json::to_value(schema).unwrap_or(Value::Null)))
+        .collect()
+}
+
+/// The hand-written certificate schemas, exported with the generated ones so a
+/// consumer gets one consistently versioned bundle
+#[cfg(feature = "operations")]
+pub fn certificate_schemas() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("backup_certificate", include_str!("../../certs/schemas/backup_schema.json")),
+        ("wipe_certificate", include_str!("../../certs/schemas/wipe_schema.json")),
+        ("destroy_certificate", include_str!("../../certs/schemas/destroy_schema.json")),
+        ("system_certificate", include_str!("../../certs/schemas/system_schema.json")),
+    ]
+}
+
+/// Write `<name>.schema.json` for every emitted structure plus an `index.json`
+#[cfg(feature = "operations")]
+pub fn export_schemas(out_dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    std::fs::create_dir_all(out_dir)?;
+
+    let mut documents: Vec<(&str, Value)> = emitted_schemas();
+    for (name, content) in certificate_schemas() {
+        documents.push((name, serde_json::from_str(content)?));
+    }
+
+    let mut written = Vec::new();
+    let mut index = Vec::new();
+    for (name, mut schema) in documents {
+        let file_name = format!("{}.schema.json", name);
+        if let Some(object) = schema.as_object_mut() {
+            object.insert(
+                "$id".to_string(),
+                Value::String(format!("https://securewipe.local/schemas/v{}/{}", CONTRACT_VERSION, file_name)),
+            );
+            object.insert("x-securewipe-version".to_string(), Value::String(CONTRACT_VERSION.to_string()));
+        }
+
+        let path = out_dir.join(&file_name);
+        std::fs::write(&path, serde_json::to_string_pretty(&schema)?)?;
+        index.push(serde_json::json!({"name": name, "file": file_name}));
+        written.push(path);
+    }
+
+    let index_path = out_dir.join("index.json");
+    std::fs::write(
+        &index_path,
+        serde_json::to_string_pretty(&serde_json::json!({
+            "version": CONTRACT_VERSION,
+            "schemas": index
+        }))?,
+    )?;
+    written.push(index_path);
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "operations")]
+    fn test_export_schemas() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = export_schemas(dir.path()).unwrap();
+        assert_eq!(written.len(), emitted_schemas().len() + certificate_schemas().len() + 1);
+
+        let plan: Value = serde_json::from_str(&std::fs::read_to_string(dir.path().join("wipe_plan.schema.json")).unwrap()).unwrap();
+        assert_eq!(plan["x-securewipe-version"], CONTRACT_VERSION);
+        assert!(plan["properties"]["main_method"].is_object());
+
+        let wipe_cert: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join("wipe_certificate.schema.json")).unwrap()).unwrap();
+        assert_eq!(wipe_cert["properties"]["cert_type"]["const"], "wipe");
+
+        let index: Value = serde_json::from_str(&std::fs::read_to_string(dir.path().join("index.json")).unwrap()).unwrap();
+        assert_eq!(index["version"], CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn test_progress_event_matches_logged_shape() {
+        // The shape cmd.rs logs for each step must deserialize into the contract
+        let logged = serde_json::json!({
+            "step": "wipe_completed",
+            "device": "/dev/sdb",
+            "verification_passed": true,
+            "timestamp": "2025-09-20T10:15:00+00:00"
+        });
+        let event: ProgressEvent = serde_json::from_value(logged).unwrap();
+        assert_eq!(event.step, "wipe_completed");
+        assert_eq!(event.details["device"], "/dev/sdb");
+    }
+}
@@ -0,0 +1,246 @@
+//! What a backup or wipe cost to run: wall time, bytes processed, average throughput
+//! and an energy estimate (wall time at a typical power draw for the drive's bus, from
+//! the site policy's `cost` section). Recorded in each certificate's `metadata.cost`
+//! and totalled across the certificates directory by `cert report`, for billing.
+
+use crate::cert::{BusType, CertificateDevice, CostSummary, MediaType, MonotonicSpan};
+use crate::policy::{CostPolicy, SitePolicy};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Cost of an operation on `device` that took `timing` and read or wrote
+/// `bytes_processed`; None when the operation wasn't timed
+pub fn estimate(device: &CertificateDevice, timing: Option<MonotonicSpan>, bytes_processed: u64) -> Option<CostSummary> {
+    let policy = SitePolicy::load_default().map(|policy| policy.cost).unwrap_or_default();
+    Some(summarize(&policy, device, timing?.duration_ms, bytes_processed))
+}
+
+fn summarize(policy: &CostPolicy, device: &CertificateDevice, wall_time_ms: u64, bytes_processed: u64) -> CostSummary {
+    let assumed_watts = if device.media_type == Some(MediaType::Virtual) {
+        0.0
+    } else {
+        match device.bus {
+            BusType::Nvme => policy.nvme_watts,
+            BusType::Sata => policy.sata_watts,
+            BusType::Sas => policy.sas_watts,
+            BusType::Usb => policy.usb_watts,
+            _ => policy.other_watts,
+        }
+    };
+    let throughput_bytes_per_sec = match wall_time_ms {
+        0 => 0,
+        ms => (bytes_processed as u128 * 1000 / ms as u128) as u64,
+    };
+    CostSummary {
+        wall_time_ms,
+        bytes_processed,
+        throughput_bytes_per_sec,
+        assumed_watts,
+        energy_wh: round_wh(assumed_watts * wall_time_ms as f64 / 3_600_000.0),
+    }
+}
+
+/// To the milliwatt-hour, so the signed figure reads the same everywhere
+fn round_wh(wh: f64) -> f64 {
+    (wh * 1000.0).round() / 1000.0
+}
+
+/// Which certificates `report` covers; every field is optional
+#[derive(Debug, Clone, Default)]
+pub struct ReportFilter {
+    /// Created at or after
+    pub since: Option<DateTime<Utc>>,
+    /// Created before
+    pub until: Option<DateTime<Utc>>,
+    /// backup, wipe or destroy
+    pub cert_type: Option<String>,
+}
+
+/// One certificate in a cost report
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CostLine {
+    pub cert_id: String,
+    pub cert_type: String,
+    pub created_at: String,
+    pub device_serial: Option<String>,
+    pub device_model: Option<String>,
+    pub result: Option<String>,
+    pub cost: CostSummary,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CostTotals {
+    pub certificates: usize,
+    pub wall_time_ms: u64,
+    pub bytes_processed: u64,
+    pub energy_wh: f64,
+}
+
+impl CostTotals {
+    fn add(&mut self, cost: &CostSummary) {
+        self.certificates += 1;
+        self.wall_time_ms += cost.wall_time_ms;
+        self.bytes_processed += cost.bytes_processed;
+        self.energy_wh = round_wh(self.energy_wh + cost.energy_wh);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CostReport {
+    /// Oldest first
+    pub certificates: Vec<CostLine>,
+    pub totals: CostTotals,
+    pub by_type: BTreeMap<String, CostTotals>,
+    /// Matching certificates issued before cost summaries were recorded
+    pub without_cost: usize,
+}
+
+/// Total the cost summaries of the certificates in `dir` that match `filter`
+pub fn report(dir: &Path, filter: &ReportFilter) -> std::io::Result<CostReport> {
+    let mut report = CostReport::default();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(report),
+        Err(e) => return Err(e),
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Some(cert) = fs::read_to_string(&path).ok().and_then(|content| serde_json::from_str::<Value>(&content).ok()) else {
+            continue;
+        };
+        let text = |pointer: &str| cert.pointer(pointer).and_then(|v| v.as_str()).map(str::to_string);
+        // Serial counters and annotation sidecars are JSON too
+        let (Some(cert_id), Some(cert_type), Some(created_at)) = (text("/cert_id"), text("/cert_type"), text("/created_at")) else {
+            continue;
+        };
+        if filter.cert_type.as_ref().is_some_and(|wanted| *wanted != cert_type) {
+            continue;
+        }
+        let Ok(created) = DateTime::parse_from_rfc3339(&created_at) else { continue };
+        if filter.since.is_some_and(|since| created < since) || filter.until.is_some_and(|until| created >= until) {
+            continue;
+        }
+
+        let Some(cost) = cert.pointer("/metadata/cost").and_then(|cost| serde_json::from_value::<CostSummary>(cost.clone()).ok())
+        else {
+            report.without_cost += 1;
+            continue;
+        };
+        report.totals.add(&cost);
+        report.by_type.entry(cert_type.clone()).or_default().add(&cost);
+        report.certificates.push(CostLine {
+            cert_id,
+            cert_type,
+            created_at,
+            device_serial: text("/device/serial"),
+            device_model: text("/device/model"),
+            result: text("/result"),
+            cost,
+        });
+    }
+    report.certificates.sort_by(|a, b| (&a.created_at, &a.cert_id).cmp(&(&b.created_at, &b.cert_id)));
+    Ok(report)
+}
+
+/// One row per certificate, for spreadsheets and billing systems
+pub fn report_csv(report: &CostReport) -> String {
+    let mut csv = String::from(
+        "cert_id,cert_type,created_at,device_serial,device_model,result,wall_time_ms,bytes_processed,throughput_bytes_per_sec,assumed_watts,energy_wh\n",
+    );
+    for line in &report.certificates {
+        let fields = [
+            line.cert_id.clone(),
+            line.cert_type.clone(),
+            line.created_at.clone(),
+            line.device_serial.clone().unwrap_or_default(),
+            line.device_model.clone().unwrap_or_default(),
+            line.result.clone().unwrap_or_default(),
+            line.cost.wall_time_ms.to_string(),
+            line.cost.bytes_processed.to_string(),
+            line.cost.throughput_bytes_per_sec.to_string(),
+            line.cost.assumed_watts.to_string(),
+            line.cost.energy_wh.to_string(),
+        ];
+        let escaped: Vec<String> = fields.iter().map(|field| crate::inventory::csv_field(field)).collect();
+        csv.push_str(&escaped.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn device(bus: BusType, media_type: Option<MediaType>) -> CertificateDevice {
+        let mut device = crate::cert_builder::unknown_device(None);
+        device.bus = bus;
+        device.media_type = media_type;
+        device
+    }
+
+    #[test]
+    fn test_summarize() {
+        let policy = CostPolicy::default();
+        // 100 GiB over an hour on NVMe
+        let cost = summarize(&policy, &device(BusType::Nvme, Some(MediaType::Physical)), 3_600_000, 100 << 30);
+        assert_eq!(cost.throughput_bytes_per_sec, (100u64 << 30) / 3600);
+        assert_eq!(cost.assumed_watts, policy.nvme_watts);
+        assert_eq!(cost.energy_wh, policy.nvme_watts);
+
+        let usb = summarize(&policy, &device(BusType::Usb, None), 90_000, 1 << 30);
+        assert_eq!(usb.energy_wh, 0.063);
+
+        let virtual_media = summarize(&policy, &device(BusType::Virtio, Some(MediaType::Virtual)), 60_000, 1 << 30);
+        assert_eq!(virtual_media.energy_wh, 0.0);
+
+        assert_eq!(summarize(&policy, &device(BusType::Sata, None), 0, 4096).throughput_bytes_per_sec, 0);
+    }
+
+    #[test]
+    fn test_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let cost = |ms: u64, energy: f64| {
+            json!({"wall_time_ms": ms, "bytes_processed": 1000, "throughput_bytes_per_sec": 10, "assumed_watts": 6.0, "energy_wh": energy})
+        };
+        let certs = [
+            json!({"cert_id": "W1", "cert_type": "wipe", "created_at": "2025-03-01T10:00:00+00:00", "device": {"serial": "S1", "model": "Disk, 1TB"}, "result": "PASS", "metadata": {"cost": cost(60_000, 0.1)}}),
+            json!({"cert_id": "B1", "cert_type": "backup", "created_at": "2025-02-01T10:00:00+00:00", "metadata": {"cost": cost(30_000, 0.05)}}),
+            json!({"cert_id": "W0", "cert_type": "wipe", "created_at": "2024-12-01T10:00:00+00:00", "metadata": {}}),
+            json!({"next": 42}),
+        ];
+        for cert in &certs {
+            let name = cert["cert_id"].as_str().unwrap_or("serial_counter");
+            fs::write(dir.path().join(format!("{}.json", name)), cert.to_string()).unwrap();
+        }
+
+        let all = report(dir.path(), &ReportFilter::default()).unwrap();
+        let ids: Vec<&str> = all.certificates.iter().map(|line| line.cert_id.as_str()).collect();
+        assert_eq!(ids, ["B1", "W1"]);
+        assert_eq!(all.without_cost, 1);
+        assert_eq!(all.totals, CostTotals { certificates: 2, wall_time_ms: 90_000, bytes_processed: 2000, energy_wh: 0.15 });
+        assert_eq!(all.by_type["wipe"].certificates, 1);
+
+        let since = DateTime::parse_from_rfc3339("2025-02-15T00:00:00Z").unwrap().with_timezone(&Utc);
+        let recent = report(dir.path(), &ReportFilter { since: Some(since), ..ReportFilter::default() }).unwrap();
+        assert_eq!(recent.certificates.len(), 1);
+        assert_eq!(recent.without_cost, 0);
+
+        let backups = report(dir.path(), &ReportFilter { cert_type: Some("backup".to_string()), ..ReportFilter::default() }).unwrap();
+        assert_eq!(backups.totals.certificates, 1);
+
+        let csv = report_csv(&all);
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.contains("W1,wipe,2025-03-01T10:00:00+00:00,S1,\"Disk, 1TB\",PASS,60000,1000,10,6,0.1"));
+
+        assert_eq!(report(&dir.path().join("missing"), &ReportFilter::default()).unwrap(), CostReport::default());
+    }
+}
@@ -1,5 +1,16 @@
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer};
 use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use crate::probe_cache;
+use crate::tool_json::{NvmeIdCtrl, SmartctlInfo};
+use crate::device_id::stable_id;
+use crate::risk::{self, Partition};
+
+pub use securewipe_types::{Device, RemovableVolume, RiskExplanation, RiskLevel};
+
+/// Disks enriched at once; each runs its own smartctl/hdparm/nvme processes
+const MAX_PARALLEL_PROBES: usize = 4;
 
 // Custom deserializer to handle size field that can be either string or integer
 fn deserialize_size<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
@@ -88,25 +99,17 @@ where
     deserializer.deserialize_option(SizeVisitor)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum RiskLevel {
-    #[serde(rename = "CRITICAL")]
-    Critical,
-    #[serde(rename = "HIGH")]
-    High,
-    #[serde(rename = "SAFE")]
-    Safe,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Device {
-    pub name: String,
-    pub model: Option<String>,
-    pub serial: Option<String>,
-    pub capacity_bytes: u64,
-    pub bus: Option<String>, // SATA, NVMe, USB
-    pub mountpoints: Vec<String>,
-    pub risk_level: RiskLevel,
+// lsblk reports RM/HOTPLUG as booleans in newer util-linux and "0"/"1" in older releases
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(serde_json::Value::Bool(b)) => b,
+        Some(serde_json::Value::String(s)) => s == "1" || s == "true",
+        Some(serde_json::Value::Number(n)) => n.as_u64() == Some(1),
+        _ => false,
+    })
 }
 
 // Internal structs for parsing lsblk JSON output
@@ -127,9 +130,38 @@ struct LsblkDevice {
     serial: Option<String>,
     tran: Option<String>, // Transport type (sata, nvme, usb, etc.)
     pkname: Option<String>, // Parent kernel name
+    wwn: Option<String>, // World Wide Name, when the drive reports one
+    fstype: Option<String>,
+    parttype: Option<String>, // GPT type GUID or MBR type code, for the risk rules
     children: Option<Vec<LsblkDevice>>,
 }
 
+#[derive(Debug, Deserialize)]
+struct LsblkVolumeOutput {
+    blockdevices: Vec<LsblkVolume>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LsblkVolume {
+    name: String,
+    #[serde(rename = "type")]
+    device_type: Option<String>,
+    #[serde(deserialize_with = "deserialize_size")]
+    size: Option<String>,
+    mountpoint: Option<String>,
+    label: Option<String>,
+    fstype: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_size")]
+    fsavail: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    rm: bool,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    hotplug: bool,
+    tran: Option<String>,
+    model: Option<String>,
+    children: Option<Vec<LsblkVolume>>,
+}
+
 pub trait DeviceDiscovery {
     fn discover_devices(&self) -> Result<Vec<Device>, Box<dyn std::error::Error>>;
 }
@@ -151,11 +183,12 @@ impl LinuxDeviceDiscovery {
         }
     }
 
-    fn run_lsblk(&self) -> Result<LsblkOutput, Box<dyn std::error::Error>> {
+    #[tracing::instrument(name = "lsblk", skip_all)]
+    fn run_lsblk(&self) -> Result<String, Box<dyn std::error::Error>> {
         let output = Command::new("lsblk")
             .args(&[
                 "-J", // JSON output
-                "-o", "NAME,TYPE,SIZE,MOUNTPOINT,MODEL,SERIAL,TRAN,PKNAME",
+                "-o", "NAME,TYPE,SIZE,MOUNTPOINT,MODEL,SERIAL,TRAN,PKNAME,WWN,FSTYPE,PARTTYPE",
                 "-b", // Show sizes in bytes
             ])
             .output()
@@ -177,9 +210,15 @@ impl LinuxDeviceDiscovery {
             ).into());
         }
 
-        let stdout = String::from_utf8(output.stdout)?;
-        let lsblk_output: LsblkOutput = serde_json::from_str(&stdout)?;
-        Ok(lsblk_output)
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// The disks listed in `lsblk -J` output, before enrichment. Every util-linux
+    /// release formats this a little differently, so malformed input is an error,
+    /// never a panic.
+    pub fn devices_from_lsblk_json(&self, json: &str) -> Result<Vec<Device>, Box<dyn std::error::Error>> {
+        let lsblk_output: LsblkOutput = serde_json::from_str(json)?;
+        Ok(lsblk_output.blockdevices.iter().filter_map(|device| self.process_device(device)).collect())
     }
 
     fn parse_size(&self, size_str: Option<&String>) -> u64 {
@@ -194,10 +233,22 @@ impl LinuxDeviceDiscovery {
             "nvme" => "NVMe".to_string(),
             "usb" => "USB".to_string(),
             "ata" => "SATA".to_string(), // ATA is typically SATA
+            "iscsi" => "iSCSI".to_string(),
             other => other.to_uppercase(),
         })
     }
 
+    /// NBD and RBD devices have no transport in lsblk; they're told apart by name
+    fn network_bus(&self, name: &str) -> Option<String> {
+        if name.starts_with("nbd") {
+            Some("NBD".to_string())
+        } else if name.starts_with("rbd") {
+            Some("RBD".to_string())
+        } else {
+            None
+        }
+    }
+
     fn collect_mountpoints(&self, device: &LsblkDevice) -> Vec<String> {
         let mut mountpoints = Vec::new();
         
@@ -218,29 +269,30 @@ impl LinuxDeviceDiscovery {
         mountpoints
     }
 
+    /// Classify by mountpoints alone, without partition types
+    #[cfg(test)]
     fn classify_risk(&self, mountpoints: &[String]) -> RiskLevel {
-        // CRITICAL: Contains root filesystem
-        if mountpoints.iter().any(|mp| mp == "/") {
-            return RiskLevel::Critical;
-        }
-        
-        // HIGH: Any mounted writable volume (excluding special filesystems)
-        let writable_mounts = mountpoints.iter().any(|mp| {
-            !mp.starts_with("/sys") &&
-            !mp.starts_with("/proc") &&
-            !mp.starts_with("/dev") &&
-            !mp.starts_with("/run") &&
-            mp != "/boot/efi" && // EFI system partition is typically read-only
-            !mp.is_empty()
+        let partitions: Vec<Partition> = mountpoints
+            .iter()
+            .map(|mp| Partition { mountpoint: Some(mp.clone()), ..Default::default() })
+            .collect();
+        risk::current().classify(&partitions).0
+    }
+
+    /// The disk and every partition under it, as the risk rules see them
+    fn collect_partitions(&self, device: &LsblkDevice, partitions: &mut Vec<Partition>) {
+        partitions.push(Partition {
+            name: device.name.clone(),
+            mountpoint: device.mountpoint.clone(),
+            fstype: device.fstype.clone(),
+            parttype: device.parttype.clone(),
         });
-        
-        if writable_mounts {
-            RiskLevel::High
-        } else {
-            RiskLevel::Safe
+        for child in device.children.iter().flatten() {
+            self.collect_partitions(child, partitions);
         }
     }
 
+    #[tracing::instrument(name = "enrich_device", skip_all, fields(device = %device.name))]
     fn enrich_device_info(&self, device: &mut Device) {
         if !self.enable_enrichment {
             return;
@@ -249,27 +301,29 @@ impl LinuxDeviceDiscovery {
         // Try to get additional info from smartctl, hdparm, or nvme-cli
         // These commands are non-destructive and read-only
         
-        // Try smartctl first (works for most drives)
-        if let Ok(output) = Command::new("smartctl")
-            .args(&["-i", &device.name])
-            .output() 
-        {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                self.parse_smartctl_output(&stdout, device);
+        // Try smartctl first (works for most drives). Results are cached per serial
+        // until the next hotplug event, since smartctl can take seconds per disk
+        match probe_cache::run(&device.name, device.serial.as_deref(), "smartctl", &["-i", "-j", &device.name]) {
+            // Exit status is a bit mask; the JSON is complete even when it's non-zero
+            Some(output) if SmartctlInfo::parse(&output.stdout).is_some() => {
+                self.apply_smartctl_json(&output.stdout, device);
+            }
+            // smartctl before 7.0 has no JSON output
+            _ => {
+                if let Some(output) = probe_cache::run(&device.name, device.serial.as_deref(), "smartctl", &["-i", &device.name]) {
+                    if output.success {
+                        self.parse_smartctl_output(&output.stdout, device);
+                    }
+                }
             }
         }
 
         // Try hdparm for SATA devices if we don't have complete info
         if device.bus.as_ref().map_or(false, |b| b == "SATA") && 
            (device.model.is_none() || device.serial.is_none()) {
-            if let Ok(output) = Command::new("hdparm")
-                .args(&["-I", &device.name])
-                .output()
-            {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    self.parse_hdparm_output(&stdout, device);
+            if let Some(output) = probe_cache::run(&device.name, device.serial.as_deref(), "hdparm", &["-I", &device.name]) {
+                if output.success {
+                    self.parse_hdparm_output(&output.stdout, device);
                 }
             }
         }
@@ -277,18 +331,64 @@ impl LinuxDeviceDiscovery {
         // Try nvme-cli for NVMe devices if we don't have complete info
         if device.bus.as_ref().map_or(false, |b| b == "NVMe") &&
            (device.model.is_none() || device.serial.is_none()) {
-            if let Ok(output) = Command::new("nvme")
-                .args(&["id-ctrl", &device.name])
-                .output()
-            {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    self.parse_nvme_output(&stdout, device);
+            if let Some(output) = probe_cache::run(&device.name, device.serial.as_deref(), "nvme", &["id-ctrl", &device.name, "-o", "json"]) {
+                if output.success {
+                    self.apply_nvme_json(&output.stdout, device);
                 }
             }
         }
     }
 
+    /// Enrich every device, a few at a time: the probes are slow external commands
+    /// that mostly wait on the drive, so disks are probed side by side
+    #[tracing::instrument(name = "enrich", skip_all, fields(devices = devices.len()))]
+    fn enrich_all(&self, devices: &mut [Device]) {
+        if !self.enable_enrichment || devices.is_empty() {
+            return;
+        }
+        let workers = MAX_PARALLEL_PROBES.min(devices.len());
+        let queue = Mutex::new(devices.iter_mut());
+        // Worker threads don't inherit the current span
+        let span = tracing::Span::current();
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                    match next {
+                        Some(device) => span.in_scope(|| self.enrich_device_info(device)),
+                        None => break,
+                    }
+                });
+            }
+        });
+    }
+
+    fn apply_smartctl_json(&self, output: &str, device: &mut Device) {
+        let Some(info) = SmartctlInfo::parse(output) else {
+            return;
+        };
+        if device.model.is_none() {
+            device.model = info.model();
+        }
+        if device.serial.is_none() {
+            device.serial = info.serial();
+        }
+    }
+
+    fn apply_nvme_json(&self, output: &str, device: &mut Device) {
+        let Some(ctrl) = NvmeIdCtrl::parse(output) else {
+            // Not JSON: an nvme-cli old enough to print the text table regardless
+            self.parse_nvme_output(output, device);
+            return;
+        };
+        if device.model.is_none() {
+            device.model = ctrl.model();
+        }
+        if device.serial.is_none() {
+            device.serial = ctrl.serial();
+        }
+    }
+
     fn parse_smartctl_output(&self, output: &str, device: &mut Device) {
         for line in output.lines() {
             if line.starts_with("Device Model:") && device.model.is_none() {
@@ -342,10 +442,12 @@ impl LinuxDeviceDiscovery {
         let device_name = format!("/dev/{}", lsblk_device.name);
         let capacity_bytes = self.parse_size(lsblk_device.size.as_ref());
         let mountpoints = self.collect_mountpoints(lsblk_device);
-        let risk_level = self.classify_risk(&mountpoints);
-        let bus = self.normalize_transport(lsblk_device.tran.as_ref());
+        let mut partitions = Vec::new();
+        self.collect_partitions(lsblk_device, &mut partitions);
+        let (risk_level, risk_explanation) = risk::current().classify(&partitions);
+        let bus = self.normalize_transport(lsblk_device.tran.as_ref()).or_else(|| self.network_bus(&lsblk_device.name));
 
-        let mut device = Device {
+        let device = Device {
             name: device_name,
             model: lsblk_device.model.clone(),
             serial: lsblk_device.serial.clone(),
@@ -353,25 +455,105 @@ impl LinuxDeviceDiscovery {
             bus,
             mountpoints,
             risk_level,
+            device_id: stable_id(lsblk_device.wwn.as_deref(), lsblk_device.model.as_deref(), lsblk_device.serial.as_deref()),
+            risk_explanation: Some(risk_explanation),
+            capacity_display: None,
         };
 
-        // Try to enrich with additional device information
-        self.enrich_device_info(&mut device);
-
         Some(device)
     }
 }
 
+impl LinuxDeviceDiscovery {
+    /// List filesystems on removable or USB disks, mounted or not, for choosing a backup
+    /// destination. Disks that carry the running system are left out entirely.
+    pub fn discover_removable_targets(&self) -> Result<Vec<RemovableVolume>, Box<dyn std::error::Error>> {
+        let output = Command::new("lsblk")
+            .args([
+                "-J",
+                "-o", "NAME,TYPE,SIZE,MOUNTPOINT,LABEL,FSTYPE,FSAVAIL,RM,HOTPLUG,TRAN,MODEL",
+                "-b",
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "lsblk failed with exit code: {}",
+                output.status.code().unwrap_or(-1)
+            ).into());
+        }
+
+        let lsblk_output: LsblkVolumeOutput = serde_json::from_slice(&output.stdout)?;
+        Ok(self.collect_removable_volumes(&lsblk_output.blockdevices))
+    }
+
+    fn collect_removable_volumes(&self, disks: &[LsblkVolume]) -> Vec<RemovableVolume> {
+        let mut volumes = Vec::new();
+        for disk in disks {
+            if disk.device_type.as_deref() != Some("disk") {
+                continue;
+            }
+            let removable = disk.rm || disk.hotplug
+                || disk.tran.as_deref().is_some_and(|t| t.eq_ignore_ascii_case("usb"));
+            if !removable {
+                continue;
+            }
+            // A removable disk carrying the running system is never a destination
+            let carries_system = self.collect_volume_mountpoints(disk).iter()
+                .any(|mp| mp == "/" || mp.starts_with("/boot"));
+            if carries_system {
+                continue;
+            }
+            self.collect_candidates(disk, disk, &mut volumes);
+        }
+        volumes
+    }
+
+    fn collect_volume_mountpoints(&self, node: &LsblkVolume) -> Vec<String> {
+        let mut mountpoints: Vec<String> = node.mountpoint.iter().cloned().collect();
+        for child in node.children.iter().flatten() {
+            mountpoints.extend(self.collect_volume_mountpoints(child));
+        }
+        mountpoints
+    }
+
+    fn collect_candidates(&self, disk: &LsblkVolume, node: &LsblkVolume, volumes: &mut Vec<RemovableVolume>) {
+        let mountpoint = node.mountpoint.clone().filter(|mp| !mp.is_empty());
+        let is_leaf = node.children.as_ref().is_none_or(|c| c.is_empty());
+        // Mounted or mountable filesystems, plus blank leaves so the user learns they need formatting
+        let candidate = node.fstype.as_deref() != Some("swap")
+            && (mountpoint.is_some() || node.fstype.is_some() || is_leaf);
+
+        if candidate {
+            volumes.push(RemovableVolume {
+                device: format!("/dev/{}", node.name),
+                backing_device: format!("/dev/{}", disk.name),
+                mountpoint,
+                label: node.label.clone().filter(|l| !l.is_empty()),
+                filesystem: node.fstype.clone(),
+                capacity_bytes: self.parse_size(node.size.as_ref()),
+                free_bytes: node.fsavail.as_ref().and_then(|s| s.parse().ok()),
+                bus: self.normalize_transport(disk.tran.as_ref()),
+                model: disk.model.as_ref().map(|m| m.trim().to_string()),
+            });
+        }
+
+        for child in node.children.iter().flatten() {
+            self.collect_candidates(disk, child, volumes);
+        }
+    }
+}
+
 impl DeviceDiscovery for LinuxDeviceDiscovery {
+    #[tracing::instrument(name = "discover", skip_all)]
     fn discover_devices(&self) -> Result<Vec<Device>, Box<dyn std::error::Error>> {
-        let lsblk_output = self.run_lsblk()?;
-        
-        let mut devices = Vec::new();
-        for lsblk_device in &lsblk_output.blockdevices {
-            if let Some(device) = self.process_device(lsblk_device) {
-                devices.push(device);
-            }
+        let mut devices = self.devices_from_lsblk_json(&self.run_lsblk()?)?;
+        self.enrich_all(&mut devices);
+        // Drives whose serial only enrichment could read get their identity now
+        for device in devices.iter_mut().filter(|device| device.device_id.is_none()) {
+            device.device_id = stable_id(None, device.model.as_deref(), device.serial.as_deref());
         }
+        crate::containers::flag_devices(&mut devices, &crate::containers::storage_roots());
 
         Ok(devices)
     }
@@ -426,6 +608,7 @@ mod tests {
                 "serial": "WD-WCC4N7ABCDEF",
                 "tran": "sata",
                 "pkname": null,
+                "wwn": "0x50014EE2B5A1C3D4",
                 "children": [
                     {
                         "name": "sdb1",
@@ -475,6 +658,18 @@ mod tests {
         assert_eq!(children[1].mountpoint, Some("/".to_string()));
     }
 
+    #[test]
+    fn test_devices_from_lsblk_json() {
+        let discovery = create_test_discovery();
+        let devices = discovery.devices_from_lsblk_json(SAMPLE_LSBLK_JSON).unwrap();
+        assert!(devices.iter().all(|device| device.name.starts_with("/dev/")));
+        assert_eq!(devices[0].name, "/dev/sda");
+
+        for malformed in ["", "[]", r#"{"blockdevices": null}"#, r#"{"blockdevices": [{"name": 7}]}"#] {
+            assert!(discovery.devices_from_lsblk_json(malformed).is_err(), "{} parsed", malformed);
+        }
+    }
+
     #[test]
     fn test_size_parsing() {
         let discovery = create_test_discovery();
@@ -524,6 +719,9 @@ mod tests {
         assert_eq!(discovery.normalize_transport(Some(&"usb".to_string())), Some("USB".to_string()));
         assert_eq!(discovery.normalize_transport(Some(&"scsi".to_string())), Some("SCSI".to_string()));
         assert_eq!(discovery.normalize_transport(None), None);
+        assert_eq!(discovery.normalize_transport(Some(&"iscsi".to_string())), Some("iSCSI".to_string()));
+        assert_eq!(discovery.network_bus("nbd0"), Some("NBD".to_string()));
+        assert_eq!(discovery.network_bus("sda"), None);
     }
 
     #[test]
@@ -599,6 +797,7 @@ mod tests {
         assert_eq!(device.model, Some("WD20EZRZ-00Z5HB0".to_string()));
         assert_eq!(device.bus, Some("SATA".to_string()));
         assert!(matches!(device.risk_level, RiskLevel::High));
+        assert_eq!(device.device_id.as_deref(), Some("wwn:0x50014ee2b5a1c3d4"));
         
         // Test USB drive (SAFE - no mountpoints)
         let sdc = &lsblk_output.blockdevices[2];
@@ -608,6 +807,8 @@ mod tests {
         assert_eq!(device.bus, Some("USB".to_string()));
         assert!(matches!(device.risk_level, RiskLevel::Safe));
         assert_eq!(device.mountpoints.len(), 0);
+        // No WWN: identity falls back to model and serial
+        assert_eq!(device.device_id, crate::device_id::stable_id(None, Some("SanDisk Ultra"), Some("4C530001171122115172")));
     }
 
     #[test]
@@ -634,6 +835,9 @@ User Capacity:    1,000,204,886,016 bytes [1.00 TB]
             bus: Some("NVMe".to_string()),
             mountpoints: vec![],
             risk_level: RiskLevel::Safe,
+            device_id: None,
+            risk_explanation: None,
+            capacity_display: None,
         };
 
         discovery.parse_smartctl_output(smartctl_output, &mut device);
@@ -642,6 +846,39 @@ User Capacity:    1,000,204,886,016 bytes [1.00 TB]
         assert_eq!(device.serial, Some("S649NX0R123456A".to_string()));
     }
 
+    #[test]
+    fn test_json_enrichment() {
+        let discovery = create_test_discovery();
+        let blank = |name: &str| Device {
+            name: name.to_string(),
+            model: None,
+            serial: None,
+            capacity_bytes: 0,
+            bus: Some("NVMe".to_string()),
+            mountpoints: vec![],
+            risk_level: RiskLevel::Safe,
+            device_id: None,
+            risk_explanation: None,
+            capacity_display: None,
+        };
+
+        let mut device = blank("/dev/sdc");
+        let smartctl = r#"{"json_format_version":[1,0],"smartctl":{"exit_status":4},"model_name":"SanDisk Ultra","serial_number":"4C530001171122115172"}"#;
+        discovery.apply_smartctl_json(smartctl, &mut device);
+        assert_eq!(device.model, Some("SanDisk Ultra".to_string()));
+        assert_eq!(device.serial, Some("4C530001171122115172".to_string()));
+
+        let mut device = blank("/dev/nvme0n1");
+        discovery.apply_nvme_json(r#"{"sn":"S649NX0R123456A     ","mn":"Samsung SSD 980 1TB   "}"#, &mut device);
+        assert_eq!(device.model, Some("Samsung SSD 980 1TB".to_string()));
+        assert_eq!(device.serial, Some("S649NX0R123456A".to_string()));
+
+        // Text from an nvme-cli too old for JSON still goes through the scraper
+        let mut device = blank("/dev/nvme0n1");
+        discovery.apply_nvme_json("sn        : S649NX0R123456A\nmn        : Samsung SSD 980 1TB\n", &mut device);
+        assert_eq!(device.serial, Some("S649NX0R123456A".to_string()));
+    }
+
     #[test]
     fn test_device_discovery_trait() {
         let discovery = LinuxDeviceDiscovery::new_without_enrichment();
@@ -653,6 +890,29 @@ User Capacity:    1,000,204,886,016 bytes [1.00 TB]
         assert!(result.is_ok() || result.is_err());
     }
     
+    #[test]
+    fn test_enrich_all_keeps_order() {
+        // Probes against missing devices fail; every device must come back, in order
+        let mut devices: Vec<Device> = (0..9)
+            .map(|i| Device {
+                name: format!("/dev/securewipe-test-missing{}", i),
+                model: Some(format!("Model {}", i)),
+                serial: None,
+                capacity_bytes: i,
+                bus: Some("SATA".to_string()),
+                mountpoints: Vec::new(),
+                risk_level: RiskLevel::Safe,
+                device_id: None,
+                risk_explanation: None,
+                capacity_display: None,
+            })
+            .collect();
+        LinuxDeviceDiscovery::new().enrich_all(&mut devices);
+        let capacities: Vec<u64> = devices.iter().map(|device| device.capacity_bytes).collect();
+        assert_eq!(capacities, (0..9).collect::<Vec<u64>>());
+        assert_eq!(devices[4].model, Some("Model 4".to_string()));
+    }
+
     #[test]
     fn test_risk_level_serialization() {
         let risk = RiskLevel::Critical;
@@ -693,6 +953,9 @@ User Capacity:    1,000,204,886,016 bytes [1.00 TB]
             bus: Some("SATA".to_string()),
             mountpoints: vec!["/".to_string()],
             risk_level: RiskLevel::Critical,
+            device_id: None,
+            risk_explanation: None,
+            capacity_display: None,
         };
         
         assert_eq!(device.name, "/dev/sda");
@@ -712,6 +975,9 @@ User Capacity:    1,000,204,886,016 bytes [1.00 TB]
             bus: Some("NVMe".to_string()),
             mountpoints: vec![],
             risk_level: RiskLevel::Safe,
+            device_id: None,
+            risk_explanation: None,
+            capacity_display: None,
         };
         
         let json = serde_json::to_string(&device);
@@ -721,4 +987,57 @@ User Capacity:    1,000,204,886,016 bytes [1.00 TB]
         assert_eq!(deserialized.name, device.name);
         assert_eq!(deserialized.capacity_bytes, device.capacity_bytes);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_removable_volume_collection() {
+        // Mixes the boolean (newer) and "0"/"1" (older) lsblk flag formats
+        let json = r#"
+        {
+            "blockdevices": [
+                {"name": "sda", "type": "disk", "size": 500107862016, "mountpoint": null,
+                 "label": null, "fstype": null, "fsavail": null, "rm": false, "hotplug": false,
+                 "tran": "sata", "model": "Samsung SSD",
+                 "children": [
+                    {"name": "sda1", "type": "part", "size": 500106813440, "mountpoint": "/media/data",
+                     "label": "data", "fstype": "ext4", "fsavail": 1000, "rm": false, "hotplug": false,
+                     "tran": null, "model": null}
+                 ]},
+                {"name": "sdb", "type": "disk", "size": "32017047552", "mountpoint": null,
+                 "label": null, "fstype": null, "fsavail": null, "rm": "1", "hotplug": "1",
+                 "tran": "usb", "model": "SanDisk Ultra   ",
+                 "children": [
+                    {"name": "sdb1", "type": "part", "size": "32015998976", "mountpoint": "/media/user/BACKUP",
+                     "label": "BACKUP", "fstype": "exfat", "fsavail": "30000000000", "rm": "1", "hotplug": "1",
+                     "tran": null, "model": null},
+                    {"name": "sdb2", "type": "part", "size": "1048576", "mountpoint": null,
+                     "label": "", "fstype": "vfat", "fsavail": null, "rm": "1", "hotplug": "1",
+                     "tran": null, "model": null}
+                 ]},
+                {"name": "sdc", "type": "disk", "size": 64023257088, "mountpoint": "/",
+                 "label": null, "fstype": "ext4", "fsavail": 1, "rm": true, "hotplug": true,
+                 "tran": "usb", "model": "Live USB"}
+            ]
+        }
+        "#;
+
+        let output: LsblkVolumeOutput = serde_json::from_str(json).unwrap();
+        let volumes = create_test_discovery().collect_removable_volumes(&output.blockdevices);
+
+        // Internal disk and the live-system USB stick are excluded
+        assert_eq!(volumes.len(), 2);
+        let volume = &volumes[0];
+        assert_eq!(volume.device, "/dev/sdb1");
+        assert_eq!(volume.backing_device, "/dev/sdb");
+        assert_eq!(volume.mountpoint.as_deref(), Some("/media/user/BACKUP"));
+        assert_eq!(volume.label.as_deref(), Some("BACKUP"));
+        assert_eq!(volume.filesystem.as_deref(), Some("exfat"));
+        assert_eq!(volume.free_bytes, Some(30000000000));
+        assert_eq!(volume.bus.as_deref(), Some("USB"));
+        assert_eq!(volume.model.as_deref(), Some("SanDisk Ultra"));
+
+        // Unmounted partitions are offered so they can be mounted on demand
+        assert_eq!(volumes[1].device, "/dev/sdb2");
+        assert!(volumes[1].mountpoint.is_none());
+        assert!(volumes[1].label.is_none());
+    }
+}
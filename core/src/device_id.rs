@@ -0,0 +1,44 @@
+//! Stable identity for a physical drive, independent of the `/dev` name it happens to
+//! get on this boot. Inventory rows, certificates and log events carry it so the same
+//! drive is recognised after a reboot, a re-plug or on another port.
+//!
+//! The World Wide Name is used when the drive reports one; otherwise a hash of model
+//! and serial. Drives with neither a WWN nor a serial have no stable identity.
+
+use sha2::{Digest, Sha256};
+
+/// `wwn:<wwn>` or `sn:<first 16 hex digits of SHA-256 over model and serial>`
+pub fn stable_id(wwn: Option<&str>, model: Option<&str>, serial: Option<&str>) -> Option<String> {
+    fn clean(value: Option<&str>) -> Option<&str> {
+        value.map(str::trim).filter(|v| !v.is_empty() && *v != "Unknown")
+    }
+    if let Some(wwn) = clean(wwn) {
+        return Some(format!("wwn:{}", wwn.to_ascii_lowercase()));
+    }
+    let serial = clean(serial)?;
+    let digest = Sha256::digest(format!("{}\n{}", clean(model).unwrap_or(""), serial).as_bytes());
+    Some(format!("sn:{}", &format!("{:x}", digest)[..16]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_id() {
+        assert_eq!(
+            stable_id(Some("0x5002538E40B1BA45"), Some("Samsung SSD 870"), Some("S5Y1NX0R123456")),
+            Some("wwn:0x5002538e40b1ba45".to_string())
+        );
+
+        let by_serial = stable_id(None, Some("Samsung SSD 870"), Some("S5Y1NX0R123456")).unwrap();
+        assert!(by_serial.starts_with("sn:"));
+        assert_eq!(by_serial.len(), 19);
+        // Padding from the tools doesn't change the identity, the model does
+        assert_eq!(stable_id(Some(" "), Some("Samsung SSD 870 "), Some(" S5Y1NX0R123456")), Some(by_serial.clone()));
+        assert_ne!(stable_id(None, Some("Other"), Some("S5Y1NX0R123456")), Some(by_serial));
+
+        assert_eq!(stable_id(None, Some("Samsung SSD 870"), None), None);
+        assert_eq!(stable_id(None, Some("Unknown"), Some("Unknown")), None);
+    }
+}
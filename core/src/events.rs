@@ -0,0 +1,282 @@
+//! The CLI's stderr event stream: one JSON record per line, each wrapping a log line,
+//! a progress step, a command's result or an error in a versioned envelope
+//! (`EventRecord`). Everything a command reports while it runs goes through here, so
+//! the UI can read stderr line by line without guessing what each line is. The
+//! protocol is specified in docs/EVENT_PROTOCOL.md.
+//!
+//! Log records are filtered by level, per module: `info` and above by default,
+//! changed by `SECUREWIPE_LOG` (or `RUST_LOG`) directives such as `warn,wipe=debug`
+//! and by the `-q`/`-v`/`-vv` flags. Progress, result and error records are never
+//! filtered; the UI depends on them.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::panic::Location;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+pub use securewipe_types::{EventRecord, EventType, LogLevel, EVENT_PROTOCOL_VERSION};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+static FILTER: RwLock<Option<LogFilter>> = RwLock::new(None);
+
+/// Which log records are written: a default threshold and per-module overrides.
+/// A threshold of `None` turns logging off.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    pub default: Option<LogLevel>,
+    pub modules: Vec<(String, Option<LogLevel>)>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self { default: Some(LogLevel::Info), modules: Vec::new() }
+    }
+}
+
+impl LogFilter {
+    /// Parse comma-separated directives: `level` sets the default, `module=level` one
+    /// module's threshold. Modules may be given as `wipe` or `securewipe::wipe`.
+    /// Directives that don't parse are ignored, as `RUST_LOG` does.
+    pub fn parse(directives: &str) -> Self {
+        let mut filter = Self::default();
+        for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    if let Some(level) = parse_level(level) {
+                        let module = module.trim().trim_start_matches("securewipe::");
+                        filter.modules.push((module.to_string(), level));
+                    }
+                }
+                None => {
+                    if let Some(level) = parse_level(directive) {
+                        filter.default = level;
+                    }
+                }
+            }
+        }
+        filter
+    }
+
+    /// `SECUREWIPE_LOG`, else `RUST_LOG`, else `info`
+    pub fn from_env() -> Self {
+        std::env::var("SECUREWIPE_LOG")
+            .or_else(|_| std::env::var("RUST_LOG"))
+            .map(|directives| Self::parse(&directives))
+            .unwrap_or_default()
+    }
+
+    pub fn enabled(&self, module: &str, level: LogLevel) -> bool {
+        let threshold = self
+            .modules
+            .iter()
+            .rev()
+            .find(|(name, _)| name == module)
+            .map_or(self.default, |(_, threshold)| *threshold);
+        threshold.is_some_and(|threshold| level >= threshold)
+    }
+}
+
+/// `Some(None)` for "off"
+fn parse_level(level: &str) -> Option<Option<LogLevel>> {
+    match level.trim().to_ascii_lowercase().as_str() {
+        "off" => Some(None),
+        "trace" => Some(Some(LogLevel::Trace)),
+        "debug" => Some(Some(LogLevel::Debug)),
+        "info" => Some(Some(LogLevel::Info)),
+        "warn" | "warning" => Some(Some(LogLevel::Warn)),
+        "error" => Some(Some(LogLevel::Error)),
+        _ => None,
+    }
+}
+
+/// Apply the environment's directives, then the command line's `-q` (warnings and
+/// errors only) or `-v`/`-vv` (debug/trace), which replace the default threshold
+/// but keep per-module overrides
+pub fn configure(quiet: bool, verbose: u8) {
+    let mut filter = LogFilter::from_env();
+    if quiet {
+        filter.default = Some(LogLevel::Warn);
+    } else if verbose == 1 {
+        filter.default = Some(LogLevel::Debug);
+    } else if verbose > 1 {
+        filter.default = Some(LogLevel::Trace);
+    }
+    set_filter(filter);
+}
+
+pub fn set_filter(filter: LogFilter) {
+    if let Ok(mut current) = FILTER.write() {
+        *current = Some(filter);
+    }
+}
+
+fn enabled(module: &str, level: LogLevel) -> bool {
+    if let Ok(current) = FILTER.read() {
+        if let Some(filter) = current.as_ref() {
+            return filter.enabled(module, level);
+        }
+    }
+    let filter = LogFilter::from_env();
+    let shown = filter.enabled(module, level);
+    set_filter(filter);
+    shown
+}
+
+/// The core module a call came from, e.g. "wipe" for src/wipe.rs
+fn caller_module(location: &Location) -> String {
+    Path::new(location.file())
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Wrap `payload` in the next envelope of this run's stream
+pub fn record(event_type: EventType, payload: Value) -> EventRecord {
+    EventRecord {
+        v: EVENT_PROTOCOL_VERSION,
+        event_type,
+        id: NEXT_ID.fetch_add(1, Ordering::SeqCst),
+        ts: chrono::Utc::now().to_rfc3339(),
+        payload,
+    }
+}
+
+/// Write one record to stderr. The line is written under the stderr lock, so records
+/// from the parallel discovery and copy threads never interleave.
+pub fn emit(event_type: EventType, payload: Value) {
+    if let Ok(line) = serde_json::to_string(&record(event_type, payload)) {
+        let _ = writeln!(std::io::stderr().lock(), "{}", line);
+    }
+}
+
+/// A free-text line for the operator, attributed to the calling module
+#[track_caller]
+pub fn log(level: LogLevel, message: &str) {
+    log_with(level, message, Value::Null);
+}
+
+/// A `LogMessage` with machine-readable context, e.g. a backup step's `step_id` and data
+#[track_caller]
+pub fn log_with(level: LogLevel, message: &str, context: Value) {
+    log_from(&caller_module(Location::caller()), level, message, context);
+}
+
+/// A `LogMessage` on behalf of `module`, for lines that don't come straight from
+/// module code, such as tracing events and span timings
+pub fn log_from(module: &str, level: LogLevel, message: &str, context: Value) {
+    if !enabled(module, level) {
+        return;
+    }
+    let mut payload = json!({
+        "level": level,
+        "message": message,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "module": module,
+    });
+    if let (Some(payload), Value::Object(context)) = (payload.as_object_mut(), context) {
+        for (key, value) in context {
+            payload.entry(key).or_insert(value);
+        }
+    }
+    emit(EventType::Log, payload);
+}
+
+#[track_caller]
+pub fn trace(message: &str) {
+    log(LogLevel::Trace, message);
+}
+
+#[track_caller]
+pub fn debug(message: &str) {
+    log(LogLevel::Debug, message);
+}
+
+#[track_caller]
+pub fn info(message: &str) {
+    log(LogLevel::Info, message);
+}
+
+#[track_caller]
+pub fn warn(message: &str) {
+    log(LogLevel::Warn, message);
+}
+
+#[track_caller]
+pub fn error(message: &str) {
+    log(LogLevel::Error, message);
+}
+
+/// A command's result or failure document
+pub fn emit_document(document: &impl Serialize) {
+    if let Ok(value) = serde_json::to_value(document) {
+        emit(event_type_of(&value), value);
+    }
+}
+
+/// How a document built by a command is classified: steps are progress, `status:
+/// "error"` documents are errors, level/message pairs are logs, anything else is a
+/// command's result
+pub fn event_type_of(document: &Value) -> EventType {
+    if document.get("step").is_some() {
+        EventType::Progress
+    } else if document.get("status").and_then(Value::as_str) == Some("error") {
+        EventType::Error
+    } else if document.get("level").is_some() && document.get("message").is_some() {
+        EventType::Log
+    } else {
+        EventType::Result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_are_versioned_and_ordered() {
+        let first = record(EventType::Log, json!({"level": "info", "message": "a"}));
+        let second = record(EventType::Progress, json!({"step": "s", "timestamp": "t"}));
+        assert_eq!(first.v, EVENT_PROTOCOL_VERSION);
+        assert!(second.id > first.id);
+
+        let line = serde_json::to_value(&second).unwrap();
+        assert_eq!(line["type"], "progress");
+        assert_eq!(line["payload"]["step"], "s");
+        assert!(line["ts"].is_string());
+    }
+
+    #[test]
+    fn test_event_type_of() {
+        assert_eq!(event_type_of(&json!({"step": "wipe_completed", "timestamp": "t"})), EventType::Progress);
+        assert_eq!(event_type_of(&json!({"status": "error", "error": "e"})), EventType::Error);
+        assert_eq!(event_type_of(&json!({"level": "warn", "message": "m"})), EventType::Log);
+        assert_eq!(event_type_of(&json!({"cmd": "cert", "status": "success"})), EventType::Result);
+    }
+
+    #[test]
+    fn test_log_filter_directives() {
+        assert_eq!(LogFilter::parse(""), LogFilter::default());
+
+        let filter = LogFilter::parse("warn, securewipe::wipe=debug,backup=off,bogus=loud");
+        assert_eq!(filter.default, Some(LogLevel::Warn));
+        assert!(!filter.enabled("cmd", LogLevel::Info));
+        assert!(filter.enabled("cmd", LogLevel::Error));
+        assert!(filter.enabled("wipe", LogLevel::Debug));
+        assert!(!filter.enabled("wipe", LogLevel::Trace));
+        assert!(!filter.enabled("backup", LogLevel::Error));
+        assert_eq!(filter.modules.len(), 2);
+
+        // Later directives for the same module win
+        assert!(LogFilter::parse("wipe=error,wipe=trace").enabled("wipe", LogLevel::Trace));
+        assert!(!LogFilter::parse("off").enabled("cmd", LogLevel::Error));
+    }
+
+    #[test]
+    fn test_caller_module() {
+        assert_eq!(caller_module(Location::caller()), "events");
+    }
+}
@@ -0,0 +1,51 @@
+//! The one wildcard SecureWipe's rules support: `*` for any run of characters. Used for
+//! risk-rule mountpoints and backup `--exclude` patterns.
+
+use std::path::Path;
+
+/// `*` matches any run of characters, everything else itself
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            let Some(text) = text.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=text.len()).filter(|&i| text.is_char_boundary(i)).any(|i| glob_match(rest, &text[i..]))
+        }
+    }
+}
+
+/// Whether `path` is excluded by `pattern`: a pattern containing `/` is matched
+/// against the whole path, any other against each of its components, so `*.tmp` or
+/// `node_modules` excludes at any depth
+pub fn path_excluded(pattern: &str, path: &Path) -> bool {
+    if pattern.contains('/') {
+        return glob_match(pattern, &path.to_string_lossy());
+    }
+    path.iter().any(|component| glob_match(pattern, &component.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("/", "/"));
+        assert!(!glob_match("/", "/home"));
+        assert!(glob_match("/sys*", "/sys/firmware"));
+        assert!(glob_match("/srv/*/data", "/srv/a/b/data"));
+        assert!(!glob_match("/srv/*/data", "/srv/a/b/logs"));
+    }
+
+    #[test]
+    fn test_path_excluded() {
+        let path = Path::new("/home/op/project/node_modules/left-pad/index.js");
+        assert!(path_excluded("node_modules", path));
+        assert!(path_excluded("*.js", path));
+        assert!(!path_excluded("*.tmp", path));
+        assert!(path_excluded("/home/op/project/*", path));
+        assert!(!path_excluded("/home/other/*", path));
+    }
+}
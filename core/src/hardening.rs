@@ -0,0 +1,320 @@
+//! Privilege minimization for `wipe --hardened`. Before the wipe starts the process
+//! gives up every capability it doesn't need (`WIPE_CAPABILITIES`), for itself and the
+//! tools it runs. Each overwrite pass then runs on a thread of its own that drops the
+//! rest once the device is open, and writes under a seccomp filter that only allows
+//! the system calls an overwrite makes. Files can still be opened read-only (sysfs
+//! reads); opening anything for writing, exec, sockets and the like fail with EPERM,
+//! so a compromised write loop can't reach beyond the device it was handed.
+//!
+//! Capabilities and seccomp filters are per thread on Linux; only the calling thread
+//! and threads it starts afterwards are affected.
+
+use libc::c_int;
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+use libc::{c_long, sock_filter, sock_fprog};
+
+/// CAP_DAC_OVERRIDE: open device nodes and the data directory regardless of mode bits
+pub const CAP_DAC_OVERRIDE: u32 = 1;
+/// CAP_SETPCAP: needed to shrink the bounding set; dropped along with the rest
+pub const CAP_SETPCAP: u32 = 8;
+/// CAP_IPC_LOCK: keep key material out of swap
+pub const CAP_IPC_LOCK: u32 = 14;
+/// CAP_SYS_RAWIO: ATA and SCSI passthrough (hdparm, smartctl)
+pub const CAP_SYS_RAWIO: u32 = 17;
+/// CAP_SYS_ADMIN: NVMe admin commands, unmounting, partition table re-reads
+pub const CAP_SYS_ADMIN: u32 = 21;
+/// CAP_SYS_NICE: `--nice` and `--ionice` above normal priority
+pub const CAP_SYS_NICE: u32 = 23;
+
+/// What a wipe and the tools it runs still need after `limit_capabilities`
+pub const WIPE_CAPABILITIES: &[u32] = &[CAP_DAC_OVERRIDE, CAP_IPC_LOCK, CAP_SYS_RAWIO, CAP_SYS_ADMIN, CAP_SYS_NICE];
+
+const LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+const PR_CAP_AMBIENT_CLEAR_ALL: libc::c_ulong = 4;
+
+// Not in the libc crate: linux/audit.h and linux/bpf_common.h
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xC000_003E;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xC000_00B7;
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const BPF_ALU: u16 = 0x04;
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const BPF_AND: u16 = 0x50;
+
+/// Offsets into `struct seccomp_data`
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const SECCOMP_DATA_NR: u32 = 0;
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const SECCOMP_DATA_ARCH: u32 = 4;
+/// Low 32 bits of the third argument (little endian)
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const SECCOMP_DATA_ARG2: u32 = 16 + 2 * 8;
+
+/// System calls an overwrite pass makes: positioned writes and syncs on the open
+/// device, progress events to stdout, memory, clocks, sleeps while paused for power,
+/// and what the runtime needs to unwind and end the thread
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const OVERWRITE_SYSCALLS: &[c_long] = &[
+    libc::SYS_read,
+    libc::SYS_write,
+    libc::SYS_readv,
+    libc::SYS_writev,
+    libc::SYS_pread64,
+    libc::SYS_pwrite64,
+    libc::SYS_lseek,
+    libc::SYS_fsync,
+    libc::SYS_fdatasync,
+    libc::SYS_close,
+    libc::SYS_fstat,
+    libc::SYS_newfstatat,
+    libc::SYS_statx,
+    libc::SYS_getdents64,
+    libc::SYS_fcntl,
+    libc::SYS_futex,
+    libc::SYS_clock_gettime,
+    libc::SYS_clock_nanosleep,
+    libc::SYS_nanosleep,
+    libc::SYS_sched_yield,
+    libc::SYS_sched_getaffinity,
+    libc::SYS_brk,
+    libc::SYS_mmap,
+    libc::SYS_munmap,
+    libc::SYS_mremap,
+    libc::SYS_mprotect,
+    libc::SYS_madvise,
+    libc::SYS_getrandom,
+    libc::SYS_rt_sigprocmask,
+    libc::SYS_rt_sigreturn,
+    libc::SYS_sigaltstack,
+    libc::SYS_getpid,
+    libc::SYS_gettid,
+    libc::SYS_tgkill,
+    libc::SYS_rseq,
+    libc::SYS_exit,
+    libc::SYS_exit_group,
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum HardeningError {
+    #[error("Hardening is not supported on this platform: {0}")]
+    Unsupported(&'static str),
+    #[error("Failed to drop capabilities: {0}")]
+    Capabilities(String),
+    #[error("Failed to install the seccomp filter: {0}")]
+    Seccomp(String),
+}
+
+#[repr(C)]
+struct CapHeader {
+    version: u32,
+    pid: c_int,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct CapData {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+/// 64-bit capability masks as the kernel's two 32-bit words
+fn split(mask: u64) -> [u32; 2] {
+    [mask as u32, (mask >> 32) as u32]
+}
+
+fn join(words: [u32; 2]) -> u64 {
+    words[0] as u64 | (words[1] as u64) << 32
+}
+
+pub fn capability_mask(capabilities: &[u32]) -> u64 {
+    capabilities.iter().filter(|&&cap| cap < 64).fold(0, |mask, &cap| mask | 1 << cap)
+}
+
+fn last_capability() -> u32 {
+    std::fs::read_to_string("/proc/sys/kernel/cap_last_cap")
+        .ok()
+        .and_then(|text| text.trim().parse().ok())
+        .unwrap_or(40)
+}
+
+/// Effective and permitted sets of the calling thread
+fn current_capabilities() -> Result<(u64, u64), HardeningError> {
+    let mut header = CapHeader { version: LINUX_CAPABILITY_VERSION_3, pid: 0 };
+    let mut data = [CapData::default(); 2];
+    if unsafe { libc::syscall(libc::SYS_capget, &mut header, data.as_mut_ptr()) } != 0 {
+        return Err(HardeningError::Capabilities(format!("capget: {}", std::io::Error::last_os_error())));
+    }
+    Ok((join([data[0].effective, data[1].effective]), join([data[0].permitted, data[1].permitted])))
+}
+
+/// Reduce the calling thread to `keep`: the bounding set (which caps what programs it
+/// runs can get) and the permitted and effective sets. Inheritable and ambient sets
+/// are cleared. Capabilities the thread doesn't have aren't gained. Returns the mask
+/// left permitted.
+pub fn limit_capabilities(keep: &[u32]) -> Result<u64, HardeningError> {
+    let keep = capability_mask(keep);
+    let (effective, permitted) = current_capabilities()?;
+
+    // Without CAP_SETPCAP (not running as root) the bounding set can't shrink, and
+    // the permitted set below is what limits this thread anyway
+    if effective & 1 << CAP_SETPCAP != 0 {
+        for cap in 0..=last_capability() {
+            if keep & 1 << cap != 0 {
+                continue;
+            }
+            let result = unsafe { libc::prctl(libc::PR_CAPBSET_DROP, cap as libc::c_ulong, 0, 0, 0) };
+            // EINVAL: a capability this kernel doesn't know
+            if result != 0 && std::io::Error::last_os_error().raw_os_error() != Some(libc::EINVAL) {
+                return Err(HardeningError::Capabilities(format!(
+                    "bounding set, capability {}: {}",
+                    cap,
+                    std::io::Error::last_os_error()
+                )));
+            }
+        }
+    }
+    // Older kernels have no ambient set; nothing to clear there
+    unsafe { libc::prctl(libc::PR_CAP_AMBIENT, PR_CAP_AMBIENT_CLEAR_ALL, 0, 0, 0) };
+
+    let kept = keep & permitted;
+    let words = split(kept);
+    let mut header = CapHeader { version: LINUX_CAPABILITY_VERSION_3, pid: 0 };
+    let data = [
+        CapData { effective: words[0], permitted: words[0], inheritable: 0 },
+        CapData { effective: words[1], permitted: words[1], inheritable: 0 },
+    ];
+    if unsafe { libc::syscall(libc::SYS_capset, &mut header, data.as_ptr()) } != 0 {
+        return Err(HardeningError::Capabilities(format!("capset: {}", std::io::Error::last_os_error())));
+    }
+    Ok(kept)
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn statement(code: u16, k: u32) -> sock_filter {
+    sock_filter { code, jt: 0, jf: 0, k }
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn jump(code: u16, k: u32, jt: u8, jf: u8) -> sock_filter {
+    sock_filter { code, jt, jf, k }
+}
+
+/// BPF program for `OVERWRITE_SYSCALLS`. A foreign architecture kills the process,
+/// since its syscall numbers mean something else; anything not listed fails with EPERM,
+/// so the wipe stops with an error rather than dying mid-write without a result.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn overwrite_filter() -> Vec<sock_filter> {
+    const LOAD: u16 = (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16;
+    const JEQ: u16 = (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16;
+    const RET: u16 = (libc::BPF_RET | libc::BPF_K) as u16;
+    const AND: u16 = BPF_ALU | BPF_AND | libc::BPF_K as u16;
+    let allow = libc::SECCOMP_RET_ALLOW;
+    let deny = libc::SECCOMP_RET_ERRNO | libc::EPERM as u32;
+    let write_flags = (libc::O_ACCMODE | libc::O_CREAT | libc::O_TRUNC) as u32;
+
+    let mut program = vec![
+        statement(LOAD, SECCOMP_DATA_ARCH),
+        jump(JEQ, AUDIT_ARCH, 1, 0),
+        statement(RET, libc::SECCOMP_RET_KILL_PROCESS),
+        statement(LOAD, SECCOMP_DATA_NR),
+    ];
+    for &syscall in OVERWRITE_SYSCALLS {
+        program.push(jump(JEQ, syscall as u32, 0, 1));
+        program.push(statement(RET, allow));
+    }
+    // openat only without write access, O_CREAT or O_TRUNC
+    program.extend([
+        jump(JEQ, libc::SYS_openat as u32, 0, 4),
+        statement(LOAD, SECCOMP_DATA_ARG2),
+        statement(AND, write_flags),
+        jump(JEQ, 0, 0, 1),
+        statement(RET, allow),
+        statement(RET, deny),
+    ]);
+    program
+}
+
+/// Put the calling thread under the overwrite filter. Sets no_new_privs first, which
+/// the kernel requires of a thread without CAP_SYS_ADMIN and which stops anything it
+/// might exec from gaining privileges.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub fn install_overwrite_filter() -> Result<(), HardeningError> {
+    let mut program = overwrite_filter();
+    let fprog = sock_fprog { len: program.len() as libc::c_ushort, filter: program.as_mut_ptr() };
+    unsafe {
+        if libc::prctl(libc::PR_GET_SECCOMP, 0, 0, 0, 0) < 0 {
+            return Err(HardeningError::Unsupported("the kernel was built without seccomp"));
+        }
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            return Err(HardeningError::Seccomp(format!("no_new_privs: {}", std::io::Error::last_os_error())));
+        }
+        if libc::prctl(libc::PR_SET_SECCOMP, libc::SECCOMP_MODE_FILTER, &fprog as *const sock_fprog) != 0 {
+            return Err(HardeningError::Seccomp(std::io::Error::last_os_error().to_string()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn install_overwrite_filter() -> Result<(), HardeningError> {
+    Err(HardeningError::Unsupported("the seccomp filter is only built for x86_64 and aarch64"))
+}
+
+/// Run `work` on a thread of its own with no capabilities and under the overwrite
+/// filter, and wait for it. The calling thread keeps its privileges.
+pub fn confined<T: Send>(work: impl FnOnce() -> T + Send) -> Result<T, HardeningError> {
+    std::thread::scope(|scope| {
+        let handle = std::thread::Builder::new()
+            .name("overwrite".to_string())
+            .spawn_scoped(scope, || {
+                limit_capabilities(&[])?;
+                install_overwrite_filter()?;
+                Ok(work())
+            })
+            .map_err(|e| HardeningError::Capabilities(format!("cannot start the confined thread: {}", e)))?;
+        handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+    use std::io::{Seek, SeekFrom, Write};
+
+    #[test]
+    fn test_capability_mask() {
+        assert_eq!(capability_mask(&[]), 0);
+        assert_eq!(capability_mask(&[CAP_SYS_RAWIO, CAP_SYS_ADMIN]), 1 << 17 | 1 << 21);
+        assert_eq!(join(split(1 << 40 | 1 << 3)), 1 << 40 | 1 << 3);
+    }
+
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    #[test]
+    fn test_confined_allows_only_the_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("disk.img");
+        let readable = dir.path().join("status");
+        fs::write(&readable, "online").unwrap();
+        let mut file = OpenOptions::new().create(true).truncate(true).write(true).open(&target).unwrap();
+        let before = current_capabilities().unwrap();
+
+        let (written, read, created) = confined(|| {
+            let written = file.seek(SeekFrom::Start(4096)).and_then(|_| file.write_all(b"wiped")).and_then(|_| file.sync_all());
+            let read = fs::read_to_string(&readable);
+            let created = fs::write(dir.path().join("other"), "x");
+            (written, read, created)
+        })
+        .unwrap();
+
+        assert!(written.is_ok());
+        assert_eq!(read.unwrap(), "online");
+        assert_eq!(created.unwrap_err().raw_os_error(), Some(libc::EPERM));
+        assert_eq!(fs::read(&target).unwrap()[4096..], *b"wiped");
+        // The filter and the dropped capabilities stayed on the confined thread
+        fs::write(dir.path().join("other"), "x").unwrap();
+        assert_eq!(current_capabilities().unwrap(), before);
+    }
+}
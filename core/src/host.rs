@@ -0,0 +1,129 @@
+//! Identifiers for the machine itself, as opposed to its drives: the hostname,
+//! systemd's machine ID and the SMBIOS manufacturer, model, serials, UUID and BIOS
+//! version. Wipe and system certificates record them so a certificate can be tied to
+//! the machine the drive came out of, not just the drive. SMBIOS strings come from
+//! `dmidecode` when it runs (it needs root), otherwise from `/sys/class/dmi/id`.
+//! Vendors fill unused SMBIOS fields with placeholders such as "To Be Filled By
+//! O.E.M."; those are dropped rather than recorded as identifiers.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::cert::HostInfo;
+
+const MACHINE_ID_PATH: &str = "/etc/machine-id";
+const HOSTNAME_PATH: &str = "/proc/sys/kernel/hostname";
+const DMI_DIR: &str = "/sys/class/dmi/id";
+
+/// Placeholder strings firmware leaves in SMBIOS fields nobody filled in
+const PLACEHOLDERS: &[&str] = &[
+    "to be filled by o.e.m.",
+    "default string",
+    "not specified",
+    "not applicable",
+    "system manufacturer",
+    "system product name",
+    "system serial number",
+    "chassis serial number",
+    "none",
+    "n/a",
+    "0123456789",
+    "123456789",
+    "00000000-0000-0000-0000-000000000000",
+    "03000200-0400-0500-0006-000700080009",
+    "ffffffff-ffff-ffff-ffff-ffffffffffff",
+];
+
+/// Read this machine's identifiers; anything unreadable is left out
+pub fn probe() -> HostInfo {
+    HostInfo {
+        hostname: hostname(),
+        machine_id: std::fs::read_to_string(MACHINE_ID_PATH)
+            .ok()
+            .map(|id| id.trim().to_string())
+            .filter(|id| id.len() == 32 && id.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))),
+        system_manufacturer: smbios("system-manufacturer", "sys_vendor"),
+        system_model: smbios("system-product-name", "product_name"),
+        system_uuid: smbios("system-uuid", "product_uuid"),
+        system_serial: smbios("system-serial-number", "product_serial"),
+        chassis_serial: smbios("chassis-serial-number", "chassis_serial"),
+        asset_tag: smbios("chassis-asset-tag", "chassis_asset_tag"),
+        bios_vendor: smbios("bios-vendor", "bios_vendor"),
+        bios_version: smbios("bios-version", "bios_version"),
+        bios_date: smbios("bios-release-date", "bios_date"),
+    }
+}
+
+/// One line for listings, e.g. "Dell Inc. Latitude 7490 (serial 5J5PX32)"
+pub fn describe(host: &HostInfo) -> String {
+    let name = [&host.system_manufacturer, &host.system_model]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let name = if name.is_empty() { host.hostname.clone() } else { name };
+    match host.chassis_serial.as_ref().or(host.system_serial.as_ref()) {
+        Some(serial) => format!("{} (serial {})", name, serial),
+        None => name,
+    }
+}
+
+/// The kernel hostname, or "localhost" if it can't be read
+pub fn hostname() -> String {
+    std::fs::read_to_string(HOSTNAME_PATH)
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+/// One SMBIOS string: `dmidecode -s <keyword>`, else `/sys/class/dmi/id/<file>`
+pub fn smbios(keyword: &str, dmi_file: &str) -> Option<String> {
+    let from_dmidecode = Command::new("dmidecode")
+        .args(["-s", keyword])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).into_owned());
+    from_dmidecode
+        .or_else(|| std::fs::read_to_string(Path::new(DMI_DIR).join(dmi_file)).ok())
+        .and_then(|raw| clean_smbios_value(&raw))
+}
+
+/// Trim a raw SMBIOS string; placeholders and blanks become None. dmidecode prints
+/// comment lines ("# SMBIOS implementations newer than ...") before the value.
+pub fn clean_smbios_value(raw: &str) -> Option<String> {
+    let value = raw.lines().map(str::trim).find(|line| !line.is_empty() && !line.starts_with('#'))?;
+    if PLACEHOLDERS.contains(&value.to_lowercase().as_str()) || value.chars().all(|c| c == '0' || c == ' ') {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_smbios_value() {
+        assert_eq!(clean_smbios_value("5J5PX32\n"), Some("5J5PX32".to_string()));
+        assert_eq!(
+            clean_smbios_value("# SMBIOS implementations newer than version 3.2.0 are not\n# fully supported.\nPF2ABC12\n"),
+            Some("PF2ABC12".to_string())
+        );
+        for placeholder in ["To Be Filled By O.E.M.", "Default string", "  \n", "0000000000", "Not Specified", "System Product Name"] {
+            assert_eq!(clean_smbios_value(placeholder), None, "{}", placeholder);
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        let mut host = HostInfo { hostname: "lab-07".to_string(), ..Default::default() };
+        assert_eq!(describe(&host), "lab-07");
+        host.system_manufacturer = Some("Dell Inc.".to_string());
+        host.system_model = Some("Latitude 7490".to_string());
+        host.system_serial = Some("5J5PX32".to_string());
+        assert_eq!(describe(&host), "Dell Inc. Latitude 7490 (serial 5J5PX32)");
+    }
+}
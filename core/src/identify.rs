@@ -0,0 +1,164 @@
+//! Points out a drive physically, so an operator can check which caddy or port holds
+//! the target before confirming a wipe. Enclosures and backplanes with a locate LED
+//! (SES, SGPIO, Intel VMD) get `ledctl locate`; anything else has its activity LED
+//! flicker from bursts of small direct reads spread over the disk. Nothing is written.
+
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest identify run accepted; the UI waits for it to finish
+pub const MAX_DURATION: Duration = Duration::from_secs(300);
+
+/// Bytes per read; a multiple of any logical block size O_DIRECT needs
+const READ_BYTES: usize = 64 * 1024;
+/// How long the LED stays lit, then dark, in each blink
+const BLINK: Duration = Duration::from_millis(400);
+
+#[derive(Debug, thiserror::Error)]
+pub enum IdentifyError {
+    #[error("Cannot read {0}: {1}")]
+    Read(String, std::io::Error),
+
+    #[error("ledctl failed: {0}")]
+    Ledctl(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentifyMethod {
+    /// The enclosure's locate LED, via `ledctl`
+    Ledctl,
+    /// The drive's activity LED, via bursts of reads
+    Reads,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentifyReport {
+    pub device: String,
+    pub method: IdentifyMethod,
+    pub seconds: u64,
+    /// Reads issued; 0 with `ledctl`
+    pub reads: u64,
+}
+
+/// Blink `device` for `duration` (capped at `MAX_DURATION`). `method` forces one
+/// way; without it `ledctl` is tried first and reads are the fallback.
+pub fn identify_device(device: &str, duration: Duration, method: Option<IdentifyMethod>) -> Result<IdentifyReport, IdentifyError> {
+    let duration = duration.min(MAX_DURATION);
+    let mut report = IdentifyReport { device: device.to_string(), method: IdentifyMethod::Ledctl, seconds: duration.as_secs(), reads: 0 };
+
+    if method != Some(IdentifyMethod::Reads) {
+        match locate_led(device, duration) {
+            Ok(()) => return Ok(report),
+            Err(e) if method == Some(IdentifyMethod::Ledctl) => return Err(e),
+            Err(e) => crate::events::debug(&format!("No locate LED for {} ({}); blinking with reads", device, e)),
+        }
+    }
+
+    report.method = IdentifyMethod::Reads;
+    report.reads = blink_with_reads(device, duration).map_err(|e| IdentifyError::Read(device.to_string(), e))?;
+    Ok(report)
+}
+
+/// Light the locate LED for `duration`, then switch it off
+fn locate_led(device: &str, duration: Duration) -> Result<(), IdentifyError> {
+    let ledctl = |pattern: &str| -> Result<(), IdentifyError> {
+        let output = Command::new("ledctl")
+            .arg(format!("{}={}", pattern, device))
+            .output()
+            .map_err(|e| IdentifyError::Ledctl(e.to_string()))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(IdentifyError::Ledctl(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+        }
+    };
+    ledctl("locate")?;
+    thread::sleep(duration);
+    ledctl("locate_off")
+}
+
+/// Alternate bursts of reads with pauses until `duration` is up; returns the reads made
+fn blink_with_reads(device: &str, duration: Duration) -> std::io::Result<u64> {
+    let mut file = open_direct(device)?;
+    let size = file.seek(SeekFrom::End(0))?;
+    // O_DIRECT needs buffers aligned to the logical block size; 4 KiB covers all of them
+    const ALIGN: usize = 4096;
+    let mut storage = vec![0u8; READ_BYTES + ALIGN];
+    let offset = storage.as_ptr().align_offset(ALIGN);
+    let buffer = &mut storage[offset..offset + READ_BYTES];
+
+    let mut offsets = read_offsets(size);
+    let mut reads = 0;
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        let lit_until = (Instant::now() + BLINK).min(deadline);
+        while Instant::now() < lit_until {
+            file.seek(SeekFrom::Start(offsets.next().unwrap_or(0)))?;
+            let _ = file.read(buffer)?;
+            reads += 1;
+        }
+        thread::sleep(BLINK.min(deadline.saturating_duration_since(Instant::now())));
+    }
+    Ok(reads)
+}
+
+/// Read-only with O_DIRECT, so reads reach the drive rather than the page cache;
+/// plain reads where the kernel refuses it (files, some virtual devices)
+#[cfg(target_os = "linux")]
+fn open_direct(device: &str) -> std::io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    match OpenOptions::new().read(true).custom_flags(libc::O_DIRECT).open(device) {
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => File::open(device),
+        result => result,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_direct(device: &str) -> std::io::Result<File> {
+    File::open(device)
+}
+
+/// Aligned offsets that stride across the whole device, so the drive's own read cache
+/// doesn't answer for it
+fn read_offsets(size: u64) -> impl Iterator<Item = u64> {
+    let slots = (size / READ_BYTES as u64).max(1);
+    // A prime stride visits every slot before repeating unless it divides the count
+    let stride = 7919 % slots;
+    let stride = if stride == 0 { 1 } else { stride };
+    (0u64..).map(move |i| (i.wrapping_mul(stride) % slots) * READ_BYTES as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_offsets_stay_on_the_device() {
+        let size = 1000 * READ_BYTES as u64 + 123;
+        let offsets: Vec<u64> = read_offsets(size).take(2000).collect();
+        assert!(offsets.iter().all(|&offset| offset % READ_BYTES as u64 == 0 && offset + READ_BYTES as u64 <= size));
+        assert_ne!(offsets[0], offsets[1]);
+        assert!(read_offsets(0).take(3).all(|offset| offset == 0));
+    }
+
+    #[test]
+    fn test_identify_with_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("disk.img");
+        let image = File::create(&path).unwrap();
+        image.set_len(4 * 1024 * 1024).unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let report = identify_device(&path, Duration::from_millis(500), Some(IdentifyMethod::Reads)).unwrap();
+        assert_eq!(report.method, IdentifyMethod::Reads);
+        assert!(report.reads > 0);
+
+        assert!(identify_device(&dir.path().join("missing").to_string_lossy(), Duration::ZERO, Some(IdentifyMethod::Reads)).is_err());
+    }
+}
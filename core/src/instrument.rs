@@ -0,0 +1,148 @@
+//! Tracing for the core pipelines. Discovery, backup, wipe, signing and PDF
+//! generation run inside `tracing` spans; when a span closes, its duration is logged
+//! at debug level (`-v`) as `<parent>/<span> took <n> ms`. `tracing` events, such as
+//! the PDF generators' `info!` lines, are forwarded into the event stream.
+//!
+//! `SECUREWIPE_TRACE` takes `tracing` env-filter directives (default `info`) and
+//! decides which spans are recorded at all. `--trace-json <file>` also writes every
+//! span to a Chrome trace file, which Perfetto, chrome://tracing and speedscope show
+//! as a flame graph.
+
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::path::Path;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+use crate::events::{self, LogLevel};
+
+pub use tracing_chrome::FlushGuard;
+
+/// Install the subscriber. Keep the returned guard until the command is done: the
+/// trace file is only complete once it is dropped.
+pub fn init(trace_json: Option<&Path>) -> Option<FlushGuard> {
+    let filter = EnvFilter::try_from_env("SECUREWIPE_TRACE").unwrap_or_else(|_| EnvFilter::new("info"));
+    let (chrome, guard) = match trace_json {
+        Some(path) => {
+            let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new().file(path).include_args(true).build();
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+    let subscriber = Registry::default().with(filter).with(PhaseTiming).with(chrome);
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        events::warn("Tracing was already set up; --trace-json and span timings are off");
+    }
+    guard
+}
+
+/// Logs each span's duration when it closes and forwards `tracing` events
+pub struct PhaseTiming;
+
+struct Started(Instant);
+
+impl<S> Layer<S> for PhaseTiming
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Started(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(elapsed_ms) = span.extensions().get::<Started>().map(|started| started.0.elapsed().as_millis() as u64)
+        else {
+            return;
+        };
+        let path: Vec<&str> = span.scope().from_root().map(|span| span.name()).collect();
+        events::log_from(
+            module_of(span.metadata().target()),
+            LogLevel::Debug,
+            &format!("{} took {} ms", path.join("/"), elapsed_ms),
+            json!({"span": path.join("/"), "elapsed_ms": elapsed_ms}),
+        );
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = FieldsVisitor::default();
+        event.record(&mut fields);
+        let message = fields.message.take().unwrap_or_default();
+        let level = match *event.metadata().level() {
+            Level::TRACE => LogLevel::Trace,
+            Level::DEBUG => LogLevel::Debug,
+            Level::INFO => LogLevel::Info,
+            Level::WARN => LogLevel::Warn,
+            Level::ERROR => LogLevel::Error,
+        };
+        let context = if fields.fields.is_empty() { Value::Null } else { json!({"fields": fields.fields}) };
+        events::log_from(module_of(event.metadata().target()), level, &message, context);
+    }
+}
+
+/// `securewipe::cert_pdf` -> `cert_pdf`, the name `SECUREWIPE_LOG` filters on
+fn module_of(target: &str) -> &str {
+    target.rsplit("::").next().unwrap_or(target)
+}
+
+#[derive(Default)]
+struct FieldsVisitor {
+    message: Option<String>,
+    fields: Map<String, Value>,
+}
+
+impl Visit for FieldsVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
+
+impl FieldsVisitor {
+    fn record(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = Some(value);
+        } else {
+            self.fields.insert(field.name().to_string(), Value::String(value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_of() {
+        assert_eq!(module_of("securewipe::cert_pdf"), "cert_pdf");
+        assert_eq!(module_of("wipe"), "wipe");
+    }
+
+    #[test]
+    fn test_trace_json_records_spans() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.json");
+        let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new().file(&path).build();
+        let subscriber = Registry::default().with(PhaseTiming).with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let _backup = tracing::info_span!("backup").entered();
+            tracing::info_span!("copy_files").in_scope(|| tracing::info!(files = 3, "copied"));
+        });
+        drop(guard);
+
+        let trace: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let names: Vec<&str> = trace.as_array().unwrap().iter().filter_map(|event| event["name"].as_str()).collect();
+        assert!(names.contains(&"backup"));
+        assert!(names.contains(&"copy_files"));
+    }
+}
@@ -0,0 +1,588 @@
+use crate::device::Device;
+use crate::device_id::stable_id;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Lifecycle state of a drive as far as this workstation knows
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceStatus {
+    Discovered,
+    Wiped,
+    WipeFailed,
+    Destroyed,
+}
+
+impl DeviceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeviceStatus::Discovered => "discovered",
+            DeviceStatus::Wiped => "wiped",
+            DeviceStatus::WipeFailed => "wipe_failed",
+            DeviceStatus::Destroyed => "destroyed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "discovered" => Some(DeviceStatus::Discovered),
+            "wiped" => Some(DeviceStatus::Wiped),
+            "wipe_failed" => Some(DeviceStatus::WipeFailed),
+            "destroyed" => Some(DeviceStatus::Destroyed),
+            _ => None,
+        }
+    }
+}
+
+/// A certificate issued for an inventoried drive
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CertificateRecord {
+    pub cert_id: String,
+    pub cert_type: String,
+    pub policy: Option<String>,
+    pub result: Option<String>,
+    pub issued_at: String,
+}
+
+/// One drive, keyed by serial number
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct InventoryEntry {
+    pub serial: String,
+    /// Stable identity (see `device_id`), shared with discovery output and certificates
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Name the operator gave the drive
+    #[serde(default)]
+    pub alias: Option<String>,
+    pub model: Option<String>,
+    pub bus: Option<String>,
+    pub capacity_bytes: Option<u64>,
+    /// Device node the drive was last seen at, e.g. /dev/sdb
+    pub last_path: Option<String>,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub status: DeviceStatus,
+    pub certificates: Vec<CertificateRecord>,
+}
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS devices (
+    serial TEXT PRIMARY KEY,
+    model TEXT,
+    bus TEXT,
+    capacity_bytes INTEGER,
+    last_path TEXT,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    status TEXT NOT NULL,
+    device_id TEXT
+);
+CREATE TABLE IF NOT EXISTS device_aliases (
+    device_id TEXT PRIMARY KEY,
+    alias TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS certificates (
+    cert_id TEXT PRIMARY KEY,
+    serial TEXT NOT NULL REFERENCES devices(serial),
+    cert_type TEXT NOT NULL,
+    policy TEXT,
+    result TEXT,
+    issued_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS certificates_by_serial ON certificates(serial);
+";
+
+const ENTRY_COLUMNS: &str = "SELECT d.serial, d.model, d.bus, d.capacity_bytes, d.last_path, d.first_seen, d.last_seen,
+        d.status, d.device_id, a.alias
+     FROM devices d LEFT JOIN device_aliases a ON a.device_id = d.device_id";
+
+/// SQLite-backed record of every drive this workstation has discovered, wiped or destroyed
+pub struct Inventory {
+    conn: Connection,
+}
+
+impl Inventory {
+    /// Open (creating if needed) the inventory database at `path`
+    pub fn open(path: &Path) -> Result<Self, Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Open the configured database (SECUREWIPE_INVENTORY_DB, or inventory.db in the data directory)
+    pub fn open_default() -> Result<Self, Box<dyn Error>> {
+        Self::open(&default_path()?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, Box<dyn Error>> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, Box<dyn Error>> {
+        conn.execute_batch(SCHEMA)?;
+        // Databases from before stable device IDs lack the column
+        let has_device_id: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('devices') WHERE name = 'device_id'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_device_id {
+            conn.execute_batch("ALTER TABLE devices ADD COLUMN device_id TEXT;")?;
+        }
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS devices_by_id ON devices(device_id);")?;
+        Ok(Inventory { conn })
+    }
+
+    /// Record that `device` was seen now. Drives without a serial cannot be
+    /// tracked across reconnects and are skipped; returns whether it was recorded.
+    pub fn record_seen(&self, device: &Device) -> Result<bool, Box<dyn Error>> {
+        let serial = match device.serial.as_deref().map(str::trim) {
+            Some(serial) if !serial.is_empty() => serial,
+            _ => return Ok(false),
+        };
+        let device_id = device
+            .device_id
+            .clone()
+            .or_else(|| stable_id(None, device.model.as_deref(), Some(serial)));
+        self.upsert_device(
+            serial,
+            device_id.as_deref(),
+            device.model.as_deref(),
+            device.bus.as_deref(),
+            Some(device.capacity_bytes),
+            Some(&device.name),
+        )?;
+        Ok(true)
+    }
+
+    /// Record the outcome of a wipe, whether or not a certificate was issued for it
+    pub fn record_wipe_outcome(&self, serial: &str, passed: bool) -> Result<(), Box<dyn Error>> {
+        let status = if passed { DeviceStatus::Wiped } else { DeviceStatus::WipeFailed };
+        self.upsert_device(serial, None, None, None, None, None)?;
+        self.set_status(serial, status)
+    }
+
+    /// Attach a signed certificate to its drive and update the drive's status.
+    /// Returns the serial the certificate was filed under, or None when the
+    /// certificate does not identify a drive (e.g. backup certificates).
+    pub fn record_certificate(&self, cert: &Value) -> Result<Option<String>, Box<dyn Error>> {
+        let text = |pointer: &str| cert.pointer(pointer).and_then(|v| v.as_str()).map(str::to_string);
+
+        let serial = match text("/device/serial") {
+            Some(serial) if !serial.trim().is_empty() => serial.trim().to_string(),
+            _ => return Ok(None),
+        };
+        let cert_id = text("/cert_id").ok_or("certificate is missing cert_id")?;
+        let cert_type = text("/cert_type").ok_or("certificate is missing cert_type")?;
+        let issued_at = text("/created_at").unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+        let result = text("/result");
+
+        let device_id = text("/device/device_id")
+            .or_else(|| stable_id(None, text("/device/model").as_deref(), Some(&serial)));
+        self.upsert_device(
+            &serial,
+            device_id.as_deref(),
+            text("/device/model").as_deref(),
+            text("/device/bus").as_deref(),
+            cert.pointer("/device/capacity_bytes").and_then(|v| v.as_u64()),
+            text("/device/path").as_deref(),
+        )?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO certificates (cert_id, serial, cert_type, policy, result, issued_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![cert_id, serial, cert_type, text("/policy/nist_level"), result, issued_at],
+        )?;
+
+        let status = match (cert_type.as_str(), result.as_deref()) {
+            ("destroy", _) => Some(DeviceStatus::Destroyed),
+            ("wipe", Some("PASS") | Some("PASS_WITH_EXCEPTIONS")) => Some(DeviceStatus::Wiped),
+            ("wipe", _) => Some(DeviceStatus::WipeFailed),
+            _ => None,
+        };
+        if let Some(status) = status {
+            self.set_status(&serial, status)?;
+        }
+
+        Ok(Some(serial))
+    }
+
+    /// All drives, most recently seen first, optionally filtered by status
+    pub fn list(&self, status: Option<DeviceStatus>) -> Result<Vec<InventoryEntry>, Box<dyn Error>> {
+        let mut statement = self.conn.prepare(&format!(
+            "{} WHERE ?1 IS NULL OR d.status = ?1 ORDER BY d.last_seen DESC, d.serial",
+            ENTRY_COLUMNS
+        ))?;
+        let rows = statement.query_map(params![status.map(|s| s.as_str())], entry_from_row)?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let mut entry = row?;
+            entry.certificates = self.certificates_for(&entry.serial)?;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    /// Look up one drive by serial, stable device ID or alias
+    pub fn show(&self, key: &str) -> Result<Option<InventoryEntry>, Box<dyn Error>> {
+        let entry = self
+            .conn
+            .query_row(
+                &format!("{} WHERE d.serial = ?1 OR d.device_id = ?1 OR a.alias = ?1", ENTRY_COLUMNS),
+                params![key],
+                entry_from_row,
+            )
+            .optional()?;
+
+        match entry {
+            Some(mut entry) => {
+                entry.certificates = self.certificates_for(&entry.serial)?;
+                Ok(Some(entry))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Most recent PASS wipe certificate for `serial` issued at or after `since`
+    /// with a policy at least as strong as `policy` (PURGE covers CLEAR)
+    pub fn recent_pass_wipe(
+        &self,
+        serial: &str,
+        policy: &str,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<CertificateRecord>, Box<dyn Error>> {
+        let requested = policy_strength(policy);
+        let recent = self
+            .certificates_for(serial)?
+            .into_iter()
+            .filter(|cert| cert.cert_type == "wipe" && cert.result.as_deref() == Some("PASS"))
+            .filter(|cert| cert.policy.as_deref().map(policy_strength) >= Some(requested))
+            .rfind(|cert| {
+                chrono::DateTime::parse_from_rfc3339(&cert.issued_at)
+                    .map(|issued_at| issued_at >= since)
+                    .unwrap_or(false)
+            });
+        Ok(recent)
+    }
+
+    /// Name a drive (found by serial, device ID or current alias), or clear its name
+    /// with `None`. Returns the drive's stable ID.
+    pub fn set_alias(&self, key: &str, alias: Option<&str>) -> Result<String, Box<dyn Error>> {
+        let entry = self.show(key)?.ok_or_else(|| format!("No inventory record for {}", key))?;
+        let device_id = entry.device_id.ok_or_else(|| format!("{} has no stable device ID", key))?;
+        match alias.map(str::trim) {
+            Some("") => return Err("Alias must not be empty".into()),
+            Some(alias) => {
+                self.conn
+                    .execute(
+                        "INSERT INTO device_aliases (device_id, alias) VALUES (?1, ?2)
+                         ON CONFLICT(device_id) DO UPDATE SET alias = excluded.alias",
+                        params![device_id, alias],
+                    )
+                    .map_err(|e| match e.sqlite_error_code() {
+                        Some(rusqlite::ErrorCode::ConstraintViolation) => {
+                            format!("Alias {} is already used by another drive", alias).into()
+                        }
+                        _ => Box::<dyn Error>::from(e),
+                    })?;
+            }
+            None => {
+                self.conn.execute("DELETE FROM device_aliases WHERE device_id = ?1", params![device_id])?;
+            }
+        }
+        Ok(device_id)
+    }
+
+    /// Every named drive, by stable device ID
+    pub fn alias_map(&self) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+        let mut statement = self.conn.prepare("SELECT device_id, alias FROM device_aliases ORDER BY alias")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<BTreeMap<_, _>, _>>()?)
+    }
+
+    fn certificates_for(&self, serial: &str) -> Result<Vec<CertificateRecord>, Box<dyn Error>> {
+        let mut statement = self.conn.prepare(
+            "SELECT cert_id, cert_type, policy, result, issued_at
+             FROM certificates WHERE serial = ?1 ORDER BY issued_at",
+        )?;
+        let rows = statement.query_map(params![serial], |row| {
+            Ok(CertificateRecord {
+                cert_id: row.get(0)?,
+                cert_type: row.get(1)?,
+                policy: row.get(2)?,
+                result: row.get(3)?,
+                issued_at: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    fn upsert_device(
+        &self,
+        serial: &str,
+        device_id: Option<&str>,
+        model: Option<&str>,
+        bus: Option<&str>,
+        capacity_bytes: Option<u64>,
+        last_path: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO devices (serial, model, bus, capacity_bytes, last_path, first_seen, last_seen, status, device_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7, ?8)
+             ON CONFLICT(serial) DO UPDATE SET
+                device_id = COALESCE(excluded.device_id, devices.device_id),
+                model = COALESCE(excluded.model, devices.model),
+                bus = COALESCE(excluded.bus, devices.bus),
+                capacity_bytes = COALESCE(excluded.capacity_bytes, devices.capacity_bytes),
+                last_path = COALESCE(excluded.last_path, devices.last_path),
+                last_seen = excluded.last_seen",
+            params![
+                serial,
+                model,
+                bus,
+                capacity_bytes.map(|bytes| bytes as i64),
+                last_path,
+                now,
+                DeviceStatus::Discovered.as_str(),
+                device_id
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn set_status(&self, serial: &str, status: DeviceStatus) -> Result<(), Box<dyn Error>> {
+        self.conn.execute(
+            "UPDATE devices SET status = ?2 WHERE serial = ?1",
+            params![serial, status.as_str()],
+        )?;
+        Ok(())
+    }
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<InventoryEntry> {
+    let status: String = row.get(7)?;
+    Ok(InventoryEntry {
+        serial: row.get(0)?,
+        model: row.get(1)?,
+        bus: row.get(2)?,
+        capacity_bytes: row.get::<_, Option<i64>>(3)?.map(|bytes| bytes as u64),
+        last_path: row.get(4)?,
+        first_seen: row.get(5)?,
+        last_seen: row.get(6)?,
+        status: DeviceStatus::parse(&status).unwrap_or(DeviceStatus::Discovered),
+        device_id: row.get(8)?,
+        alias: row.get(9)?,
+        certificates: Vec::new(),
+    })
+}
+
+/// NIST SP 800-88 ordering: CLEAR < PURGE < DESTROY
+fn policy_strength(policy: &str) -> u8 {
+    match policy {
+        "CLEAR" => 1,
+        "PURGE" => 2,
+        "DESTROY" => 3,
+        _ => 0,
+    }
+}
+
+/// The configured `inventory_db` (see `config`)
+pub fn default_path() -> Result<PathBuf, Box<dyn Error>> {
+    Ok(crate::config::Config::load()?.inventory_db)
+}
+
+/// Flatten entries to CSV, one row per drive with its certificate IDs joined by ';'
+pub fn export_csv(entries: &[InventoryEntry]) -> String {
+    let mut csv = String::from("serial,model,bus,capacity_bytes,last_path,first_seen,last_seen,status,certificates\n");
+    for entry in entries {
+        let certificates: Vec<&str> = entry.certificates.iter().map(|cert| cert.cert_id.as_str()).collect();
+        let fields = [
+            entry.serial.clone(),
+            entry.model.clone().unwrap_or_default(),
+            entry.bus.clone().unwrap_or_default(),
+            entry.capacity_bytes.map(|bytes| bytes.to_string()).unwrap_or_default(),
+            entry.last_path.clone().unwrap_or_default(),
+            entry.first_seen.clone(),
+            entry.last_seen.clone(),
+            entry.status.as_str().to_string(),
+            certificates.join(";"),
+        ];
+        let escaped: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&escaped.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::RiskLevel;
+
+    fn sample_device(serial: Option<&str>) -> Device {
+        Device {
+            name: "/dev/sdb".to_string(),
+            model: Some("Samsung SSD 870".to_string()),
+            serial: serial.map(str::to_string),
+            capacity_bytes: 500_107_862_016,
+            bus: Some("SATA".to_string()),
+            mountpoints: vec![],
+            risk_level: RiskLevel::Safe,
+            device_id: None,
+            risk_explanation: None,
+            capacity_display: None,
+        }
+    }
+
+    fn sample_certificate(cert_type: &str, cert_id: &str, result: &str) -> Value {
+        serde_json::json!({
+            "cert_type": cert_type,
+            "cert_id": cert_id,
+            "created_at": "2025-09-20T10:15:00+00:00",
+            "device": {"model": "Samsung SSD 870", "serial": "S5Y1NX0R123456", "bus": "SATA", "capacity_bytes": 500_107_862_016u64},
+            "policy": {"nist_level": "PURGE", "method": "controller_sanitize"},
+            "result": result
+        })
+    }
+
+    #[test]
+    fn test_record_seen_requires_serial() {
+        let inventory = Inventory::open_in_memory().unwrap();
+        assert!(!inventory.record_seen(&sample_device(None)).unwrap());
+        assert!(inventory.record_seen(&sample_device(Some("S5Y1NX0R123456"))).unwrap());
+        assert!(inventory.record_seen(&sample_device(Some("S5Y1NX0R123456"))).unwrap());
+
+        let entries = inventory.list(None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, DeviceStatus::Discovered);
+        assert_eq!(entries[0].last_path.as_deref(), Some("/dev/sdb"));
+    }
+
+    #[test]
+    fn test_record_certificates_updates_status() {
+        let inventory = Inventory::open_in_memory().unwrap();
+        inventory.record_seen(&sample_device(Some("S5Y1NX0R123456"))).unwrap();
+
+        inventory.record_certificate(&sample_certificate("wipe", "WPE_1", "FAIL")).unwrap();
+        assert_eq!(inventory.show("S5Y1NX0R123456").unwrap().unwrap().status, DeviceStatus::WipeFailed);
+
+        inventory.record_certificate(&sample_certificate("wipe", "WPE_2", "PASS")).unwrap();
+        let entry = inventory.show("S5Y1NX0R123456").unwrap().unwrap();
+        assert_eq!(entry.status, DeviceStatus::Wiped);
+        assert_eq!(entry.certificates.len(), 2);
+        assert_eq!(entry.certificates[1].policy.as_deref(), Some("PURGE"));
+
+        inventory.record_certificate(&sample_certificate("wipe", "WPE_3", "PASS_WITH_EXCEPTIONS")).unwrap();
+        assert_eq!(inventory.show("S5Y1NX0R123456").unwrap().unwrap().status, DeviceStatus::Wiped);
+
+        inventory.record_certificate(&sample_certificate("destroy", "DST_1", "DESTROYED")).unwrap();
+        assert_eq!(inventory.list(Some(DeviceStatus::Destroyed)).unwrap().len(), 1);
+        assert!(inventory.list(Some(DeviceStatus::Wiped)).unwrap().is_empty());
+
+        let backup = serde_json::json!({"cert_type": "backup", "cert_id": "BCK_1"});
+        assert_eq!(inventory.record_certificate(&backup).unwrap(), None);
+        assert!(inventory.show("unknown").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_recent_pass_wipe() {
+        let inventory = Inventory::open_in_memory().unwrap();
+        let issued = chrono::DateTime::parse_from_rfc3339("2025-09-20T10:15:00+00:00").unwrap().with_timezone(&chrono::Utc);
+        let serial = "S5Y1NX0R123456";
+
+        inventory.record_certificate(&sample_certificate("wipe", "WPE_FAIL", "FAIL")).unwrap();
+        assert!(inventory.recent_pass_wipe(serial, "CLEAR", issued).unwrap().is_none());
+
+        inventory.record_certificate(&sample_certificate("wipe", "WPE_PASS", "PASS")).unwrap();
+        let found = inventory.recent_pass_wipe(serial, "CLEAR", issued - chrono::Duration::days(30)).unwrap();
+        assert_eq!(found.unwrap().cert_id, "WPE_PASS");
+        assert!(inventory.recent_pass_wipe(serial, "PURGE", issued).unwrap().is_some());
+
+        // Outside the window, or a stronger policy requested
+        assert!(inventory.recent_pass_wipe(serial, "PURGE", issued + chrono::Duration::days(1)).unwrap().is_none());
+        assert!(inventory.recent_pass_wipe(serial, "DESTROY", issued).unwrap().is_none());
+        assert!(inventory.recent_pass_wipe("other", "CLEAR", issued).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let inventory = Inventory::open_in_memory().unwrap();
+        inventory.record_seen(&sample_device(Some("S5Y1NX0R123456"))).unwrap();
+        inventory.record_certificate(&sample_certificate("wipe", "WPE_1", "PASS")).unwrap();
+
+        let csv = export_csv(&inventory.list(None).unwrap());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("serial,model,"));
+        assert!(lines[1].starts_with("S5Y1NX0R123456,Samsung SSD 870,"));
+        assert!(lines[1].ends_with(",wiped,WPE_1"));
+
+        assert_eq!(csv_field("Model, \"Quoted\""), "\"Model, \"\"Quoted\"\"\"");
+    }
+    #[test]
+    fn test_device_ids_and_aliases() {
+        let inventory = Inventory::open_in_memory().unwrap();
+        let mut device = sample_device(Some("S5Y1NX0R123456"));
+        device.device_id = Some("wwn:0x5002538e40b1ba45".to_string());
+        inventory.record_seen(&device).unwrap();
+
+        // The same drive at another /dev name is the same inventory entry
+        device.name = "/dev/sdd".to_string();
+        inventory.record_seen(&device).unwrap();
+        let entry = inventory.show("wwn:0x5002538e40b1ba45").unwrap().unwrap();
+        assert_eq!(entry.serial, "S5Y1NX0R123456");
+        assert_eq!(entry.last_path.as_deref(), Some("/dev/sdd"));
+
+        inventory.set_alias("S5Y1NX0R123456", Some("bay-3")).unwrap();
+        assert_eq!(inventory.show("bay-3").unwrap().unwrap().alias.as_deref(), Some("bay-3"));
+        assert_eq!(inventory.alias_map().unwrap().get("wwn:0x5002538e40b1ba45").map(String::as_str), Some("bay-3"));
+
+        // Aliases are unique, and drives without an ID can't have one
+        let mut other = sample_device(Some("OTHER123"));
+        other.model = None;
+        inventory.record_seen(&other).unwrap();
+        let other_id = inventory.show("OTHER123").unwrap().unwrap().device_id.unwrap();
+        assert_eq!(other_id, stable_id(None, None, Some("OTHER123")).unwrap());
+        assert!(inventory.set_alias("OTHER123", Some("bay-3")).is_err());
+        assert!(inventory.set_alias("missing", Some("bay-9")).is_err());
+
+        inventory.set_alias("bay-3", None).unwrap();
+        assert!(inventory.alias_map().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_adds_device_id_to_old_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE devices (serial TEXT PRIMARY KEY, model TEXT, bus TEXT, capacity_bytes INTEGER,
+                last_path TEXT, first_seen TEXT NOT NULL, last_seen TEXT NOT NULL, status TEXT NOT NULL);
+             INSERT INTO devices VALUES ('S5Y1NX0R123456', 'Samsung SSD 870', 'SATA', 1, '/dev/sdb', 'a', 'a', 'wiped');",
+        )
+        .unwrap();
+        let inventory = Inventory::with_connection(conn).unwrap();
+        assert_eq!(inventory.show("S5Y1NX0R123456").unwrap().unwrap().device_id, None);
+
+        // Certificates carry the ID from discovery
+        let mut cert = sample_certificate("wipe", "WPE_1", "PASS");
+        cert["device"]["device_id"] = serde_json::json!("wwn:0x5002538e40b1ba45");
+        inventory.record_certificate(&cert).unwrap();
+        let entry = inventory.show("wwn:0x5002538e40b1ba45").unwrap().unwrap();
+        assert_eq!(entry.status, DeviceStatus::Wiped);
+    }
+}
@@ -0,0 +1,272 @@
+//! Wipe interlock for walk-up kiosk stations, where whoever is at the machine may not
+//! know which `/dev` name their drive got. The site policy's `kiosk.interlock` picks
+//! how the target is identified before a wipe may start: `single` refuses while any
+//! other non-critical drive is attached, and `replug` has the operator unplug the
+//! target and plug it back in, watching udev for the drive leaving and returning.
+
+use crate::device::{Device, RiskLevel};
+use crate::policy::{KioskInterlock, KioskPolicy};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Why the interlock refused a wipe
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum KioskError {
+    #[error("Kiosk mode: other drives are attached ({0}); remove them so only the drive to wipe is connected")]
+    Ambiguous(String),
+
+    #[error("Kiosk mode: {0} is not among the attached drives")]
+    NotAttached(String),
+
+    #[error("Kiosk mode: the drive came back as {0}; run the wipe again with --device {0}")]
+    Renamed(String),
+
+    #[error("Kiosk mode: {0} was not unplugged and plugged back in within {1} seconds")]
+    Timeout(String, u64),
+
+    #[error("Kiosk mode: cannot watch for the drive being replugged: {0}")]
+    Monitor(String),
+}
+
+/// Drives other than `target` that a kiosk user could have meant: everything but the
+/// system disks, which are CRITICAL and never wiped outside ISO mode anyway
+pub fn other_candidates<'a>(devices: &'a [Device], target: &str) -> Vec<&'a Device> {
+    devices
+        .iter()
+        .filter(|device| device.name != target && !matches!(device.risk_level, RiskLevel::Critical))
+        .collect()
+}
+
+/// Run the interlock the policy asks for. `devices` is what discovery found, `None`
+/// when it failed, which the interlock can't work without.
+pub fn check(policy: &KioskPolicy, devices: Option<&[Device]>, target: &str) -> Result<(), KioskError> {
+    if policy.interlock == KioskInterlock::Off {
+        return Ok(());
+    }
+    let devices = devices.ok_or_else(|| KioskError::NotAttached(target.to_string()))?;
+    let device = devices
+        .iter()
+        .find(|device| device.name == target)
+        .ok_or_else(|| KioskError::NotAttached(target.to_string()))?;
+
+    match policy.interlock {
+        KioskInterlock::Off => Ok(()),
+        KioskInterlock::Single => {
+            let others = other_candidates(devices, target);
+            if others.is_empty() {
+                Ok(())
+            } else {
+                let names: Vec<&str> = others.iter().map(|device| device.name.as_str()).collect();
+                Err(KioskError::Ambiguous(names.join(", ")))
+            }
+        }
+        KioskInterlock::Replug => {
+            // udev's serial is what its events carry; discovery's may come from smartctl
+            let serial = crate::probe_cache::udev_serial(target).or_else(|| device.serial.clone());
+            // At warn level so -q still shows it; the wipe waits on the operator
+            crate::events::warn(&format!(
+                "Kiosk mode: unplug {} ({}) and plug it back in to confirm it is the drive to wipe (waiting {} seconds)",
+                target,
+                device.model.as_deref().unwrap_or("unknown model"),
+                policy.replug_timeout_secs
+            ));
+            wait_for_replug(target, serial.as_deref(), Duration::from_secs(policy.replug_timeout_secs))
+        }
+    }
+}
+
+/// Block until `device` is removed and added again, per `udevadm monitor`
+fn wait_for_replug(device: &str, serial: Option<&str>, timeout: Duration) -> Result<(), KioskError> {
+    let mut child = Command::new("udevadm")
+        .args(["monitor", "--udev", "--subsystem-match=block", "--property"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| KioskError::Monitor(format!("udevadm: {}", e)))?;
+    let stdout = child.stdout.take().ok_or_else(|| KioskError::Monitor("udevadm has no output".to_string()))?;
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut watch = ReplugWatch::new(device, serial);
+    let mut block = String::new();
+    let deadline = Instant::now() + timeout;
+    let outcome = loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(remaining) {
+            Ok(line) if line.trim().is_empty() => {
+                let event = Uevent::parse(&block);
+                block.clear();
+                if let Some(outcome) = event.and_then(|event| watch.observe(&event)) {
+                    break outcome;
+                }
+            }
+            Ok(line) => {
+                block.push_str(&line);
+                block.push('\n');
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => break Err(KioskError::Timeout(device.to_string(), timeout.as_secs())),
+            Err(mpsc::RecvTimeoutError::Disconnected) => break Err(KioskError::Monitor("udevadm exited".to_string())),
+        }
+    };
+    let _ = child.kill();
+    let _ = child.wait();
+    outcome
+}
+
+/// One block-device event from `udevadm monitor --property`
+#[derive(Debug, Clone, PartialEq)]
+struct Uevent {
+    action: String,
+    devname: String,
+    is_disk: bool,
+    serial: Option<String>,
+}
+
+impl Uevent {
+    fn parse(block: &str) -> Option<Self> {
+        let property = |key: &str| {
+            block
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        Some(Uevent {
+            action: property("ACTION")?,
+            devname: property("DEVNAME")?,
+            is_disk: property("DEVTYPE").as_deref() == Some("disk"),
+            serial: property("ID_SERIAL_SHORT").or_else(|| property("ID_SERIAL")),
+        })
+    }
+}
+
+/// Follows udev events until the target has gone away and come back
+struct ReplugWatch<'a> {
+    device: &'a str,
+    serial: Option<&'a str>,
+    removed: bool,
+}
+
+impl<'a> ReplugWatch<'a> {
+    fn new(device: &'a str, serial: Option<&'a str>) -> Self {
+        Self { device, serial, removed: false }
+    }
+
+    fn is_target(&self, event: &Uevent) -> bool {
+        match (self.serial, event.serial.as_deref()) {
+            (Some(expected), Some(seen)) => expected == seen,
+            // Without a serial the name is all there is to go on
+            _ => event.devname == self.device,
+        }
+    }
+
+    /// `Some` once the replug is complete or has gone wrong
+    fn observe(&mut self, event: &Uevent) -> Option<Result<(), KioskError>> {
+        if !event.is_disk || !self.is_target(event) {
+            return None;
+        }
+        match event.action.as_str() {
+            "remove" => {
+                self.removed = true;
+                None
+            }
+            "add" if self.removed => {
+                if event.devname == self.device {
+                    Some(Ok(()))
+                } else {
+                    Some(Err(KioskError::Renamed(event.devname.clone())))
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(name: &str, risk_level: RiskLevel) -> Device {
+        Device {
+            name: name.to_string(),
+            model: Some("Test Disk".to_string()),
+            serial: Some(format!("SN-{}", name)),
+            capacity_bytes: 1 << 30,
+            bus: Some("USB".to_string()),
+            mountpoints: Vec::new(),
+            risk_level,
+            device_id: None,
+            risk_explanation: None,
+            capacity_display: None,
+        }
+    }
+
+    fn event(action: &str, devname: &str, serial: &str) -> Uevent {
+        Uevent { action: action.to_string(), devname: devname.to_string(), is_disk: true, serial: Some(serial.to_string()) }
+    }
+
+    #[test]
+    fn test_single_interlock() {
+        let policy = KioskPolicy { interlock: KioskInterlock::Single, ..KioskPolicy::default() };
+        let system = device("/dev/nvme0n1", RiskLevel::Critical);
+        let target = device("/dev/sdb", RiskLevel::Safe);
+        let other = device("/dev/sdc", RiskLevel::High);
+
+        let alone = vec![system.clone(), target.clone()];
+        assert_eq!(check(&policy, Some(&alone), "/dev/sdb"), Ok(()));
+
+        let crowded = vec![system, target, other];
+        assert_eq!(check(&policy, Some(&crowded), "/dev/sdb"), Err(KioskError::Ambiguous("/dev/sdc".to_string())));
+        assert_eq!(check(&policy, Some(&crowded), "/dev/sdd"), Err(KioskError::NotAttached("/dev/sdd".to_string())));
+        assert!(check(&policy, None, "/dev/sdb").is_err());
+
+        assert_eq!(check(&KioskPolicy::default(), None, "/dev/sdb"), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_uevent() {
+        let block = "UDEV  [5123.456789] add      /devices/pci0000:00/usb2/2-1/block/sdb (block)\n\
+                     ACTION=add\nDEVPATH=/devices/pci0000:00/usb2/2-1/block/sdb\nSUBSYSTEM=block\n\
+                     DEVNAME=/dev/sdb\nDEVTYPE=disk\nID_SERIAL=SanDisk_Cruzer_4C5300011\nID_SERIAL_SHORT=4C5300011\n";
+        assert_eq!(Uevent::parse(block), Some(event("add", "/dev/sdb", "4C5300011")));
+
+        let partition = block.replace("DEVTYPE=disk", "DEVTYPE=partition");
+        assert!(!Uevent::parse(&partition).unwrap().is_disk);
+        assert_eq!(Uevent::parse("monitor will print the received events for:\n"), None);
+    }
+
+    #[test]
+    fn test_replug_watch() {
+        let mut watch = ReplugWatch::new("/dev/sdb", Some("4C5300011"));
+        // Plugging the drive in before it was taken out proves nothing
+        assert_eq!(watch.observe(&event("add", "/dev/sdb", "4C5300011")), None);
+        // Another drive coming and going is ignored
+        assert_eq!(watch.observe(&event("remove", "/dev/sdc", "OTHER")), None);
+        assert_eq!(watch.observe(&event("remove", "/dev/sdb", "4C5300011")), None);
+        assert_eq!(watch.observe(&event("add", "/dev/sdb", "OTHER")), None);
+        assert_eq!(watch.observe(&event("add", "/dev/sdb", "4C5300011")), Some(Ok(())));
+
+        let mut renamed = ReplugWatch::new("/dev/sdb", Some("4C5300011"));
+        renamed.observe(&event("remove", "/dev/sdb", "4C5300011"));
+        assert_eq!(
+            renamed.observe(&event("add", "/dev/sdc", "4C5300011")),
+            Some(Err(KioskError::Renamed("/dev/sdc".to_string())))
+        );
+
+        let mut no_serial = ReplugWatch::new("/dev/sdb", None);
+        no_serial.observe(&event("remove", "/dev/sdb", "X"));
+        assert_eq!(no_serial.observe(&event("add", "/dev/sdb", "Y")), Some(Ok(())));
+    }
+}
@@ -0,0 +1,300 @@
+use qrcode::{Color, QrCode};
+use serde::Serialize;
+use schemars::JsonSchema;
+use serde_json::Value;
+use std::error::Error;
+
+/// 2" x 1" label at 203 dpi, the common direct-thermal drive label
+const LABEL_WIDTH: usize = 406;
+const LABEL_HEIGHT: usize = 203;
+const MARGIN: usize = 8;
+const QR_QUIET_ZONE: usize = 4;
+const LINE_SPACING: usize = 8;
+
+/// What gets printed on a drive label, extracted from a wipe or destroy certificate
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq)]
+pub struct LabelSummary {
+    pub cert_id: String,
+    pub cert_type: String,
+    pub serial: String,
+    pub model: String,
+    pub policy: String,
+    pub method: String,
+    /// Calendar date the certificate was issued (YYYY-MM-DD)
+    pub date: String,
+    pub result: String,
+    /// Encoded in the QR code
+    pub verify_url: String,
+}
+
+impl LabelSummary {
+    pub fn from_certificate(cert: &Value) -> Result<Self, Box<dyn Error>> {
+        let text = |pointer: &str| cert.pointer(pointer).and_then(|v| v.as_str()).map(str::to_string);
+        let required = |pointer: &str| text(pointer).ok_or_else(|| format!("certificate is missing {}", pointer));
+
+        let cert_type = required("/cert_type")?;
+        if cert_type != "wipe" && cert_type != "destroy" {
+            return Err(format!("labels are only printed for wipe and destroy certificates, not {}", cert_type).into());
+        }
+        let cert_id = required("/cert_id")?;
+
+        let created_at = required("/created_at")?;
+        let date = chrono::DateTime::parse_from_rfc3339(&created_at)
+            .map(|timestamp| timestamp.format("%Y-%m-%d").to_string())
+            .map_err(|e| format!("invalid created_at '{}': {}", created_at, e))?;
+
+        let verify_url = text("/verify_url").unwrap_or_else(|| format!("cert_id:{}", cert_id));
+
+        Ok(LabelSummary {
+            serial: required("/device/serial")?,
+            model: text("/device/model").unwrap_or_default(),
+            policy: required("/policy/nist_level")?,
+            method: text("/policy/method").unwrap_or_default(),
+            result: required("/result")?,
+            cert_id,
+            cert_type,
+            date,
+            verify_url,
+        })
+    }
+}
+
+/// Render the label as a ZPL II job with the whole label as one graphic field,
+/// so the printout matches the PNG preview dot for dot
+pub fn render_zpl(summary: &LabelSummary) -> Result<String, Box<dyn Error>> {
+    let raster = render_raster(summary)?;
+    let row_bytes = raster.width.div_ceil(8);
+    let total_bytes = row_bytes * raster.height;
+
+    let mut hex = String::with_capacity(total_bytes * 2);
+    for y in 0..raster.height {
+        for byte_index in 0..row_bytes {
+            let mut byte = 0u8;
+            for bit in 0..8 {
+                let x = byte_index * 8 + bit;
+                if x < raster.width && raster.get(x, y) {
+                    byte |= 0x80 >> bit;
+                }
+            }
+            hex.push_str(&format!("{:02X}", byte));
+        }
+    }
+
+    Ok(format!(
+        "^XA\n^FX SecureWipe {} {}\n^PW{}\n^LL{}\n^FO0,0^GFA,{},{},{},{}^FS\n^XZ\n",
+        summary.cert_id, summary.result, raster.width, raster.height, total_bytes, total_bytes, row_bytes, hex
+    ))
+}
+
+/// Render the label as a monochrome PNG at printer resolution
+pub fn render_png(summary: &LabelSummary) -> Result<Vec<u8>, Box<dyn Error>> {
+    let raster = render_raster(summary)?;
+    let image = image::GrayImage::from_fn(raster.width as u32, raster.height as u32, |x, y| {
+        image::Luma([if raster.get(x as usize, y as usize) { 0 } else { 255 }])
+    });
+
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageLuma8(image)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+    Ok(bytes)
+}
+
+/// One bit per dot, `true` is black
+struct Raster {
+    width: usize,
+    height: usize,
+    dots: Vec<bool>,
+}
+
+impl Raster {
+    fn new(width: usize, height: usize) -> Self {
+        Raster { width, height, dots: vec![false; width * height] }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.dots[y * self.width + x]
+    }
+
+    fn fill(&mut self, x: usize, y: usize, size: usize) {
+        for dy in 0..size {
+            for dx in 0..size {
+                if x + dx < self.width && y + dy < self.height {
+                    self.dots[(y + dy) * self.width + x + dx] = true;
+                }
+            }
+        }
+    }
+
+    /// Draw `text` with the built-in 5x7 font, `scale` dots per font pixel
+    fn text(&mut self, x: usize, y: usize, text: &str, scale: usize) {
+        for (index, c) in text.chars().enumerate() {
+            let origin = x + index * 6 * scale;
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for column in 0..5 {
+                    if bits & (0x10 >> column) != 0 {
+                        self.fill(origin + column * scale, y + row * scale, scale);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn render_raster(summary: &LabelSummary) -> Result<Raster, Box<dyn Error>> {
+    let mut raster = Raster::new(LABEL_WIDTH, LABEL_HEIGHT);
+
+    // QR code on the left, as large as the label height allows
+    let code = QrCode::new(summary.verify_url.as_bytes())?;
+    let modules = code.width();
+    let module_size = ((LABEL_HEIGHT - 2 * MARGIN) / (modules + 2 * QR_QUIET_ZONE)).max(1);
+    let colors = code.to_colors();
+    for y in 0..modules {
+        for x in 0..modules {
+            if colors[y * modules + x] == Color::Dark {
+                raster.fill(
+                    MARGIN + (QR_QUIET_ZONE + x) * module_size,
+                    MARGIN + (QR_QUIET_ZONE + y) * module_size,
+                    module_size,
+                );
+            }
+        }
+    }
+
+    // Text column on the right; each line shrinks to fit, then truncates
+    let text_x = MARGIN + (modules + 2 * QR_QUIET_ZONE) * module_size + MARGIN;
+    let text_width = LABEL_WIDTH.saturating_sub(text_x + MARGIN);
+    let policy = if summary.method.is_empty() {
+        summary.policy.clone()
+    } else {
+        format!("{} {}", summary.policy, summary.method)
+    };
+    let lines = [
+        (summary.result.to_uppercase(), 3),
+        (format!("SN {}", summary.serial), 2),
+        (policy, 2),
+        (summary.date.clone(), 2),
+        (summary.cert_id.clone(), 1),
+    ];
+
+    let mut y = MARGIN + QR_QUIET_ZONE * module_size;
+    for (line, max_scale) in lines.iter() {
+        let chars = line.chars().count().max(1);
+        let scale = (1..=*max_scale).rev().find(|scale| chars * 6 * scale <= text_width).unwrap_or(1);
+        let fitted: String = line.chars().take(text_width / 6).collect();
+        raster.text(text_x, y, &fitted, scale);
+        y += 7 * scale + LINE_SPACING;
+    }
+
+    Ok(raster)
+}
+
+/// 5x7 glyphs, one byte per row with bit 4 as the leftmost column
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ' ' => [0x00; 7],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_certificate() -> Value {
+        serde_json::json!({
+            "cert_type": "wipe",
+            "cert_id": "WPE_0f6c7a2e-2b1d-4f43-9c55-6d0a1e2b3c4d",
+            "created_at": "2025-09-20T10:15:00+00:00",
+            "device": {"model": "Samsung SSD 980", "serial": "S4EWNX0N123456"},
+            "policy": {"nist_level": "PURGE", "method": "nvme_sanitize"},
+            "result": "PASS",
+            "verify_url": "https://verify.securewipe.local/cert/WPE_0f6c7a2e-2b1d-4f43-9c55-6d0a1e2b3c4d"
+        })
+    }
+
+    #[test]
+    fn test_label_summary_from_certificate() {
+        let summary = LabelSummary::from_certificate(&sample_certificate()).unwrap();
+        assert_eq!(summary.serial, "S4EWNX0N123456");
+        assert_eq!(summary.policy, "PURGE");
+        assert_eq!(summary.date, "2025-09-20");
+        assert_eq!(summary.result, "PASS");
+
+        let mut backup = sample_certificate();
+        backup["cert_type"] = Value::String("backup".to_string());
+        assert!(LabelSummary::from_certificate(&backup).is_err());
+
+        let mut missing_serial = sample_certificate();
+        missing_serial["device"].as_object_mut().unwrap().remove("serial");
+        assert!(LabelSummary::from_certificate(&missing_serial).is_err());
+    }
+
+    #[test]
+    fn test_render_zpl() {
+        let summary = LabelSummary::from_certificate(&sample_certificate()).unwrap();
+        let zpl = render_zpl(&summary).unwrap();
+        assert!(zpl.starts_with("^XA\n"));
+        assert!(zpl.trim_end().ends_with("^XZ"));
+
+        let row_bytes = LABEL_WIDTH.div_ceil(8);
+        let header = format!("^GFA,{0},{0},{1},", row_bytes * LABEL_HEIGHT, row_bytes);
+        let data = &zpl[zpl.find(&header).unwrap() + header.len()..];
+        let data = &data[..data.find("^FS").unwrap()];
+        assert_eq!(data.len(), row_bytes * LABEL_HEIGHT * 2);
+        assert!(data.chars().any(|c| c != '0'));
+    }
+
+    #[test]
+    fn test_render_png() {
+        let summary = LabelSummary::from_certificate(&sample_certificate()).unwrap();
+        let png = render_png(&summary).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!(decoded.width() as usize, LABEL_WIDTH);
+        assert_eq!(decoded.height() as usize, LABEL_HEIGHT);
+    }
+}
@@ -1,20 +1,114 @@
+#[cfg(feature = "operations")]
 pub mod backup;
 pub mod cert;
+#[cfg(feature = "operations")]
+pub mod cert_builder;
 pub mod device;
+#[cfg(feature = "operations")]
+pub mod mount;
+#[cfg(feature = "operations")]
 pub mod wipe;
+#[cfg(feature = "operations")]
+pub mod wipe_plan;
 pub mod logging;
 pub mod pdf;
 pub mod cert_pdf;
+pub mod cert_text;
 pub mod signer;
 pub mod schema;
+#[cfg(feature = "operations")]
+pub mod signatures;
+#[cfg(feature = "operations")]
+pub mod label;
+pub mod inventory;
+#[cfg(feature = "operations")]
+pub mod kiosk;
+#[cfg(feature = "operations")]
+pub mod cost;
+#[cfg(feature = "operations")]
+pub mod approval;
+pub mod contracts;
+pub mod policy;
+pub mod locale;
+#[cfg(feature = "operations")]
+pub mod telemetry;
+pub mod config;
+pub mod secrets;
+pub mod audit;
+#[cfg(feature = "operations")]
+pub mod serial;
+pub mod clock;
+pub mod qr;
+pub mod pdf_stamp;
+pub mod cert_index;
+#[cfg(feature = "operations")]
+pub mod cert_fsck;
+#[cfg(feature = "operations")]
+pub mod retention;
+pub mod cert_sync;
+pub mod host;
+pub mod system_cert;
+pub mod remediation;
+pub mod lsm;
+pub mod removal;
+#[cfg(feature = "operations")]
+pub mod priority;
+#[cfg(feature = "operations")]
+pub mod power;
+#[cfg(feature = "operations")]
+pub mod virtual_disk;
+pub mod phone;
+pub mod paths;
+pub mod smart;
+pub mod probe_cache;
+pub mod tool_json;
+pub mod device_id;
+pub mod risk;
+pub mod containers;
+pub mod glob;
+#[cfg(feature = "operations")]
+pub mod annotations;
+#[cfg(feature = "operations")]
+pub mod tasks;
+pub mod cert_id;
+pub mod events;
+pub mod instrument;
+pub mod cleanup;
+#[cfg(feature = "operations")]
+pub mod identify;
+#[cfg(feature = "operations")]
+pub mod hardening;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
 
 // Re-export commonly used types for easier integration testing
-pub use backup::{BackupOperations, EncryptedBackup, BackupResult, BackupManifest};
-pub use cert::{CertificateOperations, Ed25519CertificateManager, BackupCertificate, WipeCertificate, CertificateSignature};
-pub use device::{DeviceDiscovery, LinuxDeviceDiscovery, Device, RiskLevel};
-pub use wipe::{WipeOperations, NistAlignedWipe, WipeResult, WipePolicy, WipeCommand};
+#[cfg(feature = "operations")]
+pub use backup::{BackupOperations, EncryptedBackup, BackupResult, BackupManifest, BackupErrorPolicy, BackupKeyParams, BackupVerificationReport, RestoreReport, SkippedFile, VerificationFailure};
+#[cfg(feature = "operations")]
+pub use cert::{CertificateOperations, Ed25519CertificateManager};
+pub use cert::{BackupCertificate, WipeCertificate, BackupCertificateBuilder, WipeCertificateBuilder};
+pub use securewipe_types::certificate::CertificateSignature;
+pub use device::{DeviceDiscovery, LinuxDeviceDiscovery, Device, RemovableVolume, RiskExplanation, RiskLevel};
+#[cfg(feature = "operations")]
+pub use mount::{MountedDestination, mount_destination, unmount_destination, eject_destination};
+#[cfg(feature = "operations")]
+pub use identify::{IdentifyMethod, IdentifyReport, identify_device};
+#[cfg(feature = "operations")]
+pub use wipe::{WipeOperations, NistAlignedWipe, WipeResult, WipePolicy, WipeCommand, IdentitySnapshot, WipePlan, VerificationPlan, VerificationRecord, VerificationSample, SampleClass, VerificationHistogram, ReplayReport, PatternVerification, VerifyMode, BadRegion, WipeOptions, OverwriteFill, OverwritePass, OverwriteProfile, OverwriteRecord, HpaDcoCheck, MaxSectors, MetadataRegion, MethodDecision, metadata_regions, plan_wipe, replay_verification, verify_pattern};
+#[cfg(feature = "operations")]
+pub use signatures::{SignatureFinding, scan_device};
+#[cfg(feature = "operations")]
+pub use label::{LabelSummary, render_zpl, render_png};
+pub use inventory::{Inventory, InventoryEntry, CertificateRecord, DeviceStatus};
+pub use cert_index::{CertIndex, CertificateQuery, CertificateHit, CertificatePage, IndexDrift};
+#[cfg(feature = "operations")]
+pub use approval::{Approval, ApprovalError, ApprovalRequest, ApprovalSubject, Role, Roles, requires_approval, sign_approval, verify_approval};
+#[cfg(feature = "operations")]
+pub use contracts::export_schemas;
+pub use contracts::{ProgressEvent, LogMessage, ErrorResponse, CONTRACT_VERSION};
 pub use logging::Logger;
 pub use pdf::{PdfGenerator, ensure_certificates_dir, extract_embedded_json};
-pub use cert_pdf::{CertificatePdfGenerator, generate_backup_pdf, generate_wipe_pdf};
-pub use signer::{load_private_key, canonicalize_json, sign_certificate, verify_certificate_signature, SignerError};
+pub use cert_pdf::{CertificatePdfGenerator, PdfDetail, export_certificate_pdf, generate_backup_pdf, generate_wipe_pdf};
+pub use signer::{load_private_key, load_public_key, parse_public_key_pem, canonicalize_json, sign_certificate, verify_certificate_signature, SignerError};
 pub use schema::{CertificateValidator, ValidationResult, validate_certificate, validate_certificate_json, validate_certificate_file};
\ No newline at end of file
@@ -0,0 +1,238 @@
+//! How numbers, byte sizes and dates read in PDFs, reports and human CLI output:
+//! decimal and digit-group separators and date order follow a locale, and sizes use
+//! SI (kB, MB, GB) or IEC (KiB, MiB, GiB) units. Machine output (JSON, CSV) and the
+//! signed certificate itself keep plain numbers and RFC 3339 timestamps.
+
+use crate::policy::{DisplayPolicy, SitePolicy};
+use chrono::{DateTime, FixedOffset};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Which multiples a byte size is shown in
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// Powers of 1000: kB, MB, GB, TB, as drive capacities are sold
+    #[default]
+    #[serde(alias = "decimal")]
+    Si,
+    /// Powers of 1024: KiB, MiB, GiB, TiB
+    #[serde(alias = "binary")]
+    Iec,
+}
+
+impl Units {
+    fn scale(self) -> (f64, [&'static str; 5]) {
+        match self {
+            Units::Si => (1000.0, ["kB", "MB", "GB", "TB", "PB"]),
+            Units::Iec => (1024.0, ["KiB", "MiB", "GiB", "TiB", "PiB"]),
+        }
+    }
+}
+
+/// Separators and date order per locale: language-region first, then language alone.
+/// No-break spaces rather than narrow ones, which the PDF fonts don't have.
+const LOCALES: &[(&str, char, char, &str)] = &[
+    ("en-US", '.', ',', "%m/%d/%Y"),
+    ("en", '.', ',', "%d/%m/%Y"),
+    ("de-CH", '.', '\'', "%d.%m.%Y"),
+    ("de", ',', '.', "%d.%m.%Y"),
+    ("fr-CA", ',', '\u{a0}', "%Y-%m-%d"),
+    ("fr", ',', '\u{a0}', "%d/%m/%Y"),
+    ("es", ',', '.', "%d/%m/%Y"),
+    ("it", ',', '.', "%d/%m/%Y"),
+    ("pt", ',', '.', "%d/%m/%Y"),
+    ("nl", ',', '.', "%d-%m-%Y"),
+    ("sv", ',', '\u{a0}', "%Y-%m-%d"),
+    ("pl", ',', '\u{a0}', "%d.%m.%Y"),
+    ("ru", ',', '\u{a0}', "%d.%m.%Y"),
+    ("ja", '.', ',', "%Y/%m/%d"),
+    ("zh", '.', ',', "%Y/%m/%d"),
+    ("hi", '.', ',', "%d/%m/%Y"),
+];
+
+/// Locale-dependent display settings, passed as JSON to the PDF generator
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DisplayFormat {
+    /// Normalized tag, e.g. `de-DE`; `C` when no known locale applies
+    pub locale: String,
+    pub decimal: char,
+    pub group: char,
+    /// strftime pattern for the date part of a timestamp
+    pub date: String,
+    pub units: Units,
+}
+
+impl Default for DisplayFormat {
+    /// ISO dates and `1,234.5` numbers, as output read before locales were supported
+    fn default() -> Self {
+        Self { locale: "C".to_string(), decimal: '.', group: ',', date: "%Y-%m-%d".to_string(), units: Units::default() }
+    }
+}
+
+impl DisplayFormat {
+    /// Settings for a locale tag such as `de-DE` or `de_DE.UTF-8`. Unknown locales,
+    /// `C` and `POSIX` get the defaults.
+    pub fn new(locale: &str, units: Units) -> Self {
+        let tag = normalize_tag(locale);
+        let language = tag.split('-').next().unwrap_or_default();
+        let known = LOCALES
+            .iter()
+            .find(|(name, ..)| name.eq_ignore_ascii_case(&tag))
+            .or_else(|| LOCALES.iter().find(|(name, ..)| name.eq_ignore_ascii_case(language)));
+        match known {
+            Some(&(_, decimal, group, date)) => Self { locale: tag, decimal, group, date: date.to_string(), units },
+            None => Self { units, ..Self::default() },
+        }
+    }
+
+    /// From the site policy's `display` section, falling back to the process
+    /// locale (LC_ALL, LC_NUMERIC, LANG) when it names none
+    pub fn current() -> Self {
+        Self::for_policy(&SitePolicy::load_default().map(|policy| policy.display).unwrap_or_default())
+    }
+
+    /// As `current`, for a policy that is already loaded
+    pub fn for_policy(policy: &DisplayPolicy) -> Self {
+        Self::from_policy(policy, |name| std::env::var(name).ok())
+    }
+
+    fn from_policy(policy: &DisplayPolicy, env: impl Fn(&str) -> Option<String>) -> Self {
+        let locale = policy.locale.clone().or_else(|| {
+            ["LC_ALL", "LC_NUMERIC", "LANG"].iter().find_map(|name| env(name).filter(|value| !value.is_empty()))
+        });
+        Self::new(locale.as_deref().unwrap_or("C"), policy.units)
+    }
+
+    /// `value` to `decimals` places, with digit groups
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let digits = format!("{:.*}", decimals, value.abs());
+        let (whole, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+        let mut text = String::new();
+        if value < 0.0 && digits.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            text.push('-');
+        }
+        text.push_str(&self.group_digits(whole));
+        if !fraction.is_empty() {
+            text.push(self.decimal);
+            text.push_str(fraction);
+        }
+        text
+    }
+
+    /// A count, with digit groups
+    pub fn integer(&self, value: u64) -> String {
+        self.group_digits(&value.to_string())
+    }
+
+    fn group_digits(&self, digits: &str) -> String {
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(self.group);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// A byte size in the largest unit it reaches, to two places; plain bytes below
+    /// the first unit
+    pub fn bytes(&self, bytes: u64) -> String {
+        let (base, labels) = self.units.scale();
+        let mut value = bytes as f64;
+        let mut label = None;
+        for unit in labels {
+            if value < base {
+                break;
+            }
+            value /= base;
+            label = Some(unit);
+        }
+        match label {
+            Some(label) => format!("{} {}", self.number(value, 2), label),
+            None => format!("{} bytes", self.integer(bytes)),
+        }
+    }
+
+    /// A byte size with the exact count after it, e.g. `1.00 TB (1,000,204,886,016 bytes)`,
+    /// so SI and IEC figures can't be mistaken for each other
+    pub fn bytes_exact(&self, bytes: u64) -> String {
+        match self.bytes(bytes) {
+            plain if plain.ends_with(" bytes") => plain,
+            scaled => format!("{} ({} bytes)", scaled, self.integer(bytes)),
+        }
+    }
+
+    /// Date and time in the timestamp's own offset, so it matches the signed value
+    pub fn datetime(&self, at: &DateTime<FixedOffset>) -> String {
+        format!("{} {}", at.format(&self.date), at.format("%H:%M:%S %:z"))
+    }
+
+    /// An RFC 3339 timestamp from a certificate or record; anything else as given
+    pub fn timestamp(&self, text: &str) -> String {
+        DateTime::parse_from_rfc3339(text).map(|at| self.datetime(&at)).unwrap_or_else(|_| text.to_string())
+    }
+}
+
+/// `de_DE.UTF-8@euro` to `de-DE`
+fn normalize_tag(locale: &str) -> String {
+    let tag = locale.split(['.', '@']).next().unwrap_or_default().trim();
+    tag.replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers_and_bytes() {
+        let us = DisplayFormat::new("en_US.UTF-8", Units::Si);
+        assert_eq!(us.locale, "en-US");
+        assert_eq!(us.integer(1_000_204_886_016), "1,000,204,886,016");
+        assert_eq!(us.number(-1234.5, 2), "-1,234.50");
+        assert_eq!(us.number(-0.001, 2), "0.00");
+        assert_eq!(us.bytes(1_000_204_886_016), "1.00 TB");
+        assert_eq!(us.bytes(999), "999 bytes");
+        assert_eq!(us.bytes(1_500), "1.50 kB");
+        assert_eq!(us.bytes_exact(1_000_204_886_016), "1.00 TB (1,000,204,886,016 bytes)");
+        assert_eq!(us.bytes_exact(512), "512 bytes");
+
+        let de = DisplayFormat::new("de-DE", Units::Iec);
+        assert_eq!(de.number(1234567.891, 1), "1.234.567,9");
+        assert_eq!(de.bytes(1_000_204_886_016), "931,51 GiB");
+        assert_eq!(de.bytes(1024), "1,00 KiB");
+
+        let fr = DisplayFormat::new("fr_FR", Units::Si);
+        assert_eq!(fr.integer(52_428_800), "52\u{a0}428\u{a0}800");
+        assert_eq!(DisplayFormat::new("de_CH.UTF-8", Units::Si).integer(1_000_000), "1'000'000");
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let stamp = "2024-01-15T11:45:30+05:30";
+        assert_eq!(DisplayFormat::new("en-US", Units::Si).timestamp(stamp), "01/15/2024 11:45:30 +05:30");
+        assert_eq!(DisplayFormat::new("en-GB", Units::Si).timestamp(stamp), "15/01/2024 11:45:30 +05:30");
+        assert_eq!(DisplayFormat::new("de", Units::Si).timestamp(stamp), "15.01.2024 11:45:30 +05:30");
+        assert_eq!(DisplayFormat::default().timestamp(stamp), "2024-01-15 11:45:30 +05:30");
+        assert_eq!(DisplayFormat::default().timestamp("yesterday"), "yesterday");
+    }
+
+    #[test]
+    fn test_locale_resolution() {
+        assert_eq!(DisplayFormat::new("POSIX", Units::Iec), DisplayFormat { units: Units::Iec, ..DisplayFormat::default() });
+        assert_eq!(DisplayFormat::new("tlh-QO", Units::Si), DisplayFormat::default());
+
+        let env = |name: &str| match name {
+            "LC_ALL" => Some(String::new()),
+            "LANG" => Some("nl_NL.UTF-8".to_string()),
+            _ => None,
+        };
+        assert_eq!(DisplayFormat::from_policy(&DisplayPolicy::default(), env).locale, "nl-NL");
+
+        let policy = DisplayPolicy { locale: Some("ja-JP".to_string()), units: Units::Iec };
+        let format = DisplayFormat::from_policy(&policy, env);
+        assert_eq!((format.locale.as_str(), format.units), ("ja-JP", Units::Iec));
+        assert_eq!(DisplayFormat::from_policy(&DisplayPolicy::default(), |_| None), DisplayFormat::default());
+    }
+}
@@ -1,6 +1,7 @@
-use serde_json::{json, Value};
-use std::io::{self, Write};
+use crate::events;
+use serde_json::Value;
 
+/// Writes a command's log lines and documents to the stderr event stream
 pub struct Logger {
     // In future, this could hold file handles, log levels, etc.
 }
@@ -10,31 +11,21 @@ impl Logger {
         Self {}
     }
     
+    /// A progress step, result or error document, classified by `events::event_type_of`
     pub fn log_json(&self, data: &Value) {
-        // For now, log to stderr for structured logging
-        if let Ok(json_str) = serde_json::to_string(data) {
-            let _ = writeln!(io::stderr(), "{}", json_str);
-        }
+        events::emit_document(data);
     }
     
     #[allow(dead_code)] // Used in tests and future implementations
+    #[track_caller]
     pub fn log_info(&self, message: &str) {
-        let entry = json!({
-            "level": "info",
-            "message": message,
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        });
-        self.log_json(&entry);
+        events::info(message);
     }
     
     #[allow(dead_code)] // Used in tests and future implementations
+    #[track_caller]
     pub fn log_error(&self, message: &str) {
-        let entry = json!({
-            "level": "error",
-            "message": message,
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        });
-        self.log_json(&entry);
+        events::error(message);
     }
 }
 
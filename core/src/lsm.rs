@@ -0,0 +1,165 @@
+//! Recognizes permission errors that come from a Linux security module rather than
+//! file modes. SELinux and AppArmor refuse device opens with EACCES even for root, and
+//! the plain "Permission denied" hint (run with sudo) sends operators in circles. A
+//! denial is recognized when an enforcing module stands between root and the device,
+//! or when the audit or kernel log holds a denial naming the path.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Logs searched for denial records, newest entries at the end: auditd first (SELinux,
+/// and AppArmor when auditd runs), then the kernel log as syslog keeps it
+const DENIAL_LOGS: &[&str] = &["/var/log/audit/audit.log", "/var/log/kern.log", "/var/log/syslog", "/var/log/messages"];
+
+/// How far back from the end of each log to look
+const LOG_TAIL_BYTES: u64 = 512 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lsm {
+    SeLinux,
+    AppArmor,
+}
+
+impl Lsm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Lsm::SeLinux => "SELinux",
+            Lsm::AppArmor => "AppArmor",
+        }
+    }
+
+    /// The module a log line records a denial by, if it is a denial of `path`. SELinux
+    /// AVC records name only the last path component; AppArmor records the full path.
+    pub fn from_denial(line: &str, path: &Path) -> Option<Lsm> {
+        let full = format!("name=\"{}\"", path.display());
+        if line.contains("apparmor=\"DENIED\"") && line.contains(&full) {
+            return Some(Lsm::AppArmor);
+        }
+        let base = path.file_name().map(|name| format!("name=\"{}\"", name.to_string_lossy()))?;
+        if line.contains("avc:") && line.contains("denied") && (line.contains(&base) || line.contains(&full)) {
+            return Some(Lsm::SeLinux);
+        }
+        None
+    }
+}
+
+/// An access the security module refused, with the log record when one was found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Denial {
+    pub lsm: Lsm,
+    pub record: Option<String>,
+}
+
+impl Denial {
+    /// One line for an error message, e.g. "SELinux denied access to /dev/sdb (avc: ...)"
+    pub fn describe(&self, path: &Path) -> String {
+        match &self.record {
+            Some(record) => format!("{} denied access to {} ({})", self.lsm.name(), path.display(), record),
+            None => format!("{} is enforcing and denied access to {}", self.lsm.name(), path.display()),
+        }
+    }
+}
+
+/// Why a permission error on `path` happened, if a security module is the likely
+/// cause: a logged denial of that path, or else an enforcing module when running as
+/// root, where file modes can't be what refused the access
+pub fn explain(path: Option<&Path>) -> Option<Denial> {
+    if let Some(denial) = path.and_then(logged_denial) {
+        return Some(denial);
+    }
+    let root = unsafe { libc::geteuid() } == 0;
+    enforcing().filter(|_| root).map(|lsm| Denial { lsm, record: None })
+}
+
+/// The module that can refuse this process access, probed once per run
+pub fn enforcing() -> Option<Lsm> {
+    static ENFORCING: OnceLock<Option<Lsm>> = OnceLock::new();
+    *ENFORCING.get_or_init(probe)
+}
+
+fn probe() -> Option<Lsm> {
+    let read = |path: &str| std::fs::read_to_string(path).ok();
+    let selinux = read("/sys/fs/selinux/enforce").or_else(getenforce);
+    if selinux.as_deref().is_some_and(selinux_enforcing) {
+        return Some(Lsm::SeLinux);
+    }
+    let apparmor = read("/sys/module/apparmor/parameters/enabled").is_some_and(|enabled| enabled.trim() == "Y");
+    // Newer kernels keep AppArmor's label apart from other modules'
+    let label = read("/proc/self/attr/apparmor/current").or_else(|| read("/proc/self/attr/current"));
+    if apparmor && label.as_deref().is_some_and(apparmor_confined) {
+        return Some(Lsm::AppArmor);
+    }
+    None
+}
+
+/// `getenforce` output, for systems where selinuxfs isn't at /sys/fs/selinux
+fn getenforce() -> Option<String> {
+    let output = std::process::Command::new("getenforce").output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `/sys/fs/selinux/enforce` holds 1, `getenforce` prints Enforcing
+fn selinux_enforcing(text: &str) -> bool {
+    matches!(text.trim(), "1" | "Enforcing")
+}
+
+/// A process label such as `/usr/bin/securewipe (enforce)`; unconfined processes
+/// can't be denied anything by AppArmor
+fn apparmor_confined(label: &str) -> bool {
+    let label = label.trim_end_matches('\0').trim();
+    !label.is_empty() && !label.starts_with("unconfined") && label.ends_with("(enforce)")
+}
+
+/// The most recent denial of `path` in the audit or kernel log
+fn logged_denial(path: &Path) -> Option<Denial> {
+    DENIAL_LOGS.iter().find_map(|log| {
+        let tail = read_tail(Path::new(log))?;
+        tail.lines().rev().find_map(|line| {
+            Lsm::from_denial(line, path).map(|lsm| Denial { lsm, record: Some(line.trim().to_string()) })
+        })
+    })
+}
+
+fn read_tail(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let length = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(length.saturating_sub(LOG_TAIL_BYTES))).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denial_records() {
+        let device = Path::new("/dev/sdb");
+        let avc = r#"type=AVC msg=audit(1718000000.123:456): avc:  denied  { write } for  pid=4242 comm="securewipe" name="sdb" dev="devtmpfs" ino=512 scontext=system_u:system_r:securewipe_t:s0 tcontext=system_u:object_r:fixed_disk_device_t:s0 tclass=blk_file permissive=0"#;
+        let apparmor = r#"kernel: audit: type=1400 audit(1718000000.5:77): apparmor="DENIED" operation="open" profile="/usr/bin/securewipe" name="/dev/sdb" pid=4242 comm="securewipe" requested_mask="w" denied_mask="w" fsuid=0 ouid=0"#;
+        assert_eq!(Lsm::from_denial(avc, device), Some(Lsm::SeLinux));
+        assert_eq!(Lsm::from_denial(apparmor, device), Some(Lsm::AppArmor));
+        // Another device, and an allowed access
+        assert_eq!(Lsm::from_denial(avc, Path::new("/dev/sdc")), None);
+        assert_eq!(Lsm::from_denial(&apparmor.replace("DENIED", "ALLOWED"), device), None);
+
+        let denial = Denial { lsm: Lsm::AppArmor, record: None };
+        assert_eq!(denial.describe(device), "AppArmor is enforcing and denied access to /dev/sdb");
+    }
+
+    #[test]
+    fn test_enforcement_state() {
+        assert!(selinux_enforcing("1\n"));
+        assert!(selinux_enforcing("Enforcing\n"));
+        assert!(!selinux_enforcing("Permissive\n"));
+        assert!(!selinux_enforcing("0"));
+
+        assert!(apparmor_confined("/usr/bin/securewipe (enforce)\n"));
+        assert!(!apparmor_confined("/usr/bin/securewipe (complain)\n"));
+        assert!(!apparmor_confined("unconfined\n"));
+        assert!(!apparmor_confined("unconfined_u:unconfined_r:unconfined_t:s0-s0:c0.c1023\0"));
+    }
+}
@@ -1,24 +1,117 @@
+// The verification build (no `operations` feature) leaves much of the shared code
+// below unused; it is compiled but never reached
+#![cfg_attr(not(feature = "operations"), allow(dead_code, unused_imports))]
+
 use clap::{Parser, Subcommand};
 use std::process;
 
 mod cmd;
 mod device;
+#[cfg(feature = "operations")]
 mod backup;
+#[cfg(feature = "operations")]
 mod wipe;
+#[cfg(feature = "operations")]
+mod wipe_plan;
 mod cert;
+#[cfg(feature = "operations")]
+mod cert_builder;
 mod logging;
 mod signer;
 mod schema;
+#[cfg(feature = "operations")]
+mod signatures;
+#[cfg(feature = "operations")]
+mod label;
+mod inventory;
+#[cfg(feature = "operations")]
+mod kiosk;
+#[cfg(feature = "operations")]
+mod cost;
+#[cfg(feature = "operations")]
+mod approval;
+mod contracts;
+mod policy;
+mod locale;
+#[cfg(feature = "operations")]
+mod telemetry;
+mod config;
+mod secrets;
+mod audit;
+#[cfg(feature = "operations")]
+mod serial;
+mod clock;
+mod qr;
+#[cfg(feature = "operations")]
+mod retention;
+mod remediation;
+mod lsm;
+mod removal;
+#[cfg(feature = "operations")]
+mod priority;
+#[cfg(feature = "operations")]
+mod power;
+#[cfg(feature = "operations")]
+mod virtual_disk;
+mod phone;
+mod paths;
+mod smart;
+mod probe_cache;
+mod tool_json;
+mod device_id;
+mod risk;
+mod containers;
+mod glob;
+#[cfg(feature = "operations")]
+mod annotations;
+#[cfg(feature = "operations")]
+mod tasks;
+mod cert_id;
+mod events;
+mod instrument;
+mod cleanup;
+#[cfg(feature = "operations")]
+mod identify;
+#[cfg(feature = "operations")]
+mod hardening;
+mod host;
+mod system_cert;
 
-use cmd::{DiscoverArgs, BackupArgs, WipeArgs, CertArgs};
+use cmd::{DiscoverArgs, CertArgs, ConfigOverrides};
+#[cfg(feature = "operations")]
+use cmd::{BackupArgs, WipeArgs, DestroyArgs, InventoryArgs, ApprovalArgs, SchemaArgs, ConfigArgs, SecretsArgs, KeysArgs, CleanupArgs, IdentifyArgs, TasksArgs};
 use logging::Logger;
 // ...existing code...
 
 #[derive(Parser)]
 #[command(name = "securewipe")]
-#[command(about = "Secure backup and NIST-aligned disk wiping tool")]
+#[cfg_attr(feature = "operations", command(about = "Secure backup and NIST-aligned disk wiping tool"))]
+#[cfg_attr(not(feature = "operations"), command(about = "SecureWipe certificate verification (read-only build)"))]
 #[command(version = "1.0.0")]
 struct Cli {
+    #[command(flatten)]
+    config: ConfigOverrides,
+
+    /// Use private signing keys that other users can read
+    #[arg(long, global = true)]
+    insecure_key_perms: bool,
+
+    /// Re-run smartctl/hdparm/nvme probes instead of using cached results
+    #[arg(long, global = true)]
+    no_probe_cache: bool,
+
+    /// Only log warnings and errors (progress and results are still written)
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Log more: -v adds debug lines such as each command run, -vv adds their output
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Write a Chrome trace of every span (discovery, backup, wipe, signing, PDF) to this file
+    #[arg(long, global = true, value_name = "FILE")]
+    trace_json: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -28,11 +121,43 @@ enum Commands {
     /// Discover storage devices with risk classification
     Discover(DiscoverArgs),
     /// Perform encrypted backup to external storage
+    #[cfg(feature = "operations")]
     Backup(BackupArgs),
     /// Execute NIST-aligned disk wipe operations
+    #[cfg(feature = "operations")]
     Wipe(WipeArgs),
     /// Show or export stored certificates
     Cert(CertArgs),
+    /// Certify drives that were physically destroyed
+    #[cfg(feature = "operations")]
+    Destroy(DestroyArgs),
+    /// Query the inventory of every drive seen, wiped or destroyed
+    #[cfg(feature = "operations")]
+    Inventory(InventoryArgs),
+    /// Request or grant second-person approval for HIGH/CRITICAL wipes
+    #[cfg(feature = "operations")]
+    Approval(ApprovalArgs),
+    /// Export JSON Schemas for everything the CLI emits
+    #[cfg(feature = "operations")]
+    Schema(SchemaArgs),
+    /// Show the effective configuration and where each setting came from
+    #[cfg(feature = "operations")]
+    Config(ConfigArgs),
+    /// Store or remove backup and signing-key passphrases in the OS keyring
+    #[cfg(feature = "operations")]
+    Secrets(SecretsArgs),
+    /// Report signing-key activity from the audit log
+    #[cfg(feature = "operations")]
+    Keys(KeysArgs),
+    /// Remove temp files, session records and unfinished backups left by interrupted runs
+    #[cfg(feature = "operations")]
+    Cleanup(CleanupArgs),
+    /// Blink a drive's LED so it can be found physically before a wipe
+    #[cfg(feature = "operations")]
+    Identify(IdentifyArgs),
+    /// Run scheduled work such as certificate re-verifications
+    #[cfg(feature = "operations")]
+    Tasks(TasksArgs),
 }
 
 fn main() {
@@ -41,18 +166,65 @@ fn main() {
     let logger = Logger::new();
     
     let cli = Cli::parse();
+    events::configure(cli.quiet, cli.verbose);
+    let trace_guard = instrument::init(cli.trace_json.as_deref());
+    cleanup::install_cancel_handler();
+    config::set_cli_overrides(cli.config.into_layer());
+    signer::allow_insecure_key_perms(cli.insecure_key_perms);
+    if cli.no_probe_cache {
+        probe_cache::disable();
+    }
     
     let result = match cli.command {
         Commands::Discover(args) => cmd::handle_discover(args, &logger),
+        #[cfg(feature = "operations")]
         Commands::Backup(args) => cmd::handle_backup(args, &logger),
+        #[cfg(feature = "operations")]
         Commands::Wipe(args) => cmd::handle_wipe(args, &logger),
         Commands::Cert(args) => cmd::handle_cert(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Destroy(args) => cmd::handle_destroy(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Inventory(args) => cmd::handle_inventory(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Approval(args) => cmd::handle_approval(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Schema(args) => cmd::handle_schema(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Config(args) => cmd::handle_config(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Secrets(args) => cmd::handle_secrets(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Keys(args) => cmd::handle_keys(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Cleanup(args) => cmd::handle_cleanup(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Identify(args) => cmd::handle_identify(args, &logger),
+        #[cfg(feature = "operations")]
+        Commands::Tasks(args) => cmd::handle_tasks(args, &logger),
     };
+    // process::exit skips destructors, and the trace file is written on drop
+    drop(trace_guard);
     
     match result {
         Ok(_) => process::exit(0),
         Err(e) => {
-            eprintln!("Error: {}", e);
+            // Recognised I/O failures carry a class and a hint the UI can show
+            let source: &(dyn std::error::Error + 'static) = e.as_ref();
+            let failure = remediation::classify(source, None);
+            let response = contracts::ErrorResponse {
+                cmd: None,
+                action: None,
+                status: "error".to_string(),
+                error: e.to_string(),
+                error_class: failure.map(|failure| failure.as_str().to_string()),
+                hint: failure.map(|failure| failure.hint().to_string()),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+            events::emit_document(&response);
+            if failure == Some(remediation::IoFailure::DeviceDisconnected) {
+                process::exit(removal::EXIT_DEVICE_REMOVED);
+            }
             process::exit(1);
         }
     }
@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::process::Command;
+
+/// A backup destination volume and whether it was mounted on our behalf
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountedDestination {
+    pub device: String,
+    pub mountpoint: String,
+    pub mounted_by_us: bool,
+}
+
+/// Mount `device` through udisks2 so an unprivileged user can write to it.
+/// A device that is already mounted is returned as-is with `mounted_by_us` unset.
+pub fn mount_destination(device: &str) -> Result<MountedDestination, Box<dyn Error>> {
+    validate_device_path(device)?;
+    removable_disk(device)?;
+
+    if let Some(mountpoint) = current_mountpoint(device)? {
+        return Ok(MountedDestination {
+            device: device.to_string(),
+            mountpoint,
+            mounted_by_us: false,
+        });
+    }
+
+    if filesystem_type(device)?.is_none() {
+        return Err(format!(
+            "{} has no recognizable filesystem; format it before using it as a backup destination",
+            device
+        ).into());
+    }
+
+    let output = run_udisksctl(&["mount", "--block-device", device, "--no-user-interaction"])?;
+    let mountpoint = match parse_mount_output(&output) {
+        Some(mountpoint) => mountpoint,
+        None => current_mountpoint(device)?
+            .ok_or_else(|| format!("udisksctl mounted {} but reported no mount point", device))?,
+    };
+
+    Ok(MountedDestination {
+        device: device.to_string(),
+        mountpoint,
+        mounted_by_us: true,
+    })
+}
+
+/// Flush pending writes and unmount `device` through udisks2
+pub fn unmount_destination(device: &str) -> Result<(), Box<dyn Error>> {
+    validate_device_path(device)?;
+
+    if current_mountpoint(device)?.is_none() {
+        return Ok(());
+    }
+
+    // udisks syncs on unmount too, but an explicit sync surfaces write errors here
+    let _ = Command::new("sync").output();
+    run_udisksctl(&["unmount", "--block-device", device, "--no-user-interaction"])?;
+    Ok(())
+}
+
+/// Sync, unmount every filesystem on the disk holding `device`, then power the disk
+/// off so it is safe to unplug. Returns the whole-disk device that was powered off.
+pub fn eject_destination(device: &str) -> Result<String, Box<dyn Error>> {
+    validate_device_path(device)?;
+    let disk = removable_disk(device)?;
+
+    let mounted = disk_mountpoints(&disk)?;
+
+    let _ = Command::new("sync").output();
+    for (partition, _) in &mounted {
+        run_udisksctl(&["unmount", "--block-device", partition, "--no-user-interaction"])?;
+    }
+
+    // udisks handles USB power-off without root; hdparm standby is the fallback for
+    // bridges that refuse it
+    if let Err(udisks_err) = run_udisksctl(&["power-off", "--block-device", &disk, "--no-user-interaction"]) {
+        let output = Command::new("hdparm").args(["-Y", &disk]).output()?;
+        if !output.status.success() {
+            return Err(format!(
+                "Could not power off {} ({}); hdparm -Y also failed: {}",
+                disk, udisks_err, String::from_utf8_lossy(&output.stderr).trim()
+            ).into());
+        }
+    }
+
+    Ok(disk)
+}
+
+/// The whole disk holding `device`, provided it is one of the removable disks offered as
+/// backup destinations. Internal and system disks are refused before anything touches them.
+fn removable_disk(device: &str) -> Result<String, Box<dyn Error>> {
+    let disk = whole_disk(device)?;
+    let targets = crate::device::LinuxDeviceDiscovery::new_without_enrichment().discover_removable_targets()?;
+    if !targets.iter().any(|volume| volume.backing_device == disk) {
+        return Err(format!("{} is not a removable backup destination", disk).into());
+    }
+    Ok(disk)
+}
+
+/// The whole disk a partition belongs to (a disk maps to itself)
+fn whole_disk(device: &str) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("lsblk")
+        .args(["-n", "-d", "-o", "PKNAME", device])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("{} is not a block device", device).into());
+    }
+    let parent = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(if parent.is_empty() { device.to_string() } else { format!("/dev/{}", parent) })
+}
+
+/// (device, mountpoint) for every mounted filesystem on `disk`
+fn disk_mountpoints(disk: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let output = Command::new("lsblk")
+        .args(["-n", "-l", "-p", "-o", "NAME,MOUNTPOINT", disk])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("{} is not a block device", disk).into());
+    }
+    Ok(parse_lsblk_mountpoints(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_lsblk_mountpoints(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, mountpoint) = line.trim().split_once(char::is_whitespace)?;
+            let mountpoint = mountpoint.trim();
+            if mountpoint.is_empty() {
+                None
+            } else {
+                Some((name.to_string(), mountpoint.to_string()))
+            }
+        })
+        .collect()
+}
+
+/// Block device backing the filesystem that holds `path`, if any
+pub fn backing_device(path: &str) -> Option<String> {
+    let output = Command::new("findmnt")
+        .args(["-n", "-o", "SOURCE", "--target", path])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let source = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if source.starts_with("/dev/") {
+        Some(source)
+    } else {
+        None
+    }
+}
+
+fn validate_device_path(device: &str) -> Result<(), Box<dyn Error>> {
+    if !device.starts_with("/dev/") || device.contains("..") {
+        return Err(format!("Invalid block device path: {}", device).into());
+    }
+    Ok(())
+}
+
+fn current_mountpoint(device: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let output = Command::new("findmnt")
+        .args(["-n", "-o", "TARGET", "--source", device])
+        .output()?;
+    // findmnt exits 1 when the source isn't mounted
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty()))
+}
+
+fn filesystem_type(device: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let output = Command::new("lsblk")
+        .args(["-n", "-d", "-o", "FSTYPE", device])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("{} is not a block device", device).into());
+    }
+    let fstype = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(if fstype.is_empty() { None } else { Some(fstype) })
+}
+
+fn run_udisksctl(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("udisksctl")
+        .args(args)
+        .output()
+        .map_err(|e| -> Box<dyn Error> {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                "udisksctl not found - automatic mounting requires udisks2".into()
+            } else {
+                Box::new(e)
+            }
+        })?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "udisksctl {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Extract the mount point from "Mounted /dev/sdb1 at /media/user/LABEL"
+fn parse_mount_output(output: &str) -> Option<String> {
+    let line = output.lines().find(|line| line.starts_with("Mounted "))?;
+    let (_, mountpoint) = line.split_once(" at ")?;
+    // Older udisks releases end the message with a period
+    let mountpoint = mountpoint.trim().trim_end_matches('.');
+    if mountpoint.is_empty() {
+        None
+    } else {
+        Some(mountpoint.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mount_output() {
+        assert_eq!(
+            parse_mount_output("Mounted /dev/sdb1 at /media/user/BACKUP\n"),
+            Some("/media/user/BACKUP".to_string())
+        );
+        assert_eq!(
+            parse_mount_output("Mounted /dev/sdc1 at /run/media/user/USB DISK.\n"),
+            Some("/run/media/user/USB DISK".to_string())
+        );
+        assert_eq!(parse_mount_output("Error mounting /dev/sdb1\n"), None);
+    }
+
+    #[test]
+    fn test_parse_lsblk_mountpoints() {
+        let output = "/dev/sdb\n/dev/sdb1 /media/user/BACKUP\n/dev/sdb2 /media/user/My Files\n/dev/sdb3\n";
+        assert_eq!(
+            parse_lsblk_mountpoints(output),
+            vec![
+                ("/dev/sdb1".to_string(), "/media/user/BACKUP".to_string()),
+                ("/dev/sdb2".to_string(), "/media/user/My Files".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_device_path() {
+        assert!(validate_device_path("/dev/sdb1").is_ok());
+        assert!(validate_device_path("sdb1").is_err());
+        assert!(validate_device_path("/dev/../etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Where SecureWipe keeps its files when `data_dir` isn't configured. A user's data
+//! goes under `$XDG_DATA_HOME/securewipe` and their config file under
+//! `$XDG_CONFIG_HOME/securewipe`. A `~/SecureWipe` from older releases stays in use
+//! until `securewipe config migrate` moves it. Run as a root service (systemd, or no
+//! home directory), or with SECUREWIPE_SYSTEM=1, everything goes under
+//! `/var/lib/securewipe` and the only config file is `/etc/securewipe/config.json`.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::SYSTEM_CONFIG_PATH;
+
+pub const SYSTEM_DATA_DIR: &str = "/var/lib/securewipe";
+/// The per-user directory every release before XDG support used
+pub const LEGACY_DIR_NAME: &str = "SecureWipe";
+const APP_DIR_NAME: &str = "securewipe";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PathsError {
+    #[error("Cannot determine home directory")]
+    NoHome,
+    #[error("Nothing to migrate: {0} is not a directory")]
+    NothingToMigrate(PathBuf),
+    #[error("{0} already exists and is not empty; move its contents aside first")]
+    TargetNotEmpty(PathBuf),
+    #[error("{from} and {to} are on different filesystems; move the directory by hand and set data_dir")]
+    CrossDevice { from: PathBuf, to: PathBuf },
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutKind {
+    Xdg,
+    /// `~/SecureWipe`, not migrated yet
+    Legacy,
+    System,
+}
+
+impl LayoutKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LayoutKind::Xdg => "xdg",
+            LayoutKind::Legacy => "legacy",
+            LayoutKind::System => "system",
+        }
+    }
+}
+
+/// The default data directory and config file for this run
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Layout {
+    pub kind: LayoutKind,
+    pub data_dir: PathBuf,
+    /// The user config file; the system file itself in system mode
+    pub config_file: PathBuf,
+}
+
+impl Layout {
+    /// The layout in use: system, then an unmigrated `~/SecureWipe`, then XDG
+    pub fn detect() -> Result<Self, PathsError> {
+        Self::resolve(&|var| std::env::var(var).ok(), dirs::home_dir().as_deref(), is_root(), true)
+    }
+
+    /// Where a fresh install would put things, ignoring `~/SecureWipe`; what
+    /// `migrate` moves it to
+    pub fn preferred() -> Result<Self, PathsError> {
+        Self::resolve(&|var| std::env::var(var).ok(), dirs::home_dir().as_deref(), is_root(), false)
+    }
+
+    pub fn system() -> Self {
+        Layout {
+            kind: LayoutKind::System,
+            data_dir: PathBuf::from(SYSTEM_DATA_DIR),
+            config_file: PathBuf::from(SYSTEM_CONFIG_PATH),
+        }
+    }
+
+    pub fn legacy(home: &Path) -> Self {
+        let data_dir = home.join(LEGACY_DIR_NAME);
+        Layout { kind: LayoutKind::Legacy, config_file: data_dir.join("config.json"), data_dir }
+    }
+
+    fn resolve(
+        env: &dyn Fn(&str) -> Option<String>,
+        home: Option<&Path>,
+        root: bool,
+        keep_legacy: bool,
+    ) -> Result<Self, PathsError> {
+        // systemd sets INVOCATION_ID for every unit it starts
+        let service = root && (env("INVOCATION_ID").is_some() || home.is_none());
+        if env("SECUREWIPE_SYSTEM").as_deref() == Some("1") || service {
+            return Ok(Self::system());
+        }
+        let home = home.ok_or(PathsError::NoHome)?;
+        if keep_legacy && is_real_dir(&home.join(LEGACY_DIR_NAME)) {
+            return Ok(Self::legacy(home));
+        }
+
+        // The spec says relative XDG paths are invalid and must be ignored
+        let xdg = |var: &str, fallback: &str| {
+            env(var)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+                .unwrap_or_else(|| home.join(fallback))
+                .join(APP_DIR_NAME)
+        };
+        Ok(Layout {
+            kind: LayoutKind::Xdg,
+            data_dir: xdg("XDG_DATA_HOME", ".local/share"),
+            config_file: xdg("XDG_CONFIG_HOME", ".config").join("config.json"),
+        })
+    }
+}
+
+/// A move of `~/SecureWipe` into another layout
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Migration {
+    pub from: PathBuf,
+    pub to: Layout,
+    /// The old config file, when it moves too (the new one doesn't exist yet)
+    pub config_file: Option<PathBuf>,
+}
+
+impl Migration {
+    /// What moving `home`'s `~/SecureWipe` into `to` involves, or why it can't happen
+    pub fn plan(home: &Path, to: Layout) -> Result<Self, PathsError> {
+        let from = home.join(LEGACY_DIR_NAME);
+        if !is_real_dir(&from) {
+            return Err(PathsError::NothingToMigrate(from));
+        }
+        if fs::read_dir(&to.data_dir).map(|mut entries| entries.next().is_some()).unwrap_or(false) {
+            return Err(PathsError::TargetNotEmpty(to.data_dir));
+        }
+        let old_config = from.join("config.json");
+        let config_file = (old_config.is_file() && !to.config_file.exists()).then_some(old_config);
+        Ok(Migration { from, to, config_file })
+    }
+
+    /// Move the directory, then its config file, and leave `~/SecureWipe` as a
+    /// symlink to the new place for scripts that still look there
+    pub fn apply(&self) -> Result<(), PathsError> {
+        if let Some(parent) = self.to.data_dir.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // An empty target (created by an earlier run) is fine to replace
+        let _ = fs::remove_dir(&self.to.data_dir);
+        fs::rename(&self.from, &self.to.data_dir).map_err(|e| match e.raw_os_error() {
+            Some(libc::EXDEV) => PathsError::CrossDevice { from: self.from.clone(), to: self.to.data_dir.clone() },
+            _ => PathsError::Io(e),
+        })?;
+
+        if self.config_file.is_some() {
+            if let Some(parent) = self.to.config_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(self.to.data_dir.join("config.json"), &self.to.config_file)?;
+        }
+        std::os::unix::fs::symlink(&self.to.data_dir, &self.from)?;
+        Ok(())
+    }
+}
+
+/// A directory that isn't a symlink; a migrated `~/SecureWipe` is one
+fn is_real_dir(path: &Path) -> bool {
+    fs::symlink_metadata(path).map(|m| m.is_dir()).unwrap_or(false)
+}
+
+fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_layouts() {
+        let home = tempfile::tempdir().unwrap();
+        let no_env = |_: &str| -> Option<String> { None };
+        let layout = Layout::resolve(&no_env, Some(home.path()), false, true).unwrap();
+        assert_eq!(layout.kind, LayoutKind::Xdg);
+        assert_eq!(layout.data_dir, home.path().join(".local/share/securewipe"));
+        assert_eq!(layout.config_file, home.path().join(".config/securewipe/config.json"));
+
+        let xdg = |var: &str| match var {
+            "XDG_DATA_HOME" => Some("/data".to_string()),
+            "XDG_CONFIG_HOME" => Some("relative/config".to_string()),
+            _ => None,
+        };
+        let layout = Layout::resolve(&xdg, Some(home.path()), false, true).unwrap();
+        assert_eq!(layout.data_dir, PathBuf::from("/data/securewipe"));
+        assert_eq!(layout.config_file, home.path().join(".config/securewipe/config.json"));
+
+        // An existing ~/SecureWipe wins until it's migrated
+        fs::create_dir(home.path().join(LEGACY_DIR_NAME)).unwrap();
+        assert_eq!(Layout::resolve(&no_env, Some(home.path()), false, true).unwrap(), Layout::legacy(home.path()));
+        assert_eq!(Layout::resolve(&no_env, Some(home.path()), false, false).unwrap().kind, LayoutKind::Xdg);
+
+        // Root under systemd or without a home, or asked for explicitly
+        let systemd = |var: &str| (var == "INVOCATION_ID").then(|| "abc".to_string());
+        assert_eq!(Layout::resolve(&systemd, Some(home.path()), true, true).unwrap(), Layout::system());
+        assert_eq!(Layout::resolve(&no_env, None, true, true).unwrap(), Layout::system());
+        assert_eq!(Layout::resolve(&systemd, Some(home.path()), false, true).unwrap().kind, LayoutKind::Legacy);
+        assert!(matches!(Layout::resolve(&no_env, None, false, true), Err(PathsError::NoHome)));
+    }
+
+    #[test]
+    fn test_migrate_legacy_dir() {
+        let home = tempfile::tempdir().unwrap();
+        let legacy = home.path().join(LEGACY_DIR_NAME);
+        fs::create_dir_all(legacy.join("certificates")).unwrap();
+        fs::write(legacy.join("certificates/abc.json"), "{}").unwrap();
+        fs::write(legacy.join("config.json"), "{}").unwrap();
+        let no_env = |_: &str| -> Option<String> { None };
+        let to = Layout::resolve(&no_env, Some(home.path()), false, false).unwrap();
+
+        let migration = Migration::plan(home.path(), to.clone()).unwrap();
+        assert_eq!(migration.config_file, Some(legacy.join("config.json")));
+        migration.apply().unwrap();
+
+        assert!(to.data_dir.join("certificates/abc.json").is_file());
+        assert!(to.config_file.is_file());
+        assert!(!to.data_dir.join("config.json").exists());
+        assert!(legacy.join("certificates/abc.json").is_file(), "the old path still resolves");
+        assert_eq!(Layout::resolve(&no_env, Some(home.path()), false, true).unwrap().kind, LayoutKind::Xdg);
+        assert!(matches!(Migration::plan(home.path(), to), Err(PathsError::NothingToMigrate(_))));
+    }
+}
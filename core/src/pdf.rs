@@ -1,4 +1,5 @@
 use crate::cert::{BackupCertificate, WipeCertificate};
+use crate::locale::DisplayFormat;
 use anyhow::{Context, Result};
 use printpdf::*;
 use std::fs;
@@ -7,11 +8,27 @@ use tracing::{info, warn};
 
 pub struct PdfGenerator {
     verify_base_url: Option<String>,
+    display: DisplayFormat,
 }
 
 impl PdfGenerator {
     pub fn new(verify_base_url: Option<String>) -> Self {
-        Self { verify_base_url }
+        Self { verify_base_url, display: DisplayFormat::current() }
+    }
+
+    /// The certificate's own verify link, else one under the configured base
+    fn qr_data(&self, cert_id: &str, verify_url: Option<&str>) -> String {
+        match (verify_url, &self.verify_base_url) {
+            (Some(url), _) => url.to_string(),
+            (None, Some(base_url)) => format!("{}/{}", base_url.trim_end_matches('/'), cert_id),
+            (None, None) => format!("cert_id:{}", cert_id),
+        }
+    }
+
+    /// Format sizes and dates with `display` instead of the configured settings
+    pub fn with_display(mut self, display: DisplayFormat) -> Self {
+        self.display = display;
+        self
     }
 
     /// Generate PDF certificate from backup certificate JSON
@@ -51,45 +68,34 @@ impl PdfGenerator {
 
         // Certificate Information
         self.add_section(&current_layer, &font, "Certificate Information", &mut y_position);
+        if let Some(ref serial) = cert.serial_number {
+            self.add_field(&current_layer, &font, "Certificate No.", serial, &mut y_position);
+        }
         self.add_field(&current_layer, &font, "Certificate ID", &cert.cert_id, &mut y_position);
         self.add_field(&current_layer, &font, "Type", &cert.cert_type.to_uppercase(), &mut y_position);
         self.add_field(&current_layer, &font, "Version", &cert.certificate_version, &mut y_position);
-        self.add_field(&current_layer, &font, "Created", &cert.created_at, &mut y_position);
-        self.add_field(&current_layer, &font, "Result", &cert.result, &mut y_position);
+        self.add_field(&current_layer, &font, "Created", &self.display.timestamp(&cert.created_at), &mut y_position);
+        self.add_field(&current_layer, &font, "Result", cert.result.as_str(), &mut y_position);
 
         y_position -= 10.0;
 
         // Device Information
         self.add_section(&current_layer, &font, "Device Information", &mut y_position);
-        if let Some(model) = cert.device.get("model").and_then(|v| v.as_str()) {
-            self.add_field(&current_layer, &font, "Model", model, &mut y_position);
-        }
-        if let Some(serial) = cert.device.get("serial").and_then(|v| v.as_str()) {
-            self.add_field(&current_layer, &font, "Serial Number", serial, &mut y_position);
-        }
-        if let Some(capacity) = cert.device.get("capacity_bytes").and_then(|v| v.as_u64()) {
-            let capacity_str = self.format_bytes(capacity);
-            self.add_field(&current_layer, &font, "Capacity", &capacity_str, &mut y_position);
-        }
+        self.add_field(&current_layer, &font, "Model", &cert.device.model, &mut y_position);
+        self.add_field(&current_layer, &font, "Serial Number", &cert.device.serial, &mut y_position);
+        let capacity_str = self.format_bytes(cert.device.capacity_bytes);
+        self.add_field(&current_layer, &font, "Capacity", &capacity_str, &mut y_position);
 
         y_position -= 10.0;
 
         // Backup Summary
         self.add_section(&current_layer, &font, "Backup Summary", &mut y_position);
-        if let Some(count) = cert.files_summary.get("count") {
-            self.add_field(&current_layer, &font, "Files Count", &count.to_string(), &mut y_position);
-        }
-        if let Some(bytes) = cert.files_summary.get("personal_bytes").and_then(|v| v.as_u64()) {
-            let bytes_str = self.format_bytes(bytes);
-            self.add_field(&current_layer, &font, "Personal Bytes", &bytes_str, &mut y_position);
-        }
-        if let Some(alg) = cert.crypto.get("alg").and_then(|v| v.as_str()) {
-            self.add_field(&current_layer, &font, "Encryption", alg, &mut y_position);
-        }
-        if let Some(hash) = cert.crypto.get("manifest_sha256").and_then(|v| v.as_str()) {
-            let hash_display = self.format_hash(hash);
-            self.add_field(&current_layer, &font, "Manifest SHA256", &hash_display, &mut y_position);
-        }
+        self.add_field(&current_layer, &font, "Files Count", &cert.files_summary.count.to_string(), &mut y_position);
+        let bytes_str = self.format_bytes(cert.files_summary.personal_bytes);
+        self.add_field(&current_layer, &font, "Personal Bytes", &bytes_str, &mut y_position);
+        self.add_field(&current_layer, &font, "Encryption", &cert.crypto.alg, &mut y_position);
+        let hash_display = self.format_hash(&cert.crypto.manifest_sha256);
+        self.add_field(&current_layer, &font, "Manifest SHA256", &hash_display, &mut y_position);
 
         y_position -= 10.0;
 
@@ -111,11 +117,7 @@ impl PdfGenerator {
         y_position -= 10.0;
 
         // QR Code info
-        let qr_data = if let Some(base_url) = &self.verify_base_url {
-            format!("{}/verify/{}", base_url, cert.cert_id)
-        } else {
-            format!("cert_id:{}", cert.cert_id)
-        };
+        let qr_data = self.qr_data(&cert.cert_id, cert.verify_url.as_deref());
         self.add_section(&current_layer, &font, "Verification QR Code", &mut y_position);
         self.add_field(&current_layer, &font, "QR Data", &qr_data, &mut y_position);
 
@@ -174,60 +176,72 @@ impl PdfGenerator {
 
         // Certificate Information
         self.add_section(&current_layer, &font, "Certificate Information", &mut y_position);
+        if let Some(ref serial) = cert.serial_number {
+            self.add_field(&current_layer, &font, "Certificate No.", serial, &mut y_position);
+        }
         self.add_field(&current_layer, &font, "Certificate ID", &cert.cert_id, &mut y_position);
         self.add_field(&current_layer, &font, "Type", &cert.cert_type.to_uppercase(), &mut y_position);
-        self.add_field(&current_layer, &font, "Created", &cert.created_at, &mut y_position);
+        self.add_field(&current_layer, &font, "Created", &self.display.timestamp(&cert.created_at), &mut y_position);
         
-        let result = cert.wipe_summary.get("verification_passed")
-            .and_then(|v| v.as_bool())
-            .map(|passed| if passed { "PASS" } else { "FAIL" })
-            .unwrap_or("UNKNOWN");
-        self.add_field(&current_layer, &font, "Result", result, &mut y_position);
+        self.add_field(&current_layer, &font, "Result", cert.result.as_str(), &mut y_position);
 
         y_position -= 10.0;
 
         // Device Information
         self.add_section(&current_layer, &font, "Device Information", &mut y_position);
-        if let Some(model) = cert.device.get("model").and_then(|v| v.as_str()) {
-            self.add_field(&current_layer, &font, "Model", model, &mut y_position);
-        }
-        if let Some(serial) = cert.device.get("serial").and_then(|v| v.as_str()) {
-            self.add_field(&current_layer, &font, "Serial Number", serial, &mut y_position);
-        }
-        if let Some(capacity) = cert.device.get("capacity_bytes").and_then(|v| v.as_u64()) {
-            let capacity_str = self.format_bytes(capacity);
-            self.add_field(&current_layer, &font, "Capacity", &capacity_str, &mut y_position);
-        }
+        self.add_field(&current_layer, &font, "Model", &cert.device.model, &mut y_position);
+        self.add_field(&current_layer, &font, "Serial Number", &cert.device.serial, &mut y_position);
+        let capacity_str = self.format_bytes(cert.device.capacity_bytes);
+        self.add_field(&current_layer, &font, "Capacity", &capacity_str, &mut y_position);
 
         y_position -= 10.0;
 
         // Sanitization Policy
         self.add_section(&current_layer, &font, "Sanitization Policy", &mut y_position);
-        if let Some(policy) = cert.wipe_summary.get("policy") {
-            self.add_field(&current_layer, &font, "NIST Level", &policy.to_string(), &mut y_position);
+        self.add_field(&current_layer, &font, "NIST Level", &cert.policy.nist_level, &mut y_position);
+        self.add_field(&current_layer, &font, "Method", &cert.policy.method, &mut y_position);
+        if let Some(overwrite) = &cert.policy.overwrite {
+            let passes = overwrite["passes"].as_array().map_or(0, |passes| passes.len());
+            let summary = format!("{} ({} pass(es))", overwrite["profile"].as_str().unwrap_or("unknown"), passes);
+            self.add_field(&current_layer, &font, "Overwrite Profile", &summary, &mut y_position);
         }
-        if let Some(method) = cert.wipe_summary.get("method") {
-            self.add_field(&current_layer, &font, "Method", &method.to_string(), &mut y_position);
+        if let Some(hpa_dco) = &cert.hpa_dco {
+            let status = match hpa_dco.result {
+                Some(result) => result.as_str(),
+                None => "Not verified",
+            };
+            self.add_field(&current_layer, &font, "HPA/DCO Clear", status, &mut y_position);
         }
 
         y_position -= 10.0;
 
         // Verification Results
         self.add_section(&current_layer, &font, "Verification Results", &mut y_position);
-        if let Some(samples) = cert.wipe_summary.get("verification_samples") {
+        if let Some(samples) = cert.verify.samples {
             self.add_field(&current_layer, &font, "Samples Verified", &samples.to_string(), &mut y_position);
         }
-        if let Some(passed) = cert.wipe_summary.get("verification_passed") {
-            self.add_field(&current_layer, &font, "Verification Passed", &passed.to_string(), &mut y_position);
+        if let Some(result) = &cert.verify.result {
+            self.add_field(&current_layer, &font, "Verification Result", result.as_str(), &mut y_position);
+        }
+        if let Some(pattern) = &cert.verify.pattern {
+            let checked = pattern["sectors_checked"].as_u64().unwrap_or(0);
+            let mismatched = pattern["sectors_mismatched"].as_u64().unwrap_or(0);
+            let scope = if pattern["full"].as_bool().unwrap_or(false) { "all sectors" } else { "sampled sectors" };
+            let summary = format!("{}/{} {} match", checked - mismatched.min(checked), checked, scope);
+            self.add_field(&current_layer, &font, "Pattern Readback", &summary, &mut y_position);
+        }
+        // Regions the overwrite skipped; the full list is in the signed JSON
+        if cert.exceptions.items.as_ref().is_some_and(|items| !items.is_empty()) {
+            if let Some(text) = &cert.exceptions.text {
+                self.add_field(&current_layer, &font, "Exceptions", text, &mut y_position);
+            }
         }
 
         // Linkage (if present)
         if let Some(linkage) = &cert.linkage {
-            if let Some(backup_cert_id) = linkage.get("backup_cert_id") {
-                y_position -= 10.0;
-                self.add_section(&current_layer, &font, "Evidence & Linkage", &mut y_position);
-                self.add_field(&current_layer, &font, "Backup Certificate ID", &backup_cert_id.to_string(), &mut y_position);
-            }
+            y_position -= 10.0;
+            self.add_section(&current_layer, &font, "Evidence & Linkage", &mut y_position);
+            self.add_field(&current_layer, &font, "Backup Certificate ID", &linkage.backup_cert_id, &mut y_position);
         }
 
         y_position -= 10.0;
@@ -250,11 +264,7 @@ impl PdfGenerator {
         y_position -= 10.0;
 
         // QR Code info
-        let qr_data = if let Some(base_url) = &self.verify_base_url {
-            format!("{}/verify/{}", base_url, cert.cert_id)
-        } else {
-            format!("cert_id:{}", cert.cert_id)
-        };
+        let qr_data = self.qr_data(&cert.cert_id, cert.verify_url.as_deref());
         self.add_section(&current_layer, &font, "Verification QR Code", &mut y_position);
         self.add_field(&current_layer, &font, "QR Data", &qr_data, &mut y_position);
 
@@ -299,17 +309,7 @@ impl PdfGenerator {
 
     /// Format bytes in human readable format
     fn format_bytes(&self, bytes: u64) -> String {
-        if bytes >= 1_000_000_000_000 {
-            format!("{:.2} TB", bytes as f64 / 1_000_000_000_000.0)
-        } else if bytes >= 1_000_000_000 {
-            format!("{:.2} GB", bytes as f64 / 1_000_000_000.0)
-        } else if bytes >= 1_000_000 {
-            format!("{:.2} MB", bytes as f64 / 1_000_000.0)
-        } else if bytes >= 1_000 {
-            format!("{:.2} KB", bytes as f64 / 1_000.0)
-        } else {
-            format!("{} bytes", bytes)
-        }
+        self.display.bytes_exact(bytes)
     }
 
     /// Format hash for display (truncate long hashes)
@@ -324,10 +324,7 @@ impl PdfGenerator {
 
 /// Ensure the certificates directory exists
 pub fn ensure_certificates_dir() -> Result<PathBuf> {
-    let home_dir = dirs::home_dir()
-        .context("Failed to get home directory")?;
-    
-    let certs_dir = home_dir.join("SecureWipe").join("certificates");
+    let certs_dir = crate::config::Config::load()?.certificates_dir;
     
     if !certs_dir.exists() {
         fs::create_dir_all(&certs_dir)
@@ -349,69 +346,67 @@ pub fn extract_embedded_json(pdf_path: &Path) -> Result<Option<String>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cert::{BackupCertificate, WipeCertificate, CertificateSignature};
+    use crate::cert::{BackupCertificate, WipeCertificate};
     use tempfile::TempDir;
     use std::fs;
 
     fn create_test_backup_cert() -> BackupCertificate {
-        BackupCertificate {
-            cert_id: "test_backup_123".to_string(),
-            cert_type: "backup".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: "2023-12-05T14:30:22.123456Z".to_string(),
-            issuer: serde_json::json!({"organization": "SecureWipe (SIH)"}),
-            device: serde_json::json!({
+        serde_json::from_value(serde_json::json!({
+            "cert_id": "test_backup_123",
+            "cert_type": "backup",
+            "certificate_version": "v1.0.0",
+            "created_at": "2023-12-05T14:30:22.123456Z",
+            "issuer": {"organization": "SecureWipe (SIH)", "tool_name": "securewipe", "tool_version": "v1.0.0"},
+            "device": {
                 "model": "Test SSD 1TB",
                 "serial": "TEST123456",
+                "bus": "NVMe",
                 "capacity_bytes": 1000000000000u64
-            }),
-            files_summary: serde_json::json!({
-                "count": 100,
-                "personal_bytes": 500000000u64
-            }),
-            destination: serde_json::json!({"type": "other"}),
-            crypto: serde_json::json!({"alg": "AES-256-CTR", "manifest_sha256": "a1b2c3d4e5f67890123456789012345678901234567890123456789012345678"}),
-            verification: serde_json::json!({"strategy": "sampled_files"}),
-            policy: serde_json::json!({"name": "NIST SP 800-88 Rev.1"}),
-            result: "PASS".to_string(),
-            environment: serde_json::json!({"operator": "test"}),
-            exceptions: serde_json::json!({"text": "None"}),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "test_signature_data_here".to_string(),
-            }),
-            metadata: serde_json::json!({}),
-            verify_url: "http://localhost:8000/verify".to_string(),
-        }
+            },
+            "files_summary": {"count": 100, "personal_bytes": 500000000u64},
+            "destination": {"type": "other"},
+            "crypto": {
+                "alg": "AES-256-CTR",
+                "manifest_sha256": "a1b2c3d4e5f67890123456789012345678901234567890123456789012345678",
+                "key_management": "ephemeral_session_key"
+            },
+            "verification": {"strategy": "sampled_files", "failures": 0},
+            "policy": {"name": "NIST SP 800-88 Rev.1", "version": "2023.12"},
+            "result": "PASS",
+            "environment": {"operator": "test", "os_kernel": "Linux 6.8.0", "tool_version": "v1.0.0"},
+            "exceptions": {"text": "None"},
+            "signature": {"alg": "Ed25519", "pubkey_id": "sih_root_v1", "sig": "test_signature_data_here"},
+            "metadata": {},
+            "verify_url": "http://localhost:8000/verify"
+        }))
+        .unwrap()
     }
 
     fn create_test_wipe_cert() -> WipeCertificate {
-        WipeCertificate {
-            cert_id: "test_wipe_456".to_string(),
-            cert_type: "wipe".to_string(),
-            certificate_version: "v1.0.0".to_string(),
-            created_at: "2023-12-05T15:00:30.654321Z".to_string(),
-            device: serde_json::json!({
+        serde_json::from_value(serde_json::json!({
+            "cert_id": "test_wipe_456",
+            "cert_type": "wipe",
+            "certificate_version": "v1.0.0",
+            "created_at": "2023-12-05T15:00:30.654321Z",
+            "issuer": {"organization": "SecureWipe (SIH)", "tool_name": "securewipe", "tool_version": "v1.0.0"},
+            "device": {
                 "model": "Test SSD 1TB",
                 "serial": "TEST123456",
+                "bus": "NVMe",
                 "capacity_bytes": 1000000000000u64
-            }),
-            wipe_summary: serde_json::json!({
-                "policy": "PURGE",
-                "method": "nvme_sanitize",
-                "verification_samples": 5,
-                "verification_passed": true
-            }),
-            linkage: Some(serde_json::json!({
-                "backup_cert_id": "test_backup_123"
-            })),
-            signature: Some(CertificateSignature {
-                alg: "Ed25519".to_string(),
-                pubkey_id: "sih_root_v1".to_string(),
-                sig: "test_wipe_signature_data_here".to_string(),
-            }),
-        }
+            },
+            "policy": {"nist_level": "PURGE", "method": "nvme_sanitize"},
+            "commands": [],
+            "verify": {"strategy": "controller_status", "samples": 5, "failures": 0, "result": "PASS"},
+            "result": "PASS",
+            "environment": {"operator": "test", "os_kernel": "Linux 6.8.0", "tool_version": "v1.0.0"},
+            "evidence": {},
+            "linkage": {"backup_cert_id": "test_backup_123"},
+            "exceptions": {"text": "None"},
+            "signature": {"alg": "Ed25519", "pubkey_id": "sih_root_v1", "sig": "test_wipe_signature_data_here"},
+            "metadata": {}
+        }))
+        .unwrap()
     }
 
     #[test]
@@ -454,13 +449,16 @@ mod tests {
 
     #[test]
     fn test_format_bytes() {
-        let generator = PdfGenerator::new(None);
+        let generator = PdfGenerator::new(None).with_display(DisplayFormat::default());
         
-        assert_eq!(generator.format_bytes(1024), "1.02 KB");
-        assert_eq!(generator.format_bytes(1_000_000), "1.00 MB");
-        assert_eq!(generator.format_bytes(1_000_000_000), "1.00 GB");
-        assert_eq!(generator.format_bytes(1_000_000_000_000), "1.00 TB");
+        assert_eq!(generator.format_bytes(1024), "1.02 kB (1,024 bytes)");
+        assert_eq!(generator.format_bytes(1_000_000), "1.00 MB (1,000,000 bytes)");
+        assert_eq!(generator.format_bytes(1_000_000_000), "1.00 GB (1,000,000,000 bytes)");
+        assert_eq!(generator.format_bytes(1_000_000_000_000), "1.00 TB (1,000,000,000,000 bytes)");
         assert_eq!(generator.format_bytes(500), "500 bytes");
+
+        let binary = generator.with_display(DisplayFormat::new("C", crate::locale::Units::Iec));
+        assert_eq!(binary.format_bytes(1_000_204_886_016), "931.51 GiB (1,000,204,886,016 bytes)");
     }
 
     #[test]
@@ -0,0 +1,166 @@
+//! Ed25519 stamp over the bytes of an exported certificate PDF, so a PDF edited after
+//! export is caught without the certificate JSON. The stamp is one PDF comment line
+//! appended after the document's final `%%EOF`, followed by a new `%%EOF`:
+//!
+//! ```text
+//! %SecureWipe-Signature <base64 JSON stamp>
+//! %%EOF
+//! ```
+//!
+//! Readers skip comments and still find the original `startxref`, so the PDF renders
+//! as before. The stamp signs the SHA-256 of every byte before it; anything changed
+//! before the stamp or appended after it fails verification.
+
+use crate::audit::{key_id, public_key_id};
+use crate::signer::canonicalize_json;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const STAMP_MARKER: &[u8] = b"\n%SecureWipe-Signature ";
+const STAMP_TRAILER: &[u8] = b"%%EOF\n";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PdfStampError {
+    #[error("PDF has no SecureWipe signature stamp")]
+    Unsigned,
+    #[error("Malformed PDF signature stamp: {0}")]
+    Malformed(String),
+    #[error("PDF signature check failed: {0}")]
+    Signature(String),
+}
+
+/// The signed statement about a PDF's bytes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PdfStamp {
+    pub alg: String,
+    pub key_id: String,
+    pub cert_id: String,
+    pub signed_at: String,
+    /// SHA-256 of the PDF bytes before the stamp, hex
+    pub sha256: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sig: Option<String>,
+}
+
+impl PdfStamp {
+    fn message(&self) -> Result<Vec<u8>, PdfStampError> {
+        let unsigned = PdfStamp { sig: None, ..self.clone() };
+        let value = serde_json::to_value(unsigned).map_err(|e| PdfStampError::Malformed(e.to_string()))?;
+        canonicalize_json(&value).map_err(|e| PdfStampError::Malformed(e.to_string()))
+    }
+}
+
+/// Stamp `pdf` for certificate `cert_id`. An existing stamp is replaced.
+pub fn stamp(pdf: &[u8], cert_id: &str, signing_key: &SigningKey) -> Result<Vec<u8>, PdfStampError> {
+    let mut signed = match split(pdf) {
+        Some((signed, _)) => signed.to_vec(),
+        None => pdf.to_vec(),
+    };
+    if !signed.ends_with(b"\n") {
+        signed.push(b'\n');
+    }
+
+    let mut stamp = PdfStamp {
+        alg: "Ed25519".to_string(),
+        key_id: key_id(signing_key),
+        cert_id: cert_id.to_string(),
+        signed_at: chrono::Utc::now().to_rfc3339(),
+        sha256: format!("{:x}", Sha256::digest(&signed)),
+        sig: None,
+    };
+    stamp.sig = Some(STANDARD.encode(signing_key.sign(&stamp.message()?).to_bytes()));
+    let encoded = STANDARD.encode(serde_json::to_vec(&stamp).map_err(|e| PdfStampError::Malformed(e.to_string()))?);
+
+    let mut out = signed;
+    out.extend_from_slice(&STAMP_MARKER[1..]);
+    out.extend_from_slice(encoded.as_bytes());
+    out.push(b'\n');
+    out.extend_from_slice(STAMP_TRAILER);
+    Ok(out)
+}
+
+/// Check the stamp on `pdf` against `public_key` and return it
+pub fn verify(pdf: &[u8], public_key: &[u8; 32]) -> Result<PdfStamp, PdfStampError> {
+    let (signed, line) = split(pdf).ok_or(PdfStampError::Unsigned)?;
+    let tail = &pdf[signed.len() + STAMP_MARKER.len() - 1 + line.len()..];
+    if tail != [b"\n".as_slice(), STAMP_TRAILER].concat().as_slice() {
+        return Err(PdfStampError::Signature("content was added after the stamp".to_string()));
+    }
+
+    let json = STANDARD.decode(line).map_err(|e| PdfStampError::Malformed(format!("invalid base64: {}", e)))?;
+    let stamp: PdfStamp = serde_json::from_slice(&json).map_err(|e| PdfStampError::Malformed(e.to_string()))?;
+    if stamp.alg != "Ed25519" {
+        return Err(PdfStampError::Malformed(format!("unsupported algorithm {}", stamp.alg)));
+    }
+    if stamp.key_id != public_key_id(public_key) {
+        return Err(PdfStampError::Signature(format!("stamped by key {}, not the key given", stamp.key_id)));
+    }
+    if stamp.sha256 != format!("{:x}", Sha256::digest(signed)) {
+        return Err(PdfStampError::Signature("PDF content does not match the stamped hash".to_string()));
+    }
+
+    let sig = stamp.sig.as_deref().ok_or_else(|| PdfStampError::Malformed("missing sig".to_string()))?;
+    let sig = STANDARD.decode(sig).map_err(|e| PdfStampError::Malformed(format!("invalid base64 sig: {}", e)))?;
+    let sig = Signature::from_slice(&sig).map_err(|_| PdfStampError::Malformed("signature has the wrong length".to_string()))?;
+    let key = VerifyingKey::from_bytes(public_key).map_err(|e| PdfStampError::Signature(format!("invalid public key: {}", e)))?;
+    key.verify(&stamp.message()?, &sig)
+        .map_err(|_| PdfStampError::Signature("stamp signature is not valid".to_string()))?;
+    Ok(stamp)
+}
+
+/// The signed bytes and the stamp's base64 text, when `pdf` carries a stamp
+fn split(pdf: &[u8]) -> Option<(&[u8], &[u8])> {
+    let start = pdf.windows(STAMP_MARKER.len()).rposition(|window| window == STAMP_MARKER)?;
+    let signed = &pdf[..=start];
+    let rest = &pdf[start + STAMP_MARKER.len()..];
+    let end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+    Some((signed, &rest[..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    const PDF: &[u8] = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\nstartxref\n9\n%%EOF\n";
+
+    #[test]
+    fn test_pdf_stamp_roundtrip() {
+        let key = SigningKey::generate(&mut OsRng);
+        let stamped = stamp(PDF, "cert-1", &key).unwrap();
+        assert!(stamped.starts_with(PDF));
+        assert!(stamped.ends_with(b"%%EOF\n"));
+
+        let checked = verify(&stamped, &key.verifying_key().to_bytes()).unwrap();
+        assert_eq!(checked.cert_id, "cert-1");
+        assert_eq!(checked.key_id, key_id(&key));
+
+        // Re-stamping replaces the stamp rather than signing the old one
+        let restamped = stamp(&stamped, "cert-1", &key).unwrap();
+        assert_eq!(split(&restamped).unwrap().0, PDF);
+        assert!(verify(&restamped, &key.verifying_key().to_bytes()).is_ok());
+
+        assert!(matches!(verify(PDF, &key.verifying_key().to_bytes()), Err(PdfStampError::Unsigned)));
+    }
+
+    #[test]
+    fn test_pdf_stamp_detects_tampering() {
+        let key = SigningKey::generate(&mut OsRng);
+        let public_key = key.verifying_key().to_bytes();
+        let stamped = stamp(PDF, "cert-1", &key).unwrap();
+
+        let mut edited = stamped.clone();
+        edited[12] ^= 0x01;
+        assert!(matches!(verify(&edited, &public_key), Err(PdfStampError::Signature(_))));
+
+        // An incremental update appended after the stamp
+        let mut appended = stamped.clone();
+        appended.extend_from_slice(b"2 0 obj\n<< >>\nendobj\n%%EOF\n");
+        assert!(matches!(verify(&appended, &public_key), Err(PdfStampError::Signature(_))));
+
+        let other = SigningKey::generate(&mut OsRng);
+        assert!(matches!(verify(&stamped, &other.verifying_key().to_bytes()), Err(PdfStampError::Signature(_))));
+    }
+}
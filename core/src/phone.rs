@@ -0,0 +1,350 @@
+//! Phones as a backup source, for pulling photos off a handset before it is reset.
+//! `--source adb:` copies the requested folders off the phone with `adb pull` into a
+//! private staging directory; `--source mtp:` mounts the phone with jmtpfs. Either way
+//! the files then go through the normal backup pipeline, so they are hashed into the
+//! same manifest and certificate as a disk backup.
+
+#[cfg(feature = "operations")]
+use crate::cert::{BusType, CertificateDevice};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Folders pulled over ADB when no `--paths` are given
+const ADB_DEFAULT_PATHS: &[&str] = &["/sdcard/DCIM", "/sdcard/Pictures", "/sdcard/Movies", "/sdcard/Download", "/sdcard/Documents"];
+
+/// Folders looked for in each MTP storage when no `--paths` are given
+const MTP_DEFAULT_DIRS: &[&str] = &["DCIM", "Pictures", "Movies", "Download", "Documents"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum PhoneError {
+    #[error("Invalid --source value: {0}. Must be adb:[<serial>] or mtp:[<bus>,<device>]")]
+    Spec(String),
+    #[error("{0}")]
+    Tool(String),
+    #[error("No {0} phone found. Connect it with USB, unlock it and allow access (file transfer for MTP, USB debugging for ADB)")]
+    NoDevice(&'static str),
+    #[error("Several {transport} phones are connected ({ids}); pick one with --source {transport}:<id>")]
+    Ambiguous { transport: &'static str, ids: String },
+    #[error("Phone {0} is {1}; unlock it and accept the prompt on its screen")]
+    NotReady(String, String),
+    #[error("Phone error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    Adb,
+    Mtp,
+}
+
+impl Transport {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transport::Adb => "adb",
+            Transport::Mtp => "mtp",
+        }
+    }
+}
+
+/// A parsed `--source` selector
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhoneSource {
+    pub transport: Transport,
+    /// ADB serial or MTP `<bus>,<device>`; None picks the only phone connected
+    pub id: Option<String>,
+}
+
+impl PhoneSource {
+    pub fn parse(spec: &str) -> Result<Self, PhoneError> {
+        let (transport, id) = spec.split_once(':').ok_or_else(|| PhoneError::Spec(spec.to_string()))?;
+        let transport = match transport {
+            "adb" => Transport::Adb,
+            "mtp" => Transport::Mtp,
+            _ => return Err(PhoneError::Spec(spec.to_string())),
+        };
+        let id = Some(id.trim()).filter(|id| !id.is_empty()).map(str::to_string);
+        Ok(PhoneSource { transport, id })
+    }
+}
+
+/// A connected phone
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PhoneDevice {
+    pub transport: Transport,
+    pub id: String,
+    pub model: String,
+    pub vendor: Option<String>,
+    /// ADB state (`device`, `unauthorized`, `offline`); always `device` for MTP
+    pub state: String,
+}
+
+impl PhoneDevice {
+    /// Directory name for the phone's files, which also prefixes its manifest paths
+    pub fn label(&self) -> String {
+        format!("{}-{}", self.model, self.id)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect()
+    }
+
+    #[cfg(feature = "operations")]
+    pub fn certificate_device(&self) -> CertificateDevice {
+        let source = format!("{}:{}", self.transport.as_str(), self.id);
+        let mut device = crate::cert_builder::unknown_device(Some(&source));
+        device.model = match self.vendor {
+            Some(ref vendor) => format!("{} {}", vendor, self.model),
+            None => self.model.clone(),
+        };
+        // An ADB serial is the handset's serial number; MTP doesn't report one
+        if self.transport == Transport::Adb {
+            device.serial = self.id.clone();
+        }
+        device.bus = BusType::Usb;
+        device
+    }
+}
+
+/// Phones connected over `transport`
+pub fn list_devices(transport: Transport) -> Result<Vec<PhoneDevice>, PhoneError> {
+    match transport {
+        Transport::Adb => Ok(parse_adb_devices(&run_tool("adb", &["devices", "-l"])?)),
+        Transport::Mtp => Ok(parse_mtp_devices(&run_tool("jmtpfs", &["-l"])?)),
+    }
+}
+
+fn run_tool(program: &str, args: &[&str]) -> Result<String, PhoneError> {
+    let output = Command::new(program).args(args).output().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => PhoneError::Tool(format!(
+            "{} is not installed; install {} to back up phones over {}",
+            program,
+            if program == "adb" { "android-tools (adb)" } else { "jmtpfs" },
+            if program == "adb" { "ADB" } else { "MTP" }
+        )),
+        _ => PhoneError::Io(e),
+    })?;
+    if !output.status.success() {
+        return Err(PhoneError::Tool(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `adb devices -l`: `<serial> <state> usb:1-2 product:x model:Pixel_6 device:oriole ...`
+fn parse_adb_devices(output: &str) -> Vec<PhoneDevice> {
+    output.lines()
+        .skip_while(|line| !line.starts_with("List of devices"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let id = fields.next()?.to_string();
+            let state = fields.next()?.to_string();
+            let model = fields.find_map(|field| field.strip_prefix("model:"))
+                .map(|model| model.replace('_', " "))
+                .unwrap_or_else(|| "Android device".to_string());
+            Some(PhoneDevice { transport: Transport::Adb, id, model, vendor: None, state })
+        })
+        .collect()
+}
+
+/// `jmtpfs -l`: `<busLocation>, <devNum>, <productId>, <vendorId>, <product>, <vendor>`
+fn parse_mtp_devices(output: &str) -> Vec<PhoneDevice> {
+    output.lines()
+        .skip_while(|line| !line.starts_with("Available devices"))
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(5, ", ").collect();
+            let [bus, dev, _product_id, _vendor_id, names] = fields[..] else { return None };
+            let (model, vendor) = names.rsplit_once(", ").unwrap_or((names, ""));
+            Some(PhoneDevice {
+                transport: Transport::Mtp,
+                id: format!("{},{}", bus.trim(), dev.trim()),
+                model: model.trim().to_string(),
+                vendor: Some(vendor.trim().to_string()).filter(|vendor| !vendor.is_empty()),
+                state: "device".to_string(),
+            })
+        })
+        .collect()
+}
+
+fn select(devices: Vec<PhoneDevice>, source: &PhoneSource) -> Result<PhoneDevice, PhoneError> {
+    let transport = source.transport.as_str();
+    let mut matching: Vec<PhoneDevice> = devices.into_iter()
+        .filter(|device| source.id.is_none() || source.id.as_deref() == Some(device.id.as_str()))
+        .collect();
+    let device = match matching.len() {
+        0 => return Err(PhoneError::NoDevice(transport)),
+        1 => matching.remove(0),
+        _ => {
+            let ids = matching.iter().map(|device| device.id.as_str()).collect::<Vec<_>>().join(", ");
+            return Err(PhoneError::Ambiguous { transport, ids });
+        }
+    };
+    if device.state != "device" {
+        return Err(PhoneError::NotReady(device.id, device.state));
+    }
+    Ok(device)
+}
+
+/// A phone's files made available as a local directory: staged with `adb pull`, or an
+/// MTP mount. Removed (or unmounted) when dropped.
+pub struct PhoneFiles {
+    pub device: PhoneDevice,
+    /// The backup's source base, so manifest paths start with the phone's label
+    base: PathBuf,
+    /// `<base>/<label>`
+    root: PathBuf,
+    /// Paths on the phone that were asked for but aren't there
+    pub missing: Vec<String>,
+    local_paths: Vec<String>,
+    mounted: bool,
+}
+
+impl PhoneFiles {
+    /// Find the phone `source` names and bring `paths` (paths on the phone; the
+    /// photo and download folders when empty) into a local directory
+    pub fn open(source: &PhoneSource, paths: &[String]) -> Result<Self, PhoneError> {
+        let device = select(list_devices(source.transport)?, source)?;
+        let base = std::env::temp_dir().join(format!("securewipe-phone-{}", std::process::id()));
+        create_private_dir(&base)?;
+        let root = base.join(device.label());
+        fs::create_dir_all(&root)?;
+
+        let mut files = PhoneFiles { device, base, root, missing: Vec::new(), local_paths: Vec::new(), mounted: false };
+        match source.transport {
+            Transport::Adb => files.pull(paths)?,
+            Transport::Mtp => files.mount(paths)?,
+        }
+        Ok(files)
+    }
+
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// Local copies of the requested paths that exist, for `perform_backup`
+    pub fn local_paths(&self) -> &[String] {
+        &self.local_paths
+    }
+
+    fn pull(&mut self, paths: &[String]) -> Result<(), PhoneError> {
+        let remote: Vec<String> = if paths.is_empty() {
+            ADB_DEFAULT_PATHS.iter().map(|path| path.to_string()).collect()
+        } else {
+            paths.to_vec()
+        };
+        for path in remote {
+            let local = self.root.join(path.trim_start_matches('/'));
+            let parent = local.parent().unwrap_or(&self.root).to_path_buf();
+            fs::create_dir_all(&parent)?;
+            let parent = parent.to_string_lossy().to_string();
+            match run_tool("adb", &["-s", &self.device.id, "pull", "-a", &path, &parent]) {
+                Ok(_) => self.local_paths.push(local.to_string_lossy().to_string()),
+                Err(PhoneError::Tool(message)) if message.contains("does not exist") || message.contains("No such file") => {
+                    self.missing.push(path)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn mount(&mut self, paths: &[String]) -> Result<(), PhoneError> {
+        let device_arg = format!("-device={}", self.device.id);
+        run_tool("jmtpfs", &[&device_arg, &self.root.to_string_lossy()])?;
+        self.mounted = true;
+
+        // Storages ("Internal shared storage", an SD card) are the mount's top-level folders
+        let remote: Vec<String> = if paths.is_empty() {
+            fs::read_dir(&self.root)?
+                .flatten()
+                .flat_map(|storage| {
+                    let storage = storage.file_name().to_string_lossy().to_string();
+                    MTP_DEFAULT_DIRS.iter().map(move |dir| format!("{}/{}", storage, dir))
+                })
+                .filter(|path| self.root.join(path).exists())
+                .collect()
+        } else {
+            paths.to_vec()
+        };
+        for path in remote {
+            let local = self.root.join(path.trim_start_matches('/'));
+            if local.exists() {
+                self.local_paths.push(local.to_string_lossy().to_string());
+            } else {
+                self.missing.push(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PhoneFiles {
+    fn drop(&mut self) {
+        if self.mounted {
+            let _ = Command::new("fusermount").arg("-u").arg(&self.root).status();
+        }
+        // Never delete recursively where the phone may be mounted: that deletes its files
+        if self.device.transport == Transport::Mtp {
+            let _ = fs::remove_dir(&self.root);
+        } else {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+        let _ = fs::remove_dir(&self.base);
+    }
+}
+
+/// The staging area holds plaintext copies of the phone's files, so only we can read it
+fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(PhoneSource::parse("mtp:").unwrap(), PhoneSource { transport: Transport::Mtp, id: None });
+        assert_eq!(
+            PhoneSource::parse("adb:R58M123ABC").unwrap(),
+            PhoneSource { transport: Transport::Adb, id: Some("R58M123ABC".to_string()) }
+        );
+        assert!(PhoneSource::parse("mtp").is_err());
+        assert!(PhoneSource::parse("usb:1").is_err());
+    }
+
+    #[test]
+    fn test_parse_device_lists() {
+        let adb = "* daemon started successfully\nList of devices attached\n\
+            R58M123ABC             device usb:1-2 product:beyond1lteeea model:SM_G973F device:beyond1 transport_id:3\n\
+            emulator-5554          unauthorized usb:1-3 transport_id:4\n\n";
+        let devices = parse_adb_devices(adb);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].model, "SM G973F");
+        assert_eq!(devices[0].label(), "SM_G973F-R58M123ABC");
+        assert_eq!(devices[0].certificate_device().serial, "R58M123ABC");
+        assert!(matches!(
+            select(devices.clone(), &PhoneSource { transport: Transport::Adb, id: Some("emulator-5554".to_string()) }),
+            Err(PhoneError::NotReady(..))
+        ));
+        assert!(matches!(select(devices, &PhoneSource::parse("adb:").unwrap()), Err(PhoneError::Ambiguous { .. })));
+
+        let mtp = "Device 0 (VID=18d1 and PID=4ee1) is a Google Inc Nexus/Pixel (MTP).\n\
+            Available devices (busLocation, devNum, productId, vendorId, product, vendor):\n\
+            1, 7, 0x4ee1, 0x18d1, Nexus/Pixel (MTP), Google Inc\n";
+        let devices = parse_mtp_devices(mtp);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].id, "1,7");
+        assert_eq!(devices[0].certificate_device().model, "Google Inc Nexus/Pixel (MTP)");
+        assert_eq!(select(devices, &PhoneSource::parse("mtp:").unwrap()).unwrap().id, "1,7");
+    }
+}
@@ -0,0 +1,434 @@
+//! Site wipe policy: what this workstation accepts from a wipe beyond the NIST level.
+//! Read from the configured `policy_file` (see `config`); every setting has a default,
+//! so without a file the built-in behaviour applies.
+
+use crate::config::Config;
+use crate::locale::Units;
+use crate::risk::RiskPolicy;
+#[cfg(feature = "operations")]
+use crate::wipe::{BadRegion, OverwriteProfile};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Errors raised while reading the site policy file
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError {
+    #[error("Policy file error: {0}")]
+    File(String),
+    #[error("Site policy sets issuer.strict, but issuer.{0} is not set")]
+    IssuerUnset(&'static str),
+    #[error("Invalid issuer.verify_base_url {0:?}: expected an http:// or https:// URL")]
+    VerifyBaseUrl(String),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct SitePolicy {
+    #[cfg(feature = "operations")]
+    pub bad_regions: BadRegionPolicy,
+    #[cfg(feature = "operations")]
+    pub overwrite: OverwritePolicy,
+    pub retention: RetentionPolicy,
+    pub power: PowerPolicy,
+    pub risk: RiskPolicy,
+    pub backup: BackupRequirement,
+    pub kiosk: KioskPolicy,
+    pub cost: CostPolicy,
+    pub display: DisplayPolicy,
+    pub issuer: IssuerPolicy,
+}
+
+/// Whether a wipe needs a recent backup of the same drive first
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct BackupRequirement {
+    /// Block a wipe unless `--backup-cert-id` names a PASS backup certificate for the
+    /// same drive serial
+    pub require_backup_before_wipe: bool,
+    /// How old that backup may be
+    pub max_age_days: u32,
+    /// Ed25519 public key PEM the backup certificate's signature must verify against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_path: Option<PathBuf>,
+}
+
+impl Default for BackupRequirement {
+    fn default() -> Self {
+        Self { require_backup_before_wipe: false, max_age_days: 30, public_key_path: None }
+    }
+}
+
+impl BackupRequirement {
+    /// Why a wipe of the drive with `serial` is blocked, given the linked backup
+    /// certificate (`None` when no certificate was linked or it couldn't be read)
+    pub fn blocking_reason(
+        &self,
+        backup_cert: Option<&serde_json::Value>,
+        serial: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        if !self.require_backup_before_wipe {
+            return None;
+        }
+        let Some(cert) = backup_cert else {
+            return Some("Site policy requires a backup before wiping; pass --backup-cert-id with a PASS backup certificate for this drive".to_string());
+        };
+        let field = |pointer: &str| cert.pointer(pointer).and_then(|v| v.as_str());
+        let cert_id = field("/cert_id").unwrap_or("the linked certificate");
+        if field("/cert_type") != Some("backup") {
+            return Some(format!("{} is not a backup certificate", cert_id));
+        }
+        if let Some(reason) = self.signature_problem(cert, cert_id) {
+            return Some(reason);
+        }
+        if field("/result") != Some("PASS") {
+            return Some(format!("Backup certificate {} did not pass (result {})", cert_id, field("/result").unwrap_or("missing")));
+        }
+        let Some(serial) = serial else {
+            return Some("The drive's serial number is unknown, so the linked backup can't be matched to it".to_string());
+        };
+        if field("/device/serial") != Some(serial) {
+            return Some(format!(
+                "Backup certificate {} is for drive {}, not {}",
+                cert_id,
+                field("/device/serial").unwrap_or("unknown"),
+                serial
+            ));
+        }
+        let created_at = field("/created_at").and_then(|at| DateTime::parse_from_rfc3339(at).ok());
+        match created_at {
+            Some(at) if now - at.with_timezone(&Utc) <= Duration::days(self.max_age_days as i64) => None,
+            Some(at) => Some(format!(
+                "Backup certificate {} is from {}, older than the {} days site policy allows",
+                cert_id,
+                at.format("%Y-%m-%d"),
+                self.max_age_days
+            )),
+            None => Some(format!("Backup certificate {} has no valid created_at", cert_id)),
+        }
+    }
+
+    /// Why the backup certificate can't be trusted: an unsigned or hand-edited
+    /// certificate must not count as proof of a backup
+    fn signature_problem(&self, cert: &serde_json::Value, cert_id: &str) -> Option<String> {
+        let Some(path) = self.public_key_path.as_deref() else {
+            return Some("Site policy requires a backup before wiping, but backup.public_key_path is not set, so the backup certificate's signature can't be checked".to_string());
+        };
+        let public_key = match crate::signer::load_public_key(path) {
+            Ok(public_key) => public_key,
+            Err(e) => return Some(format!("Cannot read backup.public_key_path: {}", e)),
+        };
+        match crate::signer::verify_certificate_signature(cert, &public_key) {
+            Ok(true) => None,
+            Ok(false) => Some(format!("Backup certificate {} signature does not verify against {}", cert_id, path.display())),
+            Err(e) => Some(format!("Backup certificate {} signature can't be checked: {}", cert_id, e)),
+        }
+    }
+}
+
+/// What a wipe needs from the machine's power supply
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct PowerPolicy {
+    /// Battery charge below which a wipe on battery power is warned about or blocked
+    pub min_battery_percent: u8,
+    /// `block` (default) refuses to start the wipe, `warn` only logs it
+    pub on_low_battery: LowBatteryAction,
+    /// Pause an overwrite while the machine is off mains power, resuming when it's back
+    pub pause_on_power_loss: bool,
+}
+
+impl Default for PowerPolicy {
+    fn default() -> Self {
+        Self {
+            min_battery_percent: 25,
+            on_low_battery: LowBatteryAction::Block,
+            pause_on_power_loss: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LowBatteryAction {
+    Warn,
+    #[default]
+    Block,
+}
+
+/// Typical active power draw per bus, in watts, behind the energy estimate in each
+/// certificate's cost summary. Virtual media is always counted at 0.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct CostPolicy {
+    pub nvme_watts: f64,
+    pub sata_watts: f64,
+    pub sas_watts: f64,
+    pub usb_watts: f64,
+    /// Any other bus
+    pub other_watts: f64,
+}
+
+impl Default for CostPolicy {
+    fn default() -> Self {
+        Self { nvme_watts: 6.0, sata_watts: 6.0, sas_watts: 9.0, usb_watts: 2.5, other_watts: 6.0 }
+    }
+}
+
+/// How a walk-up kiosk station makes sure the drive being wiped is the one the
+/// operator means (see `kiosk`)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct KioskPolicy {
+    /// `off` (default), `single` or `replug`
+    pub interlock: KioskInterlock,
+    /// How long `replug` waits for the drive to be unplugged and plugged back in
+    pub replug_timeout_secs: u64,
+}
+
+impl Default for KioskPolicy {
+    fn default() -> Self {
+        Self { interlock: KioskInterlock::Off, replug_timeout_secs: 120 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KioskInterlock {
+    #[default]
+    Off,
+    /// Refuse to wipe while any other non-critical drive is attached
+    Single,
+    /// Have the operator replug the target before the wipe starts
+    Replug,
+}
+
+/// How PDFs, reports and human CLI output show numbers, sizes and dates (see `locale`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct DisplayPolicy {
+    /// Locale such as `de-DE`; the process locale (LC_ALL, LC_NUMERIC, LANG) when unset
+    pub locale: Option<String>,
+    /// `si` (default, also `decimal`) or `iec` (also `binary`)
+    pub units: Units,
+}
+
+/// Who certificates name as their issuer and where their verify links point (see
+/// `cert::IssuerIdentity`). Unset fields fall back to the built-in identity.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct IssuerPolicy {
+    /// Organization named in each certificate's `issuer` block
+    pub organization: Option<String>,
+    /// Two-letter country code of the organization
+    pub country: Option<String>,
+    /// Base of each certificate's `verify_url`, which is `<verify_base_url>/<cert_id>`
+    pub verify_base_url: Option<String>,
+    /// Refuse to issue certificates or render PDFs while any field above is unset,
+    /// instead of falling back to the built-in identity
+    pub strict: bool,
+}
+
+/// Defaults for `cert archive` and `backup prune` when their flags aren't given.
+/// Nothing is archived or pruned without either a flag or a setting here.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Archive root: certificates go to `<archive_dir>/certificates`, pruned backups
+    /// to `<archive_dir>/backups`
+    pub archive_dir: Option<PathBuf>,
+    /// Age after which certificates are archived, e.g. "2y"
+    pub certificates_older_than: Option<String>,
+    /// Newest backups per destination that are always kept
+    pub backups_keep_last: Option<usize>,
+    /// Age after which backups beyond `backups_keep_last` are pruned, e.g. "180d"
+    pub backups_older_than: Option<String>,
+}
+
+/// Which overwrite scheme runs when `--overwrite-profile` isn't given
+#[cfg(feature = "operations")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct OverwritePolicy {
+    /// `nist-clear` (default), `dod-5220.22-m-3pass` or `gutmann-lite`
+    pub profile: OverwriteProfile,
+}
+
+/// How much of a drive may stay unwritten before a wipe fails outright
+#[cfg(feature = "operations")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct BadRegionPolicy {
+    /// Total bytes in bad regions a wipe may have and still pass (as PASS_WITH_EXCEPTIONS).
+    /// 0, the default, fails any wipe that left a region unwritten.
+    pub max_bad_bytes: u64,
+}
+
+#[cfg(feature = "operations")]
+impl BadRegionPolicy {
+    pub fn accepts(&self, regions: &[BadRegion]) -> bool {
+        regions.iter().map(|region| region.length).sum::<u64>() <= self.max_bad_bytes
+    }
+}
+
+impl SitePolicy {
+    pub fn load(path: &Path) -> Result<Self, PolicyError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| PolicyError::File(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_str(&content).map_err(|e| PolicyError::File(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Load the configured policy file. A missing file at the default location gives
+    /// the defaults; a file named by config, SECUREWIPE_POLICY or a flag must exist.
+    pub fn load_default() -> Result<Self, PolicyError> {
+        let config = Config::load().map_err(|e| PolicyError::File(e.to_string()))?;
+        if config.is_default("policy_file") && !config.policy_file.exists() {
+            return Ok(SitePolicy::default());
+        }
+        Self::load(&config.policy_file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(offset: u64, length: u64) -> BadRegion {
+        BadRegion { offset, length, error: "Input/output error".to_string() }
+    }
+
+    #[test]
+    fn test_bad_region_policy_accepts() {
+        let strict = BadRegionPolicy::default();
+        assert!(strict.accepts(&[]));
+        assert!(!strict.accepts(&[region(4096, 512)]));
+
+        let tolerant = BadRegionPolicy { max_bad_bytes: 1024 };
+        assert!(tolerant.accepts(&[region(4096, 512), region(8192, 512)]));
+        assert!(!tolerant.accepts(&[region(4096, 512), region(8192, 1024)]));
+    }
+
+    #[test]
+    fn test_policy_file_defaults_missing_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.json");
+
+        fs::write(&path, "{}").unwrap();
+        assert_eq!(SitePolicy::load(&path).unwrap(), SitePolicy::default());
+
+        fs::write(&path, r#"{"bad_regions": {"max_bad_bytes": 65536}}"#).unwrap();
+        assert_eq!(SitePolicy::load(&path).unwrap().bad_regions.max_bad_bytes, 65536);
+
+        fs::write(&path, r#"{"bad_regions": {"max_bad_bytes": "lots"}}"#).unwrap();
+        assert!(SitePolicy::load(&path).is_err());
+
+        fs::write(&path, r#"{"overwrite": {"profile": "dod-5220.22-m-3pass"}}"#).unwrap();
+        let policy = SitePolicy::load(&path).unwrap();
+        assert_eq!(policy.overwrite.profile, OverwriteProfile::Dod522022M3Pass);
+        assert_eq!(policy.bad_regions, BadRegionPolicy::default());
+
+        fs::write(&path, r#"{"overwrite": {"profile": "35-pass"}}"#).unwrap();
+        assert!(SitePolicy::load(&path).is_err());
+
+        fs::write(&path, r#"{"retention": {"certificates_older_than": "2y", "backups_keep_last": 3}}"#).unwrap();
+        let retention = SitePolicy::load(&path).unwrap().retention;
+        assert_eq!(retention.certificates_older_than.as_deref(), Some("2y"));
+        assert_eq!(retention.backups_keep_last, Some(3));
+        assert!(retention.archive_dir.is_none());
+
+        fs::write(&path, r#"{"power": {"on_low_battery": "warn"}}"#).unwrap();
+        let power = SitePolicy::load(&path).unwrap().power;
+        assert_eq!(power.on_low_battery, LowBatteryAction::Warn);
+        assert_eq!(power.min_battery_percent, 25);
+        assert!(power.pause_on_power_loss);
+
+        fs::write(&path, r#"{"risk": {"rules": [{"name": "var", "mountpoint": "/var", "risk": "CRITICAL"}], "protect": ["swap"]}}"#).unwrap();
+        let risk = SitePolicy::load(&path).unwrap().risk;
+        assert_eq!(risk.rules.len(), 1);
+        assert_eq!(risk.protect_level, RiskPolicy::default().protect_level);
+
+        fs::write(&path, r#"{"risk": {"protect": ["home"]}}"#).unwrap();
+        assert!(SitePolicy::load(&path).is_err());
+
+        fs::write(&path, r#"{"backup": {"require_backup_before_wipe": true}}"#).unwrap();
+        let backup = SitePolicy::load(&path).unwrap().backup;
+        assert!(backup.require_backup_before_wipe);
+        assert_eq!(backup.max_age_days, 30);
+
+        fs::write(&path, r#"{"kiosk": {"interlock": "replug"}}"#).unwrap();
+        let kiosk = SitePolicy::load(&path).unwrap().kiosk;
+        assert_eq!(kiosk.interlock, KioskInterlock::Replug);
+        assert_eq!(kiosk.replug_timeout_secs, 120);
+
+        fs::write(&path, r#"{"cost": {"usb_watts": 4.5}}"#).unwrap();
+        let cost = SitePolicy::load(&path).unwrap().cost;
+        assert_eq!(cost.usb_watts, 4.5);
+        assert_eq!(cost.nvme_watts, CostPolicy::default().nvme_watts);
+
+        fs::write(&path, r#"{"display": {"locale": "de-DE", "units": "iec"}}"#).unwrap();
+        let display = SitePolicy::load(&path).unwrap().display;
+        assert_eq!(display.locale.as_deref(), Some("de-DE"));
+        assert_eq!(display.units, Units::Iec);
+
+        fs::write(&path, r#"{"display": {"units": "binary"}}"#).unwrap();
+        assert_eq!(SitePolicy::load(&path).unwrap().display.units, Units::Iec);
+
+        fs::write(&path, r#"{"display": {"units": "GB"}}"#).unwrap();
+        assert!(SitePolicy::load(&path).is_err());
+
+        fs::write(&path, r#"{"issuer": {"organization": "Acme Recycling", "strict": true}}"#).unwrap();
+        let issuer = SitePolicy::load(&path).unwrap().issuer;
+        assert_eq!(issuer.organization.as_deref(), Some("Acme Recycling"));
+        assert!(issuer.strict && issuer.verify_base_url.is_none());
+    }
+
+    #[test]
+    fn test_backup_requirement() {
+        use ed25519_dalek::pkcs8::{spki::der::pem::LineEnding, EncodePublicKey};
+
+        let dir = tempfile::tempdir().unwrap();
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[5u8; 32]);
+        let public_key_path = dir.path().join("public.pem");
+        fs::write(&public_key_path, signing_key.verifying_key().to_public_key_pem(LineEnding::LF).unwrap()).unwrap();
+
+        let now = DateTime::parse_from_rfc3339("2025-06-30T12:00:00Z").unwrap().with_timezone(&Utc);
+        let cert = |result: &str, serial: &str, created_at: &str| {
+            let mut cert = serde_json::json!({
+                "cert_id": "BCK_1",
+                "cert_type": "backup",
+                "result": result,
+                "created_at": created_at,
+                "device": { "serial": serial }
+            });
+            crate::signer::sign_certificate(&mut cert, &signing_key, false).unwrap();
+            cert
+        };
+        let recent = cert("PASS", "S123", "2025-06-20T09:00:00Z");
+
+        assert_eq!(BackupRequirement::default().blocking_reason(None, Some("S123"), now), None);
+        let unkeyed = BackupRequirement { require_backup_before_wipe: true, ..Default::default() };
+        assert!(unkeyed.blocking_reason(Some(&recent), Some("S123"), now).unwrap().contains("public_key_path is not set"));
+        let required = BackupRequirement { public_key_path: Some(public_key_path), ..unkeyed };
+        assert_eq!(required.blocking_reason(Some(&recent), Some("S123"), now), None);
+        assert!(required.blocking_reason(None, Some("S123"), now).unwrap().contains("--backup-cert-id"));
+        assert!(required.blocking_reason(Some(&recent), None, now).unwrap().contains("serial number is unknown"));
+        assert_eq!(
+            required.blocking_reason(Some(&recent), Some("S999"), now).unwrap(),
+            "Backup certificate BCK_1 is for drive S123, not S999"
+        );
+        let failed = cert("FAIL", "S123", "2025-06-20T09:00:00Z");
+        assert!(required.blocking_reason(Some(&failed), Some("S123"), now).unwrap().contains("did not pass"));
+        let stale = cert("PASS", "S123", "2025-04-01T09:00:00Z");
+        assert!(required.blocking_reason(Some(&stale), Some("S123"), now).unwrap().contains("older than the 30 days"));
+
+        let mut edited = cert("FAIL", "S123", "2025-06-20T09:00:00Z");
+        edited["result"] = serde_json::json!("PASS");
+        assert!(required.blocking_reason(Some(&edited), Some("S123"), now).unwrap().contains("does not verify"));
+        let mut unsigned = recent.clone();
+        unsigned.as_object_mut().unwrap().remove("signature");
+        assert!(required.blocking_reason(Some(&unsigned), Some("S123"), now).unwrap().contains("can't be checked"));
+    }
+}
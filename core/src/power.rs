@@ -0,0 +1,213 @@
+//! Power-loss awareness for wipes: a laptop whose battery dies mid-wipe leaves a
+//! drive neither intact nor sanitized. Reads AC, battery and UPS state from sysfs
+//! `power_supply`; the site policy decides whether a wipe may start on a low battery,
+//! and a `PowerWatch` pauses the overwrite while the machine is off mains power.
+
+use crate::policy::PowerPolicy;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// What the machine is running on right now
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct PowerStatus {
+    /// Whether any mains/USB supply is online; None when the machine reports none
+    pub ac_online: Option<bool>,
+    /// Mean charge of the system batteries and UPSes, None without any
+    pub battery_percent: Option<u8>,
+    /// A battery or UPS reports it is discharging
+    pub discharging: bool,
+}
+
+impl PowerStatus {
+    pub fn read() -> Self {
+        Self::read_from(Path::new(POWER_SUPPLY_DIR))
+    }
+
+    fn read_from(dir: &Path) -> Self {
+        let mut status = PowerStatus::default();
+        let mut charges: Vec<u32> = Vec::new();
+        let Ok(entries) = fs::read_dir(dir) else { return status };
+        for entry in entries.flatten() {
+            let supply = entry.path();
+            let attr = |name: &str| fs::read_to_string(supply.join(name)).map(|v| v.trim().to_string()).ok();
+            match attr("type").as_deref() {
+                Some("Mains" | "USB" | "USB_C" | "USB_PD") => {
+                    let online = attr("online").as_deref() == Some("1");
+                    status.ac_online = Some(status.ac_online.unwrap_or(false) || online);
+                }
+                Some("Battery" | "UPS") => {
+                    // Wireless mice and headsets report their batteries here too
+                    if attr("scope").as_deref() == Some("Device") {
+                        continue;
+                    }
+                    if let Some(capacity) = attr("capacity").and_then(|c| c.parse().ok()) {
+                        charges.push(capacity);
+                    }
+                    status.discharging |= attr("status").as_deref() == Some("Discharging");
+                }
+                _ => {}
+            }
+        }
+        if !charges.is_empty() {
+            status.battery_percent = Some((charges.iter().sum::<u32>() / charges.len() as u32).min(100) as u8);
+        }
+        status
+    }
+
+    /// Running on a battery or UPS rather than mains
+    pub fn on_battery(&self) -> bool {
+        match self.ac_online {
+            Some(online) => !online,
+            None => self.discharging,
+        }
+    }
+
+    /// Why this isn't enough power to start a wipe, if it isn't
+    pub fn low_battery(&self, min_percent: u8) -> Option<String> {
+        match self.battery_percent {
+            Some(percent) if self.on_battery() && percent < min_percent => Some(format!(
+                "Running on battery at {}% (policy minimum {}%); connect AC power before wiping",
+                percent, min_percent
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Block while a `PowerWatch` has paused work for lost mains power, or until
+/// `cancelled` says to give up
+pub fn wait_for_power(cancelled: &dyn Fn() -> bool) {
+    while PAUSED.load(Ordering::SeqCst) && !cancelled() {
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Logs power changes until dropped and, if the policy says so, pauses work while
+/// the machine is off mains power
+pub struct PowerWatch {
+    stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl PowerWatch {
+    /// Start watching, unless the machine reports no power supplies at all (a
+    /// desktop or VM with nothing in sysfs)
+    pub fn start(policy: &PowerPolicy) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let initial = PowerStatus::read();
+        if initial == PowerStatus::default() {
+            return Self { stop, handle: None };
+        }
+
+        let thread_stop = stop.clone();
+        let policy = policy.clone();
+        let handle = thread::Builder::new()
+            .name("power-watch".to_string())
+            .spawn(move || watch(initial, policy, thread_stop))
+            .ok();
+        Self { stop, handle }
+    }
+}
+
+impl Drop for PowerWatch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        PAUSED.store(false, Ordering::SeqCst);
+    }
+}
+
+fn watch(mut previous: PowerStatus, policy: PowerPolicy, stop: Arc<AtomicBool>) {
+    let mut warned_low = false;
+    while !stop.load(Ordering::SeqCst) {
+        thread::sleep(POLL_INTERVAL);
+        let status = PowerStatus::read();
+        match (previous.on_battery(), status.on_battery()) {
+            (false, true) => {
+                let paused = policy.pause_on_power_loss;
+                PAUSED.store(paused, Ordering::SeqCst);
+                log_event("ac_lost", &status, paused);
+                if paused {
+                    crate::events::warn("AC power lost: wipe paused until power is restored");
+                }
+            }
+            (true, false) => {
+                PAUSED.store(false, Ordering::SeqCst);
+                warned_low = false;
+                log_event("ac_restored", &status, false);
+            }
+            _ => {}
+        }
+        if !warned_low && status.low_battery(policy.min_battery_percent).is_some() {
+            warned_low = true;
+            log_event("battery_low", &status, PAUSED.load(Ordering::SeqCst));
+        }
+        previous = status;
+    }
+}
+
+fn log_event(event: &str, status: &PowerStatus, paused: bool) {
+    crate::events::emit(crate::events::EventType::Progress, serde_json::json!({
+        "step": "power_event",
+        "event": event,
+        "ac_online": status.ac_online,
+        "battery_percent": status.battery_percent,
+        "paused": paused,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(dir: &Path, name: &str, attrs: &[(&str, &str)]) {
+        let path = dir.join(name);
+        fs::create_dir(&path).unwrap();
+        for (attr, value) in attrs {
+            fs::write(path.join(attr), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_read_power_supplies() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(PowerStatus::read_from(dir.path()), PowerStatus::default());
+
+        supply(dir.path(), "AC", &[("type", "Mains"), ("online", "0")]);
+        supply(dir.path(), "BAT0", &[("type", "Battery"), ("capacity", "30"), ("status", "Discharging")]);
+        supply(dir.path(), "BAT1", &[("type", "Battery"), ("capacity", "10"), ("status", "Discharging")]);
+        supply(dir.path(), "hidpp_battery_0", &[("type", "Battery"), ("scope", "Device"), ("capacity", "90")]);
+
+        let status = PowerStatus::read_from(dir.path());
+        assert_eq!(status.ac_online, Some(false));
+        assert_eq!(status.battery_percent, Some(20));
+        assert!(status.on_battery());
+    }
+
+    #[test]
+    fn test_low_battery_threshold() {
+        let on_battery = PowerStatus { ac_online: Some(false), battery_percent: Some(20), discharging: true };
+        assert!(on_battery.low_battery(25).unwrap().contains("20%"));
+        assert!(on_battery.low_battery(20).is_none());
+
+        // Plugged in, or a UPS-backed desktop with no mains entry that is charging
+        let plugged = PowerStatus { ac_online: Some(true), ..on_battery.clone() };
+        assert!(plugged.low_battery(25).is_none());
+        let ups = PowerStatus { ac_online: None, battery_percent: Some(5), discharging: false };
+        assert!(ups.low_battery(25).is_none());
+        assert!(PowerStatus { discharging: true, ..ups }.low_battery(25).is_some());
+    }
+}
@@ -0,0 +1,221 @@
+//! Lets a backup or wipe run in the background without making the machine unusable:
+//! CPU niceness, an I/O scheduling class, and a cgroup v2 I/O weight. All three are
+//! set on the current process before the heavy work starts, so the dd/nvme/hdparm
+//! processes it spawns inherit them. A limit that can't be applied (no cgroup v2, no
+//! privilege) is reported as a warning rather than stopping the operation.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Per-run cgroups are `securewipe-<pid>` directly under the root, so their weight is
+/// weighed against system.slice and user.slice
+const CGROUP_PREFIX: &str = "securewipe-";
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PriorityError {
+    #[error("Invalid --ionice value: {0}. Must be idle or best-effort[:0-7]")]
+    InvalidIoClass(String),
+}
+
+/// I/O scheduling class, as in ionice(1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoClass {
+    /// Only gets disk time when nothing else wants it
+    Idle,
+    /// Normal scheduling at a level from 0 (highest) to 7 (lowest)
+    BestEffort(u8),
+}
+
+impl IoClass {
+    pub fn parse(value: &str) -> Result<Self, PriorityError> {
+        let invalid = || PriorityError::InvalidIoClass(value.to_string());
+        match value.split_once(':') {
+            None if value == "idle" => Ok(IoClass::Idle),
+            None if value == "best-effort" => Ok(IoClass::BestEffort(4)),
+            Some(("best-effort", level)) => match level.parse::<u8>() {
+                Ok(level) if level <= 7 => Ok(IoClass::BestEffort(level)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
+
+    /// The value ioprio_set(2) takes: class in the top bits, level in the low ones
+    fn ioprio(&self) -> i32 {
+        const IOPRIO_CLASS_SHIFT: i32 = 13;
+        match self {
+            IoClass::BestEffort(level) => (2 << IOPRIO_CLASS_SHIFT) | *level as i32,
+            IoClass::Idle => 3 << IOPRIO_CLASS_SHIFT,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            IoClass::Idle => "idle".to_string(),
+            IoClass::BestEffort(level) => format!("best-effort:{}", level),
+        }
+    }
+}
+
+/// What to lower for this run; nothing is changed for a field left at None
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResourceLimits {
+    /// setpriority(2) niceness, -20 to 19
+    pub nice: Option<i32>,
+    pub io_class: Option<IoClass>,
+    /// cgroup v2 io.weight, 1 to 10000 (100 is the default every other group has)
+    pub io_weight: Option<u16>,
+}
+
+/// What was actually applied, for the `resource_limits` log event
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AppliedLimits {
+    pub nice: Option<i32>,
+    pub ionice: Option<String>,
+    pub io_weight: Option<u16>,
+    pub cgroup: Option<PathBuf>,
+    pub warnings: Vec<String>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == ResourceLimits::default()
+    }
+
+    /// Apply the limits to the current process. Call it on the thread doing the work
+    /// before other threads or child processes are started: Linux keeps niceness and
+    /// I/O priority per thread, and new threads and children inherit them.
+    pub fn apply(&self) -> AppliedLimits {
+        let mut applied = AppliedLimits::default();
+        if let Some(nice) = self.nice {
+            match set_nice(nice) {
+                Ok(()) => applied.nice = Some(nice),
+                Err(e) => applied.warnings.push(format!("Could not set niceness {}: {}", nice, e)),
+            }
+        }
+        if let Some(class) = self.io_class {
+            match set_io_class(class) {
+                Ok(()) => applied.ionice = Some(class.describe()),
+                Err(e) => applied.warnings.push(format!("Could not set I/O class {}: {}", class.describe(), e)),
+            }
+        }
+        if let Some(weight) = self.io_weight {
+            match join_weighted_cgroup(Path::new(CGROUP_ROOT), std::process::id(), weight) {
+                Ok(cgroup) => {
+                    applied.io_weight = Some(weight);
+                    applied.cgroup = Some(cgroup);
+                }
+                Err(e) => applied.warnings.push(format!("Could not set cgroup I/O weight {}: {}", weight, e)),
+            }
+        }
+        applied
+    }
+}
+
+#[cfg(unix)]
+fn set_nice(nice: i32) -> std::io::Result<()> {
+    // Raising the niceness needs no privilege; lowering it below 0 needs root
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_nice(_nice: i32) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "not supported on this platform"))
+}
+
+#[cfg(target_os = "linux")]
+fn set_io_class(class: IoClass) -> std::io::Result<()> {
+    const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+    let result = unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0 as libc::c_long, class.ioprio() as libc::c_long) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_io_class(_class: IoClass) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "not supported on this platform"))
+}
+
+/// Move process `pid` into its own cgroup under `root` with the given I/O weight.
+/// The io controller honours io.weight with the BFQ scheduler or io.cost enabled;
+/// io.bfq.weight is written as well for kernels that only have the BFQ file.
+fn join_weighted_cgroup(root: &Path, pid: u32, weight: u16) -> std::io::Result<PathBuf> {
+    let controllers = fs::read_to_string(root.join("cgroup.controllers"))
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::NotFound, "cgroup v2 is not mounted"))?;
+    if !controllers.split_whitespace().any(|c| c == "io") {
+        return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "the io controller is not available"));
+    }
+    remove_stale_cgroups(root);
+
+    // Usually already enabled by systemd
+    let subtree = fs::read_to_string(root.join("cgroup.subtree_control")).unwrap_or_default();
+    if !subtree.split_whitespace().any(|c| c == "io") {
+        fs::write(root.join("cgroup.subtree_control"), "+io")?;
+    }
+
+    let cgroup = root.join(format!("{}{}", CGROUP_PREFIX, pid));
+    if !cgroup.exists() {
+        fs::create_dir(&cgroup)?;
+    }
+    fs::write(cgroup.join("io.weight"), format!("default {}", weight))?;
+    // BFQ only takes 1-1000
+    let _ = fs::write(cgroup.join("io.bfq.weight"), weight.min(1000).to_string());
+    fs::write(cgroup.join("cgroup.procs"), pid.to_string())?;
+    Ok(cgroup)
+}
+
+/// A cgroup can't be removed while a process is in it, so each run's cgroup is left
+/// behind when it exits; clear out the empty ones from earlier runs
+fn remove_stale_cgroups(root: &Path) {
+    let Ok(entries) = fs::read_dir(root) else { return };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(CGROUP_PREFIX) {
+            // rmdir fails on a cgroup that still has processes, i.e. a run still going
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_io_class() {
+        assert_eq!(IoClass::parse("idle"), Ok(IoClass::Idle));
+        assert_eq!(IoClass::parse("best-effort"), Ok(IoClass::BestEffort(4)));
+        assert_eq!(IoClass::parse("best-effort:7"), Ok(IoClass::BestEffort(7)));
+        assert!(IoClass::parse("best-effort:8").is_err());
+        assert!(IoClass::parse("realtime").is_err());
+
+        // Same encoding as ionice -c3 and ionice -c2 -n7
+        assert_eq!(IoClass::Idle.ioprio(), 0x6000);
+        assert_eq!(IoClass::BestEffort(7).ioprio(), 0x4007);
+    }
+
+    #[test]
+    fn test_join_weighted_cgroup() {
+        // A directory standing in for the cgroup2 mount
+        let root = tempfile::tempdir().unwrap();
+        assert!(join_weighted_cgroup(root.path(), 4242, 50).is_err());
+
+        fs::write(root.path().join("cgroup.controllers"), "cpuset cpu io memory pids\n").unwrap();
+        fs::write(root.path().join("cgroup.subtree_control"), "cpu memory\n").unwrap();
+        fs::create_dir(root.path().join("securewipe-1")).unwrap();
+
+        let cgroup = join_weighted_cgroup(root.path(), 4242, 50).unwrap();
+        assert_eq!(cgroup, root.path().join("securewipe-4242"));
+        assert_eq!(fs::read_to_string(cgroup.join("io.weight")).unwrap(), "default 50");
+        assert_eq!(fs::read_to_string(cgroup.join("cgroup.procs")).unwrap(), "4242");
+        assert_eq!(fs::read_to_string(root.path().join("cgroup.subtree_control")).unwrap(), "+io");
+        assert!(!root.path().join("securewipe-1").exists());
+    }
+}
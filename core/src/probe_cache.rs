@@ -0,0 +1,196 @@
+//! Cache of read-only controller probes (`smartctl -i`, `hdparm -I`, `nvme id-ctrl`)
+//! so discovery and planning don't re-run them for every disk on every invocation.
+//! Entries are keyed by drive serial and are valid while the kernel's uevent sequence
+//! number is unchanged and the TTL hasn't run out: any hotplug event bumps the
+//! sequence number and invalidates everything cached before it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// How long a probe result is trusted when no hotplug event has happened
+pub const DEFAULT_TTL_SECS: u64 = 15 * 60;
+const UEVENT_SEQNUM: &str = "/sys/kernel/uevent_seqnum";
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+static CACHE: Mutex<Option<(PathBuf, ProbeCache)>> = Mutex::new(None);
+
+/// Run every probe afresh for this run
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeOutput {
+    pub stdout: String,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+struct CacheEntry {
+    uevent_seqnum: u64,
+    /// Unix seconds of the first probe cached under this sequence number
+    probed_at: u64,
+    /// stdout of each successful probe, by command line
+    outputs: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProbeCache {
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+impl ProbeCache {
+    /// A missing or unreadable cache file is just an empty cache
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp, path)
+    }
+
+    pub fn lookup(&self, serial: &str, command: &str, seqnum: u64, now: u64, ttl: u64) -> Option<&str> {
+        let entry = self.entries.get(serial)?;
+        if entry.uevent_seqnum != seqnum || now.saturating_sub(entry.probed_at) >= ttl {
+            return None;
+        }
+        entry.outputs.get(command).map(String::as_str)
+    }
+
+    pub fn store(&mut self, serial: &str, command: &str, stdout: &str, seqnum: u64, now: u64, ttl: u64) {
+        let entry = self.entries.entry(serial.to_string()).or_default();
+        if entry.uevent_seqnum != seqnum || now.saturating_sub(entry.probed_at) >= ttl {
+            *entry = CacheEntry { uevent_seqnum: seqnum, probed_at: now, outputs: BTreeMap::new() };
+        }
+        entry.outputs.insert(command.to_string(), stdout.to_string());
+        // Drives that have been unplugged never get looked up again
+        self.entries.retain(|_, entry| now.saturating_sub(entry.probed_at) < ttl);
+    }
+}
+
+/// Run `program args` against `device`, or return its cached stdout. Only successful
+/// probes are cached, and only when the drive's serial is known (`serial`, else udev's).
+pub fn run(device: &str, serial: Option<&str>, program: &str, args: &[&str]) -> Option<ProbeOutput> {
+    let command = format!("{} {}", program, args.join(" "));
+    let key = serial.map(str::to_string).or_else(|| udev_serial(device));
+    let seqnum = uevent_seqnum();
+    let cacheable = !DISABLED.load(Ordering::Relaxed);
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+
+    if let (true, Some(key), Some(seqnum)) = (cacheable, &key, seqnum) {
+        let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        let (_, probes) = cache.get_or_insert_with(open_cache);
+        if let Some(stdout) = probes.lookup(key, &command, seqnum, now, DEFAULT_TTL_SECS) {
+            return Some(ProbeOutput { stdout: stdout.to_string(), success: true });
+        }
+    }
+
+    let output = Command::new(program).args(args).output().ok()?;
+    let probe = ProbeOutput { stdout: String::from_utf8_lossy(&output.stdout).to_string(), success: output.status.success() };
+
+    if let (true, true, Some(key), Some(seqnum)) = (cacheable, probe.success, &key, seqnum) {
+        let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        let (path, probes) = cache.get_or_insert_with(open_cache);
+        probes.store(key, &command, &probe.stdout, seqnum, now, DEFAULT_TTL_SECS);
+        // A read-only data directory only costs the speed-up
+        let _ = probes.save(path);
+    }
+    Some(probe)
+}
+
+fn open_cache() -> (PathBuf, ProbeCache) {
+    let path = crate::config::Config::load()
+        .map(|config| config.data_dir)
+        .unwrap_or_else(|_| std::env::temp_dir().join("securewipe"))
+        .join("cache")
+        .join("probes.json");
+    let cache = ProbeCache::load(&path);
+    (path, cache)
+}
+
+/// Incremented by the kernel for every uevent, hotplug included
+fn uevent_seqnum() -> Option<u64> {
+    fs::read_to_string(UEVENT_SEQNUM).ok()?.trim().parse().ok()
+}
+
+/// The serial udev recorded for a block device, from its database entry
+pub fn udev_serial(device: &str) -> Option<String> {
+    let name = fs::canonicalize(device).ok()?.file_name()?.to_string_lossy().to_string();
+    let dev = fs::read_to_string(format!("/sys/class/block/{}/dev", name)).ok()?;
+    let data = fs::read_to_string(format!("/run/udev/data/b{}", dev.trim())).ok()?;
+    parse_udev_serial(&data)
+}
+
+fn parse_udev_serial(data: &str) -> Option<String> {
+    let property = |key: &str| {
+        data.lines()
+            .find_map(|line| line.strip_prefix("E:")?.strip_prefix(key)?.strip_prefix('='))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    property("ID_SERIAL_SHORT").or_else(|| property("ID_SERIAL"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_invalidation() {
+        let mut cache = ProbeCache::default();
+        cache.store("S4EWNX0R123456", "smartctl -i /dev/sda", "Serial Number: S4EWNX0R123456", 100, 1000, 600);
+        assert_eq!(
+            cache.lookup("S4EWNX0R123456", "smartctl -i /dev/sda", 100, 1200, 600),
+            Some("Serial Number: S4EWNX0R123456")
+        );
+        assert_eq!(cache.lookup("S4EWNX0R123456", "hdparm -I /dev/sda", 100, 1200, 600), None);
+        assert_eq!(cache.lookup("OTHER", "smartctl -i /dev/sda", 100, 1200, 600), None);
+
+        // A hotplug event anywhere bumps the sequence number
+        assert_eq!(cache.lookup("S4EWNX0R123456", "smartctl -i /dev/sda", 101, 1200, 600), None);
+        // And results age out regardless
+        assert_eq!(cache.lookup("S4EWNX0R123456", "smartctl -i /dev/sda", 100, 1600, 600), None);
+
+        // A probe under a new sequence number drops what was cached under the old one
+        cache.store("S4EWNX0R123456", "hdparm -I /dev/sda", "Security:", 101, 1300, 600);
+        assert_eq!(cache.lookup("S4EWNX0R123456", "smartctl -i /dev/sda", 101, 1300, 600), None);
+        assert_eq!(cache.lookup("S4EWNX0R123456", "hdparm -I /dev/sda", 101, 1300, 600), Some("Security:"));
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache/probes.json");
+        assert_eq!(ProbeCache::load(&path), ProbeCache::default());
+
+        let mut cache = ProbeCache::default();
+        cache.store("WD-WCC4N7ABCDEF", "nvme id-ctrl /dev/nvme0n1", "sanitize", 7, 50, 600);
+        cache.save(&path).unwrap();
+        assert_eq!(ProbeCache::load(&path), cache);
+
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(ProbeCache::load(&path), ProbeCache::default());
+    }
+
+    #[test]
+    fn test_parse_udev_serial() {
+        let data = "S:disk/by-id/ata-WDC_WD20EZRZ\nE:ID_SERIAL=WDC_WD20EZRZ-00Z5HB0_WD-WCC4N7ABCDEF\nE:ID_SERIAL_SHORT=WD-WCC4N7ABCDEF\n";
+        assert_eq!(parse_udev_serial(data), Some("WD-WCC4N7ABCDEF".to_string()));
+        assert_eq!(parse_udev_serial("E:ID_SERIAL=QEMU_HARDDISK\n"), Some("QEMU_HARDDISK".to_string()));
+        assert_eq!(parse_udev_serial("E:ID_TYPE=disk\n"), None);
+    }
+}
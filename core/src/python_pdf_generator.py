@@ -52,6 +52,9 @@ except ImportError:
 try:
     from test_pdf_certificates import BackupCertificatePDFGenerator
     from test_wipe_pdf_certificates import create_wipe_certificate_pdf, create_sample_wipe_certificate
+    from test_destroy_pdf_certificates import create_destroy_certificate_pdf
+    from test_system_pdf_certificates import create_system_certificate_pdf
+    from pdf_formatting import configure as configure_formatting
 except ImportError as e:
     print(f"❌ Could not import existing PDF generators: {e}")
     print("   Make sure you're running from the project root")
@@ -103,11 +106,29 @@ def generate_backup_pdf(cert_data, output_path, skip_validation=False):
         return False, str(e)
 
 
-def generate_wipe_pdf(cert_data, output_path):
+def generate_wipe_pdf(cert_data, output_path, detail='full'):
     """Generate wipe certificate PDF using existing high-quality generator"""
     try:
         # Use the existing wipe certificate generator
-        create_wipe_certificate_pdf(cert_data, output_path)
+        create_wipe_certificate_pdf(cert_data, output_path, detail)
+        return True, output_path
+    except Exception as e:
+        return False, str(e)
+
+
+def generate_destroy_pdf(cert_data, output_path):
+    """Generate physical destruction certificate PDF"""
+    try:
+        create_destroy_certificate_pdf(cert_data, output_path)
+        return True, output_path
+    except Exception as e:
+        return False, str(e)
+
+
+def generate_system_pdf(cert_data, output_path):
+    """Generate system sanitization certificate PDF"""
+    try:
+        create_system_certificate_pdf(cert_data, output_path)
         return True, output_path
     except Exception as e:
         return False, str(e)
@@ -117,14 +138,20 @@ def main():
     parser = argparse.ArgumentParser(description='Generate high-quality PDF certificates')
     parser.add_argument('--cert-file', required=True, help='Path to certificate JSON file')
     parser.add_argument('--output', required=True, help='Output PDF path')
-    parser.add_argument('--type', choices=['backup', 'wipe'], required=True, 
+    parser.add_argument('--type', choices=['backup', 'wipe', 'destroy', 'system'], required=True, 
                        help='Certificate type')
     parser.add_argument('--validate', action='store_true', default=False,
                        help='Validate certificate against schema')
     parser.add_argument('--no-validate', action='store_true',
                         help='Skip certificate schema validation (default)')
+    parser.add_argument('--detail', choices=['full', 'summary'], default='full',
+                        help='full adds the command log appendix to wipe PDFs; summary leaves it out')
+    parser.add_argument('--format', default=None,
+                        help='Locale display settings as JSON (decimal, group, date, units), from the CLI')
     
     args = parser.parse_args()
+    if args.format:
+        configure_formatting(json.loads(args.format))
     
     # Load certificate
     try:
@@ -158,8 +185,12 @@ def main():
     skip_validation = args.no_validate
     if args.type == 'backup':
         success, result = generate_backup_pdf(cert_data, str(output_path), skip_validation)
+    elif args.type == 'destroy':
+        success, result = generate_destroy_pdf(cert_data, str(output_path))
+    elif args.type == 'system':
+        success, result = generate_system_pdf(cert_data, str(output_path))
     else:  # wipe
-        success, result = generate_wipe_pdf(cert_data, str(output_path))
+        success, result = generate_wipe_pdf(cert_data, str(output_path), args.detail)
     
     if success:
         file_size = os.path.getsize(output_path)
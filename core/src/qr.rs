@@ -0,0 +1,294 @@
+//! Signed QR payloads for offline verification. The QR holds the certificate's
+//! headline facts (id, number, result, policy, date) and a separate Ed25519
+//! signature over them, CBOR-encoded and then base45-encoded so the text stays in the
+//! QR alphanumeric set: `SW1:<base45>`. A phone scanner reads it back as plain text
+//! and `securewipe cert verify-qr` checks it against the issuer's public key, with
+//! no network and no certificate file.
+
+use ciborium::value::Value as Cbor;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+pub const QR_PREFIX: &str = "SW1:";
+const QR_VERSION: u64 = 1;
+/// Bytes of the certificate's own signature carried in the QR, enough to tell
+/// re-signed or forged copies of a certificate apart
+const CERT_SIG_PREFIX_LEN: usize = 8;
+const KEY_ID_LEN: usize = 8;
+const BASE45_ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+#[derive(Debug, thiserror::Error)]
+pub enum QrError {
+    #[error("Invalid QR payload: {0}")]
+    Format(String),
+    #[error("QR signature check failed: {0}")]
+    Signature(String),
+}
+
+fn format_error(message: impl Into<String>) -> QrError {
+    QrError::Format(message.into())
+}
+
+/// What a QR payload states about its certificate
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct QrClaims {
+    pub cert_id: String,
+    pub serial_number: Option<String>,
+    pub result: String,
+    pub policy: String,
+    /// Calendar date the certificate was issued (YYYY-MM-DD)
+    pub date: String,
+    /// Leading bytes of the certificate's signature, hex
+    pub cert_sig_prefix: String,
+    /// Same id as the signing audit log uses for the key
+    pub key_id: String,
+}
+
+impl QrClaims {
+    /// Claims for a certificate whose signature is `cert_sig`; None when the
+    /// document lacks the fields a QR reports (it isn't a certificate)
+    pub fn from_certificate(cert: &Value, cert_sig: &[u8], key: &VerifyingKey) -> Option<Self> {
+        let text = |pointer: &str| cert.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+        let created_at = text("/created_at")?;
+        let date = chrono::DateTime::parse_from_rfc3339(&created_at).ok()?.format("%Y-%m-%d").to_string();
+        Some(QrClaims {
+            cert_id: text("/cert_id")?,
+            serial_number: text("/serial_number"),
+            result: text("/result")?,
+            policy: text("/policy/nist_level").or_else(|| text("/policy/name"))?,
+            date,
+            cert_sig_prefix: to_hex(cert_sig.get(..CERT_SIG_PREFIX_LEN)?),
+            key_id: to_hex(&key_id_bytes(key)),
+        })
+    }
+}
+
+/// Encode `claims` and sign them with `key`
+pub fn encode(claims: &QrClaims, key: &SigningKey) -> Result<String, QrError> {
+    let body = Cbor::Array(vec![
+        Cbor::Integer(QR_VERSION.into()),
+        Cbor::Text(claims.cert_id.clone()),
+        claims.serial_number.clone().map_or(Cbor::Null, Cbor::Text),
+        Cbor::Text(claims.result.clone()),
+        Cbor::Text(claims.policy.clone()),
+        Cbor::Text(claims.date.clone()),
+        Cbor::Bytes(from_hex(&claims.cert_sig_prefix)?),
+        Cbor::Bytes(from_hex(&claims.key_id)?),
+    ]);
+    let body_bytes = to_cbor(&body)?;
+    let signature = key.sign(&body_bytes);
+    let envelope = Cbor::Array(vec![Cbor::Bytes(body_bytes), Cbor::Bytes(signature.to_bytes().to_vec())]);
+    Ok(format!("{}{}", QR_PREFIX, base45_encode(&to_cbor(&envelope)?)))
+}
+
+/// Signed QR text for a certificate just signed with `key`, or None for documents
+/// that aren't certificates
+pub fn for_certificate(cert: &Value, cert_sig: &[u8], key: &SigningKey) -> Result<Option<String>, QrError> {
+    match QrClaims::from_certificate(cert, cert_sig, &key.verifying_key()) {
+        Some(claims) => encode(&claims, key).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Decode a scanned payload and check its signature against `public_key`
+pub fn verify(payload: &str, public_key: &[u8; 32]) -> Result<QrClaims, QrError> {
+    let (claims, body_bytes, signature) = decode(payload)?;
+    let key = VerifyingKey::from_bytes(public_key).map_err(|e| QrError::Signature(format!("invalid public key: {}", e)))?;
+    if claims.key_id != to_hex(&key_id_bytes(&key)) {
+        return Err(QrError::Signature(format!("signed by key {}, not the key given", claims.key_id)));
+    }
+    key.verify(&body_bytes, &signature)
+        .map_err(|_| QrError::Signature("signature does not match the payload".to_string()))?;
+    Ok(claims)
+}
+
+fn decode(payload: &str) -> Result<(QrClaims, Vec<u8>, Signature), QrError> {
+    let encoded = payload
+        .trim()
+        .strip_prefix(QR_PREFIX)
+        .ok_or_else(|| format_error(format!("expected a payload starting with {}", QR_PREFIX)))?;
+    let envelope = from_cbor(&base45_decode(encoded)?)?;
+    let (body_bytes, signature) = match envelope {
+        Cbor::Array(items) => match <[Cbor; 2]>::try_from(items) {
+            Ok([Cbor::Bytes(body), Cbor::Bytes(signature)]) => (body, signature),
+            _ => return Err(format_error("expected [body, signature]")),
+        },
+        _ => return Err(format_error("expected [body, signature]")),
+    };
+    let signature = Signature::from_slice(&signature).map_err(|_| format_error("signature has the wrong length"))?;
+
+    let fields = match from_cbor(&body_bytes)? {
+        Cbor::Array(fields) => fields,
+        _ => return Err(format_error("body is not an array")),
+    };
+    let [version, cert_id, serial_number, result, policy, date, cert_sig_prefix, key_id] =
+        <[Cbor; 8]>::try_from(fields).map_err(|fields| format_error(format!("expected 8 body fields, got {}", fields.len())))?;
+    match version {
+        Cbor::Integer(version) if u64::try_from(version).ok() == Some(QR_VERSION) => {}
+        _ => return Err(format_error("unsupported payload version")),
+    }
+    let text = |value: Cbor, name: &str| match value {
+        Cbor::Text(text) => Ok(text),
+        _ => Err(format_error(format!("{} is not text", name))),
+    };
+    let bytes = |value: Cbor, name: &str| match value {
+        Cbor::Bytes(bytes) => Ok(to_hex(&bytes)),
+        _ => Err(format_error(format!("{} is not bytes", name))),
+    };
+    let claims = QrClaims {
+        cert_id: text(cert_id, "cert_id")?,
+        serial_number: match serial_number {
+            Cbor::Null => None,
+            value => Some(text(value, "serial_number")?),
+        },
+        result: text(result, "result")?,
+        policy: text(policy, "policy")?,
+        date: text(date, "date")?,
+        cert_sig_prefix: bytes(cert_sig_prefix, "cert_sig_prefix")?,
+        key_id: bytes(key_id, "key_id")?,
+    };
+    Ok((claims, body_bytes, signature))
+}
+
+fn key_id_bytes(key: &VerifyingKey) -> Vec<u8> {
+    Sha256::digest(key.to_bytes())[..KEY_ID_LEN].to_vec()
+}
+
+fn to_cbor(value: &Cbor) -> Result<Vec<u8>, QrError> {
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(value, &mut bytes).map_err(|e| format_error(e.to_string()))?;
+    Ok(bytes)
+}
+
+fn from_cbor(bytes: &[u8]) -> Result<Cbor, QrError> {
+    ciborium::de::from_reader(bytes).map_err(|e| format_error(format!("bad CBOR: {}", e)))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, QrError> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(format_error(format!("odd-length hex '{}'", hex)));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format_error(format!("bad hex '{}'", hex))))
+        .collect()
+}
+
+/// RFC 9285 base45: every two bytes become three characters, a trailing byte two
+fn base45_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() / 2 * 3 + 2);
+    for chunk in bytes.chunks(2) {
+        let (mut n, digits) = match *chunk {
+            [a, b] => (a as usize * 256 + b as usize, 3),
+            [a] => (a as usize, 2),
+            _ => unreachable!(),
+        };
+        for _ in 0..digits {
+            out.push(BASE45_ALPHABET[n % 45] as char);
+            n /= 45;
+        }
+    }
+    out
+}
+
+fn base45_decode(text: &str) -> Result<Vec<u8>, QrError> {
+    let digits = text
+        .bytes()
+        .map(|c| BASE45_ALPHABET.iter().position(|&a| a == c).ok_or_else(|| format_error(format!("'{}' is not base45", c as char))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut out = Vec::with_capacity(digits.len() / 3 * 2 + 1);
+    for chunk in digits.chunks(3) {
+        match *chunk {
+            [c, d, e] => {
+                let n = c + d * 45 + e * 45 * 45;
+                if n > 0xFFFF {
+                    return Err(format_error("base45 group out of range"));
+                }
+                out.extend_from_slice(&[(n >> 8) as u8, n as u8]);
+            }
+            [c, d] => {
+                let n = c + d * 45;
+                if n > 0xFF {
+                    return Err(format_error("base45 group out of range"));
+                }
+                out.push(n as u8);
+            }
+            _ => return Err(format_error("base45 text has a dangling character")),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use serde_json::json;
+
+    fn sample_cert() -> Value {
+        json!({
+            "cert_id": "0b6e1c9a-54e2-4b7f-9a53-8f0f2b7c1d11",
+            "serial_number": "SIH-2025-000123",
+            "cert_type": "wipe",
+            "created_at": "2025-09-14T10:22:31Z",
+            "policy": { "nist_level": "PURGE", "method": "controller_sanitize" },
+            "result": "PASS"
+        })
+    }
+
+    #[test]
+    fn test_base45_rfc9285_vectors() {
+        assert_eq!(base45_encode(b"AB"), "BB8");
+        assert_eq!(base45_encode(b"Hello!!"), "%69 VD92EX0");
+        assert_eq!(base45_encode(b"ietf!"), "QED8WEX0");
+        assert_eq!(base45_decode("QED8WEX0").unwrap(), b"ietf!");
+        assert!(base45_decode("ZZZ").is_err());
+        assert!(base45_decode("a").is_err());
+    }
+
+    #[test]
+    fn test_signed_qr_roundtrip() {
+        let key = SigningKey::generate(&mut OsRng);
+        let payload = for_certificate(&sample_cert(), &[7u8; 64], &key).unwrap().unwrap();
+        assert!(payload.starts_with(QR_PREFIX));
+        assert!(payload.len() < 300, "payload too long for a small QR: {}", payload.len());
+
+        let claims = verify(&payload, &key.verifying_key().to_bytes()).unwrap();
+        assert_eq!(claims.cert_id, "0b6e1c9a-54e2-4b7f-9a53-8f0f2b7c1d11");
+        assert_eq!(claims.serial_number.as_deref(), Some("SIH-2025-000123"));
+        assert_eq!(claims.result, "PASS");
+        assert_eq!(claims.policy, "PURGE");
+        assert_eq!(claims.date, "2025-09-14");
+        assert_eq!(claims.cert_sig_prefix, "0707070707070707");
+        assert_eq!(claims.key_id, crate::audit::key_id(&key));
+    }
+
+    #[test]
+    fn test_signed_qr_rejects_tampering_and_other_keys() {
+        let key = SigningKey::generate(&mut OsRng);
+        let payload = for_certificate(&sample_cert(), &[7u8; 64], &key).unwrap().unwrap();
+
+        let other = SigningKey::generate(&mut OsRng);
+        assert!(matches!(verify(&payload, &other.verifying_key().to_bytes()), Err(QrError::Signature(_))));
+
+        // Same key id, different claims: swap in a body from another certificate
+        let mut forged = sample_cert();
+        forged["result"] = json!("FAIL");
+        let forged_claims = QrClaims::from_certificate(&forged, &[7u8; 64], &key.verifying_key()).unwrap();
+        let (_, forged_body, _) = decode(&encode(&forged_claims, &key).unwrap()).unwrap();
+        let (_, _, signature) = decode(&payload).unwrap();
+        let envelope = Cbor::Array(vec![Cbor::Bytes(forged_body), Cbor::Bytes(signature.to_bytes().to_vec())]);
+        let spliced = format!("{}{}", QR_PREFIX, base45_encode(&to_cbor(&envelope).unwrap()));
+        assert!(matches!(verify(&spliced, &key.verifying_key().to_bytes()), Err(QrError::Signature(_))));
+
+        assert!(matches!(verify("https://verify.example/cert/1", &key.verifying_key().to_bytes()), Err(QrError::Format(_))));
+        // Documents that aren't certificates get no QR
+        assert!(for_certificate(&json!({"test": "data"}), &[7u8; 64], &key).unwrap().is_none());
+    }
+}
@@ -0,0 +1,229 @@
+//! Turns the raw I/O errors backup and wipe run into ("Input/output error", "Read-only
+//! file system") into a cause and a hint the operator can act on. The class and hint go
+//! out in the `file_skipped`, `backup_aborted` and `error_response` JSON, and the UI
+//! shows the hint.
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+/// Common causes of a failed read or write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IoFailure {
+    /// The device went away (USB unplugged, hub reset)
+    DeviceDisconnected,
+    ReadOnlyMedia,
+    PermissionDenied,
+    /// The drive failed the transfer itself, typically failing sectors
+    MediaError,
+    NoSpace,
+    DeviceBusy,
+    /// SELinux refused the access, whatever the file modes say
+    SeLinuxDenied,
+    /// AppArmor refused the access, whatever the file modes say
+    AppArmorDenied,
+}
+
+impl IoFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IoFailure::DeviceDisconnected => "device_disconnected",
+            IoFailure::ReadOnlyMedia => "read_only_media",
+            IoFailure::PermissionDenied => "permission_denied",
+            IoFailure::MediaError => "media_error",
+            IoFailure::NoSpace => "no_space",
+            IoFailure::DeviceBusy => "device_busy",
+            IoFailure::SeLinuxDenied => "selinux_denied",
+            IoFailure::AppArmorDenied => "apparmor_denied",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            IoFailure::DeviceDisconnected => {
+                "The device disconnected during the operation. Reseat the USB cable, use a port directly on the \
+                 computer rather than an unpowered hub, and retry; a backup can be continued with `securewipe backup --resume <dir>`."
+            }
+            IoFailure::ReadOnlyMedia => {
+                "The media is read-only. Check for a write-protect switch on the card or drive; if the kernel \
+                 remounted it read-only after errors (see `dmesg`), unmount, check the filesystem and reconnect."
+            }
+            IoFailure::PermissionDenied => {
+                "Permission denied. Run securewipe with sudo, or give your user access to the device or folder."
+            }
+            IoFailure::MediaError => {
+                "The drive reported a failed transfer, which usually means failing sectors. Check its health with \
+                 `smartctl -a <device>`; back up with `--on-error skip` to copy what is still readable, and treat the drive as failing."
+            }
+            IoFailure::NoSpace => {
+                "The destination is out of space or over quota. Free up space or choose a larger destination."
+            }
+            IoFailure::DeviceBusy => {
+                "The device is in use. Unmount its partitions and close programs using it, then retry."
+            }
+            IoFailure::SeLinuxDenied => {
+                "SELinux denied the access; sudo doesn't help, since SELinux applies to root too. Find the denial with \
+                 `ausearch -m avc -ts recent`, then allow it with a local policy module (`audit2allow -M securewipe` on \
+                 those records, then `semodule -i securewipe.pp`), or run `setenforce 0` for this wipe and `setenforce 1` after."
+            }
+            IoFailure::AppArmorDenied => {
+                "AppArmor denied the access; sudo doesn't help, since AppArmor applies to root too. Find the denial with \
+                 `journalctl -k | grep 'apparmor=\"DENIED\"'`, then add the device or path to the profile it names, or \
+                 put that profile in complain mode with `aa-complain <profile>` and retry."
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn from_errno(code: i32) -> Option<Self> {
+        match code {
+            libc::ENODEV | libc::ENXIO | libc::ENOMEDIUM | libc::ESHUTDOWN => Some(IoFailure::DeviceDisconnected),
+            libc::EROFS => Some(IoFailure::ReadOnlyMedia),
+            libc::EACCES | libc::EPERM => Some(IoFailure::PermissionDenied),
+            libc::EIO | libc::EMEDIUMTYPE => Some(IoFailure::MediaError),
+            libc::ENOSPC | libc::EDQUOT => Some(IoFailure::NoSpace),
+            libc::EBUSY => Some(IoFailure::DeviceBusy),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn from_errno(_code: i32) -> Option<Self> {
+        None
+    }
+
+    pub fn from_lsm(lsm: crate::lsm::Lsm) -> Self {
+        match lsm {
+            crate::lsm::Lsm::SeLinux => IoFailure::SeLinuxDenied,
+            crate::lsm::Lsm::AppArmor => IoFailure::AppArmorDenied,
+        }
+    }
+}
+
+impl fmt::Display for IoFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error already classified against the device or path it happened on, so the
+/// class survives the trip through `anyhow` into the command's error response
+#[derive(Debug)]
+pub struct ClassifiedError {
+    pub failure: IoFailure,
+    pub message: String,
+}
+
+impl fmt::Display for ClassifiedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ClassifiedError {}
+
+/// strerror texts, for errors that were turned into strings before reaching us
+const MESSAGES: &[(&str, IoFailure)] = &[
+    ("No such device", IoFailure::DeviceDisconnected),
+    ("No medium found", IoFailure::DeviceDisconnected),
+    ("Read-only file system", IoFailure::ReadOnlyMedia),
+    ("Permission denied", IoFailure::PermissionDenied),
+    ("Operation not permitted", IoFailure::PermissionDenied),
+    ("Input/output error", IoFailure::MediaError),
+    ("No space left on device", IoFailure::NoSpace),
+    ("Disk quota exceeded", IoFailure::NoSpace),
+    ("Device or resource busy", IoFailure::DeviceBusy),
+];
+
+/// Classify `error`, looking through its source chain for an OS error code before
+/// falling back to its message. `location` is the device or destination involved:
+/// a media error on one that can no longer be reached means it was unplugged.
+pub fn classify(error: &(dyn Error + 'static), location: Option<&Path>) -> Option<IoFailure> {
+    let mut current = Some(error);
+    while let Some(err) = current {
+        if let Some(classified) = err.downcast_ref::<ClassifiedError>() {
+            return Some(classified.failure);
+        }
+        if let Some(failure) = err.downcast_ref::<std::io::Error>()
+            .and_then(|e| e.raw_os_error())
+            .and_then(IoFailure::from_errno)
+        {
+            return Some(refine(failure, location));
+        }
+        current = err.source();
+    }
+    classify_message(&error.to_string(), location)
+}
+
+/// Classify an error that only survives as text, such as a `BadRegion`'s error
+pub fn classify_message(message: &str, location: Option<&Path>) -> Option<IoFailure> {
+    // io::Error's Display ends in "(os error N)", which survives formatting
+    let from_code = message.rfind("(os error ")
+        .and_then(|start| message[start + 10..].split(')').next())
+        .and_then(|code| code.parse().ok())
+        .and_then(IoFailure::from_errno);
+    let failure = from_code.or_else(|| {
+        MESSAGES.iter().find(|(text, _)| message.contains(text)).map(|(_, failure)| *failure)
+    })?;
+    Some(refine(failure, location))
+}
+
+fn refine(failure: IoFailure, location: Option<&Path>) -> IoFailure {
+    // A stat on a yanked USB disk (or a filesystem on it) fails too
+    match (failure, location) {
+        (IoFailure::MediaError, _) if crate::removal::device_removed() => IoFailure::DeviceDisconnected,
+        (IoFailure::MediaError, Some(path)) if !path.exists() => IoFailure::DeviceDisconnected,
+        // Root being refused, or a logged denial of the path, points at SELinux or AppArmor
+        (IoFailure::PermissionDenied, _) => {
+            crate::lsm::explain(location).map(|denial| IoFailure::from_lsm(denial.lsm)).unwrap_or(failure)
+        }
+        _ => failure,
+    }
+}
+
+/// `error_class` and `hint` fields for a JSON event, empty when `failure` is None
+pub fn fields(failure: Option<IoFailure>) -> serde_json::Map<String, serde_json::Value> {
+    let mut map = serde_json::Map::new();
+    if let Some(failure) = failure {
+        map.insert("error_class".to_string(), failure.as_str().into());
+        map.insert("hint".to_string(), failure.hint().into());
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_io_errors() {
+        let eio = std::io::Error::from_raw_os_error(libc::EIO);
+        assert_eq!(classify(&eio, None), Some(IoFailure::MediaError));
+        assert_eq!(classify(&std::io::Error::from_raw_os_error(libc::EROFS), None), Some(IoFailure::ReadOnlyMedia));
+        assert_eq!(classify(&std::io::Error::from_raw_os_error(libc::ENOENT), None), None);
+
+        // An I/O error on a device that has vanished is a disconnect, not bad media
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(classify(&eio, Some(dir.path())), Some(IoFailure::MediaError));
+        assert_eq!(classify(&eio, Some(&dir.path().join("sdz"))), Some(IoFailure::DeviceDisconnected));
+
+        let classified = ClassifiedError { failure: IoFailure::DeviceBusy, message: "wipe failed".to_string() };
+        assert_eq!(classify(&classified, None), Some(IoFailure::DeviceBusy));
+    }
+
+    #[test]
+    fn test_classify_message_text() {
+        // Formatted into a string on the way up, e.g. by handle_file_error
+        let message = format!("/home/user/a.txt: {}", std::io::Error::from_raw_os_error(libc::ENOSPC));
+        assert_eq!(classify_message(&message, None), Some(IoFailure::NoSpace));
+        assert_eq!(classify_message("Input/output error", None), Some(IoFailure::MediaError));
+        assert_eq!(classify_message("Permission denied: Cannot write to device /dev/sdb", None), Some(IoFailure::PermissionDenied));
+        assert_eq!(classify_message("Certificate file not found", None), None);
+
+        let fields = fields(Some(IoFailure::ReadOnlyMedia));
+        assert_eq!(fields["error_class"], "read_only_media");
+        assert!(fields["hint"].as_str().unwrap().contains("write-protect"));
+        assert_eq!(IoFailure::from_lsm(crate::lsm::Lsm::AppArmor).as_str(), "apparmor_denied");
+        assert!(IoFailure::SeLinuxDenied.hint().contains("ausearch"));
+    }
+}
@@ -0,0 +1,241 @@
+//! Notices when the disk a wipe or backup is using is unplugged, so the operation stops
+//! at once instead of retrying every remaining sector or skipping every remaining file.
+//! A `DeviceWatch` listens for the kernel's block-device `remove` uevents and, between
+//! events, checks the targets are still there; either sets a process-wide flag that the
+//! copy and overwrite loops check.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Process exit code when an operation stopped because its device was removed
+pub const EXIT_DEVICE_REMOVED: i32 = 4;
+
+/// Status recorded for a session or backup that stopped because its device was removed
+pub const INTERRUPTED_DEVICE_REMOVED: &str = "interrupted-device-removed";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+static REMOVED: AtomicBool = AtomicBool::new(false);
+
+/// Whether a watched device has gone away during this run
+pub fn device_removed() -> bool {
+    REMOVED.load(Ordering::SeqCst)
+}
+
+/// An error that means the device is gone rather than that one write failed
+pub fn is_removal(error: &std::io::Error) -> bool {
+    #[cfg(target_os = "linux")]
+    if let Some(libc::ENODEV | libc::ENXIO | libc::ENOMEDIUM) = error.raw_os_error() {
+        return true;
+    }
+    let _ = error;
+    device_removed()
+}
+
+/// Something to watch: a block device by kernel name, or a path (a backup destination)
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Block(String),
+    Path(PathBuf),
+}
+
+impl Target {
+    fn new(path: &Path) -> Self {
+        // Follow /dev/disk/by-id style links to the kernel name the uevents use
+        let resolved = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        match (resolved.strip_prefix("/dev"), resolved.file_name()) {
+            (Ok(_), Some(name)) => Target::Block(name.to_string_lossy().to_string()),
+            _ => Target::Path(path.to_path_buf()),
+        }
+    }
+
+    fn present(&self) -> bool {
+        match self {
+            Target::Block(name) => Path::new("/sys/class/block").join(name).exists(),
+            // A stat on a filesystem whose disk was pulled fails, so this goes false too
+            Target::Path(path) => path.exists(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Target::Block(name) => format!("/dev/{}", name),
+            Target::Path(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// Watches devices until dropped
+pub struct DeviceWatch {
+    stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl DeviceWatch {
+    /// Start watching `targets`: block device nodes and/or directories. Targets that
+    /// aren't present to begin with are ignored.
+    pub fn start(targets: &[PathBuf]) -> Self {
+        let targets: Vec<Target> = targets.iter()
+            .map(|path| Target::new(path))
+            .filter(Target::present)
+            .collect();
+        let stop = Arc::new(AtomicBool::new(false));
+        if targets.is_empty() {
+            return Self { stop, handle: None };
+        }
+
+        let thread_stop = stop.clone();
+        let handle = thread::Builder::new()
+            .name("device-watch".to_string())
+            .spawn(move || watch(targets, thread_stop))
+            .ok();
+        Self { stop, handle }
+    }
+}
+
+impl Drop for DeviceWatch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn watch(targets: Vec<Target>, stop: Arc<AtomicBool>) {
+    let socket = UeventSocket::open();
+    while !stop.load(Ordering::SeqCst) {
+        // The socket wait doubles as the poll interval
+        let removed_name = match socket {
+            Some(ref socket) => socket.next_removal(),
+            None => {
+                thread::sleep(POLL_INTERVAL);
+                None
+            }
+        };
+        let gone = targets.iter().find(|target| match (target, &removed_name) {
+            (Target::Block(name), Some(removed)) if name == removed => true,
+            _ => !target.present(),
+        });
+        if let Some(target) = gone {
+            REMOVED.store(true, Ordering::SeqCst);
+            crate::events::emit(crate::events::EventType::Progress, serde_json::json!({
+                "step": "device_removed",
+                "device": target.describe(),
+                "status": INTERRUPTED_DEVICE_REMOVED,
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }));
+            return;
+        }
+    }
+}
+
+/// Kernel name of the block device a `remove` uevent is about
+fn parse_removal(message: &[u8]) -> Option<String> {
+    let mut fields = message.split(|&b| b == 0).map(String::from_utf8_lossy);
+    if !fields.next()?.starts_with("remove@") {
+        return None;
+    }
+    let mut block = false;
+    let mut name = None;
+    for field in fields {
+        if field == "SUBSYSTEM=block" {
+            block = true;
+        } else if let Some(value) = field.strip_prefix("DEVNAME=") {
+            name = Some(value.trim_start_matches("/dev/").to_string());
+        }
+    }
+    name.filter(|_| block)
+}
+
+/// NETLINK_KOBJECT_UEVENT socket with a receive timeout of `POLL_INTERVAL`
+struct UeventSocket {
+    #[cfg(target_os = "linux")]
+    fd: libc::c_int,
+}
+
+impl UeventSocket {
+    #[cfg(target_os = "linux")]
+    fn open() -> Option<Self> {
+        unsafe {
+            let fd = libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_KOBJECT_UEVENT);
+            if fd < 0 {
+                return None;
+            }
+            let socket = UeventSocket { fd };
+            let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups = 1; // kernel events
+            let timeout = libc::timeval { tv_sec: 0, tv_usec: POLL_INTERVAL.as_micros() as libc::suseconds_t };
+            let bound = libc::bind(fd, &addr as *const _ as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t) == 0;
+            let timed = libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_RCVTIMEO, &timeout as *const _ as *const libc::c_void,
+                std::mem::size_of::<libc::timeval>() as libc::socklen_t) == 0;
+            (bound && timed).then_some(socket)
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn open() -> Option<Self> {
+        None
+    }
+
+    /// Wait up to `POLL_INTERVAL` for a uevent; the removed block device's name, if it was one
+    #[cfg(target_os = "linux")]
+    fn next_removal(&self) -> Option<String> {
+        let mut buffer = [0u8; 8192];
+        let received = unsafe { libc::recv(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len(), 0) };
+        if received <= 0 {
+            return None;
+        }
+        parse_removal(&buffer[..received as usize])
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn next_removal(&self) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for UeventSocket {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_removal_uevent() {
+        let removed = b"remove@/devices/pci0000:00/usb1/1-1/host6/target6:0:0/6:0:0:0/block/sdb\0ACTION=remove\0\
+            DEVPATH=/devices/pci0000:00/usb1/1-1/host6/target6:0:0/6:0:0:0/block/sdb\0SUBSYSTEM=block\0DEVNAME=sdb\0DEVTYPE=disk\0";
+        assert_eq!(parse_removal(removed), Some("sdb".to_string()));
+
+        let added = b"add@/devices/virtual/block/loop0\0ACTION=add\0SUBSYSTEM=block\0DEVNAME=loop0\0";
+        assert_eq!(parse_removal(added), None);
+        let usb = b"remove@/devices/pci0000:00/usb1/1-1\0ACTION=remove\0SUBSYSTEM=usb\0DEVNAME=bus/usb/001/004\0";
+        assert_eq!(parse_removal(usb), None);
+    }
+
+    #[test]
+    fn test_targets_and_presence() {
+        assert_eq!(Target::new(Path::new("/dev/nonexistent-sdz")), Target::Block("nonexistent-sdz".to_string()));
+        assert!(!Target::Block("nonexistent-sdz".to_string()).present());
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = Target::new(dir.path());
+        assert!(matches!(target, Target::Path(_)));
+        assert!(target.present());
+
+        assert!(is_removal(&std::io::Error::from_raw_os_error(libc::ENODEV)));
+        assert_eq!(is_removal(&std::io::Error::from_raw_os_error(libc::EIO)), device_removed());
+    }
+}
@@ -0,0 +1,362 @@
+//! Retention for old certificates and backups: `cert archive` moves certificates past
+//! an age out of the certificates directory, `backup prune` moves backups outside the
+//! retention rules out of a destination. Nothing is deleted. Every artifact is moved
+//! atomically (a rename, or copy + fsync + rename across filesystems) and recorded in
+//! the audit log.
+
+use crate::audit::{operator, AuditLog};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const CERTIFICATE_ARCHIVED_EVENT: &str = "certificate_archived";
+pub const BACKUP_PRUNED_EVENT: &str = "backup_pruned";
+
+/// Files next to a certificate that move with it: exported PDFs, drive labels and
+/// annotations
+const SIDECAR_EXTENSIONS: &[&str] = &["pdf", "zpl", "png", "jsonl"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum RetentionError {
+    #[error("Retention error: {0}")]
+    Io(String),
+    #[error("Refusing to overwrite {0}")]
+    Exists(String),
+    #[error(transparent)]
+    Audit(#[from] crate::audit::AuditError),
+}
+
+fn io_error(path: &Path, e: impl std::fmt::Display) -> RetentionError {
+    RetentionError::Io(format!("{}: {}", path.display(), e))
+}
+
+/// One certificate or backup moved (or, in a dry run, that would be moved)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RetainedItem {
+    pub id: String,
+    pub created_at: String,
+    /// Paths as they were before the move
+    pub files: Vec<String>,
+    pub destination: String,
+}
+
+/// Parse an age such as `90d`, `6w`, `18m` or `2y` (a month is 30 days, a year 365)
+pub fn parse_age(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value.len().checked_sub(1).filter(|_| value.is_ascii())?;
+    let count: i64 = value[..split].parse().ok().filter(|count| *count > 0)?;
+    let days = match &value[split..] {
+        "d" => count,
+        "w" => count * 7,
+        "m" => count * 30,
+        "y" => count * 365,
+        _ => return None,
+    };
+    Some(Duration::days(days))
+}
+
+/// Move certificates in `cert_dir` created before `cutoff`, with their sidecar files,
+/// into `to`. With `dry_run` nothing moves and nothing is recorded.
+pub fn archive_certificates(
+    cert_dir: &Path,
+    to: &Path,
+    cutoff: DateTime<Utc>,
+    dry_run: bool,
+    audit: &AuditLog,
+) -> Result<Vec<RetainedItem>, RetentionError> {
+    let entries = fs::read_dir(cert_dir).map_err(|e| io_error(cert_dir, e))?;
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+
+    let mut archived = Vec::new();
+    for name in names.iter().filter(|name| name.ends_with(".json")) {
+        let path = cert_dir.join(name);
+        let cert = match fs::read_to_string(&path).ok().and_then(|content| serde_json::from_str::<Value>(&content).ok()) {
+            Some(cert) => cert,
+            None => continue,
+        };
+        let (cert_id, created_at) = match (cert["cert_id"].as_str(), cert["created_at"].as_str()) {
+            (Some(cert_id), Some(created_at)) => (cert_id.to_string(), created_at.to_string()),
+            _ => continue,
+        };
+        match DateTime::parse_from_rfc3339(&created_at) {
+            Ok(at) if at < cutoff => {}
+            _ => continue,
+        }
+
+        // Sidecars first and the JSON last, so an interrupted run is picked up again
+        let stem = &name[..name.len() - ".json".len()];
+        let mut files: Vec<PathBuf> = names
+            .iter()
+            .filter(|other| {
+                let (base, ext) = other.rsplit_once('.').unwrap_or((other.as_str(), ""));
+                SIDECAR_EXTENSIONS.contains(&ext) && (base == stem || base.starts_with(&format!("{}_", stem)))
+            })
+            .map(|other| cert_dir.join(other))
+            .collect();
+        files.push(path.clone());
+
+        let item = RetainedItem {
+            id: cert_id.clone(),
+            created_at,
+            files: files.iter().map(|file| file.display().to_string()).collect(),
+            destination: to.display().to_string(),
+        };
+        if !dry_run {
+            let sha256 = format!("{:x}", Sha256::digest(fs::read(&path).map_err(|e| io_error(&path, e))?));
+            for file in &files {
+                move_atomic(file, to)?;
+            }
+            audit.append(
+                CERTIFICATE_ARCHIVED_EVENT,
+                &operator(),
+                json!({ "cert_id": cert_id, "sha256": sha256, "files": item.files, "destination": item.destination }),
+            )?;
+        }
+        archived.push(item);
+    }
+    Ok(archived)
+}
+
+/// Move backups under `root` that fall outside retention into `to`. The newest
+/// `keep_last` backups are always kept; of the rest, those created before `cutoff`
+/// are moved, or all of them when there is no cutoff. A backup is a directory holding
+/// a `manifest.json`; unfinished in-progress directories are never pruned.
+pub fn prune_backups(
+    root: &Path,
+    to: &Path,
+    keep_last: usize,
+    cutoff: Option<DateTime<Utc>>,
+    dry_run: bool,
+    audit: &AuditLog,
+) -> Result<Vec<RetainedItem>, RetentionError> {
+    let entries = fs::read_dir(root).map_err(|e| io_error(root, e))?;
+    let mut backups: Vec<(DateTime<Utc>, String, PathBuf)> = Vec::new();
+    for dir in entries.flatten().map(|entry| entry.path()) {
+        let in_progress = dir
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with(crate::backup::IN_PROGRESS_PREFIX));
+        if dir == to || in_progress || !dir.is_dir() {
+            continue;
+        }
+        let manifest = match fs::read_to_string(dir.join("manifest.json")) {
+            Ok(manifest) => manifest,
+            Err(_) => continue,
+        };
+        let created_at = serde_json::from_str::<Value>(&manifest)
+            .ok()
+            .and_then(|manifest| manifest["created_at"].as_str().map(str::to_string))
+            .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+            .map(|at| at.with_timezone(&Utc));
+        let created_at = match created_at {
+            Some(at) => at,
+            None => fs::metadata(&dir).and_then(|meta| meta.modified()).map_err(|e| io_error(&dir, e))?.into(),
+        };
+        let id = dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        backups.push((created_at, id, dir));
+    }
+    // Newest first
+    backups.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let mut pruned = Vec::new();
+    for (created_at, id, dir) in backups.into_iter().skip(keep_last) {
+        if cutoff.is_some_and(|cutoff| created_at >= cutoff) {
+            continue;
+        }
+        let item = RetainedItem {
+            id: id.clone(),
+            created_at: created_at.to_rfc3339(),
+            files: vec![dir.display().to_string()],
+            destination: to.display().to_string(),
+        };
+        if !dry_run {
+            let manifest_sha256 = format!(
+                "{:x}",
+                Sha256::digest(fs::read(dir.join("manifest.json")).map_err(|e| io_error(&dir, e))?)
+            );
+            move_atomic(&dir, to)?;
+            audit.append(
+                BACKUP_PRUNED_EVENT,
+                &operator(),
+                json!({ "backup_id": id, "manifest_sha256": manifest_sha256, "from": item.files[0], "destination": item.destination }),
+            )?;
+        }
+        pruned.push(item);
+    }
+    Ok(pruned)
+}
+
+/// Move `src` (a file or directory) into `dir` under the same name. Within a filesystem
+/// this is one rename. Across filesystems the copy is written under a temporary name,
+/// synced and renamed into place before `src` is removed, so the destination never
+/// holds a partial artifact. A file already at the destination with identical content
+/// (left by an interrupted run) counts as moved.
+pub fn move_atomic(src: &Path, dir: &Path) -> Result<PathBuf, RetentionError> {
+    let name = src.file_name().ok_or_else(|| io_error(src, "no file name"))?;
+    fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+    let dest = dir.join(name);
+
+    if dest.exists() {
+        if src.is_file() && same_content(src, &dest)? {
+            fs::remove_file(src).map_err(|e| io_error(src, e))?;
+            return Ok(dest);
+        }
+        return Err(RetentionError::Exists(dest.display().to_string()));
+    }
+
+    match fs::rename(src, &dest) {
+        Ok(()) => {}
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {
+            let partial = dir.join(format!(".{}.partial", name.to_string_lossy()));
+            if partial.exists() {
+                remove(&partial)?;
+            }
+            copy_synced(src, &partial)?;
+            fs::rename(&partial, &dest).map_err(|e| io_error(&dest, e))?;
+            sync_dir(dir)?;
+            remove(src)?;
+        }
+        Err(e) => return Err(io_error(src, e)),
+    }
+    sync_dir(dir)?;
+    if let Some(parent) = src.parent() {
+        sync_dir(parent)?;
+    }
+    Ok(dest)
+}
+
+fn same_content(a: &Path, b: &Path) -> Result<bool, RetentionError> {
+    let digest = |path: &Path| fs::read(path).map(Sha256::digest).map_err(|e| io_error(path, e));
+    Ok(digest(a)? == digest(b)?)
+}
+
+fn copy_synced(src: &Path, dest: &Path) -> Result<(), RetentionError> {
+    if src.is_dir() {
+        fs::create_dir(dest).map_err(|e| io_error(dest, e))?;
+        for entry in fs::read_dir(src).map_err(|e| io_error(src, e))? {
+            let entry = entry.map_err(|e| io_error(src, e))?;
+            copy_synced(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+        sync_dir(dest)
+    } else {
+        fs::copy(src, dest).map_err(|e| io_error(dest, e))?;
+        File::open(dest).and_then(|file| file.sync_all()).map_err(|e| io_error(dest, e))
+    }
+}
+
+fn remove(path: &Path) -> Result<(), RetentionError> {
+    let removed = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+    removed.map_err(|e| io_error(path, e))
+}
+
+fn sync_dir(dir: &Path) -> Result<(), RetentionError> {
+    match File::open(dir).and_then(|file| file.sync_all()) {
+        Ok(()) => Ok(()),
+        // Some filesystems (e.g. vfat on USB sticks) can't fsync a directory
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => Ok(()),
+        Err(e) => Err(io_error(dir, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_cert(dir: &Path, cert_id: &str, created_at: &str) {
+        let cert = json!({ "cert_id": cert_id, "cert_type": "wipe", "created_at": created_at });
+        fs::write(dir.join(format!("{}.json", cert_id)), cert.to_string()).unwrap();
+    }
+
+    fn write_backup(root: &Path, id: &str, created_at: &str) {
+        fs::create_dir_all(root.join(id).join("home")).unwrap();
+        fs::write(root.join(id).join("home").join("a.txt"), b"data").unwrap();
+        fs::write(root.join(id).join("manifest.json"), json!({ "created_at": created_at }).to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("90d"), Some(Duration::days(90)));
+        assert_eq!(parse_age("6w"), Some(Duration::days(42)));
+        assert_eq!(parse_age("2y"), Some(Duration::days(730)));
+        assert_eq!(parse_age("0d"), None);
+        assert_eq!(parse_age("2 years"), None);
+        assert_eq!(parse_age(""), None);
+    }
+
+    #[test]
+    fn test_archive_certificates_moves_old_with_sidecars() {
+        let dir = tempfile::tempdir().unwrap();
+        let certs = dir.path().join("certificates");
+        let archive = dir.path().join("archive");
+        fs::create_dir_all(&certs).unwrap();
+        write_cert(&certs, "old", "2020-01-01T00:00:00Z");
+        fs::write(certs.join("old.pdf"), b"%PDF").unwrap();
+        fs::write(certs.join("old_label.zpl"), b"^XA").unwrap();
+        write_cert(&certs, "new", "2025-01-01T00:00:00Z");
+        fs::write(certs.join("serials.json"), b"{}").unwrap();
+        let audit = AuditLog::new(dir.path().join("audit.jsonl"));
+        let cutoff = "2024-01-01T00:00:00Z".parse().unwrap();
+
+        let planned = archive_certificates(&certs, &archive, cutoff, true, &audit).unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].files.len(), 3);
+        assert!(certs.join("old.json").exists());
+
+        let archived = archive_certificates(&certs, &archive, cutoff, false, &audit).unwrap();
+        assert_eq!(archived[0].id, "old");
+        for name in ["old.json", "old.pdf", "old_label.zpl"] {
+            assert!(!certs.join(name).exists());
+            assert!(archive.join(name).exists());
+        }
+        assert!(certs.join("new.json").exists());
+
+        let entries = audit.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event, CERTIFICATE_ARCHIVED_EVENT);
+        assert_eq!(entries[0].details["cert_id"], "old");
+    }
+
+    #[test]
+    fn test_prune_backups_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("backups");
+        write_backup(&root, "b1", "2023-01-01T00:00:00Z");
+        write_backup(&root, "b2", "2024-01-01T00:00:00Z");
+        write_backup(&root, "b3", "2025-01-01T00:00:00Z");
+        let trash = root.join(".trash");
+        let audit = AuditLog::new(dir.path().join("audit.jsonl"));
+
+        // b3 is kept as the newest; b2 and b1 are beyond it and older than the cutoff
+        let cutoff = Some("2024-06-01T00:00:00Z".parse().unwrap());
+        let pruned = prune_backups(&root, &trash, 1, cutoff, false, &audit).unwrap();
+        assert_eq!(pruned.iter().map(|item| item.id.as_str()).collect::<Vec<_>>(), vec!["b2", "b1"]);
+        assert!(root.join("b3").exists());
+        assert!(trash.join("b1").join("home").join("a.txt").exists());
+        assert_eq!(audit.entries().unwrap().len(), 2);
+
+        // The trash directory itself is never treated as a backup
+        assert!(prune_backups(&root, &trash, 0, None, true, &audit).unwrap().iter().all(|item| item.id == "b3"));
+    }
+
+    #[test]
+    fn test_move_atomic_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let to = dir.path().join("to");
+        fs::create_dir_all(&to).unwrap();
+        fs::write(dir.path().join("a.json"), b"one").unwrap();
+        fs::write(to.join("a.json"), b"two").unwrap();
+        assert!(matches!(move_atomic(&dir.path().join("a.json"), &to), Err(RetentionError::Exists(_))));
+
+        // Identical content left by an interrupted run completes the move
+        fs::write(to.join("a.json"), b"one").unwrap();
+        move_atomic(&dir.path().join("a.json"), &to).unwrap();
+        assert!(!dir.path().join("a.json").exists());
+    }
+}
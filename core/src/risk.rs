@@ -0,0 +1,232 @@
+//! Rule-driven risk classification of discovered drives. The rules come from the
+//! `risk` section of the site policy; the built-in ones reproduce the old heuristics
+//! (root filesystem is CRITICAL, any other real mount HIGH, nothing mounted SAFE), so
+//! a site only writes rules for what it wants classified differently, e.g. `/var` on
+//! its own disk.
+
+use crate::device::{RiskExplanation, RiskLevel};
+use crate::glob::glob_match;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::sync::OnceLock;
+
+const EFI_SYSTEM_GUID: &str = "c12a7328-f81f-11d2-ba4b-00a0c93ec93b";
+const BIOS_BOOT_GUID: &str = "21686148-6449-6e6f-744e-656564454649";
+const XBOOTLDR_GUID: &str = "bc13c2ff-59e6-4262-a352-b275fd6f7172";
+const LINUX_SWAP_GUID: &str = "0657fd6d-a4ab-43c4-84e5-0933c84b4f4f";
+
+static POLICY: OnceLock<RiskPolicy> = OnceLock::new();
+
+/// Mountpoint patterns and protected partition contents, checked against every
+/// partition of a drive; the highest risk any of them gives is the drive's risk
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct RiskPolicy {
+    /// Checked in order; the first rule whose pattern matches a mountpoint decides its risk
+    pub rules: Vec<RiskRule>,
+    /// Risk of a mountpoint no rule matches
+    pub unmatched_mount: RiskLevel,
+    /// Partition contents that make a drive risky even when not mounted
+    pub protect: Vec<ProtectedContent>,
+    /// Risk of a drive holding any of `protect`
+    pub protect_level: RiskLevel,
+}
+
+impl Default for RiskPolicy {
+    fn default() -> Self {
+        let rule = |name: &str, mountpoint: &str, risk: RiskLevel| RiskRule {
+            name: name.to_string(),
+            mountpoint: mountpoint.to_string(),
+            risk,
+        };
+        Self {
+            rules: vec![
+                rule("root", "/", RiskLevel::Critical),
+                rule("sysfs", "/sys*", RiskLevel::Safe),
+                rule("procfs", "/proc*", RiskLevel::Safe),
+                rule("devfs", "/dev*", RiskLevel::Safe),
+                rule("runtime", "/run*", RiskLevel::Safe),
+                // The EFI system partition is typically mounted read-only
+                rule("efi", "/boot/efi", RiskLevel::Safe),
+            ],
+            unmatched_mount: RiskLevel::High,
+            protect: Vec::new(),
+            protect_level: RiskLevel::Critical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct RiskRule {
+    /// Shown by `discover --explain-risk`
+    pub name: String,
+    /// Mountpoint, with `*` matching any run of characters, e.g. "/var" or "/srv/*"
+    pub mountpoint: String,
+    pub risk: RiskLevel,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtectedContent {
+    /// A swap partition, active or not
+    Swap,
+    /// An EFI system partition
+    Efi,
+    /// A BIOS boot or XBOOTLDR partition, or whatever is mounted at /boot
+    Boot,
+}
+
+impl ProtectedContent {
+    fn name(self) -> &'static str {
+        match self {
+            ProtectedContent::Swap => "protect-swap",
+            ProtectedContent::Efi => "protect-efi",
+            ProtectedContent::Boot => "protect-boot",
+        }
+    }
+
+    fn found_in(self, partition: &Partition) -> bool {
+        let parttype = partition.parttype.as_deref().unwrap_or("").to_ascii_lowercase();
+        match self {
+            ProtectedContent::Swap => {
+                partition.fstype.as_deref() == Some("swap") || parttype == LINUX_SWAP_GUID || parttype == "0x82"
+            }
+            ProtectedContent::Efi => parttype == EFI_SYSTEM_GUID || parttype == "0xef",
+            ProtectedContent::Boot => {
+                partition.mountpoint.as_deref() == Some("/boot") || parttype == BIOS_BOOT_GUID || parttype == XBOOTLDR_GUID
+            }
+        }
+    }
+}
+
+/// What classification needs to know about the disk itself and each partition on it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Partition {
+    pub name: String,
+    pub mountpoint: Option<String>,
+    pub fstype: Option<String>,
+    pub parttype: Option<String>,
+}
+
+impl RiskPolicy {
+    pub fn classify(&self, partitions: &[Partition]) -> (RiskLevel, RiskExplanation) {
+        let mut verdicts: Vec<(RiskLevel, RiskExplanation)> = Vec::new();
+
+        for mountpoint in partitions.iter().filter_map(|p| p.mountpoint.as_deref()).filter(|mp| !mp.is_empty()) {
+            let verdict = match self.rules.iter().find(|rule| glob_match(&rule.mountpoint, mountpoint)) {
+                Some(rule) => (rule.risk.clone(), explanation(&rule.name, mountpoint)),
+                None => (self.unmatched_mount.clone(), explanation("unmatched-mount", mountpoint)),
+            };
+            verdicts.push(verdict);
+        }
+        for content in &self.protect {
+            if let Some(partition) = partitions.iter().find(|p| content.found_in(p)) {
+                verdicts.push((self.protect_level.clone(), explanation(content.name(), &format!("/dev/{}", partition.name))));
+            }
+        }
+
+        // The riskiest verdict wins; among equals, the first one found
+        let mut chosen: Option<(RiskLevel, RiskExplanation)> = None;
+        for verdict in verdicts {
+            let riskier = match &chosen {
+                Some((level, _)) => rank(&verdict.0) > rank(level),
+                None => true,
+            };
+            if riskier {
+                chosen = Some(verdict);
+            }
+        }
+        chosen.unwrap_or((RiskLevel::Safe, RiskExplanation { rule: "unmounted".to_string(), matched: None }))
+    }
+}
+
+/// The risk rules in force: those installed by the command, else the site policy's,
+/// else the built-in ones
+pub fn current() -> &'static RiskPolicy {
+    POLICY.get_or_init(|| match crate::policy::SitePolicy::load_default() {
+        Ok(policy) => policy.risk,
+        Err(e) => {
+            crate::events::warn(&format!("Using built-in risk rules: {}", e));
+            RiskPolicy::default()
+        }
+    })
+}
+
+/// Use these rules for the rest of the run. Only the first call, before any
+/// discovery, has an effect.
+pub fn install(policy: RiskPolicy) {
+    let _ = POLICY.set(policy);
+}
+
+fn explanation(rule: &str, matched: &str) -> RiskExplanation {
+    RiskExplanation { rule: rule.to_string(), matched: Some(matched.to_string()) }
+}
+
+fn rank(level: &RiskLevel) -> u8 {
+    match level {
+        RiskLevel::Safe => 0,
+        RiskLevel::High => 1,
+        RiskLevel::Critical => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mounted(name: &str, mountpoint: &str) -> Partition {
+        Partition { name: name.to_string(), mountpoint: Some(mountpoint.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let policy = RiskPolicy::default();
+
+        let (level, why) = policy.classify(&[mounted("sda1", "/boot/efi"), mounted("sda2", "/")]);
+        assert_eq!(level, RiskLevel::Critical);
+        assert_eq!(why, explanation("root", "/"));
+
+        let (level, why) = policy.classify(&[mounted("sdb1", "/var")]);
+        assert_eq!(level, RiskLevel::High);
+        assert_eq!(why.rule, "unmatched-mount");
+
+        let (level, why) = policy.classify(&[mounted("sdc1", "/run/media/usb")]);
+        assert_eq!(level, RiskLevel::Safe);
+        assert_eq!(why.rule, "runtime");
+
+        let (level, why) = policy.classify(&[Partition { name: "sdd".to_string(), ..Default::default() }]);
+        assert_eq!(level, RiskLevel::Safe);
+        assert_eq!(why.matched, None);
+    }
+
+    #[test]
+    fn test_site_rules_and_protected_contents() {
+        let policy: RiskPolicy = serde_json::from_str(
+            r#"{
+                "rules": [
+                    {"name": "var", "mountpoint": "/var", "risk": "CRITICAL"},
+                    {"name": "scratch", "mountpoint": "/mnt/scratch*", "risk": "SAFE"}
+                ],
+                "protect": ["swap", "efi"]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(policy.classify(&[mounted("sdb1", "/var")]).0, RiskLevel::Critical);
+        assert_eq!(policy.classify(&[mounted("sdb1", "/mnt/scratch2")]).0, RiskLevel::Safe);
+        // Rules replace the built-in ones entirely
+        assert_eq!(policy.classify(&[mounted("sdb1", "/")]).0, RiskLevel::High);
+
+        let swap = Partition { name: "sdc2".to_string(), fstype: Some("swap".to_string()), ..Default::default() };
+        let (level, why) = policy.classify(&[mounted("sdc1", "/data"), swap]);
+        assert_eq!(level, RiskLevel::Critical);
+        assert_eq!(why, explanation("protect-swap", "/dev/sdc2"));
+
+        let esp = Partition {
+            name: "sdd1".to_string(),
+            parttype: Some("C12A7328-F81F-11D2-BA4B-00A0C93EC93B".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(policy.classify(&[esp]).1.rule, "protect-efi");
+    }
+}
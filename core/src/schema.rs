@@ -1,5 +1,9 @@
+//! Certificate schema validation. The validator itself is `securewipe_verify::schema`,
+//! the one the verification page runs, so a certificate this accepts is accepted
+//! there too; this module loads the schema files and wraps the results.
+
 use anyhow::{Context, Result};
-use jsonschema::{JSONSchema, ValidationError};
+use securewipe_verify::schema::Schema;
 use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -33,8 +37,10 @@ impl ValidationResult {
 
 /// Certificate schema validator
 pub struct CertificateValidator {
-    backup_schema: Option<JSONSchema>,
-    wipe_schema: Option<JSONSchema>,
+    backup_schema: Option<Schema>,
+    wipe_schema: Option<Schema>,
+    destroy_schema: Option<Schema>,
+    system_schema: Option<Schema>,
 }
 
 impl CertificateValidator {
@@ -68,15 +74,19 @@ impl CertificateValidator {
 
         let backup_schema = Self::load_schema(&schema_dir, "backup_schema.json")?;
         let wipe_schema = Self::load_schema(&schema_dir, "wipe_schema.json")?;
+        let destroy_schema = Self::load_schema(&schema_dir, "destroy_schema.json")?;
+        let system_schema = Self::load_schema(&schema_dir, "system_schema.json")?;
 
         Ok(Self {
             backup_schema,
             wipe_schema,
+            destroy_schema,
+            system_schema,
         })
     }
 
     /// Load a schema from file
-    fn load_schema(schema_dir: &Path, filename: &str) -> Result<Option<JSONSchema>> {
+    fn load_schema(schema_dir: &Path, filename: &str) -> Result<Option<Schema>> {
         let schema_path = schema_dir.join(filename);
         
         if !schema_path.exists() {
@@ -87,14 +97,22 @@ impl CertificateValidator {
         let schema_content = fs::read_to_string(&schema_path)
             .with_context(|| format!("Failed to read schema file: {}", schema_path.display()))?;
 
-        let schema_value: Value = serde_json::from_str(&schema_content)
-            .with_context(|| format!("Failed to parse schema JSON: {}", schema_path.display()))?;
-
-        let compiled_schema = JSONSchema::compile(&schema_value)
-            .map_err(|e| anyhow::anyhow!("Failed to compile schema {}: {}", schema_path.display(), e))?;
+        let schema = Schema::parse(&schema_content)
+            .map_err(|e| anyhow::anyhow!("Failed to load schema {}: {}", schema_path.display(), e))?;
 
         debug!(schema_path = %schema_path.display(), "Schema loaded successfully");
-        Ok(Some(compiled_schema))
+        Ok(Some(schema))
+    }
+
+    /// Whether a schema was loaded for `cert_type`
+    pub fn has_schema(&self, cert_type: &str) -> bool {
+        match cert_type {
+            "backup" => self.backup_schema.is_some(),
+            "wipe" => self.wipe_schema.is_some(),
+            "destroy" => self.destroy_schema.is_some(),
+            "system" => self.system_schema.is_some(),
+            _ => false,
+        }
     }
 
     /// Validate a certificate JSON value
@@ -106,62 +124,30 @@ impl CertificateValidator {
         match cert_type {
             "backup" => self.validate_backup_certificate(cert_value),
             "wipe" => self.validate_wipe_certificate(cert_value),
+            "destroy" => self.validate_destroy_certificate(cert_value),
+            "system" => self.validate_system_certificate(cert_value),
             _ => Err(anyhow::anyhow!("Unsupported certificate type: {}", cert_type)),
         }
     }
 
     /// Validate a backup certificate
     pub fn validate_backup_certificate(&self, cert_value: &Value) -> Result<ValidationResult> {
-        match &self.backup_schema {
-            Some(schema) => {
-                let validation_result = schema.validate(cert_value);
-                match validation_result {
-                    Ok(()) => {
-                        debug!("Backup certificate passed schema validation");
-                        Ok(ValidationResult::success(Some("backup".to_string())))
-                    }
-                    Err(validation_errors) => {
-                        let errors: Vec<String> = validation_errors
-                            .map(|error| format_validation_error(&error))
-                            .collect();
-                        
-                        debug!(errors = ?errors, "Backup certificate failed schema validation");
-                        Ok(ValidationResult::failure(errors, Some("backup".to_string())))
-                    }
-                }
-            }
-            None => {
-                warn!("Backup schema not loaded, skipping validation");
-                Ok(ValidationResult::success(Some("backup".to_string())))
-            }
-        }
+        Ok(validate_with(self.backup_schema.as_ref(), "backup", cert_value))
     }
 
     /// Validate a wipe certificate
     pub fn validate_wipe_certificate(&self, cert_value: &Value) -> Result<ValidationResult> {
-        match &self.wipe_schema {
-            Some(schema) => {
-                let validation_result = schema.validate(cert_value);
-                match validation_result {
-                    Ok(()) => {
-                        debug!("Wipe certificate passed schema validation");
-                        Ok(ValidationResult::success(Some("wipe".to_string())))
-                    }
-                    Err(validation_errors) => {
-                        let errors: Vec<String> = validation_errors
-                            .map(|error| format_validation_error(&error))
-                            .collect();
-                        
-                        debug!(errors = ?errors, "Wipe certificate failed schema validation");
-                        Ok(ValidationResult::failure(errors, Some("wipe".to_string())))
-                    }
-                }
-            }
-            None => {
-                warn!("Wipe schema not loaded, skipping validation");
-                Ok(ValidationResult::success(Some("wipe".to_string())))
-            }
-        }
+        Ok(validate_with(self.wipe_schema.as_ref(), "wipe", cert_value))
+    }
+
+    /// Validate a physical destruction certificate
+    pub fn validate_destroy_certificate(&self, cert_value: &Value) -> Result<ValidationResult> {
+        Ok(validate_with(self.destroy_schema.as_ref(), "destroy", cert_value))
+    }
+
+    /// Validate a system sanitization certificate
+    pub fn validate_system_certificate(&self, cert_value: &Value) -> Result<ValidationResult> {
+        Ok(validate_with(self.system_schema.as_ref(), "system", cert_value))
     }
 
     /// Validate certificate from JSON string
@@ -188,21 +174,28 @@ impl Default for CertificateValidator {
             Self {
                 backup_schema: None,
                 wipe_schema: None,
+                destroy_schema: None,
+                system_schema: None,
             }
         })
     }
 }
 
-/// Format a validation error for human-readable output
-fn format_validation_error(error: &ValidationError) -> String {
-    let instance_path = error.instance_path.to_string();
-    let instance_path = if instance_path.is_empty() {
-        "root".to_string()
-    } else {
-        instance_path
+/// Validate against `schema`, or pass with a warning when it wasn't loaded. The
+/// shared validator already words its errors "Validation error at <path>: <reason>".
+fn validate_with(schema: Option<&Schema>, schema_id: &str, cert_value: &Value) -> ValidationResult {
+    let Some(schema) = schema else {
+        warn!(schema_id, "Schema not loaded, skipping validation");
+        return ValidationResult::success(Some(schema_id.to_string()));
     };
-
-    format!("Validation error at {}: {}", instance_path, error)
+    let errors = schema.validate(cert_value);
+    if errors.is_empty() {
+        debug!(schema_id, "Certificate passed schema validation");
+        ValidationResult::success(Some(schema_id.to_string()))
+    } else {
+        debug!(schema_id, errors = ?errors, "Certificate failed schema validation");
+        ValidationResult::failure(errors, Some(schema_id.to_string()))
+    }
 }
 
 /// Convenience function to validate a certificate value
@@ -397,6 +390,42 @@ mod tests {
         assert_eq!(result.schema_id, Some("wipe".to_string()));
     }
 
+    #[test]
+    fn test_bundled_destroy_schema_example() {
+        // Runs against the real schema bundle so the shipped example stays valid
+        let validator = CertificateValidator::new().unwrap();
+        assert!(validator.destroy_schema.is_some());
+        let schema_path = std::env::current_dir().unwrap().join("../certs/schemas/destroy_schema.json");
+        let schema: Value = serde_json::from_str(&fs::read_to_string(schema_path).unwrap()).unwrap();
+        let mut example = schema["examples"][0].clone();
+
+        let result = validator.validate_certificate(&example).unwrap();
+        assert!(result.valid, "{:?}", result.errors);
+        assert_eq!(result.schema_id, Some("destroy".to_string()));
+
+        example["destruction"].as_object_mut().unwrap().remove("witness");
+        let result = validator.validate_certificate(&example).unwrap();
+        assert!(!result.valid);
+        assert!(result.errors.iter().any(|e| e.contains("witness")));
+    }
+
+    #[test]
+    fn test_bundled_system_schema_example() {
+        let validator = CertificateValidator::new().unwrap();
+        assert!(validator.system_schema.is_some());
+        let schema_path = std::env::current_dir().unwrap().join("../certs/schemas/system_schema.json");
+        let schema: Value = serde_json::from_str(&fs::read_to_string(schema_path).unwrap()).unwrap();
+        let mut example = schema["examples"][0].clone();
+
+        let result = validator.validate_certificate(&example).unwrap();
+        assert!(result.valid, "{:?}", result.errors);
+        assert_eq!(result.schema_id, Some("system".to_string()));
+
+        example["components"] = serde_json::json!([]);
+        let result = validator.validate_certificate(&example).unwrap();
+        assert!(!result.valid);
+    }
+
     #[test]
     fn test_certificate_json_validation() {
         let temp_dir = setup_test_schemas().unwrap();
@@ -0,0 +1,347 @@
+//! Backup and signing-key passphrases, kept in the OS keyring (Secret Service through
+//! `secret-tool` on Linux, the login keychain through `security` on macOS) instead of
+//! `.env`. A live ISO has no keyring daemon, so there a passphrase can come from an
+//! owner-only file named by an environment variable, or from the variable holding the
+//! value itself (for CI); both win over the keyring.
+
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use zeroize::Zeroizing;
+
+/// Keyring service every SecureWipe secret is filed under
+pub const KEYRING_SERVICE: &str = "securewipe";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Keyring error: {0}")]
+    Keyring(String),
+    #[error("No OS keyring available: {0}")]
+    Unavailable(String),
+    #[error("Passphrase file error: {0}")]
+    File(String),
+    #[error("Passphrase prompt failed: {0}")]
+    Prompt(String),
+}
+
+/// The secrets SecureWipe knows how to store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretKind {
+    BackupPassphrase,
+    SignKeyPassphrase,
+}
+
+impl SecretKind {
+    pub const ALL: [SecretKind; 2] = [SecretKind::BackupPassphrase, SecretKind::SignKeyPassphrase];
+
+    /// Name used on the command line and as the keyring account
+    pub fn name(&self) -> &'static str {
+        match self {
+            SecretKind::BackupPassphrase => "backup-passphrase",
+            SecretKind::SignKeyPassphrase => "sign-key-passphrase",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Variable holding the passphrase itself
+    pub fn env_var(&self) -> &'static str {
+        match self {
+            SecretKind::BackupPassphrase => "SECUREWIPE_BACKUP_PASSPHRASE",
+            SecretKind::SignKeyPassphrase => "SECUREWIPE_SIGN_KEY_PASSPHRASE",
+        }
+    }
+
+    /// Variable naming a passphrase file, for sessions without a keyring
+    pub fn file_env_var(&self) -> &'static str {
+        match self {
+            SecretKind::BackupPassphrase => "SECUREWIPE_BACKUP_PASSPHRASE_FILE",
+            SecretKind::SignKeyPassphrase => "SECUREWIPE_SIGN_KEY_PASSPHRASE_FILE",
+        }
+    }
+}
+
+/// Where a resolved secret came from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum SecretSource {
+    File { path: PathBuf },
+    Env { var: String },
+    Keyring,
+    Prompt,
+}
+
+impl fmt::Display for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretSource::File { path } => write!(f, "file {}", path.display()),
+            SecretSource::Env { var } => write!(f, "environment variable {}", var),
+            SecretSource::Keyring => write!(f, "OS keyring"),
+            SecretSource::Prompt => write!(f, "terminal prompt"),
+        }
+    }
+}
+
+/// A passphrase, wiped from memory when dropped; `Debug` never prints the value
+#[derive(Clone)]
+pub struct Secret {
+    value: Zeroizing<String>,
+    pub source: SecretSource,
+}
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+}
+
+#[cfg(test)]
+impl Secret {
+    /// A typed-in passphrase, for tests in other modules
+    pub(crate) fn for_tests(value: &str) -> Self {
+        Secret { value: Zeroizing::new(value.to_string()), source: SecretSource::Prompt }
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secret").field("value", &"<redacted>").field("source", &self.source).finish()
+    }
+}
+
+/// Find the secret for `kind`: the file named by its file variable, else its value
+/// variable, else the keyring. A keyring that is missing or unreachable counts as
+/// holding nothing, but a named file that can't be used is an error.
+pub fn resolve(kind: SecretKind) -> Result<Option<Secret>, SecretError> {
+    if let Some(path) = std::env::var_os(kind.file_env_var()) {
+        let path = PathBuf::from(path);
+        let value = read_secret_file(&path)?;
+        return Ok(Some(Secret { value, source: SecretSource::File { path } }));
+    }
+    if let Some(value) = std::env::var(kind.env_var()).ok().filter(|value| !value.is_empty()) {
+        return Ok(Some(Secret { value: Zeroizing::new(value), source: SecretSource::Env { var: kind.env_var().to_string() } }));
+    }
+    match lookup(kind) {
+        Ok(Some(value)) => Ok(Some(Secret { value, source: SecretSource::Keyring })),
+        Ok(None) | Err(SecretError::Unavailable(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Ask for a secret on the terminal with echo off. None when stdin isn't a terminal,
+/// as under the desktop UI or in scripts.
+pub fn prompt(label: &str) -> Result<Option<Secret>, SecretError> {
+    use std::io::BufRead;
+
+    if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+        return Ok(None);
+    }
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+        return Err(SecretError::Prompt(std::io::Error::last_os_error().to_string()));
+    }
+    let mut silent = original;
+    silent.c_lflag &= !libc::ECHO;
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &silent) };
+
+    eprint!("{}: ", label);
+    let mut line = Zeroizing::new(String::new());
+    let read = std::io::stdin().lock().read_line(&mut line);
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &original) };
+    eprintln!();
+
+    read.map_err(|e| SecretError::Prompt(e.to_string()))?;
+    let value = Zeroizing::new(line.trim_end_matches(['\n', '\r']).to_string());
+    Ok(Some(Secret { value, source: SecretSource::Prompt }))
+}
+
+/// Read a passphrase file, refusing one that group or others can read
+pub fn read_secret_file(path: &Path) -> Result<Zeroizing<String>, SecretError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = fs::metadata(path).map_err(|e| SecretError::File(format!("{}: {}", path.display(), e)))?;
+    if metadata.permissions().mode() & 0o077 != 0 {
+        return Err(SecretError::File(format!(
+            "{} is readable by other users; run chmod 600 on it",
+            path.display()
+        )));
+    }
+    let content = Zeroizing::new(
+        fs::read_to_string(path).map_err(|e| SecretError::File(format!("{}: {}", path.display(), e)))?,
+    );
+    let value = content.strip_suffix('\n').unwrap_or(&content);
+    let value = value.strip_suffix('\r').unwrap_or(value);
+    if value.is_empty() {
+        return Err(SecretError::File(format!("{} is empty", path.display())));
+    }
+    Ok(Zeroizing::new(value.to_string()))
+}
+
+/// Run a keyring tool, feeding `input` on stdin. A tool that isn't installed means
+/// there is no keyring.
+fn run_keyring_tool(program: &str, args: &[&str], input: Option<&str>) -> Result<Output, SecretError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SecretError::Unavailable(format!("{} is not installed", program)),
+            _ => SecretError::Keyring(format!("{}: {}", program, e)),
+        })?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| SecretError::Keyring(format!("{}: {}", program, e)))?;
+    }
+    child.wait_with_output().map_err(|e| SecretError::Keyring(format!("{}: {}", program, e)))
+}
+
+fn stderr_text(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).trim().to_string()
+}
+
+/// Save `value` in the keyring, replacing any earlier one
+#[cfg(target_os = "linux")]
+pub fn store(kind: SecretKind, value: &str) -> Result<(), SecretError> {
+    let label = format!("SecureWipe {}", kind.name());
+    let output = run_keyring_tool(
+        "secret-tool",
+        &["store", "--label", &label, "service", KEYRING_SERVICE, "account", kind.name()],
+        Some(value),
+    )?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(SecretError::Unavailable(stderr_text(&output)))
+    }
+}
+
+/// The stored value, or None when nothing is stored
+#[cfg(target_os = "linux")]
+pub fn lookup(kind: SecretKind) -> Result<Option<Zeroizing<String>>, SecretError> {
+    let output = run_keyring_tool("secret-tool", &["lookup", "service", KEYRING_SERVICE, "account", kind.name()], None)?;
+    if output.status.success() {
+        let value = Zeroizing::new(
+            String::from_utf8(output.stdout)
+                .map_err(|_| SecretError::Keyring(format!("{} in the keyring is not UTF-8", kind.name())))?,
+        );
+        return Ok(Some(Zeroizing::new(value.strip_suffix('\n').unwrap_or(&value).to_string())));
+    }
+    // secret-tool exits 1 silently when nothing matches; anything on stderr means the
+    // Secret Service itself couldn't be reached
+    match stderr_text(&output) {
+        message if message.is_empty() => Ok(None),
+        message => Err(SecretError::Unavailable(message)),
+    }
+}
+
+/// Remove the stored value; removing one that isn't there is not an error
+#[cfg(target_os = "linux")]
+pub fn clear(kind: SecretKind) -> Result<(), SecretError> {
+    let output = run_keyring_tool("secret-tool", &["clear", "service", KEYRING_SERVICE, "account", kind.name()], None)?;
+    match stderr_text(&output) {
+        message if output.status.success() || message.is_empty() => Ok(()),
+        message => Err(SecretError::Unavailable(message)),
+    }
+}
+
+/// `security` exits with this when no matching item exists
+#[cfg(target_os = "macos")]
+const KEYCHAIN_ITEM_NOT_FOUND: i32 = 44;
+
+/// Save `value` in the keyring, replacing any earlier one. `security` only takes the
+/// password as an argument, so it is briefly visible in the process list.
+#[cfg(target_os = "macos")]
+pub fn store(kind: SecretKind, value: &str) -> Result<(), SecretError> {
+    let output = run_keyring_tool(
+        "security",
+        &["add-generic-password", "-U", "-s", KEYRING_SERVICE, "-a", kind.name(), "-w", value],
+        None,
+    )?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(SecretError::Keyring(stderr_text(&output)))
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn lookup(kind: SecretKind) -> Result<Option<Zeroizing<String>>, SecretError> {
+    let output = run_keyring_tool("security", &["find-generic-password", "-s", KEYRING_SERVICE, "-a", kind.name(), "-w"], None)?;
+    match output.status.code() {
+        Some(0) => {
+            let value = Zeroizing::new(
+                String::from_utf8(output.stdout)
+                    .map_err(|_| SecretError::Keyring(format!("{} in the keychain is not UTF-8", kind.name())))?,
+            );
+            Ok(Some(Zeroizing::new(value.strip_suffix('\n').unwrap_or(&value).to_string())))
+        }
+        Some(KEYCHAIN_ITEM_NOT_FOUND) => Ok(None),
+        _ => Err(SecretError::Keyring(stderr_text(&output))),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn clear(kind: SecretKind) -> Result<(), SecretError> {
+    let output = run_keyring_tool("security", &["delete-generic-password", "-s", KEYRING_SERVICE, "-a", kind.name()], None)?;
+    match output.status.code() {
+        Some(0) | Some(KEYCHAIN_ITEM_NOT_FOUND) => Ok(()),
+        _ => Err(SecretError::Keyring(stderr_text(&output))),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn store(_kind: SecretKind, _value: &str) -> Result<(), SecretError> {
+    Err(SecretError::Unavailable("no supported keyring on this platform".to_string()))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn lookup(_kind: SecretKind) -> Result<Option<Zeroizing<String>>, SecretError> {
+    Err(SecretError::Unavailable("no supported keyring on this platform".to_string()))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn clear(_kind: SecretKind) -> Result<(), SecretError> {
+    Err(SecretError::Unavailable("no supported keyring on this platform".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_secret_kind_names() {
+        for kind in SecretKind::ALL {
+            assert_eq!(SecretKind::parse(kind.name()), Some(kind));
+        }
+        assert_eq!(SecretKind::parse("backup"), None);
+
+        let secret = Secret { value: Zeroizing::new("hunter2".to_string()), source: SecretSource::Keyring };
+        assert!(!format!("{:?}", secret).contains("hunter2"));
+    }
+
+    #[test]
+    fn test_read_secret_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.pass");
+        fs::write(&path, "correct horse battery staple\n").unwrap();
+
+        // Group/world-readable files are refused until tightened
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(read_secret_file(&path).is_err());
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(read_secret_file(&path).unwrap().as_str(), "correct horse battery staple");
+
+        fs::write(&path, "\n").unwrap();
+        assert!(read_secret_file(&path).is_err());
+        assert!(read_secret_file(&dir.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Human-referenceable certificate numbers such as `SIH-2025-000123`, issued alongside
+//! the UUID cert ids. Each issuer has a single counter that only ever goes up, kept in
+//! `serials.json` in the certificates directory.
+
+use crate::config::Config;
+use chrono::{DateTime, Datelike, Utc};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+pub(crate) const STORE_FILE: &str = "serials.json";
+const LOCK_FILE: &str = "serials.lock";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SerialError {
+    #[error("Serial number store error: {0}")]
+    Store(String),
+}
+
+fn store_error(path: &Path, e: impl std::fmt::Display) -> SerialError {
+    SerialError::Store(format!("{}: {}", path.display(), e))
+}
+
+/// Issuer code used as the serial prefix: the acronym in parentheses when the
+/// organization has one ("SecureWipe (SIH)" gives "SIH"), otherwise its letters and
+/// digits, uppercased and cut to 8
+pub fn issuer_code(organization: &str) -> String {
+    let source = match (organization.find('('), organization.rfind(')')) {
+        (Some(open), Some(close)) if open < close => &organization[open + 1..close],
+        _ => organization,
+    };
+    let code: String = source
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .take(8)
+        .collect();
+    if code.is_empty() {
+        "SW".to_string()
+    } else {
+        code
+    }
+}
+
+pub fn format_serial(issuer_code: &str, year: i32, number: u64) -> String {
+    format!("{}-{}-{:06}", issuer_code, year, number)
+}
+
+/// Split a serial into issuer code, year and number
+pub fn parse_serial(serial: &str) -> Option<(&str, i32, u64)> {
+    let mut parts = serial.rsplitn(3, '-');
+    let number = parts.next()?.parse().ok()?;
+    let year = parts.next()?.parse().ok()?;
+    let code = parts.next().filter(|code| !code.is_empty())?;
+    Some((code, year, number))
+}
+
+/// The per-issuer counters in one certificates directory
+pub struct SerialStore {
+    dir: PathBuf,
+}
+
+impl SerialStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The store in the configured certificates directory
+    pub fn open_default() -> Result<Self, SerialError> {
+        let config = Config::load().map_err(|e| SerialError::Store(e.to_string()))?;
+        Ok(Self::new(config.certificates_dir))
+    }
+
+    /// Reserve the next number for `issuer_code`. Runs under an exclusive lock, and
+    /// never goes below the highest serial already on a certificate in the directory,
+    /// so a lost or restored counter file can't hand out a number twice.
+    pub fn next(&self, issuer_code: &str, year: i32) -> Result<String, SerialError> {
+        fs::create_dir_all(&self.dir).map_err(|e| store_error(&self.dir, e))?;
+        let lock_path = self.dir.join(LOCK_FILE);
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| store_error(&lock_path, e))?;
+        if unsafe { libc::flock(lock.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(store_error(&lock_path, std::io::Error::last_os_error()));
+        }
+
+        let mut counters = self.counters()?;
+        let last = counters.get(issuer_code).copied().unwrap_or(0).max(self.highest_issued(issuer_code));
+        let number = last + 1;
+        counters.insert(issuer_code.to_string(), number);
+        self.save(&counters)?;
+        // Dropping `lock` releases it
+        Ok(format_serial(issuer_code, year, number))
+    }
+
+    /// Last number issued per issuer code
+    pub fn counters(&self) -> Result<BTreeMap<String, u64>, SerialError> {
+        let path = self.dir.join(STORE_FILE);
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| store_error(&path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(store_error(&path, e)),
+        }
+    }
+
+    fn save(&self, counters: &BTreeMap<String, u64>) -> Result<(), SerialError> {
+        let path = self.dir.join(STORE_FILE);
+        let tmp = self.dir.join(format!("{}.tmp", STORE_FILE));
+        let json = serde_json::to_string_pretty(counters).map_err(|e| store_error(&path, e))?;
+        File::create(&tmp)
+            .and_then(|mut file| {
+                file.write_all(json.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp, &path))
+            .and_then(|_| File::open(&self.dir)?.sync_all())
+            .map_err(|e| store_error(&path, e))
+    }
+
+    /// Highest number on any certificate in the directory issued under `issuer_code`
+    fn highest_issued(&self, issuer_code: &str) -> u64 {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return 0,
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter(|path| path.file_name().is_some_and(|name| name != STORE_FILE))
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|content| serde_json::from_str::<Value>(&content).ok())
+            .filter_map(|cert| {
+                let serial = cert.get("serial_number")?.as_str()?.to_string();
+                let (code, _, number) = parse_serial(&serial)?;
+                (code == issuer_code).then_some(number)
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Give `cert` its serial number from the issuer organization and creation year it
+/// carries. A certificate that already has one keeps it, so re-signing doesn't
+/// renumber. Call before signing: the serial is part of the signed bytes.
+pub fn assign_serial(cert: &mut Value) -> Result<String, SerialError> {
+    if let Some(serial) = cert.get("serial_number").and_then(Value::as_str) {
+        return Ok(serial.to_string());
+    }
+    let organization = cert["issuer"]["organization"].as_str().unwrap_or("SecureWipe");
+    let year = cert["created_at"]
+        .as_str()
+        .and_then(|created_at| DateTime::parse_from_rfc3339(created_at).ok())
+        .map(|created_at| created_at.year())
+        .unwrap_or_else(|| Utc::now().year());
+    let serial = SerialStore::open_default()?.next(&issuer_code(organization), year)?;
+    cert["serial_number"] = Value::String(serial.clone());
+    Ok(serial)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serial_format() {
+        assert_eq!(issuer_code("SecureWipe (SIH)"), "SIH");
+        assert_eq!(issuer_code("Acme Recycling Ltd."), "ACMERECY");
+        assert_eq!(issuer_code("()"), "SW");
+
+        let serial = format_serial("SIH", 2025, 123);
+        assert_eq!(serial, "SIH-2025-000123");
+        assert_eq!(parse_serial(&serial), Some(("SIH", 2025, 123)));
+        assert_eq!(parse_serial("SIH-2025-1234567"), Some(("SIH", 2025, 1234567)));
+        assert_eq!(parse_serial("2025-000123"), None);
+        assert_eq!(parse_serial("SIH-25x-000123"), None);
+    }
+
+    #[test]
+    fn test_serial_store_is_monotonic_per_issuer() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SerialStore::new(dir.path());
+        assert_eq!(store.next("SIH", 2025).unwrap(), "SIH-2025-000001");
+        assert_eq!(store.next("SIH", 2026).unwrap(), "SIH-2026-000002");
+        assert_eq!(store.next("ACME", 2026).unwrap(), "ACME-2026-000001");
+
+        // Losing the counter file doesn't reissue numbers already on certificates
+        fs::write(dir.path().join("cert-a.json"), r#"{"serial_number": "SIH-2026-000040"}"#).unwrap();
+        fs::remove_file(dir.path().join(STORE_FILE)).unwrap();
+        assert_eq!(store.next("SIH", 2026).unwrap(), "SIH-2026-000041");
+        assert_eq!(store.counters().unwrap()["SIH"], 41);
+    }
+
+    #[test]
+    fn test_serial_store_concurrent_allocation() {
+        let dir = tempfile::tempdir().unwrap();
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let path = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    let store = SerialStore::new(path);
+                    (0..5).map(|_| store.next("SIH", 2025).unwrap()).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut serials: Vec<String> = handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect();
+        serials.sort();
+        serials.dedup();
+        assert_eq!(serials.len(), 40);
+        assert_eq!(serials.last().unwrap(), "SIH-2025-000040");
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom};
+
+/// A filesystem or partition-table signature still present on a wiped device
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SignatureFinding {
+    /// Absolute byte offset of the magic on the device
+    pub offset: u64,
+    /// Short identifier such as "gpt_header" or "ext_superblock"
+    pub kind: String,
+}
+
+struct KnownSignature {
+    kind: &'static str,
+    /// Offset of the magic from the start of the disk or partition
+    offset: u64,
+    magic: &'static [u8],
+}
+
+/// Magics at fixed offsets, in the spirit of wipefs/libblkid
+const SIGNATURES: &[KnownSignature] = &[
+    KnownSignature { kind: "mbr_boot_signature", offset: 510, magic: &[0x55, 0xAA] },
+    KnownSignature { kind: "gpt_header", offset: 512, magic: b"EFI PART" },
+    KnownSignature { kind: "gpt_header", offset: 4096, magic: b"EFI PART" },
+    KnownSignature { kind: "ntfs_boot_sector", offset: 3, magic: b"NTFS    " },
+    KnownSignature { kind: "exfat_boot_sector", offset: 3, magic: b"EXFAT   " },
+    KnownSignature { kind: "fat32_boot_sector", offset: 0x52, magic: b"FAT32   " },
+    KnownSignature { kind: "fat16_boot_sector", offset: 0x36, magic: b"FAT16   " },
+    KnownSignature { kind: "fat12_boot_sector", offset: 0x36, magic: b"FAT12   " },
+    KnownSignature { kind: "ext_superblock", offset: 1024 + 0x38, magic: &[0x53, 0xEF] },
+    KnownSignature { kind: "xfs_superblock", offset: 0, magic: b"XFSB" },
+    KnownSignature { kind: "btrfs_superblock", offset: 0x10040, magic: b"_BHRfS_M" },
+    KnownSignature { kind: "luks_header", offset: 0, magic: b"LUKS\xba\xbe" },
+    KnownSignature { kind: "lvm2_label", offset: 512, magic: b"LABELONE" },
+    KnownSignature { kind: "linux_swap", offset: 4086, magic: b"SWAPSPACE2" },
+    KnownSignature { kind: "hfsplus_volume_header", offset: 1024, magic: b"H+" },
+    KnownSignature { kind: "apfs_container", offset: 32, magic: b"NXSB" },
+    KnownSignature { kind: "iso9660_volume", offset: 0x8001, magic: b"CD001" },
+];
+
+/// Where partitions usually start: the disk itself, the legacy DOS 63-sector
+/// alignment, and the 1 MiB alignment used by every modern partitioning tool
+const PARTITION_BASES: &[u64] = &[0, 63 * 512, 1024 * 1024];
+
+/// Scan `device` for leftover signatures. Read-only.
+pub fn scan_device(device: &str) -> Result<Vec<SignatureFinding>, Box<dyn std::error::Error>> {
+    let mut file = OpenOptions::new().read(true).open(device)?;
+    let size = file.seek(SeekFrom::End(0))?;
+    Ok(scan_reader(&mut file, size)?)
+}
+
+/// Scan any seekable source of `size` bytes; split out so it can run on images
+pub fn scan_reader<R: Read + Seek>(reader: &mut R, size: u64) -> std::io::Result<Vec<SignatureFinding>> {
+    let mut findings = Vec::new();
+
+    for &base in PARTITION_BASES {
+        for signature in SIGNATURES {
+            let offset = base + signature.offset;
+            if magic_at(reader, size, offset, signature.magic)? {
+                findings.push(SignatureFinding { offset, kind: signature.kind.to_string() });
+            }
+        }
+    }
+
+    // The backup GPT header sits in the last logical block, which survives a
+    // wipe that stopped short of the end of the disk
+    for block_size in [512u64, 4096] {
+        if size >= block_size && magic_at(reader, size, size - block_size, b"EFI PART")? {
+            findings.push(SignatureFinding { offset: size - block_size, kind: "gpt_backup_header".to_string() });
+        }
+    }
+
+    Ok(findings)
+}
+
+fn magic_at<R: Read + Seek>(reader: &mut R, size: u64, offset: u64, magic: &[u8]) -> std::io::Result<bool> {
+    if offset + magic.len() as u64 > size {
+        return Ok(false);
+    }
+    let mut buffer = vec![0u8; magic.len()];
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer == magic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_scan_clean_image() {
+        let image = vec![0u8; 2 * 1024 * 1024];
+        let size = image.len() as u64;
+        assert!(scan_reader(&mut Cursor::new(image), size).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scan_finds_leftover_structures() {
+        let mut image = vec![0u8; 4 * 1024 * 1024];
+        let size = image.len() as u64;
+        // Partition table at the front, ext4 in the first 1 MiB-aligned partition,
+        // and the backup GPT header at the very end
+        image[510..512].copy_from_slice(&[0x55, 0xAA]);
+        image[512..520].copy_from_slice(b"EFI PART");
+        let ext_magic = 1024 * 1024 + 1024 + 0x38;
+        image[ext_magic..ext_magic + 2].copy_from_slice(&[0x53, 0xEF]);
+        let backup_gpt = image.len() - 512;
+        image[backup_gpt..backup_gpt + 8].copy_from_slice(b"EFI PART");
+
+        let kinds: Vec<String> = scan_reader(&mut Cursor::new(image), size)
+            .unwrap()
+            .into_iter()
+            .map(|finding| finding.kind)
+            .collect();
+        assert_eq!(kinds, vec!["mbr_boot_signature", "gpt_header", "ext_superblock", "gpt_backup_header"]);
+    }
+
+    #[test]
+    fn test_scan_small_device() {
+        // Offsets past the end of a tiny device are skipped rather than erroring
+        let image = vec![0u8; 600];
+        assert!(scan_reader(&mut Cursor::new(image), 600).unwrap().is_empty());
+    }
+}
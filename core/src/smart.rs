@@ -0,0 +1,191 @@
+//! SMART write counters read before and after a wipe. An overwrite of the whole drive
+//! should move the drive's own host-writes counter by about the drive's size; one
+//! that barely moves suggests the writes never reached the media (a lying USB bridge,
+//! a write cache that was dropped, the wrong device). The delta is corroborating
+//! evidence only: it never fails a wipe, but a suspicious one is flagged.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const GIB: u64 = 1 << 30;
+const MIB: u64 = 1 << 20;
+/// NVMe data units are thousands of 512-byte blocks
+const NVME_DATA_UNIT: u64 = 512 * 1000;
+
+/// A drive's lifetime host-writes counter at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SmartSnapshot {
+    pub captured_at: String,
+    /// The counter the figure came from, e.g. "Total_LBAs_Written" or "data_units_written"
+    pub counter: String,
+    pub host_bytes_written: u64,
+    /// Bytes per counter step, so a delta is only accurate to this
+    pub resolution_bytes: u64,
+    /// SHA-256 of the `smartctl -a -j` output
+    pub raw_sha256: String,
+}
+
+impl SmartSnapshot {
+    /// Read the write counter out of `smartctl -a -j` output (NVMe, ATA or SCSI)
+    pub fn parse(output: &str) -> Option<Self> {
+        let json: serde_json::Value = serde_json::from_str(output).ok()?;
+        let (counter, host_bytes_written, resolution_bytes) = nvme_writes(&json)
+            .or_else(|| ata_writes(&json))
+            .or_else(|| scsi_writes(&json))?;
+        Some(SmartSnapshot {
+            captured_at: chrono::Utc::now().to_rfc3339(),
+            counter,
+            host_bytes_written,
+            resolution_bytes,
+            raw_sha256: format!("{:x}", Sha256::digest(output.as_bytes())),
+        })
+    }
+}
+
+fn nvme_writes(json: &serde_json::Value) -> Option<(String, u64, u64)> {
+    let units = json["nvme_smart_health_information_log"]["data_units_written"].as_u64()?;
+    Some(("data_units_written".to_string(), units.saturating_mul(NVME_DATA_UNIT), NVME_DATA_UNIT))
+}
+
+fn ata_writes(json: &serde_json::Value) -> Option<(String, u64, u64)> {
+    let block = json["logical_block_size"].as_u64().unwrap_or(512);
+    json["ata_smart_attributes"]["table"].as_array()?.iter().find_map(|attribute| {
+        let name = attribute["name"].as_str()?;
+        // Vendors count in sectors or in binary units, and name the attribute after it
+        let unit = match name {
+            "Total_LBAs_Written" | "Total_Host_Sector_Write" => block,
+            "Host_Writes_32MiB" => 32 * MIB,
+            "Host_Writes_MiB" => MIB,
+            "Host_Writes_GiB" | "Lifetime_Writes_GiB" | "Total_Writes_GiB" => GIB,
+            _ => return None,
+        };
+        // Some drives pack other counters into the upper bytes of the 48-bit raw value
+        let raw = attribute["raw"]["value"].as_u64()? & 0xFFFF_FFFF_FFFF;
+        Some((name.to_string(), raw.saturating_mul(unit), unit))
+    })
+}
+
+fn scsi_writes(json: &serde_json::Value) -> Option<(String, u64, u64)> {
+    let processed = &json["scsi_error_counter_log"]["write"]["gigabytes_processed"];
+    let gigabytes: f64 = processed.as_str().and_then(|value| value.parse().ok()).or_else(|| processed.as_f64())?;
+    // Reported with three decimals, i.e. to the megabyte
+    Some(("gigabytes_processed".to_string(), (gigabytes * 1e9) as u64, 1_000_000))
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteAssessment {
+    /// The counter moved by at least half of what the overwrite wrote
+    Consistent,
+    /// The counter moved, but by less than half of what the overwrite wrote
+    Low,
+    /// The overwrite wrote data but the counter didn't move
+    NothingWritten,
+    /// A firmware sanitize doesn't go through the host write path
+    NotExpected,
+}
+
+/// Before/after write counters and what comparing them with the overwrite shows
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SmartDelta {
+    pub before: SmartSnapshot,
+    pub after: SmartSnapshot,
+    pub bytes_written: u64,
+    /// What the overwrite engine wrote; None for firmware methods
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_bytes: Option<u64>,
+    pub assessment: WriteAssessment,
+    pub suspicious: bool,
+}
+
+impl SmartDelta {
+    /// Compare two snapshots of the same counter. None when they aren't comparable
+    /// (different counters, or the counter went backwards).
+    pub fn compare(before: SmartSnapshot, after: SmartSnapshot, expected_bytes: Option<u64>) -> Option<Self> {
+        if before.counter != after.counter || after.host_bytes_written < before.host_bytes_written {
+            return None;
+        }
+        let bytes_written = after.host_bytes_written - before.host_bytes_written;
+        let assessment = match expected_bytes {
+            None => WriteAssessment::NotExpected,
+            Some(expected) if bytes_written == 0 && expected > after.resolution_bytes => WriteAssessment::NothingWritten,
+            Some(expected) if bytes_written.saturating_add(after.resolution_bytes) < expected / 2 => WriteAssessment::Low,
+            Some(_) => WriteAssessment::Consistent,
+        };
+        Some(SmartDelta {
+            before,
+            after,
+            bytes_written,
+            expected_bytes,
+            suspicious: matches!(assessment, WriteAssessment::Low | WriteAssessment::NothingWritten),
+            assessment,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        match self.expected_bytes {
+            Some(expected) => format!(
+                "drive counted {} bytes written ({}) against {} bytes overwritten",
+                self.bytes_written, self.after.counter, expected
+            ),
+            None => format!("drive counted {} bytes written ({})", self.bytes_written, self.after.counter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_write_counters() {
+        let nvme = r#"{"nvme_smart_health_information_log": {"data_units_written": 2000}}"#;
+        let snapshot = SmartSnapshot::parse(nvme).unwrap();
+        assert_eq!(snapshot.counter, "data_units_written");
+        assert_eq!(snapshot.host_bytes_written, 2000 * 512_000);
+
+        let ata = r#"{"logical_block_size": 512, "ata_smart_attributes": {"table": [
+            {"id": 9, "name": "Power_On_Hours", "raw": {"value": 1234}},
+            {"id": 241, "name": "Total_LBAs_Written", "raw": {"value": 1000000}}
+        ]}}"#;
+        assert_eq!(SmartSnapshot::parse(ata).unwrap().host_bytes_written, 512_000_000);
+
+        let ssd = r#"{"ata_smart_attributes": {"table": [{"id": 241, "name": "Host_Writes_32MiB", "raw": {"value": 10}}]}}"#;
+        assert_eq!(SmartSnapshot::parse(ssd).unwrap().resolution_bytes, 32 * MIB);
+
+        let scsi = r#"{"scsi_error_counter_log": {"write": {"gigabytes_processed": "12.500"}}}"#;
+        assert_eq!(SmartSnapshot::parse(scsi).unwrap().host_bytes_written, 12_500_000_000);
+
+        assert!(SmartSnapshot::parse(r#"{"smartctl": {"exit_status": 2}}"#).is_none());
+    }
+
+    #[test]
+    fn test_compare_against_overwrite() {
+        let snapshot = |bytes: u64| SmartSnapshot {
+            captured_at: String::new(),
+            counter: "Total_LBAs_Written".to_string(),
+            host_bytes_written: bytes,
+            resolution_bytes: 512,
+            raw_sha256: String::new(),
+        };
+        let size = 500_000_000_000;
+
+        let full = SmartDelta::compare(snapshot(10), snapshot(10 + size), Some(size)).unwrap();
+        assert_eq!(full.assessment, WriteAssessment::Consistent);
+        assert!(!full.suspicious);
+
+        let none = SmartDelta::compare(snapshot(10), snapshot(10), Some(size)).unwrap();
+        assert_eq!(none.assessment, WriteAssessment::NothingWritten);
+        assert!(none.suspicious);
+
+        let low = SmartDelta::compare(snapshot(0), snapshot(size / 10), Some(size)).unwrap();
+        assert_eq!(low.assessment, WriteAssessment::Low);
+
+        let sanitize = SmartDelta::compare(snapshot(0), snapshot(0), None).unwrap();
+        assert_eq!(sanitize.assessment, WriteAssessment::NotExpected);
+        assert!(!sanitize.suspicious);
+
+        assert!(SmartDelta::compare(snapshot(10), snapshot(5), Some(size)).is_none());
+    }
+}
@@ -0,0 +1,371 @@
+//! System sanitization certificates: one signed document for a whole machine,
+//! naming the host and summarizing the signed wipe or destroy certificate of each of
+//! its drives. Every component is pinned by the SHA-256 of its certificate file, so
+//! `verify_chain` can show that the drive certificates found next to it are the
+//! ones that were aggregated, unaltered and validly signed.
+
+use securewipe_types::CertificateResult;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Certificate types a system certificate can aggregate
+pub const COMPONENT_TYPES: &[&str] = &["wipe", "destroy"];
+
+/// Component summary fields that must match the component certificate itself
+const SUMMARY_FIELDS: &[&str] = &["cert_type", "serial_number", "created_at", "device", "nist_level", "method", "result"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum SystemCertError {
+    #[error("A system certificate needs at least one component certificate")]
+    NoComponents,
+    #[error("Component certificate {0} is listed more than once")]
+    Duplicate(String),
+    #[error("{0} is a {1} certificate; only wipe and destroy certificates can be components")]
+    WrongType(String, String),
+    #[error("Component certificate {0} is not signed")]
+    Unsigned(String),
+    #[error("Component certificate {0} has no {1}")]
+    MissingField(String, &'static str),
+    #[error("Component certificate {0} has unknown result {1:?}")]
+    UnknownResult(String, String),
+    #[error("{0} is not a system certificate")]
+    NotSystem(String),
+    #[error("Invalid certificate JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A component certificate as stored; the hash covers the exact file bytes
+#[derive(Debug, Clone)]
+pub struct ComponentCert {
+    pub value: Value,
+    pub sha256: String,
+}
+
+impl ComponentCert {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SystemCertError> {
+        Ok(Self { value: serde_json::from_slice(bytes)?, sha256: format!("{:x}", Sha256::digest(bytes)) })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SystemCertError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// `<cert_dir>/<cert_id>.json`, or None for an ID that can't name a stored file
+    pub fn load_by_id(cert_dir: &Path, cert_id: &str) -> Result<Option<Self>, SystemCertError> {
+        let path = cert_dir.join(format!("{}.json", cert_id));
+        if !crate::cert_id::is_storable(cert_id) || !path.is_file() {
+            return Ok(None);
+        }
+        Self::load(&path).map(Some)
+    }
+
+    pub fn cert_id(&self) -> &str {
+        self.value.get("cert_id").and_then(Value::as_str).unwrap_or("")
+    }
+
+    /// The entry for this certificate in a system certificate's `components`
+    pub fn summary(&self) -> Result<Value, SystemCertError> {
+        let cert = &self.value;
+        let id = self.cert_id().to_string();
+        if id.is_empty() {
+            return Err(SystemCertError::MissingField("(unknown)".to_string(), "cert_id"));
+        }
+        let cert_type = cert.get("cert_type").and_then(Value::as_str).unwrap_or("");
+        if !COMPONENT_TYPES.contains(&cert_type) {
+            return Err(SystemCertError::WrongType(id, cert_type.to_string()));
+        }
+        if cert.get("signature").and_then(|s| s.get("sig")).is_none() {
+            return Err(SystemCertError::Unsigned(id));
+        }
+        let text = |pointer: &str, field: &'static str| {
+            cert.pointer(pointer)
+                .and_then(Value::as_str)
+                .map(|s| Value::String(s.to_string()))
+                .ok_or_else(|| SystemCertError::MissingField(id.clone(), field))
+        };
+        let device = cert.get("device").ok_or_else(|| SystemCertError::MissingField(id.clone(), "device"))?;
+        // Only a result the certificate format defines can count towards the machine's
+        let result = text("/result", "result")?;
+        let result: CertificateResult = serde_json::from_value(result.clone())
+            .map_err(|_| SystemCertError::UnknownResult(id.clone(), result.as_str().unwrap_or_default().to_string()))?;
+
+        let mut summary = serde_json::json!({
+            "cert_id": id,
+            "cert_type": cert_type,
+            "certificate_sha256": self.sha256,
+            "created_at": text("/created_at", "created_at")?,
+            "device": {
+                "model": device.get("model").and_then(Value::as_str).unwrap_or("Unknown"),
+                "serial": device.get("serial").and_then(Value::as_str).unwrap_or(""),
+                "bus": device.get("bus").and_then(Value::as_str).unwrap_or("UNKNOWN"),
+                "capacity_bytes": device.get("capacity_bytes").and_then(Value::as_u64).unwrap_or(0)
+            },
+            "nist_level": text("/policy/nist_level", "policy.nist_level")?,
+            "method": text("/policy/method", "policy.method")?,
+            "result": result.as_str()
+        });
+        if let Some(serial) = cert.get("serial_number").and_then(Value::as_str) {
+            summary["serial_number"] = Value::String(serial.to_string());
+        }
+        Ok(summary)
+    }
+}
+
+/// Build a schema-compliant system certificate JSON (unsigned)
+///
+/// Contracts:
+/// - Inputs: signed wipe/destroy certificates for the machine's drives, in the
+///   order they should be listed
+/// - Output: serde_json::Value matching certs/schemas/system_schema.json except for signature
+/// - Errors: no components, a repeated component, or one that isn't a signed wipe or destroy certificate
+#[cfg(feature = "operations")]
+pub fn build_system_certificate(
+    components: &[ComponentCert],
+    host: &crate::cert::HostInfo,
+    identity: &crate::cert::IssuerIdentity,
+    notes: Option<&str>,
+) -> Result<Value, SystemCertError> {
+    if components.is_empty() {
+        return Err(SystemCertError::NoComponents);
+    }
+    let mut summaries = Vec::with_capacity(components.len());
+    for (i, component) in components.iter().enumerate() {
+        if components[..i].iter().any(|earlier| earlier.cert_id() == component.cert_id()) {
+            return Err(SystemCertError::Duplicate(component.cert_id().to_string()));
+        }
+        summaries.push(component.summary()?);
+    }
+
+    let cert_id = crate::cert_id::generate(crate::cert_id::CertKind::System);
+    let mut cert = serde_json::json!({
+        "cert_type": "system",
+        "cert_id": cert_id,
+        "certificate_version": "v1.0.0",
+        "created_at": chrono::Utc::now().to_rfc3339(),
+        "issuer": serde_json::to_value(identity.issuer())?,
+        "host": serde_json::to_value(host)?,
+        "policy": {"nist_level": weakest_level(&summaries)},
+        "components": summaries,
+        "result": aggregate_result(&summaries).as_str(),
+        "environment": serde_json::to_value(crate::cert_builder::environment(None))?,
+        "metadata": {},
+        "verify_url": identity.verify_url(&cert_id)
+    });
+    if let Some(notes) = notes {
+        cert["notes"] = Value::String(notes.to_string());
+    }
+    Ok(cert)
+}
+
+// Helper: a machine is only as sanitized as its least sanitized drive
+#[cfg(feature = "operations")]
+fn weakest_level(summaries: &[Value]) -> &'static str {
+    const ORDER: [&str; 3] = ["CLEAR", "PURGE", "DESTROY"];
+    summaries
+        .iter()
+        .map(|s| ORDER.iter().position(|level| s["nist_level"] == *level).unwrap_or(0))
+        .min()
+        .map_or("CLEAR", |i| ORDER[i])
+}
+
+// Helper: any failed drive fails the machine, and so does a result that isn't
+// recognized; destroyed drives count as passed
+#[cfg(feature = "operations")]
+fn aggregate_result(summaries: &[Value]) -> CertificateResult {
+    let results: Vec<Option<CertificateResult>> = summaries
+        .iter()
+        .map(|s| serde_json::from_value(s["result"].clone()).ok())
+        .collect();
+    if results.iter().any(|r| !matches!(r, Some(CertificateResult::Pass | CertificateResult::PassWithExceptions))) {
+        CertificateResult::Fail
+    } else if results.contains(&Some(CertificateResult::PassWithExceptions)) {
+        CertificateResult::PassWithExceptions
+    } else {
+        CertificateResult::Pass
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentStatus {
+    Verified,
+    /// No certificate with the component's ID in the directory
+    Missing,
+    /// The certificate file changed after it was aggregated
+    Altered,
+    /// The file hash matches but the summary doesn't describe it
+    Mismatch,
+    /// The component's own signature doesn't verify against the key given
+    BadSignature,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentCheck {
+    pub cert_id: String,
+    pub status: ComponentStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainReport {
+    pub valid: bool,
+    /// Whether component signatures were checked, i.e. a public key was given
+    pub signatures_checked: bool,
+    pub components: Vec<ComponentCheck>,
+}
+
+/// Check each component of a system certificate against the certificate of the same
+/// ID in `cert_dir`. The system certificate's own signature is not checked here.
+pub fn verify_chain(cert: &Value, cert_dir: &Path, public_key: Option<&[u8; 32]>) -> Result<ChainReport, SystemCertError> {
+    let cert_id = cert.get("cert_id").and_then(Value::as_str).unwrap_or("(unknown)");
+    let listed = match (cert.get("cert_type").and_then(Value::as_str), cert.get("components").and_then(Value::as_array)) {
+        (Some("system"), Some(listed)) => listed,
+        _ => return Err(SystemCertError::NotSystem(cert_id.to_string())),
+    };
+
+    let mut components = Vec::with_capacity(listed.len());
+    for entry in listed {
+        let id = entry.get("cert_id").and_then(Value::as_str).unwrap_or("").to_string();
+        let check = |status, detail: Option<String>| ComponentCheck { cert_id: id.clone(), status, detail };
+        let component = match ComponentCert::load_by_id(cert_dir, &id)? {
+            Some(component) => component,
+            None => {
+                components.push(check(ComponentStatus::Missing, None));
+                continue;
+            }
+        };
+        if entry.get("certificate_sha256").and_then(Value::as_str) != Some(component.sha256.as_str()) {
+            components.push(check(ComponentStatus::Altered, Some(format!("file hash is {}", component.sha256))));
+            continue;
+        }
+        let summary = match component.summary() {
+            Ok(summary) => summary,
+            Err(e) => {
+                components.push(check(ComponentStatus::Mismatch, Some(e.to_string())));
+                continue;
+            }
+        };
+        if let Some(field) = SUMMARY_FIELDS.iter().find(|field| entry.get(**field) != summary.get(**field)) {
+            components.push(check(ComponentStatus::Mismatch, Some(format!("{} differs from the certificate", field))));
+            continue;
+        }
+        if let Some(key) = public_key {
+            if !crate::signer::verify_certificate_signature(&component.value, key).unwrap_or(false) {
+                components.push(check(ComponentStatus::BadSignature, None));
+                continue;
+            }
+        }
+        components.push(check(ComponentStatus::Verified, None));
+    }
+
+    Ok(ChainReport {
+        valid: !components.is_empty() && components.iter().all(|c| c.status == ComponentStatus::Verified),
+        signatures_checked: public_key.is_some(),
+        components,
+    })
+}
+
+#[cfg(all(test, feature = "operations"))]
+mod tests {
+    use super::*;
+    use crate::cert::IssuerIdentity;
+    use crate::cert::HostInfo;
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn signed_wipe(dir: &Path, id: &str, serial: &str, level: &str, result: &str, key: &SigningKey) -> ComponentCert {
+        let mut cert = json!({
+            "cert_type": "wipe",
+            "cert_id": id,
+            "created_at": "2025-06-30T12:00:00Z",
+            "device": {"model": "Disk", "serial": serial, "bus": "SATA", "capacity_bytes": 1000},
+            "policy": {"nist_level": level, "method": "overwrite"},
+            "result": result
+        });
+        crate::signer::sign_certificate(&mut cert, key, false).unwrap();
+        let path = dir.join(format!("{}.json", id));
+        std::fs::write(&path, serde_json::to_string_pretty(&cert).unwrap()).unwrap();
+        ComponentCert::load(&path).unwrap()
+    }
+
+    #[test]
+    fn test_build_and_verify_chain() {
+        let dir = TempDir::new().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        let public_key = key.verifying_key().to_bytes();
+        let components = vec![
+            signed_wipe(dir.path(), "WPE_A", "S1", "PURGE", "PASS", &key),
+            signed_wipe(dir.path(), "WPE_B", "S2", "CLEAR", "PASS_WITH_EXCEPTIONS", &key),
+        ];
+        let host = HostInfo { hostname: "lab-07".to_string(), chassis_serial: Some("5J5PX32".to_string()), ..Default::default() };
+
+        let cert = build_system_certificate(&components, &host, &IssuerIdentity::default(), None).unwrap();
+        assert_eq!(cert["policy"]["nist_level"], "CLEAR");
+        assert_eq!(cert["result"], "PASS_WITH_EXCEPTIONS");
+        assert_eq!(cert["host"]["chassis_serial"], "5J5PX32");
+        assert!(cert["cert_id"].as_str().unwrap().starts_with("SYS_"));
+
+        let report = verify_chain(&cert, dir.path(), Some(&public_key)).unwrap();
+        assert!(report.valid, "{:?}", report);
+
+        // Re-signing changes the file, so the pinned hash no longer matches
+        let mut altered = components[0].value.clone();
+        altered["result"] = json!("FAIL");
+        crate::signer::sign_certificate(&mut altered, &key, true).unwrap();
+        std::fs::write(dir.path().join("WPE_A.json"), altered.to_string()).unwrap();
+        std::fs::remove_file(dir.path().join("WPE_B.json")).unwrap();
+        let report = verify_chain(&cert, dir.path(), Some(&public_key)).unwrap();
+        assert!(!report.valid);
+        assert_eq!(report.components[0].status, ComponentStatus::Altered);
+        assert_eq!(report.components[1].status, ComponentStatus::Missing);
+
+        // A forged component signature is caught even when the hash was updated to match
+        let mut forged = components[0].value.clone();
+        forged["result"] = json!("FAIL");
+        crate::signer::sign_certificate(&mut forged, &SigningKey::generate(&mut OsRng), true).unwrap();
+        std::fs::write(dir.path().join("WPE_A.json"), forged.to_string()).unwrap();
+        let forged = ComponentCert::load(&dir.path().join("WPE_A.json")).unwrap();
+        let cert = build_system_certificate(&[forged], &host, &IssuerIdentity::default(), None).unwrap();
+        assert_eq!(cert["result"], "FAIL");
+        let report = verify_chain(&cert, dir.path(), Some(&public_key)).unwrap();
+        assert_eq!(report.components[0].status, ComponentStatus::BadSignature);
+        assert!(verify_chain(&cert, dir.path(), None).unwrap().valid);
+    }
+
+    #[test]
+    fn test_build_rejects_bad_components() {
+        let dir = TempDir::new().unwrap();
+        let key = SigningKey::generate(&mut OsRng);
+        let host = HostInfo { hostname: "lab-07".to_string(), ..Default::default() };
+        let identity = IssuerIdentity::default();
+        let wipe = signed_wipe(dir.path(), "WPE_A", "S1", "PURGE", "PASS", &key);
+
+        assert!(matches!(build_system_certificate(&[], &host, &identity, None), Err(SystemCertError::NoComponents)));
+        assert!(matches!(
+            build_system_certificate(&[wipe.clone(), wipe.clone()], &host, &identity, None),
+            Err(SystemCertError::Duplicate(_))
+        ));
+
+        let mut unsigned = wipe.clone();
+        unsigned.value.as_object_mut().unwrap().remove("signature");
+        assert!(matches!(build_system_certificate(&[unsigned], &host, &identity, None), Err(SystemCertError::Unsigned(_))));
+
+        let mut unknown = wipe.clone();
+        unknown.value["result"] = json!("PASSED");
+        assert!(matches!(
+            build_system_certificate(&[unknown], &host, &identity, None),
+            Err(SystemCertError::UnknownResult(_, result)) if result == "PASSED"
+        ));
+
+        let mut backup = wipe;
+        backup.value["cert_type"] = json!("backup");
+        assert!(matches!(build_system_certificate(&[backup], &host, &identity, None), Err(SystemCertError::WrongType(..))));
+    }
+}
@@ -0,0 +1,407 @@
+//! Deferred work. `cert schedule-reverify` queues a re-verification of a wipe
+//! certificate for a later date in `tasks.json` in the data directory; `tasks run`
+//! executes the tasks that have come due. A re-verification replays the sectors
+//! sampled during the wipe when the drive is attached, and otherwise records that it
+//! was unavailable. Every attempt is appended to a `<cert_id>_reverify.jsonl` sidecar
+//! next to the certificate, so a drive that stays in service builds up a dated trail
+//! showing it still reads back as wiped.
+
+use crate::config::Config;
+use crate::device::Device;
+use crate::wipe::{replay_verification, VerificationRecord};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+const STORE_FILE: &str = "tasks.json";
+const LOCK_FILE: &str = "tasks.lock";
+
+/// Events recorded in the audit log
+pub const REVERIFY_SCHEDULED_EVENT: &str = "reverify_scheduled";
+pub const CERTIFICATE_REVERIFIED_EVENT: &str = "certificate_reverified";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TaskError {
+    #[error("Task store error: {0}")]
+    Store(String),
+    #[error("Certificate not found: {0}")]
+    NotFound(String),
+    #[error("{0} is not a wipe certificate")]
+    NotWipe(String),
+    #[error("{0} has no replayable verification data (issued before --verify-seed support)")]
+    NotReplayable(String),
+    #[error("{0}: {1}")]
+    Io(String, String),
+    #[error(transparent)]
+    Id(#[from] crate::cert_id::CertIdError),
+}
+
+fn store_error(path: &Path, e: impl std::fmt::Display) -> TaskError {
+    TaskError::Store(format!("{}: {}", path.display(), e))
+}
+
+fn io_error(path: &Path, e: impl std::fmt::Display) -> TaskError {
+    TaskError::Io(path.display().to_string(), e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    /// Replay a wipe certificate's verification samples
+    Reverify,
+}
+
+/// How a re-verification went
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReverifyOutcome {
+    /// Every sampled sector still matched the certificate
+    Passed,
+    /// A sample, the device size or the seed no longer matched
+    Failed,
+    /// No attached drive has the certificate's serial or device ID
+    DeviceUnavailable,
+    /// The drive was found but couldn't be read
+    Error,
+}
+
+impl ReverifyOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReverifyOutcome::Passed => "passed",
+            ReverifyOutcome::Failed => "failed",
+            ReverifyOutcome::DeviceUnavailable => "device unavailable",
+            ReverifyOutcome::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Task {
+    pub id: u64,
+    pub kind: TaskKind,
+    pub cert_id: String,
+    /// The drive to look for, copied from the certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    pub created_at: String,
+    pub created_by: String,
+    pub due_at: String,
+    /// Set once the task has run, whatever the outcome
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<ReverifyOutcome>,
+}
+
+impl Task {
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.completed_at.is_none()
+            && DateTime::parse_from_rfc3339(&self.due_at).map(|due| due <= now).unwrap_or(true)
+    }
+}
+
+/// One re-verification attempt, as appended to the certificate's sidecar
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Reverification {
+    pub cert_id: String,
+    pub task_id: u64,
+    pub at: String,
+    pub outcome: ReverifyOutcome,
+    /// Device path the drive was found at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    pub samples_checked: usize,
+    pub samples_matched: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mismatched_offsets: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// SHA-256 of the certificate file the samples were compared against
+    pub certificate_sha256: String,
+}
+
+pub fn reverifications_path(cert_dir: &Path, cert_id: &str) -> PathBuf {
+    cert_dir.join(format!("{}_reverify.jsonl", cert_id))
+}
+
+/// Earlier re-verifications of `cert_id`, oldest first
+pub fn reverifications(cert_dir: &Path, cert_id: &str) -> Result<Vec<Reverification>, TaskError> {
+    let path = reverifications_path(cert_dir, cert_id);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(&path, e)),
+    };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| io_error(&path, e)))
+        .collect()
+}
+
+fn append_reverification(cert_dir: &Path, record: &Reverification) -> Result<(), TaskError> {
+    let path = reverifications_path(cert_dir, &record.cert_id);
+    let mut line = serde_json::to_vec(record).map_err(|e| io_error(&path, e))?;
+    line.push(b'\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(&path).map_err(|e| io_error(&path, e))?;
+    file.write_all(&line).and_then(|_| file.sync_data()).map_err(|e| io_error(&path, e))
+}
+
+/// A wipe certificate, with the verification record a re-verification replays
+struct ReplayableCertificate {
+    value: Value,
+    sha256: String,
+    record: VerificationRecord,
+}
+
+fn load_replayable(cert_dir: &Path, cert_id: &str) -> Result<ReplayableCertificate, TaskError> {
+    let cert_path = crate::cert_id::certificate_path(cert_dir, cert_id)?;
+    let bytes = fs::read(&cert_path).map_err(|_| TaskError::NotFound(cert_path.display().to_string()))?;
+    let value: Value = serde_json::from_slice(&bytes).map_err(|e| io_error(&cert_path, e))?;
+    if value.get("cert_type").and_then(Value::as_str) != Some("wipe") {
+        return Err(TaskError::NotWipe(cert_id.to_string()));
+    }
+    let record = value
+        .get("verify")
+        .and_then(|verify| verify.get("replay"))
+        .cloned()
+        .and_then(|replay| serde_json::from_value(replay).ok())
+        .ok_or_else(|| TaskError::NotReplayable(cert_id.to_string()))?;
+    Ok(ReplayableCertificate { value, sha256: format!("{:x}", Sha256::digest(&bytes)), record })
+}
+
+/// The attached drive a certificate was issued for: by stable device ID when both
+/// sides have one, otherwise by serial. Never by path, which can change between boots.
+pub fn locate<'a>(devices: &'a [Device], serial: Option<&str>, device_id: Option<&str>) -> Option<&'a Device> {
+    let serial = serial.map(str::trim).filter(|serial| !serial.is_empty());
+    devices.iter().find(|device| match (device_id, device.device_id.as_deref()) {
+        (Some(wanted), Some(found)) => wanted == found,
+        _ => serial.is_some() && device.serial.as_deref().map(str::trim) == serial,
+    })
+}
+
+/// The pending and completed tasks in one data directory
+pub struct TaskStore {
+    dir: PathBuf,
+}
+
+impl TaskStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The store in the configured data directory
+    pub fn open_default() -> Result<Self, TaskError> {
+        let config = Config::load().map_err(|e| TaskError::Store(e.to_string()))?;
+        Ok(Self::new(config.data_dir))
+    }
+
+    pub fn tasks(&self) -> Result<Vec<Task>, TaskError> {
+        let path = self.dir.join(STORE_FILE);
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| store_error(&path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(store_error(&path, e)),
+        }
+    }
+
+    /// Pending tasks due by `now`, oldest due date first
+    pub fn due(&self, now: DateTime<Utc>) -> Result<Vec<Task>, TaskError> {
+        let mut due: Vec<Task> = self.tasks()?.into_iter().filter(|task| task.is_due(now)).collect();
+        due.sort_by(|a, b| a.due_at.cmp(&b.due_at).then(a.id.cmp(&b.id)));
+        Ok(due)
+    }
+
+    /// Queue a re-verification of `cert_id` for `after` from now. Fails unless the
+    /// certificate is a wipe certificate with replayable samples.
+    pub fn schedule_reverify(
+        &self,
+        cert_dir: &Path,
+        cert_id: &str,
+        after: Duration,
+        created_by: &str,
+    ) -> Result<Task, TaskError> {
+        let cert = load_replayable(cert_dir, cert_id)?;
+        let device = &cert.value["device"];
+        let now = Utc::now();
+        self.update(|tasks| {
+            let task = Task {
+                id: tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1,
+                kind: TaskKind::Reverify,
+                cert_id: cert_id.to_string(),
+                serial: device.get("serial").and_then(Value::as_str).map(str::to_string),
+                device_id: device.get("device_id").and_then(Value::as_str).map(str::to_string),
+                created_at: now.to_rfc3339(),
+                created_by: created_by.to_string(),
+                due_at: (now + after).to_rfc3339(),
+                completed_at: None,
+                outcome: None,
+            };
+            tasks.push(task.clone());
+            task
+        })
+    }
+
+    /// Run one due task against the drives currently attached and record the result
+    pub fn run(&self, cert_dir: &Path, task: &Task, devices: &[Device]) -> Result<Reverification, TaskError> {
+        let cert = load_replayable(cert_dir, &task.cert_id)?;
+        let mut reverification = Reverification {
+            cert_id: task.cert_id.clone(),
+            task_id: task.id,
+            at: Utc::now().to_rfc3339(),
+            outcome: ReverifyOutcome::DeviceUnavailable,
+            device: None,
+            samples_checked: 0,
+            samples_matched: 0,
+            mismatched_offsets: Vec::new(),
+            detail: None,
+            certificate_sha256: cert.sha256,
+        };
+        if let Some(device) = locate(devices, task.serial.as_deref(), task.device_id.as_deref()) {
+            reverification.device = Some(device.name.clone());
+            match replay_verification(&device.name, &cert.record) {
+                Ok(report) => {
+                    reverification.outcome = if report.passed { ReverifyOutcome::Passed } else { ReverifyOutcome::Failed };
+                    reverification.samples_checked = report.samples_checked;
+                    reverification.samples_matched = report.samples_matched;
+                    reverification.mismatched_offsets = report.mismatched_offsets;
+                    if !report.device_size_matches {
+                        reverification.detail = Some("device size differs from the certificate".to_string());
+                    } else if !report.offsets_match_seed {
+                        reverification.detail = Some("sample offsets don't match the recorded seed".to_string());
+                    }
+                }
+                Err(e) => {
+                    reverification.outcome = ReverifyOutcome::Error;
+                    reverification.detail = Some(e.to_string());
+                }
+            }
+        }
+
+        append_reverification(cert_dir, &reverification)?;
+        let at = reverification.at.clone();
+        let outcome = reverification.outcome;
+        self.update(|tasks| {
+            if let Some(stored) = tasks.iter_mut().find(|stored| stored.id == task.id) {
+                stored.completed_at = Some(at);
+                stored.outcome = Some(outcome);
+            }
+        })?;
+        Ok(reverification)
+    }
+
+    /// Read, change and write the task list under an exclusive lock
+    fn update<T>(&self, change: impl FnOnce(&mut Vec<Task>) -> T) -> Result<T, TaskError> {
+        fs::create_dir_all(&self.dir).map_err(|e| store_error(&self.dir, e))?;
+        let lock_path = self.dir.join(LOCK_FILE);
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| store_error(&lock_path, e))?;
+        if unsafe { libc::flock(lock.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(store_error(&lock_path, std::io::Error::last_os_error()));
+        }
+
+        let mut tasks = self.tasks()?;
+        let result = change(&mut tasks);
+        self.save(&tasks)?;
+        // Dropping `lock` releases it
+        Ok(result)
+    }
+
+    fn save(&self, tasks: &[Task]) -> Result<(), TaskError> {
+        let path = self.dir.join(STORE_FILE);
+        let tmp = self.dir.join(format!("{}.tmp", STORE_FILE));
+        let json = serde_json::to_string_pretty(tasks).map_err(|e| store_error(&path, e))?;
+        File::create(&tmp)
+            .and_then(|mut file| {
+                file.write_all(json.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| store_error(&path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::RiskLevel;
+    use serde_json::json;
+
+    fn device(name: &str, serial: Option<&str>, device_id: Option<&str>) -> Device {
+        Device {
+            name: name.to_string(),
+            model: None,
+            serial: serial.map(str::to_string),
+            capacity_bytes: 0,
+            bus: None,
+            mountpoints: Vec::new(),
+            risk_level: RiskLevel::Safe,
+            device_id: device_id.map(str::to_string),
+            risk_explanation: None,
+            capacity_display: None,
+        }
+    }
+
+    #[test]
+    fn test_locate() {
+        let devices = [
+            device("/dev/sda", Some("S1"), Some("wwn:0x5000c500a1b2c3d4")),
+            device("/dev/sdb", Some("S2"), None),
+        ];
+        assert_eq!(locate(&devices, Some("S1"), None).unwrap().name, "/dev/sda");
+        assert_eq!(locate(&devices, Some("S2"), Some("sn:abc")).unwrap().name, "/dev/sdb");
+        assert_eq!(locate(&devices, Some(" S2 "), None).unwrap().name, "/dev/sdb");
+        // A different drive that happens to report the same serial
+        assert!(locate(&devices, Some("S1"), Some("wwn:0x5000c500ffffffff")).is_none());
+        assert!(locate(&devices, None, None).is_none());
+        assert!(locate(&devices, Some(""), None).is_none());
+    }
+
+    #[test]
+    fn test_schedule_and_run_without_device() {
+        let data = tempfile::tempdir().unwrap();
+        let certs = tempfile::tempdir().unwrap();
+        let cert = json!({
+            "cert_id": "WPE_1",
+            "cert_type": "wipe",
+            "device": {"serial": "S1"},
+            "verify": {"replay": {"seed": 7, "sample_bytes": 4096, "device_size": 1048576, "samples": []}}
+        });
+        fs::write(certs.path().join("WPE_1.json"), cert.to_string()).unwrap();
+        fs::write(certs.path().join("BKP_1.json"), json!({"cert_id": "BKP_1", "cert_type": "backup"}).to_string()).unwrap();
+
+        let store = TaskStore::new(data.path());
+        assert!(matches!(
+            store.schedule_reverify(certs.path(), "BKP_1", Duration::days(30), "alice"),
+            Err(TaskError::NotWipe(_))
+        ));
+        let later = store.schedule_reverify(certs.path(), "WPE_1", Duration::days(30), "alice").unwrap();
+        let now = store.schedule_reverify(certs.path(), "WPE_1", Duration::zero(), "alice").unwrap();
+        assert_eq!((later.id, now.id), (1, 2));
+        assert_eq!(later.serial.as_deref(), Some("S1"));
+
+        let due = store.due(Utc::now()).unwrap();
+        assert_eq!(due, vec![now.clone()]);
+        assert_eq!(store.due(Utc::now() + Duration::days(31)).unwrap().len(), 2);
+
+        let result = store.run(certs.path(), &due[0], &[device("/dev/sda", Some("S9"), None)]).unwrap();
+        assert_eq!(result.outcome, ReverifyOutcome::DeviceUnavailable);
+        assert!(store.due(Utc::now()).unwrap().is_empty());
+        let stored = store.tasks().unwrap();
+        assert_eq!(stored[1].outcome, Some(ReverifyOutcome::DeviceUnavailable));
+        assert!(stored[0].completed_at.is_none());
+        assert_eq!(reverifications(certs.path(), "WPE_1").unwrap(), vec![result]);
+    }
+}
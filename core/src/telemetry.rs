@@ -0,0 +1,211 @@
+//! Opt-in, anonymized wipe statistics to help prioritize wipe-method support. Off
+//! unless the configured `telemetry_file` (see `config`) sets `{"enabled": true}`.
+//! A record holds only what ran, on which bus, for how long and whether it passed:
+//! no device paths, serials, models, hostnames or exact times.
+
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Errors raised while reading the telemetry config or writing a record
+#[derive(Debug, thiserror::Error)]
+pub enum TelemetryError {
+    #[error("Telemetry config error: {0}")]
+    Config(String),
+    #[error("Telemetry write error: {0}")]
+    Write(String),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// Nothing is recorded unless this is true
+    pub enabled: bool,
+    /// HTTPS URL each record is also POSTed to; local file only when unset
+    pub endpoint: Option<String>,
+    /// Where records are appended; `telemetry.jsonl` in the data directory when unset
+    pub log_path: Option<PathBuf>,
+}
+
+/// One anonymized wipe outcome
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TelemetryRecord {
+    pub operation: String,
+    /// "controller_sanitize" or "overwrite"
+    pub method: String,
+    pub policy: String,
+    /// Overwrite profile, when the wipe overwrote
+    pub profile: Option<String>,
+    /// Drive bus as discovered ("SATA", "NVMe", "USB", ...), or "unknown"
+    pub bus: String,
+    /// Rounded to whole seconds
+    pub duration_secs: u64,
+    pub success: bool,
+    /// Whether controller sanitize was tried and the wipe fell back to overwriting
+    pub fallback: bool,
+    /// Day only, so a record can't be matched to a certificate by timestamp
+    pub date: String,
+    pub version: String,
+}
+
+impl TelemetryConfig {
+    pub fn load(path: &Path) -> Result<Self, TelemetryError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| TelemetryError::Config(format!("{}: {}", path.display(), e)))?;
+        let config: Self = serde_json::from_str(&content)
+            .map_err(|e| TelemetryError::Config(format!("{}: {}", path.display(), e)))?;
+        if let Some(endpoint) = &config.endpoint {
+            if !endpoint.starts_with("https://") {
+                return Err(TelemetryError::Config(format!(
+                    "{}: endpoint must be an https:// URL, got {}",
+                    path.display(),
+                    endpoint
+                )));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load the configured telemetry file. Without one at the default location,
+    /// telemetry is disabled; a file named by config, SECUREWIPE_TELEMETRY or a flag
+    /// must exist.
+    pub fn load_default() -> Result<Self, TelemetryError> {
+        let config = Config::load().map_err(|e| TelemetryError::Config(e.to_string()))?;
+        if config.is_default("telemetry_file") && !config.telemetry_file.exists() {
+            return Ok(TelemetryConfig::default());
+        }
+        Self::load(&config.telemetry_file)
+    }
+
+    fn resolved_log_path(&self) -> Result<PathBuf, TelemetryError> {
+        match &self.log_path {
+            Some(path) => Ok(path.clone()),
+            None => Config::load()
+                .map(|config| config.data_dir.join("telemetry.jsonl"))
+                .map_err(|e| TelemetryError::Write(e.to_string())),
+        }
+    }
+
+    /// Append `record` to the local log and, when an endpoint is configured, send it
+    /// there. Returns where the record went. Does nothing when disabled. A failed
+    /// upload is reported but the local copy is kept.
+    pub fn record(&self, record: &TelemetryRecord) -> Result<Vec<String>, TelemetryError> {
+        if !self.enabled {
+            return Ok(Vec::new());
+        }
+
+        let line = serde_json::to_string(record).map_err(|e| TelemetryError::Write(e.to_string()))?;
+        let path = self.resolved_log_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| TelemetryError::Write(format!("{}: {}", parent.display(), e)))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", line))
+            .map_err(|e| TelemetryError::Write(format!("{}: {}", path.display(), e)))?;
+        let mut destinations = vec![path.display().to_string()];
+
+        if let Some(endpoint) = &self.endpoint {
+            post_record(endpoint, &line)?;
+            destinations.push(endpoint.clone());
+        }
+        Ok(destinations)
+    }
+}
+
+/// POST one JSON record with curl, the same way other external tools are driven
+fn post_record(endpoint: &str, line: &str) -> Result<(), TelemetryError> {
+    let mut child = Command::new("curl")
+        .args(["-sS", "--fail", "--max-time", "10", "-X", "POST"])
+        .args(["-H", "Content-Type: application/json", "--data-binary", "@-", endpoint])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| TelemetryError::Write(format!("curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(line.as_bytes())
+            .map_err(|e| TelemetryError::Write(format!("curl: {}", e)))?;
+    }
+    let output = child.wait_with_output().map_err(|e| TelemetryError::Write(format!("curl: {}", e)))?;
+    if !output.status.success() {
+        return Err(TelemetryError::Write(format!(
+            "upload to {} failed: {}",
+            endpoint,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_record() -> TelemetryRecord {
+        TelemetryRecord {
+            operation: "wipe".to_string(),
+            method: "overwrite".to_string(),
+            policy: "PURGE".to_string(),
+            profile: Some("nist-clear".to_string()),
+            bus: "USB".to_string(),
+            duration_secs: 5400,
+            success: true,
+            fallback: true,
+            date: "2025-09-20".to_string(),
+            version: "1.0.0".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_telemetry_disabled_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("telemetry.json");
+        fs::write(&path, "{}").unwrap();
+        let config = TelemetryConfig::load(&path).unwrap();
+        assert!(!config.enabled);
+
+        let config = TelemetryConfig { log_path: Some(dir.path().join("out.jsonl")), ..config };
+        assert!(config.record(&sample_record()).unwrap().is_empty());
+        assert!(!dir.path().join("out.jsonl").exists());
+    }
+
+    #[test]
+    fn test_telemetry_appends_local_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("nested").join("telemetry.jsonl");
+        let config = TelemetryConfig { enabled: true, endpoint: None, log_path: Some(log.clone()) };
+
+        assert_eq!(config.record(&sample_record()).unwrap(), vec![log.display().to_string()]);
+        config.record(&sample_record()).unwrap();
+
+        let content = fs::read_to_string(&log).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let parsed: TelemetryRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(parsed, sample_record());
+        // Only the documented fields leave the machine
+        let keys: Vec<String> = serde_json::from_str::<serde_json::Value>(lines[0]).unwrap()
+            .as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys.len(), 10);
+        assert!(!content.contains("/dev/"));
+    }
+
+    #[test]
+    fn test_telemetry_endpoint_must_be_https() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("telemetry.json");
+        fs::write(&path, r#"{"enabled": true, "endpoint": "http://stats.example.com"}"#).unwrap();
+        assert!(TelemetryConfig::load(&path).is_err());
+
+        fs::write(&path, r#"{"enabled": true, "endpoint": "https://stats.example.com/v1"}"#).unwrap();
+        let config = TelemetryConfig::load(&path).unwrap();
+        assert_eq!(config.endpoint.as_deref(), Some("https://stats.example.com/v1"));
+    }
+}
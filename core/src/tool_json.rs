@@ -0,0 +1,165 @@
+//! Typed models of `smartctl --json` and `nvme ... -o json` output. Every field is
+//! optional and unknown ones are ignored, so output from older and newer tool releases
+//! parses the same; the text scrapers are only a fallback for tools too old to emit JSON.
+
+use serde::{Deserialize, Deserializer};
+
+/// `smartctl -i -j` (also the identity part of `-a -j`); exit status is a bit mask and
+/// non-zero often comes with complete output, so only the fields decide
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SmartctlInfo {
+    pub model_name: Option<String>,
+    /// SCSI drives before smartctl 7.3 report vendor and product, not a model name
+    pub scsi_vendor: Option<String>,
+    pub scsi_product: Option<String>,
+    pub serial_number: Option<String>,
+    pub firmware_version: Option<String>,
+    pub user_capacity: Option<SmartctlCapacity>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SmartctlCapacity {
+    #[serde(deserialize_with = "lenient_u64")]
+    pub bytes: Option<u64>,
+}
+
+impl SmartctlInfo {
+    pub fn parse(output: &str) -> Option<Self> {
+        serde_json::from_str(output).ok()
+    }
+
+    pub fn model(&self) -> Option<String> {
+        trimmed(self.model_name.as_deref()).or_else(|| {
+            let parts: Vec<String> = [&self.scsi_vendor, &self.scsi_product]
+                .into_iter()
+                .filter_map(|part| trimmed(part.as_deref()))
+                .collect();
+            (!parts.is_empty()).then(|| parts.join(" "))
+        })
+    }
+
+    pub fn serial(&self) -> Option<String> {
+        trimmed(self.serial_number.as_deref())
+    }
+
+    pub fn firmware(&self) -> Option<String> {
+        trimmed(self.firmware_version.as_deref())
+    }
+
+    pub fn capacity_bytes(&self) -> Option<u64> {
+        self.user_capacity.as_ref().and_then(|capacity| capacity.bytes)
+    }
+}
+
+/// `nvme id-ctrl -o json`
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NvmeIdCtrl {
+    pub sn: Option<String>,
+    pub mn: Option<String>,
+    pub fr: Option<String>,
+    /// Total NVM capacity; nvme-cli prints this 128-bit field as a float in some releases
+    #[serde(deserialize_with = "lenient_u64")]
+    pub tnvmcap: Option<u64>,
+    /// Sanitize Capabilities: bit 0 crypto erase, bit 1 block erase, bit 2 overwrite
+    #[serde(deserialize_with = "lenient_u64")]
+    pub sanicap: Option<u64>,
+}
+
+impl NvmeIdCtrl {
+    pub fn parse(output: &str) -> Option<Self> {
+        serde_json::from_str(output).ok()
+    }
+
+    pub fn serial(&self) -> Option<String> {
+        trimmed(self.sn.as_deref())
+    }
+
+    pub fn model(&self) -> Option<String> {
+        trimmed(self.mn.as_deref())
+    }
+
+    pub fn firmware(&self) -> Option<String> {
+        trimmed(self.fr.as_deref())
+    }
+
+    /// Client drives often report 0 for total NVM capacity
+    pub fn capacity_bytes(&self) -> Option<u64> {
+        self.tnvmcap.filter(|&capacity| capacity > 0)
+    }
+
+    pub fn sanitize_supported(&self) -> bool {
+        self.sanicap.unwrap_or(0) & 0b111 != 0
+    }
+}
+
+fn trimmed(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// A count given as an integer, a float, or a decimal or `0x` hex string, depending
+/// on the tool release
+fn lenient_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.and_then(|value| match value {
+        serde_json::Value::Number(number) => number
+            .as_u64()
+            .or_else(|| number.as_f64().filter(|f| *f >= 0.0).map(|f| f as u64)),
+        serde_json::Value::String(text) => {
+            let text = text.trim();
+            match text.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => text.parse().ok(),
+            }
+        }
+        _ => None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smartctl_info_across_versions() {
+        let ata = r#"{"json_format_version":[1,0],"smartctl":{"version":[7,4],"exit_status":4},
+            "device":{"name":"/dev/sda","protocol":"ATA"},"model_name":"WDC WD20EZRZ-00Z5HB0",
+            "serial_number":"WD-WCC4N7ABCDEF","firmware_version":"80.00A80",
+            "user_capacity":{"blocks":3907029168,"bytes":2000398934016},"logical_block_size":512,
+            "some_future_field":{"nested":true}}"#;
+        let info = SmartctlInfo::parse(ata).unwrap();
+        assert_eq!(info.model().as_deref(), Some("WDC WD20EZRZ-00Z5HB0"));
+        assert_eq!(info.serial().as_deref(), Some("WD-WCC4N7ABCDEF"));
+        assert_eq!(info.capacity_bytes(), Some(2000398934016));
+
+        // smartctl 7.1 on a SCSI disk: no model_name, capacity as a string
+        let scsi = r#"{"json_format_version":[1,0],"scsi_vendor":"SEAGATE ","scsi_product":"ST4000NM0023",
+            "serial_number":"Z1Z0ABCD","user_capacity":{"bytes":"4000787030016"}}"#;
+        let info = SmartctlInfo::parse(scsi).unwrap();
+        assert_eq!(info.model().as_deref(), Some("SEAGATE ST4000NM0023"));
+        assert_eq!(info.capacity_bytes(), Some(4000787030016));
+
+        // smartctl before 7.0 rejects -j and prints text
+        assert!(SmartctlInfo::parse("=======> UNRECOGNIZED OPTION: j").is_none());
+    }
+
+    #[test]
+    fn test_nvme_id_ctrl() {
+        let id_ctrl = r#"{"vid":5197,"sn":"S4EWNX0R123456  ","mn":"Samsung SSD 980 PRO 1TB                 ",
+            "fr":"5B2QGXA7","tnvmcap":1.000204886016e12,"sanicap":3}"#;
+        let ctrl = NvmeIdCtrl::parse(id_ctrl).unwrap();
+        assert_eq!(ctrl.serial().as_deref(), Some("S4EWNX0R123456"));
+        assert_eq!(ctrl.model().as_deref(), Some("Samsung SSD 980 PRO 1TB"));
+        assert_eq!(ctrl.capacity_bytes(), Some(1000204886016));
+        assert!(ctrl.sanitize_supported());
+
+        let client = NvmeIdCtrl::parse(r#"{"sn":"X","tnvmcap":0,"sanicap":"0x0"}"#).unwrap();
+        assert_eq!(client.capacity_bytes(), None);
+        assert!(!client.sanitize_supported());
+    }
+}
@@ -0,0 +1,231 @@
+//! Virtual media: disk image files (raw, qcow2) and volumes a hypervisor or cloud
+//! provider attaches as block devices (virtio, Xen, EBS, Persistent Disk, ...). Their
+//! "controller" is the provider's storage stack, so a sanitize command or HPA/DCO clear
+//! proves nothing about where the data physically lives. They are only ever
+//! overwritten, and certificates record them as `virtual` media.
+//!
+//! Network block devices (iSCSI, NBD, Ceph RBD, NVMe over Fabrics) are the same case
+//! with the storage on another machine; wiping one also needs `--allow-network-device`.
+
+use crate::wipe::{MethodDecision, WipePlan};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const QCOW2_MAGIC: &[u8; 4] = b"QFI\xfb";
+
+/// Model or vendor strings of emulated and cloud disks, and the platform they mean
+const VIRTUAL_MODELS: &[(&str, &str)] = &[
+    ("Amazon Elastic Block Store", "Amazon EBS"),
+    ("PersistentDisk", "Google Persistent Disk"),
+    ("BlockVolume", "Oracle Cloud block volume"),
+    ("Virtual Disk", "Hyper-V/Azure virtual disk"),
+    ("Virtual disk", "VMware virtual disk"),
+    ("QEMU HARDDISK", "QEMU emulated disk"),
+    ("VBOX HARDDISK", "VirtualBox virtual disk"),
+];
+
+/// Kernel name prefixes of paravirtual block drivers
+const VIRTUAL_NAME_PREFIXES: &[(&str, &str)] = &[("vd", "virtio-blk volume"), ("xvd", "Xen volume")];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VirtualKind {
+    /// A regular file holding a disk image
+    ImageFile,
+    /// A block device backed by a hypervisor or cloud volume
+    BlockVolume,
+    /// A block device whose storage is reached over the network
+    NetworkVolume,
+}
+
+/// A wipe target identified as virtual, and how
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct VirtualMedia {
+    pub kind: VirtualKind,
+    /// `raw` or `qcow2`, for image files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// What identified it, e.g. "qcow2 header", "Amazon EBS" or "--virtual"
+    pub detected_by: String,
+}
+
+impl VirtualMedia {
+    /// Identify `path` as virtual media: an image file, or a block device whose
+    /// driver or model belongs to a hypervisor or cloud. None for anything else.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.is_file() {
+            let format = image_format(path);
+            return Some(VirtualMedia {
+                kind: VirtualKind::ImageFile,
+                detected_by: format!("{} image file", format),
+                format: Some(format.to_string()),
+            });
+        }
+        let name = fs::canonicalize(path).ok()?.file_name()?.to_string_lossy().to_string();
+        let sysfs = Path::new("/sys/class/block").join(&name);
+        let read = |attr: &str| fs::read_to_string(sysfs.join(attr)).unwrap_or_default();
+        let device_link = fs::canonicalize(sysfs.join("device")).map(|p| p.display().to_string()).unwrap_or_default();
+        if let Some(transport) = network_block_device(&name, &device_link, &read("device/transport")) {
+            return Some(VirtualMedia {
+                kind: VirtualKind::NetworkVolume,
+                format: None,
+                detected_by: transport.to_string(),
+            });
+        }
+        virtual_block_device(&name, &read("device/model"), &read("device/vendor")).map(|platform| VirtualMedia {
+            kind: VirtualKind::BlockVolume,
+            format: None,
+            detected_by: platform.to_string(),
+        })
+    }
+
+    /// For `--virtual` on a target detection didn't recognise
+    pub fn declared(path: &Path) -> Self {
+        let is_file = fs::metadata(path).map(|m| m.is_file()).unwrap_or(false);
+        VirtualMedia {
+            kind: if is_file { VirtualKind::ImageFile } else { VirtualKind::BlockVolume },
+            format: is_file.then(|| image_format(path).to_string()),
+            detected_by: "--virtual".to_string(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match (self.kind, &self.format) {
+            (VirtualKind::ImageFile, Some(format)) => format!("{} disk image", format),
+            (VirtualKind::ImageFile, None) => "disk image".to_string(),
+            (VirtualKind::BlockVolume, _) => format!("virtual volume ({})", self.detected_by),
+            (VirtualKind::NetworkVolume, _) => format!("network block device ({})", self.detected_by),
+        }
+    }
+
+    pub fn is_network(&self) -> bool {
+        self.kind == VirtualKind::NetworkVolume
+    }
+
+    pub fn sanitize_rejection(&self) -> String {
+        if self.is_network() {
+            return format!(
+                "Network storage ({}): a controller sanitize would only reach the remote target's storage stack and can't be attested",
+                self.describe()
+            );
+        }
+        format!(
+            "Virtual media ({}): a controller sanitize can't be attested for storage the hypervisor or cloud provides",
+            self.describe()
+        )
+    }
+
+    /// Overwrite only: reject the controller sanitize and skip the HPA/DCO clear
+    pub fn apply_to_plan(&self, plan: &mut WipePlan) {
+        let candidates = std::mem::take(&mut plan.method_decision.candidates)
+            .into_iter()
+            .map(|candidate| {
+                if candidate.method == "overwrite" {
+                    candidate
+                } else {
+                    candidate.rejected(self.sanitize_rejection())
+                }
+            })
+            .collect();
+        plan.main_method = "overwrite".to_string();
+        plan.hpa_dco_clear = false;
+        plan.method_decision = MethodDecision::choose(candidates, "overwrite");
+    }
+}
+
+fn image_format(path: &Path) -> &'static str {
+    let mut magic = [0u8; 4];
+    let is_qcow2 = fs::File::open(path).and_then(|mut file| file.read_exact(&mut magic)).is_ok() && &magic == QCOW2_MAGIC;
+    if is_qcow2 {
+        "qcow2"
+    } else {
+        "raw"
+    }
+}
+
+/// The network transport behind a block device, from its kernel name, the sysfs path
+/// of its device (iSCSI disks sit under a `session`) and an NVMe controller's transport
+fn network_block_device(name: &str, device_link: &str, nvme_transport: &str) -> Option<&'static str> {
+    if name.starts_with("nbd") {
+        return Some("NBD");
+    }
+    if name.starts_with("rbd") {
+        return Some("Ceph RBD");
+    }
+    if device_link.split('/').any(|part| part.strip_prefix("session").is_some_and(|n| n.parse::<u32>().is_ok())) {
+        return Some("iSCSI");
+    }
+    match nvme_transport.trim() {
+        "tcp" => Some("NVMe over TCP"),
+        "rdma" => Some("NVMe over RDMA"),
+        "fc" => Some("NVMe over Fibre Channel"),
+        _ => None,
+    }
+}
+
+/// The platform a block device belongs to, from its kernel name, model and vendor
+fn virtual_block_device(name: &str, model: &str, vendor: &str) -> Option<&'static str> {
+    let (model, vendor) = (model.trim(), vendor.trim());
+    VIRTUAL_NAME_PREFIXES.iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .or_else(|| VIRTUAL_MODELS.iter().find(|(text, _)| model.contains(text)))
+        .map(|(_, platform)| *platform)
+        .or(match vendor {
+            "QEMU" => Some("QEMU emulated disk"),
+            "VMware" | "VMware," => Some("VMware virtual disk"),
+            "Msft" => Some("Hyper-V/Azure virtual disk"),
+            "VBOX" => Some("VirtualBox virtual disk"),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_image_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let qcow2 = dir.path().join("disk.qcow2");
+        fs::write(&qcow2, [&QCOW2_MAGIC[..], &[0, 0, 0, 3]].concat()).unwrap();
+        let raw = dir.path().join("disk.img");
+        fs::write(&raw, vec![0u8; 4096]).unwrap();
+
+        let media = VirtualMedia::detect(&qcow2).unwrap();
+        assert_eq!(media.kind, VirtualKind::ImageFile);
+        assert_eq!(media.format.as_deref(), Some("qcow2"));
+        assert_eq!(VirtualMedia::detect(&raw).unwrap().format.as_deref(), Some("raw"));
+        assert!(VirtualMedia::detect(&dir.path().join("missing.img")).is_none());
+        assert_eq!(VirtualMedia::declared(&raw).detected_by, "--virtual");
+    }
+
+    #[test]
+    fn test_virtual_block_devices() {
+        assert_eq!(virtual_block_device("nvme1n1", "Amazon Elastic Block Store              \n", ""), Some("Amazon EBS"));
+        assert_eq!(virtual_block_device("vdb", "", "0x1af4"), Some("virtio-blk volume"));
+        assert_eq!(virtual_block_device("xvdf", "", ""), Some("Xen volume"));
+        assert_eq!(virtual_block_device("sda", "QEMU HARDDISK", "ATA"), Some("QEMU emulated disk"));
+        assert_eq!(virtual_block_device("sdb", "Virtual Disk", "Msft"), Some("Hyper-V/Azure virtual disk"));
+        assert_eq!(virtual_block_device("sda", "Samsung SSD 870", "ATA"), None);
+        assert_eq!(virtual_block_device("nvme0n1", "WDC WDS500G2B0C", ""), None);
+    }
+
+    #[test]
+    fn test_network_block_devices() {
+        assert_eq!(network_block_device("nbd0", "", ""), Some("NBD"));
+        assert_eq!(network_block_device("rbd3", "", ""), Some("Ceph RBD"));
+        let iscsi = "/sys/devices/platform/host3/session1/target3:0:0/3:0:0:0";
+        assert_eq!(network_block_device("sdc", iscsi, ""), Some("iSCSI"));
+        assert_eq!(network_block_device("nvme1n1", "/sys/devices/virtual/nvme-fabrics/ctl/nvme1", "tcp\n"), Some("NVMe over TCP"));
+        assert_eq!(network_block_device("nvme0n1", "/sys/devices/pci0000:00/0000:00:1d.0/0000:3d:00.0/nvme/nvme0", "pcie\n"), None);
+        assert_eq!(network_block_device("sda", "/sys/devices/pci0000:00/0000:00:17.0/ata1/host0/target0:0:0/0:0:0:0", ""), None);
+
+        let media = VirtualMedia { kind: VirtualKind::NetworkVolume, format: None, detected_by: "iSCSI".to_string() };
+        assert!(media.is_network());
+        assert_eq!(media.describe(), "network block device (iSCSI)");
+    }
+}
@@ -1,39 +1,28 @@
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::process::{Command, Stdio};
 use std::io::{Write, Read, Seek, SeekFrom};
 use std::fs::OpenOptions;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
 use std::time::{Instant, Duration};
 use std::thread;
 use std::sync::mpsc;
-use rand::RngCore;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use crate::signatures::{scan_device, SignatureFinding};
+use crate::policy::BadRegionPolicy;
+use crate::clock::MonotonicStart;
+use crate::events;
+use crate::cert::MonotonicSpan;
+use crate::virtual_disk::VirtualMedia;
+use crate::smart::{SmartDelta, SmartSnapshot};
+use crate::tool_json::{NvmeIdCtrl, SmartctlInfo};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WipePlan {
-    pub device: String,
-    pub risk: String,
-    pub policy: WipePolicy,
-    pub hpa_dco_clear: bool,
-    pub main_method: String,
-    pub verification: VerificationPlan,
-    pub blocked: bool,
-    pub reason: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VerificationPlan {
-    pub strategy: String,
-    pub samples: usize,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum WipePolicy {
-    #[serde(rename = "CLEAR")]
-    Clear,
-    #[serde(rename = "PURGE")]
-    Purge,
-}
+pub use securewipe_types::{CapabilityCheck, DeviceState, MaxSectors, MethodCandidate, MethodDecision, VerificationPlan, WipePlan, WipePolicy};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WipeCommand {
     pub command: String,
     pub exit_code: i32,
@@ -41,7 +30,7 @@ pub struct WipeCommand {
     pub output: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WipeResult {
     pub device: String,
     pub policy: WipePolicy,
@@ -50,6 +39,573 @@ pub struct WipeResult {
     pub verification_samples: usize,
     pub verification_passed: bool,
     pub fallback_reason: Option<String>,
+    /// Controller identity read just before the wipe started
+    #[serde(default)]
+    pub identity_before: Option<IdentitySnapshot>,
+    /// Controller identity read again once verification finished
+    #[serde(default)]
+    pub identity_after: Option<IdentitySnapshot>,
+    /// Seed, offsets and digests of the verification samples, for later replay
+    #[serde(default)]
+    pub verification: Option<VerificationRecord>,
+    /// Partition-table and filesystem signatures still present after the wipe
+    #[serde(default)]
+    pub residual_signatures: Vec<SignatureFinding>,
+    /// Methods tried during the wipe and why any fallback was needed
+    #[serde(default)]
+    pub method_decision: Option<MethodDecision>,
+    /// Readback comparison against the keyed overwrite pattern (pattern verify modes only)
+    #[serde(default)]
+    pub pattern_verification: Option<PatternVerification>,
+    /// Regions the overwrite engine skipped after its retries ran out
+    #[serde(default)]
+    pub bad_regions: Vec<BadRegion>,
+    /// Overwrite profile and the passes it wrote (overwrite method only)
+    #[serde(default)]
+    pub overwrite: Option<OverwriteRecord>,
+    /// HPA/DCO state read back around the clear step (PURGE overwrites only)
+    #[serde(default)]
+    pub hpa_dco: Option<HpaDcoCheck>,
+    /// MBR and GPT areas zeroed and re-read after the main wipe
+    #[serde(default)]
+    pub partition_metadata: Vec<MetadataRegion>,
+    /// Whole operation measured on the monotonic clock
+    #[serde(default)]
+    pub timing: Option<MonotonicSpan>,
+    /// Set when the target was an image file or hypervisor/cloud volume (overwrite only)
+    #[serde(default)]
+    pub virtual_media: Option<VirtualMedia>,
+    /// The drive's own host-writes counter before and after (physical drives only)
+    #[serde(default)]
+    pub smart_delta: Option<SmartDelta>,
+}
+
+/// What the drive reported around the HPA/DCO clear step, so the certificate can
+/// state whether hidden sectors were actually exposed rather than assume it
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct HpaDcoCheck {
+    pub before: Option<MaxSectors>,
+    pub after: Option<MaxSectors>,
+    /// DCO "Real max sectors", when the drive reports one
+    pub dco_real_max: Option<u64>,
+    /// hdparm commands run to remove the HPA or restore the DCO
+    pub commands: Vec<String>,
+    /// None when max sectors couldn't be read back after the clear
+    pub passed: Option<bool>,
+}
+
+/// A partition-table area zeroed by the final metadata step
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MetadataRegion {
+    /// `mbr`, `primary_gpt` or `backup_gpt`
+    pub name: String,
+    pub offset: u64,
+    pub length: u64,
+    /// True once the region read back as all zeros
+    pub verified: bool,
+}
+
+impl MetadataRegion {
+    fn new(name: &str, offset: u64, length: u64) -> Self {
+        MetadataRegion { name: name.to_string(), offset, length, verified: false }
+    }
+}
+
+/// The areas the metadata step zeroes on a device of `device_size` bytes: the MBR,
+/// the primary GPT (header and entries, rounded up to the first MiB so 4Kn layouts
+/// and oversized entry arrays are covered) and the backup GPT in the last MiB.
+/// Regions never overlap, so small devices get a shorter backup area or none.
+pub fn metadata_regions(device_size: u64) -> Vec<MetadataRegion> {
+    let size = device_size - device_size % VERIFY_SAMPLE_BYTES;
+    if size == 0 {
+        return Vec::new();
+    }
+    let mut regions = vec![MetadataRegion::new("mbr", 0, VERIFY_SAMPLE_BYTES)];
+    let primary_end = METADATA_SPAN_BYTES.min(size);
+    if primary_end > VERIFY_SAMPLE_BYTES {
+        regions.push(MetadataRegion::new("primary_gpt", VERIFY_SAMPLE_BYTES, primary_end - VERIFY_SAMPLE_BYTES));
+    }
+    let backup_start = size.saturating_sub(METADATA_SPAN_BYTES).max(primary_end);
+    if size > backup_start {
+        regions.push(MetadataRegion::new("backup_gpt", backup_start, size - backup_start));
+    }
+    regions
+}
+
+/// A stretch of the device that would not take a write, even sector by sector
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct BadRegion {
+    pub offset: u64,
+    pub length: u64,
+    /// The last I/O error reported for the region
+    pub error: String,
+}
+
+impl BadRegion {
+    pub fn contains(&self, offset: u64) -> bool {
+        offset >= self.offset && offset - self.offset < self.length
+    }
+}
+
+/// Caller-chosen settings for `perform_wipe_with_options`
+#[derive(Debug, Clone, Default)]
+pub struct WipeOptions {
+    /// Seed for the verification sample offsets; drawn at random when None
+    pub verify_seed: Option<u64>,
+    pub verify_mode: VerifyMode,
+    /// How many bytes of bad regions the wipe may leave and still pass
+    pub bad_regions: BadRegionPolicy,
+    /// Passes to write when the wipe falls back to (or is forced into) overwriting
+    pub profile: OverwriteProfile,
+    /// Virtual target: overwrite only, with no controller or HPA/DCO commands
+    pub virtual_media: Option<VirtualMedia>,
+    /// Run each overwrite pass without capabilities and under a seccomp filter
+    pub hardened: bool,
+}
+
+/// What one overwrite pass writes
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(tag = "fill", rename_all = "snake_case")]
+pub enum OverwriteFill {
+    Zeros,
+    Random,
+    /// The keyed pattern for this verification seed
+    Pattern { seed: u64 },
+    /// A byte sequence repeated from offset 0, e.g. `ff` or `924924`
+    Repeat {
+        #[serde(with = "hex_bytes")]
+        #[schemars(with = "String")]
+        bytes: Vec<u8>,
+    },
+}
+
+impl OverwriteFill {
+    fn repeat(bytes: &[u8]) -> Self {
+        OverwriteFill::Repeat { bytes: bytes.to_vec() }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            OverwriteFill::Zeros => "zeros".to_string(),
+            OverwriteFill::Random => "random".to_string(),
+            OverwriteFill::Pattern { seed } => format!("pattern seed={}", seed),
+            OverwriteFill::Repeat { bytes } => format!("repeat {}", hex_bytes::encode(bytes)),
+        }
+    }
+
+    fn fill(&self, random: &mut ChaCha20Rng, offset: u64, buffer: &mut [u8]) {
+        match self {
+            OverwriteFill::Zeros => buffer.fill(0),
+            OverwriteFill::Random => random.fill_bytes(buffer),
+            OverwriteFill::Pattern { seed } => expected_pattern(*seed, offset, buffer),
+            OverwriteFill::Repeat { bytes } => {
+                let period = bytes.len() as u64;
+                for (index, byte) in buffer.iter_mut().enumerate() {
+                    *byte = bytes[((offset + index as u64) % period) as usize];
+                }
+            }
+        }
+    }
+}
+
+/// Repeat fill bytes as lowercase hex in JSON
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn encode(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        if hex.is_empty() || hex.len() % 2 != 0 || !hex.is_ascii() {
+            return Err(D::Error::custom(format!("invalid repeat bytes: {:?}", hex)));
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// Named overwrite schemes. NIST SP 800-88 needs one pass; the others exist because
+/// some contracts still name DoD 5220.22-M or Gutmann-style multi-pass wipes.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum OverwriteProfile {
+    /// One pass: zeros for CLEAR, random for PURGE
+    #[default]
+    #[serde(rename = "nist-clear")]
+    NistClear,
+    /// Zeros, ones (0xFF), then random
+    #[serde(rename = "dod-5220.22-m-3pass")]
+    Dod522022M3Pass,
+    /// Seven passes: two random, 0x55, 0xAA, the 0x924924 / 0x6DB6DB MFM patterns, random
+    #[serde(rename = "gutmann-lite")]
+    GutmannLite,
+}
+
+impl OverwriteProfile {
+    /// Parse the `--overwrite-profile` CLI value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().replace('_', "-").as_str() {
+            "nist-clear" => Some(OverwriteProfile::NistClear),
+            "dod-5220.22-m-3pass" => Some(OverwriteProfile::Dod522022M3Pass),
+            "gutmann-lite" => Some(OverwriteProfile::GutmannLite),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OverwriteProfile::NistClear => "nist-clear",
+            OverwriteProfile::Dod522022M3Pass => "dod-5220.22-m-3pass",
+            OverwriteProfile::GutmannLite => "gutmann-lite",
+        }
+    }
+
+    /// Multi-pass profiles are asked for by name, so a controller sanitize can't stand in
+    pub fn requires_overwrite(self) -> bool {
+        self != OverwriteProfile::NistClear
+    }
+
+    /// Fills for each pass, in order. With `pattern_seed` the final pass writes the
+    /// keyed pattern instead, so pattern readback has something to compare against.
+    pub fn passes(self, policy: &WipePolicy, pattern_seed: Option<u64>) -> Vec<OverwriteFill> {
+        let mut passes = match self {
+            OverwriteProfile::NistClear => match policy {
+                WipePolicy::Clear => vec![OverwriteFill::Zeros],
+                WipePolicy::Purge => vec![OverwriteFill::Random],
+            },
+            OverwriteProfile::Dod522022M3Pass => {
+                vec![OverwriteFill::Zeros, OverwriteFill::repeat(&[0xFF]), OverwriteFill::Random]
+            }
+            OverwriteProfile::GutmannLite => vec![
+                OverwriteFill::Random,
+                OverwriteFill::Random,
+                OverwriteFill::repeat(&[0x55]),
+                OverwriteFill::repeat(&[0xAA]),
+                OverwriteFill::repeat(&[0x92, 0x49, 0x24]),
+                OverwriteFill::repeat(&[0x6D, 0xB6, 0xDB]),
+                OverwriteFill::Random,
+            ],
+        };
+        if let (Some(seed), Some(last)) = (pattern_seed, passes.last_mut()) {
+            *last = OverwriteFill::Pattern { seed };
+        }
+        passes
+    }
+
+    /// Record the profile in `plan`. A profile that requires overwriting replaces a
+    /// planned controller sanitize.
+    pub fn apply_to_plan(self, plan: &mut WipePlan) {
+        plan.overwrite_profile = Some(self.as_str().to_string());
+        if self.requires_overwrite() && plan.main_method != "overwrite" {
+            let candidates = std::mem::take(&mut plan.method_decision.candidates)
+                .into_iter()
+                .map(|candidate| {
+                    if candidate.method == "overwrite" {
+                        candidate
+                    } else {
+                        candidate.rejected(self.sanitize_rejection())
+                    }
+                })
+                .collect();
+            plan.main_method = "overwrite".to_string();
+            plan.method_decision = MethodDecision::choose(candidates, "overwrite");
+        }
+    }
+
+    fn sanitize_rejection(self) -> String {
+        format!("Overwrite profile {} requires overwrite passes", self.as_str())
+    }
+}
+
+/// One overwrite pass as it ran
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct OverwritePass {
+    /// 1-based position in the profile
+    pub pass: usize,
+    #[serde(flatten)]
+    pub fill: OverwriteFill,
+    pub bytes_written: u64,
+    /// Bytes this pass skipped as bad regions
+    pub bad_bytes: u64,
+}
+
+/// The overwrite profile a wipe ran and every pass it wrote
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct OverwriteRecord {
+    pub profile: OverwriteProfile,
+    pub passes: Vec<OverwritePass>,
+}
+
+/// How an overwrite is checked once it finishes
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyMode {
+    /// Sample sectors and check they look wiped (zero, constant or random fill)
+    #[default]
+    Heuristic,
+    /// Overwrite with a keyed pattern and compare the sampled sectors against it
+    Pattern,
+    /// Overwrite with a keyed pattern and compare every sector against it
+    PatternFull,
+}
+
+impl VerifyMode {
+    /// Parse the `--verify-mode` CLI value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "heuristic" => Some(VerifyMode::Heuristic),
+            "pattern" => Some(VerifyMode::Pattern),
+            "pattern-full" | "pattern_full" => Some(VerifyMode::PatternFull),
+            _ => None,
+        }
+    }
+
+    pub fn writes_pattern(self) -> bool {
+        !matches!(self, VerifyMode::Heuristic)
+    }
+}
+
+/// Size of each verification sample read from the device
+pub const VERIFY_SAMPLE_BYTES: u64 = 512;
+
+/// Bytes written or compared per read/write call by the overwrite engine and pattern readback
+const OVERWRITE_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Bytes zeroed for each GPT copy: covers the header and entry array at any sector size
+const METADATA_SPAN_BYTES: u64 = 1024 * 1024;
+
+/// How often an overwrite pass reports how far it has got
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Attempts at each chunk write, and then at each sector write, before giving up on it
+const WRITE_ATTEMPTS: usize = 3;
+
+/// Mismatching sector offsets kept in a pattern verification; the count covers the rest
+const PATTERN_MISMATCH_LIMIT: usize = 64;
+
+/// Domain separator for deriving the pattern key from the verification seed
+const PATTERN_KEY_CONTEXT: &[u8] = b"securewipe overwrite pattern v1";
+
+/// Outcome of comparing the device against the keyed overwrite pattern
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct PatternVerification {
+    pub seed: u64,
+    /// ChaCha20 keystream keyed with SHA-256(context || seed as little-endian u64)
+    pub algorithm: String,
+    /// Every sector was compared rather than the seed's sample offsets
+    pub full: bool,
+    pub sectors_checked: u64,
+    pub sectors_mismatched: u64,
+    /// The first mismatching sector offsets, at most 64
+    pub mismatched_offsets: Vec<u64>,
+    pub passed: bool,
+}
+
+/// Everything needed to re-read exactly the sectors that were sampled after a wipe
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct VerificationRecord {
+    pub seed: u64,
+    pub sample_bytes: u64,
+    pub device_size: u64,
+    pub samples: Vec<VerificationSample>,
+    /// Bad regions the sample offsets were drawn around
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_regions: Vec<BadRegion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct VerificationSample {
+    pub offset: u64,
+    pub sha256: String,
+    pub passed: bool,
+    pub class: SampleClass,
+    /// Shannon entropy in bits per byte
+    pub entropy: f64,
+    /// Pearson chi-square of the byte distribution against uniform (255 degrees of freedom)
+    pub chi_square: f64,
+}
+
+/// What a verification sample's contents look like
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleClass {
+    /// Every byte is zero
+    ZeroFill,
+    /// Every byte has the same non-zero value (e.g. 0xFF after some sanitize actions)
+    ConstantFill,
+    /// Byte distribution is consistent with uniform random data
+    RandomFill,
+    /// Anything else: filesystem structures, leftover user data
+    Structured,
+}
+
+impl SampleClass {
+    pub fn is_wiped(self) -> bool {
+        !matches!(self, SampleClass::Structured)
+    }
+}
+
+/// Upper-tail critical value of chi-square with 255 degrees of freedom at p = 0.001.
+/// Uniform random data exceeds it one time in a thousand.
+const CHI_SQUARE_CRITICAL: f64 = 330.52;
+
+/// Entropy and chi-square statistics for one sample, and the class they imply
+pub fn score_sample(data: &[u8]) -> (SampleClass, f64, f64) {
+    let mut counts = [0u64; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+
+    let len = data.len() as f64;
+    let expected = len / 256.0;
+    let mut entropy = 0.0;
+    let mut chi_square = 0.0;
+    for &count in &counts {
+        if count > 0 {
+            let p = count as f64 / len;
+            entropy -= p * p.log2();
+        }
+        let diff = count as f64 - expected;
+        chi_square += diff * diff / expected;
+    }
+
+    let class = if counts[0] as usize == data.len() {
+        SampleClass::ZeroFill
+    } else if counts.iter().any(|&count| count as usize == data.len()) {
+        SampleClass::ConstantFill
+    } else if chi_square <= CHI_SQUARE_CRITICAL {
+        SampleClass::RandomFill
+    } else {
+        SampleClass::Structured
+    };
+
+    (class, entropy, chi_square)
+}
+
+/// Sample classes counted over the whole device and over equal-sized regions of it.
+/// A run of structured regions at the end of the disk is the classic partial overwrite.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct VerificationHistogram {
+    pub totals: ClassCounts,
+    pub regions: Vec<RegionCounts>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ClassCounts {
+    pub zero_fill: usize,
+    pub constant_fill: usize,
+    pub random_fill: usize,
+    pub structured: usize,
+}
+
+impl ClassCounts {
+    fn add(&mut self, class: SampleClass) {
+        match class {
+            SampleClass::ZeroFill => self.zero_fill += 1,
+            SampleClass::ConstantFill => self.constant_fill += 1,
+            SampleClass::RandomFill => self.random_fill += 1,
+            SampleClass::Structured => self.structured += 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct RegionCounts {
+    pub start: u64,
+    pub end: u64,
+    #[serde(flatten)]
+    pub counts: ClassCounts,
+}
+
+/// Regions the device is split into for the verification histogram
+pub const HISTOGRAM_REGIONS: u64 = 8;
+
+impl VerificationRecord {
+    pub fn histogram(&self) -> VerificationHistogram {
+        let region_size = (self.device_size / HISTOGRAM_REGIONS).max(1);
+        let mut histogram = VerificationHistogram {
+            totals: ClassCounts::default(),
+            regions: (0..HISTOGRAM_REGIONS)
+                .map(|i| RegionCounts {
+                    start: i * region_size,
+                    end: if i + 1 == HISTOGRAM_REGIONS { self.device_size } else { (i + 1) * region_size },
+                    counts: ClassCounts::default(),
+                })
+                .collect(),
+        };
+
+        for sample in &self.samples {
+            histogram.totals.add(sample.class);
+            let region = (sample.offset / region_size).min(HISTOGRAM_REGIONS - 1) as usize;
+            histogram.regions[region].counts.add(sample.class);
+        }
+        histogram
+    }
+}
+
+/// Outcome of re-reading a certificate's verification samples
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayReport {
+    pub device: String,
+    pub seed: u64,
+    pub device_size_matches: bool,
+    pub offsets_match_seed: bool,
+    pub samples_checked: usize,
+    pub samples_matched: usize,
+    pub mismatched_offsets: Vec<u64>,
+    pub passed: bool,
+}
+
+/// What the drive controller reports about itself at one point in time. Comparing a
+/// before/after pair shows the wipe ran against the same physical device.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct IdentitySnapshot {
+    pub captured_at: String,
+    /// Tool the values came from: "nvme_id_ctrl", "hdparm_identify" or "smartctl_info"
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub firmware: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity_bytes: Option<u64>,
+    /// Raw SSTAT word from the NVMe sanitize log (status in bits 2:0, global data
+    /// erased in bit 8)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sanitize_status: Option<u64>,
+    /// SHA-256 of the raw tool output the fields were parsed from
+    pub raw_sha256: String,
+}
+
+impl IdentitySnapshot {
+    /// Same serial, model, firmware and capacity. A snapshot without a serial never
+    /// matches, since nothing would tie it to a physical drive.
+    pub fn same_device_as(&self, other: &IdentitySnapshot) -> bool {
+        self.serial.is_some()
+            && self.serial == other.serial
+            && self.model == other.model
+            && self.firmware == other.firmware
+            && self.capacity_bytes == other.capacity_bytes
+    }
+
+    /// Whether the sanitize log moved to a completed state between `before` and
+    /// `self`. None when either snapshot has no sanitize log.
+    pub fn sanitize_recorded_since(&self, before: &IdentitySnapshot) -> Option<bool> {
+        let after = self.sanitize_status?;
+        let before = before.sanitize_status?;
+        // 1 = completed, 4 = completed without deallocation
+        let completed = matches!(after & 0x7, 1 | 4);
+        Some(completed && after != before)
+    }
 }
 
 #[allow(dead_code)] // MVP: Implementation pending
@@ -67,56 +623,182 @@ pub struct NistAlignedWipe;
 
 impl WipeOperations for NistAlignedWipe {
     fn perform_wipe(
+        &self,
+        device: &str,
+        policy: WipePolicy,
+        is_critical: bool,
+    ) -> Result<WipeResult, Box<dyn std::error::Error>> {
+        self.perform_wipe_with_options(device, policy, is_critical, &WipeOptions::default())
+    }
+}
+
+impl NistAlignedWipe {
+    /// Like `perform_wipe`, but picks verification sectors from `options.verify_seed`
+    /// so the same sectors can be re-read later (a random seed is drawn and recorded
+    /// when none is given), and applies the verify mode and bad-region allowance.
+    #[tracing::instrument(name = "wipe", skip_all, fields(device = %device, policy = ?policy))]
+    pub fn perform_wipe_with_options(
         &self,
         device: &str,
         policy: WipePolicy,
         _is_critical: bool,
+        options: &WipeOptions,
     ) -> Result<WipeResult, Box<dyn std::error::Error>> {
+        let started = MonotonicStart::now();
+        let verify_mode = options.verify_mode;
+        let seed = options.verify_seed.unwrap_or_else(|| rand::thread_rng().next_u64());
         let mut commands = Vec::new();
-        let mut method = String::new();
+        let method;
         let mut fallback_reason = None;
+        let mut bad_regions = Vec::new();
 
-        println!("Starting NIST-aligned wipe on {}", device);
+        events::info(&format!("Starting NIST-aligned wipe on {}", device));
 
         // Check if we have permission to write to the device
         self.check_device_permissions(device)?;
 
+        // An emulated controller's identity says nothing about the storage behind it
+        let virtual_media = options.virtual_media.as_ref();
+        let identity_before = if virtual_media.is_none() { self.capture_identity(device, &mut commands) } else { None };
+        let smart_before = if virtual_media.is_none() { self.capture_smart(device, &mut commands) } else { None };
+
         // Step 1: Unmount all partitions on the device before wiping
         self.unmount_device(device, &mut commands)?;
 
         // Step 1: Try controller sanitize first, fallback to overwrite methods
-        match self.try_controller_sanitize(device, &policy, &mut commands) {
-            Ok(true) => {
-                method = "controller_sanitize".to_string();
-                println!("Controller sanitize successful");
-            }
-            Ok(false) | Err(_) => {
-                // Fallback to overwrite methods
-                fallback_reason = Some("Controller sanitize not available or failed".to_string());
-                method = "overwrite".to_string();
-                
-                match policy {
-                    WipePolicy::Clear => {
-                        self.perform_clear_wipe(device, &mut commands)?;
-                    }
-                    WipePolicy::Purge => {
-                        self.perform_purge_wipe(device, &mut commands)?;
-                    }
+        let profile = options.profile;
+        let mut sanitize = MethodCandidate::new("controller_sanitize");
+        let mut overwrite = MethodCandidate::new("overwrite");
+        let sanitized = if let Some(media) = virtual_media {
+            sanitize = sanitize.rejected(media.sanitize_rejection());
+            false
+        } else if profile.requires_overwrite() {
+            sanitize = sanitize.rejected(profile.sanitize_rejection());
+            false
+        } else {
+            match self.try_controller_sanitize(device, &policy, &mut commands, &mut sanitize.checks) {
+                Ok(true) => true,
+                Ok(false) | Err(_) => {
+                    fallback_reason = Some("Controller sanitize not available or failed".to_string());
+                    sanitize = sanitize.rejected("Controller sanitize not available or failed");
+                    false
                 }
             }
+        };
+
+        let mut overwrite_record = None;
+        let mut hpa_dco = None;
+        if sanitized {
+            method = "controller_sanitize".to_string();
+            overwrite = overwrite.rejected("Controller sanitize completed");
+            events::info("Controller sanitize successful");
+        } else {
+            method = "overwrite".to_string();
+            if policy == WipePolicy::Purge && virtual_media.is_none() {
+                hpa_dco = Some(self.clear_hpa_dco(device, &mut commands));
+            }
+
+            let fills = profile.passes(&policy, verify_mode.writes_pattern().then_some(seed));
+            events::info(&format!("Performing {} overwrite ({} pass(es))", profile.as_str(), fills.len()));
+            let total = fills.len();
+            let mut passes = Vec::with_capacity(total);
+            for (index, fill) in fills.into_iter().enumerate() {
+                events::info(&format!("Overwrite pass {}/{}: {}", index + 1, total, fill.describe()));
+                let (bytes_written, regions) = self.overwrite_device(device, &fill, options.hardened, &mut commands)?;
+                passes.push(OverwritePass {
+                    pass: index + 1,
+                    fill,
+                    bytes_written,
+                    bad_bytes: regions.iter().map(|region| region.length).sum(),
+                });
+                bad_regions.extend(regions);
+            }
+            // A weak sector usually fails on every pass; report it once
+            bad_regions = merge_bad_regions(bad_regions);
+            overwrite_record = Some(OverwriteRecord { profile, passes });
         }
 
+        // An interrupted sanitize or overwrite can leave the backup GPT at the end of
+        // the disk intact, and partitioning tools will restore the table from it
+        let partition_metadata = self.destroy_partition_metadata(device, &mut commands)?;
+
         // Step 2: Verification sampling
         let verification_samples = match policy {
             WipePolicy::Clear => 32,
             WipePolicy::Purge => 128,
         };
         
-        let verification_passed = self.verify_wipe(device, verification_samples)?;
+        let (mut samples_passed, verification) = self.verify_wipe(device, verification_samples, seed, &bad_regions)?;
+
+        // A pattern only exists on disk if the overwrite engine wrote it; after a
+        // controller sanitize the heuristic check is all there is
+        let pattern_verification = if verify_mode.writes_pattern() && method == "overwrite" {
+            let offsets: Vec<u64> = verification.samples.iter().map(|sample| sample.offset).collect();
+            let full = verify_mode == VerifyMode::PatternFull;
+            let report = verify_pattern(device, seed, if full { None } else { Some(&offsets) }, &bad_regions, &partition_metadata)?;
+            events::info(&format!("Pattern readback: {}/{} sectors match", report.sectors_checked - report.sectors_mismatched, report.sectors_checked));
+            samples_passed = report.passed;
+            Some(report)
+        } else {
+            None
+        };
+
+        // Random samples rarely land on the few sectors holding partition tables and
+        // superblocks, so check those directly: any survivor fails verification
+        let residual_signatures = scan_device(device)?;
+        for finding in &residual_signatures {
+            events::warn(&format!("Signature still present after wipe: {} at offset {}", finding.kind, finding.offset));
+        }
+
+        // Sectors that never took the overwrite may still hold data, so they only
+        // pass when site policy allows that much to be left behind
+        let bad_bytes: u64 = bad_regions.iter().map(|region| region.length).sum();
+        for region in &bad_regions {
+            events::warn(&format!("Bad region skipped: {} bytes at offset {} ({})", region.length, region.offset, region.error));
+        }
+        let failures: std::collections::BTreeSet<_> = bad_regions.iter()
+            .filter_map(|region| crate::remediation::classify_message(&region.error, Some(std::path::Path::new(device))))
+            .collect();
+        for failure in failures {
+            events::warn(&format!("Hint ({}): {}", failure, failure.hint()));
+        }
+        let bad_regions_accepted = options.bad_regions.accepts(&bad_regions);
+        if !bad_regions_accepted {
+            events::warn(&format!("Bad regions total {} bytes, over the {} bytes site policy allows", bad_bytes, options.bad_regions.max_bad_bytes));
+        }
+        // Sectors still hidden behind an HPA/DCO were never overwritten
+        let hpa_dco_confirmed = hpa_dco.as_ref().and_then(|check| check.passed) != Some(false);
+        if !hpa_dco_confirmed {
+            events::warn("HPA/DCO clear did not take effect: hidden sectors remain");
+        }
+        let metadata_cleared = partition_metadata.iter().all(|region| region.verified);
+        if !metadata_cleared {
+            events::warn("Partition metadata did not read back as zeros");
+        }
+        let verification_passed = samples_passed
+            && residual_signatures.is_empty()
+            && bad_regions_accepted
+            && hpa_dco_confirmed
+            && metadata_cleared;
         
-        println!("Wipe verification: {} samples, result: {}", 
+        events::info(&format!("Wipe verification: {} samples, result: {}", 
                 verification_samples, 
-                if verification_passed { "PASSED" } else { "FAILED" });
+                if verification_passed { "PASSED" } else { "FAILED" }));
+
+        let identity_after = if virtual_media.is_none() { self.capture_identity(device, &mut commands) } else { None };
+        let method_decision = MethodDecision::choose(vec![sanitize, overwrite], &method);
+
+        // Corroborating only: a counter that didn't move is reported, not failed
+        let expected_bytes = overwrite_record.as_ref().map(|record| record.passes.iter().map(|pass| pass.bytes_written).sum());
+        let smart_delta = smart_before
+            .zip(self.capture_smart(device, &mut commands))
+            .and_then(|(before, after)| SmartDelta::compare(before, after, expected_bytes));
+        if let Some(delta) = &smart_delta {
+            events::info(&format!("SMART write counter: {}", delta.describe()));
+            if delta.suspicious {
+                events::warn("The drive's write counter suggests the overwrite did not reach the media");
+            }
+        }
 
         Ok(WipeResult {
             device: device.to_string(),
@@ -126,23 +808,224 @@ impl WipeOperations for NistAlignedWipe {
             verification_samples,
             verification_passed,
             fallback_reason,
+            identity_before,
+            identity_after,
+            verification: Some(verification),
+            residual_signatures,
+            method_decision: Some(method_decision),
+            pattern_verification,
+            bad_regions,
+            overwrite: overwrite_record,
+            hpa_dco,
+            partition_metadata,
+            timing: Some(started.finish()),
+            virtual_media: options.virtual_media.clone(),
+            smart_delta,
         })
     }
 }
 
+/// True when `hdparm -I` reports ATA security as enabled (a password is set)
+fn ata_security_enabled(identify_output: &str) -> bool {
+    identify_output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("Security:"))
+        .skip(1)
+        .take_while(|line| line.starts_with('\t') || line.trim().is_empty())
+        .any(|line| line.trim() == "enabled")
+}
+
+fn sha256_hex(data: &str) -> String {
+    format!("{:x}", Sha256::digest(data.as_bytes()))
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// Build a snapshot from `nvme id-ctrl -o json` and, when available,
+/// `nvme sanitize-log -o json`
+fn parse_nvme_identity(id_ctrl: &str, sanitize_log: Option<&str>) -> Option<IdentitySnapshot> {
+    let ctrl = NvmeIdCtrl::parse(id_ctrl)?;
+    let serial = ctrl.serial()?;
+
+    let mut raw = id_ctrl.to_string();
+    let sanitize_status = sanitize_log.and_then(|log| {
+        raw.push_str(log);
+        let log: serde_json::Value = serde_json::from_str(log).ok()?;
+        find_key(&log, "sstat").and_then(|v| v.as_u64())
+    });
+
+    Some(IdentitySnapshot {
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        source: "nvme_id_ctrl".to_string(),
+        model: ctrl.model(),
+        serial: Some(serial),
+        firmware: ctrl.firmware(),
+        capacity_bytes: ctrl.capacity_bytes(),
+        sanitize_status,
+        raw_sha256: sha256_hex(&raw),
+    })
+}
+
+/// nvme-cli nests the sanitize log under the device name in some releases
+fn find_key<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a serde_json::Value> {
+    let object = value.as_object()?;
+    object
+        .get(key)
+        .or_else(|| object.values().find_map(|child| find_key(child, key)))
+}
+
+/// Build a snapshot from `hdparm -I` output
+fn parse_hdparm_identity(identify: &str) -> Option<IdentitySnapshot> {
+    let field = |name: &str| {
+        identify
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(name))
+            .and_then(|rest| non_empty(Some(rest.trim_start_matches(':'))))
+    };
+    let first_number = |text: &str| -> Option<u64> {
+        text.split(|c: char| !c.is_ascii_digit()).find(|s| !s.is_empty())?.parse().ok()
+    };
+
+    let serial = field("Serial Number")?;
+    let sectors = identify
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("LBA48") && line.contains("user addressable sectors"))
+        .or_else(|| {
+            identify
+                .lines()
+                .map(str::trim)
+                .find(|line| line.starts_with("LBA") && line.contains("user addressable sectors"))
+        })
+        .and_then(|line| line.split_once(':'))
+        .and_then(|(_, value)| first_number(value));
+    let sector_size = identify
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("Logical") && line.contains("Sector size"))
+        .and_then(|line| line.split_once(':'))
+        .and_then(|(_, value)| first_number(value))
+        .unwrap_or(512);
+
+    Some(IdentitySnapshot {
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        source: "hdparm_identify".to_string(),
+        model: field("Model Number"),
+        serial: Some(serial),
+        firmware: field("Firmware Revision"),
+        capacity_bytes: sectors.map(|count| count * sector_size),
+        sanitize_status: None,
+        raw_sha256: sha256_hex(identify),
+    })
+}
+
+/// Parse the ` max sectors   = 976771055/976773168, HPA is enabled` line of `hdparm -N`
+fn parse_hdparm_max_sectors(output: &str) -> Option<MaxSectors> {
+    let line = output.lines().find(|line| line.trim_start().starts_with("max sectors"))?;
+    let counts = line.split_once('=')?.1.split(',').next()?;
+    let (current, native) = counts.trim().split_once('/')?;
+    Some(MaxSectors { current: current.trim().parse().ok()?, native: native.trim().parse().ok()? })
+}
+
+/// Parse `Real max sectors: 976773168` from `hdparm --dco-identify`
+fn parse_dco_real_max(output: &str) -> Option<u64> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Real max sectors:"))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Cleared when the re-read shows no HPA and a native max at least the DCO's real max.
+/// None when max sectors couldn't be read back.
+fn hpa_dco_cleared(after: Option<MaxSectors>, dco_real_max: Option<u64>) -> Option<bool> {
+    after.map(|state| !state.hpa_active() && dco_real_max.is_none_or(|real_max| state.native >= real_max))
+}
+
+/// Build a snapshot from `smartctl -i -j` output
+fn parse_smartctl_identity(info: &str) -> Option<IdentitySnapshot> {
+    let info_json = SmartctlInfo::parse(info)?;
+    let serial = info_json.serial()?;
+
+    Some(IdentitySnapshot {
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        source: "smartctl_info".to_string(),
+        model: info_json.model(),
+        serial: Some(serial),
+        firmware: info_json.firmware(),
+        capacity_bytes: info_json.capacity_bytes(),
+        sanitize_status: None,
+        raw_sha256: sha256_hex(info),
+    })
+}
+
 impl NistAlignedWipe {
-    fn dd_completed_ok(&self, cmd: &WipeCommand) -> bool {
-        // dd returns exit code 1 when it hits end of device with no count specified
-        // and reports "No space left on device". Treat this as a successful full write.
-        cmd.exit_code == 0 || (cmd.exit_code == 1 && cmd.output.to_lowercase().contains("no space left on device"))
+    /// Undo state an interrupted wipe can leave behind. Today that is the ATA
+    /// security password set before a secure erase; partitions are deliberately
+    /// not remounted.
+    pub fn cleanup_after_cancel(&self, device: &str) -> Result<Vec<WipeCommand>, Box<dyn std::error::Error>> {
+        let mut commands = Vec::new();
+        
+        if let Ok(identify) = self.execute_command("hdparm", &["-I", device], &mut commands) {
+            if identify.exit_code == 0 && ata_security_enabled(&identify.output) {
+                events::warn(&format!("ATA security left enabled on {}, disabling", device));
+                self.execute_command("hdparm", &["--user-master", "u", "--security-disable", "p", device], &mut commands)?;
+            }
+        }
+        
+        Ok(commands)
+    }
+
+    /// Read the controller's identity, trying nvme-cli, hdparm and smartctl in turn.
+    /// Failures are not fatal: the certificate simply carries no identity proof.
+    /// `smartctl` exits non-zero for many drive-health conditions, so only the
+    /// output decides whether a counter was read
+    fn capture_smart(&self, device: &str, commands: &mut Vec<WipeCommand>) -> Option<SmartSnapshot> {
+        let output = self.execute_command("smartctl", &["-a", "-j", device], commands).ok()?;
+        SmartSnapshot::parse(&output.output)
     }
 
+    fn capture_identity(&self, device: &str, commands: &mut Vec<WipeCommand>) -> Option<IdentitySnapshot> {
+        if device.contains("nvme") {
+            if let Ok(id_ctrl) = self.execute_command("nvme", &["id-ctrl", device, "-o", "json"], commands) {
+                if id_ctrl.exit_code == 0 {
+                    let sanitize_log = self
+                        .execute_command("nvme", &["sanitize-log", device, "-o", "json"], commands)
+                        .ok()
+                        .filter(|log| log.exit_code == 0);
+                    if let Some(snapshot) = parse_nvme_identity(
+                        &id_ctrl.output,
+                        sanitize_log.as_ref().map(|log| log.output.as_str()),
+                    ) {
+                        return Some(snapshot);
+                    }
+                }
+            }
+        }
+
+        if let Ok(identify) = self.execute_command("hdparm", &["-I", device], commands) {
+            if identify.exit_code == 0 {
+                if let Some(snapshot) = parse_hdparm_identity(&identify.output) {
+                    return Some(snapshot);
+                }
+            }
+        }
+
+        // USB bridges often reject hdparm but pass SMART through SAT
+        match self.execute_command("smartctl", &["-i", "-j", device], commands) {
+            Ok(info) => parse_smartctl_identity(&info.output),
+            Err(_) => None,
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
     fn unmount_device(
         &self,
         device: &str,
         commands: &mut Vec<WipeCommand>,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Unmounting all partitions on {}", device);
+        events::info(&format!("Unmounting all partitions on {}", device));
         
         // Find all mounted partitions for this device
         let lsblk_result = self.execute_command("lsblk", &["-J", "-o", "NAME,MOUNTPOINT", device], commands)?;
@@ -155,7 +1038,7 @@ impl NistAlignedWipe {
                         // Check main device mountpoint
                         if let Some(mountpoint) = device_info.get("mountpoint").and_then(|v| v.as_str()) {
                             if !mountpoint.is_empty() {
-                                println!("Unmounting {}", mountpoint);
+                                events::debug(&format!("Unmounting {}", mountpoint));
                                 let _umount_result = self.execute_command("umount", &[mountpoint], commands)?;
                             }
                         }
@@ -165,7 +1048,7 @@ impl NistAlignedWipe {
                             for partition in children {
                                 if let Some(mountpoint) = partition.get("mountpoint").and_then(|v| v.as_str()) {
                                     if !mountpoint.is_empty() {
-                                        println!("Unmounting partition at {}", mountpoint);
+                                        events::debug(&format!("Unmounting partition at {}", mountpoint));
                                         let _umount_result = self.execute_command("umount", &[mountpoint], commands)?;
                                     }
                                 }
@@ -181,7 +1064,7 @@ impl NistAlignedWipe {
         for i in 1..=16 {
             let partition = format!("{}{}", device, i);
             if std::path::Path::new(&partition).exists() {
-                println!("Force unmounting partition {}", partition);
+                events::debug(&format!("Force unmounting partition {}", partition));
                 let _umount_result = self.execute_command("umount", &[&partition], commands)?;
             }
         }
@@ -198,6 +1081,13 @@ impl NistAlignedWipe {
             Ok(_) => Ok(()),
             Err(e) => {
                 if e.kind() == std::io::ErrorKind::PermissionDenied {
+                    // sudo can't get past a security module, so don't suggest it
+                    if let Some(denial) = crate::lsm::explain(Some(std::path::Path::new(device))) {
+                        return Err(Box::new(crate::remediation::ClassifiedError {
+                            failure: crate::remediation::IoFailure::from_lsm(denial.lsm),
+                            message: format!("Permission denied: {}", denial.describe(std::path::Path::new(device))),
+                        }));
+                    }
                     Err(format!(
                         "Permission denied: Cannot write to device {}. \
                          This application needs elevated privileges to perform disk wiping. \
@@ -211,24 +1101,37 @@ impl NistAlignedWipe {
         }
     }
 
+    #[tracing::instrument(name = "controller_sanitize", skip_all)]
     fn try_controller_sanitize(
         &self,
         device: &str,
         policy: &WipePolicy,
         commands: &mut Vec<WipeCommand>,
+        checks: &mut Vec<CapabilityCheck>,
     ) -> Result<bool, Box<dyn std::error::Error>> {
         // Skip controller sanitize for USB devices as they often don't support it
         if device.contains("sd") && !device.contains("nvme") {
-            println!("Skipping controller sanitize for USB device {}", device);
+            events::info(&format!("Skipping controller sanitize for USB device {}", device));
+            checks.push(
+                CapabilityCheck::new("device path", "controller_sanitize", false)
+                    .detail("sd* devices are treated as USB bridges, which rarely pass sanitize through"),
+            );
             return Ok(false);
         }
 
         // Try NVMe sanitize first
         if device.contains("nvme") {
             match self.execute_command("nvme", &["sanitize", device], commands) {
-                Ok(nvme_result) if nvme_result.exit_code == 0 => return Ok(true),
-                _ => {} // Continue to try other methods
-            }
+                Ok(nvme_result) if nvme_result.exit_code == 0 => {
+                    checks.push(CapabilityCheck::new("nvme sanitize", "sanitize", true));
+                    return Ok(true);
+                }
+                Ok(nvme_result) => checks.push(
+                    CapabilityCheck::new("nvme sanitize", "sanitize", false)
+                        .detail(format!("exit code {}", nvme_result.exit_code)),
+                ),
+                Err(e) => checks.push(CapabilityCheck::new("nvme sanitize", "sanitize", false).detail(e.to_string())),
+            } // Continue to try other methods
         }
 
         // Try SATA secure erase
@@ -240,171 +1143,260 @@ impl NistAlignedWipe {
         // Check if secure erase is supported (with timeout handling)
         match self.execute_command("hdparm", &["-I", device], commands) {
             Ok(identify_result) => {
-                if identify_result.output.contains("Security") && identify_result.output.contains("erase") {
+                let supported = identify_result.output.contains("Security") && identify_result.output.contains("erase");
+                checks.push(CapabilityCheck::new("hdparm -I", "security_erase", supported));
+                if supported {
                     // Set security password (required for secure erase)
                     match self.execute_command("hdparm", &["--user-master", "u", "--security-set-pass", "p", device], commands) {
                         Ok(_) => {}
                         Err(e) => {
-                            println!("Failed to set security password: {}", e);
+                            events::warn(&format!("Failed to set security password: {}", e));
+                            checks.push(
+                                CapabilityCheck::new("hdparm --security-set-pass", "security_erase", false)
+                                    .detail(e.to_string()),
+                            );
                             return Ok(false);
                         }
                     }
 
                     // Perform secure erase
-                    match self.execute_command("hdparm", &["--user-master", "u", &format!("--security-{}", method), "p", device], commands) {
-                        Ok(erase_result) if erase_result.exit_code == 0 => return Ok(true),
-                        _ => {}
+                    let erase_flag = format!("--security-{}", method);
+                    match self.execute_command("hdparm", &["--user-master", "u", &erase_flag, "p", device], commands) {
+                        Ok(erase_result) if erase_result.exit_code == 0 => {
+                            checks.push(CapabilityCheck::new(&format!("hdparm {}", erase_flag), method, true));
+                            return Ok(true);
+                        }
+                        Ok(erase_result) => checks.push(
+                            CapabilityCheck::new(&format!("hdparm {}", erase_flag), method, false)
+                                .detail(format!("exit code {}", erase_result.exit_code)),
+                        ),
+                        Err(e) => checks.push(
+                            CapabilityCheck::new(&format!("hdparm {}", erase_flag), method, false).detail(e.to_string()),
+                        ),
                     }
                 }
             }
             Err(e) => {
-                println!("hdparm identify failed (likely unsupported on this device): {}", e);
+                events::debug(&format!("hdparm identify failed (likely unsupported on this device): {}", e));
+                checks.push(CapabilityCheck::new("hdparm -I", "security_erase", false).detail(e.to_string()));
             }
         }
 
         Ok(false) // No controller sanitize available
     }
 
-    fn perform_clear_wipe(
+    /// Overwrite the whole device with `fill`. Unlike dd, a failed write doesn't end
+    /// the pass: the chunk is retried, then rewritten sector by sector, and sectors
+    /// that still refuse the write are skipped and returned as bad regions.
+    /// Returns the bytes written alongside the skipped regions. `hardened` runs the
+    /// pass on a confined thread once the device is open (see `hardening`).
+    #[tracing::instrument(name = "overwrite", skip_all)]
+    fn overwrite_device(
         &self,
         device: &str,
+        fill: &OverwriteFill,
+        hardened: bool,
         commands: &mut Vec<WipeCommand>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Performing CLEAR wipe (single zero pass)");
-        
-        // Single pass with zeros
-        let dd_result = self.execute_command(
-            "dd",
-            &[
-                "if=/dev/zero",
-                &format!("of={}", device),
-                "bs=1M",
-                "conv=fdatasync",
-                "status=progress"
-            ],
-            commands,
-        )?;
+    ) -> Result<(u64, Vec<BadRegion>), Box<dyn std::error::Error>> {
+        events::info(&format!("Overwriting {} with {}", device, fill.describe()));
+        let start_time = Instant::now();
 
-        if !self.dd_completed_ok(&dd_result) {
-            return Err(format!("Zero-fill failed: {} (exit {})", dd_result.output, dd_result.exit_code).into());
-        }
+        // O_DSYNC makes each write reach the media before returning, so a failing
+        // sector shows up on its own write rather than at a final flush
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_DSYNC)
+            .open(device)?;
+        let size = device_size(&mut file)?;
+        let display = crate::locale::DisplayFormat::current();
+        let mut last_report = Instant::now();
+        let mut progress = |written| {
+            if written < size && last_report.elapsed() < PROGRESS_INTERVAL {
+                return;
+            }
+            last_report = Instant::now();
+            events::emit_document(&serde_json::json!({
+                "step": "overwrite_progress",
+                "device": device,
+                "fill": fill.describe(),
+                "bytes_written": written,
+                "bytes_total": size,
+                "display": format!("{} of {}", display.bytes(written), display.bytes(size)),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            }));
+        };
+        let mut write_pass = || -> std::io::Result<Vec<BadRegion>> {
+            let bad_regions = overwrite_range(&mut file, size, fill, &mut progress)?;
+            file.sync_all()?;
+            Ok(bad_regions)
+        };
+        let bad_regions = if hardened { crate::hardening::confined(write_pass)?? } else { write_pass()? };
 
-        Ok(())
+        let bad_bytes: u64 = bad_regions.iter().map(|region| region.length).sum();
+        let bytes_written = size - bad_bytes;
+        let cmd_record = WipeCommand {
+            command: format!("overwrite of={} fill={}{}", device, fill.describe(), if hardened { " confined=seccomp" } else { "" }),
+            exit_code: 0,
+            elapsed_ms: start_time.elapsed().as_millis() as u64,
+            output: format!(
+                "{} bytes written, {} bad regions ({} bytes skipped)",
+                bytes_written, bad_regions.len(), bad_bytes
+            ),
+        };
+        events::debug(&format!("Executed: {} (exit: 0, time: {}ms)", cmd_record.command, cmd_record.elapsed_ms));
+        commands.push(cmd_record);
+
+        Ok((bytes_written, bad_regions))
     }
 
-    fn perform_purge_wipe(
+    /// Zero the MBR and both GPT copies, then re-read each area with its cached pages
+    /// dropped so the check sees what the device holds. One command is recorded per
+    /// area; an area that won't take the write or reads back non-zero is left
+    /// unverified rather than ending the wipe.
+    #[tracing::instrument(skip_all)]
+    fn destroy_partition_metadata(
         &self,
         device: &str,
         commands: &mut Vec<WipeCommand>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Performing PURGE wipe (HPA/DCO clear + random pass + verification)");
-        
-        // Step 1: Clear HPA/DCO if present
-        self.clear_hpa_dco(device, commands)?;
-        
-        // Step 2: Single pass with random data
-        let dd_result = self.execute_command(
-            "dd",
-            &[
-                "if=/dev/urandom",
-                &format!("of={}", device),
-                "bs=1M",
-                "conv=fdatasync",
-                "status=progress"
-            ],
-            commands,
-        )?;
-
-        if !self.dd_completed_ok(&dd_result) {
-            return Err(format!("Random overwrite failed: {} (exit {})", dd_result.output, dd_result.exit_code).into());
+    ) -> Result<Vec<MetadataRegion>, Box<dyn std::error::Error>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_DSYNC)
+            .open(device)?;
+        let size = device_size(&mut file)?;
+        let mut regions = metadata_regions(size);
+        for region in &mut regions {
+            let start_time = Instant::now();
+            let zeros = vec![0u8; region.length as usize];
+            let outcome = write_with_retries(&mut file, region.offset, &zeros)
+                .and_then(|_| file.sync_data())
+                .and_then(|_| read_back_zeroed(&mut file, region.offset, region.length));
+            let output = match &outcome {
+                Ok(true) => "zeroed, readback verified".to_string(),
+                Ok(false) => "readback not zero".to_string(),
+                Err(e) => e.to_string(),
+            };
+            region.verified = matches!(outcome, Ok(true));
+            let cmd_record = WipeCommand {
+                command: format!(
+                    "zero {} of={} offset={} length={}",
+                    region.name, device, region.offset, region.length
+                ),
+                exit_code: if region.verified { 0 } else { 1 },
+                elapsed_ms: start_time.elapsed().as_millis() as u64,
+                output,
+            };
+            events::debug(&format!("Executed: {} (exit: {}, time: {}ms)", cmd_record.command, cmd_record.exit_code, cmd_record.elapsed_ms));
+            commands.push(cmd_record);
         }
-
-        Ok(())
+        Ok(regions)
     }
 
+    /// Restore the DCO and remove the HPA, then read max sectors back to confirm the
+    /// native capacity is visible. Probe failures are recorded, not fatal: drives
+    /// without ATA max-sector support (NVMe, most USB bridges) just have no readout.
+    #[tracing::instrument(skip_all)]
     fn clear_hpa_dco(
         &self,
         device: &str,
         commands: &mut Vec<WipeCommand>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Clearing HPA/DCO settings");
-        
-        // Check and disable HPA
-        let hpa_check = self.execute_command("hdparm", &["-N", device], commands)?;
-        if hpa_check.output.contains("HPA") {
-            let _hpa_disable = self.execute_command("hdparm", &["-N", "p", device], commands)?;
+    ) -> HpaDcoCheck {
+        events::info("Clearing HPA/DCO settings");
+        let mut check = HpaDcoCheck {
+            before: self.read_max_sectors(device, commands),
+            ..HpaDcoCheck::default()
+        };
+
+        // DCO first: restoring it can raise the native max that the HPA is measured against
+        check.dco_real_max = self
+            .execute_command("hdparm", &["--dco-identify", device], commands)
+            .ok()
+            .filter(|identify| identify.exit_code == 0)
+            .and_then(|identify| parse_dco_real_max(&identify.output));
+        let mut current = check.before;
+        if let Some(real_max) = check.dco_real_max {
+            if current.is_none_or(|state| real_max > state.native) {
+                if let Ok(restore) = self.execute_command("hdparm", &["--yes-i-know-what-i-am-doing", "--dco-restore", device], commands) {
+                    check.commands.push(restore.command);
+                }
+                current = self.read_max_sectors(device, commands);
+            }
         }
 
-        // Check and disable DCO
-        let dco_check = self.execute_command("hdparm", &["--dco-identify", device], commands)?;
-        if dco_check.exit_code == 0 && dco_check.output.contains("DCO") {
-            let _dco_restore = self.execute_command("hdparm", &["--dco-restore", device], commands)?;
+        // Then the HPA: make the native max visible, permanently
+        if let Some(state) = current.filter(MaxSectors::hpa_active) {
+            let max = format!("-Np{}", state.native);
+            if let Ok(set_max) = self.execute_command("hdparm", &["--yes-i-know-what-i-am-doing", &max, device], commands) {
+                check.commands.push(set_max.command);
+            }
         }
 
-        Ok(())
+        check.after = self.read_max_sectors(device, commands);
+        check.passed = hpa_dco_cleared(check.after, check.dco_real_max);
+        check
     }
 
+    fn read_max_sectors(&self, device: &str, commands: &mut Vec<WipeCommand>) -> Option<MaxSectors> {
+        let output = self.execute_command("hdparm", &["-N", device], commands).ok()?;
+        if output.exit_code != 0 {
+            return None;
+        }
+        parse_hdparm_max_sectors(&output.output)
+    }
+
+    #[tracing::instrument(name = "verify", skip_all, fields(samples = sample_count))]
     fn verify_wipe(
         &self,
         device: &str,
         sample_count: usize,
-    ) -> Result<bool, Box<dyn std::error::Error>> {
-        println!("Verifying wipe with {} random samples", sample_count);
+        seed: u64,
+        bad_regions: &[BadRegion],
+    ) -> Result<(bool, VerificationRecord), Box<dyn std::error::Error>> {
+        events::info(&format!("Verifying wipe with {} random samples (seed {})", sample_count, seed));
         
         let mut file = OpenOptions::new().read(true).open(device)?;
-        
-        // Get device size
-        file.seek(SeekFrom::End(0))?;
-        let device_size = file.stream_position()?;
-        
-        if device_size == 0 {
-            return Err("Cannot determine device size".into());
-        }
+        let device_size = device_size(&mut file)?;
 
-        let mut rng = rand::thread_rng();
         let mut verified_count = 0;
+        let mut samples = Vec::with_capacity(sample_count);
         
-        for _ in 0..sample_count {
-            // Random sector to check
-            let offset = (rng.next_u64() % (device_size / 512)) * 512;
-            
-            // Read 512 bytes
-            file.seek(SeekFrom::Start(offset))?;
-            let mut buffer = [0u8; 512];
-            file.read_exact(&mut buffer)?;
+        // Sectors that were never written can't say anything about the overwrite
+        for offset in sample_offsets(seed, device_size, sample_count, bad_regions) {
+            let buffer = read_sample(&mut file, offset)?;
+            let (class, entropy, chi_square) = score_sample(&buffer);
             
-            // Check if sector appears to be wiped (mostly zeros or random-looking)
-            let zero_count = buffer.iter().filter(|&&b| b == 0).count();
-            let is_likely_wiped = zero_count > 400 || self.appears_random(&buffer);
-            
-            if is_likely_wiped {
+            if class.is_wiped() {
                 verified_count += 1;
             }
+            samples.push(VerificationSample {
+                offset,
+                sha256: format!("{:x}", Sha256::digest(buffer)),
+                passed: class.is_wiped(),
+                class,
+                entropy,
+                chi_square,
+            });
         }
         
-        // Consider verification passed if >95% of samples look wiped
+        // Consider verification passed if >95% of samples look wiped. Fewer samples
+        // than asked for only happens when bad regions crowd out the draws.
+        let sample_count = samples.len();
         let success_threshold = (sample_count * 95) / 100;
-        let passed = verified_count >= success_threshold;
+        let passed = sample_count > 0 && verified_count >= success_threshold;
         
-        println!("Verification: {}/{} samples passed ({}%)", 
+        events::info(&format!("Verification: {}/{} samples passed ({}%)", 
                 verified_count, sample_count, 
-                (verified_count * 100) / sample_count);
-        
-        Ok(passed)
-    }
-
-    fn appears_random(&self, data: &[u8]) -> bool {
-        // Simple randomness check: count bit transitions
-        let mut transitions = 0;
-        for i in 1..data.len() {
-            if data[i] != data[i-1] {
-                transitions += 1;
-            }
-        }
+                (verified_count * 100) / sample_count.max(1)));
         
-        // Random data should have many transitions
-        // More than 30% transitions suggests randomness
-        (transitions * 100) / data.len() > 30
+        Ok((passed, VerificationRecord {
+            seed,
+            sample_bytes: VERIFY_SAMPLE_BYTES,
+            device_size,
+            samples,
+            excluded_regions: bad_regions.to_vec(),
+        }))
     }
 
     fn execute_command(
@@ -455,14 +1447,307 @@ impl NistAlignedWipe {
             output: output_str,
         };
 
-        println!("Executed: {} (exit: {}, time: {}ms)",
-                cmd_record.command, cmd_record.exit_code, cmd_record.elapsed_ms);
+        events::debug(&format!("Executed: {} (exit: {}, time: {}ms)",
+                cmd_record.command, cmd_record.exit_code, cmd_record.elapsed_ms));
+        if !cmd_record.output.trim().is_empty() {
+            events::trace(&format!("{} output:\n{}", command, cmd_record.output.trim_end()));
+        }
 
         commands.push(cmd_record.clone());
         Ok(cmd_record)
     }
 }
 
+/// Sector-aligned sample offsets for `seed`. The same seed and device size always
+/// give the same offsets, which is what makes a verification replayable.
+/// Offsets inside `avoid` are redrawn, giving up after 16 draws per requested sample,
+/// so a mostly-bad device gets fewer samples.
+pub fn sample_offsets(seed: u64, device_size: u64, count: usize, avoid: &[BadRegion]) -> Vec<u64> {
+    let sectors = device_size / VERIFY_SAMPLE_BYTES;
+    if sectors == 0 {
+        return Vec::new();
+    }
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let mut offsets = Vec::with_capacity(count);
+    let mut draws = 0;
+    while offsets.len() < count && draws < count * 16 {
+        draws += 1;
+        let offset = (rng.next_u64() % sectors) * VERIFY_SAMPLE_BYTES;
+        if !avoid.iter().any(|region| region.contains(offset)) {
+            offsets.push(offset);
+        }
+    }
+    offsets
+}
+
+/// Write `fill` over the first `size` bytes of `target`, skipping what won't take a
+/// write after `WRITE_ATTEMPTS` tries. `progress` gets the bytes covered after each chunk.
+/// Returns the skipped regions, merged where adjacent.
+pub(crate) fn overwrite_range<W: Write + Seek>(
+    target: &mut W,
+    size: u64,
+    fill: &OverwriteFill,
+    progress: &mut dyn FnMut(u64),
+) -> std::io::Result<Vec<BadRegion>> {
+    let mut random = ChaCha20Rng::from_entropy();
+    let mut buffer = vec![0u8; OVERWRITE_CHUNK_BYTES];
+    let mut bad_regions: Vec<BadRegion> = Vec::new();
+    let sector = VERIFY_SAMPLE_BYTES as usize;
+    let mut position: u64 = 0;
+    while position < size {
+        // Hold here while the machine is off mains power; an unplugged drive ends the wait
+        crate::power::wait_for_power(&crate::removal::device_removed);
+        let len = (size - position).min(OVERWRITE_CHUNK_BYTES as u64) as usize;
+        fill.fill(&mut random, position, &mut buffer[..len]);
+        if let Err(e) = write_with_retries(target, position, &buffer[..len]) {
+            if crate::removal::is_removal(&e) {
+                return Err(e);
+            }
+            // Narrow the failure down to the sectors that actually refuse the write
+            for (index, data) in buffer[..len].chunks(sector).enumerate() {
+                let offset = position + (index * sector) as u64;
+                if let Err(e) = write_with_retries(target, offset, data) {
+                    if crate::removal::is_removal(&e) {
+                        return Err(e);
+                    }
+                    match bad_regions.last_mut() {
+                        Some(last) if last.offset + last.length == offset => {
+                            last.length += data.len() as u64;
+                            last.error = e.to_string();
+                        }
+                        _ => bad_regions.push(BadRegion { offset, length: data.len() as u64, error: e.to_string() }),
+                    }
+                }
+            }
+        }
+        position += len as u64;
+        progress(position);
+    }
+    Ok(bad_regions)
+}
+
+/// Sort `regions` and merge any that overlap or touch
+fn merge_bad_regions(mut regions: Vec<BadRegion>) -> Vec<BadRegion> {
+    regions.sort_by_key(|region| region.offset);
+    let mut merged: Vec<BadRegion> = Vec::with_capacity(regions.len());
+    for region in regions {
+        match merged.last_mut() {
+            Some(last) if region.offset <= last.offset + last.length => {
+                let end = (last.offset + last.length).max(region.offset + region.length);
+                last.length = end - last.offset;
+                last.error = region.error;
+            }
+            _ => merged.push(region),
+        }
+    }
+    merged
+}
+
+fn write_with_retries<W: Write + Seek>(target: &mut W, offset: u64, data: &[u8]) -> std::io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        let result = target.seek(SeekFrom::Start(offset))
+            .and_then(|_| target.write_all(data))
+            .and_then(|_| target.flush());
+        match result {
+            Ok(()) => return Ok(()),
+            // Retrying a device that has been unplugged only delays the inevitable
+            Err(e) if attempt >= WRITE_ATTEMPTS || crate::removal::is_removal(&e) => return Err(e),
+            Err(_) => attempt += 1,
+        }
+    }
+}
+
+/// Re-read `length` bytes at `offset` and report whether they're all zero. The range
+/// is dropped from the page cache first, otherwise the read returns our own write.
+fn read_back_zeroed(file: &mut std::fs::File, offset: u64, length: u64) -> std::io::Result<bool> {
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), offset as libc::off_t, length as libc::off_t, libc::POSIX_FADV_DONTNEED);
+    }
+    let mut buffer = vec![0u8; length as usize];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer.iter().all(|&byte| byte == 0))
+}
+
+/// Zero the bytes of `buffer`, which holds the device from `offset`, that fall in `regions`
+fn zero_metadata_overlap(regions: &[MetadataRegion], offset: u64, buffer: &mut [u8]) {
+    let end = offset + buffer.len() as u64;
+    for region in regions {
+        let from = region.offset.max(offset);
+        let to = (region.offset + region.length).min(end);
+        if from < to {
+            buffer[(from - offset) as usize..(to - offset) as usize].fill(0);
+        }
+    }
+}
+
+fn device_size(file: &mut std::fs::File) -> Result<u64, Box<dyn std::error::Error>> {
+    let size = file.seek(SeekFrom::End(0))?;
+    if size == 0 {
+        return Err("Cannot determine device size".into());
+    }
+    Ok(size)
+}
+
+fn read_sample(file: &mut std::fs::File, offset: u64) -> Result<[u8; VERIFY_SAMPLE_BYTES as usize], Box<dyn std::error::Error>> {
+    let mut buffer = [0u8; VERIFY_SAMPLE_BYTES as usize];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Keystream positioned at byte `offset` of the overwrite pattern for `seed`.
+/// `offset` must be a multiple of 4 (sector offsets always are).
+fn pattern_rng(seed: u64, offset: u64) -> ChaCha20Rng {
+    let mut hasher = Sha256::new();
+    hasher.update(PATTERN_KEY_CONTEXT);
+    hasher.update(seed.to_le_bytes());
+    let mut rng = ChaCha20Rng::from_seed(hasher.finalize().into());
+    rng.set_word_pos(u128::from(offset / 4));
+    rng
+}
+
+/// The pattern bytes the overwrite engine wrote at `offset` for `seed`
+pub fn expected_pattern(seed: u64, offset: u64, buffer: &mut [u8]) {
+    pattern_rng(seed, offset).fill_bytes(buffer);
+}
+
+/// Compare `device` against the keyed pattern for `seed`: only the sectors at
+/// `offsets`, or every sector when `offsets` is None. Sectors inside `bad_regions`
+/// were never written and are skipped; sectors inside `zeroed` were cleared by the
+/// metadata step and must read back as zeros. Any other sector that can't be read
+/// counts as a mismatch. Read-only.
+pub fn verify_pattern(
+    device: &str,
+    seed: u64,
+    offsets: Option<&[u64]>,
+    bad_regions: &[BadRegion],
+    zeroed: &[MetadataRegion],
+) -> Result<PatternVerification, Box<dyn std::error::Error>> {
+    let mut file = OpenOptions::new().read(true).open(device)?;
+    let size = device_size(&mut file)?;
+
+    let mut sectors_checked: u64 = 0;
+    let mut mismatched: Vec<u64> = Vec::new();
+    let mut sectors_mismatched: u64 = 0;
+    let mut record_mismatch = |offset: u64| {
+        sectors_mismatched += 1;
+        if mismatched.len() < PATTERN_MISMATCH_LIMIT {
+            mismatched.push(offset);
+        }
+    };
+
+    let sector = VERIFY_SAMPLE_BYTES as usize;
+    match offsets {
+        Some(offsets) => {
+            let mut expected = [0u8; VERIFY_SAMPLE_BYTES as usize];
+            for &offset in offsets.iter().filter(|&&offset| !bad_regions.iter().any(|region| region.contains(offset))) {
+                expected_pattern(seed, offset, &mut expected);
+                zero_metadata_overlap(zeroed, offset, &mut expected);
+                sectors_checked += 1;
+                if read_sample(&mut file, offset).ok() != Some(expected) {
+                    record_mismatch(offset);
+                }
+            }
+        }
+        None => {
+            let mut rng = pattern_rng(seed, 0);
+            let mut actual = vec![0u8; OVERWRITE_CHUNK_BYTES];
+            let mut expected = vec![0u8; OVERWRITE_CHUNK_BYTES];
+            let mut position: u64 = 0;
+            while position < size {
+                let len = (size - position).min(OVERWRITE_CHUNK_BYTES as u64) as usize;
+                rng.fill_bytes(&mut expected[..len]);
+                zero_metadata_overlap(zeroed, position, &mut expected[..len]);
+                // A chunk that won't read in one go is re-read sector by sector, and
+                // only the sectors that still fail are lost
+                let chunk_read = file.seek(SeekFrom::Start(position))
+                    .and_then(|_| file.read_exact(&mut actual[..len]))
+                    .is_ok();
+                for (index, want) in expected[..len].chunks(sector).enumerate() {
+                    let offset = position + (index * sector) as u64;
+                    if bad_regions.iter().any(|region| region.contains(offset)) {
+                        continue;
+                    }
+                    sectors_checked += 1;
+                    let matches = if chunk_read {
+                        &actual[index * sector..index * sector + want.len()] == want
+                    } else {
+                        let got = &mut actual[..want.len()];
+                        file.seek(SeekFrom::Start(offset)).is_ok() && file.read_exact(got).is_ok() && *got == *want
+                    };
+                    if !matches {
+                        record_mismatch(offset);
+                    }
+                }
+                position += len as u64;
+            }
+        }
+    }
+
+    Ok(PatternVerification {
+        seed,
+        algorithm: "chacha20".to_string(),
+        full: offsets.is_none(),
+        sectors_checked,
+        sectors_mismatched,
+        passed: sectors_checked > 0 && sectors_mismatched == 0,
+        mismatched_offsets: mismatched,
+    })
+}
+
+/// Re-read the sectors listed in `record` from `device` and check they still hash to
+/// the recorded digests. Read-only; never writes to the device.
+pub fn replay_verification(device: &str, record: &VerificationRecord) -> Result<ReplayReport, Box<dyn std::error::Error>> {
+    if record.sample_bytes != VERIFY_SAMPLE_BYTES {
+        return Err(format!(
+            "Unsupported verification sample size {} (expected {})",
+            record.sample_bytes, VERIFY_SAMPLE_BYTES
+        ).into());
+    }
+
+    let mut file = OpenOptions::new().read(true).open(device)?;
+    let current_size = device_size(&mut file)?;
+
+    // Offsets that don't follow from the seed mean the record was edited or produced
+    // by something other than this tool
+    let recorded_offsets: Vec<u64> = record.samples.iter().map(|sample| sample.offset).collect();
+    let offsets_match_seed = sample_offsets(record.seed, record.device_size, record.samples.len(), &record.excluded_regions)
+        == recorded_offsets;
+
+    let mut mismatched_offsets = Vec::new();
+    for sample in &record.samples {
+        let matches = sample.offset + VERIFY_SAMPLE_BYTES <= current_size
+            && format!("{:x}", Sha256::digest(read_sample(&mut file, sample.offset)?)) == sample.sha256;
+        if !matches {
+            mismatched_offsets.push(sample.offset);
+        }
+    }
+
+    let device_size_matches = current_size == record.device_size;
+    let samples_checked = record.samples.len();
+    Ok(ReplayReport {
+        device: device.to_string(),
+        seed: record.seed,
+        device_size_matches,
+        offsets_match_seed,
+        samples_checked,
+        samples_matched: samples_checked - mismatched_offsets.len(),
+        passed: device_size_matches && offsets_match_seed && samples_checked > 0 && mismatched_offsets.is_empty(),
+        mismatched_offsets,
+    })
+}
+
+/// Block `plan` for `reason`, keeping any reason it was already blocked for
+pub fn block_plan(plan: &mut WipePlan, reason: String) {
+    plan.blocked = true;
+    plan.reason = Some(match plan.reason.take() {
+        Some(existing) => format!("{}; {}", existing, reason),
+        None => reason,
+    });
+}
+
 /// Plan a wipe operation without performing destructive actions
 pub fn plan_wipe(
     device: &str,
@@ -485,45 +1770,66 @@ pub fn plan_wipe(
         reason = Some("CRITICAL disk wipe blocked unless running from bootable ISO mode".to_string());
     }
 
-    // Probe controller capabilities (non-fatal)
+    // Probe controller capabilities (non-fatal), reusing what discovery just probed
     let hdparm_output = if let Some(mock) = mock_hdparm {
         Some(mock.to_string())
     } else {
-        Command::new("hdparm")
-            .arg("-I")
-            .arg(device)
-            .output()
-            .ok()
-            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        crate::probe_cache::run(device, None, "hdparm", &["-I", device]).map(|output| output.stdout)
     };
 
     let nvme_output = if let Some(mock) = mock_nvme {
         Some(mock.to_string())
     } else {
-        Command::new("nvme")
-            .arg("id-ctrl")
-            .arg(device)
-            .output()
-            .ok()
-            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        crate::probe_cache::run(device, None, "nvme", &["id-ctrl", device, "-o", "json"]).map(|output| output.stdout)
     };
 
     // Determine method based on controller capabilities
-    if let Some(ref hdparm) = hdparm_output {
-        if hdparm.contains("sanitize") || hdparm.contains("Security") {
-            main_method = "controller_sanitize".to_string();
+    let mut sanitize = MethodCandidate::new("controller_sanitize");
+    match hdparm_output {
+        Some(ref hdparm) => {
+            let supported = hdparm.contains("sanitize") || hdparm.contains("Security");
+            sanitize = sanitize.check(CapabilityCheck::new("hdparm -I", "sanitize_or_security_erase", supported));
+            if supported {
+                main_method = "controller_sanitize".to_string();
+            }
+            if hdparm.contains("HPA") || hdparm.contains("DCO") {
+                hpa_dco_clear = true;
+            }
         }
-        if hdparm.contains("HPA") || hdparm.contains("DCO") {
-            hpa_dco_clear = true;
+        None => {
+            sanitize = sanitize.check(
+                CapabilityCheck::new("hdparm -I", "sanitize_or_security_erase", false).detail("hdparm could not be run"),
+            );
         }
     }
 
-    if let Some(ref nvme) = nvme_output {
-        if nvme.contains("sanitize") {
-            main_method = "controller_sanitize".to_string();
+    match nvme_output {
+        Some(ref nvme) => {
+            // SANICAP from the JSON; text scraping only for mocks and ancient nvme-cli
+            let supported = match NvmeIdCtrl::parse(nvme) {
+                Some(ctrl) => ctrl.sanitize_supported(),
+                None => nvme.contains("sanitize"),
+            };
+            sanitize = sanitize.check(CapabilityCheck::new("nvme id-ctrl", "sanitize", supported));
+            if supported {
+                main_method = "controller_sanitize".to_string();
+            }
+        }
+        None => {
+            sanitize = sanitize.check(
+                CapabilityCheck::new("nvme id-ctrl", "sanitize", false).detail("nvme could not be run"),
+            );
         }
     }
 
+    let overwrite = MethodCandidate::new("overwrite");
+    let candidates = if main_method == "controller_sanitize" {
+        vec![sanitize, overwrite.rejected("Controller sanitize is supported and preferred")]
+    } else {
+        vec![sanitize.rejected("Neither hdparm nor nvme reports sanitize support"), overwrite]
+    };
+    let method_decision = MethodDecision::choose(candidates, &main_method);
+
     let verification = VerificationPlan {
         strategy: "random_sectors".to_string(),
         samples: 128,
@@ -538,6 +1844,9 @@ pub fn plan_wipe(
         verification,
         blocked,
         reason,
+        method_decision,
+        overwrite_profile: None,
+        device_state: None,
     }
 }
 
@@ -621,6 +1930,19 @@ mod tests {
             verification_samples: 5,
             verification_passed: true,
             fallback_reason: Some("Controller sanitize not supported".to_string()),
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
         };
         
         let json = serde_json::to_string(&result);
@@ -693,6 +2015,11 @@ mod tests {
         );
         assert_eq!(plan.main_method, "controller_sanitize");
         assert_eq!(plan.policy, WipePolicy::Purge);
+        assert_eq!(plan.method_decision.chosen, "controller_sanitize");
+        let sanitize = &plan.method_decision.candidates[0];
+        assert!(sanitize.selected);
+        assert!(sanitize.checks.iter().any(|c| c.source == "nvme id-ctrl" && c.supported));
+        assert!(plan.method_decision.candidates[1].rejection_reason.is_some());
     }
 
     #[test]
@@ -707,6 +2034,448 @@ mod tests {
         );
         assert_eq!(plan.main_method, "overwrite");
         assert!(!plan.hpa_dco_clear);
+
+        // The trace says why sanitize was passed over
+        let sanitize = &plan.method_decision.candidates[0];
+        assert_eq!(sanitize.method, "controller_sanitize");
+        assert!(!sanitize.selected);
+        assert!(sanitize.checks.iter().all(|c| !c.supported));
+        assert_eq!(sanitize.rejection_reason.as_deref(), Some("Neither hdparm nor nvme reports sanitize support"));
+        assert!(plan.method_decision.candidates[1].selected);
+    }
+
+    #[test]
+    fn test_ata_security_enabled_parsing() {
+        let enabled = "Security: \n\tMaster password revision code = 65534\n\t\tsupported\n\t\tenabled\n\tnot\tlocked\n";
+        let disabled = "Security: \n\tMaster password revision code = 65534\n\t\tsupported\n\tnot\tenabled\n\tnot\tlocked\n";
+        
+        assert!(ata_security_enabled(enabled));
+        assert!(!ata_security_enabled(disabled));
+        assert!(!ata_security_enabled("Commands/features:\n\t   *\tSMART feature set\n"));
+    }
+    
+    #[test]
+    fn test_parse_nvme_identity() {
+        let id_ctrl = r#"{"vid":5197,"sn":"S4EWNX0R123456  ","mn":"Samsung SSD 980 PRO 1TB","fr":"5B2QGXA7","tnvmcap":1000204886016}"#;
+        let before_log = r#"{"nvme0n1":{"sprog":65535,"sstat":0,"scdw10":0}}"#;
+        let after_log = r#"{"sprog":65535,"sstat":257,"scdw10":2}"#;
+
+        let before = parse_nvme_identity(id_ctrl, Some(before_log)).unwrap();
+        assert_eq!(before.serial.as_deref(), Some("S4EWNX0R123456"));
+        assert_eq!(before.firmware.as_deref(), Some("5B2QGXA7"));
+        assert_eq!(before.capacity_bytes, Some(1000204886016));
+        assert_eq!(before.sanitize_status, Some(0));
+
+        let after = parse_nvme_identity(id_ctrl, Some(after_log)).unwrap();
+        assert_eq!(after.sanitize_status, Some(257));
+        assert!(after.same_device_as(&before));
+        assert_eq!(after.sanitize_recorded_since(&before), Some(true));
+        assert_eq!(before.sanitize_recorded_since(&before), Some(false));
+
+        let no_log = parse_nvme_identity(id_ctrl, None).unwrap();
+        assert_eq!(no_log.sanitize_recorded_since(&before), None);
+        assert!(parse_nvme_identity(r#"{"sn":"  "}"#, None).is_none());
+    }
+
+    #[test]
+    fn test_parse_hdparm_identity() {
+        let identify = "\nATA device, with non-removable media\n\tModel Number:       WDC WD10EZEX-08WN4A0\n\tSerial Number:      WD-WCC6Y0ABCDEF\n\tFirmware Revision:  01.01A01\nStandards:\nConfiguration:\n\tLBA    user addressable sectors:   268435455\n\tLBA48  user addressable sectors:  1953525168\n\tLogical  Sector size:                   512 bytes\n\tPhysical Sector size:                  4096 bytes\n";
+        let snapshot = parse_hdparm_identity(identify).unwrap();
+        assert_eq!(snapshot.model.as_deref(), Some("WDC WD10EZEX-08WN4A0"));
+        assert_eq!(snapshot.serial.as_deref(), Some("WD-WCC6Y0ABCDEF"));
+        assert_eq!(snapshot.firmware.as_deref(), Some("01.01A01"));
+        assert_eq!(snapshot.capacity_bytes, Some(1953525168 * 512));
+
+        let mut other = snapshot.clone();
+        other.serial = Some("WD-WCC6Y0OTHER".to_string());
+        assert!(!other.same_device_as(&snapshot));
+        assert!(parse_hdparm_identity("Model Number: X\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_smartctl_identity() {
+        let info = r#"{"model_name":"SanDisk Ultra","serial_number":"4C530001","firmware_version":"1.00","user_capacity":{"blocks":60062500,"bytes":30752000000}}"#;
+        let snapshot = parse_smartctl_identity(info).unwrap();
+        assert_eq!(snapshot.source, "smartctl_info");
+        assert_eq!(snapshot.capacity_bytes, Some(30752000000));
+        assert!(parse_smartctl_identity(r#"{"smartctl":{"exit_status":2}}"#).is_none());
+
+        // Absent fields are left out of the serialized snapshot entirely
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert!(json.get("sanitize_status").is_none());
+    }
+
+    #[test]
+    fn test_parse_hpa_dco_readouts() {
+        let enabled = "\n/dev/sda:\n max sectors   = 976771055/976773168, HPA is enabled\n";
+        let disabled = "\n/dev/sda:\n max sectors   = 976773168/976773168, HPA is disabled\n";
+        let before = parse_hdparm_max_sectors(enabled).unwrap();
+        assert_eq!(before, MaxSectors { current: 976771055, native: 976773168 });
+        assert!(before.hpa_active());
+        let after = parse_hdparm_max_sectors(disabled).unwrap();
+        assert!(!after.hpa_active());
+        assert!(parse_hdparm_max_sectors("SG_IO: bad/missing sense data").is_none());
+
+        let dco = "/dev/sda:\nDCO Revision: 0x0002\nThe following features can be selectively disabled via DCO:\n\tTransfer modes:\n\tReal max sectors: 976773168\n";
+        assert_eq!(parse_dco_real_max(dco), Some(976773168));
+        assert_eq!(parse_dco_real_max("DCO not supported"), None);
+
+        assert_eq!(hpa_dco_cleared(Some(after), Some(976773168)), Some(true));
+        assert_eq!(hpa_dco_cleared(Some(before), None), Some(false));
+        // The HPA is gone but the DCO still hides the tail of the drive
+        assert_eq!(hpa_dco_cleared(Some(after), Some(1000215216)), Some(false));
+        assert_eq!(hpa_dco_cleared(None, Some(976773168)), None);
+    }
+
+    #[test]
+    fn test_sample_offsets_reproducible() {
+        let size = 64 * 1024 * 1024;
+        let offsets = sample_offsets(42, size, 128, &[]);
+        assert_eq!(offsets.len(), 128);
+        assert_eq!(offsets, sample_offsets(42, size, 128, &[]));
+        assert_ne!(offsets, sample_offsets(43, size, 128, &[]));
+        assert!(offsets.iter().all(|&o| o % VERIFY_SAMPLE_BYTES == 0 && o + VERIFY_SAMPLE_BYTES <= size));
+        assert!(sample_offsets(42, 100, 8, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_replay_verification() {
+        let path = std::env::temp_dir().join(format!("securewipe_replay_{}.img", std::process::id()));
+        std::fs::write(&path, vec![0u8; 256 * 1024]).unwrap();
+        let device = path.to_str().unwrap();
+
+        let (passed, record) = NistAlignedWipe.verify_wipe(device, 16, 7, &[]).unwrap();
+        assert!(passed);
+        assert_eq!(record.seed, 7);
+        assert_eq!(record.samples.len(), 16);
+
+        let report = replay_verification(device, &record).unwrap();
+        assert!(report.passed);
+        assert_eq!(report.samples_matched, 16);
+
+        // Data written to a sampled sector after the wipe shows up on replay
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(record.samples[3].offset)).unwrap();
+        file.write_all(b"restored data").unwrap();
+        drop(file);
+        let report = replay_verification(device, &record).unwrap();
+        assert!(!report.passed);
+        assert!(report.mismatched_offsets.contains(&record.samples[3].offset));
+
+        // Offsets that the seed would not produce are flagged
+        let mut forged = record.clone();
+        forged.seed = 8;
+        assert!(!replay_verification(device, &forged).unwrap().offsets_match_seed);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_pattern_overwrite_readback() {
+        let path = std::env::temp_dir().join(format!("securewipe_pattern_{}.img", std::process::id()));
+        // Not a multiple of the chunk size, so the last partial chunk is exercised
+        std::fs::write(&path, vec![0u8; OVERWRITE_CHUNK_BYTES + 64 * 1024]).unwrap();
+        let device = path.to_str().unwrap();
+
+        let mut commands = Vec::new();
+        let (bytes_written, bad_regions) = NistAlignedWipe
+            .overwrite_device(device, &OverwriteFill::Pattern { seed: 42 }, true, &mut commands)
+            .unwrap();
+        assert_eq!(bytes_written, OVERWRITE_CHUNK_BYTES as u64 + 64 * 1024);
+        assert!(bad_regions.is_empty());
+        assert!(commands[0].command.ends_with("fill=pattern seed=42 confined=seccomp"));
+
+        // Random-access keystream matches what the sequential writer produced
+        let mut expected = [0u8; VERIFY_SAMPLE_BYTES as usize];
+        expected_pattern(42, OVERWRITE_CHUNK_BYTES as u64 + 512, &mut expected);
+        let mut file = OpenOptions::new().read(true).open(&path).unwrap();
+        assert_eq!(read_sample(&mut file, OVERWRITE_CHUNK_BYTES as u64 + 512).unwrap(), expected);
+
+        let full = verify_pattern(device, 42, None, &[], &[]).unwrap();
+        assert!(full.passed);
+        assert!(full.full);
+        assert_eq!(full.sectors_checked, (OVERWRITE_CHUNK_BYTES as u64 + 64 * 1024) / VERIFY_SAMPLE_BYTES);
+
+        let offsets = sample_offsets(42, OVERWRITE_CHUNK_BYTES as u64 + 64 * 1024, 16, &[]);
+        assert!(verify_pattern(device, 42, Some(&offsets), &[], &[]).unwrap().passed);
+
+        // The wrong seed's pattern doesn't match anything
+        assert_eq!(verify_pattern(device, 43, Some(&offsets), &[], &[]).unwrap().sectors_mismatched, 16);
+
+        // A single rewritten sector is caught by the full readback
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.seek(SeekFrom::Start(4096)).unwrap();
+        file.write_all(b"restored data").unwrap();
+        drop(file);
+        let full = verify_pattern(device, 42, None, &[], &[]).unwrap();
+        assert!(!full.passed);
+        assert_eq!(full.sectors_mismatched, 1);
+        assert_eq!(full.mismatched_offsets, vec![4096]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_regions_layout() {
+        let mib = METADATA_SPAN_BYTES;
+        let regions = metadata_regions(8 * mib + 100);
+        let spans: Vec<(&str, u64, u64)> = regions.iter().map(|r| (r.name.as_str(), r.offset, r.length)).collect();
+        assert_eq!(spans, vec![("mbr", 0, 512), ("primary_gpt", 512, mib - 512), ("backup_gpt", 7 * mib, mib)]);
+
+        // The backup area never reaches back into the primary one
+        let regions = metadata_regions(mib + 4096);
+        assert_eq!(regions[2].offset, mib);
+        assert_eq!(regions[2].length, 4096);
+        assert_eq!(metadata_regions(mib).len(), 2);
+        assert_eq!(metadata_regions(512).len(), 1);
+        assert!(metadata_regions(100).is_empty());
+    }
+
+    #[test]
+    fn test_destroy_partition_metadata() {
+        let path = std::env::temp_dir().join(format!("securewipe_metadata_{}.img", std::process::id()));
+        let size = 3 * METADATA_SPAN_BYTES;
+        let device = path.to_str().unwrap();
+        std::fs::write(&path, vec![0u8; size as usize]).unwrap();
+
+        let mut commands = Vec::new();
+        NistAlignedWipe.overwrite_device(device, &OverwriteFill::Pattern { seed: 7 }, false, &mut commands).unwrap();
+        // A stale protective MBR and GPT headers at both ends, as an interrupted wipe leaves them
+        let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+        for (offset, data) in [(510, &b"\x55\xaa"[..]), (512, b"EFI PART"), (size - 512, b"EFI PART")] {
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            file.write_all(data).unwrap();
+        }
+        drop(file);
+
+        let mut commands = Vec::new();
+        let regions = NistAlignedWipe.destroy_partition_metadata(device, &mut commands).unwrap();
+        assert_eq!(regions.len(), 3);
+        assert!(regions.iter().all(|region| region.verified));
+        assert_eq!(commands.len(), 3);
+        assert!(commands.iter().all(|command| command.exit_code == 0));
+        assert!(commands[2].command.starts_with("zero backup_gpt of="));
+
+        let mut file = OpenOptions::new().read(true).open(&path).unwrap();
+        assert_eq!(read_sample(&mut file, 0).unwrap(), [0u8; 512]);
+        assert_eq!(read_sample(&mut file, size - 512).unwrap(), [0u8; 512]);
+
+        // The pattern survives between the two areas, and readback expects zeros inside them
+        let full = verify_pattern(device, 7, None, &[], &regions).unwrap();
+        assert!(full.passed);
+        let full = verify_pattern(device, 7, None, &[], &[]).unwrap();
+        assert_eq!(full.sectors_mismatched, 2 * METADATA_SPAN_BYTES / VERIFY_SAMPLE_BYTES);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Refuses writes that touch any of its bad sectors, like a drive with pending
+    /// reallocations
+    struct FlakyDisk {
+        data: std::io::Cursor<Vec<u8>>,
+        bad_sectors: Vec<u64>,
+    }
+
+    impl Write for FlakyDisk {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let start = self.data.position() / VERIFY_SAMPLE_BYTES;
+            let end = (self.data.position() + buf.len() as u64).div_ceil(VERIFY_SAMPLE_BYTES);
+            if self.bad_sectors.iter().any(|sector| (start..end).contains(sector)) {
+                return Err(std::io::Error::other("Input/output error"));
+            }
+            self.data.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Seek for FlakyDisk {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.data.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_overwrite_skips_bad_sectors() {
+        let size = OVERWRITE_CHUNK_BYTES as u64 * 2;
+        let mut disk = FlakyDisk {
+            data: std::io::Cursor::new(vec![0xAA; size as usize]),
+            // Two adjacent sectors in the first chunk, one in the second
+            bad_sectors: vec![8, 9, 2048 + 100],
+        };
+
+        let bad_regions = overwrite_range(&mut disk, size, &OverwriteFill::Zeros, &mut |_| {}).unwrap();
+        assert_eq!(bad_regions.len(), 2);
+        assert_eq!((bad_regions[0].offset, bad_regions[0].length), (8 * 512, 1024));
+        assert_eq!((bad_regions[1].offset, bad_regions[1].length), ((2048 + 100) * 512, 512));
+        assert!(bad_regions[0].error.contains("Input/output error"));
+
+        // Everything outside the bad sectors was still written
+        let data = disk.data.into_inner();
+        assert!(data[..8 * 512].iter().all(|&b| b == 0));
+        assert!(data[8 * 512..10 * 512].iter().all(|&b| b == 0xAA));
+        assert!(data[10 * 512..].iter().enumerate().all(|(i, &b)| {
+            let offset = (10 * 512 + i) as u64;
+            b == if bad_regions[1].contains(offset) { 0xAA } else { 0 }
+        }));
+    }
+
+    #[test]
+    fn test_sample_offsets_avoid_bad_regions() {
+        let size = 1024 * 1024;
+        // A region no draw lands in leaves the offsets unchanged
+        let past_end = BadRegion { offset: size, length: 512, error: "Input/output error".to_string() };
+        assert_eq!(sample_offsets(42, size, 64, &[past_end]), sample_offsets(42, size, 64, &[]));
+
+        let bad = BadRegion { offset: 0, length: size / 2, error: "Input/output error".to_string() };
+        assert!(bad.contains(0) && bad.contains(size / 2 - 1) && !bad.contains(size / 2));
+        let offsets = sample_offsets(42, size, 64, std::slice::from_ref(&bad));
+        assert_eq!(offsets.len(), 64);
+        assert!(offsets.iter().all(|&offset| !bad.contains(offset)));
+        assert_eq!(offsets, sample_offsets(42, size, 64, std::slice::from_ref(&bad)));
+
+        // Readback skips the region that was never written
+        let path = std::env::temp_dir().join(format!("securewipe_badregion_{}.img", std::process::id()));
+        std::fs::write(&path, vec![0u8; size as usize]).unwrap();
+        let device = path.to_str().unwrap();
+        let (passed, record) = NistAlignedWipe.verify_wipe(device, 32, 9, std::slice::from_ref(&bad)).unwrap();
+        assert!(passed);
+        assert_eq!(record.excluded_regions, vec![bad.clone()]);
+        assert!(replay_verification(device, &record).unwrap().offsets_match_seed);
+        let full = verify_pattern(device, 42, None, std::slice::from_ref(&bad), &[]).unwrap();
+        assert_eq!(full.sectors_checked, size / 2 / VERIFY_SAMPLE_BYTES);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_merge_bad_regions_across_passes() {
+        let region = |offset, length| BadRegion { offset, length, error: "Input/output error".to_string() };
+        let merged = merge_bad_regions(vec![region(8192, 512), region(4096, 1024), region(4096, 512), region(5120, 512)]);
+        assert_eq!(merged, vec![region(4096, 1536), region(8192, 512)]);
+        assert!(merge_bad_regions(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_overwrite_profiles() {
+        assert_eq!(OverwriteProfile::parse("DoD-5220.22-M-3pass"), Some(OverwriteProfile::Dod522022M3Pass));
+        assert_eq!(OverwriteProfile::parse("gutmann_lite"), Some(OverwriteProfile::GutmannLite));
+        assert_eq!(OverwriteProfile::parse("gutmann"), None);
+        for profile in [OverwriteProfile::NistClear, OverwriteProfile::Dod522022M3Pass, OverwriteProfile::GutmannLite] {
+            assert_eq!(OverwriteProfile::parse(profile.as_str()), Some(profile));
+            assert_eq!(serde_json::to_value(profile).unwrap(), profile.as_str());
+        }
+
+        assert_eq!(OverwriteProfile::NistClear.passes(&WipePolicy::Clear, None), vec![OverwriteFill::Zeros]);
+        assert_eq!(OverwriteProfile::NistClear.passes(&WipePolicy::Purge, None), vec![OverwriteFill::Random]);
+        assert_eq!(
+            OverwriteProfile::Dod522022M3Pass.passes(&WipePolicy::Clear, None),
+            vec![OverwriteFill::Zeros, OverwriteFill::repeat(&[0xFF]), OverwriteFill::Random]
+        );
+        // Pattern verification takes over the final pass
+        let gutmann = OverwriteProfile::GutmannLite.passes(&WipePolicy::Purge, Some(7));
+        assert_eq!(gutmann.len(), 7);
+        assert_eq!(gutmann[6], OverwriteFill::Pattern { seed: 7 });
+
+        // Repeats stay in phase across chunk boundaries
+        let fill = OverwriteFill::repeat(&[0x92, 0x49, 0x24]);
+        let mut random = ChaCha20Rng::seed_from_u64(0);
+        let mut buffer = [0u8; 4];
+        fill.fill(&mut random, 1024 * 1024, &mut buffer);
+        assert_eq!(buffer, [0x49, 0x24, 0x92, 0x49]);
+
+        let pass = OverwritePass { pass: 2, fill: OverwriteFill::repeat(&[0xFF]), bytes_written: 4096, bad_bytes: 0 };
+        let json = serde_json::to_value(&pass).unwrap();
+        assert_eq!(json, serde_json::json!({"pass": 2, "fill": "repeat", "bytes": "ff", "bytes_written": 4096, "bad_bytes": 0}));
+        assert_eq!(serde_json::from_value::<OverwritePass>(json).unwrap(), pass);
+    }
+
+    #[test]
+    fn test_overwrite_profile_overrides_planned_sanitize() {
+        let mut plan = plan_wipe("/dev/nvme0n1", Some(WipePolicy::Purge), false, false, None, Some("sanitize supported"));
+        assert_eq!(plan.main_method, "controller_sanitize");
+
+        OverwriteProfile::NistClear.apply_to_plan(&mut plan);
+        assert_eq!(plan.main_method, "controller_sanitize");
+        assert_eq!(plan.overwrite_profile.as_deref(), Some("nist-clear"));
+
+        OverwriteProfile::Dod522022M3Pass.apply_to_plan(&mut plan);
+        assert_eq!(plan.main_method, "overwrite");
+        assert_eq!(plan.method_decision.chosen, "overwrite");
+        let sanitize = &plan.method_decision.candidates[0];
+        assert!(!sanitize.selected);
+        assert!(sanitize.rejection_reason.as_deref().unwrap().contains("dod-5220.22-m-3pass"));
+        assert!(plan.method_decision.candidates[1].selected);
+    }
+
+    #[test]
+    fn test_verify_mode_parse() {
+        assert_eq!(VerifyMode::parse("heuristic"), Some(VerifyMode::Heuristic));
+        assert_eq!(VerifyMode::parse("pattern-full"), Some(VerifyMode::PatternFull));
+        assert_eq!(VerifyMode::parse("looks-random"), None);
+        assert!(!VerifyMode::default().writes_pattern());
+    }
+
+    #[test]
+    fn test_score_sample_classes() {
+        assert_eq!(score_sample(&[0u8; 512]).0, SampleClass::ZeroFill);
+        assert_eq!(score_sample(&[0xFFu8; 512]).0, SampleClass::ConstantFill);
+
+        let mut random = [0u8; 512];
+        ChaCha20Rng::seed_from_u64(1).fill_bytes(&mut random);
+        let (class, entropy, chi_square) = score_sample(&random);
+        assert_eq!(class, SampleClass::RandomFill);
+        assert!(entropy > 7.0);
+        assert!(chi_square < CHI_SQUARE_CRITICAL);
+
+        // A mostly-empty ext4 superblock sector: the old "mostly zeros" rule passed this
+        let mut superblock = [0u8; 512];
+        superblock[0x38] = 0x53;
+        superblock[0x39] = 0xEF;
+        superblock[0x78..0x80].copy_from_slice(b"rootfs\0\0");
+        let (class, entropy, _) = score_sample(&superblock);
+        assert_eq!(class, SampleClass::Structured);
+        assert!(entropy < 1.0);
+
+        // Text has varied bytes but a very skewed distribution
+        let text: Vec<u8> = b"Quarterly report - confidential. ".iter().cycle().take(512).copied().collect();
+        assert_eq!(score_sample(&text).0, SampleClass::Structured);
+    }
+
+    #[test]
+    fn test_verification_histogram_regions() {
+        let sample = |offset: u64, class: SampleClass| VerificationSample {
+            offset,
+            sha256: String::new(),
+            passed: class.is_wiped(),
+            class,
+            entropy: 0.0,
+            chi_square: 0.0,
+        };
+        let record = VerificationRecord {
+            seed: 0,
+            sample_bytes: VERIFY_SAMPLE_BYTES,
+            device_size: 8 * 1024,
+            samples: vec![
+                sample(0, SampleClass::ZeroFill),
+                sample(1024, SampleClass::RandomFill),
+                sample(7 * 1024, SampleClass::Structured),
+                sample(8 * 1024 - 512, SampleClass::Structured),
+            ],
+            excluded_regions: Vec::new(),
+        };
+
+        let histogram = record.histogram();
+        assert_eq!(histogram.regions.len(), HISTOGRAM_REGIONS as usize);
+        assert_eq!(histogram.totals.structured, 2);
+        assert_eq!(histogram.regions[0].counts.zero_fill, 1);
+        assert_eq!(histogram.regions[1].counts.random_fill, 1);
+        assert_eq!(histogram.regions[7].counts.structured, 2);
+        assert_eq!(histogram.regions[7].end, 8 * 1024);
+
+        let json = serde_json::to_value(&histogram.regions[7]).unwrap();
+        assert_eq!(json["structured"], 2);
     }
 
     #[test]
@@ -0,0 +1,199 @@
+//! The wipe plan `wipe` prints and acts on, and the GUI's `plan_wipe` shows. Controller
+//! probing is `wipe::plan_wipe`; this adds everything else that decides or blocks a
+//! plan: the drive's discovered risk, container storage, virtual and network media,
+//! the site's overwrite profile and its backup requirement. Both front ends assemble
+//! plans here so they block the same drives for the same reasons. A drive whose risk
+//! can't be established blocks the plan instead of being assumed SAFE.
+
+use std::path::Path;
+
+use crate::device::{Device, RiskLevel};
+use crate::policy::SitePolicy;
+use crate::virtual_disk::VirtualMedia;
+use crate::wipe::{block_plan, plan_wipe, DeviceState, OverwriteProfile, VerifyMode, WipePlan, WipePolicy};
+
+/// What to plan, as given on the command line or by the GUI
+#[derive(Debug, Clone)]
+pub struct PlanRequest {
+    pub device: String,
+    pub policy: WipePolicy,
+    pub iso_mode: bool,
+    /// `None` keeps the planner's default sample count
+    pub samples: Option<usize>,
+    pub verify_mode: VerifyMode,
+    /// `None` uses the site policy's profile
+    pub overwrite_profile: Option<OverwriteProfile>,
+    /// Treat the device as virtual media even when detection doesn't recognise it
+    pub declared_virtual: bool,
+    /// Acknowledge that a network block device is only overwritten from this host
+    pub allow_network_device: bool,
+    /// Backup certificate that satisfies the site's backup requirement
+    pub backup_cert_id: Option<String>,
+}
+
+/// Where the drive's risk level came from
+#[derive(Debug, Clone)]
+pub enum RiskSource {
+    Discovered(Box<Device>),
+    /// Image files are not block devices, so discovery never lists them
+    ImageFile,
+    NotFound,
+    DiscoveryFailed(String),
+}
+
+/// A plan plus what went into it, for the caller to log and act on
+#[derive(Debug, Clone)]
+pub struct AssembledPlan {
+    pub plan: WipePlan,
+    pub risk_source: RiskSource,
+    pub risk: RiskLevel,
+    pub overwrite_profile: OverwriteProfile,
+    pub virtual_media: Option<VirtualMedia>,
+    /// Set when the site requires a backup: `Ok(())` when the linked certificate
+    /// satisfied it, otherwise why not
+    pub backup_check: Option<Result<(), String>>,
+}
+
+impl AssembledPlan {
+    /// The discovered drive, when discovery listed it
+    pub fn device(&self) -> Option<&Device> {
+        match &self.risk_source {
+            RiskSource::Discovered(device) => Some(device),
+            _ => None,
+        }
+    }
+
+    pub fn serial(&self) -> Option<&str> {
+        self.device().and_then(|device| device.serial.as_deref())
+    }
+}
+
+/// Assemble the plan for `request` from a discovery run (`Err` holds why discovery
+/// failed) and the site policy
+pub fn assemble(request: &PlanRequest, discovered: Result<&[Device], String>, site_policy: &SitePolicy) -> AssembledPlan {
+    let (risk_source, risk_unknown) = match discovered {
+        Ok(devices) => match devices.iter().find(|d| d.name == request.device) {
+            Some(device) => (RiskSource::Discovered(Box::new(device.clone())), None),
+            None if Path::new(&request.device).is_file() => (RiskSource::ImageFile, None),
+            None => (
+                RiskSource::NotFound,
+                Some(format!("{} was not found by device discovery, so its risk can't be established", request.device)),
+            ),
+        },
+        Err(e) => {
+            let reason = format!("Device discovery failed ({}), so the risk of {} can't be established", e, request.device);
+            (RiskSource::DiscoveryFailed(e), Some(reason))
+        }
+    };
+    let device = match &risk_source {
+        RiskSource::Discovered(device) => Some(device.as_ref()),
+        _ => None,
+    };
+    let risk = device.map_or(RiskLevel::Safe, |d| d.risk_level.clone());
+
+    let mut plan = plan_wipe(&request.device, Some(request.policy.clone()), matches!(risk, RiskLevel::Critical), request.iso_mode, None, None);
+    if let Some(samples) = request.samples {
+        plan.verification.samples = samples;
+    }
+    plan.device_state = device.map(|d| DeviceState {
+        device_id: d.device_id.clone(),
+        serial: d.serial.clone(),
+        model: d.model.clone(),
+        capacity_bytes: d.capacity_bytes,
+        mountpoints: d.mountpoints.clone(),
+    });
+    if let Some(reason) = risk_unknown {
+        block_plan(&mut plan, reason);
+    }
+    // A disk holding live container storage is in use whatever its mounts look like
+    crate::containers::block_plan(&mut plan, device.and_then(|d| d.risk_explanation.as_ref()));
+    match request.verify_mode {
+        VerifyMode::Heuristic => {}
+        VerifyMode::Pattern => plan.verification.strategy = "pattern_readback".to_string(),
+        VerifyMode::PatternFull => plan.verification.strategy = "pattern_full_readback".to_string(),
+    }
+    let overwrite_profile = request.overwrite_profile.unwrap_or(site_policy.overwrite.profile);
+    overwrite_profile.apply_to_plan(&mut plan);
+
+    // Image files and hypervisor/cloud volumes are overwritten, never sanitized
+    let device_path = Path::new(&request.device);
+    let virtual_media = VirtualMedia::detect(device_path)
+        .or_else(|| request.declared_virtual.then(|| VirtualMedia::declared(device_path)));
+    if let Some(ref media) = virtual_media {
+        media.apply_to_plan(&mut plan);
+        if media.is_network() && !request.allow_network_device {
+            let reason = format!(
+                "{} can only be overwritten, not sanitized; pass --allow-network-device to acknowledge this",
+                media.describe()
+            );
+            block_plan(&mut plan, reason);
+        }
+    }
+
+    let backup_check = site_policy.backup.require_backup_before_wipe.then(|| {
+        let backup_cert = request.backup_cert_id.as_deref().map(load_certificate).transpose();
+        let reason = match backup_cert {
+            Ok(cert) => site_policy.backup.blocking_reason(cert.as_ref(), device.and_then(|d| d.serial.as_deref()), chrono::Utc::now()),
+            Err(e) => Some(e),
+        };
+        match reason {
+            Some(reason) => Err(reason),
+            None => Ok(()),
+        }
+    });
+    if let Some(Err(reason)) = &backup_check {
+        block_plan(&mut plan, reason.clone());
+    }
+
+    AssembledPlan { plan, risk_source, risk, overwrite_profile, virtual_media, backup_check }
+}
+
+/// A stored certificate by ID
+pub fn load_certificate(cert_id: &str) -> Result<serde_json::Value, String> {
+    let config = crate::config::Config::load().map_err(|e| e.to_string())?;
+    let path = crate::cert_id::certificate_path(&config.certificates_dir, cert_id).map_err(|e| e.to_string())?;
+    std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+        .map_err(|e| format!("Could not read certificate {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(device: &str) -> PlanRequest {
+        PlanRequest {
+            device: device.to_string(),
+            policy: WipePolicy::Purge,
+            iso_mode: false,
+            samples: Some(16),
+            verify_mode: VerifyMode::Heuristic,
+            overwrite_profile: None,
+            declared_virtual: false,
+            allow_network_device: false,
+            backup_cert_id: None,
+        }
+    }
+
+    #[test]
+    fn test_unknown_risk_blocks_plan() {
+        let policy = SitePolicy::default();
+        let failed = assemble(&request("/dev/sw-missing"), Err("lsblk failed".to_string()), &policy);
+        assert!(failed.plan.blocked);
+        assert!(failed.plan.reason.unwrap().contains("Device discovery failed (lsblk failed)"));
+        assert!(matches!(failed.risk_source, RiskSource::DiscoveryFailed(_)));
+
+        let missing = assemble(&request("/dev/sw-missing"), Ok(&[]), &policy);
+        assert!(missing.plan.blocked);
+        assert!(missing.plan.reason.unwrap().contains("not found by device discovery"));
+        assert_eq!(missing.plan.verification.samples, 16);
+
+        let image = tempfile::NamedTempFile::new().unwrap();
+        let image_path = image.path().display().to_string();
+        let planned = assemble(&request(&image_path), Ok(&[]), &policy);
+        assert!(matches!(planned.risk_source, RiskSource::ImageFile));
+        assert!(!planned.plan.blocked, "{:?}", planned.plan.reason);
+        assert!(planned.virtual_media.is_some());
+    }
+}
@@ -94,6 +94,11 @@ mod integration_tests {
             total_files: 2,
             total_bytes: 2048,
             manifest_sha256: "test_manifest_hash".to_string(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
         
         // Test serialization and deserialization
@@ -155,11 +160,18 @@ mod integration_tests {
                 total_files: 0,
                 total_bytes: 0,
                 manifest_sha256: "test_manifest_hash".to_string(),
+                skipped_files: Vec::new(),
+                sparse_files: HashMap::new(),
+                encoded_paths: HashMap::new(),
+                ciphertext_sha256: HashMap::new(),
+                keystream_offsets: HashMap::new(),
             },
             destination: "/mnt/backup".to_string(),
             encryption_method: "AES-256-CTR".to_string(),
             verification_samples: 5,
             verification_passed: true,
+            timing: None,
+            destination_canary: None,
         };
         
         let backup_cert = cert_mgr.create_backup_certificate(&backup_result).unwrap();
@@ -173,6 +185,19 @@ mod integration_tests {
             verification_samples: 5,
             verification_passed: true,
             fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
         };
         
         let wipe_cert = cert_mgr.create_wipe_certificate(&wipe_result, Some(&backup_cert.cert_id)).unwrap();
@@ -181,7 +206,7 @@ mod integration_tests {
         assert!(wipe_cert.linkage.is_some());
         
         if let Some(linkage) = &wipe_cert.linkage {
-            assert!(linkage.get("backup_cert_id").is_some());
+            assert_eq!(linkage.backup_cert_id, backup_cert.cert_id);
         }
     }
     
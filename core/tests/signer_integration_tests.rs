@@ -23,7 +23,7 @@ fn get_dev_public_key_path() -> PathBuf {
 #[test]
 fn test_sign_and_verify_with_dev_keys() {
     if !dev_keys_exist() {
-        eprintln!("⚠️  Skipping test: Dev keys not found. Run 'openssl genpkey -algorithm Ed25519 -out keys/dev_private.pem && openssl pkey -in keys/dev_private.pem -pubout -out keys/dev_public.pem' from repo root.");
+        eprintln!("⚠️  Skipping test: Dev keys not found. Run 'openssl genpkey -algorithm Ed25519 -out keys/dev_private.pem && openssl pkey -in keys/dev_private.pem -pubout -out keys/dev_public.pem && chmod 600 keys/dev_private.pem' from repo root.");
         return;
     }
     
@@ -92,7 +92,7 @@ fn test_sign_and_verify_with_dev_keys() {
 #[test]
 fn test_tamper_detection_with_dev_keys() {
     if !dev_keys_exist() {
-        eprintln!("⚠️  Skipping test: Dev keys not found. Run 'openssl genpkey -algorithm Ed25519 -out keys/dev_private.pem && openssl pkey -in keys/dev_private.pem -pubout -out keys/dev_public.pem' from repo root.");
+        eprintln!("⚠️  Skipping test: Dev keys not found. Run 'openssl genpkey -algorithm Ed25519 -out keys/dev_private.pem && openssl pkey -in keys/dev_private.pem -pubout -out keys/dev_public.pem && chmod 600 keys/dev_private.pem' from repo root.");
         return;
     }
     
@@ -172,7 +172,7 @@ fn test_tamper_detection_with_dev_keys() {
 #[test]
 fn test_missing_signature_detection() {
     if !dev_keys_exist() {
-        eprintln!("⚠️  Skipping test: Dev keys not found. Run 'openssl genpkey -algorithm Ed25519 -out keys/dev_private.pem && openssl pkey -in keys/dev_private.pem -pubout -out keys/dev_public.pem' from repo root.");
+        eprintln!("⚠️  Skipping test: Dev keys not found. Run 'openssl genpkey -algorithm Ed25519 -out keys/dev_private.pem && openssl pkey -in keys/dev_private.pem -pubout -out keys/dev_public.pem && chmod 600 keys/dev_private.pem' from repo root.");
         return;
     }
     
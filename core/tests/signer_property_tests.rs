@@ -0,0 +1,198 @@
+//! Property tests for the signing core: canonicalization and Ed25519 signatures over
+//! arbitrary certificate-shaped JSON. A certificate must verify after it is written
+//! to disk and read back, and no re-ordering of its keys may change what was signed.
+
+use ed25519_dalek::SigningKey;
+use proptest::prelude::*;
+use serde_json::{json, Number, Value};
+
+use securewipe::{canonicalize_json, sign_certificate, verify_certificate_signature};
+
+fn key(seed: [u8; 32]) -> SigningKey {
+    SigningKey::from_bytes(&seed)
+}
+
+/// Arbitrary JSON whose strings and keys match `text`
+fn any_json(text: &'static str) -> impl Strategy<Value = Value> {
+    leaf(text).prop_recursive(4, 48, 6, move |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::btree_map(text, inner, 0..6).prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+fn leaf(text: &'static str) -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_filter_map("JSON has no NaN or infinity", |f| Number::from_f64(f).map(Value::Number)),
+        text.prop_map(Value::String),
+    ]
+}
+
+/// The fields every certificate has, with arbitrary values, plus arbitrary extras
+fn certificate() -> impl Strategy<Value = Value> {
+    (
+        prop_oneof![Just("backup"), Just("wipe"), Just("destroy")],
+        "[A-Z]{3}_[0-9]{8}T[0-9]{6}Z_[0-9A-Z]{11}",
+        "[A-Za-z0-9 ._-]{0,40}",
+        "[A-Za-z0-9]{0,20}",
+        any::<u64>(),
+        prop::collection::btree_map("x_[a-z]{1,8}", any_json(".{0,12}"), 0..4),
+    )
+        .prop_map(|(cert_type, cert_id, model, serial, capacity_bytes, extras)| {
+            let mut cert = json!({
+                "cert_id": cert_id,
+                "cert_type": cert_type,
+                "certificate_version": "v1.0.0",
+                "created_at": "2025-09-20T10:15:00.000000+00:00",
+                "device": {
+                    "model": model,
+                    "serial": serial,
+                    "capacity_bytes": capacity_bytes
+                }
+            });
+            cert.as_object_mut().unwrap().extend(extras);
+            cert
+        })
+}
+
+/// Write `value` as JSON text with every object's keys in an order picked by `order`,
+/// which a `serde_json::Value` can't represent
+fn write_shuffled(value: &Value, order: &mut impl FnMut(usize) -> usize, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            if !entries.is_empty() {
+                let pivot = order(entries.len()) % entries.len();
+                entries.rotate_left(pivot);
+                if order(2) == 1 {
+                    entries.reverse();
+                }
+            }
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(&serde_json::to_string(key).unwrap());
+                out.push_str(": ");
+                write_shuffled(value, order, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_shuffled(item, order, out);
+            }
+            out.push(']');
+        }
+        leaf => out.push_str(&serde_json::to_string(leaf).unwrap()),
+    }
+}
+
+proptest! {
+    #[test]
+    fn signed_certificates_verify_after_a_round_trip(cert in certificate(), seed in any::<[u8; 32]>()) {
+        let key = key(seed);
+        let mut signed = cert.clone();
+        sign_certificate(&mut signed, &key, false).unwrap();
+        prop_assert!(verify_certificate_signature(&signed, key.verifying_key().as_bytes()).unwrap());
+
+        // As stored on disk and as pasted back in
+        for text in [serde_json::to_string_pretty(&signed).unwrap(), serde_json::to_string(&signed).unwrap()] {
+            let reloaded: Value = serde_json::from_str(&text).unwrap();
+            prop_assert!(verify_certificate_signature(&reloaded, key.verifying_key().as_bytes()).unwrap());
+        }
+
+        // Signing never touches the certificate's own fields
+        signed.as_object_mut().unwrap().remove("signature");
+        prop_assert_eq!(signed, cert);
+    }
+
+    // canonicalize_json drops whitespace inside strings and keys too, so for text
+    // containing whitespace its output re-canonicalizes to something else
+    #[test]
+    fn canonical_form_is_a_fixed_point(value in any_json("\\S{0,12}")) {
+        let canonical = canonicalize_json(&value).unwrap();
+        let reparsed: Value = serde_json::from_slice(&canonical).unwrap();
+        prop_assert_eq!(canonicalize_json(&reparsed).unwrap(), canonical);
+    }
+
+    #[test]
+    fn key_order_does_not_change_the_canonical_form(value in any_json(".{0,12}"), picks in prop::collection::vec(any::<usize>(), 64)) {
+        let mut picks = picks.into_iter().cycle();
+        let mut text = String::new();
+        write_shuffled(&value, &mut |_| picks.next().unwrap(), &mut text);
+        let shuffled: Value = serde_json::from_str(&text).unwrap();
+        prop_assert_eq!(canonicalize_json(&shuffled).unwrap(), canonicalize_json(&value).unwrap());
+    }
+
+    #[test]
+    fn signatures_survive_key_reordering(cert in certificate(), seed in any::<[u8; 32]>(), picks in prop::collection::vec(any::<usize>(), 64)) {
+        let key = key(seed);
+        let mut signed = cert;
+        sign_certificate(&mut signed, &key, false).unwrap();
+
+        let mut picks = picks.into_iter().cycle();
+        let mut text = String::new();
+        write_shuffled(&signed, &mut |_| picks.next().unwrap(), &mut text);
+        let reordered: Value = serde_json::from_str(&text).unwrap();
+        prop_assert!(verify_certificate_signature(&reordered, key.verifying_key().as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn changed_numbers_break_the_signature(cert in certificate(), seed in any::<[u8; 32]>()) {
+        let key = key(seed);
+        let mut signed = cert;
+        sign_certificate(&mut signed, &key, false).unwrap();
+
+        let capacity = signed["device"]["capacity_bytes"].as_u64().unwrap();
+        signed["device"]["capacity_bytes"] = json!(capacity.wrapping_add(1));
+        prop_assert!(!verify_certificate_signature(&signed, key.verifying_key().as_bytes()).unwrap());
+    }
+}
+
+#[test]
+fn non_finite_numbers_never_reach_a_signature() {
+    // serde_json has no representation for them: they can't be parsed, and building
+    // one from an f64 gives null rather than a number
+    for text in ["NaN", "Infinity", "-Infinity", "1e400", "-1e400", r#"{"capacity_bytes": NaN}"#] {
+        assert!(serde_json::from_str::<Value>(text).is_err(), "{} parsed", text);
+    }
+    assert!(Number::from_f64(f64::NAN).is_none());
+    assert!(Number::from_f64(f64::INFINITY).is_none());
+    assert_eq!(json!({"v": f64::NAN}), json!({"v": null}));
+}
+
+#[test]
+fn large_integers_are_signed_exactly() {
+    // Above 2^53 a double can't tell these apart; the signature must
+    let key = key([9; 32]);
+    let mut signed = json!({"cert_type": "wipe", "verify_seed": 18_446_744_073_709_551_615u64});
+    sign_certificate(&mut signed, &key, false).unwrap();
+    assert!(String::from_utf8(canonicalize_json(&signed).unwrap()).unwrap().contains("18446744073709551615"));
+
+    signed["verify_seed"] = json!(18_446_744_073_709_551_614u64);
+    assert!(!verify_certificate_signature(&signed, key.verifying_key().as_bytes()).unwrap());
+
+    let mut negative = json!({"offset": i64::MIN});
+    sign_certificate(&mut negative, &key, false).unwrap();
+    let reloaded: Value = serde_json::from_str(&serde_json::to_string(&negative).unwrap()).unwrap();
+    assert!(verify_certificate_signature(&reloaded, key.verifying_key().as_bytes()).unwrap());
+}
+
+#[test]
+fn non_object_documents_are_rejected() {
+    let key = key([3; 32]);
+    for mut value in [json!([1, 2]), json!("cert"), json!(42), Value::Null] {
+        assert!(sign_certificate(&mut value, &key, false).is_err());
+    }
+}
@@ -43,6 +43,9 @@ mod validation_tests {
             bus: Some("SATA".to_string()),
             mountpoints: vec![],
             risk_level: RiskLevel::Safe,
+            device_id: None,
+            risk_explanation: None,
+            capacity_display: None,
         };
 
         let manifest = BackupManifest {
@@ -51,6 +54,11 @@ mod validation_tests {
             total_files: 0,
             total_bytes: 0,
             manifest_sha256: "test_manifest_hash".to_string(),
+            skipped_files: Vec::new(),
+            sparse_files: HashMap::new(),
+            encoded_paths: HashMap::new(),
+            ciphertext_sha256: HashMap::new(),
+            keystream_offsets: HashMap::new(),
         };
 
         let backup_result = BackupResult {
@@ -60,6 +68,8 @@ mod validation_tests {
             encryption_method: "AES-256-CTR".to_string(),
             verification_samples: 5,
             verification_passed: true,
+            timing: None,
+            destination_canary: None,
         };
 
         let wipe_result = WipeResult {
@@ -70,12 +80,27 @@ mod validation_tests {
             verification_samples: 5,
             verification_passed: true,
             fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
         };
 
         let signature = CertificateSignature {
             alg: "Ed25519".to_string(),
             pubkey_id: "sih_root_v1".to_string(),
             sig: "test_sig".to_string(),
+            canonicalization: None,
+            qr: None,
         };
 
         // Verify structs are created correctly
@@ -128,11 +153,18 @@ mod validation_tests {
                 total_files: 0,
                 total_bytes: 0,
                 manifest_sha256: "test_manifest_hash".to_string(),
+                skipped_files: Vec::new(),
+                sparse_files: HashMap::new(),
+                encoded_paths: HashMap::new(),
+                ciphertext_sha256: HashMap::new(),
+                keystream_offsets: HashMap::new(),
             },
             destination: "/test".to_string(),
             encryption_method: "AES-256-CTR".to_string(),
             verification_samples: 5,
             verification_passed: true,
+            timing: None,
+            destination_canary: None,
         };
         let result = cert_mgr.create_backup_certificate(&backup_result);
         assert!(result.is_ok());
@@ -243,4 +275,147 @@ mod validation_tests {
         assert_eq!(result["file"], "test_data/nonexistent.json");
         assert!(result.get("error").is_some());
     }
-}
\ No newline at end of file
+
+    fn schema_dir() -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../certs/schemas")
+    }
+
+    fn schema_example(file_name: &str) -> serde_json::Value {
+        let schema: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(schema_dir().join(file_name)).unwrap()).unwrap();
+        schema["examples"][0].clone()
+    }
+
+    fn test_signature() -> CertificateSignature {
+        CertificateSignature {
+            alg: "Ed25519".to_string(),
+            pubkey_id: "sih_root_v1".to_string(),
+            sig: "c2lnbmF0dXJl".to_string(),
+            canonicalization: None,
+            qr: None,
+        }
+    }
+
+    #[test]
+    fn test_schema_examples_round_trip_through_typed_certificates() {
+        // Any field the structs drop, rename or retype shows up as a diff here
+        let backup_example = schema_example("backup_schema.json");
+        let backup: BackupCertificate = serde_json::from_value(backup_example.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&backup).unwrap(), backup_example);
+
+        let wipe_example = schema_example("wipe_schema.json");
+        let wipe: WipeCertificate = serde_json::from_value(wipe_example.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&wipe).unwrap(), wipe_example);
+    }
+
+    #[test]
+    fn test_built_certificates_validate_against_schemas() {
+        use securewipe::cert::*;
+
+        let validator = CertificateValidator::from_schema_dir(Some(schema_dir())).unwrap();
+
+        let backup = BackupCertificateBuilder::new("BCK_TEST_001", "2024-01-15T10:30:00+05:30")
+            .issuer(Issuer {
+                organization: "SecureWipe (SIH)".to_string(),
+                tool_name: "securewipe".to_string(),
+                tool_version: "v1.0.0".to_string(),
+                country: Some("IN".to_string()),
+            })
+            .device(CertificateDevice {
+                model: "Samsung SSD 980 PRO".to_string(),
+                serial: "S6TXNX0R123456".to_string(),
+                bus: BusType::Nvme,
+                capacity_bytes: 1000204886016,
+                logical_block_size: Some(512),
+                total_lbas: None,
+                firmware: None,
+                namespace_id: None,
+                path: Some("/dev/nvme0n1".to_string()),
+                protocol_path: None,
+                media_type: None,
+                device_id: None,
+            })
+            .files_summary(FilesSummary {
+                count: 2847,
+                personal_bytes: 52428800,
+                included_paths: Some(vec!["~/Documents".to_string()]),
+                excluded_paths: None,
+            })
+            .destination(BackupDestination {
+                kind: DestinationType::Usb,
+                label: Some("BACKUP_USB".to_string()),
+                fs: Some("exfat".to_string()),
+                mountpoint: None,
+                path: None,
+            })
+            .crypto(BackupCrypto {
+                alg: "AES-256-CTR".to_string(),
+                manifest_sha256: "d2".repeat(32),
+                key_management: KeyManagement::EphemeralSessionKey,
+            })
+            .verification(BackupVerification {
+                strategy: VerificationStrategy::SampledFiles,
+                samples: Some(5),
+                coverage: Some(Coverage::Percent { percent: 15.5 }),
+                failures: 0,
+                notes: None,
+            })
+            .policy(BackupPolicy {
+                name: "NIST SP 800-88 Rev.1".to_string(),
+                version: "2023.12".to_string(),
+            })
+            .result(CertificateResult::Pass)
+            .environment(Environment {
+                operator: "Automated".to_string(),
+                os_kernel: "Linux 6.8.0-35-generic".to_string(),
+                tool_version: "v1.0.0".to_string(),
+                device_firmware: None,
+                containerized: Some(false),
+                time_source: None,
+                destination_canary: None,
+            })
+            .signature(test_signature())
+            .build()
+            .unwrap();
+        let result = validator
+            .validate_backup_certificate(&serde_json::to_value(&backup).unwrap())
+            .unwrap();
+        assert!(result.valid, "backup errors: {:?}", result.errors);
+
+        let wipe_result = WipeResult {
+            device: "/dev/sda".to_string(),
+            policy: WipePolicy::Purge,
+            method: "controller_sanitize".to_string(),
+            commands: vec![WipeCommand {
+                command: "hdparm --security-erase NULL /dev/sda".to_string(),
+                exit_code: 0,
+                elapsed_ms: 1200,
+                output: String::new(),
+            }],
+            verification_samples: 5,
+            verification_passed: true,
+            fallback_reason: None,
+            identity_before: None,
+            identity_after: None,
+            verification: None,
+            residual_signatures: Vec::new(),
+            method_decision: None,
+            pattern_verification: None,
+            bad_regions: Vec::new(),
+            overwrite: None,
+            hpa_dco: None,
+            partition_metadata: Vec::new(),
+            timing: None,
+            virtual_media: None,
+            smart_delta: None,
+        };
+        let mut wipe = Ed25519CertificateManager
+            .create_wipe_certificate(&wipe_result, Some(&backup.cert_id))
+            .unwrap();
+        wipe.signature = Some(test_signature());
+        let result = validator
+            .validate_wipe_certificate(&serde_json::to_value(&wipe).unwrap())
+            .unwrap();
+        assert!(result.valid, "wipe errors: {:?}", result.errors);
+    }
+}
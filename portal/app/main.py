@@ -14,7 +14,8 @@ from typing import Dict, Any, List, Optional, Union
 
 from fastapi import FastAPI, HTTPException, Request
 from fastapi.middleware.cors import CORSMiddleware
-from fastapi.responses import HTMLResponse
+from fastapi.responses import HTMLResponse, PlainTextResponse
+from fastapi.staticfiles import StaticFiles
 from pydantic import BaseModel, ValidationError
 import jsonschema
 from jsonschema import validate, ValidationError as JsonSchemaValidationError
@@ -44,11 +45,15 @@ app.add_middleware(
 BACKUP_SCHEMA = None
 WIPE_SCHEMA = None
 PUBLIC_KEY_BYTES = None
+PUBLIC_KEY_PEM = None
+
+# wasm-pack output of verify/wasm, for verification in the browser (GET /verify/browser)
+WASM_PKG_DIR = Path(__file__).parent.parent.parent / "verify" / "wasm" / "pkg"
 
 
 def load_public_key():
     """Load Ed25519 public key from PEM file"""
-    global PUBLIC_KEY_BYTES
+    global PUBLIC_KEY_BYTES, PUBLIC_KEY_PEM
     
     # Get public key path from environment or use default
     pubkey_path = os.environ.get("SECUREWIPE_PUBKEY_PATH", "keys/dev_public.pem")
@@ -75,6 +80,7 @@ def load_public_key():
             encoding=serialization.Encoding.Raw,
             format=serialization.PublicFormat.Raw
         )
+        PUBLIC_KEY_PEM = pem_content
         
     except FileNotFoundError:
         raise RuntimeError(f"Public key file not found: {pubkey_path}")
@@ -137,6 +143,9 @@ def canonicalize_json(value: Dict[str, Any]) -> bytes:
 load_schemas()
 load_public_key()
 
+if WASM_PKG_DIR.exists():
+    app.mount("/wasm", StaticFiles(directory=WASM_PKG_DIR), name="wasm")
+
 
 class VerificationResult(BaseModel):
     """Response model for certificate verification"""
@@ -441,6 +450,11 @@ async def get_home():
                 </ul>
             </div>
             
+            <div class="endpoint">
+                <h2><span class="method">GET</span> /verify/browser</h2>
+                <p>Verify a certificate file entirely in the browser: the same Rust verification code as <code>securewipe cert verify</code>, compiled to WebAssembly. The certificate is never uploaded.</p>
+            </div>
+            
             <div class="endpoint">
                 <h2><span class="method">GET</span> /verify/{cert_id}</h2>
                 <p>Get information about certificate verification by ID (placeholder for future implementation).</p>
@@ -529,6 +543,74 @@ async def verify_certificate(request: Request):
         )
 
 
+@app.get("/public-key", response_class=PlainTextResponse)
+async def get_public_key():
+    """The issuer's Ed25519 public key PEM, for verification in the browser"""
+    return PlainTextResponse(content=PUBLIC_KEY_PEM)
+
+
+@app.get("/verify/browser", response_class=HTMLResponse)
+async def get_browser_verification():
+    """
+    Verify a certificate client-side with the WebAssembly build of securewipe-verify
+
+    Needs verify/wasm/pkg, built with `wasm-pack build --target web` in verify/wasm.
+    """
+    if not WASM_PKG_DIR.exists():
+        raise HTTPException(
+            status_code=503,
+            detail="Browser verification is not built. Run `wasm-pack build --target web` in verify/wasm."
+        )
+    html_content = """
+    <!DOCTYPE html>
+    <html>
+    <head>
+        <title>SecureWipe Certificate Verification</title>
+        <style>
+            body { font-family: Arial, sans-serif; margin: 40px; background-color: #f5f5f5; }
+            .container { max-width: 800px; margin: 0 auto; background: white; padding: 30px; border-radius: 8px; box-shadow: 0 2px 10px rgba(0,0,0,0.1); }
+            .header { text-align: center; color: #2c3e50; margin-bottom: 30px; }
+            .valid { color: #27ae60; }
+            .invalid { color: #c0392b; }
+            pre { background: #f8f9fa; padding: 15px; border-radius: 5px; overflow-x: auto; }
+        </style>
+    </head>
+    <body>
+        <div class="container">
+            <div class="header">
+                <h1>🔒 Verify a SecureWipe Certificate</h1>
+                <p>Checked in your browser; the certificate is not uploaded</p>
+            </div>
+            <input type="file" id="certificate" accept=".json,application/json">
+            <h2 id="verdict"></h2>
+            <pre id="report"></pre>
+        </div>
+        <script type="module">
+            import init, { verifyCertificate } from "/wasm/securewipe_verify_wasm.js";
+
+            await init();
+            const publicKey = await (await fetch("/public-key")).text();
+
+            document.getElementById("certificate").addEventListener("change", async (event) => {
+                const verdict = document.getElementById("verdict");
+                const text = await event.target.files[0].text();
+                try {
+                    const report = JSON.parse(verifyCertificate(text, publicKey));
+                    verdict.textContent = report.valid ? "✅ Valid certificate" : "❌ Not valid";
+                    verdict.className = report.valid ? "valid" : "invalid";
+                    document.getElementById("report").textContent = JSON.stringify(report, null, 2);
+                } catch (error) {
+                    verdict.textContent = "❌ " + error;
+                    verdict.className = "invalid";
+                }
+            });
+        </script>
+    </body>
+    </html>
+    """
+    return HTMLResponse(content=html_content)
+
+
 @app.get("/verify/{cert_id}")
 async def get_certificate_info(cert_id: str):
     """
@@ -13,7 +13,7 @@ from fastapi.testclient import TestClient
 from pathlib import Path
 from unittest.mock import patch, MagicMock
 
-from app.main import app, validate_certificate, canonicalize_json, verify_ed25519_signature
+from app.main import app, validate_certificate, canonicalize_json, verify_ed25519_signature, WASM_PKG_DIR
 
 
 # Test client
@@ -456,6 +456,22 @@ class TestHomeEndpoint:
         assert "SecureWipe Verification Portal" in response.text
         assert "/verify" in response.text
 
+    def test_browser_verification_page(self):
+        """The in-browser page loads the WebAssembly verifier, or says it isn't built"""
+        response = client.get("/verify/browser")
+        if WASM_PKG_DIR.exists():
+            assert response.status_code == 200
+            assert "verifyCertificate" in response.text
+        else:
+            assert response.status_code == 503
+            assert "wasm-pack" in response.json()["detail"]
+    
+    def test_public_key(self):
+        """The page gets the issuer key as PEM"""
+        response = client.get("/public-key")
+        assert response.status_code == 200
+        assert "BEGIN PUBLIC KEY" in response.text
+
 
 class TestCertificateVerification:
     """Test certificate verification endpoint"""
@@ -0,0 +1,23 @@
+#!/bin/bash
+# Build the core CLI and stage it as the Tauri sidecar (binaries/securewipe-<target-triple>)
+set -e
+
+ROOT_DIR="$(cd "$(dirname "$0")/.." && pwd)"
+PROFILE="${1:-release}"
+TRIPLE="$(rustc -vV | sed -n 's/^host: //p')"
+EXT=""
+case "$TRIPLE" in
+    *windows*) EXT=".exe" ;;
+esac
+
+cd "$ROOT_DIR/core"
+if [ "$PROFILE" = "release" ]; then
+    cargo build --release
+else
+    cargo build
+fi
+
+mkdir -p "$ROOT_DIR/ui/src-tauri/binaries"
+cp "$ROOT_DIR/core/target/$PROFILE/securewipe$EXT" \
+   "$ROOT_DIR/ui/src-tauri/binaries/securewipe-$TRIPLE$EXT"
+echo "Staged sidecar: ui/src-tauri/binaries/securewipe-$TRIPLE$EXT"
@@ -0,0 +1,67 @@
+#!/usr/bin/env python3
+"""
+Locale-aware numbers, byte sizes and dates for the certificate PDFs
+
+The Rust CLI resolves the locale and unit settings (see core/src/locale.rs) and
+passes them with --format; configure() applies them to every generator. Without
+it, numbers read 1,234.56, dates are ISO and sizes use SI units.
+"""
+
+from datetime import datetime
+
+FORMAT = {
+    "locale": "C",
+    "decimal": ".",
+    "group": ",",
+    "date": "%Y-%m-%d",
+    "units": "si",
+}
+
+UNITS = {
+    "si": (1000, ["kB", "MB", "GB", "TB", "PB"]),
+    "iec": (1024, ["KiB", "MiB", "GiB", "TiB", "PiB"]),
+}
+
+def configure(settings):
+    """Use the display settings passed by the CLI"""
+    FORMAT.update({key: value for key, value in settings.items() if key in FORMAT})
+
+def format_number(value, decimals=0):
+    """Number with the locale's digit groups and decimal separator"""
+    text = f"{value:,.{decimals}f}"
+    return text.replace(",", "\0").replace(".", FORMAT["decimal"]).replace("\0", FORMAT["group"])
+
+def format_bytes(bytes_value):
+    """Byte size in the largest SI or IEC unit it reaches"""
+    base, labels = UNITS.get(FORMAT["units"], UNITS["si"])
+    value = float(bytes_value)
+    label = None
+    for unit in labels:
+        if value < base:
+            break
+        value /= base
+        label = unit
+    if label is None:
+        return f"{format_number(bytes_value)} bytes"
+    return f"{format_number(value, 2)} {label}"
+
+def format_bytes_exact(bytes_value):
+    """Byte size followed by the exact count, so SI and IEC figures can't be confused"""
+    scaled = format_bytes(bytes_value)
+    if scaled.endswith(" bytes"):
+        return scaled
+    return f"{scaled} ({format_number(bytes_value)} bytes)"
+
+def format_timestamp(text):
+    """RFC 3339 timestamp in the locale's date order, keeping its own offset"""
+    try:
+        at = datetime.fromisoformat(text.replace("Z", "+00:00"))
+    except (AttributeError, ValueError):
+        return text
+    offset = at.utcoffset()
+    if offset is None:
+        return f"{at.strftime(FORMAT['date'])} {at.strftime('%H:%M:%S')}"
+    minutes = int(offset.total_seconds()) // 60
+    sign = "-" if minutes < 0 else "+"
+    zone = f"{sign}{abs(minutes) // 60:02d}:{abs(minutes) % 60:02d}"
+    return f"{at.strftime(FORMAT['date'])} {at.strftime('%H:%M:%S')} {zone}"
@@ -0,0 +1,250 @@
+#!/usr/bin/env python3
+"""
+SecureWipe Physical Destruction Certificate PDF Generation Test
+
+Renders DESTROY-level certificates (shredding, degaussing, ...) with the same
+layout, QR code and signature block as wipe certificates.
+"""
+
+import json
+import sys
+import os
+from pathlib import Path
+
+try:
+    from jsonschema import validate, ValidationError
+except ImportError:
+    print("❌ Missing dependency: jsonschema")
+    print("   Install with: pip install jsonschema")
+    sys.exit(1)
+
+try:
+    from reportlab.lib.pagesizes import A4
+    from reportlab.lib.styles import getSampleStyleSheet, ParagraphStyle
+    from reportlab.lib.units import inch
+    from reportlab.lib import colors
+    from reportlab.platypus import SimpleDocTemplate, Table, TableStyle, Paragraph, Spacer, Image
+    from reportlab.lib.enums import TA_CENTER
+except ImportError:
+    print("❌ Missing dependency: reportlab")
+    print("   Install with: pip install reportlab")
+    sys.exit(1)
+
+from test_wipe_pdf_certificates import generate_qr_code, format_bytes, format_hash, create_clickable_url, describe_clock
+from pdf_formatting import format_bytes_exact, format_timestamp
+
+PROJECT_ROOT = Path(__file__).parent.parent
+SCHEMA_PATH = PROJECT_ROOT / "certs" / "schemas" / "destroy_schema.json"
+
+METHOD_LABELS = {
+    "shred": "Shredding",
+    "disintegrate": "Disintegration",
+    "pulverize": "Pulverization",
+    "incinerate": "Incineration",
+    "melt": "Melting",
+    "degauss": "Degaussing",
+}
+
+
+def load_schema():
+    """Load the destroy certificate JSON schema"""
+    with open(SCHEMA_PATH, 'r') as f:
+        return json.load(f)
+
+
+def create_sample_destroy_certificate():
+    """Use the example shipped with the schema as the sample certificate"""
+    return load_schema()["examples"][0]
+
+
+def _table(rows, label_color, font_size=9):
+    table = Table(rows, colWidths=[2*inch, 3.5*inch])
+    table.setStyle(TableStyle([
+        ('BACKGROUND', (0, 0), (0, -1), label_color),
+        ('TEXTCOLOR', (0, 0), (-1, -1), colors.black),
+        ('ALIGN', (0, 0), (-1, -1), 'LEFT'),
+        ('FONTNAME', (0, 0), (-1, -1), 'Helvetica'),
+        ('FONTSIZE', (0, 0), (-1, -1), font_size),
+        ('GRID', (0, 0), (-1, -1), 1, colors.black),
+        ('VALIGN', (0, 0), (-1, -1), 'TOP'),
+    ]))
+    return table
+
+
+def create_destroy_certificate_pdf(cert_data, output_path):
+    """Generate PDF certificate for a physically destroyed drive"""
+    doc = SimpleDocTemplate(output_path, pagesize=A4,
+                          rightMargin=72, leftMargin=72,
+                          topMargin=72, bottomMargin=18)
+
+    styles = getSampleStyleSheet()
+    story = []
+
+    title_style = ParagraphStyle(
+        'CustomTitle',
+        parent=styles['Heading1'],
+        fontSize=24,
+        spaceAfter=30,
+        alignment=TA_CENTER,
+        textColor=colors.darkblue
+    )
+    header_style = ParagraphStyle(
+        'CustomHeader',
+        parent=styles['Heading2'],
+        fontSize=14,
+        spaceAfter=12,
+        textColor=colors.darkblue
+    )
+    small_text_style = ParagraphStyle(
+        'SmallText',
+        parent=styles['Normal'],
+        fontSize=8,
+        wordWrap='LTR'
+    )
+
+    story.append(Paragraph("SecureWipe Physical Destruction Certificate", title_style))
+    story.append(Paragraph("NIST SP 800-88 DESTROY", styles['Heading3']))
+    if cert_data.get('serial_number'):
+        story.append(Paragraph(f"Certificate No. {cert_data['serial_number']}", header_style))
+    story.append(Spacer(1, 20))
+
+    story.append(_table([
+        ["Certificate ID:", cert_data['cert_id']],
+        ["Certificate Type:", cert_data['cert_type'].upper()],
+        ["Certificate Version:", cert_data['certificate_version']],
+        ["Created:", format_timestamp(cert_data['created_at'])],
+        ["Result:", cert_data['result']],
+        ["Verification URL:", create_clickable_url(cert_data.get('verify_url', 'N/A'), None, small_text_style)]
+    ], colors.lightgrey, 10))
+    story.append(Spacer(1, 20))
+
+    device = cert_data['device']
+    story.append(Paragraph("Device Information", header_style))
+    story.append(_table([
+        ["Model:", device['model']],
+        ["Serial Number:", device['serial']],
+        ["Bus Type:", device['bus']],
+        ["Capacity:", format_bytes_exact(device['capacity_bytes'])],
+        ["Asset Tag:", device.get('asset_tag', 'N/A')],
+    ], colors.lightblue))
+    story.append(Spacer(1, 15))
+
+    policy = cert_data['policy']
+    destruction = cert_data['destruction']
+    particle_size = destruction.get('particle_size_mm')
+    story.append(Paragraph("Destruction Record", header_style))
+    story.append(_table([
+        ["NIST Level:", policy['nist_level']],
+        ["Method:", METHOD_LABELS.get(policy['method'], policy['method'])],
+        ["Reason:", Paragraph(policy.get('reason', 'N/A'), small_text_style)],
+        ["Performed At:", format_timestamp(destruction['performed_at'])],
+        ["Location:", destruction.get('location', 'N/A')],
+        ["Equipment:", destruction.get('equipment', 'N/A')],
+        ["Particle Size:", f"{particle_size} mm" if particle_size is not None else 'N/A'],
+        ["Operator:", destruction['operator']],
+        ["Witness:", destruction['witness']],
+        ["Notes:", Paragraph(destruction.get('notes', 'N/A'), small_text_style)],
+    ], colors.lightgreen))
+    story.append(Spacer(1, 15))
+
+    story.append(Paragraph("Photo Evidence", header_style))
+    photos = cert_data['evidence'].get('photos', [])
+    photo_rows = [["File", "SHA-256", "Size"]]
+    for photo in photos:
+        photo_rows.append([
+            Paragraph(photo['file_name'], small_text_style),
+            Paragraph(format_hash(photo['sha256']), small_text_style),
+            format_bytes(photo['size_bytes'])
+        ])
+    if not photos:
+        photo_rows.append(["No photos attached", "", ""])
+    photo_table = Table(photo_rows, colWidths=[2*inch, 2.5*inch, 1*inch])
+    photo_table.setStyle(TableStyle([
+        ('BACKGROUND', (0, 0), (-1, 0), colors.grey),
+        ('TEXTCOLOR', (0, 0), (-1, 0), colors.whitesmoke),
+        ('FONTNAME', (0, 0), (-1, 0), 'Helvetica-Bold'),
+        ('FONTNAME', (0, 1), (-1, -1), 'Helvetica'),
+        ('FONTSIZE', (0, 0), (-1, -1), 8),
+        ('GRID', (0, 0), (-1, -1), 1, colors.black),
+        ('VALIGN', (0, 0), (-1, -1), 'TOP'),
+    ]))
+    story.append(photo_table)
+    story.append(Spacer(1, 15))
+
+    linkage = cert_data.get('linkage', {})
+    if linkage:
+        story.append(Paragraph("Linkage", header_style))
+        story.append(_table([
+            ["Backup Certificate:", linkage.get('backup_cert_id', 'N/A')],
+            ["Wipe Certificate:", linkage.get('wipe_cert_id', 'N/A')],
+        ], colors.lavender, 8))
+        story.append(Spacer(1, 15))
+
+    story.append(Paragraph("Digital Signature & Verification", header_style))
+    qr_path = generate_qr_code(cert_data)
+    sig_table = Table([
+        ["Algorithm:", cert_data['signature']['alg']],
+        ["Public Key ID:", cert_data['signature']['pubkey_id']],
+        ["Signature:", Paragraph(format_hash(cert_data['signature']['sig'], 30), small_text_style)],
+        ["Issuer:", f"{cert_data['issuer']['organization']} ({cert_data['issuer'].get('country', 'N/A')})"]
+    ], colWidths=[2*inch, 2.5*inch])
+    sig_table.setStyle(TableStyle([
+        ('BACKGROUND', (0, 0), (0, -1), colors.mistyrose),
+        ('TEXTCOLOR', (0, 0), (-1, -1), colors.black),
+        ('ALIGN', (0, 0), (-1, -1), 'LEFT'),
+        ('FONTNAME', (0, 0), (-1, -1), 'Helvetica'),
+        ('FONTSIZE', (0, 0), (-1, -1), 8),
+        ('GRID', (0, 0), (-1, -1), 1, colors.black),
+    ]))
+    layout_table = Table([[sig_table, Image(qr_path, width=1.5*inch, height=1.5*inch)]],
+                         colWidths=[4.5*inch, 1.5*inch])
+    layout_table.setStyle(TableStyle([('VALIGN', (0, 0), (-1, -1), 'TOP')]))
+    story.append(layout_table)
+    story.append(Spacer(1, 20))
+
+    footer_text = f"Generated by {cert_data['issuer']['tool_name']} {cert_data['issuer']['tool_version']} | " \
+                  f"Environment: {cert_data['environment']['os_kernel']} | " \
+                  f"Clock: {describe_clock(cert_data['environment'])}"
+    story.append(Paragraph(footer_text, styles['Normal']))
+
+    doc.build(story)
+
+    try:
+        os.unlink(qr_path)
+    except OSError:
+        pass
+
+
+def main():
+    print("Testing SecureWipe Destroy Certificate PDF Generation")
+    print("=" * 50)
+
+    schema = load_schema()
+    cert_data = create_sample_destroy_certificate()
+
+    try:
+        validate(instance=cert_data, schema=schema)
+        print("Certificate validation: PASS")
+    except ValidationError as e:
+        print(f"Schema validation failed: {e.message}")
+        print("Certificate validation: FAIL")
+        return False
+
+    output_path = "/tmp/test_destroy_certificate.pdf"
+    try:
+        create_destroy_certificate_pdf(cert_data, output_path)
+        print(f"PDF generated successfully: {output_path}")
+        print(f"File size: {os.path.getsize(output_path)} bytes")
+        print("=" * 50)
+        print("Test Result: SUCCESS")
+        return True
+    except Exception as e:
+        print(f"PDF generation failed: {e}")
+        print("=" * 50)
+        print("Test Result: FAILED")
+        return False
+
+
+if __name__ == "__main__":
+    success = main()
+    sys.exit(0 if success else 1)
@@ -9,6 +9,7 @@ import tempfile
 import os
 from datetime import datetime
 from pathlib import Path
+from xml.sax.saxutils import escape
 import jsonschema
 from reportlab.lib.pagesizes import letter, A4
 from reportlab.platypus import SimpleDocTemplate, Table, TableStyle, Paragraph, Spacer, Image
@@ -20,6 +21,8 @@ import qrcode
 from io import BytesIO
 import base64
 
+from pdf_formatting import format_bytes_exact, format_number, format_timestamp
+
 
 def wrap_long_text(text, max_length=50):
     """Wrap long text to fit in table cells"""
@@ -64,6 +67,22 @@ def create_clickable_url(url, display_text=None, style=None):
     return Paragraph(link_text, style)
 
 
+def describe_clock(environment):
+    """One-line summary of the clock state recorded with the certificate"""
+    source = environment.get('time_source')
+    if not source:
+        return "N/A"
+    parts = [source['clock_source']]
+    if 'synchronized' in source:
+        parts.append("synchronized" if source['synchronized'] else "NOT synchronized")
+    if 'offset_ms' in source:
+        sign = "-" if source['offset_ms'] < 0 else "+"
+        parts.append(f"offset {sign}{format_number(abs(source['offset_ms']), 3)} ms")
+    if 'monotonic' in source:
+        parts.append(f"monotonic duration {format_number(source['monotonic']['duration_ms'] / 1000, 1)} s")
+    return ", ".join(parts)
+
+
 class BackupCertificatePDFGenerator:
     """Generate PDF certificates from backup certificate JSON matching the new schema."""
     
@@ -136,9 +155,11 @@ class BackupCertificatePDFGenerator:
         
         # Header
         story.append(Paragraph("SecureWipe Backup Certificate", title_style))
+        if cert_data.get('serial_number'):
+            story.append(Paragraph(f"Certificate No. {cert_data['serial_number']}", heading_style))
         story.append(Paragraph(f"Certificate ID: {cert_data['cert_id']}", styles['Normal']))
         story.append(Paragraph(f"Version: {cert_data['certificate_version']}", styles['Normal']))
-        story.append(Paragraph(f"Issued: {cert_data['created_at']}", styles['Normal']))
+        story.append(Paragraph(f"Issued: {format_timestamp(cert_data['created_at'])}", styles['Normal']))
         story.append(Spacer(1, 20))
         
         # Result Badge
@@ -174,7 +195,7 @@ class BackupCertificatePDFGenerator:
             ['Model', cert_data['device']['model']],
             ['Serial', cert_data['device']['serial']],
             ['Bus Type', cert_data['device']['bus']],
-            ['Capacity', f"{cert_data['device']['capacity_bytes']:,} bytes"],
+            ['Capacity', format_bytes_exact(cert_data['device']['capacity_bytes'])],
             ['Path', cert_data['device'].get('path', 'N/A')]
         ]
         device_table = Table(device_data, colWidths=[2*inch, 4*inch])
@@ -193,8 +214,8 @@ class BackupCertificatePDFGenerator:
         # Backup Summary
         story.append(Paragraph("Backup Summary", heading_style))
         backup_data = [
-            ['Files Count', f"{cert_data['files_summary']['count']:,}"],
-            ['Personal Data', f"{cert_data['files_summary']['personal_bytes']:,} bytes"],
+            ['Files Count', format_number(cert_data['files_summary']['count'])],
+            ['Personal Data', format_bytes_exact(cert_data['files_summary']['personal_bytes'])],
             ['Destination Type', cert_data['destination']['type'].upper()],
             ['Destination Label', cert_data['destination'].get('label', 'N/A')],
             ['File System', cert_data['destination'].get('fs', 'N/A')]
@@ -250,8 +271,12 @@ class BackupCertificatePDFGenerator:
             ['Operator', cert_data['environment']['operator']],
             ['OS Kernel', cert_data['environment']['os_kernel']],
             ['Tool Version', cert_data['environment']['tool_version']],
-            ['Containerized', str(cert_data['environment'].get('containerized', 'N/A'))]
+            ['Containerized', str(cert_data['environment'].get('containerized', 'N/A'))],
+            ['Clock', describe_clock(cert_data['environment'])]
         ]
+        operator_note = cert_data.get('metadata', {}).get('operator_note')
+        if operator_note:
+            env_data.append(['Operator Note', Paragraph(escape(operator_note), styles['Normal'])])
         env_table = Table(env_data, colWidths=[2*inch, 4*inch])
         env_table.setStyle(TableStyle([
             ('BACKGROUND', (0, 0), (0, -1), colors.lightgrey),
@@ -268,8 +293,9 @@ class BackupCertificatePDFGenerator:
         # QR Code for verification
         story.append(Paragraph("Certificate Verification", heading_style))
         
-        # Create QR data - either verify_url or cert_id
-        qr_data = cert_data.get('verify_url', f"cert_id:{cert_data['cert_id']}")
+        # Create QR data - the signed offline payload, else verify_url or cert_id
+        qr_data = cert_data.get('signature', {}).get('qr') \
+            or cert_data.get('verify_url', f"cert_id:{cert_data['cert_id']}")
         qr_image = self.generate_qr_code(qr_data)
         
         # Center the QR code
@@ -0,0 +1,225 @@
+#!/usr/bin/env python3
+"""
+SecureWipe System Sanitization Certificate PDF Generation Test
+
+Renders the one-per-machine certificate: the host's identifiers, a row per drive
+linking to that drive's own wipe or destroy certificate, and the usual QR code and
+signature block.
+"""
+
+import json
+import sys
+import os
+from pathlib import Path
+
+try:
+    from jsonschema import validate, ValidationError
+except ImportError:
+    print("❌ Missing dependency: jsonschema")
+    print("   Install with: pip install jsonschema")
+    sys.exit(1)
+
+try:
+    from reportlab.lib.pagesizes import A4
+    from reportlab.lib.styles import getSampleStyleSheet, ParagraphStyle
+    from reportlab.lib.units import inch
+    from reportlab.lib import colors
+    from reportlab.platypus import SimpleDocTemplate, Table, TableStyle, Paragraph, Spacer, Image
+    from reportlab.lib.enums import TA_CENTER
+except ImportError:
+    print("❌ Missing dependency: reportlab")
+    print("   Install with: pip install reportlab")
+    sys.exit(1)
+
+from test_wipe_pdf_certificates import generate_qr_code, format_bytes, format_hash, create_clickable_url, describe_clock
+from test_destroy_pdf_certificates import _table
+from pdf_formatting import format_timestamp
+
+PROJECT_ROOT = Path(__file__).parent.parent
+SCHEMA_PATH = PROJECT_ROOT / "certs" / "schemas" / "system_schema.json"
+
+RESULT_COLORS = {
+    "PASS": colors.green,
+    "PASS_WITH_EXCEPTIONS": colors.orange,
+    "FAIL": colors.red,
+    "DESTROYED": colors.green,
+}
+
+
+def load_schema():
+    """Load the system certificate JSON schema"""
+    with open(SCHEMA_PATH, 'r') as f:
+        return json.load(f)
+
+
+def create_sample_system_certificate():
+    """Use the example shipped with the schema as the sample certificate"""
+    return load_schema()["examples"][0]
+
+
+def create_system_certificate_pdf(cert_data, output_path):
+    """Generate PDF certificate for a whole machine and its drives"""
+    doc = SimpleDocTemplate(output_path, pagesize=A4,
+                          rightMargin=72, leftMargin=72,
+                          topMargin=72, bottomMargin=18)
+
+    styles = getSampleStyleSheet()
+    story = []
+
+    title_style = ParagraphStyle(
+        'CustomTitle',
+        parent=styles['Heading1'],
+        fontSize=24,
+        spaceAfter=30,
+        alignment=TA_CENTER,
+        textColor=colors.darkblue
+    )
+    header_style = ParagraphStyle(
+        'CustomHeader',
+        parent=styles['Heading2'],
+        fontSize=14,
+        spaceAfter=12,
+        textColor=colors.darkblue
+    )
+    small_text_style = ParagraphStyle(
+        'SmallText',
+        parent=styles['Normal'],
+        fontSize=8,
+        wordWrap='LTR'
+    )
+
+    story.append(Paragraph("SecureWipe System Sanitization Certificate", title_style))
+    story.append(Paragraph(f"NIST SP 800-88 {cert_data['policy']['nist_level']}", styles['Heading3']))
+    if cert_data.get('serial_number'):
+        story.append(Paragraph(f"Certificate No. {cert_data['serial_number']}", header_style))
+    story.append(Spacer(1, 20))
+
+    story.append(_table([
+        ["Certificate ID:", cert_data['cert_id']],
+        ["Certificate Type:", cert_data['cert_type'].upper()],
+        ["Certificate Version:", cert_data['certificate_version']],
+        ["Created:", format_timestamp(cert_data['created_at'])],
+        ["Result:", cert_data['result']],
+        ["Verification URL:", create_clickable_url(cert_data.get('verify_url', 'N/A'), None, small_text_style)]
+    ], colors.lightgrey, 10))
+    story.append(Spacer(1, 20))
+
+    host = cert_data['host']
+    story.append(Paragraph("System Information", header_style))
+    story.append(_table([
+        ["Hostname:", host['hostname']],
+        ["Manufacturer:", host.get('system_manufacturer', 'N/A')],
+        ["Model:", host.get('system_model', 'N/A')],
+        ["Chassis Serial:", host.get('chassis_serial', 'N/A')],
+        ["System Serial:", host.get('system_serial', 'N/A')],
+        ["System UUID:", host.get('system_uuid', 'N/A')],
+        ["Machine ID:", host.get('machine_id', 'N/A')],
+        ["Asset Tag:", host.get('asset_tag', 'N/A')],
+        ["BIOS:", " ".join(host[k] for k in ('bios_vendor', 'bios_version', 'bios_date') if host.get(k)) or 'N/A'],
+        ["Sanitization Level:", cert_data['policy']['nist_level']],
+    ], colors.lightblue))
+    story.append(Spacer(1, 15))
+
+    story.append(Paragraph("Drives", header_style))
+    drive_rows = [["Drive", "Certificate", "Method", "Result"]]
+    for component in cert_data['components']:
+        device = component['device']
+        drive_rows.append([
+            Paragraph(f"{device['model']}<br/>S/N {device['serial']}<br/>"
+                      f"{device['bus']}, {format_bytes(device['capacity_bytes'])}", small_text_style),
+            Paragraph(f"{component['cert_id']}<br/>{component.get('serial_number', '')}<br/>"
+                      f"SHA-256 {format_hash(component['certificate_sha256'])}", small_text_style),
+            Paragraph(f"{component['nist_level']}<br/>{component['method']}", small_text_style),
+            component['result'],
+        ])
+    drive_table = Table(drive_rows, colWidths=[1.8*inch, 2.1*inch, 1.2*inch, 1*inch])
+    drive_style = [
+        ('BACKGROUND', (0, 0), (-1, 0), colors.grey),
+        ('TEXTCOLOR', (0, 0), (-1, 0), colors.whitesmoke),
+        ('FONTNAME', (0, 0), (-1, 0), 'Helvetica-Bold'),
+        ('FONTNAME', (0, 1), (-1, -1), 'Helvetica'),
+        ('FONTSIZE', (0, 0), (-1, -1), 8),
+        ('GRID', (0, 0), (-1, -1), 1, colors.black),
+        ('VALIGN', (0, 0), (-1, -1), 'TOP'),
+    ]
+    for row, component in enumerate(cert_data['components'], start=1):
+        drive_style.append(('TEXTCOLOR', (3, row), (3, row), RESULT_COLORS.get(component['result'], colors.black)))
+    drive_table.setStyle(TableStyle(drive_style))
+    story.append(drive_table)
+    story.append(Paragraph("Each drive certificate is issued and signed separately; the hash above pins "
+                           "the exact certificate this document covers.", small_text_style))
+    story.append(Spacer(1, 15))
+
+    if cert_data.get('notes'):
+        story.append(Paragraph("Notes", header_style))
+        story.append(Paragraph(cert_data['notes'], small_text_style))
+        story.append(Spacer(1, 15))
+
+    story.append(Paragraph("Digital Signature & Verification", header_style))
+    qr_path = generate_qr_code(cert_data)
+    sig_table = Table([
+        ["Algorithm:", cert_data['signature']['alg']],
+        ["Public Key ID:", cert_data['signature']['pubkey_id']],
+        ["Signature:", Paragraph(format_hash(cert_data['signature']['sig'], 30), small_text_style)],
+        ["Issuer:", f"{cert_data['issuer']['organization']} ({cert_data['issuer'].get('country', 'N/A')})"]
+    ], colWidths=[2*inch, 2.5*inch])
+    sig_table.setStyle(TableStyle([
+        ('BACKGROUND', (0, 0), (0, -1), colors.mistyrose),
+        ('TEXTCOLOR', (0, 0), (-1, -1), colors.black),
+        ('ALIGN', (0, 0), (-1, -1), 'LEFT'),
+        ('FONTNAME', (0, 0), (-1, -1), 'Helvetica'),
+        ('FONTSIZE', (0, 0), (-1, -1), 8),
+        ('GRID', (0, 0), (-1, -1), 1, colors.black),
+    ]))
+    layout_table = Table([[sig_table, Image(qr_path, width=1.5*inch, height=1.5*inch)]],
+                         colWidths=[4.5*inch, 1.5*inch])
+    layout_table.setStyle(TableStyle([('VALIGN', (0, 0), (-1, -1), 'TOP')]))
+    story.append(layout_table)
+    story.append(Spacer(1, 20))
+
+    footer_text = f"Generated by {cert_data['issuer']['tool_name']} {cert_data['issuer']['tool_version']} | " \
+                  f"Environment: {cert_data['environment']['os_kernel']} | " \
+                  f"Clock: {describe_clock(cert_data['environment'])}"
+    story.append(Paragraph(footer_text, styles['Normal']))
+
+    doc.build(story)
+
+    try:
+        os.unlink(qr_path)
+    except OSError:
+        pass
+
+
+def main():
+    print("Testing SecureWipe System Certificate PDF Generation")
+    print("=" * 50)
+
+    schema = load_schema()
+    cert_data = create_sample_system_certificate()
+
+    try:
+        validate(instance=cert_data, schema=schema)
+        print("Certificate validation: PASS")
+    except ValidationError as e:
+        print(f"Schema validation failed: {e.message}")
+        print("Certificate validation: FAIL")
+        return False
+
+    output_path = "/tmp/test_system_certificate.pdf"
+    try:
+        create_system_certificate_pdf(cert_data, output_path)
+        print(f"PDF generated successfully: {output_path}")
+        print(f"File size: {os.path.getsize(output_path)} bytes")
+        print("=" * 50)
+        print("Test Result: SUCCESS")
+        return True
+    except Exception as e:
+        print(f"PDF generation failed: {e}")
+        print("=" * 50)
+        print("Test Result: FAILED")
+        return False
+
+
+if __name__ == "__main__":
+    success = main()
+    sys.exit(0 if success else 1)
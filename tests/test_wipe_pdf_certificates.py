@@ -15,6 +15,9 @@ import os
 import tempfile
 from datetime import datetime, timezone
 from pathlib import Path
+from xml.sax.saxutils import escape
+
+from pdf_formatting import format_bytes, format_bytes_exact, format_number, format_timestamp
 
 try:
     import jsonschema
@@ -29,7 +32,7 @@ try:
     from reportlab.lib.styles import getSampleStyleSheet, ParagraphStyle
     from reportlab.lib.units import inch
     from reportlab.lib import colors
-    from reportlab.platypus import SimpleDocTemplate, Table, TableStyle, Paragraph, Spacer, Image
+    from reportlab.platypus import SimpleDocTemplate, Table, TableStyle, Paragraph, Spacer, Image, PageBreak
     from reportlab.lib.enums import TA_LEFT, TA_CENTER, TA_RIGHT
     from reportlab.graphics.shapes import Drawing, Rect
     from reportlab.graphics import renderPDF
@@ -159,8 +162,9 @@ def generate_qr_code(data):
     )
     
     if isinstance(data, dict):
-        # Use the verify_url directly if available, otherwise use cert_id
-        qr_text = data.get('verify_url', f"cert_id:{data.get('cert_id', 'N/A')}")
+        # Prefer the signed offline payload, then the verify_url, then the cert_id
+        qr_text = data.get('signature', {}).get('qr') \
+            or data.get('verify_url', f"cert_id:{data.get('cert_id', 'N/A')}")
     else:
         qr_text = str(data)
     
@@ -174,19 +178,6 @@ def generate_qr_code(data):
     img.save(temp_path)
     return temp_path
 
-def format_bytes(bytes_value):
-    """Format bytes into human readable format"""
-    if bytes_value >= 1024**4:
-        return f"{bytes_value / 1024**4:.2f} TB"
-    elif bytes_value >= 1024**3:
-        return f"{bytes_value / 1024**3:.2f} GB" 
-    elif bytes_value >= 1024**2:
-        return f"{bytes_value / 1024**2:.2f} MB"
-    elif bytes_value >= 1024:
-        return f"{bytes_value / 1024:.2f} KB"
-    else:
-        return f"{bytes_value} bytes"
-
 def wrap_long_text(text, max_length=50):
     """Wrap long text to fit in table cells"""
     if len(str(text)) <= max_length:
@@ -229,8 +220,64 @@ def create_clickable_url(url, display_text=None, style=None):
     link_text = f'<link href="{url}">{display_text}</link>'
     return Paragraph(link_text, style)
 
-def create_wipe_certificate_pdf(cert_data, output_path):
-    """Generate professional PDF certificate for wipe operations"""
+def describe_clock(environment):
+    """One-line summary of the clock state recorded with the certificate"""
+    source = environment.get('time_source')
+    if not source:
+        return "N/A"
+    parts = [source['clock_source']]
+    if 'synchronized' in source:
+        parts.append("synchronized" if source['synchronized'] else "NOT synchronized")
+    if 'offset_ms' in source:
+        sign = "-" if source['offset_ms'] < 0 else "+"
+        parts.append(f"offset {sign}{format_number(abs(source['offset_ms']), 3)} ms")
+    if 'monotonic' in source:
+        parts.append(f"monotonic duration {format_number(source['monotonic']['duration_ms'] / 1000, 1)} s")
+    return ", ".join(parts)
+
+def summarize_commands(commands):
+    """One-line count of the executed commands for the certificate page"""
+    failed = sum(1 for cmd in commands if cmd['exit'] != 0)
+    total_ms = sum(cmd['ms'] for cmd in commands)
+    return f"{len(commands)} commands executed, {failed} with non-zero exit, {format_number(total_ms / 1000, 1)} s in total"
+
+def command_log_appendix(commands, header_style, small_text_style):
+    """Appendix A: every executed command with its exit code, elapsed time and output
+    hashes. The table splits across as many pages as it needs, header repeated."""
+    flowables = [PageBreak(), Paragraph("Appendix A: Command Log", header_style)]
+    rows = [["#", "Command", "Exit", "Elapsed (ms)"]]
+    for index, cmd in enumerate(commands, 1):
+        cell = escape(cmd['cmd'])
+        for stream in ('stdout', 'stderr'):
+            digest = cmd.get(f'{stream}_sha256')
+            if digest:
+                cell += f"<br/>{stream} sha256: {digest}"
+        rows.append([str(index), Paragraph(cell, small_text_style), str(cmd['exit']), str(cmd['ms'])])
+
+    table = Table(rows, colWidths=[0.4*inch, 4.1*inch, 0.6*inch, 0.9*inch], repeatRows=1)
+    style = [
+        ('BACKGROUND', (0, 0), (-1, 0), colors.grey),
+        ('TEXTCOLOR', (0, 0), (-1, 0), colors.whitesmoke),
+        ('ALIGN', (0, 0), (-1, -1), 'LEFT'),
+        ('FONTNAME', (0, 0), (-1, 0), 'Helvetica-Bold'),
+        ('FONTNAME', (0, 1), (-1, -1), 'Helvetica'),
+        ('FONTSIZE', (0, 0), (-1, -1), 8),
+        ('GRID', (0, 0), (-1, -1), 1, colors.black),
+        ('VALIGN', (0, 0), (-1, -1), 'TOP'),
+    ]
+    for row, cmd in enumerate(commands, 1):
+        if cmd['exit'] != 0:
+            style.append(('TEXTCOLOR', (2, row), (2, row), colors.red))
+    table.setStyle(TableStyle(style))
+
+    flowables.append(table)
+    flowables.append(Spacer(1, 10))
+    flowables.append(Paragraph(summarize_commands(commands), small_text_style))
+    return flowables
+
+def create_wipe_certificate_pdf(cert_data, output_path, detail='full'):
+    """Generate professional PDF certificate for wipe operations. With detail='full'
+    the executed commands follow as an appendix; 'summary' leaves them out."""
     doc = SimpleDocTemplate(output_path, pagesize=A4,
                           rightMargin=72, leftMargin=72,
                           topMargin=72, bottomMargin=18)
@@ -267,6 +314,8 @@ def create_wipe_certificate_pdf(cert_data, output_path):
     # Title
     story.append(Paragraph("SecureWipe Data Sanitization Certificate", title_style))
     story.append(Paragraph("NIST SP 800-88 Compliant Wipe Operation", styles['Heading3']))
+    if cert_data.get('serial_number'):
+        story.append(Paragraph(f"Certificate No. {cert_data['serial_number']}", styles['Heading2']))
     story.append(Spacer(1, 20))
     
     # Certificate Info Header
@@ -274,7 +323,7 @@ def create_wipe_certificate_pdf(cert_data, output_path):
         ["Certificate ID:", cert_data['cert_id']],
         ["Certificate Type:", cert_data['cert_type'].upper()],
         ["Certificate Version:", cert_data['certificate_version']],
-        ["Created:", cert_data['created_at']],
+        ["Created:", format_timestamp(cert_data['created_at'])],
         ["Result:", cert_data['result']],
         ["Verification URL:", create_clickable_url(cert_data.get('verify_url', 'N/A'), None, small_text_style)]
     ]
@@ -300,7 +349,7 @@ def create_wipe_certificate_pdf(cert_data, output_path):
         ["Model:", cert_data['device']['model']],
         ["Serial Number:", cert_data['device']['serial']],
         ["Bus Type:", cert_data['device']['bus']],
-        ["Capacity:", format_bytes(cert_data['device']['capacity_bytes'])],
+        ["Capacity:", format_bytes_exact(cert_data['device']['capacity_bytes'])],
         ["Device Path:", cert_data['device'].get('path', 'N/A')],
         ["Protocol Path:", cert_data['device'].get('protocol_path', 'N/A')],
         ["Firmware:", cert_data['device'].get('firmware', 'N/A')]
@@ -319,6 +368,30 @@ def create_wipe_certificate_pdf(cert_data, output_path):
     story.append(device_table)
     story.append(Spacer(1, 15))
     
+    # Host the drive was wiped in
+    host = cert_data.get('host')
+    if host:
+        story.append(Paragraph("Host System", header_style))
+        host_data = [
+            ["Hostname:", host['hostname']],
+            ["Manufacturer:", host.get('system_manufacturer', 'N/A')],
+            ["Model:", host.get('system_model', 'N/A')],
+            ["Chassis Serial:", host.get('chassis_serial', 'N/A')],
+            ["System Serial:", host.get('system_serial', 'N/A')],
+            ["BIOS:", " ".join(host[k] for k in ('bios_vendor', 'bios_version', 'bios_date') if host.get(k)) or 'N/A']
+        ]
+        host_table = Table(host_data, colWidths=[2*inch, 3.5*inch])
+        host_table.setStyle(TableStyle([
+            ('BACKGROUND', (0, 0), (0, -1), colors.lightblue),
+            ('TEXTCOLOR', (0, 0), (-1, -1), colors.black),
+            ('ALIGN', (0, 0), (-1, -1), 'LEFT'),
+            ('FONTNAME', (0, 0), (-1, -1), 'Helvetica'),
+            ('FONTSIZE', (0, 0), (-1, -1), 9),
+            ('GRID', (0, 0), (-1, -1), 1, colors.black),
+        ]))
+        story.append(host_table)
+        story.append(Spacer(1, 15))
+    
     # Wipe Policy
     story.append(Paragraph("Sanitization Policy", header_style))
     policy_data = [
@@ -365,32 +438,12 @@ def create_wipe_certificate_pdf(cert_data, output_path):
     story.append(hpa_table)
     story.append(Spacer(1, 15))
     
-    # Commands Executed
+    # Commands Executed (the full log is Appendix A)
     story.append(Paragraph("Commands Executed", header_style))
-    cmd_headers = [["Command", "Exit Code", "Duration (ms)"]]
-    cmd_data = []
-    for cmd in cert_data['commands']:
-        # Use Paragraph for command text to enable proper wrapping
-        cmd_text = Paragraph(wrap_long_text(cmd['cmd'], 45), small_text_style)
-        cmd_data.append([
-            cmd_text,
-            str(cmd['exit']),
-            str(cmd['ms'])
-        ])
-    
-    cmd_table = Table(cmd_headers + cmd_data, colWidths=[3*inch, 1*inch, 1.5*inch])
-    cmd_table.setStyle(TableStyle([
-        ('BACKGROUND', (0, 0), (-1, 0), colors.grey),
-        ('TEXTCOLOR', (0, 0), (-1, 0), colors.whitesmoke),
-        ('ALIGN', (0, 0), (-1, -1), 'LEFT'),
-        ('FONTNAME', (0, 0), (-1, 0), 'Helvetica-Bold'),
-        ('FONTNAME', (0, 1), (-1, -1), 'Helvetica'),
-        ('FONTSIZE', (0, 0), (-1, -1), 8),
-        ('GRID', (0, 0), (-1, -1), 1, colors.black),
-        ('VALIGN', (0, 0), (-1, -1), 'TOP'),
-    ]))
-    
-    story.append(cmd_table)
+    cmd_summary = summarize_commands(cert_data['commands'])
+    if detail == 'full':
+        cmd_summary += " (full log in Appendix A)"
+    story.append(Paragraph(cmd_summary, styles['Normal']))
     story.append(Spacer(1, 15))
     
     # Verification Results
@@ -423,6 +476,9 @@ def create_wipe_certificate_pdf(cert_data, output_path):
         ["Backup Certificate:", cert_data['linkage']['backup_cert_id']],
         ["Certificate Hash:", Paragraph(format_hash(cert_data['metadata'].get('certificate_json_sha256', 'N/A')), small_text_style)]
     ]
+    operator_note = cert_data['metadata'].get('operator_note')
+    if operator_note:
+        evidence_data.append(["Operator Note:", Paragraph(escape(operator_note), small_text_style)])
     
     evidence_table = Table(evidence_data, colWidths=[2*inch, 3.5*inch])
     evidence_table.setStyle(TableStyle([
@@ -441,7 +497,8 @@ def create_wipe_certificate_pdf(cert_data, output_path):
     story.append(Paragraph("Digital Signature & Verification", header_style))
     
     # Generate QR code
-    qr_path = generate_qr_code(cert_data.get('metadata', {}).get('qr_payload', cert_data))
+    qr_path = generate_qr_code(cert_data.get('signature', {}).get('qr')
+                               or cert_data.get('metadata', {}).get('qr_payload', cert_data))
     
     # Create signature table with QR code
     sig_data = [
@@ -477,8 +534,12 @@ def create_wipe_certificate_pdf(cert_data, output_path):
     # Footer
     footer_text = f"Generated by {cert_data['issuer']['tool_name']} v{cert_data['issuer']['tool_version']} | " \
                  f"Environment: {cert_data['environment']['os_kernel']} | " \
-                 f"Operator: {cert_data['environment']['operator']}"
+                 f"Operator: {cert_data['environment']['operator']} | " \
+                 f"Clock: {describe_clock(cert_data['environment'])}"
     story.append(Paragraph(footer_text, styles['Normal']))
+
+    if detail == 'full' and cert_data['commands']:
+        story.extend(command_log_appendix(cert_data['commands'], header_style, small_text_style))
     
     # Build PDF
     doc.build(story)
@@ -0,0 +1,878 @@
+//! Typed certificate documents mirroring `certs/schemas/backup_schema.json` and
+//! `certs/schemas/wipe_schema.json`.
+//!
+//! Field names and renames follow the schemas exactly; optional schema fields are
+//! `Option`s that are left out of the JSON when unset. Nested records whose shape
+//! is owned by the core engine (verification replay, histogram, pattern readback,
+//! identity proof, approval) stay as `serde_json::Value` so this crate needs no
+//! engine code.
+
+use crate::plan::MethodDecision;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+pub const CERTIFICATE_VERSION: &str = "v1.0.0";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum CertificateResult {
+    #[serde(rename = "PASS")]
+    Pass,
+    /// Wipe certificates only: verified, but some regions could not be overwritten and
+    /// site policy accepted them; `exceptions` lists the regions
+    #[serde(rename = "PASS_WITH_EXCEPTIONS")]
+    PassWithExceptions,
+    #[serde(rename = "FAIL")]
+    Fail,
+}
+
+impl CertificateResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CertificateResult::Pass => "PASS",
+            CertificateResult::PassWithExceptions => "PASS_WITH_EXCEPTIONS",
+            CertificateResult::Fail => "FAIL",
+        }
+    }
+
+    pub fn from_passed(passed: bool) -> Self {
+        if passed {
+            CertificateResult::Pass
+        } else {
+            CertificateResult::Fail
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CertificateSignature {
+    pub alg: String, // "Ed25519"
+    pub pubkey_id: String, // "sih_root_v1"
+    pub sig: String, // Base64 signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonicalization: Option<String>, // "RFC8785_JSON" or "deterministic_minified"
+    /// Signed QR payload (`SW1:...`) for offline verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qr: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Issuer {
+    pub organization: String,
+    pub tool_name: String,
+    pub tool_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BusType {
+    #[serde(rename = "SATA")]
+    Sata,
+    #[serde(rename = "NVMe")]
+    Nvme,
+    #[serde(rename = "USB")]
+    Usb,
+    #[serde(rename = "SAS")]
+    Sas,
+    #[serde(rename = "VIRTIO")]
+    Virtio,
+    /// iSCSI, NBD, Ceph RBD or NVMe over Fabrics: the storage is on another machine
+    #[serde(rename = "NETWORK")]
+    Network,
+    // Anything lsblk reports that the schema doesn't know about
+    #[serde(rename = "UNKNOWN", other)]
+    Unknown,
+}
+
+impl BusType {
+    /// Map an lsblk TRAN value (or an already normalised bus name) to the schema enum
+    pub fn from_transport(tran: &str) -> Self {
+        match tran.to_lowercase().as_str() {
+            "sata" | "ata" => BusType::Sata,
+            "nvme" => BusType::Nvme,
+            "usb" => BusType::Usb,
+            "sas" => BusType::Sas,
+            "virtio" => BusType::Virtio,
+            "iscsi" | "nbd" | "rbd" | "network" => BusType::Network,
+            _ => BusType::Unknown,
+        }
+    }
+}
+
+/// Whether a certificate's device is hardware or storage a hypervisor or cloud provides
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaType {
+    Physical,
+    /// A disk image file or a hypervisor/cloud volume; only overwritten, never sanitized
+    Virtual,
+}
+
+/// The drive a certificate is about. `protocol_path` only exists in the wipe schema.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CertificateDevice {
+    pub model: String,
+    pub serial: String,
+    pub bus: BusType,
+    pub capacity_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logical_block_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_lbas: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firmware: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<MediaType>,
+    /// Stable drive identity, as in discovery output and the inventory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Environment {
+    pub operator: String,
+    pub os_kernel: String,
+    pub tool_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_firmware: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub containerized: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_source: Option<TimeSource>,
+    /// Write check of the backup destination made before copying
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_canary: Option<DestinationCanary>,
+}
+
+/// The machine a certificate was issued on: its hostname, systemd machine ID and
+/// SMBIOS identity. Fields the firmware leaves blank or fills with placeholders are
+/// omitted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HostInfo {
+    pub hostname: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_manufacturer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_serial: Option<String>,
+    /// Usually the serial printed on the case label
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chassis_serial: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bios_vendor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bios_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bios_date: Option<String>,
+}
+
+/// A small file written to the backup destination with synchronous (and, where the
+/// filesystem allows, direct) I/O and read back before a backup starts, to catch
+/// read-only or silently failing media up front
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DestinationCanary {
+    pub passed: bool,
+    pub bytes: u64,
+    /// Whether the write and read bypassed the page cache (O_DIRECT)
+    pub direct_io: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// State of the system clock when the certificate was issued. `created_at` comes
+/// from that clock, so this is what a timestamp dispute has to go on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimeSource {
+    /// Service disciplining the clock: "chrony", "systemd-timesyncd", "none" or "unknown"
+    pub clock_source: String,
+    /// Whether that service reported the clock as synchronized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub synchronized: Option<bool>,
+    /// NTP server the clock was following
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// System clock minus reference time, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset_ms: Option<f64>,
+    /// Kernel boot id; monotonic readings only compare within the same boot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boot_id: Option<String>,
+    /// The operation as measured on the monotonic clock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monotonic: Option<MonotonicSpan>,
+}
+
+/// Start and end of an operation on `CLOCK_MONOTONIC` (milliseconds since boot),
+/// which changes to the wall clock don't move
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MonotonicSpan {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Exceptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// How much of the data a verification pass looked at
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Coverage {
+    Percent { percent: f64 },
+    Samples { samples: u64 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStrategy {
+    RandomSectors,
+    FullReadback,
+    ControllerStatus,
+    /// Backup certificates only
+    SampledFiles,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct QrPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<CertificateResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nist_level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256_cert_json: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sig: Option<String>,
+}
+
+// Backup certificate sections
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FilesSummary {
+    pub count: u64,
+    pub personal_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub included_paths: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded_paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DestinationType {
+    Usb,
+    Nas,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupDestination {
+    #[serde(rename = "type")]
+    pub kind: DestinationType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mountpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyManagement {
+    EphemeralSessionKey,
+    ExternalKms,
+    UserProvidedKey,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupCrypto {
+    pub alg: String, // "AES-256-CTR"
+    pub manifest_sha256: String,
+    pub key_management: KeyManagement,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupVerification {
+    pub strategy: VerificationStrategy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<Coverage>,
+    pub failures: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupPolicy {
+    pub name: String,
+    pub version: String,
+}
+
+/// What an operation took, for reporting and billing. `energy_wh` is an estimate: the
+/// wall time at a typical power draw for the drive's bus, not a measurement.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct CostSummary {
+    pub wall_time_ms: u64,
+    pub bytes_processed: u64,
+    pub throughput_bytes_per_sec: u64,
+    /// Power draw the estimate assumes
+    pub assumed_watts: f64,
+    pub energy_wh: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BackupMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_json_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qr_payload: Option<QrPayload>,
+    /// Set with `backup --note`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<CostSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupCertificate {
+    pub cert_id: String,
+    /// Issuer-scoped sequential number, e.g. `SIH-2025-000123`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    pub cert_type: String, // "backup"
+    pub certificate_version: String,
+    pub created_at: String,
+    pub issuer: Issuer,
+    pub device: CertificateDevice,
+    pub files_summary: FilesSummary,
+    pub destination: BackupDestination,
+    pub crypto: BackupCrypto,
+    pub verification: BackupVerification,
+    pub policy: BackupPolicy,
+    pub result: CertificateResult,
+    pub environment: Environment,
+    pub exceptions: Exceptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<CertificateSignature>,
+    pub metadata: BackupMetadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_url: Option<String>,
+}
+
+// Wipe certificate sections
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WipePolicyRecord {
+    pub nist_level: String, // "CLEAR", "PURGE" or "DESTROY"
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_mapping: Option<String>,
+    /// Candidates considered for `method` and why the others were passed over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method_decision: Option<MethodDecision>,
+    /// Overwrite profile and the passes it wrote (overwrite method only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct HpaDco {
+    /// True only when the re-read after the clear step showed the whole native capacity
+    pub cleared: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commands: Option<Vec<String>>,
+    /// `hdparm -N` readout before the clear step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<MaxSectors>,
+    /// `hdparm -N` readout after the clear step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<MaxSectors>,
+    /// DCO "Real max sectors", when the drive reports a device configuration overlay
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dco_real_max: Option<u64>,
+    /// PASS when the re-read confirmed the clear, FAIL when hidden sectors remained;
+    /// absent when the drive's max sectors couldn't be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<CertificateResult>,
+}
+
+/// Current (visible) and native max sectors as reported by `hdparm -N`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MaxSectors {
+    pub current: u64,
+    pub native: u64,
+}
+
+impl MaxSectors {
+    /// A host protected area hides the sectors between `current` and `native`
+    pub fn hpa_active(&self) -> bool {
+        self.current < self.native
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandRecord {
+    pub cmd: String,
+    pub exit: i32,
+    pub ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdout_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResidualSignature {
+    pub offset: u64,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WipeVerification {
+    pub strategy: VerificationStrategy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<Coverage>,
+    pub failures: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<CertificateResult>,
+    /// Serialized `securewipe::VerificationRecord`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<Value>,
+    /// Serialized `securewipe::VerificationHistogram`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub residual_signatures: Option<Vec<ResidualSignature>>,
+    /// Serialized `securewipe::PatternVerification`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Evidence {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_snapshot_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nvme_identify_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nvme_sanitize_status_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs_sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Linkage {
+    pub backup_cert_id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WipeMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_json_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qr_payload: Option<QrPayload>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_status: Option<String>,
+    /// Set with `wipe --note`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<CostSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WipeCertificate {
+    pub cert_id: String,
+    /// Issuer-scoped sequential number, e.g. `SIH-2025-000123`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    pub cert_type: String, // "wipe"
+    pub certificate_version: String,
+    pub created_at: String,
+    pub issuer: Issuer,
+    pub device: CertificateDevice,
+    pub policy: WipePolicyRecord,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hpa_dco: Option<HpaDco>,
+    pub commands: Vec<CommandRecord>,
+    pub verify: WipeVerification,
+    pub result: CertificateResult,
+    pub environment: Environment,
+    /// The machine the drive was wiped in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<HostInfo>,
+    pub evidence: Evidence,
+    /// Serialized before/after controller identity comparison
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_proof: Option<Value>,
+    /// Serialized `securewipe::SmartDelta`: the drive's write counter around the wipe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_delta: Option<Value>,
+    /// Serialized `securewipe::Approval` for HIGH/CRITICAL devices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linkage: Option<Linkage>,
+    pub exceptions: Exceptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<CertificateSignature>,
+    pub metadata: WipeMetadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_url: Option<String>,
+}
+
+/// A builder was asked to `build()` before a required section was set
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingField(pub &'static str);
+
+impl fmt::Display for MissingField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "certificate field '{}' is required", self.0)
+    }
+}
+
+impl std::error::Error for MissingField {}
+
+fn required<T>(value: Option<T>, field: &'static str) -> Result<T, MissingField> {
+    value.ok_or(MissingField(field))
+}
+
+/// Assembles a [`BackupCertificate`]; `exceptions` and `metadata` default to empty
+#[derive(Debug, Clone, Default)]
+pub struct BackupCertificateBuilder {
+    cert_id: Option<String>,
+    created_at: Option<String>,
+    issuer: Option<Issuer>,
+    device: Option<CertificateDevice>,
+    files_summary: Option<FilesSummary>,
+    destination: Option<BackupDestination>,
+    crypto: Option<BackupCrypto>,
+    verification: Option<BackupVerification>,
+    policy: Option<BackupPolicy>,
+    result: Option<CertificateResult>,
+    environment: Option<Environment>,
+    exceptions: Exceptions,
+    signature: Option<CertificateSignature>,
+    metadata: BackupMetadata,
+    verify_url: Option<String>,
+}
+
+impl BackupCertificateBuilder {
+    pub fn new(cert_id: impl Into<String>, created_at: impl Into<String>) -> Self {
+        Self {
+            cert_id: Some(cert_id.into()),
+            created_at: Some(created_at.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn issuer(mut self, issuer: Issuer) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    pub fn device(mut self, device: CertificateDevice) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    pub fn files_summary(mut self, files_summary: FilesSummary) -> Self {
+        self.files_summary = Some(files_summary);
+        self
+    }
+
+    pub fn destination(mut self, destination: BackupDestination) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    pub fn crypto(mut self, crypto: BackupCrypto) -> Self {
+        self.crypto = Some(crypto);
+        self
+    }
+
+    pub fn verification(mut self, verification: BackupVerification) -> Self {
+        self.verification = Some(verification);
+        self
+    }
+
+    pub fn policy(mut self, policy: BackupPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn result(mut self, result: CertificateResult) -> Self {
+        self.result = Some(result);
+        self
+    }
+
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    pub fn exceptions(mut self, exceptions: Exceptions) -> Self {
+        self.exceptions = exceptions;
+        self
+    }
+
+    pub fn signature(mut self, signature: CertificateSignature) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
+    pub fn metadata(mut self, metadata: BackupMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn verify_url(mut self, verify_url: impl Into<String>) -> Self {
+        self.verify_url = Some(verify_url.into());
+        self
+    }
+
+    pub fn build(self) -> Result<BackupCertificate, MissingField> {
+        Ok(BackupCertificate {
+            cert_id: required(self.cert_id, "cert_id")?,
+            serial_number: None,
+            cert_type: "backup".to_string(),
+            certificate_version: CERTIFICATE_VERSION.to_string(),
+            created_at: required(self.created_at, "created_at")?,
+            issuer: required(self.issuer, "issuer")?,
+            device: required(self.device, "device")?,
+            files_summary: required(self.files_summary, "files_summary")?,
+            destination: required(self.destination, "destination")?,
+            crypto: required(self.crypto, "crypto")?,
+            verification: required(self.verification, "verification")?,
+            policy: required(self.policy, "policy")?,
+            result: required(self.result, "result")?,
+            environment: required(self.environment, "environment")?,
+            exceptions: self.exceptions,
+            signature: self.signature,
+            metadata: self.metadata,
+            verify_url: self.verify_url,
+        })
+    }
+}
+
+/// Assembles a [`WipeCertificate`]; `commands`, `evidence`, `exceptions` and
+/// `metadata` default to empty
+#[derive(Debug, Clone, Default)]
+pub struct WipeCertificateBuilder {
+    cert_id: Option<String>,
+    created_at: Option<String>,
+    issuer: Option<Issuer>,
+    device: Option<CertificateDevice>,
+    policy: Option<WipePolicyRecord>,
+    hpa_dco: Option<HpaDco>,
+    commands: Vec<CommandRecord>,
+    verify: Option<WipeVerification>,
+    result: Option<CertificateResult>,
+    environment: Option<Environment>,
+    host: Option<HostInfo>,
+    evidence: Evidence,
+    identity_proof: Option<Value>,
+    smart_delta: Option<Value>,
+    approval: Option<Value>,
+    linkage: Option<Linkage>,
+    exceptions: Exceptions,
+    signature: Option<CertificateSignature>,
+    metadata: WipeMetadata,
+    verify_url: Option<String>,
+}
+
+impl WipeCertificateBuilder {
+    pub fn new(cert_id: impl Into<String>, created_at: impl Into<String>) -> Self {
+        Self {
+            cert_id: Some(cert_id.into()),
+            created_at: Some(created_at.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn issuer(mut self, issuer: Issuer) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    pub fn device(mut self, device: CertificateDevice) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    pub fn policy(mut self, policy: WipePolicyRecord) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn hpa_dco(mut self, hpa_dco: HpaDco) -> Self {
+        self.hpa_dco = Some(hpa_dco);
+        self
+    }
+
+    pub fn commands(mut self, commands: Vec<CommandRecord>) -> Self {
+        self.commands = commands;
+        self
+    }
+
+    pub fn verify(mut self, verify: WipeVerification) -> Self {
+        self.verify = Some(verify);
+        self
+    }
+
+    pub fn result(mut self, result: CertificateResult) -> Self {
+        self.result = Some(result);
+        self
+    }
+
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    pub fn host(mut self, host: HostInfo) -> Self {
+        self.host = Some(host);
+        self
+    }
+
+    pub fn evidence(mut self, evidence: Evidence) -> Self {
+        self.evidence = evidence;
+        self
+    }
+
+    pub fn identity_proof(mut self, identity_proof: Value) -> Self {
+        self.identity_proof = Some(identity_proof);
+        self
+    }
+
+    pub fn smart_delta(mut self, smart_delta: Value) -> Self {
+        self.smart_delta = Some(smart_delta);
+        self
+    }
+
+    pub fn approval(mut self, approval: Value) -> Self {
+        self.approval = Some(approval);
+        self
+    }
+
+    pub fn linkage(mut self, backup_cert_id: impl Into<String>) -> Self {
+        self.linkage = Some(Linkage { backup_cert_id: backup_cert_id.into() });
+        self
+    }
+
+    pub fn exceptions(mut self, exceptions: Exceptions) -> Self {
+        self.exceptions = exceptions;
+        self
+    }
+
+    pub fn signature(mut self, signature: CertificateSignature) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
+    pub fn metadata(mut self, metadata: WipeMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn verify_url(mut self, verify_url: impl Into<String>) -> Self {
+        self.verify_url = Some(verify_url.into());
+        self
+    }
+
+    pub fn build(self) -> Result<WipeCertificate, MissingField> {
+        Ok(WipeCertificate {
+            cert_id: required(self.cert_id, "cert_id")?,
+            serial_number: None,
+            cert_type: "wipe".to_string(),
+            certificate_version: CERTIFICATE_VERSION.to_string(),
+            created_at: required(self.created_at, "created_at")?,
+            issuer: required(self.issuer, "issuer")?,
+            device: required(self.device, "device")?,
+            policy: required(self.policy, "policy")?,
+            hpa_dco: self.hpa_dco,
+            commands: self.commands,
+            verify: required(self.verify, "verify")?,
+            result: required(self.result, "result")?,
+            environment: required(self.environment, "environment")?,
+            host: self.host,
+            evidence: self.evidence,
+            identity_proof: self.identity_proof,
+            smart_delta: self.smart_delta,
+            approval: self.approval,
+            linkage: self.linkage,
+            exceptions: self.exceptions,
+            signature: self.signature,
+            metadata: self.metadata,
+            verify_url: self.verify_url,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_reports_missing_section() {
+        let err = WipeCertificateBuilder::new("WPE_1", "2024-01-15T11:45:30+05:30")
+            .issuer(Issuer {
+                organization: "SecureWipe (SIH)".to_string(),
+                tool_name: "securewipe".to_string(),
+                tool_version: "v1.0.0".to_string(),
+                country: None,
+            })
+            .build()
+            .unwrap_err();
+        assert_eq!(err, MissingField("device"));
+    }
+
+    #[test]
+    fn test_unknown_bus_and_coverage_tags() {
+        assert_eq!(serde_json::from_str::<BusType>("\"mmc\"").unwrap(), BusType::Unknown);
+        assert_eq!(BusType::from_transport("ata"), BusType::Sata);
+
+        let coverage = serde_json::to_value(Coverage::Samples { samples: 50 }).unwrap();
+        assert_eq!(coverage, serde_json::json!({"mode": "samples", "samples": 50}));
+    }
+}
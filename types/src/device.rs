@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum RiskLevel {
+    #[serde(rename = "CRITICAL")]
+    Critical,
+    #[serde(rename = "HIGH")]
+    High,
+    #[serde(rename = "SAFE")]
+    Safe,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Device {
+    pub name: String,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub capacity_bytes: u64,
+    pub bus: Option<String>, // SATA, NVMe, USB
+    pub mountpoints: Vec<String>,
+    pub risk_level: RiskLevel,
+    /// Same across reboots and /dev renames: `wwn:<wwn>` or `sn:<model+serial hash>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    /// Which risk rule decided `risk_level`; only output by `discover --explain-risk`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_explanation: Option<RiskExplanation>,
+    /// `capacity_bytes` in the configured SI or IEC units, e.g. `1.00 TB`; only
+    /// output by `discover`, and `capacity_bytes` stays the exact figure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity_display: Option<String>,
+}
+
+/// The risk rule that fired for a drive and what it matched
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RiskExplanation {
+    pub rule: String,
+    /// The mountpoint or partition that matched; None when no rule matched anything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched: Option<String>,
+}
+
+/// A volume on removable/USB media that can serve as a backup destination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RemovableVolume {
+    pub device: String,         // Block device holding the filesystem, e.g. /dev/sdc1
+    pub backing_device: String, // Whole disk, e.g. /dev/sdc
+    pub mountpoint: Option<String>, // None until mounted
+
+    pub label: Option<String>,
+    pub filesystem: Option<String>,
+    pub capacity_bytes: u64,
+    pub free_bytes: Option<u64>,
+    pub bus: Option<String>,
+    pub model: Option<String>,
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A structured progress line written to stderr while a command runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ProgressEvent {
+    /// Machine-readable step name, e.g. "wipe_plan_generated" or "wipe_completed"
+    pub step: String,
+    pub timestamp: String,
+    /// Step-specific fields
+    #[serde(flatten)]
+    pub details: BTreeMap<String, Value>,
+}
+
+/// Ordered from most to least verbose, so `level >= threshold` means "shown"
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// A free-text log line written to stderr
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LogMessage {
+    pub level: LogLevel,
+    pub message: String,
+    pub timestamp: String,
+    /// Core module that wrote the line, e.g. "wipe"; the name `SECUREWIPE_LOG` filters on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+}
+
+/// The JSON printed to stdout when a command fails in a structured way
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ErrorResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cmd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    /// Always "error"
+    pub status: String,
+    pub error: String,
+    /// Cause of a recognised I/O failure, e.g. "device_disconnected" or "media_error"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_class: Option<String>,
+    /// What the operator can do about `error_class`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    pub timestamp: String,
+}
+
+/// Version of the stderr event stream; bumped when a record's envelope changes meaning
+pub const EVENT_PROTOCOL_VERSION: u32 = 1;
+
+/// What an event record's payload is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    /// A `LogMessage`
+    Log,
+    /// A `ProgressEvent`
+    Progress,
+    /// A command's response document, as also printed on stdout
+    Result,
+    /// An `ErrorResponse`
+    Error,
+}
+
+/// One line of the CLI's stderr stream (see docs/EVENT_PROTOCOL.md)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EventRecord {
+    /// `EVENT_PROTOCOL_VERSION` of the emitter
+    pub v: u32,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    /// Position in this run's stream, starting at 1
+    pub id: u64,
+    pub ts: String,
+    pub payload: Value,
+}
+
+impl EventRecord {
+    /// The payload as a progress event, when this record is one
+    pub fn progress(&self) -> Option<ProgressEvent> {
+        match self.event_type {
+            EventType::Progress => serde_json::from_value(self.payload.clone()).ok(),
+            _ => None,
+        }
+    }
+}
+
+// Payloads the Tauri backend emits to the frontend
+
+/// One line of CLI output, emitted as `securewipe://stdout` or `securewipe://stderr`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEvent {
+    pub line: String,
+    pub ts: String,
+    pub stream: String,
+}
+
+/// Emitted as `securewipe://exit` once the CLI process is gone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitEvent {
+    pub code: Option<i32>,
+    pub ts: String,
+    /// Why the run ended early, e.g. "interrupted-device-removed"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Emitted as `securewipe://safe-to-remove` after a destination is ejected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafeToRemoveEvent {
+    pub device: String,
+    pub disk: String,
+    pub ts: String,
+}
+
+/// Emitted as `securewipe://timeout` when the watchdog kills or warns about a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeoutEvent {
+    pub session_id: String,
+    pub kind: String,   // "total" or "stalled"
+    pub action: String, // "killed" or "warned"
+    pub elapsed_secs: u64,
+    pub silent_secs: u64,
+    pub ts: String,
+}
+
+/// Emitted as `securewipe://heartbeat` while a long operation is still running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatEvent {
+    pub session_id: String,
+    pub elapsed_secs: u64,
+    pub silent_secs: u64,
+    pub ts: String,
+}
+
+/// Emitted as `wipe://start` just before the wipe command is launched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WipeStartEvent {
+    pub session_id: String,
+    pub device: String,
+    pub policy: String,
+    pub timestamp: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_event_field_names() {
+        // The frontend listens for these exact keys
+        let start = serde_json::to_value(WipeStartEvent {
+            session_id: "wipe_1".to_string(),
+            device: "/dev/sdb".to_string(),
+            policy: "PURGE".to_string(),
+            timestamp: "2025-09-20T10:15:00+00:00".to_string(),
+        })
+        .unwrap();
+        assert_eq!(start["session_id"], "wipe_1");
+        assert_eq!(start["device"], "/dev/sdb");
+
+        let exit = serde_json::to_value(ExitEvent { code: None, ts: "t".to_string(), reason: None }).unwrap();
+        assert!(exit["code"].is_null());
+        assert_eq!(exit["ts"], "t");
+        assert!(exit.get("reason").is_none());
+    }
+
+    #[test]
+    fn test_event_record_envelope() {
+        let line = r#"{"v":1,"type":"progress","id":3,"ts":"t","payload":{"step":"wipe_completed","timestamp":"t","device":"/dev/sdb"}}"#;
+        let record: EventRecord = serde_json::from_str(line).unwrap();
+        assert_eq!(record.event_type, EventType::Progress);
+        assert_eq!(record.progress().unwrap().details["device"], "/dev/sdb");
+
+        let log: EventRecord =
+            serde_json::from_str(r#"{"v":1,"type":"log","id":1,"ts":"t","payload":{"level":"warn","message":"m","timestamp":"t"}}"#).unwrap();
+        assert!(log.progress().is_none());
+    }
+}
@@ -0,0 +1,27 @@
+//! Data types shared between the `securewipe` core crate and the Tauri backend.
+//!
+//! Everything here is plain serde data: no I/O, no platform code. The core
+//! crate re-exports these from their original modules, so `securewipe::Device`
+//! and `securewipe_types::Device` are the same type.
+
+pub mod certificate;
+pub mod device;
+pub mod events;
+pub mod plan;
+
+pub use certificate::{
+    BackupCertificate, BackupCertificateBuilder, BackupCrypto, BackupDestination, BackupMetadata,
+    BackupPolicy, BackupVerification, BusType, CertificateDevice, CertificateResult,
+    CertificateSignature, CommandRecord, CostSummary, Coverage, DestinationCanary, DestinationType, Environment, Evidence,
+    Exceptions, FilesSummary, HostInfo, HpaDco, Issuer, KeyManagement, Linkage, MaxSectors, MediaType, MissingField, MonotonicSpan,
+    QrPayload, ResidualSignature, TimeSource, VerificationStrategy, WipeCertificate, WipeCertificateBuilder,
+    WipeMetadata, WipePolicyRecord, WipeVerification, CERTIFICATE_VERSION,
+};
+pub use device::{Device, RemovableVolume, RiskExplanation, RiskLevel};
+pub use events::{
+    ErrorResponse, EventRecord, EventType, ExitEvent, HeartbeatEvent, LogEvent, LogLevel, LogMessage,
+    ProgressEvent, SafeToRemoveEvent, TimeoutEvent, WipeStartEvent, EVENT_PROTOCOL_VERSION,
+};
+pub use plan::{
+    CapabilityCheck, DeviceState, MethodCandidate, MethodDecision, VerificationPlan, WipePlan, WipePolicy,
+};
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WipePlan {
+    pub device: String,
+    pub risk: String,
+    pub policy: WipePolicy,
+    pub hpa_dco_clear: bool,
+    pub main_method: String,
+    pub verification: VerificationPlan,
+    pub blocked: bool,
+    pub reason: Option<String>,
+    /// How `main_method` was arrived at
+    #[serde(default)]
+    pub method_decision: MethodDecision,
+    /// Named overwrite profile (e.g. `dod-5220.22-m-3pass`) the overwrite method will run
+    #[serde(default)]
+    pub overwrite_profile: Option<String>,
+    /// The drive as discovered when the plan was made, so an approved plan no longer
+    /// matches once a different drive sits at `device` or it gets mounted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_state: Option<DeviceState>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeviceState {
+    /// Stable identity of the drive (see `device_id` in the core crate)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    pub serial: Option<String>,
+    pub model: Option<String>,
+    pub capacity_bytes: u64,
+    pub mountpoints: Vec<String>,
+}
+
+/// Why a wipe used the method it did: every candidate in order of preference, what
+/// was probed for it and why it was passed over
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MethodDecision {
+    pub candidates: Vec<MethodCandidate>,
+    pub chosen: String,
+}
+
+impl MethodDecision {
+    /// Record `method` as the choice; every other candidate is marked as passed over
+    pub fn choose(candidates: Vec<MethodCandidate>, method: &str) -> Self {
+        let candidates = candidates
+            .into_iter()
+            .map(|mut candidate| {
+                candidate.selected = candidate.method == method;
+                if candidate.selected {
+                    candidate.rejection_reason = None;
+                }
+                candidate
+            })
+            .collect();
+        MethodDecision { candidates, chosen: method.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MethodCandidate {
+    pub method: String, // "controller_sanitize", "overwrite"
+    #[serde(default)]
+    pub checks: Vec<CapabilityCheck>,
+    pub selected: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+}
+
+impl MethodCandidate {
+    pub fn new(method: &str) -> Self {
+        MethodCandidate { method: method.to_string(), checks: Vec::new(), selected: false, rejection_reason: None }
+    }
+
+    pub fn check(mut self, check: CapabilityCheck) -> Self {
+        self.checks.push(check);
+        self
+    }
+
+    pub fn rejected(mut self, reason: impl Into<String>) -> Self {
+        self.rejection_reason = Some(reason.into());
+        self
+    }
+}
+
+/// One capability probe, e.g. whether `nvme id-ctrl` lists sanitize support
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CapabilityCheck {
+    pub source: String,     // command or rule that answered, e.g. "nvme id-ctrl"
+    pub capability: String, // e.g. "sanitize", "security_erase"
+    pub supported: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl CapabilityCheck {
+    pub fn new(source: &str, capability: &str, supported: bool) -> Self {
+        CapabilityCheck { source: source.to_string(), capability: capability.to_string(), supported, detail: None }
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct VerificationPlan {
+    pub strategy: String,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum WipePolicy {
+    #[serde(rename = "CLEAR")]
+    Clear,
+    #[serde(rename = "PURGE")]
+    Purge,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_decision_marks_choice() {
+        let candidates = vec![
+            MethodCandidate::new("controller_sanitize")
+                .check(CapabilityCheck::new("nvme id-ctrl", "sanitize", false))
+                .rejected("Controller reports no sanitize support"),
+            MethodCandidate::new("overwrite").rejected("Controller sanitize preferred"),
+        ];
+        let decision = MethodDecision::choose(candidates, "overwrite");
+
+        assert_eq!(decision.chosen, "overwrite");
+        assert!(!decision.candidates[0].selected);
+        assert!(decision.candidates[0].rejection_reason.is_some());
+        assert!(decision.candidates[1].selected);
+        assert!(decision.candidates[1].rejection_reason.is_none());
+
+        let json = serde_json::to_value(&decision).unwrap();
+        assert_eq!(json["candidates"][0]["checks"][0]["source"], "nvme id-ctrl");
+        assert!(json["candidates"][1].get("rejection_reason").is_none());
+    }
+}
@@ -0,0 +1,163 @@
+// Filesystem scope for the file-related commands. The webview may only reach the
+// home directory, the SecureWipe data directory, mounted removable media, and
+// folders the user has explicitly granted through the native folder picker.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct FsScope {
+    granted: Mutex<Vec<PathBuf>>,
+}
+
+impl FsScope {
+    /// Resolve `path` (with `~` and env vars expanded) and ensure it falls inside
+    /// the scope. The returned path is canonical, so callers should use it rather
+    /// than the original string.
+    pub fn validate(&self, path: &str) -> Result<PathBuf, String> {
+        let expanded = shellexpand::full(path)
+            .map_err(|e| format!("Failed to expand path '{}': {}", path, e))?;
+        let resolved = resolve(Path::new(expanded.as_ref()))?;
+
+        let granted = self.granted.lock().unwrap();
+        if default_roots().iter().chain(granted.iter()).any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            Err(format!(
+                "Access to {} is outside the allowed locations. Use \"Grant Folder Access\" to allow it.",
+                resolved.display()
+            ))
+        }
+    }
+
+    /// Add a user-chosen folder to the scope for the rest of this session
+    pub fn grant(&self, folder: &Path) -> Result<PathBuf, String> {
+        let canonical = folder
+            .canonicalize()
+            .map_err(|e| format!("Cannot grant access to {}: {}", folder.display(), e))?;
+        if !canonical.is_dir() {
+            return Err(format!("{} is not a folder", canonical.display()));
+        }
+
+        let mut granted = self.granted.lock().unwrap();
+        if !granted.contains(&canonical) {
+            granted.push(canonical.clone());
+        }
+        Ok(canonical)
+    }
+}
+
+/// Canonicalize `path`; for paths that don't exist yet, canonicalize the nearest
+/// existing ancestor and re-append the rest so symlinks can't escape the scope
+fn resolve(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_absolute() {
+        return Err(format!("Path must be absolute: {}", path.display()));
+    }
+
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        match existing.canonicalize() {
+            Ok(mut canonical) => {
+                for component in missing.iter().rev() {
+                    canonical.push(component);
+                }
+                return Ok(canonical);
+            }
+            Err(_) => {
+                let name = match existing.components().next_back() {
+                    Some(Component::Normal(name)) => name,
+                    _ => return Err(format!("Invalid path: {}", path.display())),
+                };
+                missing.push(name);
+                existing = match existing.parent() {
+                    Some(parent) => parent,
+                    None => return Err(format!("Invalid path: {}", path.display())),
+                };
+            }
+        }
+    }
+}
+
+/// Locations that are always in scope, canonicalized; missing ones are dropped
+fn default_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    // The data directory may be outside home (system mode) or a symlink to another
+    // disk, so it is listed on its own
+    if let Ok(config) = securewipe::config::Config::load() {
+        roots.push(config.data_dir);
+    }
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home);
+    }
+    roots.extend(removable_mount_points());
+
+    roots.into_iter().filter_map(|root| root.canonicalize().ok()).collect()
+}
+
+/// Mount points of currently mounted removable media
+#[cfg(target_os = "linux")]
+fn removable_mount_points() -> Vec<PathBuf> {
+    let mounts = match fs::read_to_string("/proc/mounts") {
+        Ok(mounts) => mounts,
+        Err(_) => return Vec::new(),
+    };
+
+    mounts
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        // /proc/mounts escapes spaces and tabs as octal
+        .map(|mount| mount.replace("\\040", " ").replace("\\011", "\t"))
+        .filter(|mount| {
+            mount.starts_with("/media/") || mount.starts_with("/run/media/") || mount.starts_with("/mnt/")
+        })
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn removable_mount_points() -> Vec<PathBuf> {
+    fs::read_dir("/Volumes")
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn removable_mount_points() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_missing_tail() {
+        let base = std::env::temp_dir().canonicalize().unwrap();
+        let resolved = resolve(&base.join("securewipe_missing").join("cert.json")).unwrap();
+        assert_eq!(resolved, base.join("securewipe_missing").join("cert.json"));
+
+        assert!(resolve(Path::new("relative/path")).is_err());
+        assert!(resolve(&base.join("securewipe_missing").join("..").join("etc")).is_err());
+    }
+
+    #[test]
+    fn test_grant_extends_scope() {
+        let scope = FsScope::default();
+        let dir = std::env::temp_dir().join(format!("securewipe_scope_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("data.txt");
+
+        // Only assert the negative case when the temp dir isn't already under home
+        let inside_default = default_roots().iter().any(|root| dir.canonicalize().unwrap().starts_with(root));
+        if !inside_default {
+            assert!(scope.validate(file.to_str().unwrap()).is_err());
+        }
+
+        scope.grant(&dir).unwrap();
+        assert!(scope.validate(file.to_str().unwrap()).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
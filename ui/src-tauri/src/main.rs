@@ -7,22 +7,17 @@ use std::process::Stdio;
 use std::sync::{Arc, Mutex};
 use std::path::Path;
 use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::Window;
-use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::time::{timeout, Duration};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct LogEvent {
-    line: String,
-    ts: String,
-    stream: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ExitEvent {
-    code: Option<i32>,
-    ts: String,
-}
+mod fs_scope;
+mod notifications;
+mod sessions;
+use fs_scope::FsScope;
+use notifications::NotificationPrefs;
+use securewipe_types::{ExitEvent, HeartbeatEvent, LogEvent, SafeToRemoveEvent, TimeoutEvent, WipeStartEvent};
+use sessions::SessionRecord;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct FileSystemEntry {
@@ -40,13 +35,183 @@ struct DirectoryListing {
     total_items: usize,
 }
 
-type ProcessMap = Arc<Mutex<HashMap<String, u32>>>;
+#[derive(Debug, Serialize, Deserialize)]
+struct FolderBreakdown {
+    path: String,
+    size: u64,
+    file_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LargestFile {
+    path: String,
+    size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SelectionAnalysis {
+    total_size: u64,
+    total_files: u64,
+    folders: Vec<FolderBreakdown>,
+    largest_files: Vec<LargestFile>,
+    write_bytes_per_sec: Option<u64>,
+    estimated_seconds: Option<u64>,
+    /// Why the destination couldn't be benchmarked, e.g. it doesn't exist yet
+    write_benchmark_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CertificateVerification {
+    cert_id: Option<String>,
+    cert_type: Option<String>,
+    schema_valid: bool,
+    signature_valid: Option<bool>,
+    trusted_key: Option<String>,
+    /// The caller-supplied key the signature matched; it is not trusted, so
+    /// `signature_valid` stays false
+    untrusted_key: Option<String>,
+    errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LinkedCertificate {
+    cert_id: String,
+    cert_type: Option<String>,
+    relation: String, // "backup" (this wipe's source) or "wipe" (wipes of this backup)
+    path: Option<String>,
+    pdf_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CertificateDetails {
+    cert_id: String,
+    cert_type: Option<String>,
+    path: String,
+    pdf_path: Option<String>,
+    certificate: serde_json::Value,
+    linked: Vec<LinkedCertificate>,
+}
+
+const LARGEST_FILES_LIMIT: usize = 10;
+const WRITE_BENCHMARK_BYTES: usize = 16 * 1024 * 1024;
+
+/// How long an operation may run, and how long it may go without output
+#[derive(Debug, Clone, PartialEq)]
+struct TimeoutPolicy {
+    total: Option<Duration>,
+    stall: Duration,
+    kill_on_stall: bool,
+}
+
+const WATCHDOG_TICK: Duration = Duration::from_secs(5);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_TIMEOUT_SECS: u64 = 1200; // 20 minutes
+
+impl TimeoutPolicy {
+    /// Destructive wipes never time out (multi-hour overwrites are normal) and are only
+    /// flagged when silent; backups are bounded by the stall watchdog; everything else
+    /// also gets a total limit from `timeout_secs`, SECUREWIPE_TIMEOUT_SECS or the default.
+    /// A limit of 0 disables the total timeout.
+    fn for_operation(subcommand: &str, is_destructive: bool, timeout_secs: Option<u64>) -> Self {
+        if subcommand == "wipe" && is_destructive {
+            return Self {
+                total: None,
+                stall: Duration::from_secs(30 * 60),
+                kill_on_stall: false,
+            };
+        }
+
+        let configured = timeout_secs.or_else(|| {
+            std::env::var("SECUREWIPE_TIMEOUT_SECS").ok().and_then(|v| v.parse().ok())
+        });
+        let total = match (subcommand, configured) {
+            (_, Some(0)) => None,
+            (_, Some(secs)) => Some(Duration::from_secs(secs)),
+            ("backup", None) => None,
+            (_, None) => Some(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+        };
+
+        Self {
+            total,
+            stall: Duration::from_secs(10 * 60),
+            kill_on_stall: true,
+        }
+    }
+}
+
+/// A CLI child tracked for cancellation; on Unix `pid` is also its process group id
+#[derive(Debug, Clone)]
+struct RunningProcess {
+    pid: u32,
+    device: Option<String>,
+    destructive: bool,
+}
+
+type ProcessMap = Arc<Mutex<HashMap<String, RunningProcess>>>;
+
+/// How long children get to exit after SIGTERM before SIGKILL
+const TERMINATE_GRACE: Duration = Duration::from_secs(5);
+
+/// Dev-mode override for the core binary, e.g. a locally built target/debug/securewipe
+const EXECUTABLE_OVERRIDE_ENV: &str = "SECUREWIPE_BIN";
+
+/// Repository root when running from ui/src-tauri in development; the current
+/// directory otherwise
+fn project_root() -> std::path::PathBuf {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    current_dir.parent()
+        .and_then(|p| p.parent())
+        .filter(|root| root.join("core").is_dir())
+        .map(|root| root.to_path_buf())
+        .unwrap_or(current_dir)
+}
+
+/// The securewipe CLI, ready for arguments. Release builds only ever run the bundled
+/// sidecar. Debug builds also honour `SECUREWIPE_BIN`, and when no sidecar has been
+/// staged fall back to the core crate's target dir, then PATH.
+fn securewipe_command() -> Result<std::process::Command, String> {
+    if cfg!(debug_assertions) {
+        if let Some(path) = std::env::var_os(EXECUTABLE_OVERRIDE_ENV).filter(|path| !path.is_empty()) {
+            return Ok(std::process::Command::new(path));
+        }
+        if let Some(path) = unstaged_dev_executable() {
+            return Ok(std::process::Command::new(path));
+        }
+    }
+
+    tauri::api::process::Command::new_sidecar("securewipe")
+        .map(Into::into)
+        .map_err(|e| format!("Cannot locate the bundled securewipe binary: {}", e))
+}
+
+/// Where a debug build finds the CLI when `npm run prepare:sidecar` hasn't staged one
+/// next to the app executable
+fn unstaged_dev_executable() -> Option<std::path::PathBuf> {
+    let binary_name = format!("securewipe{}", std::env::consts::EXE_SUFFIX);
+    let staged = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&binary_name)))
+        .is_some_and(|path| path.exists());
+    if staged {
+        return None;
+    }
+
+    let root = project_root();
+    for profile in ["release", "debug"] {
+        let candidate = root.join("core").join("target").join(profile).join(&binary_name);
+        if candidate.exists() {
+            return Some(candidate);
+        }
+    }
+    Some(std::path::PathBuf::from(binary_name))
+}
 
 #[tauri::command]
 async fn run_securewipe(
     window: Window,
     args: Vec<String>,
     session_id: Option<String>,
+    timeout_secs: Option<u64>,
     app_state: tauri::State<'_, ProcessMap>,
 ) -> Result<(), String> {
     // Expand paths in arguments first, then sanitize
@@ -57,57 +222,43 @@ async fn run_securewipe(
     let session_id = session_id.unwrap_or_else(|| {
         format!("session_{}", chrono::Utc::now().timestamp_millis())
     });
-
-    // Determine executable path based on platform
-    let executable = if cfg!(windows) {
-        "securewipe.exe".to_string() // In production, this should be bundled
-    } else {
-        // For development, use the built binary from the core directory
-        let current_dir = std::env::current_dir().unwrap_or_default();
-        let project_root = current_dir.parent().and_then(|p| p.parent()).unwrap_or(&current_dir);
-        
-        // Try release build first, then debug build
-        let release_path = project_root.join("core/target/release/securewipe");
-        let debug_path = project_root.join("core/target/debug/securewipe");
-        
-        if release_path.exists() {
-            release_path.to_string_lossy().to_string()
-        } else if debug_path.exists() {
-            debug_path.to_string_lossy().to_string()
-        } else {
-            // Fallback to PATH lookup
-            "securewipe".to_string()
-        }
-    };
+    sessions::check_session_id(&session_id)?;
 
     // Check if this is a destructive wipe operation
     let is_destructive = sanitized_args.contains(&"--danger-allow-wipe".to_string());
+    let policy = TimeoutPolicy::for_operation(&sanitized_args[0], is_destructive, timeout_secs);
 
-    let mut cmd = if is_destructive {
+    if is_destructive {
         // For destructive operations, provide clear user guidance about permissions
-        println!("INFO: Executing destructive wipe operation - elevated privileges required");
-        
-        // First try to run without sudo and provide clear error messaging
-        let mut normal_cmd = tokio::process::Command::new(&executable);
-        normal_cmd.args(&sanitized_args);
-        normal_cmd
-    } else {
-        // For non-destructive operations (discover, backup, planning), run normally
-        let mut normal_cmd = tokio::process::Command::new(&executable);
-        normal_cmd.args(&sanitized_args);
-        normal_cmd
-    };
-
-    cmd.stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        securewipe::events::info("Executing destructive wipe operation - elevated privileges required");
+    }
+    let mut cmd = tokio::process::Command::from(securewipe_command()?);
+    cmd.args(&sanitized_args);
+
+    // Output goes to per-session log files rather than pipes, so the CLI survives
+    // a UI crash and a restarted UI can reattach by tailing the same files
+    let device = sanitized_args.iter()
+        .position(|arg| arg == "--device")
+        .and_then(|i| sanitized_args.get(i + 1))
+        .cloned();
+    let mut record = SessionRecord::new(&session_id, device.clone(), is_destructive, &sanitized_args)?;
+    let stdout_file = fs::File::create(&record.stdout_log)
+        .map_err(|e| format!("Failed to create session log {}: {}", record.stdout_log.display(), e))?;
+    let stderr_file = fs::File::create(&record.stderr_log)
+        .map_err(|e| format!("Failed to create session log {}: {}", record.stderr_log.display(), e))?;
+
+    cmd.stdout(Stdio::from(stdout_file))
+        .stderr(Stdio::from(stderr_file))
         .stdin(Stdio::null())
         .env("SECUREWIPE_DANGER", "1"); // Set environment variable for destructive operations
 
     // Set working directory to project root so relative paths work
     // For sudo, we need to make sure the working directory is set correctly
-    let current_dir = std::env::current_dir().unwrap_or_default();
-    let project_root = current_dir.parent().and_then(|p| p.parent()).unwrap_or(&current_dir);
-    cmd.current_dir(&project_root);
+    cmd.current_dir(project_root());
+
+    // Own process group so cancellation also reaches dd/nvme/hdparm grandchildren
+    #[cfg(unix)]
+    cmd.process_group(0);
 
     let mut child = cmd.spawn().map_err(|e| {
         format!("Failed to spawn securewipe process: {}", e)
@@ -116,85 +267,91 @@ async fn run_securewipe(
     // Get child PID for cancellation
     let child_id = child.id().unwrap_or(0);
     
-    // Store child PID for potential cancellation
+    // Store child PID for potential cancellation, in memory and on disk
+    record.pid = child_id;
+    if let Err(e) = record.save() {
+        securewipe::events::warn(&format!("Session {} will not survive a UI restart: {}", session_id, e));
+    }
     {
         let mut processes = app_state.lock().unwrap();
-        processes.insert(session_id.clone(), child_id);
+        processes.insert(session_id.clone(), RunningProcess {
+            pid: child_id,
+            device,
+            destructive: is_destructive,
+        });
     }
 
-    // Get handles to stdout and stderr
-    let stdout = child.stdout.take().ok_or("Failed to get stdout")?;
-    let stderr = child.stderr.take().ok_or("Failed to get stderr")?;
-
     let window_clone = window.clone();
     let session_clone = session_id.clone();
     let app_state_clone = app_state.inner().clone();
+    let last_output = Arc::new(Mutex::new(std::time::Instant::now()));
 
     // Spawn task to handle process lifecycle
     tokio::spawn(async move {
-        // Create readers for stdout and stderr
-        let stdout_reader = BufReader::new(stdout);
-        let stderr_reader = BufReader::new(stderr);
-
-        // Create tasks for reading stdout and stderr
-        let window_stdout = window_clone.clone();
-        let stdout_task = tokio::spawn(async move {
-            let mut lines = stdout_reader.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
-                // Truncate oversized lines
-                let truncated_line = if line.len() > 65536 {
-                    format!("{}... [TRUNCATED: {} bytes]", &line[..65536], line.len())
-                } else {
-                    line
-                };
+        let (stdout_task, stderr_task, output_done) = spawn_log_tails(&record, &window_clone, &last_output);
+
+        // Wait for the process, checking the watchdog on every tick
+        let started = std::time::Instant::now();
+        let mut stall_warned = false;
+        let mut last_heartbeat = started;
+        let exit_code = loop {
+            match timeout(WATCHDOG_TICK, child.wait()).await {
+                Ok(Ok(status)) => break status.code(),
+                Ok(Err(_)) => break Some(-1), // Process error
+                Err(_) => {}
+            }
 
-                let event = LogEvent {
-                    line: truncated_line,
+            let elapsed = started.elapsed();
+            let silent = last_output.lock().unwrap().elapsed();
+            let timeout_event = |kind: &str, action: &str| {
+                let _ = window_clone.emit("securewipe://timeout", &TimeoutEvent {
+                    session_id: session_clone.clone(),
+                    kind: kind.to_string(),
+                    action: action.to_string(),
+                    elapsed_secs: elapsed.as_secs(),
+                    silent_secs: silent.as_secs(),
                     ts: chrono::Utc::now().to_rfc3339(),
-                    stream: "stdout".to_string(),
-                };
+                });
+            };
 
-                let _ = window_stdout.emit("securewipe://stdout", &event);
+            if policy.total.map_or(false, |limit| elapsed >= limit) {
+                timeout_event("total", "killed");
+                let _ = tokio::task::spawn_blocking(move || terminate_process_tree(child_id)).await;
+                let _ = child.kill().await;
+                break Some(-2); // Timeout code
             }
-        });
 
-        let window_stderr = window_clone.clone();
-        let stderr_task = tokio::spawn(async move {
-            let mut lines = stderr_reader.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
-                // Truncate oversized lines
-                let truncated_line = if line.len() > 65536 {
-                    format!("{}... [TRUNCATED: {} bytes]", &line[..65536], line.len())
-                } else {
-                    line
-                };
+            if silent >= policy.stall {
+                if policy.kill_on_stall {
+                    timeout_event("stalled", "killed");
+                    let _ = tokio::task::spawn_blocking(move || terminate_process_tree(child_id)).await;
+                    let _ = child.kill().await;
+                    break Some(-2);
+                } else if !stall_warned {
+                    // Long wipes may legitimately be quiet; tell the UI but keep going
+                    timeout_event("stalled", "warned");
+                    stall_warned = true;
+                }
+            } else {
+                stall_warned = false;
+            }
 
-                let event = LogEvent {
-                    line: truncated_line,
+            // Keepalive so the UI can tell a quiet operation from a dead runner
+            if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+                last_heartbeat = std::time::Instant::now();
+                let _ = window_clone.emit("securewipe://heartbeat", &HeartbeatEvent {
+                    session_id: session_clone.clone(),
+                    elapsed_secs: elapsed.as_secs(),
+                    silent_secs: silent.as_secs(),
                     ts: chrono::Utc::now().to_rfc3339(),
-                    stream: "stderr".to_string(),
-                };
-
-                let _ = window_stderr.emit("securewipe://stderr", &event);
+                });
             }
-        });
+        };
 
-        // Wait for both reading tasks to complete
+        // Drain whatever output is left once the process is gone
+        output_done.store(true, Ordering::SeqCst);
         let _ = tokio::join!(stdout_task, stderr_task);
-
-        // Wait for the process to complete with timeout
-        let timeout_duration = Duration::from_secs(1200); // 20 minutes
-        let exit_status = timeout(timeout_duration, child.wait()).await;
-
-        let exit_code = match exit_status {
-            Ok(Ok(status)) => status.code(),
-            Ok(Err(_)) => Some(-1), // Process error
-            Err(_) => {
-                // Timeout - kill the process
-                let _ = child.kill().await;
-                Some(-2) // Timeout code
-            }
-        };
+        record.remove();
 
         // Remove from process map
         {
@@ -203,12 +360,142 @@ async fn run_securewipe(
         }
 
         // Emit exit event
+        let progress = notifications::last_progress(&record.stderr_log);
         let exit_event = ExitEvent {
             code: exit_code,
             ts: chrono::Utc::now().to_rfc3339(),
+            reason: notifications::interruption(exit_code, progress.as_ref()).map(str::to_string),
         };
 
         let _ = window_clone.emit("securewipe://exit", &exit_event);
+        notifications::notify_finished(&window_clone, &record, exit_code);
+    });
+
+    Ok(())
+}
+
+/// Emit one CLI output line to the UI, truncating oversized lines
+fn emit_log_line(window: &Window, stream: &str, line: String) {
+    let truncated_line = if line.len() > 65536 {
+        let mut cut = 65536;
+        while !line.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        format!("{}... [TRUNCATED: {} bytes]", &line[..cut], line.len())
+    } else {
+        line
+    };
+
+    let event = LogEvent {
+        line: truncated_line,
+        ts: chrono::Utc::now().to_rfc3339(),
+        stream: stream.to_string(),
+    };
+
+    let _ = window.emit(&format!("securewipe://{}", stream), &event);
+}
+
+type TailHandles = (tokio::task::JoinHandle<()>, tokio::task::JoinHandle<()>, Arc<AtomicBool>);
+
+/// Start following a session's stdout/stderr logs; set the flag to stop after draining
+fn spawn_log_tails(record: &SessionRecord, window: &Window, last_output: &Arc<Mutex<std::time::Instant>>) -> TailHandles {
+    let done = Arc::new(AtomicBool::new(false));
+    let stdout_task = tokio::spawn(sessions::tail_log(
+        record.stdout_log.clone(), "stdout", window.clone(), last_output.clone(), done.clone()));
+    let stderr_task = tokio::spawn(sessions::tail_log(
+        record.stderr_log.clone(), "stderr", window.clone(), last_output.clone(), done.clone()));
+    (stdout_task, stderr_task, done)
+}
+
+/// Sessions from a previous UI run whose CLI process is still alive
+#[tauri::command]
+fn list_orphaned_sessions(app_state: tauri::State<'_, ProcessMap>) -> Vec<SessionRecord> {
+    let tracked = app_state.lock().unwrap();
+    sessions::orphaned_sessions()
+        .into_iter()
+        .filter(|record| !tracked.contains_key(&record.session_id))
+        .collect()
+}
+
+/// Re-stream an orphaned session's output from the start and emit its exit when it ends
+#[tauri::command]
+async fn reattach_session(
+    window: Window,
+    session_id: String,
+    app_state: tauri::State<'_, ProcessMap>,
+) -> Result<(), String> {
+    sessions::check_session_id(&session_id)?;
+    let record = SessionRecord::load(&session_id)?;
+    if !sessions::process_alive(record.pid) {
+        record.remove();
+        return Err(format!("Session {} is no longer running", session_id));
+    }
+
+    {
+        let mut processes = app_state.lock().unwrap();
+        if processes.contains_key(&session_id) {
+            return Err(format!("Session {} is already attached", session_id));
+        }
+        processes.insert(session_id.clone(), RunningProcess {
+            pid: record.pid,
+            device: record.device.clone(),
+            destructive: record.destructive,
+        });
+    }
+
+    let app_state_clone = app_state.inner().clone();
+    tokio::spawn(async move {
+        let last_output = Arc::new(Mutex::new(std::time::Instant::now()));
+        let (stdout_task, stderr_task, output_done) = spawn_log_tails(&record, &window, &last_output);
+
+        // Not our child, so there is no exit status to collect; poll for liveness
+        while sessions::process_alive(record.pid) {
+            tokio::time::sleep(WATCHDOG_TICK).await;
+        }
+
+        output_done.store(true, Ordering::SeqCst);
+        let _ = tokio::join!(stdout_task, stderr_task);
+        record.remove();
+        app_state_clone.lock().unwrap().remove(&record.session_id);
+
+        let progress = notifications::last_progress(&record.stderr_log);
+        let _ = window.emit("securewipe://exit", &ExitEvent {
+            code: None,
+            ts: chrono::Utc::now().to_rfc3339(),
+            reason: notifications::interruption(None, progress.as_ref()).map(str::to_string),
+        });
+        notifications::notify_finished(&window, &record, None);
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_notification_prefs() -> NotificationPrefs {
+    NotificationPrefs::load()
+}
+
+#[tauri::command]
+fn set_notification_prefs(prefs: NotificationPrefs) -> Result<(), String> {
+    prefs.save()
+}
+
+/// Stop an orphaned session without reattaching to it
+#[tauri::command]
+fn terminate_session(session_id: String) -> Result<(), String> {
+    sessions::check_session_id(&session_id)?;
+    let record = SessionRecord::load(&session_id)?;
+    let process = RunningProcess {
+        pid: record.pid,
+        device: record.device.clone(),
+        destructive: record.destructive,
+    };
+
+    std::thread::spawn(move || {
+        if sessions::process_alive(process.pid) {
+            terminate_and_clean_up(process);
+        }
+        record.remove();
     });
 
     Ok(())
@@ -219,31 +506,66 @@ fn cancel_securewipe(
     session_id: String,
     app_state: tauri::State<'_, ProcessMap>,
 ) -> Result<(), String> {
-    let mut processes = app_state.lock().unwrap();
-    
-    if let Some(pid) = processes.remove(&session_id) {
-        // Use system kill command to terminate the process
-        #[cfg(unix)]
-        {
-            use std::process::Command;
-            let _ = Command::new("kill")
-                .arg("-TERM")
-                .arg(pid.to_string())
-                .output();
+    let process = app_state.lock().unwrap().remove(&session_id)
+        .ok_or_else(|| "Session not found".to_string())?;
+
+    // Escalation and cleanup can take seconds; don't block the UI thread
+    std::thread::spawn(move || terminate_and_clean_up(process));
+
+    Ok(())
+}
+
+/// Stop the process tree, then undo device state an interrupted wipe may leave
+fn terminate_and_clean_up(process: RunningProcess) {
+    terminate_process_tree(process.pid);
+
+    if process.destructive {
+        if let Some(device) = process.device {
+            let result = securewipe_command().and_then(|mut cmd| cmd
+                .args(["wipe", "--device", &device, "--cleanup-only"])
+                .stdin(Stdio::null())
+                .current_dir(project_root())
+                .output()
+                .map_err(|e| e.to_string()));
+            match result {
+                Ok(output) if output.status.success() => {}
+                Ok(output) => securewipe::events::warn(&format!("Post-cancel cleanup for {} failed: {}",
+                    device, String::from_utf8_lossy(&output.stderr))),
+                Err(e) => securewipe::events::warn(&format!("Failed to run post-cancel cleanup for {}: {}", device, e)),
+            }
         }
-        
-        #[cfg(windows)]
-        {
-            use std::process::Command;
-            let _ = Command::new("taskkill")
-                .args(&["/PID", &pid.to_string(), "/F"])
-                .output();
+    }
+}
+
+/// SIGTERM the whole process group, then SIGKILL whatever survives the grace period.
+/// Unix only: the core crate doesn't build for Windows (native support is still on the
+/// roadmap), so there is no Windows variant to keep correct.
+#[cfg(unix)]
+fn terminate_process_tree(pid: u32) {
+    use std::process::Command;
+
+    // pid 0 means the child was never tracked; "-0" would signal our own group
+    if pid == 0 {
+        return;
+    }
+
+    let group = format!("-{}", pid);
+    let _ = Command::new("kill").args(["-TERM", "--", &group]).output();
+
+    let deadline = std::time::Instant::now() + TERMINATE_GRACE;
+    while std::time::Instant::now() < deadline {
+        let alive = Command::new("kill")
+            .args(["-0", "--", &group])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        if !alive {
+            return;
         }
-        
-        Ok(())
-    } else {
-        Err("Session not found".to_string())
+        std::thread::sleep(Duration::from_millis(200));
     }
+
+    let _ = Command::new("kill").args(["-KILL", "--", &group]).output();
 }
 
 fn expand_paths_in_args(args: &[String]) -> Result<Vec<String>, String> {
@@ -256,7 +578,7 @@ fn expand_paths_in_args(args: &[String]) -> Result<Vec<String>, String> {
             match shellexpand::full(arg) {
                 Ok(expanded) => expanded_args.push(expanded.to_string()),
                 Err(e) => {
-                    eprintln!("Warning: Failed to expand path '{}': {}", arg, e);
+                    securewipe::events::warn(&format!("Failed to expand path '{}': {}", arg, e));
                     // If expansion fails, use the original argument
                     expanded_args.push(arg.clone());
                 }
@@ -301,13 +623,10 @@ fn sanitize_args(args: &[String]) -> Result<Vec<String>, String> {
     // Additional validation for specific subcommands
     match subcommand.as_str() {
         "wipe" => {
-            // For wipe, check if this is destructive mode or planning mode
-            if args.contains(&"--danger-allow-wipe".to_string()) {
-                // Destructive wipe mode - allow without --format requirement
-                // This will be handled by the execute_destructive_wipe command
-            } else if !args.contains(&"--format".to_string()) {
-                // Planning mode - require --format for safety
-                return Err("Wipe command must include --format for planning mode".to_string());
+            // Only destructive mode (from execute_destructive_wipe) goes through the CLI;
+            // planning is served in-process by the plan_wipe command
+            if !args.contains(&"--danger-allow-wipe".to_string()) {
+                return Err("Wipe planning must use the plan_wipe command".to_string());
             }
         }
         "backup" => {
@@ -334,9 +653,12 @@ fn sanitize_args(args: &[String]) -> Result<Vec<String>, String> {
 }
 
 #[tauri::command]
-async fn browse_folders(path: Option<String>) -> Result<DirectoryListing, String> {
+async fn browse_folders(
+    path: Option<String>,
+    scope: tauri::State<'_, FsScope>,
+) -> Result<DirectoryListing, String> {
     let browse_path = match path {
-        Some(p) => Path::new(&p).to_path_buf(),
+        Some(p) => scope.validate(&p)?,
         None => dirs::home_dir().ok_or("Could not determine home directory")?,
     };
 
@@ -411,12 +733,33 @@ async fn browse_folders(path: Option<String>) -> Result<DirectoryListing, String
     })
 }
 
+/// Let the user extend the file scope by picking a folder in the native dialog;
+/// returns the granted folder, or None if they cancel
+#[tauri::command]
+async fn grant_folder_access(scope: tauri::State<'_, FsScope>) -> Result<Option<String>, String> {
+    let picked = tokio::task::spawn_blocking(|| {
+        tauri::api::dialog::blocking::FileDialogBuilder::new()
+            .set_title("Grant SecureWipe access to a folder")
+            .pick_folder()
+    })
+    .await
+    .map_err(|e| format!("Folder picker failed: {}", e))?;
+
+    match picked {
+        Some(folder) => Ok(Some(scope.grant(&folder)?.to_string_lossy().to_string())),
+        None => Ok(None),
+    }
+}
+
 #[tauri::command]
-async fn calculate_selection_size(paths: Vec<String>) -> Result<u64, String> {
+async fn calculate_selection_size(
+    paths: Vec<String>,
+    scope: tauri::State<'_, FsScope>,
+) -> Result<u64, String> {
     let mut total_size = 0u64;
 
     for path_str in paths {
-        let path = Path::new(&path_str);
+        let path = &scope.validate(&path_str)?;
         if !path.exists() {
             continue;
         }
@@ -433,6 +776,111 @@ async fn calculate_selection_size(paths: Vec<String>) -> Result<u64, String> {
     Ok(total_size)
 }
 
+#[tauri::command]
+async fn analyze_selection(
+    paths: Vec<String>,
+    destination: Option<String>,
+    scope: tauri::State<'_, FsScope>,
+) -> Result<SelectionAnalysis, String> {
+    let mut folders = Vec::new();
+    let mut largest_files = Vec::new();
+
+    for path_str in paths {
+        let path = &scope.validate(&path_str)?;
+        if !path.exists() {
+            continue;
+        }
+
+        let mut breakdown = FolderBreakdown {
+            path: path_str.clone(),
+            size: 0,
+            file_count: 0,
+        };
+        scan_selection_entry(path, &mut breakdown, &mut largest_files);
+        folders.push(breakdown);
+    }
+
+    folders.sort_by(|a, b| b.size.cmp(&a.size));
+    let total_size = folders.iter().map(|f| f.size).sum();
+    let total_files = folders.iter().map(|f| f.file_count).sum();
+
+    // A short write benchmark on the destination gives a realistic throughput figure
+    let (write_bytes_per_sec, write_benchmark_error) = match destination {
+        Some(dest) => match benchmark_destination_write(&scope.validate(&dest)?) {
+            Ok(rate) => (Some(rate), None),
+            Err(e) => (None, Some(e)),
+        },
+        None => (None, None),
+    };
+    let estimated_seconds = write_bytes_per_sec
+        .filter(|rate| *rate > 0)
+        .map(|rate| (total_size + rate - 1) / rate);
+
+    Ok(SelectionAnalysis {
+        total_size,
+        total_files,
+        folders,
+        largest_files,
+        write_bytes_per_sec,
+        estimated_seconds,
+        write_benchmark_error,
+    })
+}
+
+/// Symlinks are neither counted nor followed, so a link loop can't recurse forever
+fn scan_selection_entry(path: &Path, breakdown: &mut FolderBreakdown, largest: &mut Vec<LargestFile>) {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return,
+    };
+    if metadata.is_file() {
+        let size = metadata.len();
+        breakdown.size += size;
+        breakdown.file_count += 1;
+
+        // Keep only the top N, sorted largest first
+        if largest.len() < LARGEST_FILES_LIMIT || largest.last().map_or(true, |f| size > f.size) {
+            let pos = largest.partition_point(|f| f.size >= size);
+            largest.insert(pos, LargestFile {
+                path: path.to_string_lossy().to_string(),
+                size,
+            });
+            largest.truncate(LARGEST_FILES_LIMIT);
+        }
+    } else if metadata.is_dir() {
+        if let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.flatten() {
+                scan_selection_entry(&entry.path(), breakdown, largest);
+            }
+        }
+    }
+}
+
+/// Write and fsync a scratch file in the existing destination directory, returning
+/// bytes per second. A missing destination is reported, not created.
+fn benchmark_destination_write(destination: &Path) -> Result<u64, String> {
+    use std::io::Write;
+
+    if !destination.is_dir() {
+        return Err(format!("Destination {} does not exist or is not a directory", destination.display()));
+    }
+    let probe_path = destination.join(format!(".securewipe_bench_{}", std::process::id()));
+    let buffer = vec![0xA5u8; WRITE_BENCHMARK_BYTES];
+
+    let start = std::time::Instant::now();
+    let result = fs::File::create(&probe_path)
+        .and_then(|mut file| {
+            file.write_all(&buffer)?;
+            file.sync_all()
+        });
+    let elapsed = start.elapsed();
+    let _ = fs::remove_file(&probe_path);
+
+    result.map_err(|e| format!("Destination write benchmark failed: {}", e))?;
+    let secs = elapsed.as_secs_f64().max(0.001);
+    Ok((WRITE_BENCHMARK_BYTES as f64 / secs) as u64)
+}
+
 #[tauri::command]
 async fn get_home_dir() -> Result<String, String> {
     dirs::home_dir()
@@ -441,8 +889,16 @@ async fn get_home_dir() -> Result<String, String> {
 }
 
 #[tauri::command]
-async fn list_cert_files(directory: String) -> Result<Vec<String>, String> {
-    let cert_dir = Path::new(&directory);
+async fn get_data_dir() -> Result<String, String> {
+    data_dir().map(|path| path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn list_cert_files(
+    directory: String,
+    scope: tauri::State<'_, FsScope>,
+) -> Result<Vec<String>, String> {
+    let cert_dir = &scope.validate(&directory)?;
     
     if !cert_dir.exists() {
         return Ok(Vec::new()); // Return empty list if directory doesn't exist yet
@@ -478,28 +934,72 @@ async fn list_cert_files(directory: String) -> Result<Vec<String>, String> {
     Ok(cert_files)
 }
 
+/// Operator-chosen names of drives, by stable device ID
+#[tauri::command]
+async fn get_device_aliases() -> Result<std::collections::BTreeMap<String, String>, String> {
+    tokio::task::spawn_blocking(|| {
+        let inventory = securewipe::Inventory::open_default()
+            .map_err(|e| format!("Failed to open device inventory: {}", e))?;
+        inventory.alias_map().map_err(|e| format!("Failed to read device aliases: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Device alias task failed: {}", e))?
+}
+
+/// Name a drive (by serial, device ID or current alias), or clear its name with `None`
 #[tauri::command]
-async fn read_file_content(file_path: String) -> Result<String, String> {
-    match fs::read_to_string(&file_path) {
+async fn set_device_alias(drive: String, alias: Option<String>) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let inventory = securewipe::Inventory::open_default()
+            .map_err(|e| format!("Failed to open device inventory: {}", e))?;
+        inventory
+            .set_alias(&drive, alias.as_deref())
+            .map_err(|e| format!("Failed to set alias for {}: {}", drive, e))
+    })
+    .await
+    .map_err(|e| format!("Device alias task failed: {}", e))?
+}
+
+/// Search stored certificates by cert id, certificate number, drive serial or model,
+/// with structured filters and paging. The index is synced with the certificates
+/// directory first, so only new or changed files are read.
+#[tauri::command]
+async fn search_certificates(query: securewipe::CertificateQuery) -> Result<securewipe::CertificatePage, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut index = securewipe::CertIndex::open_default()
+            .map_err(|e| format!("Failed to open certificate index: {}", e))?;
+        index.refresh().map_err(|e| format!("Failed to refresh certificate index: {}", e))?;
+        index.search(&query).map_err(|e| format!("Certificate search failed: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Certificate search task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn read_file_content(
+    file_path: String,
+    scope: tauri::State<'_, FsScope>,
+) -> Result<String, String> {
+    match fs::read_to_string(scope.validate(&file_path)?) {
         Ok(content) => Ok(content),
         Err(e) => Err(format!("Failed to read file {}: {}", file_path, e)),
     }
 }
 
 #[tauri::command]
-async fn file_exists(file_path: String) -> Result<bool, String> {
-    Ok(Path::new(&file_path).exists())
+async fn file_exists(file_path: String, scope: tauri::State<'_, FsScope>) -> Result<bool, String> {
+    Ok(scope.validate(&file_path)?.exists())
 }
 
 #[tauri::command]
-async fn open_path(path: String) -> Result<(), String> {
+async fn open_path(path: String, scope: tauri::State<'_, FsScope>) -> Result<(), String> {
     use std::process::Command;
     
     // Validate and canonicalize path to prevent traversal attacks
-    let canonical_path = match Path::new(&path).canonicalize() {
-        Ok(p) => p,
-        Err(_) => return Err(format!("Invalid or non-existent path: {}", path))
-    };
+    let canonical_path = scope.validate(&path)?;
+    if !canonical_path.exists() {
+        return Err(format!("Invalid or non-existent path: {}", path));
+    }
     
     #[cfg(target_os = "linux")]
     {
@@ -534,9 +1034,10 @@ async fn generate_pdf_for_cert(
     cert_json_path: String,
     _session_id: Option<String>,
     _app_state: tauri::State<'_, ProcessMap>,
+    scope: tauri::State<'_, FsScope>,
 ) -> Result<String, String> {
     // Extract cert_id from the JSON file to determine PDF path
-    let cert_content = fs::read_to_string(&cert_json_path)
+    let cert_content = fs::read_to_string(scope.validate(&cert_json_path)?)
         .map_err(|e| format!("Failed to read certificate file: {}", e))?;
     
     let cert_data: serde_json::Value = serde_json::from_str(&cert_content)
@@ -546,11 +1047,8 @@ async fn generate_pdf_for_cert(
         .and_then(|v| v.as_str())
         .ok_or("Certificate ID not found in JSON")?;
     
-    // Get home directory for custom PDF save location
-    let home_dir = dirs::home_dir()
-        .ok_or("Could not determine home directory")?;
-    
-    let backups_dir = home_dir.join("SecureWipe").join("backups");
+    // Custom PDF save location under the data directory
+    let backups_dir = data_dir()?.join("backups");
     
     // Create backups directory if it doesn't exist
     if !backups_dir.exists() {
@@ -569,85 +1067,150 @@ async fn generate_pdf_for_cert(
     let expanded_args = expand_paths_in_args(&args)?;
     let sanitized_args = sanitize_args(&expanded_args)?;
     
-    // Determine executable path 
-    let executable = if cfg!(windows) {
-        "securewipe.exe".to_string()
-    } else {
-        let current_dir = std::env::current_dir().unwrap_or_default();
-        let project_root = current_dir.parent().and_then(|p| p.parent()).unwrap_or(&current_dir);
-        
-        let release_path = project_root.join("core/target/release/securewipe");
-        let debug_path = project_root.join("core/target/debug/securewipe");
-        
-        if release_path.exists() {
-            release_path.to_string_lossy().to_string()
-        } else if debug_path.exists() {
-            debug_path.to_string_lossy().to_string()
-        } else {
-            "securewipe".to_string()
-        }
-    };
-
     // Run the CLI command synchronously
-    let mut cmd = tokio::process::Command::new(executable);
+    let mut cmd = tokio::process::Command::from(securewipe_command()?);
     cmd.args(&sanitized_args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .stdin(Stdio::null());
 
     // Set working directory to project root so relative paths work
-    let current_dir = std::env::current_dir().unwrap_or_default();
-    let project_root = current_dir.parent().and_then(|p| p.parent()).unwrap_or(&current_dir);
-    cmd.current_dir(project_root);
+    cmd.current_dir(project_root());
 
     let output = cmd.output().await
         .map_err(|e| format!("Failed to execute securewipe: {}", e))?;
     
-    // Check if command succeeded with debugging
-    println!("CLI command completed with status: {:?}", output.status);
-    println!("CLI stdout: {}", String::from_utf8_lossy(&output.stdout));
-    println!("CLI stderr: {}", String::from_utf8_lossy(&output.stderr));
-    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let response = parse_export_pdf_response(&stdout);
+
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("CLI command failed: {}", stderr));
+        // Prefer the structured error from the CLI response over raw stderr
+        let reason = response.as_ref()
+            .and_then(|r| r.get("error").and_then(|v| v.as_str()).map(String::from))
+            .unwrap_or_else(|| String::from_utf8_lossy(&output.stderr).trim().to_string());
+        return Err(format!("PDF export failed ({}): {}", output.status, reason));
     }
-    
-    // Check if PDF was generated
-    let default_pdf_path = home_dir.join("SecureWipe").join("certificates").join(format!("{}.pdf", cert_id));
+
+    let response = response.ok_or_else(|| format!(
+        "PDF export returned no JSON response. stdout: {}", stdout.trim()))?;
+    if response.get("status").and_then(|v| v.as_str()) != Some("success") {
+        return Err(format!("PDF export reported failure: {}", response));
+    }
+    let pdf_path = response.get("pdf_path")
+        .and_then(|v| v.as_str())
+        .map(std::path::PathBuf::from)
+        .ok_or("PDF export response is missing pdf_path")?;
+
+    wait_for_complete_file(&pdf_path).await?;
+
     let custom_pdf_path = backups_dir.join(format!("{}.pdf", cert_id));
-    
-    println!("Looking for PDF at: {}", default_pdf_path.display());
-    
-    // Wait longer to ensure Python script completes (increased from 500ms to 3000ms)
-    tokio::time::sleep(tokio::time::Duration::from_millis(3000)).await;
-    
-    println!("After wait - PDF exists: {}", default_pdf_path.exists());
-    
-    if default_pdf_path.exists() {
-        // Copy to custom location
-        fs::copy(&default_pdf_path, &custom_pdf_path)
-            .map_err(|e| format!("Failed to copy PDF to backups directory: {}", e))?;
-        
-        println!("PDF copied to: {}", custom_pdf_path.display());
-        Ok(custom_pdf_path.to_string_lossy().to_string())
-    } else {
-        // Additional debugging - check if directory exists and list contents
-        let cert_dir = default_pdf_path.parent().unwrap();
-        if cert_dir.exists() {
-            if let Ok(entries) = fs::read_dir(cert_dir) {
-                println!("Certificate directory contents:");
-                for entry in entries {
-                    if let Ok(entry) = entry {
-                        println!("  - {}", entry.path().display());
-                    }
+    fs::copy(&pdf_path, &custom_pdf_path)
+        .map_err(|e| format!("Failed to copy PDF to backups directory: {}", e))?;
+
+    Ok(custom_pdf_path.to_string_lossy().to_string())
+}
+
+/// Generate a certificate PDF in-process through the core crate's export path. Without
+/// `output_path` the user picks the destination in a save dialog; returns None if they cancel.
+#[tauri::command]
+async fn generate_pdf(
+    cert_json_path: String,
+    output_path: Option<String>,
+    scope: tauri::State<'_, FsScope>,
+) -> Result<Option<String>, String> {
+    // A path picked in the save dialog is the user's choice; only explicit ones are checked
+    let output_path = match output_path {
+        Some(path) => Some(scope.validate(&path)?),
+        None => None,
+    };
+    let cert_json = fs::read_to_string(scope.validate(&cert_json_path)?)
+        .map_err(|e| format!("Failed to read certificate file: {}", e))?;
+    let cert_data: serde_json::Value = serde_json::from_str(&cert_json)
+        .map_err(|e| format!("Failed to parse certificate JSON: {}", e))?;
+    let cert_id = cert_data.get("cert_id")
+        .and_then(|v| v.as_str())
+        .ok_or("Certificate ID not found in JSON")?
+        .to_string();
+
+    tokio::task::spawn_blocking(move || {
+        let output_path = match output_path {
+            Some(path) => path,
+            None => {
+                let mut dialog = tauri::api::dialog::blocking::FileDialogBuilder::new()
+                    .set_title("Save certificate PDF")
+                    .set_file_name(&format!("{}.pdf", cert_id))
+                    .add_filter("PDF", &["pdf"]);
+                if let Ok(dir) = data_dir() {
+                    dialog = dialog.set_directory(dir.join("backups"));
+                }
+                match dialog.save_file() {
+                    Some(path) => path,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        // Stamped like `cert --export-pdf` when a signing key is configured
+        let signing_key = securewipe::signer::load_private_key(None).ok();
+        let pdf_path = securewipe::export_certificate_pdf(
+            &cert_json, Some(&output_path), securewipe::PdfDetail::default(), signing_key.as_ref())
+            .map_err(|e| format!("PDF generation failed: {}", e))?;
+        Ok(Some(pdf_path.to_string_lossy().to_string()))
+    })
+    .await
+    .map_err(|e| format!("PDF generation task failed: {}", e))?
+}
+
+/// Pull the `cert --export-pdf` JSON object out of CLI stdout, skipping any
+/// non-JSON lines printed before it
+fn parse_export_pdf_response(stdout: &str) -> Option<serde_json::Value> {
+    let mut offset = 0;
+    for line in stdout.split_inclusive('\n') {
+        if line.trim_start().starts_with('{') {
+            let mut stream = serde_json::Deserializer::from_str(&stdout[offset..])
+                .into_iter::<serde_json::Value>();
+            if let Some(Ok(value)) = stream.next() {
+                if value.get("action").and_then(|v| v.as_str()) == Some("export_pdf") {
+                    return Some(value);
                 }
             }
-        } else {
-            println!("Certificate directory does not exist: {}", cert_dir.display());
         }
-        
-        Err(format!("PDF was not generated by CLI. Expected at: {}", default_pdf_path.display()))
+        offset += line.len();
+    }
+    None
+}
+
+/// Poll with bounded backoff until the file exists, is non-empty and its size
+/// has stopped changing
+async fn wait_for_complete_file(path: &Path) -> Result<(), String> {
+    const MAX_WAIT: Duration = Duration::from_secs(15);
+    let start = std::time::Instant::now();
+    let mut delay = Duration::from_millis(25);
+    let mut last_size = None;
+
+    loop {
+        match fs::metadata(path) {
+            Ok(meta) if meta.len() > 0 => {
+                if last_size == Some(meta.len()) {
+                    return Ok(());
+                }
+                last_size = Some(meta.len());
+            }
+            Ok(_) => last_size = None,
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Cannot read generated PDF {}: {}", path.display(), e));
+            }
+            Err(_) => {}
+        }
+
+        if start.elapsed() >= MAX_WAIT {
+            return Err(match last_size {
+                Some(size) => format!("PDF {} was still being written after {:?} ({} bytes)", path.display(), MAX_WAIT, size),
+                None => format!("CLI reported PDF at {} but it did not appear within {:?}", path.display(), MAX_WAIT),
+            });
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(Duration::from_millis(500));
     }
 }
 
@@ -729,15 +1292,394 @@ async fn execute_destructive_wipe(
     let session_id = format!("wipe_{}", chrono::Utc::now().timestamp_millis());
 
     // Emit start event to frontend
-    let _ = window.emit("wipe://start", &serde_json::json!({
-        "session_id": session_id,
-        "device": confirmation.device,
-        "policy": confirmation.policy,
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    }));
+    let _ = window.emit("wipe://start", &WipeStartEvent {
+        session_id: session_id.clone(),
+        device: confirmation.device.clone(),
+        policy: confirmation.policy.clone(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    });
 
     // Execute the wipe command
-    run_securewipe(window, args, Some(session_id), app_state).await
+    run_securewipe(window, args, Some(session_id), None, app_state).await
+}
+
+/// Plan a wipe through the same core assembly `securewipe wipe` uses, so the GUI
+/// blocks exactly the drives the CLI would
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn plan_wipe(
+    device: String,
+    policy: Option<String>,
+    samples: Option<usize>,
+    iso_mode: Option<bool>,
+    no_enrich: Option<bool>,
+    verify_mode: Option<String>,
+    overwrite_profile: Option<String>,
+    virtual_media: Option<bool>,
+    allow_network_device: Option<bool>,
+    backup_cert_id: Option<String>,
+) -> Result<securewipe_types::WipePlan, String> {
+    use securewipe::{DeviceDiscovery, LinuxDeviceDiscovery, OverwriteProfile, VerifyMode, WipePolicy};
+
+    let policy = match policy.as_deref().map(str::to_uppercase).as_deref() {
+        None | Some("PURGE") => WipePolicy::Purge,
+        Some("CLEAR") => WipePolicy::Clear,
+        Some(other) => return Err(format!("Invalid policy: {}. Must be CLEAR or PURGE", other)),
+    };
+    let verify_mode = match verify_mode.as_deref() {
+        None => VerifyMode::Heuristic,
+        Some(mode) => VerifyMode::parse(mode)
+            .ok_or_else(|| format!("Invalid verify mode: {}. Must be heuristic, pattern or pattern-full", mode))?,
+    };
+    let overwrite_profile = match overwrite_profile.as_deref() {
+        None => None,
+        Some(name) => Some(OverwriteProfile::parse(name).ok_or_else(|| {
+            format!("Invalid overwrite profile: {}. Must be nist-clear, dod-5220.22-m-3pass or gutmann-lite", name)
+        })?),
+    };
+    let site_policy = securewipe::policy::SitePolicy::load_default()
+        .map_err(|e| format!("Failed to load site policy: {}", e))?;
+    securewipe::risk::install(site_policy.risk.clone());
+
+    let request = securewipe::wipe_plan::PlanRequest {
+        device,
+        policy,
+        iso_mode: iso_mode.unwrap_or(false),
+        samples,
+        verify_mode,
+        overwrite_profile,
+        declared_virtual: virtual_media.unwrap_or(false),
+        allow_network_device: allow_network_device.unwrap_or(false),
+        backup_cert_id,
+    };
+
+    // Discovery and controller probes shell out, so keep them off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let discovery = if no_enrich.unwrap_or(false) {
+            LinuxDeviceDiscovery::new_without_enrichment()
+        } else {
+            LinuxDeviceDiscovery::new()
+        };
+        // A failed discovery blocks the plan rather than treating the drive as safe
+        let discovered = discovery.discover_devices();
+        securewipe::wipe_plan::assemble(&request, discovered.as_deref().map_err(|e| e.to_string()), &site_policy).plan
+    })
+    .await
+    .map_err(|e| format!("Wipe planning task failed: {}", e))
+}
+
+/// Re-check a stored backup's manifest and file hashes before the user moves on to wiping
+#[tauri::command]
+async fn verify_backup(
+    backup_dir: String,
+    scope: tauri::State<'_, FsScope>,
+) -> Result<securewipe::BackupVerificationReport, String> {
+    let backup_dir = scope.validate(&backup_dir)?;
+    tokio::task::spawn_blocking(move || {
+        securewipe::EncryptedBackup::new()
+            .verify_backup(&backup_dir)
+            .map_err(|e| format!("Failed to verify backup {}: {}", backup_dir.display(), e))
+    })
+    .await
+    .map_err(|e| format!("Backup verification task failed: {}", e))?
+}
+
+/// Removable/USB volumes the user can pick as a backup destination
+#[tauri::command]
+async fn list_removable_destinations() -> Result<Vec<securewipe_types::RemovableVolume>, String> {
+    tokio::task::spawn_blocking(|| {
+        securewipe::LinuxDeviceDiscovery::new_without_enrichment()
+            .discover_removable_targets()
+            .map_err(|e| format!("Failed to list removable destinations: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Removable destination task failed: {}", e))?
+}
+
+/// Mount a removable volume through udisks2 so it can be used as a backup destination
+#[tauri::command]
+async fn mount_destination(device: String) -> Result<securewipe::MountedDestination, String> {
+    tokio::task::spawn_blocking(move || {
+        securewipe::mount_destination(&device)
+            .map_err(|e| format!("Failed to mount {}: {}", device, e))
+    })
+    .await
+    .map_err(|e| format!("Mount task failed: {}", e))?
+}
+
+/// Unmount a destination mounted by mount_destination when no backup ran on it
+#[tauri::command]
+async fn unmount_destination(device: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        securewipe::unmount_destination(&device)
+            .map_err(|e| format!("Failed to unmount {}: {}", device, e))
+    })
+    .await
+    .map_err(|e| format!("Unmount task failed: {}", e))?
+}
+
+/// Sync, unmount and power off the drive holding `device`, then tell the UI it is
+/// safe to unplug
+#[tauri::command]
+async fn eject_destination(window: Window, device: String) -> Result<String, String> {
+    let device_clone = device.clone();
+    let disk = tokio::task::spawn_blocking(move || {
+        securewipe::eject_destination(&device_clone)
+            .map_err(|e| format!("Failed to eject {}: {}", device_clone, e))
+    })
+    .await
+    .map_err(|e| format!("Eject task failed: {}", e))??;
+
+    let _ = window.emit("securewipe://safe-to-remove", &SafeToRemoveEvent {
+        device,
+        disk: disk.clone(),
+        ts: chrono::Utc::now().to_rfc3339(),
+    });
+    Ok(disk)
+}
+
+/// Blink the drive's LED for `seconds` so the operator can find it before confirming a
+/// wipe; resolves once it stops
+#[tauri::command]
+async fn identify_device(device: String, seconds: Option<u64>) -> Result<securewipe::IdentifyReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let duration = std::time::Duration::from_secs(seconds.unwrap_or(15));
+        securewipe::identify_device(&device, duration, None)
+            .map_err(|e| format!("Failed to identify {}: {}", device, e))
+    })
+    .await
+    .map_err(|e| format!("Identify task failed: {}", e))?
+}
+
+/// Public keys trusted for verification: SECUREWIPE_PUBKEY_PATH, then every *.pem
+/// under the data directory's keys/. A key the webview names is never among them.
+fn trusted_public_keys() -> Vec<std::path::PathBuf> {
+    let mut keys = Vec::new();
+
+    if let Ok(path) = std::env::var("SECUREWIPE_PUBKEY_PATH") {
+        keys.push(std::path::PathBuf::from(path));
+    }
+    if let Ok(dir) = data_dir() {
+        if let Ok(entries) = fs::read_dir(dir.join("keys")) {
+            let mut pems: Vec<_> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("pem"))
+                .collect();
+            pems.sort();
+            keys.extend(pems);
+        }
+    }
+
+    keys
+}
+
+#[tauri::command]
+async fn verify_certificate(
+    file_path: String,
+    pubkey_path: Option<String>,
+    scope: tauri::State<'_, FsScope>,
+) -> Result<CertificateVerification, String> {
+    let pubkey_path = match pubkey_path {
+        Some(path) => Some(scope.validate(&path)?.to_string_lossy().to_string()),
+        None => None,
+    };
+    let content = fs::read_to_string(scope.validate(&file_path)?)
+        .map_err(|e| format!("Failed to read certificate {}: {}", file_path, e))?;
+    let cert: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid JSON in certificate {}: {}", file_path, e))?;
+
+    let mut errors = Vec::new();
+
+    let schema_valid = match securewipe::CertificateValidator::default().validate_certificate(&cert) {
+        Ok(result) => {
+            errors.extend(result.errors.into_iter().map(|e| format!("schema: {}", e)));
+            result.valid
+        }
+        Err(e) => {
+            errors.push(format!("schema: {}", e));
+            false
+        }
+    };
+
+    let mut signature_valid = None;
+    let mut trusted_key = None;
+    let mut untrusted_key = None;
+
+    match cert.get("signature").filter(|sig| !sig.is_null()) {
+        None => errors.push("signature: certificate is not signed".to_string()),
+        Some(sig) if sig.get("pubkey_id").and_then(|v| v.as_str()) != Some("sih_root_v1") => {
+            signature_valid = Some(false);
+            errors.push("signature: pubkey_id must be 'sih_root_v1'".to_string());
+        }
+        Some(_) => {
+            let mut malformed = false;
+            let keys = trusted_public_keys();
+            if keys.is_empty() {
+                errors.push("signature: no trusted public key configured (set SECUREWIPE_PUBKEY_PATH or add a .pem to the data directory's keys/)".to_string());
+            }
+            for key_path in keys {
+                let key = match securewipe::load_public_key(&key_path) {
+                    Ok(key) => key,
+                    Err(e) => {
+                        errors.push(format!("key {}: {}", key_path.display(), e));
+                        continue;
+                    }
+                };
+                match securewipe::verify_certificate_signature(&cert, &key) {
+                    Ok(true) => {
+                        signature_valid = Some(true);
+                        trusted_key = Some(key_path.display().to_string());
+                        break;
+                    }
+                    Ok(false) => signature_valid = Some(false),
+                    Err(e) => {
+                        // Malformed signature block; no other key will do better
+                        signature_valid = Some(false);
+                        malformed = true;
+                        errors.push(format!("signature: {}", e));
+                        break;
+                    }
+                }
+            }
+            if signature_valid != Some(true) && !malformed {
+                if signature_valid == Some(false) {
+                    errors.push("signature: does not match any trusted public key".to_string());
+                }
+                // A supplied key only says who signed, never that the signer is trusted
+                if let Some(path) = pubkey_path {
+                    let matched = securewipe::load_public_key(std::path::Path::new(&path))
+                        .ok()
+                        .and_then(|key| securewipe::verify_certificate_signature(&cert, &key).ok())
+                        == Some(true);
+                    if matched {
+                        errors.push(format!("signature: matches only the supplied key {}, which is not a trusted key", path));
+                        untrusted_key = Some(path);
+                    }
+                    signature_valid = Some(false);
+                }
+            }
+        }
+    }
+
+    Ok(CertificateVerification {
+        cert_id: cert.get("cert_id").and_then(|v| v.as_str()).map(String::from),
+        cert_type: cert.get("cert_type").and_then(|v| v.as_str()).map(String::from),
+        schema_valid,
+        signature_valid,
+        trusted_key,
+        untrusted_key,
+        errors,
+    })
+}
+
+/// The CLI's data directory (XDG, system or legacy layout, or `data_dir` from the config)
+fn data_dir() -> Result<std::path::PathBuf, String> {
+    securewipe::config::Config::load()
+        .map(|config| config.data_dir)
+        .map_err(|e| format!("Failed to load configuration: {}", e))
+}
+
+fn certificates_dir() -> Result<std::path::PathBuf, String> {
+    securewipe::config::Config::load()
+        .map(|config| config.certificates_dir)
+        .map_err(|e| format!("Failed to load configuration: {}", e))
+}
+
+/// PDFs live next to the JSON or in the data directory's backups/; prefer the latter
+fn find_certificate_pdf(cert_id: &str, json_path: &Path) -> Option<String> {
+    let backups_pdf = data_dir()
+        .ok()
+        .map(|dir| dir.join("backups").join(format!("{}.pdf", cert_id)));
+    let default_pdf = json_path.with_extension("pdf");
+
+    backups_pdf
+        .into_iter()
+        .chain(std::iter::once(default_pdf))
+        .find(|path| path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+fn load_certificate_json(path: &Path) -> Option<serde_json::Value> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+#[tauri::command]
+async fn get_certificate_details(cert_id: String) -> Result<CertificateDetails, String> {
+    let cert_dir = certificates_dir()?;
+    let cert_path = securewipe::cert_id::find_certificate(&cert_dir, &cert_id).map_err(|e| e.to_string())?;
+    let certificate = load_certificate_json(&cert_path)
+        .ok_or_else(|| format!("Certificate not found or unreadable: {}", cert_path.display()))?;
+    let cert_type = certificate.get("cert_type").and_then(|v| v.as_str()).map(String::from);
+
+    let mut linked = Vec::new();
+    match cert_type.as_deref() {
+        Some("wipe") => {
+            // A wipe points back at the backup taken beforehand
+            let backup_id = certificate.pointer("/linkage/backup_cert_id").and_then(|v| v.as_str());
+            // An ID that can't name a stored certificate is a corrupt link; leave it out
+            // rather than join it onto the certificates directory
+            let link = backup_id.filter(|id| *id != "UNLINKED").and_then(|id| {
+                securewipe::cert_id::certificate_path(&cert_dir, id).ok().map(|path| (id, path))
+            });
+            if let Some((backup_id, backup_path)) = link {
+                // Keep the link even if the backup certificate is not on this machine
+                let backup = load_certificate_json(&backup_path);
+                let found = backup.is_some();
+                linked.push(LinkedCertificate {
+                    cert_id: backup_id.to_string(),
+                    cert_type: backup
+                        .and_then(|b| b.get("cert_type").and_then(|v| v.as_str()).map(String::from)),
+                    relation: "backup".to_string(),
+                    path: Some(backup_path.to_string_lossy().to_string()).filter(|_| found),
+                    pdf_path: find_certificate_pdf(backup_id, &backup_path),
+                });
+            }
+        }
+        Some("backup") => {
+            // Any wipe certificates that reference this backup
+            if let Ok(entries) = fs::read_dir(&cert_dir) {
+                for path in entries.flatten().map(|entry| entry.path()) {
+                    if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                        continue;
+                    }
+                    let other = match load_certificate_json(&path) {
+                        Some(other) => other,
+                        None => continue,
+                    };
+                    let references_us = other.pointer("/linkage/backup_cert_id")
+                        .and_then(|v| v.as_str()) == Some(cert_id.as_str());
+                    if !references_us {
+                        continue;
+                    }
+                    let other_id = other.get("cert_id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+                    if !securewipe::cert_id::is_storable(&other_id) {
+                        continue;
+                    }
+                    linked.push(LinkedCertificate {
+                        pdf_path: find_certificate_pdf(&other_id, &path),
+                        cert_id: other_id,
+                        cert_type: other.get("cert_type").and_then(|v| v.as_str()).map(String::from),
+                        relation: "wipe".to_string(),
+                        path: Some(path.to_string_lossy().to_string()),
+                    });
+                }
+            }
+            linked.sort_by(|a, b| a.cert_id.cmp(&b.cert_id));
+        }
+        _ => {}
+    }
+
+    Ok(CertificateDetails {
+        pdf_path: find_certificate_pdf(&cert_id, &cert_path),
+        cert_id,
+        cert_type,
+        path: cert_path.to_string_lossy().to_string(),
+        certificate,
+        linked,
+    })
 }
 
 #[tauri::command]
@@ -793,19 +1735,41 @@ fn main() {
 
     tauri::Builder::default()
         .manage(process_map)
+        .manage(FsScope::default())
         .invoke_handler(tauri::generate_handler![
             run_securewipe, 
             cancel_securewipe,
+            list_orphaned_sessions,
+            reattach_session,
+            terminate_session,
+            get_notification_prefs,
+            set_notification_prefs,
             execute_destructive_wipe,
             validate_wipe_device,
+            plan_wipe,
+            list_removable_destinations,
+            verify_backup,
+            mount_destination,
+            unmount_destination,
+            eject_destination,
+            identify_device,
+            verify_certificate,
+            get_certificate_details,
             browse_folders,
+            grant_folder_access,
             calculate_selection_size,
+            analyze_selection,
             get_home_dir,
+            get_data_dir,
             list_cert_files,
+            search_certificates,
+            get_device_aliases,
+            set_device_alias,
             read_file_content,
             file_exists,
             open_path,
-            generate_pdf_for_cert
+            generate_pdf_for_cert,
+            generate_pdf
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
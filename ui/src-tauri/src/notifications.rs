@@ -0,0 +1,254 @@
+// Desktop notifications for long operations that end while the window is minimized.
+// The message comes from the exit code and the CLI's structured progress lines, never
+// from matching free-text log output.
+
+use securewipe_types::{EventRecord, ProgressEvent};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::api::notification::Notification;
+use tauri::{Manager, Window};
+
+use crate::sessions::SessionRecord;
+
+/// Which notifications the user wants, from notifications.json in the data directory
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationPrefs {
+    pub enabled: bool,
+    pub on_success: bool,
+    pub on_failure: bool,
+    /// Skip the notification when the window is on screen; the UI already shows the result
+    pub only_when_minimized: bool,
+}
+
+impl Default for NotificationPrefs {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            on_success: true,
+            on_failure: true,
+            only_when_minimized: true,
+        }
+    }
+}
+
+impl NotificationPrefs {
+    fn path() -> Result<PathBuf, String> {
+        let dir = securewipe::config::Config::load()
+            .map_err(|e| format!("Failed to load configuration: {}", e))?
+            .data_dir;
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        Ok(dir.join("notifications.json"))
+    }
+
+    /// Saved preferences, or the defaults when none are saved or the file is unreadable
+    pub fn load() -> Self {
+        Self::path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path()?;
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize notification preferences: {}", e))?;
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| format!("Failed to write notification preferences {}: {}", path.display(), e))
+    }
+
+    fn allows(&self, notice: &Notice, minimized: bool) -> bool {
+        let wanted = if notice.failed { self.on_failure } else { self.on_success };
+        self.enabled && wanted && (minimized || !self.only_when_minimized)
+    }
+}
+
+/// What a finished session tells the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub title: String,
+    pub body: String,
+    pub failed: bool,
+}
+
+/// The last structured progress line in a session's stderr log
+pub fn last_progress(stderr_log: &Path) -> Option<ProgressEvent> {
+    let content = fs::read_to_string(stderr_log).ok()?;
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<EventRecord>(line.trim()).ok())
+        .find_map(|record| record.progress())
+}
+
+/// Why a session ended early, from its exit code or, for a reattached session whose
+/// code can't be collected, its last progress step
+pub fn interruption(code: Option<i32>, progress: Option<&ProgressEvent>) -> Option<&'static str> {
+    let removed = code == Some(securewipe::removal::EXIT_DEVICE_REMOVED)
+        || (code.is_none() && progress.map(|event| event.step.as_str()) == Some("device_removed"));
+    removed.then_some(securewipe::removal::INTERRUPTED_DEVICE_REMOVED)
+}
+
+/// The notice for a session that ended with `code`, or None for operations too short
+/// to be worth one. Only destructive wipes and backups qualify. `code` is None for a
+/// reattached session, whose exit status can't be collected; the last progress step
+/// decides then.
+pub fn notice_for(
+    args: &[String],
+    destructive: bool,
+    device: Option<&str>,
+    code: Option<i32>,
+    progress: Option<&ProgressEvent>,
+) -> Option<Notice> {
+    let operation = match args.first().map(String::as_str) {
+        Some("wipe") if destructive => "Wipe",
+        Some("backup") => "Backup",
+        _ => return None,
+    };
+    let target = device.map(|device| format!(" of {}", device)).unwrap_or_default();
+    let step = progress.map(|event| event.step.as_str());
+    let verification_failed = progress
+        .filter(|event| event.step == "wipe_completed")
+        .and_then(|event| event.details.get("verification_passed"))
+        .and_then(|value| value.as_bool())
+        == Some(false);
+
+    let failure = match code {
+        _ if interruption(code, progress).is_some() => Some("was interrupted: the device was removed".to_string()),
+        Some(0) if verification_failed => Some("finished, but verification FAILED".to_string()),
+        Some(0) => None,
+        Some(-2) => Some("was stopped by the timeout watchdog".to_string()),
+        Some(code) => Some(match step {
+            Some(step) => format!("failed with exit code {} after step {}", code, step),
+            None => format!("failed with exit code {}", code),
+        }),
+        None if verification_failed => Some("finished, but verification FAILED".to_string()),
+        None if step == Some("wipe_completed") || operation == "Backup" => None,
+        None => Some("ended without reporting completion".to_string()),
+    };
+
+    Some(match failure {
+        Some(reason) => Notice {
+            title: format!("SecureWipe: {} failed", operation),
+            body: format!("{}{} {}", operation, target, reason),
+            failed: true,
+        },
+        None => Notice {
+            title: format!("SecureWipe: {} complete", operation),
+            body: format!("{}{} finished successfully", operation, target),
+            failed: false,
+        },
+    })
+}
+
+/// Show the OS notification for a finished session, if the user's preferences want one
+pub fn notify_finished(window: &Window, record: &SessionRecord, code: Option<i32>) {
+    let progress = last_progress(&record.stderr_log);
+    let notice = match notice_for(&record.args, record.destructive, record.device.as_deref(), code, progress.as_ref()) {
+        Some(notice) => notice,
+        None => return,
+    };
+
+    let minimized = window.is_minimized().unwrap_or(false);
+    if !NotificationPrefs::load().allows(&notice, minimized) {
+        return;
+    }
+
+    let identifier = window.config().tauri.bundle.identifier.clone();
+    if let Err(e) = Notification::new(identifier).title(&notice.title).body(&notice.body).show() {
+        securewipe::events::warn(&format!("Could not show notification: {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn progress(line: &str) -> ProgressEvent {
+        serde_json::from_str(line).unwrap()
+    }
+
+    #[test]
+    fn test_notice_for_wipe_outcomes() {
+        let wipe = args(&["wipe", "--device", "/dev/sdb", "--danger-allow-wipe"]);
+        let completed = progress(r#"{"step":"wipe_completed","verification_passed":true,"timestamp":"t"}"#);
+        let unverified = progress(r#"{"step":"wipe_completed","verification_passed":false,"timestamp":"t"}"#);
+
+        let notice = notice_for(&wipe, true, Some("/dev/sdb"), Some(0), Some(&completed)).unwrap();
+        assert!(!notice.failed);
+        assert_eq!(notice.body, "Wipe of /dev/sdb finished successfully");
+
+        // A clean exit isn't a success when the structured result says verification failed
+        let notice = notice_for(&wipe, true, Some("/dev/sdb"), Some(0), Some(&unverified)).unwrap();
+        assert!(notice.failed);
+
+        let started = progress(r#"{"step":"device_risk_check","timestamp":"t"}"#);
+        let notice = notice_for(&wipe, true, Some("/dev/sdb"), Some(1), Some(&started)).unwrap();
+        assert_eq!(notice.body, "Wipe of /dev/sdb failed with exit code 1 after step device_risk_check");
+        assert!(notice_for(&wipe, true, None, Some(-2), None).unwrap().failed);
+
+        // Reattached sessions have no exit code
+        assert!(!notice_for(&wipe, true, None, None, Some(&completed)).unwrap().failed);
+        assert!(notice_for(&wipe, true, None, None, Some(&started)).unwrap().failed);
+
+        // An unplugged drive is reported as such, from the exit code or the last step
+        let removed = notice_for(&wipe, true, Some("/dev/sdb"), Some(4), Some(&started)).unwrap();
+        assert_eq!(removed.body, "Wipe of /dev/sdb was interrupted: the device was removed");
+        let removed_step = progress(r#"{"step":"device_removed","device":"/dev/sdb","timestamp":"t"}"#);
+        assert_eq!(interruption(None, Some(&removed_step)), Some("interrupted-device-removed"));
+        assert_eq!(interruption(Some(1), Some(&removed_step)), None);
+
+        // Planning runs and other short commands never notify
+        assert!(notice_for(&args(&["wipe", "--format", "json"]), false, None, Some(0), None).is_none());
+        assert!(notice_for(&args(&["discover"]), false, None, Some(1), None).is_none());
+        assert!(notice_for(&args(&["backup"]), false, None, Some(0), None).is_some());
+    }
+
+    #[test]
+    fn test_prefs_gate_notices() {
+        let success = Notice { title: String::new(), body: String::new(), failed: false };
+        let failure = Notice { failed: true, ..success.clone() };
+
+        let prefs = NotificationPrefs::default();
+        assert!(prefs.allows(&success, true));
+        assert!(!prefs.allows(&success, false));
+
+        let prefs = NotificationPrefs { on_success: false, only_when_minimized: false, ..Default::default() };
+        assert!(!prefs.allows(&success, true));
+        assert!(prefs.allows(&failure, false));
+
+        let prefs = NotificationPrefs { enabled: false, ..Default::default() };
+        assert!(!prefs.allows(&failure, true));
+
+        // Missing fields fall back to the defaults
+        let prefs: NotificationPrefs = serde_json::from_str(r#"{"on_success": false}"#).unwrap();
+        assert_eq!(prefs, NotificationPrefs { on_success: false, ..Default::default() });
+    }
+
+    #[test]
+    fn test_last_progress_skips_log_messages() {
+        let path = std::env::temp_dir().join(format!("securewipe_notify_{}.log", std::process::id()));
+        fs::write(
+            &path,
+            concat!(
+                "{\"v\":1,\"type\":\"progress\",\"id\":1,\"ts\":\"t\",\"payload\":{\"step\":\"wipe_plan_generated\",\"timestamp\":\"t\"}}\n",
+                "{\"v\":1,\"type\":\"progress\",\"id\":2,\"ts\":\"t\",\"payload\":{\"step\":\"wipe_completed\",\"verification_passed\":true,\"timestamp\":\"t\"}}\n",
+                "{\"v\":1,\"type\":\"log\",\"id\":3,\"ts\":\"t\",\"payload\":{\"level\":\"info\",\"message\":\"done\",\"timestamp\":\"t\"}}\n",
+                "plain text\n",
+            ),
+        )
+        .unwrap();
+        assert_eq!(last_progress(&path).unwrap().step, "wipe_completed");
+        fs::remove_file(&path).unwrap();
+        assert!(last_progress(&path).is_none());
+    }
+}
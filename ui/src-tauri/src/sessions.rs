@@ -0,0 +1,201 @@
+// On-disk records of running CLI sessions, so a crashed or restarted UI can find
+// wipes that are still going and reattach to (or stop) them.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Window;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub pid: u32,
+    pub device: Option<String>,
+    pub destructive: bool,
+    pub args: Vec<String>,
+    pub started_at: String,
+    pub stdout_log: PathBuf,
+    pub stderr_log: PathBuf,
+}
+
+/// sessions/ in the data directory, created on demand
+pub fn sessions_dir() -> Result<PathBuf, String> {
+    let dir = securewipe::config::Config::load()
+        .map_err(|e| format!("Failed to load configuration: {}", e))?
+        .data_dir
+        .join("sessions");
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create sessions directory {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// Session IDs come from the webview and name files in sessions/, so only
+/// `[A-Za-z0-9_-]{1,64}` is accepted
+pub fn check_session_id(session_id: &str) -> Result<(), String> {
+    let valid = (1..=64).contains(&session_id.len())
+        && session_id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid session ID {:?}", session_id))
+    }
+}
+
+impl SessionRecord {
+    pub fn new(session_id: &str, device: Option<String>, destructive: bool, args: &[String]) -> Result<Self, String> {
+        check_session_id(session_id)?;
+        let dir = sessions_dir()?;
+        Ok(Self {
+            session_id: session_id.to_string(),
+            pid: 0,
+            device,
+            destructive,
+            args: args.to_vec(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            stdout_log: dir.join(format!("{}.stdout.log", session_id)),
+            stderr_log: dir.join(format!("{}.stderr.log", session_id)),
+        })
+    }
+
+    fn record_path(session_id: &str) -> Result<PathBuf, String> {
+        check_session_id(session_id)?;
+        Ok(sessions_dir()?.join(format!("{}.json", session_id)))
+    }
+
+    /// Write atomically so a crash mid-write never leaves a torn record
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::record_path(&self.session_id)?;
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize session record: {}", e))?;
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| format!("Failed to write session record {}: {}", path.display(), e))
+    }
+
+    pub fn load(session_id: &str) -> Result<Self, String> {
+        let path = Self::record_path(session_id)?;
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Session {} not found: {}", session_id, e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Corrupt session record {}: {}", path.display(), e))
+    }
+
+    /// Drop the record; the log files are kept for the audit trail
+    pub fn remove(&self) {
+        if let Ok(path) = Self::record_path(&self.session_id) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+pub use securewipe::cleanup::process_alive;
+
+/// Records whose process is still alive; stale records are cleaned up on the way
+pub fn orphaned_sessions() -> Vec<SessionRecord> {
+    let dir = match sessions_dir() {
+        Ok(dir) => dir,
+        Err(_) => return Vec::new(),
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut sessions = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let record: SessionRecord = match fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+        {
+            Some(record) => record,
+            None => continue,
+        };
+
+        if process_alive(record.pid) {
+            sessions.push(record);
+        } else {
+            record.remove();
+        }
+    }
+
+    sessions.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    sessions
+}
+
+/// Follow a log file from the start, emitting each complete line, until `done` is
+/// set and the file has been drained
+pub async fn tail_log(
+    path: PathBuf,
+    stream: &'static str,
+    window: Window,
+    last_output: Arc<Mutex<std::time::Instant>>,
+    done: Arc<AtomicBool>,
+) {
+    let file = match open_when_present(&path, &done).await {
+        Some(file) => file,
+        None => return,
+    };
+    let mut reader = BufReader::new(file);
+    let mut pending = Vec::new();
+
+    loop {
+        // Read `done` before the read so nothing written just before exit is missed
+        let finished = done.load(Ordering::SeqCst);
+        match reader.read_until(b'\n', &mut pending).await {
+            Ok(0) | Err(_) => {
+                if finished {
+                    if !pending.is_empty() {
+                        crate::emit_log_line(&window, stream, String::from_utf8_lossy(&pending).to_string());
+                    }
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Ok(_) => {
+                // A partial line stays pending until the writer finishes it
+                if pending.ends_with(b"\n") {
+                    *last_output.lock().unwrap() = std::time::Instant::now();
+                    let line = String::from_utf8_lossy(&pending)
+                        .trim_end_matches(&['\r', '\n'][..])
+                        .to_string();
+                    pending.clear();
+                    crate::emit_log_line(&window, stream, line);
+                }
+            }
+        }
+    }
+}
+
+async fn open_when_present(path: &Path, done: &AtomicBool) -> Option<tokio::fs::File> {
+    loop {
+        if let Ok(file) = tokio::fs::File::open(path).await {
+            return Some(file);
+        }
+        if done.load(Ordering::SeqCst) {
+            return None;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_id_charset() {
+        assert!(check_session_id("wipe_1760000000000").is_ok());
+        assert!(check_session_id("session-A9").is_ok());
+        for bad in ["", "../../etc/cron.d/x", "/tmp/x", "a.b", "a b", &"a".repeat(65)] {
+            assert!(check_session_id(bad).is_err(), "{:?}", bad);
+        }
+    }
+}
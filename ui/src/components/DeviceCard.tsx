@@ -1,4 +1,5 @@
 import type { Device } from '../contexts/AppContext';
+import { exactBytes, formatCapacity } from '../utils/units';
 
 interface RiskBadgeProps {
     level: Device['risk_level'];
@@ -27,11 +28,6 @@ function DeviceCard({ device, selected = false, onSelect }: DeviceCardProps) {
         }
     };
 
-    const formatCapacity = (bytes: number) => {
-        const gb = (bytes / (1024 ** 3)).toFixed(1);
-        return `${gb} GB`;
-    };
-
     const formatSerial = (serial: string) => {
         if (!serial || serial === 'N/A') return serial;
         
@@ -52,7 +48,9 @@ function DeviceCard({ device, selected = false, onSelect }: DeviceCardProps) {
     return (
         <div className={cardClassName} onClick={handleClick}>
             <div className="flex justify-between items-center mb-4">
-                <h3 className="font-semibold">{device.model}</h3>
+                <h3 className="font-semibold" title={device.device_id}>
+                    {device.alias ? `${device.alias} (${device.model})` : device.model}
+                </h3>
                 <RiskBadge level={device.risk_level} />
             </div>
 
@@ -72,7 +70,7 @@ function DeviceCard({ device, selected = false, onSelect }: DeviceCardProps) {
                 </div>
                 <div>
                     <span className="font-medium">Capacity:</span>
-                    <div>{formatCapacity(device.capacity)}</div>
+                    <div title={exactBytes(device.capacity)}>{formatCapacity(device.capacity, device.capacity_display)}</div>
                 </div>
                 <div>
                     <span className="font-medium">Bus:</span>
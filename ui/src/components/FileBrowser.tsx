@@ -28,6 +28,7 @@ const FileBrowser: React.FC<FileBrowserProps> = ({
     navigateTo,
     navigateUp,
     goHome,
+    grantFolderAccess,
     toggleSelection,
     selectAll,
     clearSelection,
@@ -125,6 +126,13 @@ const FileBrowser: React.FC<FileBrowserProps> = ({
         >
           🏠 Home
         </button>
+        <button
+          onClick={grantFolderAccess}
+          className="px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded text-gray-700"
+          title="Allow access to a folder outside your home directory"
+        >
+          📂 Grant Folder Access
+        </button>
         
         {parts.map((part, index) => (
           <span key={index}>
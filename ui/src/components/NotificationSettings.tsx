@@ -0,0 +1,58 @@
+import { useEffect, useState } from 'react';
+import { useSecureWipe } from '../hooks/useSecureWipe';
+import type { NotificationPrefs } from '../hooks/useSecureWipe';
+
+const TOGGLES: { key: keyof NotificationPrefs; label: string }[] = [
+    { key: 'enabled', label: 'Notify me when a wipe or backup ends' },
+    { key: 'on_success', label: 'Include successful operations' },
+    { key: 'on_failure', label: 'Include failures and verification problems' },
+    { key: 'only_when_minimized', label: 'Only while the window is minimized' },
+];
+
+function NotificationSettings() {
+    const { getNotificationPrefs, setNotificationPrefs } = useSecureWipe();
+    const [prefs, setPrefs] = useState<NotificationPrefs | null>(null);
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        getNotificationPrefs().then(setPrefs).catch((e) => setError(String(e)));
+    }, [getNotificationPrefs]);
+
+    const toggle = async (key: keyof NotificationPrefs, value: boolean) => {
+        if (!prefs) return;
+        const next = { ...prefs, [key]: value };
+        setPrefs(next);
+        try {
+            await setNotificationPrefs(next);
+            setError(null);
+        } catch (e) {
+            setError(String(e));
+        }
+    };
+
+    if (!prefs) {
+        return null;
+    }
+
+    return (
+        <div className="card mt-6" style={{ textAlign: 'left' }}>
+            <h3 className="font-semibold mb-2">🔔 Desktop Notifications</h3>
+            {TOGGLES.map(({ key, label }) => (
+                <label key={key} className="flex items-center gap-2 mt-2 text-sm" style={{ cursor: 'pointer' }}>
+                    <input
+                        type="checkbox"
+                        checked={prefs[key]}
+                        disabled={key !== 'enabled' && !prefs.enabled}
+                        onChange={(e) => toggle(key, e.target.checked)}
+                    />
+                    {label}
+                </label>
+            ))}
+            {error && (
+                <p className="text-sm mt-2" style={{ color: '#dc2626' }}>{error}</p>
+            )}
+        </div>
+    );
+}
+
+export default NotificationSettings;
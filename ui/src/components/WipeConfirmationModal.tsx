@@ -1,5 +1,7 @@
 import { useState } from 'react';
 import { Device } from '../contexts/AppContext';
+import { useSecureWipe } from '../hooks/useSecureWipe';
+import { exactBytes, formatCapacity } from '../utils/units';
 
 interface WipeConfirmationModalProps {
     device: Device;
@@ -11,6 +13,9 @@ interface WipeConfirmationModalProps {
 
 function WipeConfirmationModal({ device, policy, onConfirm, onCancel, isOpen }: WipeConfirmationModalProps) {
     const [userInput, setUserInput] = useState('');
+    const [identifying, setIdentifying] = useState(false);
+    const [identifyError, setIdentifyError] = useState<string | null>(null);
+    const { identifyDevice } = useSecureWipe();
     const expectedInput = `WIPE ${device.serial || 'UNKNOWN'}`;
 
     const handleConfirm = () => {
@@ -18,6 +23,18 @@ function WipeConfirmationModal({ device, policy, onConfirm, onCancel, isOpen }:
         setUserInput(''); // Clear input after confirm
     };
 
+    const handleIdentify = async () => {
+        setIdentifying(true);
+        setIdentifyError(null);
+        try {
+            await identifyDevice(device.path, 15);
+        } catch (error) {
+            setIdentifyError(String(error));
+        } finally {
+            setIdentifying(false);
+        }
+    };
+
     if (!isOpen) return null;
 
     const isValidInput = userInput === expectedInput;
@@ -98,7 +115,7 @@ function WipeConfirmationModal({ device, policy, onConfirm, onCancel, isOpen }:
                                 {device.serial || 'Unknown'}
                             </div>
                             <div><strong>Capacity:</strong></div>
-                            <div>{(device.capacity / (1024 ** 3)).toFixed(1)} GB</div>
+                            <div title={exactBytes(device.capacity)}>{formatCapacity(device.capacity, device.capacity_display)}</div>
                             <div><strong>Path:</strong></div>
                             <div style={{ 
                                 fontFamily: 'monospace',
@@ -118,6 +135,26 @@ function WipeConfirmationModal({ device, policy, onConfirm, onCancel, isOpen }:
                             </div>
                         </div>
                     </div>
+                    <div style={{ display: 'flex', alignItems: 'center', gap: '0.75rem', marginTop: '0.75rem' }}>
+                        <button
+                            onClick={handleIdentify}
+                            disabled={identifying}
+                            style={{
+                                padding: '0.5rem 1rem',
+                                backgroundColor: '#ffffff',
+                                color: '#1e40af',
+                                border: '1px solid #93c5fd',
+                                borderRadius: '6px',
+                                cursor: identifying ? 'not-allowed' : 'pointer',
+                                fontSize: '0.85rem'
+                            }}
+                        >
+                            {identifying ? 'Blinking drive LED...' : 'Identify drive (blink LED)'}
+                        </button>
+                        <span style={{ fontSize: '0.8rem', color: identifyError ? '#dc2626' : '#6b7280' }}>
+                            {identifyError || 'Check which caddy or port is blinking before you confirm.'}
+                        </span>
+                    </div>
                 </div>
 
                 <div style={{ marginBottom: '1.5rem' }}>
@@ -5,7 +5,11 @@ export interface Device {
     path: string;
     model: string;
     serial: string;
+    device_id?: string;
+    alias?: string;
     capacity: number;
+    /** Capacity in the configured SI or IEC units, from `discover` */
+    capacity_display?: string;
     bus: string;
     mountpoints: string[];
     risk_level: 'CRITICAL' | 'HIGH' | 'SAFE';
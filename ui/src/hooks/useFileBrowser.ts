@@ -1,5 +1,6 @@
 import { useState, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
+import { formatBytes } from '../utils/units';
 
 export interface FileSystemEntry {
   name: string;
@@ -15,6 +16,22 @@ export interface DirectoryListing {
   total_items: number;
 }
 
+export interface FolderBreakdown {
+  path: string;
+  size: number;
+  file_count: number;
+}
+
+export interface SelectionAnalysis {
+  total_size: number;
+  total_files: number;
+  folders: FolderBreakdown[];
+  largest_files: { path: string; size: number }[];
+  write_bytes_per_sec?: number;
+  estimated_seconds?: number;
+  write_benchmark_error?: string;
+}
+
 export interface UseFileBrowserReturn {
   currentPath: string;
   listing: DirectoryListing | null;
@@ -26,6 +43,7 @@ export interface UseFileBrowserReturn {
   navigateTo: (path: string) => Promise<void>;
   navigateUp: () => Promise<void>;
   goHome: () => Promise<void>;
+  grantFolderAccess: () => Promise<void>;
   
   // Selection
   toggleSelection: (path: string) => void;
@@ -34,6 +52,7 @@ export interface UseFileBrowserReturn {
   
   // Size calculation
   calculateSelectionSize: () => Promise<number>;
+  analyzeSelection: (destination?: string) => Promise<SelectionAnalysis | null>;
   
   // Utilities
   formatFileSize: (bytes: number) => string;
@@ -96,6 +115,18 @@ export const useFileBrowser = (): UseFileBrowserReturn => {
     }
   }, []);
 
+  // Folders outside home and mounted media must be granted through the native picker
+  const grantFolderAccess = useCallback(async () => {
+    try {
+      const granted = await invoke<string | null>('grant_folder_access');
+      if (granted) {
+        await navigateTo(granted);
+      }
+    } catch (err) {
+      setError(typeof err === 'string' ? err : String(err));
+    }
+  }, [navigateTo]);
+
   const toggleSelection = useCallback((path: string) => {
     setSelectedPaths(prev => {
       if (prev.includes(path)) {
@@ -129,15 +160,21 @@ export const useFileBrowser = (): UseFileBrowserReturn => {
     }
   }, [selectedPaths]);
 
-  const formatFileSize = useCallback((bytes: number): string => {
-    if (bytes === 0) return '0 B';
-    
-    const k = 1024;
-    const sizes = ['B', 'KB', 'MB', 'GB', 'TB'];
-    const i = Math.floor(Math.log(bytes) / Math.log(k));
+  const analyzeSelection = useCallback(async (destination?: string): Promise<SelectionAnalysis | null> => {
+    if (selectedPaths.length === 0) return null;
     
-    return parseFloat((bytes / Math.pow(k, i)).toFixed(2)) + ' ' + sizes[i];
-  }, []);
+    try {
+      return await invoke<SelectionAnalysis>('analyze_selection', {
+        paths: selectedPaths,
+        destination: destination ?? null,
+      });
+    } catch (err) {
+      console.error('Failed to analyze selection:', err);
+      return null;
+    }
+  }, [selectedPaths]);
+
+  const formatFileSize = useCallback((bytes: number): string => formatBytes(bytes), []);
 
   const isSelected = useCallback((path: string): boolean => {
     return selectedPaths.includes(path);
@@ -153,12 +190,14 @@ export const useFileBrowser = (): UseFileBrowserReturn => {
     navigateTo,
     navigateUp,
     goHome,
+    grantFolderAccess,
     
     toggleSelection,
     selectAll,
     clearSelection,
     
     calculateSelectionSize,
+    analyzeSelection,
     
     formatFileSize,
     isSelected,
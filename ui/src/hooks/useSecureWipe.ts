@@ -1,4 +1,4 @@
-import { useCallback, useEffect, useState } from 'react';
+import { useCallback, useEffect, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { open } from '@tauri-apps/api/shell';
@@ -11,6 +11,157 @@ export interface LogEvent {
     stream: 'stdout' | 'stderr';
 }
 
+/** One record of the CLI's stderr event stream (docs/EVENT_PROTOCOL.md) */
+export interface CliEvent {
+    v: number;
+    type: 'log' | 'progress' | 'result' | 'error';
+    id: number;
+    ts: string;
+    payload: any;
+}
+
+/** The event on a stderr line, or null for anything else */
+export function parseCliEvent(line: string): CliEvent | null {
+    try {
+        const entry = JSON.parse(line);
+        return entry && typeof entry.v === 'number' && typeof entry.type === 'string' && 'payload' in entry
+            ? entry as CliEvent
+            : null;
+    } catch {
+        return null;
+    }
+}
+
+/** WipePlan as serialized by the core library's plan_wipe */
+export interface CoreWipePlan {
+    device: string;
+    risk: string;
+    policy: 'CLEAR' | 'PURGE';
+    hpa_dco_clear: boolean;
+    main_method: string;
+    verification: {
+        strategy: string;
+        samples: number;
+    };
+    blocked: boolean;
+    reason: string | null;
+}
+
+export interface LocalVerifyResult {
+    cert_id: string | null;
+    cert_type: string | null;
+    schema_valid: boolean;
+    signature_valid: boolean | null;
+    trusted_key: string | null;
+    untrusted_key: string | null;
+    errors: string[];
+}
+
+export interface LinkedCertificate {
+    cert_id: string;
+    cert_type: string | null;
+    relation: 'backup' | 'wipe';
+    path: string | null;
+    pdf_path: string | null;
+}
+
+export interface CertificateDetails {
+    cert_id: string;
+    cert_type: string | null;
+    path: string;
+    pdf_path: string | null;
+    certificate: Record<string, any>;
+    linked: LinkedCertificate[];
+}
+
+/** A mounted removable/USB volume offered as a backup destination */
+export interface RemovableVolume {
+    device: string;
+    backing_device: string;
+    mountpoint: string | null;
+    label: string | null;
+    filesystem: string | null;
+    capacity_bytes: number;
+    free_bytes: number | null;
+    bus: string | null;
+    model: string | null;
+}
+
+export interface MountedDestination {
+    device: string;
+    mountpoint: string;
+    mounted_by_us: boolean;
+}
+
+/** How `identify_device` blinked a drive */
+export interface IdentifyReport {
+    device: string;
+    method: 'ledctl' | 'reads';
+    seconds: number;
+    reads: number;
+}
+
+/** A CLI run left over from a previous UI session that is still running */
+export interface OrphanedSession {
+    session_id: string;
+    pid: number;
+    device: string | null;
+    destructive: boolean;
+    args: string[];
+    started_at: string;
+}
+
+/** Which OS notifications to show when a wipe or backup ends; see notifications.rs */
+export interface NotificationPrefs {
+    enabled: boolean;
+    on_success: boolean;
+    on_failure: boolean;
+    only_when_minimized: boolean;
+}
+
+export interface BackupVerificationReport {
+    backup_dir: string;
+    manifest_valid: boolean;
+    files_checked: number;
+    files_passed: number;
+    presence_only: number;
+    failures: { path: string; reason: string }[];
+    passed: boolean;
+}
+
+/** Filters for search_certificates; every field is optional */
+export interface CertificateQuery {
+    text?: string;
+    cert_type?: string;
+    result?: string;
+    policy?: string;
+    since?: string;
+    until?: string;
+    offset?: number;
+    limit?: number;
+}
+
+export interface CertificateHit {
+    cert_id: string;
+    cert_type: string;
+    serial_number: string | null;
+    device_serial: string | null;
+    device_model: string | null;
+    policy: string | null;
+    result: string | null;
+    created_at: string;
+    path: string;
+    pdf_path: string | null;
+}
+
+/** One page of search results, newest first */
+export interface CertificatePage {
+    total: number;
+    offset: number;
+    limit: number;
+    items: CertificateHit[];
+}
+
 export interface RunResult {
     exitCode: number;
     stdout: string[];
@@ -36,21 +187,73 @@ export interface VerifyResult {
 interface ExitEvent {
     code: number | null;
     ts: string;
+    reason?: string;
+}
+
+interface TimeoutEvent {
+    session_id: string;
+    kind: 'total' | 'stalled';
+    action: 'killed' | 'warned';
+    elapsed_secs: number;
+    silent_secs: number;
+    ts: string;
+}
+
+interface SafeToRemoveEvent {
+    device: string;
+    disk: string;
+    ts: string;
 }
 
 const MAX_LOG_LINES = 2000;
 
+// Several screens use this hook; only the first mount looks for leftover sessions
+let orphanCheckDone = false;
+
 export function useSecureWipe() {
     const { dispatch, addToast, addLog } = useApp();
     const [logs, setLogs] = useState<LogEvent[]>([]);
     const [running, setRunning] = useState(false);
     const [currentSession, setCurrentSession] = useState<string | null>(null);
+    // Error classes already hinted at during this run, so a run of skipped files gives one toast
+    const shownHints = useRef(new Set<string>());
 
     // Set up event listeners for streaming logs
     useEffect(() => {
+        // The CLI adds error_class and hint to JSON lines for I/O failures it recognises
+        const showRemediationHint = (line: string) => {
+            if (!line.includes('"hint"')) return;
+            const entry = parseCliEvent(line)?.payload;
+            if (!entry) return;
+            const hint = entry.hint ?? entry.data?.hint;
+            const errorClass = entry.error_class ?? entry.data?.error_class ?? hint;
+            if (typeof hint === 'string' && !shownHints.current.has(errorClass)) {
+                shownHints.current.add(errorClass);
+                addToast(hint, 'warning');
+            }
+        };
+
+        // The CLI logs power_event lines when the machine goes on or off mains during a wipe
+        const showPowerEvent = (line: string) => {
+            if (!line.includes('"power_event"')) return;
+            const entry = parseCliEvent(line)?.payload;
+            if (!entry) return;
+            if (entry.event === 'ac_lost') {
+                addToast(entry.paused
+                    ? 'AC power lost: the wipe is paused until power is restored'
+                    : 'AC power lost: the wipe continues on battery', 'warning');
+            } else if (entry.event === 'ac_restored') {
+                addToast('AC power restored', 'info');
+            } else if (entry.event === 'battery_low') {
+                addToast(`Battery low (${entry.battery_percent}%): connect AC power`, 'error');
+            }
+        };
+
         let unlistenStdout: UnlistenFn | undefined;
         let unlistenStderr: UnlistenFn | undefined;
         let unlistenExit: UnlistenFn | undefined;
+        let unlistenTimeout: UnlistenFn | undefined;
+        let unlistenSafeToRemove: UnlistenFn | undefined;
 
         const setupListeners = async () => {
             unlistenStdout = await listen<LogEvent>('securewipe://stdout', (event) => {
@@ -69,6 +272,8 @@ export function useSecureWipe() {
                     const newLogs = [...prev, logEvent];
                     return newLogs.slice(-MAX_LOG_LINES);
                 });
+                showRemediationHint(logEvent.line);
+                showPowerEvent(logEvent.line);
                 // Only add to context logs, not duplicate - filter out signing key errors
                 if (!logEvent.line.includes('Failed to load signing key') && 
                     !logEvent.line.includes('SECUREWIPE_SIGN_KEY_PATH not set')) {
@@ -78,12 +283,35 @@ export function useSecureWipe() {
 
             unlistenExit = await listen<ExitEvent>('securewipe://exit', (event) => {
                 console.log('Process exited with code:', event.payload.code);
+                shownHints.current.clear();
+                if (event.payload.reason === 'interrupted-device-removed') {
+                    addToast('Operation interrupted: the device was removed. Reconnect it and start again.', 'error');
+                }
                 setRunning(false);
                 setCurrentSession(null);
                 
                 // Don't treat all non-zero exits as failures immediately
                 // Let the specific operation handlers decide based on output content
             });
+
+            unlistenTimeout = await listen<TimeoutEvent>('securewipe://timeout', (event) => {
+                const { kind, action, elapsed_secs, silent_secs } = event.payload;
+                const minutes = (secs: number) => Math.round(secs / 60);
+                if (action === 'killed') {
+                    const why = kind === 'total'
+                        ? `exceeded its time limit after ${minutes(elapsed_secs)} min`
+                        : `produced no output for ${minutes(silent_secs)} min`;
+                    addToast(`Operation stopped: it ${why}`, 'error');
+                } else {
+                    addToast(`No output for ${minutes(silent_secs)} min — the operation is still running`, 'warning');
+                }
+                addLog(`[${event.payload.ts}] WATCHDOG: ${kind} timeout (${action})`);
+            });
+
+            unlistenSafeToRemove = await listen<SafeToRemoveEvent>('securewipe://safe-to-remove', (event) => {
+                addToast(`${event.payload.disk} is powered off and safe to remove`, 'success');
+                addLog(`[${event.payload.ts}] Destination ${event.payload.disk} ejected`);
+            });
         };
 
         setupListeners();
@@ -92,8 +320,43 @@ export function useSecureWipe() {
             if (unlistenStdout) unlistenStdout();
             if (unlistenStderr) unlistenStderr();
             if (unlistenExit) unlistenExit();
+            if (unlistenTimeout) unlistenTimeout();
+            if (unlistenSafeToRemove) unlistenSafeToRemove();
         };
-    }, [addLog]);
+    }, [addLog, addToast]);
+
+    const listOrphanedSessions = useCallback(async (): Promise<OrphanedSession[]> => {
+        return await invoke<OrphanedSession[]>('list_orphaned_sessions');
+    }, []);
+
+    /** Stream an orphaned session's output again; the exit event fires when it ends */
+    const reattachSession = useCallback(async (sessionId: string): Promise<void> => {
+        setLogs([]);
+        dispatch({ type: 'CLEAR_LOGS' });
+        await invoke('reattach_session', { sessionId });
+        setCurrentSession(sessionId);
+        setRunning(true);
+    }, [dispatch]);
+
+    const terminateSession = useCallback(async (sessionId: string): Promise<void> => {
+        await invoke('terminate_session', { sessionId });
+        addToast('Previous operation stopped', 'warning');
+    }, [addToast]);
+
+    // A wipe started before a UI crash or restart keeps running; pick it back up
+    useEffect(() => {
+        if (orphanCheckDone) return;
+        orphanCheckDone = true;
+        listOrphanedSessions()
+            .then(async (sessions) => {
+                const session = sessions.find(s => s.destructive) ?? sessions[0];
+                if (!session) return;
+                await reattachSession(session.session_id);
+                const what = session.device ? `${session.args[0]} on ${session.device}` : session.args[0];
+                addToast(`Reattached to a running ${what} from a previous session`, 'info');
+            })
+            .catch(error => console.warn('Failed to check for running sessions:', error));
+    }, [listOrphanedSessions, reattachSession, addToast]);
 
     const run = useCallback(async (args: string[], sessionId?: string): Promise<RunResult> => {
         const session = sessionId || `session_${Date.now()}`;
@@ -215,17 +478,20 @@ export function useSecureWipe() {
             const result = await run(['discover', '--format', 'json']);
 
             if (result.exitCode === 0) {
-                console.log('CLI stdout:', result.stdout);
-                console.log('CLI stderr:', result.stderr);
-                
                 const devices: Device[] = parseJsonOutput(result.stdout);
+                // Names are a convenience; discovery still works without the inventory
+                const aliases: Record<string, string> = await invoke<Record<string, string>>('get_device_aliases')
+                    .catch(() => ({}));
                 
                 // Map the CLI output format to our expected Device interface
                 const mappedDevices: Device[] = devices.map((device: any) => ({
                     path: device.name || device.path || '',
                     model: device.model || 'Unknown Device',
                     serial: device.serial || 'N/A',
+                    device_id: device.device_id,
+                    alias: device.device_id ? aliases[device.device_id] : undefined,
                     capacity: device.capacity_bytes || device.capacity || 0,
+                    capacity_display: device.capacity_display,
                     bus: device.bus || 'Unknown',
                     mountpoints: device.mountpoints || [],
                     risk_level: device.risk_level || 'SAFE',
@@ -258,43 +524,47 @@ export function useSecureWipe() {
         samples?: number;
         isoMode?: boolean;
         noEnrich?: boolean;
+        verifyMode?: string;
+        overwriteProfile?: string;
+        virtualMedia?: boolean;
+        allowNetworkDevice?: boolean;
+        backupCertId?: string | null;
     }): Promise<WipePlan> => {
         dispatch({ type: 'SET_OPERATION', payload: 'Creating wipe plan...' });
 
         try {
-            const args = ['wipe', '--device', opts.device, '--format', 'json'];
-            if (opts.samples) args.push('--samples', String(opts.samples));
-            if (opts.isoMode) args.push('--iso-mode');
-            if (opts.noEnrich) args.push('--no-enrich');
+            // Typed plan straight from the core library; no stdout parsing
+            const corePlan = await invoke<CoreWipePlan>('plan_wipe', {
+                device: opts.device,
+                samples: opts.samples ?? null,
+                isoMode: opts.isoMode ?? false,
+                noEnrich: opts.noEnrich ?? false,
+                verifyMode: opts.verifyMode ?? null,
+                overwriteProfile: opts.overwriteProfile ?? null,
+                virtualMedia: opts.virtualMedia ?? false,
+                allowNetworkDevice: opts.allowNetworkDevice ?? false,
+                backupCertId: opts.backupCertId ?? null,
+            });
 
-            const result = await run(args);
+            const wipePlan: WipePlan = {
+                device_path: corePlan.device,
+                policy: corePlan.policy,
+                main_method: corePlan.main_method,
+                hpa_dco_clear: corePlan.hpa_dco_clear,
+                verification: {
+                    samples: corePlan.verification.samples
+                },
+                blocked: corePlan.blocked,
+                block_reason: corePlan.reason ?? undefined
+            };
 
-            if (result.exitCode === 0) {
-                const wipePlanData = parseJsonOutput(result.stdout);
-                
-                // Map the CLI output format to our expected WipePlan interface
-                const wipePlan: WipePlan = {
-                    device_path: wipePlanData.device_path || opts.device,
-                    policy: wipePlanData.policy || 'PURGE',
-                    main_method: wipePlanData.main_method || 'Unknown Method',
-                    hpa_dco_clear: wipePlanData.hpa_dco_clear || false,
-                    verification: {
-                        samples: wipePlanData.verification?.samples || opts.samples || 128
-                    },
-                    blocked: wipePlanData.blocked || false,
-                    block_reason: wipePlanData.block_reason
-                };
-                
-                dispatch({ type: 'SET_WIPE_PLAN', payload: wipePlan });
-                addToast('Wipe plan created successfully', 'success');
-                return wipePlan;
-            } else {
-                throw new Error(result.stderr.join('\n') || 'Failed to create wipe plan');
-            }
+            dispatch({ type: 'SET_WIPE_PLAN', payload: wipePlan });
+            addToast('Wipe plan created successfully', 'success');
+            return wipePlan;
         } finally {
             dispatch({ type: 'SET_OPERATION', payload: null });
         }
-    }, [run, dispatch, addToast]);
+    }, [dispatch, addToast]);
 
     const backup = useCallback(async (opts: {
         device: string;
@@ -303,6 +573,10 @@ export function useSecureWipe() {
         signKeyPath?: string;
         includePaths?: string[];
         allowCritical?: boolean;
+        /** Lower the backup's CPU and disk priority so the machine stays usable */
+        lowPriority?: boolean;
+        /** Device mounted via mountDestination; the CLI logs it and unmounts it afterwards */
+        autoMountedDevice?: string;
     }): Promise<{ certPathJson?: string; certPathPdf?: string; manifestSha256?: string }> => {
         dispatch({ type: 'SET_OPERATION', payload: 'Running backup...' });
 
@@ -339,6 +613,12 @@ export function useSecureWipe() {
             if (opts.allowCritical) {
                 args.push('--critical-ok'); // This will be filtered out by backend
             }
+            if (opts.autoMountedDevice) {
+                args.push('--auto-mounted', opts.autoMountedDevice);
+            }
+            if (opts.lowPriority) {
+                args.push('--nice', '10', '--ionice', 'idle');
+            }
 
             const result = await run(args);
 
@@ -353,12 +633,12 @@ export function useSecureWipe() {
             );
 
             // Check for critical errors (not signing key errors)
-            const hasCriticalError = result.stderr.some(line => 
+            const isCriticalError = (line: string) =>
+                parseCliEvent(line)?.type === 'error' ||
                 line.includes('Permission denied') ||
                 line.includes('No such file') ||
-                line.includes('Device busy') ||
-                (line.includes('Error:') && !line.includes('Failed to load signing key'))
-            );
+                line.includes('Device busy');
+            const hasCriticalError = result.stderr.some(isCriticalError);
 
             // Backup succeeded if we see success indicators and no critical errors
             if (hasSuccessLog && !hasCriticalError) {
@@ -382,12 +662,8 @@ export function useSecureWipe() {
                 return certPaths;
             } else if (hasCriticalError) {
                 // Only throw error if we have critical errors
-                const errorLines = result.stderr.filter(line => 
-                    line.includes('Permission denied') ||
-                    line.includes('No such file') ||
-                    line.includes('Device busy') ||
-                    (line.includes('Error:') && !line.includes('Failed to load signing key'))
-                );
+                const errorLines = result.stderr.filter(isCriticalError)
+                    .map(line => parseCliEvent(line)?.payload?.error ?? line);
                 const errorMsg = errorLines.length > 0 ? errorLines.join('\n') : 'Backup process failed';
                 throw new Error(errorMsg);
             } else {
@@ -505,6 +781,57 @@ export function useSecureWipe() {
         }
     }, []);
 
+    /** Generate a PDF in-process and let the user choose where to save it; null if cancelled */
+    const savePdfAs = useCallback(async (certJsonPath: string): Promise<string | null> => {
+        return await invoke<string | null>('generate_pdf', { certJsonPath, outputPath: null });
+    }, []);
+
+    const verifyLocal = useCallback(async (filePath: string): Promise<LocalVerifyResult> => {
+        return await invoke<LocalVerifyResult>('verify_certificate', { filePath });
+    }, []);
+
+    const listRemovableDestinations = useCallback(async (): Promise<RemovableVolume[]> => {
+        return await invoke<RemovableVolume[]>('list_removable_destinations');
+    }, []);
+
+    const mountDestination = useCallback(async (device: string): Promise<MountedDestination> => {
+        return await invoke<MountedDestination>('mount_destination', { device });
+    }, []);
+
+    const unmountDestination = useCallback(async (device: string): Promise<void> => {
+        await invoke('unmount_destination', { device });
+    }, []);
+
+    /** Sync, unmount and power off the drive holding `device`; resolves to the disk ejected */
+    const ejectDestination = useCallback(async (device: string): Promise<string> => {
+        return await invoke<string>('eject_destination', { device });
+    }, []);
+
+    /** Blink the drive's LED for `seconds`; resolves when it stops */
+    const identifyDevice = useCallback(async (device: string, seconds?: number): Promise<IdentifyReport> => {
+        return await invoke<IdentifyReport>('identify_device', { device, seconds });
+    }, []);
+
+    const verifyBackup = useCallback(async (backupDir: string): Promise<BackupVerificationReport> => {
+        return await invoke<BackupVerificationReport>('verify_backup', { backupDir });
+    }, []);
+
+    const searchCertificates = useCallback(async (query: CertificateQuery): Promise<CertificatePage> => {
+        return await invoke<CertificatePage>('search_certificates', { query });
+    }, []);
+
+    const getCertificateDetails = useCallback(async (certId: string): Promise<CertificateDetails> => {
+        return await invoke<CertificateDetails>('get_certificate_details', { certId });
+    }, []);
+
+    const getNotificationPrefs = useCallback(async (): Promise<NotificationPrefs> => {
+        return await invoke<NotificationPrefs>('get_notification_prefs');
+    }, []);
+
+    const setNotificationPrefs = useCallback(async (prefs: NotificationPrefs): Promise<void> => {
+        await invoke('set_notification_prefs', { prefs });
+    }, []);
+
     return {
         logs,
         running,
@@ -513,10 +840,25 @@ export function useSecureWipe() {
         planWipe,
         backup,
         cancel,
+        listOrphanedSessions,
+        reattachSession,
+        terminateSession,
         clearLogs,
         generatePdfForCert,
+        savePdfAs,
         openPath,
-        verifyOnline
+        verifyOnline,
+        verifyLocal,
+        verifyBackup,
+        getCertificateDetails,
+        searchCertificates,
+        listRemovableDestinations,
+        mountDestination,
+        unmountDestination,
+        ejectDestination,
+        identifyDevice,
+        getNotificationPrefs,
+        setNotificationPrefs
     };
 }
 
@@ -2,16 +2,17 @@ import { useState, useEffect } from 'react';
 import { useNavigate } from 'react-router-dom';
 import { open } from '@tauri-apps/api/dialog';
 import { useApp } from '../contexts/AppContext';
-import { useSecureWipe } from '../hooks/useSecureWipe';
+import { useSecureWipe, RemovableVolume } from '../hooks/useSecureWipe';
 import LogViewer from '../components/LogViewer';
 import FileLink from '../components/FileLink';
 import Progress from '../components/Progress';
 import FileBrowser from '../components/FileBrowser';
+import { formatBytes, formatCapacity } from '../utils/units';
 
 function Backup() {
     const navigate = useNavigate();
     const { state, addToast, dispatch } = useApp();
-    const { backup, running } = useSecureWipe();
+    const { backup, running, listRemovableDestinations, mountDestination, unmountDestination, ejectDestination } = useSecureWipe();
     const [destination, setDestination] = useState('~/SecureWipe/backups');
     const [selectedFiles, setSelectedFiles] = useState<string[]>([]);
     const [signKeyPath, setSignKeyPath] = useState('');
@@ -19,6 +20,55 @@ function Backup() {
     const [showFileBrowser, setShowFileBrowser] = useState(false);
     const [progressTimer, setProgressTimer] = useState<ReturnType<typeof setInterval> | null>(null);
     const [completionProcessed, setCompletionProcessed] = useState(false);
+    const [removableVolumes, setRemovableVolumes] = useState<RemovableVolume[]>([]);
+    // Volume we mounted for this backup; the CLI unmounts it when the backup finishes
+    const [autoMounted, setAutoMounted] = useState<{ device: string; mountpoint: string } | null>(null);
+    const [ejectAfterBackup, setEjectAfterBackup] = useState(true);
+    const [lowPriority, setLowPriority] = useState(false);
+
+    // Removable device holding the current destination, if any
+    const destinationDevice = autoMounted && destination.startsWith(autoMounted.mountpoint)
+        ? autoMounted.device
+        : removableVolumes.find(v => v.mountpoint && destination.startsWith(v.mountpoint))?.device;
+
+    const refreshRemovableVolumes = async () => {
+        try {
+            setRemovableVolumes(await listRemovableDestinations());
+        } catch (error) {
+            console.error('Failed to list removable destinations:', error);
+            setRemovableVolumes([]);
+        }
+    };
+
+    useEffect(() => {
+        refreshRemovableVolumes();
+    }, []);
+
+    const handleSelectVolume = async (volume: RemovableVolume) => {
+        // Switching away from a volume we mounted: put it back the way we found it
+        if (autoMounted && autoMounted.device !== volume.device) {
+            await unmountDestination(autoMounted.device).catch(error =>
+                console.warn('Failed to unmount previous destination:', error));
+            setAutoMounted(null);
+        }
+
+        if (volume.mountpoint) {
+            setDestination(volume.mountpoint);
+            return;
+        }
+
+        try {
+            const mounted = await mountDestination(volume.device);
+            setDestination(mounted.mountpoint);
+            if (mounted.mounted_by_us) {
+                setAutoMounted({ device: mounted.device, mountpoint: mounted.mountpoint });
+            }
+            addToast(`Mounted ${volume.label || volume.device} at ${mounted.mountpoint}`, 'success');
+            await refreshRemovableVolumes();
+        } catch (error) {
+            addToast(typeof error === 'string' ? error : `Failed to mount ${volume.device}`, 'error');
+        }
+    };
 
     // Progress tracking based on log patterns - this runs for both running and completed states
     useEffect(() => {
@@ -120,6 +170,22 @@ function Backup() {
             }
         }
 
+        // The CLI pauses until space is freed; Cancel stops it and leaves the backup resumable
+        if (/destination_full/.test(latestLog)) {
+            dispatch({
+                type: 'SET_PROGRESS',
+                payload: {
+                    title: 'Backup Paused: Destination Full',
+                    currentStep: 3,
+                    totalSteps: 5,
+                    currentStepName: '⚠️ Free up space on the destination to continue, or cancel to stop',
+                    percentage: 60
+                }
+            });
+            addToast('Backup destination is full. Free up space to continue, or cancel; the backup can be resumed later.', 'warning');
+            return;
+        }
+
         // Regular progress tracking
         for (const { pattern, step, name } of progressPatterns) {
             if (pattern.test(latestLog)) {
@@ -229,8 +295,19 @@ function Backup() {
                 sign: true,
                 signKeyPath: signKeyPath || undefined,
                 includePaths,
-                allowCritical: state.selectedDevice.risk_level === 'CRITICAL'
+                allowCritical: state.selectedDevice.risk_level === 'CRITICAL',
+                lowPriority,
+                autoMountedDevice: autoMounted && destination.startsWith(autoMounted.mountpoint)
+                    ? autoMounted.device
+                    : undefined
             });
+            setAutoMounted(null); // The CLI has already unmounted it
+
+            if (ejectAfterBackup && destinationDevice) {
+                // Failure here doesn't undo the backup; the user can still eject from the OS
+                ejectDestination(destinationDevice).catch(error =>
+                    addToast(typeof error === 'string' ? error : 'Failed to eject the destination drive', 'warning'));
+            }
 
             // Clear the fallback timer
             if (progressTimer) {
@@ -264,6 +341,9 @@ function Backup() {
             }, 4000); // Extended to show completion message
 
         } catch (error) {
+            // The CLI releases an auto-mounted destination on failure too
+            setAutoMounted(null);
+
             // Clear the fallback timer on error if it exists
             if (progressTimer) {
                 clearInterval(progressTimer);
@@ -505,7 +585,7 @@ function Backup() {
                     <div className="text-center p-6 bg-white rounded-xl border border-gray-100" style={{ boxShadow: '0 1px 3px 0 rgb(0 0 0 / 0.1)' }}>
                         <div style={{ fontSize: '2rem', marginBottom: '1rem' }}>📊</div>
                         <div className="font-semibold text-gray-900" style={{ fontSize: '1.125rem', marginBottom: '0.5rem' }}>
-                            {(state.selectedDevice.capacity / (1024 ** 3)).toFixed(1)} GB
+                            {formatCapacity(state.selectedDevice.capacity, state.selectedDevice.capacity_display)}
                         </div>
                         <div className="text-sm text-gray-500">Storage Capacity</div>
                     </div>
@@ -630,6 +710,48 @@ function Backup() {
                             📁 Browse
                         </button>
                     </div>
+
+                    {/* Removable media picker */}
+                    <div className="flex items-center gap-2 mt-3" style={{ flexWrap: 'wrap' }}>
+                        <span className="text-sm text-gray-600">Removable media:</span>
+                        {removableVolumes.length === 0 && (
+                            <span className="text-sm text-gray-500">none detected</span>
+                        )}
+                        {removableVolumes.map(volume => (
+                            <button
+                                key={volume.device}
+                                className={`btn btn-secondary text-sm ${destination === volume.mountpoint ? 'font-semibold' : ''}`}
+                                onClick={() => handleSelectVolume(volume)}
+                                title={`${volume.device} on ${volume.backing_device}${volume.model ? ` (${volume.model})` : ''}`}
+                            >
+                                🔌 {volume.label || volume.mountpoint || volume.device}
+                                {!volume.mountpoint && ' · not mounted'}
+                                {volume.filesystem && ` · ${volume.filesystem}`}
+                                {volume.free_bytes !== null && ` · ${formatBytes(volume.free_bytes)} free`}
+                            </button>
+                        ))}
+                        <button className="btn btn-secondary text-sm" onClick={refreshRemovableVolumes}>
+                            🔄 Refresh
+                        </button>
+                    </div>
+                    {destinationDevice && (
+                        <label className="flex items-center gap-2 mt-3 text-sm" style={{ cursor: 'pointer' }}>
+                            <input
+                                type="checkbox"
+                                checked={ejectAfterBackup}
+                                onChange={(e) => setEjectAfterBackup(e.target.checked)}
+                            />
+                            Eject and power off {destinationDevice} after a successful backup
+                        </label>
+                    )}
+                    <label className="flex items-center gap-2 mt-3 text-sm" style={{ cursor: 'pointer' }}>
+                        <input
+                            type="checkbox"
+                            checked={lowPriority}
+                            onChange={(e) => setLowPriority(e.target.checked)}
+                        />
+                        Run in the background at low priority (slower, keeps this computer responsive)
+                    </label>
                 </div>
 
                 {/* Path Selection Mode */}
@@ -7,6 +7,7 @@ import LogViewer from '../components/LogViewer';
 import FileLink from '../components/FileLink';
 import Progress from '../components/Progress';
 import FileBrowser from '../components/FileBrowser';
+import { formatCapacity } from '../utils/units';
 
 function Backup() {
     const navigate = useNavigate();
@@ -202,7 +203,7 @@ function Backup() {
                         <strong>Path:</strong> {state.selectedDevice.path}
                     </div>
                     <div className="device-detail">
-                        <strong>Size:</strong> {(state.selectedDevice.capacity / (1024 ** 3)).toFixed(1)} GB
+                        <strong>Size:</strong> {formatCapacity(state.selectedDevice.capacity, state.selectedDevice.capacity_display)}
                     </div>
                     <div className="device-detail">
                         <strong>Risk Level:</strong> 
@@ -7,6 +7,7 @@ import LogViewer from '../components/LogViewer';
 import FileLink from '../components/FileLink';
 import Progress from '../components/Progress';
 import FileBrowser from '../components/FileBrowser';
+import { formatCapacity } from '../utils/units';
 
 function Backup() {
     const navigate = useNavigate();
@@ -353,7 +354,7 @@ function Backup() {
                     <div className="text-center p-6 bg-white rounded-xl border border-gray-100" style={{ boxShadow: '0 1px 3px 0 rgb(0 0 0 / 0.1)' }}>
                         <div style={{ fontSize: '2rem', marginBottom: '1rem' }}>📊</div>
                         <div className="font-semibold text-gray-900" style={{ fontSize: '1.125rem', marginBottom: '0.5rem' }}>
-                            {(state.selectedDevice.capacity / (1024 ** 3)).toFixed(1)} GB
+                            {formatCapacity(state.selectedDevice.capacity, state.selectedDevice.capacity_display)}
                         </div>
                         <div className="text-sm text-gray-500">Storage Capacity</div>
                     </div>
@@ -1,7 +1,9 @@
 import { useState, useEffect } from 'react';
+import type { FormEvent } from 'react';
 import { useNavigate } from 'react-router-dom';
 import { invoke } from '@tauri-apps/api/tauri';
 import { useSecureWipe } from '../hooks/useSecureWipe';
+import type { LocalVerifyResult, BackupVerificationReport, CertificateHit } from '../hooks/useSecureWipe';
 import { useApp } from '../contexts/AppContext';
 
 import QRPreview from '../components/QRPreview';
@@ -16,17 +18,12 @@ interface Certificate {
     verifyUrl?: string;
 }
 
-interface CertificateData {
-    cert_id: string;
-    cert_type: string;
-    created_at: string;
-    // Add other fields as needed
-}
+const PAGE_SIZE = 25;
 
 function Certificates() {
     const navigate = useNavigate();
     const { addToast } = useApp();
-    const { generatePdfForCert, openPath, verifyOnline } = useSecureWipe();
+    const { generatePdfForCert, savePdfAs, openPath, verifyOnline, verifyLocal, verifyBackup, searchCertificates } = useSecureWipe();
     const [certificates, setCertificates] = useState<Certificate[]>([]);
     const [selectedCert, setSelectedCert] = useState<Certificate | null>(null);
     const [loading, setLoading] = useState(true);
@@ -34,11 +31,21 @@ function Certificates() {
     const [generatingPdf, setGeneratingPdf] = useState(false);
     const [verifyResult, setVerifyResult] = useState<any>(null);
     const [showVerifyModal, setShowVerifyModal] = useState(false);
-
-    // Load actual certificates from the filesystem
+    const [localVerify, setLocalVerify] = useState<Record<string, LocalVerifyResult>>({});
+    const [backupVerify, setBackupVerify] = useState<Record<string, BackupVerificationReport>>({});
+    const [verifyingBackup, setVerifyingBackup] = useState(false);
+    const [searchText, setSearchText] = useState('');
+    const [submittedText, setSubmittedText] = useState('');
+    const [typeFilter, setTypeFilter] = useState('');
+    const [offset, setOffset] = useState(0);
+    const [total, setTotal] = useState(0);
+
+    // Load one page of certificates from the certificate index
     useEffect(() => {
         loadCertificates();
-    }, []);
+    }, [offset, typeFilter, submittedText]);
+
+    const filtersActive = submittedText !== '' || typeFilter !== '';
 
     const loadCertificates = async () => {
         try {
@@ -47,55 +54,25 @@ function Certificates() {
             
             // Try to use Tauri commands
             try {
-                const homeDir = await invoke('get_home_dir');
-                const certDir = `${homeDir}/SecureWipe/certificates`;
-                
-                // List JSON files in the certificates directory
-                const certFiles: string[] = await invoke('list_cert_files', { directory: certDir });
-                
+                const dataDir = await invoke('get_data_dir') as string;
+                const page = await searchCertificates({
+                    text: submittedText || undefined,
+                    cert_type: typeFilter || undefined,
+                    offset,
+                    limit: PAGE_SIZE
+                });
+
                 const loadedCerts: Certificate[] = [];
-                
-                for (const file of certFiles) {
-                    try {
-                        // Read and parse each certificate
-                        const certContent: string = await invoke('read_file_content', { filePath: file });
-                        const certData: CertificateData = JSON.parse(certContent);
-                        
-                        // Check for PDF in both default location and backups directory
-                        const defaultPdfPath = file.replace('.json', '.pdf');
-                        const homeDir = await invoke('get_home_dir') as string;
-                        const backupsPdfPath = `${homeDir}/SecureWipe/backups/${certData.cert_id}.pdf`;
-                        
-                        const defaultPdfExists: boolean = await invoke('file_exists', { filePath: defaultPdfPath }) as boolean;
-                        const backupsPdfExists: boolean = await invoke('file_exists', { filePath: backupsPdfPath }) as boolean;
-                        
-                        // Prefer backups location, fallback to default location
-                        const pdfPath = backupsPdfExists ? backupsPdfPath : (defaultPdfExists ? defaultPdfPath : undefined);
-                        const pdfExists = backupsPdfExists || defaultPdfExists;
-                        
-                        const cert: Certificate = {
-                            id: certData.cert_id,
-                            type: certData.cert_type as 'backup' | 'wipe',
-                            filename: file.split('/').pop() || file,
-                            path: file,
-                            pdfPath: pdfExists ? pdfPath : undefined,
-                            created: new Date(certData.created_at),
-                            verifyUrl: `http://localhost:8000/verify?cert_id=${encodeURIComponent(certData.cert_id)}`
-                        };
-                        
-                        loadedCerts.push(cert);
-                    } catch (parseError) {
-                        console.warn(`Failed to parse certificate ${file}:`, parseError);
-                    }
+                for (const hit of page.items) {
+                    // Generated PDFs are saved to backups/ in the data directory; exported ones sit next to the JSON
+                    const backupsPdfPath = `${dataDir}/backups/${hit.cert_id}.pdf`;
+                    const backupsPdfExists = await invoke('file_exists', { filePath: backupsPdfPath }) as boolean;
+                    loadedCerts.push(certificateFromHit(hit, backupsPdfExists ? backupsPdfPath : undefined));
                 }
-                
-                // Sort by creation time (newest first)
-                loadedCerts.sort((a, b) => b.created.getTime() - a.created.getTime());
-                
+
+                setTotal(page.total);
                 setCertificates(loadedCerts);
-                if (loadedCerts.length > 0) {
-                    setSelectedCert(loadedCerts[0]);
-                }
+                setSelectedCert(loadedCerts[0] ?? null);
             } catch (tauriError) {
                 console.warn('Tauri commands not available, using fallback:', tauriError);
                 
@@ -125,6 +102,29 @@ function Certificates() {
 
 
 
+    const certificateFromHit = (hit: CertificateHit, backupsPdfPath?: string): Certificate => ({
+        id: hit.cert_id,
+        type: hit.cert_type as 'backup' | 'wipe',
+        filename: hit.path.split('/').pop() || hit.path,
+        path: hit.path,
+        pdfPath: backupsPdfPath ?? hit.pdf_path ?? undefined,
+        created: new Date(hit.created_at),
+        verifyUrl: `http://localhost:8000/verify?cert_id=${encodeURIComponent(hit.cert_id)}`
+    });
+
+    const handleSearch = (event: FormEvent) => {
+        event.preventDefault();
+        setSubmittedText(searchText.trim());
+        setOffset(0);
+    };
+
+    const handleClearSearch = () => {
+        setSearchText('');
+        setSubmittedText('');
+        setTypeFilter('');
+        setOffset(0);
+    };
+
     const handleBackToHome = () => {
         navigate('/');
     };
@@ -199,7 +199,7 @@ function Certificates() {
             
             const result = await generatePdfForCert(cert.path);
             
-            // Update certificate with PDF path (saved in ~/SecureWipe/backups/)
+            // Update certificate with PDF path (saved in the data directory's backups/)
             setCertificates(prev => prev.map(c => 
                 c.id === cert.id ? { ...c, pdfPath: result.pdfPath } : c
             ));
@@ -209,7 +209,7 @@ function Certificates() {
                 setSelectedCert(prev => prev ? { ...prev, pdfPath: result.pdfPath } : prev);
             }
             
-            addToast(`PDF generated successfully! Saved to ~/SecureWipe/backups/`, 'success');
+            addToast(`PDF generated successfully! Saved to ${result.pdfPath}`, 'success');
             
             // Auto-open the generated PDF
             await openPath(result.pdfPath);
@@ -249,10 +249,64 @@ function Certificates() {
         }
     };
 
+    const handleSavePdfAs = async (cert: Certificate) => {
+        try {
+            setGeneratingPdf(true);
+            const pdfPath = await savePdfAs(cert.path);
+            if (pdfPath) {
+                addToast(`PDF saved to ${pdfPath}`, 'success');
+                await openPath(pdfPath);
+            }
+        } catch (error) {
+            addToast(`Failed to save PDF: ${error}`, 'error');
+        } finally {
+            setGeneratingPdf(false);
+        }
+    };
+
+    const handleVerifyLocal = async (cert: Certificate) => {
+        try {
+            const result = await verifyLocal(cert.path);
+            setLocalVerify(prev => ({ ...prev, [cert.id]: result }));
+
+            if (result.schema_valid && result.signature_valid) {
+                addToast('Certificate signature and schema are valid', 'success');
+            } else {
+                addToast(`Certificate verification failed: ${result.errors[0] ?? 'unknown error'}`, 'error');
+            }
+        } catch (error) {
+            addToast(`Verification failed: ${error}`, 'error');
+        }
+    };
+
+    const handleVerifyBackup = async (cert: Certificate) => {
+        setVerifyingBackup(true);
+        try {
+            // The backup lives in <destination.path>/<cert_id>
+            const certData = JSON.parse(await invoke('read_file_content', { filePath: cert.path }) as string);
+            const destination = certData.destination?.path;
+            if (!destination) {
+                throw new Error('Certificate does not record a backup destination');
+            }
+            const report = await verifyBackup(`${destination.replace(/\/+$/, '')}/${cert.id}`);
+            setBackupVerify(prev => ({ ...prev, [cert.id]: report }));
+
+            if (report.passed) {
+                addToast(`Backup verified: ${report.files_passed}/${report.files_checked} files intact`, 'success');
+            } else {
+                addToast(`Backup verification failed: ${report.failures.length} problem(s) found`, 'error');
+            }
+        } catch (error) {
+            addToast(`Backup verification failed: ${error instanceof Error ? error.message : error}`, 'error');
+        } finally {
+            setVerifyingBackup(false);
+        }
+    };
+
     const handleOpenBackupsFolder = async () => {
         try {
-            const homeDir = await invoke('get_home_dir') as string;
-            const backupsPath = `${homeDir}/SecureWipe/backups`;
+            const dataDir = await invoke('get_data_dir') as string;
+            const backupsPath = `${dataDir}/backups`;
             await openPath(backupsPath);
             addToast('Opened backups folder', 'success');
         } catch (error) {
@@ -308,6 +362,89 @@ function Certificates() {
         );
     }
 
+    const searchBar = (
+        <div className="card mb-6" style={{ padding: '1rem' }}>
+            <form className="flex gap-4" onSubmit={handleSearch}>
+                <input
+                    type="search"
+                    placeholder="Search by cert ID, certificate number, serial or model"
+                    value={searchText}
+                    onChange={(e) => setSearchText(e.target.value)}
+                    style={{ flex: 1, padding: '0.5rem' }}
+                />
+                <select
+                    value={typeFilter}
+                    onChange={(e) => { setTypeFilter(e.target.value); setOffset(0); }}
+                >
+                    <option value="">All types</option>
+                    <option value="backup">Backup</option>
+                    <option value="wipe">Wipe</option>
+                    <option value="destroy">Destroy</option>
+                    <option value="system">System</option>
+                </select>
+                <button type="submit" className="btn btn-secondary">🔍 Search</button>
+                {filtersActive && (
+                    <button type="button" className="btn btn-secondary" onClick={handleClearSearch}>Clear</button>
+                )}
+            </form>
+
+            {certificates.length > 0 && (
+                <>
+                    <ul style={{ listStyle: 'none', padding: 0, margin: '1rem 0 0' }}>
+                        {certificates.map(cert => (
+                            <li key={cert.id}>
+                                <button
+                                    type="button"
+                                    className="btn btn-secondary"
+                                    style={{ width: '100%', textAlign: 'left', marginBottom: '0.25rem', fontWeight: cert.id === selectedCert?.id ? 600 : 400 }}
+                                    onClick={() => setSelectedCert(cert)}
+                                >
+                                    {getCertTypeIcon(cert.type)} {cert.id} · {formatDate(cert.created)}
+                                </button>
+                            </li>
+                        ))}
+                    </ul>
+                    <div className="flex gap-4" style={{ alignItems: 'center', justifyContent: 'space-between', marginTop: '0.5rem' }}>
+                        <button
+                            className="btn btn-secondary"
+                            disabled={offset === 0}
+                            onClick={() => setOffset(Math.max(0, offset - PAGE_SIZE))}
+                        >
+                            ← Newer
+                        </button>
+                        <span style={{ color: '#64748b', fontSize: '0.9rem' }}>
+                            {offset + 1}–{offset + certificates.length} of {total}
+                        </span>
+                        <button
+                            className="btn btn-secondary"
+                            disabled={offset + certificates.length >= total}
+                            onClick={() => setOffset(offset + PAGE_SIZE)}
+                        >
+                            Older →
+                        </button>
+                    </div>
+                </>
+            )}
+        </div>
+    );
+
+    if (certificates.length === 0 && filtersActive) {
+        return (
+            <div style={{ maxWidth: '800px', margin: '0 auto' }}>
+                <div className="mb-6">
+                    <h2 className="font-semibold mb-4" style={{ fontSize: '1.5rem' }}>
+                        Certificate Management
+                    </h2>
+                </div>
+                {searchBar}
+                <div className="card text-center" style={{ padding: '3rem' }}>
+                    <h3 className="font-semibold mb-2">No Matching Certificates</h3>
+                    <p style={{ color: '#64748b' }}>Try a different search or clear the filters.</p>
+                </div>
+            </div>
+        );
+    }
+
     if (certificates.length === 0) {
         return (
             <div style={{ maxWidth: '800px', margin: '0 auto' }}>
@@ -371,6 +508,8 @@ function Certificates() {
                 </div>
             </div>
 
+            {searchBar}
+
             {/* Certificate Display - Clean Center Layout */}
             <div className="card" style={{ padding: '2rem' }}>
                 <div style={{ textAlign: 'center', marginBottom: '2rem' }}>
@@ -384,6 +523,58 @@ function Certificates() {
                     <p style={{ color: '#64748b', fontSize: '0.75rem', fontFamily: 'monospace' }}>
                         ID: {currentCert.id}
                     </p>
+                    {localVerify[currentCert.id] && (() => {
+                        const result = localVerify[currentCert.id];
+                        const valid = result.schema_valid && result.signature_valid === true;
+                        return (
+                            <div
+                                title={result.errors.join('\n')}
+                                style={{
+                                    display: 'inline-block',
+                                    marginTop: '0.75rem',
+                                    padding: '0.25rem 0.75rem',
+                                    borderRadius: '9999px',
+                                    fontSize: '0.8rem',
+                                    fontWeight: 600,
+                                    color: valid ? '#166534' : '#991b1b',
+                                    background: valid ? '#dcfce7' : '#fee2e2'
+                                }}
+                            >
+                                {valid ? '✅ Signature & schema valid' : '❌ Verification failed'}
+                            </div>
+                        );
+                    })()}
+                    {backupVerify[currentCert.id] && (() => {
+                        const report = backupVerify[currentCert.id];
+                        return (
+                            <div style={{ marginTop: '0.75rem', fontSize: '0.8rem' }}>
+                                <div style={{
+                                    display: 'inline-block',
+                                    padding: '0.25rem 0.75rem',
+                                    borderRadius: '9999px',
+                                    fontWeight: 600,
+                                    color: report.passed ? '#166534' : '#991b1b',
+                                    background: report.passed ? '#dcfce7' : '#fee2e2'
+                                }}>
+                                    {report.passed
+                                        ? `✅ Backup intact (${report.files_passed}/${report.files_checked} files)`
+                                        : `❌ Backup damaged (${report.failures.length} problem(s))`}
+                                </div>
+                                {report.presence_only > 0 && (
+                                    <p style={{ color: '#64748b', marginTop: '0.5rem' }}>
+                                        {report.presence_only} file(s) from an older backup could only be checked for presence
+                                    </p>
+                                )}
+                                {report.failures.length > 0 && (
+                                    <ul style={{ color: '#991b1b', marginTop: '0.5rem', textAlign: 'left', display: 'inline-block' }}>
+                                        {report.failures.slice(0, 10).map(failure => (
+                                            <li key={failure.path}>{failure.path}: {failure.reason}</li>
+                                        ))}
+                                    </ul>
+                                )}
+                            </div>
+                        );
+                    })()}
                 </div>
 
                 {/* QR Code in Center */}
@@ -409,6 +600,25 @@ function Certificates() {
                         🔍 Verify Online
                     </button>
 
+                    <button
+                        className="btn btn-secondary"
+                        onClick={() => handleVerifyLocal(currentCert)}
+                        style={{ width: '100%' }}
+                    >
+                        🛡️ Verify Locally
+                    </button>
+
+                    {currentCert.type === 'backup' && (
+                        <button
+                            className="btn btn-secondary"
+                            onClick={() => handleVerifyBackup(currentCert)}
+                            style={{ width: '100%' }}
+                            disabled={verifyingBackup}
+                        >
+                            {verifyingBackup ? '⏳ Verifying Backup...' : '🧮 Verify Backup'}
+                        </button>
+                    )}
+
                     <button
                         className="btn btn-secondary"
                         onClick={() => handleOpenJson(currentCert)}
@@ -436,6 +646,15 @@ function Certificates() {
                         </button>
                     )}
 
+                    <button
+                        className="btn btn-secondary"
+                        onClick={() => handleSavePdfAs(currentCert)}
+                        style={{ width: '100%' }}
+                        disabled={generatingPdf}
+                    >
+                        💾 Save PDF As…
+                    </button>
+
                     <button
                         className="btn btn-outline"
                         onClick={handleOpenBackupsFolder}
@@ -6,6 +6,7 @@ import { invoke } from '@tauri-apps/api/tauri';
 import { listen } from '@tauri-apps/api/event';
 import LogViewer from '../components/LogViewer';
 import WipeConfirmationModal from '../components/WipeConfirmationModal';
+import { formatCapacity } from '../utils/units';
 
 interface WipeProgress {
     session_id: string;
@@ -187,7 +188,7 @@ function DestructiveWipe() {
                     </div>
                     <div>
                         <span className="font-medium">Capacity:</span>
-                        <div>{(state.selectedDevice.capacity / (1024 ** 3)).toFixed(1)} GB</div>
+                        <div>{formatCapacity(state.selectedDevice.capacity, state.selectedDevice.capacity_display)}</div>
                     </div>
                     <div>
                         <span className="font-medium">Risk Level:</span>
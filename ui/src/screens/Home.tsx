@@ -1,6 +1,7 @@
 import { useNavigate } from 'react-router-dom';
 import { useEffect } from 'react';
 import { useApp } from '../contexts/AppContext';
+import NotificationSettings from '../components/NotificationSettings';
 
 function Home() {
     const navigate = useNavigate();
@@ -143,6 +144,8 @@ function Home() {
                 </div>
             </div>
 
+            <NotificationSettings />
+
             {/* Safety Notice for MVP */}
             <div className="alert alert-info mt-6" style={{ textAlign: 'left' }}>
                 <h4 className="font-semibold mb-2">🛡️ MVP Safety Mode</h4>
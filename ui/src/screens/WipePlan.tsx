@@ -3,6 +3,7 @@ import { useNavigate } from 'react-router-dom';
 import { useApp } from '../contexts/AppContext';
 import { useSecureWipe } from '../hooks/useSecureWipe';
 import LogViewer from '../components/LogViewer';
+import { formatCapacity } from '../utils/units';
 
 function WipePlan() {
     const navigate = useNavigate();
@@ -96,7 +97,7 @@ function WipePlan() {
                         </div>
                         <div>
                             <span className="font-medium">Capacity:</span>
-                            <div>{(state.selectedDevice.capacity / (1024 ** 3)).toFixed(1)} GB</div>
+                            <div>{formatCapacity(state.selectedDevice.capacity, state.selectedDevice.capacity_display)}</div>
                         </div>
                         <div>
                             <span className="font-medium">Risk Level:</span>
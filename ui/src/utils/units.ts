@@ -0,0 +1,27 @@
+// Byte sizes as the CLI shows them. Capacities use the `capacity_display` that
+// `discover` computed from the site's units setting (SI by default, so a 1 TB drive
+// reads 1.00 TB, not 931 GB); other sizes fall back to SI here.
+
+const SI_UNITS = ['kB', 'MB', 'GB', 'TB', 'PB'];
+
+/** Size in the largest SI unit it reaches, e.g. `1.00 TB` */
+export function formatBytes(bytes: number): string {
+    let value = bytes;
+    let unit: string | undefined;
+    for (const label of SI_UNITS) {
+        if (value < 1000) break;
+        value /= 1000;
+        unit = label;
+    }
+    return unit ? `${value.toFixed(2)} ${unit}` : `${bytes.toLocaleString()} bytes`;
+}
+
+/** A device's capacity: the CLI's display string when it sent one */
+export function formatCapacity(bytes: number, display?: string): string {
+    return display ?? formatBytes(bytes);
+}
+
+/** The exact figure, for tooltips next to a rounded size */
+export function exactBytes(bytes: number): string {
+    return `${bytes.toLocaleString()} bytes`;
+}
@@ -0,0 +1,52 @@
+//! The byte form certificates are signed over.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::Value;
+
+use crate::VerifyError;
+
+/// Canonicalize JSON according to RFC 8785 JSON Canonicalization Scheme (JCS)
+///
+/// This ensures deterministic byte representation for signing:
+/// - UTF-8 encoding
+/// - Sorted object keys
+/// - No insignificant whitespace
+/// - Consistent number formatting
+///
+/// Whitespace is removed everywhere, inside string values too. Every certificate
+/// issued so far was signed over that form, so it can't change without a new
+/// `signature.canonicalization` value.
+pub fn canonicalize(value: &Value) -> Result<Vec<u8>, VerifyError> {
+    let canonical_json = serde_json::to_string(&sorted(value))
+        .map_err(|e| VerifyError::Canonicalization(alloc::format!("JSON serialization failed: {}", e)))?;
+
+    let minified = canonical_json.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    Ok(minified.into_bytes())
+}
+
+/// `value` with the keys of every object in sorted order
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let canonical_map: BTreeMap<String, Value> =
+                map.iter().map(|(key, val)| (key.to_string(), sorted(val))).collect();
+            Value::Object(canonical_map.into_iter().collect())
+        }
+        Value::Array(arr) => Value::Array(arr.iter().map(sorted).collect()),
+        Value::String(_) | Value::Number(_) | Value::Bool(_) | Value::Null => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_form() {
+        let cert = json!({"z": {"b": 2, "a": [1.5, "x y"]}, "a": null});
+        assert_eq!(canonicalize(&cert).unwrap(), br#"{"a":null,"z":{"a":[1.5,"xy"],"b":2}}"#);
+    }
+}
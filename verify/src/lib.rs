@@ -0,0 +1,141 @@
+//! Certificate verification shared by the `securewipe` core crate and the
+//! verification page.
+//!
+//! Everything here is `no_std` + `alloc`: canonicalization, Ed25519 signature checks
+//! and JSON Schema validation against the schemas in `certs/schemas/`, which are
+//! compiled in. The core crate's signer and schema validator call into this crate,
+//! and `wasm/` exports the same code to JavaScript, so the browser and
+//! `securewipe cert verify` run the same canonicalization, signature and schema
+//! checks.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod canonical;
+pub mod schema;
+pub mod signature;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde::Serialize;
+use serde_json::Value;
+
+pub use canonical::canonicalize;
+pub use signature::{parse_public_key_pem, verify_signature};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    InvalidKey(String),
+    Canonicalization(String),
+    Signature(String),
+    Schema(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InvalidKey(message) => write!(f, "Invalid public key: {}", message),
+            VerifyError::Canonicalization(message) => write!(f, "JSON canonicalization failed: {}", message),
+            VerifyError::Signature(message) => write!(f, "Signature verification failed: {}", message),
+            VerifyError::Schema(message) => write!(f, "Schema validation failed: {}", message),
+        }
+    }
+}
+
+/// The outcome of checking one certificate document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    /// Schema valid and carrying a good signature
+    pub valid: bool,
+    pub cert_id: Option<String>,
+    pub cert_type: Option<String>,
+    pub schema_valid: bool,
+    pub schema_errors: Vec<String>,
+    /// `None` for an unsigned certificate
+    pub signature_valid: Option<bool>,
+    /// Why a check couldn't run at all, e.g. unparseable JSON or an unknown cert_type
+    pub error: Option<String>,
+}
+
+/// Check a certificate's schema and signature against the issuer's public key
+pub fn verify_certificate(cert_json: &str, public_key: &[u8; 32]) -> Report {
+    let mut report = Report {
+        valid: false,
+        cert_id: None,
+        cert_type: None,
+        schema_valid: false,
+        schema_errors: Vec::new(),
+        signature_valid: None,
+        error: None,
+    };
+    let cert: Value = match serde_json::from_str(cert_json) {
+        Ok(cert) => cert,
+        Err(e) => {
+            report.error = Some(alloc::format!("Invalid certificate JSON: {}", e));
+            return report;
+        }
+    };
+    let field = |name: &str| cert.get(name).and_then(Value::as_str).map(str::to_string);
+    report.cert_id = field("cert_id");
+    report.cert_type = field("cert_type");
+
+    match schema::validate_certificate(&cert) {
+        Ok(errors) => {
+            report.schema_valid = errors.is_empty();
+            report.schema_errors = errors;
+        }
+        Err(e) => report.error = Some(e.to_string()),
+    }
+    if cert.get("signature").is_some() {
+        match verify_signature(&cert, public_key) {
+            Ok(valid) => report.signature_valid = Some(valid),
+            Err(e) => {
+                report.signature_valid = Some(false);
+                report.error.get_or_insert_with(|| e.to_string());
+            }
+        }
+    }
+    report.valid = report.schema_valid && report.signature_valid == Some(true);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    pub(crate) fn signed(mut cert: Value, key: &SigningKey) -> Value {
+        cert.as_object_mut().unwrap().remove("signature");
+        let signature = key.sign(&canonicalize(&cert).unwrap());
+        cert["signature"] = serde_json::json!({
+            "alg": "Ed25519",
+            "pubkey_id": "sih_root_v1",
+            "sig": base64::Engine::encode(&base64::engine::general_purpose::STANDARD, signature.to_bytes()),
+            "canonicalization": "RFC8785_JSON"
+        });
+        cert
+    }
+
+    #[test]
+    fn test_verify_certificate() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        let sample: Value = serde_json::from_str(include_str!("../../tests/test_backup_cert.json")).unwrap();
+        let cert = signed(sample, &key);
+
+        let report = verify_certificate(&serde_json::to_string(&cert).unwrap(), &public_key);
+        assert!(report.valid, "{:?}", report);
+        assert_eq!(report.cert_type.as_deref(), Some("backup"));
+
+        // Another issuer's key
+        let other = SigningKey::from_bytes(&[8u8; 32]).verifying_key().to_bytes();
+        let report = verify_certificate(&serde_json::to_string(&cert).unwrap(), &other);
+        assert_eq!(report.signature_valid, Some(false));
+        assert!(report.schema_valid && !report.valid);
+
+        let report = verify_certificate("{not json", &public_key);
+        assert!(!report.valid && report.error.is_some());
+    }
+}
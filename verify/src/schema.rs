@@ -0,0 +1,420 @@
+//! JSON Schema (draft-07) validation against the certificate schemas in
+//! `certs/schemas/`, compiled into the crate.
+//!
+//! Only the keywords those schemas use are implemented; `KEYWORDS` lists them and a
+//! test fails when a schema starts using anything else. Annotations (`title`,
+//! `description`, `examples`, ...) are ignored, as are formats other than
+//! `date-time` and `uri`.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use regex_automata::meta::Regex;
+use regex_automata::util::syntax;
+use serde_json::Value;
+
+use crate::VerifyError;
+
+pub const BACKUP_SCHEMA: &str = include_str!("../../certs/schemas/backup_schema.json");
+pub const WIPE_SCHEMA: &str = include_str!("../../certs/schemas/wipe_schema.json");
+pub const DESTROY_SCHEMA: &str = include_str!("../../certs/schemas/destroy_schema.json");
+pub const SYSTEM_SCHEMA: &str = include_str!("../../certs/schemas/system_schema.json");
+
+/// Keywords the validator understands, assertions and annotations alike
+pub const KEYWORDS: &[&str] = &[
+    "$schema", "$id", "$ref", "definitions", "title", "description", "default", "examples",
+    "type", "enum", "const", "properties", "required", "additionalProperties", "items",
+    "minItems", "maxItems", "minLength", "maxLength", "pattern", "format",
+    "minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum", "oneOf", "anyOf", "allOf",
+];
+
+/// The schema for a certificate's `cert_type`
+pub fn schema_for(cert_type: &str) -> Option<&'static str> {
+    match cert_type {
+        "backup" => Some(BACKUP_SCHEMA),
+        "wipe" => Some(WIPE_SCHEMA),
+        "destroy" => Some(DESTROY_SCHEMA),
+        "system" => Some(SYSTEM_SCHEMA),
+        _ => None,
+    }
+}
+
+/// Validate a certificate against the schema for its `cert_type`. The error list
+/// is empty for a valid certificate; `Err` means there was no schema to apply.
+pub fn validate_certificate(cert: &Value) -> Result<Vec<String>, VerifyError> {
+    let cert_type = cert.get("cert_type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| VerifyError::Schema("Certificate missing 'cert_type' field".to_string()))?;
+    let text = schema_for(cert_type)
+        .ok_or_else(|| VerifyError::Schema(format!("Unsupported certificate type: {}", cert_type)))?;
+    Ok(Schema::parse(text)?.validate(cert))
+}
+
+pub struct Schema {
+    root: Value,
+}
+
+impl Schema {
+    pub fn parse(text: &str) -> Result<Self, VerifyError> {
+        let root = serde_json::from_str(text)
+            .map_err(|e| VerifyError::Schema(format!("Invalid schema JSON: {}", e)))?;
+        Ok(Self { root })
+    }
+
+    /// Every violation, as "Validation error at <JSON pointer>: <reason>"
+    pub fn validate(&self, instance: &Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        self.check(&self.root, instance, &mut Vec::new(), &mut errors);
+        errors
+            .into_iter()
+            .map(|(path, reason)| {
+                let at = if path.is_empty() { "root".to_string() } else { path };
+                format!("Validation error at {}: {}", at, reason)
+            })
+            .collect()
+    }
+
+    fn check(&self, schema: &Value, instance: &Value, path: &mut Vec<String>, errors: &mut Vec<(String, String)>) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return fail(errors, path, "False schema does not allow any value".to_string()),
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+        // In draft-07 a $ref replaces its sibling keywords
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match reference.strip_prefix('#').and_then(|pointer| self.root.pointer(pointer)) {
+                Some(target) => self.check(target, instance, path, errors),
+                None => fail(errors, path, format!("Unresolvable reference {}", reference)),
+            }
+            return;
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let matches = match expected {
+                Value::String(name) => is_type(instance, name),
+                Value::Array(names) => names.iter().filter_map(Value::as_str).any(|name| is_type(instance, name)),
+                _ => true,
+            };
+            if !matches {
+                fail(errors, path, format!("{} is not of type {}", instance, type_list(expected)));
+            }
+        }
+        if let Some(Value::Array(options)) = schema.get("enum") {
+            if !options.iter().any(|option| equal(option, instance)) {
+                fail(errors, path, format!("{} is not one of {}", instance, Value::Array(options.clone())));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if !equal(expected, instance) {
+                fail(errors, path, format!("{} was expected", expected));
+            }
+        }
+        for keyword in ["allOf", "anyOf", "oneOf"] {
+            if let Some(Value::Array(subschemas)) = schema.get(keyword) {
+                self.check_combinator(keyword, subschemas, instance, path, errors);
+            }
+        }
+
+        match instance {
+            Value::Object(object) => {
+                let properties = schema.get("properties").and_then(Value::as_object);
+                if let Some(Value::Array(required)) = schema.get("required") {
+                    for name in required.iter().filter_map(Value::as_str) {
+                        if !object.contains_key(name) {
+                            fail(errors, path, format!("\"{}\" is a required property", name));
+                        }
+                    }
+                }
+                for (name, value) in object {
+                    let subschema = properties.and_then(|properties| properties.get(name));
+                    path.push(escape(name));
+                    match (subschema, schema.get("additionalProperties")) {
+                        (Some(subschema), _) => self.check(subschema, value, path, errors),
+                        (None, Some(Value::Bool(false))) => {
+                            path.pop();
+                            fail(errors, path, format!("Additional properties are not allowed ('{}' was unexpected)", name));
+                            continue;
+                        }
+                        (None, Some(additional)) => self.check(additional, value, path, errors),
+                        (None, None) => {}
+                    }
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                    if (items.len() as u64) < min {
+                        fail(errors, path, format!("{} has less than {} item{}", instance, min, plural(min)));
+                    }
+                }
+                if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                    if items.len() as u64 > max {
+                        fail(errors, path, format!("{} has more than {} item{}", instance, max, plural(max)));
+                    }
+                }
+                for (index, item) in items.iter().enumerate() {
+                    let subschema = match schema.get("items") {
+                        Some(Value::Array(tuple)) => tuple.get(index),
+                        other => other,
+                    };
+                    if let Some(subschema) = subschema {
+                        path.push(index.to_string());
+                        self.check(subschema, item, path, errors);
+                        path.pop();
+                    }
+                }
+            }
+            Value::String(text) => {
+                let length = text.chars().count() as u64;
+                if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                    if length < min {
+                        fail(errors, path, format!("{} is shorter than {} character{}", instance, min, plural(min)));
+                    }
+                }
+                if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                    if length > max {
+                        fail(errors, path, format!("{} is longer than {} character{}", instance, max, plural(max)));
+                    }
+                }
+                if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                    match compile(pattern) {
+                        Ok(regex) if regex.is_match(text.as_str()) => {}
+                        Ok(_) => fail(errors, path, format!("{} does not match \"{}\"", instance, pattern)),
+                        Err(e) => fail(errors, path, format!("Invalid pattern \"{}\": {}", pattern, e)),
+                    }
+                }
+                let formatted = match schema.get("format").and_then(Value::as_str) {
+                    Some("date-time") => is_date_time(text),
+                    Some("uri") => is_uri(text),
+                    _ => true,
+                };
+                if !formatted {
+                    let format = schema["format"].as_str().unwrap_or_default();
+                    fail(errors, path, format!("{} is not a \"{}\"", instance, format));
+                }
+            }
+            Value::Number(number) => {
+                let value = number.as_f64().unwrap_or(f64::NAN);
+                let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+                if let Some(min) = bound("minimum").filter(|min| value < *min) {
+                    fail(errors, path, format!("{} is less than the minimum of {}", instance, min));
+                }
+                if let Some(max) = bound("maximum").filter(|max| value > *max) {
+                    fail(errors, path, format!("{} is greater than the maximum of {}", instance, max));
+                }
+                if let Some(min) = bound("exclusiveMinimum").filter(|min| value <= *min) {
+                    fail(errors, path, format!("{} is less than or equal to the minimum of {}", instance, min));
+                }
+                if let Some(max) = bound("exclusiveMaximum").filter(|max| value >= *max) {
+                    fail(errors, path, format!("{} is greater than or equal to the maximum of {}", instance, max));
+                }
+            }
+            Value::Bool(_) | Value::Null => {}
+        }
+    }
+
+    fn check_combinator(
+        &self,
+        keyword: &str,
+        subschemas: &[Value],
+        instance: &Value,
+        path: &mut Vec<String>,
+        errors: &mut Vec<(String, String)>,
+    ) {
+        let passing = subschemas
+            .iter()
+            .filter(|subschema| {
+                let mut scratch = Vec::new();
+                self.check(subschema, instance, path, &mut scratch);
+                scratch.is_empty()
+            })
+            .count();
+        let reason = match keyword {
+            "allOf" if passing < subschemas.len() => "is not valid under all of the given schemas",
+            "anyOf" if passing == 0 => "is not valid under any of the given schemas",
+            "oneOf" if passing == 0 => "is not valid under any of the given schemas",
+            "oneOf" if passing > 1 => "is valid under more than one of the given schemas",
+            _ => return,
+        };
+        fail(errors, path, format!("{} {}", instance, reason));
+    }
+}
+
+fn fail(errors: &mut Vec<(String, String)>, path: &[String], reason: String) {
+    let pointer = path.iter().map(|segment| format!("/{}", segment)).collect();
+    errors.push((pointer, reason));
+}
+
+/// A JSON pointer segment
+fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+fn plural(count: u64) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+fn is_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => match instance {
+            Value::Number(number) => number.is_i64() || number.is_u64() || number.as_f64().is_some_and(|f| f == (f as i64) as f64),
+            _ => false,
+        },
+        "boolean" => instance.is_boolean(),
+        "null" => instance.is_null(),
+        _ => false,
+    }
+}
+
+fn type_list(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => {
+            let names: Vec<String> = names.iter().map(|name| format!("{}", name)).collect();
+            names.join(", ")
+        }
+        other => format!("{}", other),
+    }
+}
+
+/// JSON equality, with 1 and 1.0 the same number
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a == b || a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(x, y)| equal(x, y)),
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, x)| b.get(key).is_some_and(|y| equal(x, y)))
+        }
+        _ => left == right,
+    }
+}
+
+/// Schema patterns are ECMA 262 regexes: `\d` and `\w` are ASCII, and a pattern
+/// matches anywhere in the string unless anchored
+fn compile(pattern: &str) -> Result<Regex, String> {
+    Regex::builder()
+        .syntax(syntax::Config::new().unicode(false).utf8(false))
+        .build(pattern)
+        .map_err(|e| e.to_string())
+}
+
+/// RFC 3339 `date-time`, e.g. 2024-05-01T12:00:00.123Z or 2024-05-01T12:00:00+05:30
+fn is_date_time(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let digits = |range: core::ops::Range<usize>| -> Option<u32> {
+        let slice = bytes.get(range)?;
+        slice.iter().all(u8::is_ascii_digit).then(|| slice.iter().fold(0, |n, d| n * 10 + u32::from(d - b'0')))
+    };
+    let at = |index: usize, expected: &[u8]| bytes.get(index).is_some_and(|b| expected.contains(b));
+    let (Some(_year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) =
+        (digits(0..4), digits(5..7), digits(8..10), digits(11..13), digits(14..16), digits(17..19))
+    else {
+        return false;
+    };
+    if !(at(4, b"-") && at(7, b"-") && at(10, b"Tt ") && at(13, b":") && at(16, b":")) {
+        return false;
+    }
+    if !((1..=12).contains(&month) && (1..=31).contains(&day) && hour < 24 && minute < 60 && second <= 60) {
+        return false;
+    }
+    let mut rest = &text[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let length = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if length == 0 {
+            return false;
+        }
+        rest = &fraction[length..];
+    }
+    match rest.as_bytes() {
+        [b'Z' | b'z'] => true,
+        [b'+' | b'-', h1, h2, b':', m1, m2] => {
+            [h1, h2, m1, m2].iter().all(|b| b.is_ascii_digit())
+                && (h1 - b'0') * 10 + (h2 - b'0') < 24
+                && (m1 - b'0') * 10 + (m2 - b'0') < 60
+        }
+        _ => false,
+    }
+}
+
+/// An absolute URI: a scheme, a colon, and no whitespace
+fn is_uri(text: &str) -> bool {
+    let Some((scheme, rest)) = text.split_once(':') else {
+        return false;
+    };
+    let mut scheme_chars = scheme.chars();
+    scheme_chars.next().is_some_and(|first| first.is_ascii_alphabetic())
+        && scheme_chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        && !rest.is_empty()
+        && !text.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Keywords in schema position: everything except property names, enum and
+    /// const values, examples and defaults
+    fn keywords(schema: &Value, found: &mut Vec<String>) {
+        let Value::Object(map) = schema else { return };
+        for (key, value) in map {
+            found.push(key.clone());
+            match key.as_str() {
+                "properties" | "definitions" => {
+                    value.as_object().into_iter().flatten().for_each(|(_, subschema)| keywords(subschema, found))
+                }
+                "items" | "additionalProperties" => keywords(value, found),
+                "oneOf" | "anyOf" | "allOf" => value.as_array().into_iter().flatten().for_each(|s| keywords(s, found)),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_schemas_use_known_keywords() {
+        for text in [BACKUP_SCHEMA, WIPE_SCHEMA, DESTROY_SCHEMA, SYSTEM_SCHEMA] {
+            let mut found = Vec::new();
+            keywords(&serde_json::from_str(text).unwrap(), &mut found);
+            for keyword in found {
+                assert!(KEYWORDS.contains(&keyword.as_str()), "unsupported schema keyword {}", keyword);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        let schema = Schema::parse(
+            &json!({
+                "type": "object",
+                "required": ["id", "at"],
+                "additionalProperties": false,
+                "properties": {
+                    "id": {"type": "string", "pattern": "^[a-f0-9]{4}$"},
+                    "at": {"type": "string", "format": "date-time"},
+                    "size": {"type": ["integer", "null"], "minimum": 0},
+                    "kind": {"$ref": "#/definitions/kind"}
+                },
+                "definitions": {"kind": {"enum": ["wipe", "backup"]}}
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let valid = json!({"id": "0a1f", "at": "2024-05-01T12:00:00.5+05:30", "size": 3.0, "kind": "wipe"});
+        assert_eq!(schema.validate(&valid), Vec::<String>::new());
+
+        let errors = schema.validate(&json!({"id": "0A1F", "at": "2024-13-01T12:00:00Z", "size": -1, "kind": "x", "extra": 1}));
+        assert_eq!(errors.len(), 5, "{:?}", errors);
+        assert!(errors.contains(&"Validation error at /id: \"0A1F\" does not match \"^[a-f0-9]{4}$\"".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("Validation error at root: Additional properties")));
+        assert_eq!(schema.validate(&json!({"id": "0a1f"})), ["Validation error at root: \"at\" is a required property"]);
+
+        // \d is ASCII, as in ECMA 262
+        assert!(!compile(r"^\d$").unwrap().is_match("٣"));
+        assert!(is_uri("https://verify.example.com/c/1") && !is_uri("no scheme"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Ed25519 signatures over the canonical certificate, and the issuer's public key.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde_json::Value;
+
+use crate::{canonicalize, VerifyError};
+
+/// Parse an Ed25519 public key from SPKI PEM ("-----BEGIN PUBLIC KEY-----")
+pub fn parse_public_key_pem(pem_content: &str) -> Result<[u8; 32], VerifyError> {
+    if !pem_content.contains("-----BEGIN PUBLIC KEY-----") {
+        return Err(VerifyError::InvalidKey("Expected '-----BEGIN PUBLIC KEY-----' for Ed25519 public key. Provide an Ed25519 PUBLIC KEY PEM.".to_string()));
+    }
+
+    if !pem_content.contains("-----END PUBLIC KEY-----") {
+        return Err(VerifyError::InvalidKey("Missing '-----END PUBLIC KEY-----' footer. Provide an Ed25519 PUBLIC KEY PEM.".to_string()));
+    }
+
+    let lines: Vec<&str> = pem_content.lines().collect();
+    let start_idx = lines.iter().position(|&line| line.contains("BEGIN PUBLIC KEY"))
+        .ok_or_else(|| VerifyError::InvalidKey("No PEM begin marker found".to_string()))?;
+    let end_idx = lines.iter().position(|&line| line.contains("END PUBLIC KEY"))
+        .ok_or_else(|| VerifyError::InvalidKey("No PEM end marker found".to_string()))?;
+
+    if start_idx >= end_idx {
+        return Err(VerifyError::InvalidKey("Invalid PEM structure".to_string()));
+    }
+
+    let base64_content: String = lines[start_idx + 1..end_idx].concat();
+    let der_bytes = STANDARD.decode(&base64_content)
+        .map_err(|e| VerifyError::InvalidKey(format!("Failed to decode PEM base64: {}", e)))?;
+
+    // For Ed25519 SPKI, the raw public key is the last 32 bytes
+    if der_bytes.len() < 32 {
+        return Err(VerifyError::InvalidKey(format!("Invalid Ed25519 public key DER: too short ({} bytes). Provide an Ed25519 PUBLIC KEY PEM.", der_bytes.len())));
+    }
+
+    let key_start = der_bytes.len() - 32;
+    let mut key_bytes = [0u8; 32];
+    key_bytes.copy_from_slice(&der_bytes[key_start..]);
+
+    Ok(key_bytes)
+}
+
+/// Verify a certificate's `signature` block: an Ed25519 signature over the
+/// canonical form of everything else. `Ok(false)` means the signature is well
+/// formed but doesn't match; `Err` means there is nothing to check.
+pub fn verify_signature(value: &Value, public_key_bytes: &[u8; 32]) -> Result<bool, VerifyError> {
+    let signature_obj = value.get("signature")
+        .ok_or_else(|| VerifyError::Signature("No signature found in certificate".to_string()))?;
+
+    let alg = signature_obj.get("alg")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| VerifyError::Signature("Missing or invalid signature.alg".to_string()))?;
+
+    if alg != "Ed25519" {
+        return Err(VerifyError::Signature(format!("Unsupported algorithm: {}", alg)));
+    }
+
+    let sig_b64 = signature_obj.get("sig")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| VerifyError::Signature("Missing or invalid signature.sig".to_string()))?;
+
+    let signature_bytes = STANDARD.decode(sig_b64)
+        .map_err(|e| VerifyError::Signature(format!("Invalid base64 signature: {}", e)))?;
+
+    let signature = Signature::from_bytes(&signature_bytes.try_into()
+        .map_err(|_| VerifyError::Signature("Invalid signature length".to_string()))?);
+
+    let mut unsigned_cert = value.clone();
+    unsigned_cert.as_object_mut()
+        .ok_or_else(|| VerifyError::Canonicalization("Certificate must be JSON object".to_string()))?
+        .remove("signature");
+
+    let canonical_bytes = canonicalize(&unsigned_cert)?;
+
+    let verifying_key = VerifyingKey::from_bytes(public_key_bytes)
+        .map_err(|e| VerifyError::Signature(format!("Invalid public key: {}", e)))?;
+
+    Ok(verifying_key.verify(&canonical_bytes, &signature).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use serde_json::json;
+
+    #[test]
+    fn test_signature_checks() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let public_key = key.verifying_key().to_bytes();
+        let cert = crate::tests::signed(json!({"cert_id": "c1", "note": "erased ok"}), &key);
+        assert_eq!(verify_signature(&cert, &public_key), Ok(true));
+
+        let mut tampered = cert.clone();
+        tampered["cert_id"] = json!("c2");
+        assert_eq!(verify_signature(&tampered, &public_key), Ok(false));
+
+        let mut other_alg = cert.clone();
+        other_alg["signature"]["alg"] = json!("RSA");
+        assert!(matches!(verify_signature(&other_alg, &public_key), Err(VerifyError::Signature(_))));
+
+        // SPKI DER is a fixed 12-byte prefix followed by the raw key
+        let mut der = Vec::from(&[0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00][..]);
+        der.extend_from_slice(&public_key);
+        let pem = format!("-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n", STANDARD.encode(&der));
+        assert_eq!(parse_public_key_pem(&pem), Ok(public_key));
+        assert!(parse_public_key_pem("not a key").is_err());
+    }
+}
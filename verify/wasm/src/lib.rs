@@ -0,0 +1,15 @@
+//! The JavaScript interface to `securewipe-verify`, for the verification page.
+//!
+//! Build with `wasm-pack build --target web` from this directory; the page loads
+//! `pkg/securewipe_verify_wasm.js` and calls `verifyCertificate`.
+
+use wasm_bindgen::prelude::*;
+
+/// Verify a certificate document against the issuer's Ed25519 public key PEM.
+/// Returns the `Report` as a JSON string; throws only for an unusable key.
+#[wasm_bindgen(js_name = verifyCertificate)]
+pub fn verify_certificate(cert_json: &str, public_key_pem: &str) -> Result<String, JsValue> {
+    let public_key = securewipe_verify::parse_public_key_pem(public_key_pem).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let report = securewipe_verify::verify_certificate(cert_json, &public_key);
+    serde_json::to_string(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+}